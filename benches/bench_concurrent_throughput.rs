@@ -0,0 +1,294 @@
+#![allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+
+//! Criterion benchmark measuring query throughput when many tasks share one pool.
+//!
+//! Each of `BENCH_CONCURRENCY` tasks runs its share of `BENCH_QUERIES` single-row lookups, checking
+//! a connection out per query and returning it, the way request handlers do. That is repeated for
+//! every pool size in `BENCH_POOL_SIZES`, so the results show where the pool (rather than the
+//! database) becomes the bottleneck. Criterion reports queries/second; after each measurement the
+//! bench also prints checkout-wait percentiles and how many checkouts had to wait for a slot.
+//!
+//! Environment:
+//! - `BENCH_CONCURRENCY`: concurrent tasks (default 32)
+//! - `BENCH_POOL_SIZES`: comma-separated pool sizes (default `1,4,16`)
+//! - `BENCH_QUERIES`: lookups per iteration (default 2048)
+//! - `BENCH_BACKENDS`: comma-separated backends (default `sqlite,turso`; add `postgres` to include
+//!   the Postgres test server, which uses `TESTING_PG_PASSWORD`)
+
+use bb8::Pool;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use sql_middleware::benchmark::dataset::{DatasetSchema, seed_rows, shuffled_ids};
+use sql_middleware::middleware::{DatabaseType, MiddlewarePool};
+#[cfg(feature = "postgres")]
+use sql_middleware::middleware::{PgConfig, PostgresOptions};
+#[cfg(feature = "postgres")]
+use sql_middleware::postgres::PgManager;
+use sql_middleware::sqlite::apply_wal_pragmas;
+use sql_middleware::sqlite::config::SqliteManager;
+use sql_middleware::{ConfigAndPool, RowValues, SqlMiddlewareDbError};
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tokio::task::JoinSet;
+
+static TOKIO_RUNTIME: LazyLock<Runtime> =
+    LazyLock::new(|| Runtime::new().expect("create tokio runtime"));
+
+/// Lookup ids, shuffled deterministically so every run hits rows in the same order.
+static IDS: LazyLock<Arc<Vec<i64>>> =
+    LazyLock::new(|| Arc::new(shuffled_ids(env_usize("BENCH_QUERIES", 2048), 1_234_567)));
+
+fn env_usize(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn pool_sizes() -> Vec<u32> {
+    std::env::var("BENCH_POOL_SIZES")
+        .unwrap_or_else(|_| "1,4,16".to_string())
+        .split(',')
+        .filter_map(|size| size.trim().parse().ok())
+        .filter(|&size| size > 0)
+        .collect()
+}
+
+fn backend_enabled(name: &str) -> bool {
+    std::env::var("BENCH_BACKENDS")
+        .unwrap_or_else(|_| "sqlite,turso".to_string())
+        .split(',')
+        .any(|backend| backend.trim().eq_ignore_ascii_case(name))
+}
+
+/// Drop, recreate, and fill the lookup table with one row per id.
+async fn seed(cap: &ConfigAndPool, rows: usize) -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    seed_rows(&mut conn, rows, DatasetSchema::Throughput).await
+}
+
+/// Checkout waits observed during one bench function, plus the pool's own wait counter.
+#[derive(Default)]
+struct WaitStats {
+    waits: Vec<Duration>,
+    pool_waited: Option<u64>,
+}
+
+impl WaitStats {
+    fn report(&mut self, id: &str) {
+        if self.waits.is_empty() {
+            return;
+        }
+        self.waits.sort_unstable();
+        let pct = |p: usize| self.waits[(self.waits.len() - 1) * p / 100];
+        let waited = self.pool_waited.map_or_else(
+            || "n/a (no pool)".to_string(),
+            |waited| format!("{waited}/{}", self.waits.len()),
+        );
+        println!(
+            "{id}: checkout wait p50={:?} p90={:?} p99={:?} max={:?}; checkouts that waited: {waited}",
+            pct(50),
+            pct(90),
+            pct(99),
+            pct(100),
+        );
+    }
+}
+
+/// Total checkouts that had to wait, for bb8-backed pools.
+fn pool_get_waited(cap: &ConfigAndPool) -> Option<u64> {
+    match &cap.pool {
+        MiddlewarePool::Sqlite(pool) => Some(pool.state().statistics.get_waited),
+        #[cfg(feature = "postgres")]
+        MiddlewarePool::Postgres(pool) => Some(pool.state().statistics.get_waited),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Run every lookup across `concurrency` tasks; returns each checkout's wait.
+async fn run_lookups(
+    cap: &ConfigAndPool,
+    select: &'static str,
+    ids: &Arc<Vec<i64>>,
+    concurrency: usize,
+) -> Result<Vec<Duration>, SqlMiddlewareDbError> {
+    let per_task = ids.len().div_ceil(concurrency.max(1)).max(1);
+    let mut join_set = JoinSet::new();
+    for task in 0..ids.len().div_ceil(per_task) {
+        let cap = cap.clone();
+        let ids = Arc::clone(ids);
+        join_set.spawn(async move {
+            let chunk = &ids[task * per_task..((task + 1) * per_task).min(ids.len())];
+            let mut waits = Vec::with_capacity(chunk.len());
+            for &id in chunk {
+                let started = Instant::now();
+                let mut conn = cap.get_connection().await?;
+                waits.push(started.elapsed());
+                let params = [RowValues::Int(id)];
+                let result = conn.query(select).params(&params).select().await?;
+                black_box(result.results.len());
+            }
+            Ok::<_, SqlMiddlewareDbError>(waits)
+        });
+    }
+
+    let mut waits = Vec::with_capacity(ids.len());
+    while let Some(outcome) = join_set.join_next().await {
+        waits.extend(outcome.expect("lookup task panicked")?);
+    }
+    Ok(waits)
+}
+
+fn bench_target(
+    group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>,
+    id: BenchmarkId,
+    label: &str,
+    cap: &ConfigAndPool,
+    select: &'static str,
+) {
+    let concurrency = env_usize("BENCH_CONCURRENCY", 32);
+    let stats = Arc::new(Mutex::new(WaitStats::default()));
+    let waited_before = pool_get_waited(cap);
+
+    group.bench_function(id, |b| {
+        let stats = Arc::clone(&stats);
+        b.to_async(&*TOKIO_RUNTIME).iter_custom(|iters| {
+            let cap = cap.clone();
+            let stats = Arc::clone(&stats);
+            async move {
+                let mut total = Duration::default();
+                for _ in 0..iters {
+                    let start = Instant::now();
+                    let waits = run_lookups(&cap, select, &IDS, concurrency)
+                        .await
+                        .expect("concurrent lookups");
+                    total += start.elapsed();
+                    stats.lock().expect("wait stats lock").waits.extend(waits);
+                }
+                total
+            }
+        });
+    });
+
+    let mut stats = stats.lock().expect("wait stats lock");
+    stats.pool_waited = pool_get_waited(cap)
+        .zip(waited_before)
+        .map(|(after, before)| after - before);
+    stats.report(label);
+}
+
+fn bench_sqlite(group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>) {
+    let path = PathBuf::from("benchmark_concurrent_throughput.db");
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_string_lossy().into_owned();
+    for pool_size in pool_sizes() {
+        let cap = TOKIO_RUNTIME.block_on(async {
+            let pool = Pool::builder()
+                .max_size(pool_size)
+                .build(SqliteManager::new(path.clone()))
+                .await
+                .expect("build sqlite pool");
+            {
+                let mut conn = pool.get_owned().await.expect("sqlite checkout");
+                apply_wal_pragmas(&mut conn).await.expect("enable WAL");
+            }
+            let cap =
+                ConfigAndPool::from_pool(MiddlewarePool::Sqlite(pool), DatabaseType::Sqlite, false);
+            seed(&cap, IDS.len()).await.expect("seed sqlite");
+            cap
+        });
+        bench_target(
+            group,
+            BenchmarkId::new("sqlite", format!("pool_{pool_size}")),
+            &format!("sqlite/pool_{pool_size}"),
+            &cap,
+            DatasetSchema::Throughput.select_by_id(),
+        );
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "turso")]
+fn bench_turso(group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>) {
+    // Turso hands out connections from a database handle rather than a pool, so pool size does
+    // not apply; one measurement shows how it scales with the same concurrency.
+    let path = PathBuf::from("benchmark_concurrent_throughput_turso.db");
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_string_lossy().into_owned();
+    let cap = TOKIO_RUNTIME.block_on(async {
+        let cap = ConfigAndPool::turso_builder(path.clone())
+            .build()
+            .await
+            .expect("open turso database");
+        seed(&cap, IDS.len()).await.expect("seed turso");
+        cap
+    });
+    bench_target(
+        group,
+        BenchmarkId::new("turso", "direct"),
+        "turso/direct",
+        &cap,
+        DatasetSchema::Throughput.select_by_id(),
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "postgres")]
+fn bench_postgres(group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>) {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(std::env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    let tokio_config = PostgresOptions::new(cfg).to_tokio_config();
+
+    for pool_size in pool_sizes() {
+        let cap = TOKIO_RUNTIME.block_on(async {
+            let pool = Pool::builder()
+                .max_size(pool_size)
+                .build(PgManager::new(tokio_config.clone()))
+                .await
+                .expect("build postgres pool");
+            let cap = ConfigAndPool::from_pool(
+                MiddlewarePool::Postgres(pool),
+                DatabaseType::Postgres,
+                false,
+            );
+            seed(&cap, IDS.len()).await.expect("seed postgres");
+            cap
+        });
+        bench_target(
+            group,
+            BenchmarkId::new("postgres", format!("pool_{pool_size}")),
+            &format!("postgres/pool_{pool_size}"),
+            &cap,
+            "SELECT id, name, score FROM bench_throughput WHERE id = $1",
+        );
+    }
+}
+
+fn concurrent_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("concurrent_throughput");
+    group.throughput(Throughput::Elements(IDS.len() as u64));
+
+    if backend_enabled("sqlite") {
+        bench_sqlite(&mut group);
+    }
+    #[cfg(feature = "turso")]
+    if backend_enabled("turso") {
+        bench_turso(&mut group);
+    }
+    #[cfg(feature = "postgres")]
+    if backend_enabled("postgres") {
+        bench_postgres(&mut group);
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_throughput);
+criterion_main!(benches);
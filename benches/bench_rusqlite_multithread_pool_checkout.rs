@@ -7,14 +7,13 @@
 //! up in real-world async applications.
 
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use rand::SeedableRng;
-use rand::seq::SliceRandom;
-use rand_chacha::ChaCha8Rng;
 use rusqlite::{Connection, Result as RusqliteResult, Row};
+use sql_middleware::benchmark::dataset::{
+    Dataset, DatasetSchema, lookup_count, prepare_sqlite_dataset, shuffled_ids,
+};
 use sql_middleware::{ConfigAndPool, RowValues, SqlMiddlewareDbError};
-use std::fs;
 use std::hint::black_box;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::{Arc, LazyLock, Mutex};
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
@@ -22,22 +21,6 @@ use tokio::task::JoinSet;
 
 const SQLITE_SELECT: &str = "SELECT id, name, score, active FROM test WHERE id = ?1";
 
-/// Holds the reusable on-disk `SQLite` dataset plus deterministic lookup IDs.
-struct Dataset {
-    path: String,
-    ids: Vec<i64>,
-}
-
-impl Dataset {
-    fn path(&self) -> &str {
-        &self.path
-    }
-
-    fn ids(&self) -> &[i64] {
-        &self.ids
-    }
-}
-
 /// Row representation shared across benchmark variants to keep decode cost consistent.
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -127,18 +110,16 @@ static TOKIO_RUNTIME: LazyLock<Runtime> =
 
 // Dataset prepared once and reused across benchmark runs.
 static DATASET: LazyLock<Dataset> = LazyLock::new(|| {
-    let row_count = lookup_row_count_to_run();
+    let row_count = lookup_count(1024);
     let path = PathBuf::from("benchmark_sqlite_multithread_lookup.db");
-    prepare_sqlite_dataset(&path, row_count).expect("prepare sqlite dataset");
-
-    let mut ids: Vec<i64> = (1..=row_count as i64).collect();
-    let mut rng = ChaCha8Rng::seed_from_u64(9_876_543_210);
-    ids.shuffle(&mut rng);
-
-    Dataset {
-        path: path.to_string_lossy().into_owned(),
-        ids,
-    }
+    TOKIO_RUNTIME
+        .block_on(prepare_sqlite_dataset(
+            &path,
+            row_count,
+            DatasetSchema::Lookup,
+        ))
+        .expect("prepare sqlite dataset");
+    Dataset::new(&path, shuffled_ids(row_count, 9_876_543_210))
 });
 
 // Middleware pool initialised once so subsequent benchmark iterations exercise steady-state behaviour.
@@ -151,19 +132,6 @@ static MIDDLEWARE_CONFIG: LazyLock<ConfigAndPool> = LazyLock::new(|| {
 // Number of concurrent workers to launch for multi-threaded fan-out.
 static BENCH_CONCURRENCY: LazyLock<usize> = LazyLock::new(|| concurrency_to_run().max(1));
 
-/// Resolve how many lookups each iteration should perform.
-fn lookup_row_count_to_run() -> usize {
-    std::env::var("BENCH_LOOKUPS")
-        .ok()
-        .and_then(|value| value.parse().ok())
-        .or_else(|| {
-            std::env::var("BENCH_ROWS")
-                .ok()
-                .and_then(|value| value.parse().ok())
-        })
-        .unwrap_or(1024)
-}
-
 /// Resolve how many worker tasks to run in parallel.
 fn concurrency_to_run() -> usize {
     std::env::var("BENCH_CONCURRENCY")
@@ -172,42 +140,6 @@ fn concurrency_to_run() -> usize {
         .unwrap_or(8)
 }
 
-/// Create a fresh `SQLite` file with predictable contents for repeatable runs.
-fn prepare_sqlite_dataset(path: &Path, row_count: usize) -> RusqliteResult<()> {
-    if path.exists() {
-        let _ = fs::remove_file(path);
-    }
-
-    let mut conn = Connection::open(path)?;
-    conn.execute_batch(
-        "
-        PRAGMA journal_mode = WAL;
-        CREATE TABLE test (
-            id      INTEGER PRIMARY KEY,
-            name    TEXT NOT NULL,
-            score   REAL NOT NULL,
-            active  INTEGER NOT NULL
-        );
-        ",
-    )?;
-
-    let tx = conn.transaction()?;
-    {
-        let mut insert_stmt =
-            tx.prepare("INSERT INTO test (id, name, score, active) VALUES (?1, ?2, ?3, ?4)")?;
-
-        for id in 1..=row_count as i64 {
-            let name = format!("name-{id}");
-            let score = id as f64 * 0.5;
-            let active = id % 2 == 0;
-            insert_stmt.execute(rusqlite::params![id, name, score, i32::from(active)])?;
-        }
-    }
-    tx.commit()?;
-
-    Ok(())
-}
-
 fn chunk_size(total: usize, concurrency: usize) -> usize {
     if concurrency == 0 {
         return total.max(1);
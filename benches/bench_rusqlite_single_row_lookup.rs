@@ -5,54 +5,35 @@
 //! we focus on call overhead instead of storage effects.
 
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use rand::SeedableRng;
-use rand::seq::SliceRandom;
-use rand_chacha::ChaCha8Rng;
-use rusqlite::{Connection, Row, params};
+use rusqlite::{Connection, Row};
+use sql_middleware::benchmark::dataset::{
+    Dataset, DatasetSchema, lookup_count, prepare_sqlite_dataset, shuffled_ids,
+};
 use sql_middleware::sqlite::{Params as SqliteParams, build_result_set as sqlite_build_result_set};
 use sql_middleware::{
     ConfigAndPool, ConversionMode, MiddlewarePoolConnection, RowValues, SqlMiddlewareDbError,
     convert_sql_params,
 };
 use std::cell::RefCell;
-use std::fs;
 use std::hint::black_box;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 
-/// Holds the reusable on-disk database path plus deterministic id workload.
-struct Dataset {
-    path: String,
-    ids: Vec<i64>,
-}
-
-impl Dataset {
-    fn path(&self) -> &str {
-        &self.path
-    }
-
-    fn ids(&self) -> &[i64] {
-        &self.ids
-    }
-}
-
 // Prepare a shared SQLite file once so both benchmark variants hit identical data.
 static DATASET: LazyLock<Dataset> = LazyLock::new(|| {
-    let row_count = lookup_row_count_to_run();
+    let row_count = lookup_count(1000);
     let path = PathBuf::from("benchmark_sqlite_single_lookup.db");
-    prepare_sqlite_dataset(&path, row_count).expect("failed to prepare SQLite dataset");
-
-    let mut ids: Vec<i64> = (1..=row_count as i64).collect();
-    let mut rng = ChaCha8Rng::seed_from_u64(1_234_567_890);
-    ids.shuffle(&mut rng);
-
-    Dataset {
-        path: path.to_string_lossy().into_owned(),
-        ids,
-    }
+    TOKIO_RUNTIME
+        .block_on(prepare_sqlite_dataset(
+            &path,
+            row_count,
+            DatasetSchema::Lookup,
+        ))
+        .expect("failed to prepare SQLite dataset");
+    Dataset::new(&path, shuffled_ids(row_count, 1_234_567_890))
 });
 
 // Dedicated runtime for the async middleware path.
@@ -124,55 +105,6 @@ impl MiddlewareQueryBreakdown {
     }
 }
 
-/// Resolve how many lookups each iteration should perform.
-fn lookup_row_count_to_run() -> usize {
-    std::env::var("BENCH_LOOKUPS")
-        .ok()
-        .and_then(|value| value.parse().ok())
-        .or_else(|| {
-            std::env::var("BENCH_ROWS")
-                .ok()
-                .and_then(|value| value.parse().ok())
-        })
-        .unwrap_or(1000)
-}
-
-/// Create a fresh `SQLite` file with predictable contents for repeatable runs.
-fn prepare_sqlite_dataset(path: &Path, row_count: usize) -> rusqlite::Result<()> {
-    if path.exists() {
-        let _ = fs::remove_file(path);
-    }
-
-    let mut conn = Connection::open(path)?;
-    conn.execute_batch(
-        "
-        PRAGMA journal_mode = WAL;
-        CREATE TABLE test (
-            id      INTEGER PRIMARY KEY,
-            name    TEXT NOT NULL,
-            score   REAL NOT NULL,
-            active  INTEGER NOT NULL
-        );
-        ",
-    )?;
-
-    let transaction = conn.transaction()?;
-    {
-        let mut insert_stmt = transaction
-            .prepare("INSERT INTO test (id, name, score, active) VALUES (?1, ?2, ?3, ?4)")?;
-
-        for id in 1..=row_count as i64 {
-            let name = format!("name-{id}");
-            let score = id as f64 * 0.5;
-            let active = id % 2 == 0;
-            insert_stmt.execute(params![id, name, score, active])?;
-        }
-    }
-    transaction.commit()?;
-
-    Ok(())
-}
-
 /// Compact struct used in both benchmark variants to ensure identical decoding cost.
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -200,7 +132,7 @@ impl BenchRow {
         };
 
         let name = match row.get_by_index(1) {
-            Some(RowValues::Text(text)) => text.clone(),
+            Some(RowValues::Text(text)) => text.to_string(),
             _ => panic!("expected text name column"),
         };
 
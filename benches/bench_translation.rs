@@ -0,0 +1,180 @@
+//! Criterion micro-benchmarks for placeholder translation.
+//!
+//! `translate_placeholders` runs on every translated query, so its scanner state machine is on the
+//! hot path. The `translate_placeholders` group feeds it representative SQL in both directions:
+//! a small DML statement, a large multi-row insert, a ~10KB PL/pgSQL function body (dollar-quoted,
+//! so nothing inside it may be rewritten), and a script that is mostly comments and string
+//! literals. Throughput is reported in bytes of SQL scanned.
+//!
+//! The `translate_query_for_target` group covers the query builder's early exits (no params, a
+//! backend without a placeholder style, translation off for the call or the pool) against a full
+//! translation of the same statement, so a regression that makes an exit scan the SQL shows up.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use sql_middleware::benchmark::translation::translate_query_for_target;
+use sql_middleware::{
+    PlaceholderStyle, QueryOptions, RowValues, TranslationMode, translate_placeholders,
+};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+const SMALL_DML: &str = "UPDATE users SET name = $1, email = $2, updated_at = now() WHERE id = $3";
+
+/// Multi-row insert with `rows * 3` placeholders, numbered `$1..`.
+fn multi_row_insert(rows: usize) -> String {
+    let mut sql = String::from("INSERT INTO events (id, kind, payload) VALUES ");
+    for row in 0..rows {
+        let base = row * 3;
+        let sep = if row + 1 == rows { ";" } else { ", " };
+        write!(sql, "(${}, ${}, ${}){sep}", base + 1, base + 2, base + 3).expect("write to string");
+    }
+    sql
+}
+
+/// A PL/pgSQL function of roughly `target_len` bytes whose body references its own arguments,
+/// followed by a call that does take placeholders.
+fn plpgsql_body(target_len: usize) -> String {
+    let mut sql = String::from(
+        "CREATE OR REPLACE FUNCTION apply_adjustments(account_id BIGINT, amount NUMERIC)\n\
+         RETURNS NUMERIC LANGUAGE plpgsql AS $fn$\n\
+         DECLARE\n    balance NUMERIC := 0;\n    note TEXT;\n\
+         BEGIN\n",
+    );
+    let mut step = 0;
+    while sql.len() < target_len {
+        step += 1;
+        write!(
+            sql,
+            "    -- step {step}: fold the adjustment into the running balance\n\
+             \x20   IF $2 > {step} THEN\n\
+             \x20       balance := balance + $2 * 0.{step:02};\n\
+             \x20       note := format('step %s for account %s ($1)', {step}, $1);\n\
+             \x20       UPDATE accounts SET balance = balance WHERE id = $1 AND note <> 'skip ?';\n\
+             \x20   END IF;\n"
+        )
+        .expect("write to string");
+    }
+    sql.push_str("    RETURN balance;\nEND;\n$fn$;\nSELECT apply_adjustments($1, $2);");
+    sql
+}
+
+/// A script dominated by line/block comments and quoted strings that mention placeholders.
+fn commented_script(statements: usize) -> String {
+    let mut sql = String::new();
+    for stmt in 0..statements {
+        write!(
+            sql,
+            "-- statement {stmt}: parameters are $1 and $2, never ?1 inside comments\n\
+             /* block comment with a nested look-alike: WHERE id = $1 /* and ?2 */ still comment */\n\
+             -- another note about 'quotes' and \"identifiers\" that should be skipped\n\
+             INSERT INTO audit (id, message, tag) VALUES ($1, 'literal $2 and ?3 stay put', \"tag\"); -- trailing $3\n"
+        )
+        .expect("write to string");
+    }
+    sql
+}
+
+/// Swap `$N` for `?N`, for the SQLite → Postgres direction.
+fn to_sqlite_style(sql: &str) -> String {
+    translate_placeholders(sql, PlaceholderStyle::Sqlite, true).into_owned()
+}
+
+fn translate_placeholders_group(c: &mut Criterion) {
+    let postgres_inputs = [
+        ("small_dml", SMALL_DML.to_string()),
+        ("multi_row_insert_300", multi_row_insert(100)),
+        ("plpgsql_10kb", plpgsql_body(10 * 1024)),
+        ("commented_script", commented_script(64)),
+    ];
+
+    let mut group = c.benchmark_group("translate_placeholders");
+    for (name, sql) in &postgres_inputs {
+        group.throughput(Throughput::Bytes(sql.len() as u64));
+        group.bench_with_input(BenchmarkId::new("to_sqlite", name), sql, |b, sql| {
+            b.iter(|| translate_placeholders(black_box(sql), PlaceholderStyle::Sqlite, true));
+        });
+
+        let sqlite_sql = to_sqlite_style(sql);
+        group.bench_with_input(
+            BenchmarkId::new("to_postgres", name),
+            &sqlite_sql,
+            |b, sql| {
+                b.iter(|| translate_placeholders(black_box(sql), PlaceholderStyle::Postgres, true));
+            },
+        );
+
+        // Already in the target style: a full scan that ends up borrowing the input.
+        group.bench_with_input(BenchmarkId::new("unchanged", name), sql, |b, sql| {
+            b.iter(|| translate_placeholders(black_box(sql), PlaceholderStyle::Postgres, true));
+        });
+    }
+    group.finish();
+}
+
+/// One call shape for the query builder's translation step.
+struct Exit<'a> {
+    name: &'static str,
+    style: Option<PlaceholderStyle>,
+    pool_default: bool,
+    params: &'a [RowValues],
+    options: QueryOptions,
+}
+
+impl<'a> Exit<'a> {
+    fn new(
+        name: &'static str,
+        style: Option<PlaceholderStyle>,
+        pool_default: bool,
+        params: &'a [RowValues],
+        options: QueryOptions,
+    ) -> Self {
+        Self {
+            name,
+            style,
+            pool_default,
+            params,
+            options,
+        }
+    }
+}
+
+fn translate_query_for_target_group(c: &mut Criterion) {
+    let sql = plpgsql_body(10 * 1024);
+    let params = [RowValues::Int(7), RowValues::Float(12.5)];
+    let on = QueryOptions::default().with_translation(TranslationMode::ForceOn);
+    let off = QueryOptions::default().with_translation(TranslationMode::ForceOff);
+    let pool_default = QueryOptions::default();
+    let sqlite = Some(PlaceholderStyle::Sqlite);
+
+    let cases = [
+        Exit::new("translate", sqlite, false, &params, on.clone()),
+        Exit::new("no_params", sqlite, true, &[], on.clone()),
+        Exit::new("no_placeholder_style", None, true, &params, on),
+        Exit::new("forced_off", sqlite, true, &params, off),
+        Exit::new("pool_default_off", sqlite, false, &params, pool_default),
+    ];
+
+    let mut group = c.benchmark_group("translate_query_for_target");
+    group.throughput(Throughput::Bytes(sql.len() as u64));
+    for case in cases {
+        group.bench_function(case.name, |b| {
+            b.iter(|| {
+                translate_query_for_target(
+                    case.style,
+                    case.pool_default,
+                    black_box(&sql),
+                    black_box(case.params),
+                    &case.options,
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    translate_placeholders_group,
+    translate_query_for_target_group
+);
+criterion_main!(benches);
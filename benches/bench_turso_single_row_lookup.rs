@@ -6,58 +6,37 @@
 //! `bench_rusqlite_single_row_lookup` so results stay comparable.
 
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use rand::SeedableRng;
-use rand::seq::SliceRandom;
-use rand_chacha::ChaCha8Rng;
+use sql_middleware::benchmark::dataset::{
+    Dataset, DatasetSchema, lookup_count, prepare_turso_dataset, shuffled_ids,
+};
 use sql_middleware::turso::{Params as TursoParams, build_result_set as turso_build_result_set};
 use sql_middleware::{
     ConfigAndPool, ConversionMode, MiddlewarePoolConnection, ParamConverter, RowValues,
     SqlMiddlewareDbError,
 };
-use std::fs;
 use std::hint::black_box;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use turso::Value as TursoValue;
 
-/// Holds the reusable database path plus deterministic id workload.
-struct Dataset {
-    path: String,
-    ids: Vec<i64>,
-}
-
-impl Dataset {
-    fn path(&self) -> &str {
-        &self.path
-    }
-
-    fn ids(&self) -> &[i64] {
-        &self.ids
-    }
-}
-
 // Dedicated runtime shared across async benchmarks.
 static TOKIO_RUNTIME: LazyLock<Runtime> =
     LazyLock::new(|| Runtime::new().expect("create tokio runtime"));
 
 // Prepare a shared Turso dataset once so all benchmark variants hit identical data.
 static DATASET: LazyLock<Dataset> = LazyLock::new(|| {
-    let row_count = lookup_row_count_to_run();
+    let row_count = lookup_count(1000);
     let path = PathBuf::from("benchmark_turso_single_lookup.db");
     TOKIO_RUNTIME
-        .block_on(prepare_turso_dataset(&path, row_count))
+        .block_on(prepare_turso_dataset(
+            &path,
+            row_count,
+            DatasetSchema::Lookup,
+        ))
         .expect("failed to prepare Turso dataset");
-
-    let mut ids: Vec<i64> = (1..=row_count as i64).collect();
-    let mut rng = ChaCha8Rng::seed_from_u64(1_234_567_890);
-    ids.shuffle(&mut rng);
-
-    Dataset {
-        path: path.to_string_lossy().into_owned(),
-        ids,
-    }
+    Dataset::new(&path, shuffled_ids(row_count, 1_234_567_890))
 });
 
 static MIDDLEWARE_CONFIG: LazyLock<ConfigAndPool> = LazyLock::new(|| {
@@ -125,65 +104,6 @@ impl MiddlewareQueryBreakdown {
     }
 }
 
-/// Resolve how many lookups each iteration should perform.
-fn lookup_row_count_to_run() -> usize {
-    std::env::var("BENCH_LOOKUPS")
-        .ok()
-        .and_then(|value| value.parse().ok())
-        .or_else(|| {
-            std::env::var("BENCH_ROWS")
-                .ok()
-                .and_then(|value| value.parse().ok())
-        })
-        .unwrap_or(1000)
-}
-
-/// Create (or reset) a Turso database with predictable contents for repeatable runs.
-async fn prepare_turso_dataset(path: &Path, row_count: usize) -> Result<(), SqlMiddlewareDbError> {
-    if path.exists() {
-        let _ = fs::remove_file(path);
-        let owned = path.to_string_lossy().into_owned();
-        let _ = fs::remove_file(format!("{owned}-wal"));
-        let _ = fs::remove_file(format!("{owned}-shm"));
-    }
-
-    let config = ConfigAndPool::turso_builder(path.to_string_lossy().into_owned())
-        .build()
-        .await?;
-    let mut conn = config.get_connection().await?;
-
-    conn.execute_batch(
-        "
-        DROP TABLE IF EXISTS test;
-        CREATE TABLE test (
-            id      INTEGER PRIMARY KEY,
-            name    TEXT NOT NULL,
-            score   REAL NOT NULL,
-            active  INTEGER NOT NULL
-        );
-        BEGIN;
-        ",
-    )
-    .await?;
-
-    for id in 1..=row_count as i64 {
-        let params = [
-            RowValues::Int(id),
-            RowValues::Text(format!("name-{id}")),
-            RowValues::Float(id as f64 * 0.5),
-            RowValues::Bool(id % 2 == 0),
-        ];
-        let _ = conn
-            .query("INSERT INTO test (id, name, score, active) VALUES (?1, ?2, ?3, ?4)")
-            .params(&params)
-            .dml()
-            .await?;
-    }
-
-    conn.execute_batch("COMMIT;").await?;
-    Ok(())
-}
-
 /// Compact struct used to ensure identical decoding cost across benchmarks.
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -202,7 +122,7 @@ impl BenchRow {
         };
 
         let name = match row.get_by_index(1) {
-            Some(RowValues::Text(text)) => text.clone(),
+            Some(RowValues::Text(text)) => text.to_string(),
             _ => panic!("expected text name column"),
         };
 
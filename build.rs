@@ -0,0 +1,17 @@
+//! Sets `cfg(any_backend)` when at least one database backend feature is enabled.
+//!
+//! Items that only make sense with a connection to dispatch on are gated with
+//! `#[cfg(any_backend)]`, so the backend-less build (translation, `RowValues`, `ResultSet`) stays
+//! warning-free without per-module lint waivers.
+
+const BACKENDS: &[&str] = &["POSTGRES", "SQLITE", "MSSQL", "TURSO"];
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(any_backend)");
+    if BACKENDS
+        .iter()
+        .any(|backend| std::env::var_os(format!("CARGO_FEATURE_{backend}")).is_some())
+    {
+        println!("cargo::rustc-cfg=any_backend");
+    }
+}
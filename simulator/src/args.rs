@@ -1,8 +1,11 @@
 use clap::{Parser, ValueEnum};
 use serde::Serialize;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::backends::sqlite::{JournalMode, SqliteBackendConfig, Synchronous};
 use crate::properties::PropertyKind;
+use crate::report::OutputFormat;
 
 #[derive(Debug, Clone, Copy, ValueEnum, Serialize)]
 pub(crate) enum BackendKind {
@@ -42,6 +45,65 @@ pub(crate) struct Args {
     pub(crate) log: Option<PathBuf>,
     #[arg(long)]
     pub(crate) dump_plan_on_failure: Option<PathBuf>,
+    /// Explore alternative task interleavings of the plan instead of running it once.
+    #[arg(long)]
+    pub(crate) explore: bool,
+    #[arg(long, default_value_t = 64)]
+    pub(crate) max_interleavings: usize,
+    /// Run against a database file at this path instead of in memory. Any existing file there
+    /// (and its `-wal`/`-shm`/`-journal` files) is deleted first.
+    #[arg(long)]
+    pub(crate) sqlite_path: Option<PathBuf>,
+    /// `PRAGMA journal_mode` for the SQLite database.
+    #[arg(long, value_enum, default_value = "wal")]
+    pub(crate) journal_mode: JournalMode,
+    /// `PRAGMA synchronous` for every SQLite connection; SQLite's default when omitted.
+    #[arg(long, value_enum)]
+    pub(crate) synchronous: Option<Synchronous>,
+    /// Spread the plan's tasks over this many worker processes sharing the `--sqlite-path`
+    /// database (task `t` runs in process `t % N`).
+    #[arg(long, default_value_t = 1)]
+    pub(crate) processes: usize,
+    /// Run as worker `N` of a `--processes` run, taking steps over stdin/stdout.
+    #[arg(long, hide = true)]
+    pub(crate) worker_index: Option<usize>,
+    /// Simulated time a checkout may wait for a free connection before failing.
+    #[arg(long, default_value_t = 1_000)]
+    pub(crate) checkout_timeout_ms: u64,
+    /// Value behind `{{table}}`/`{{unique_id}}` in plan SQL; defaults to the low 32 bits of the seed.
+    #[arg(long)]
+    pub(crate) run_id: Option<u32>,
+    /// Generate and run interactions indefinitely, verifying invariants at periodic checkpoints.
+    #[arg(long)]
+    pub(crate) soak: bool,
+    /// Wall-clock time between soak checkpoints (e.g. `60s`, `5m`).
+    #[arg(long, default_value = "60s", value_parser = humantime::parse_duration)]
+    pub(crate) checkpoint_every: Duration,
+    /// Stop a soak run after this many passing checkpoints instead of running until interrupted.
+    #[arg(long)]
+    pub(crate) max_checkpoints: Option<u64>,
+    /// Fail a soak run when resident memory grows faster than this many KiB per minute.
+    #[arg(long)]
+    pub(crate) max_rss_slope_kib_per_min: Option<f64>,
+    /// Fail a soak run when open file descriptors grow faster than this many per minute.
+    #[arg(long)]
+    pub(crate) max_fd_slope_per_min: Option<f64>,
+    /// Wall-clock time between RSS/FD samples for the leak limits (e.g. `5s`).
+    #[arg(long, default_value = "5s", value_parser = humantime::parse_duration)]
+    pub(crate) leak_sample_every: Duration,
+    /// Rewrite the given plan files in the current format version, then exit.
+    #[arg(long, num_args = 1.., value_name = "PLAN")]
+    pub(crate) migrate_plan: Vec<PathBuf>,
+    /// Show a live dashboard on stderr instead of logging steps to stdout.
+    #[arg(long)]
+    pub(crate) tui: bool,
+    /// `json` prints one structured report when the run ends instead of leaving results to the
+    /// step log.
+    #[arg(long, value_enum, default_value = "text")]
+    pub(crate) output: OutputFormat,
+    /// Write the `--output json` report here instead of stdout.
+    #[arg(long)]
+    pub(crate) output_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -61,17 +123,55 @@ pub(crate) struct SimConfig {
     pub(crate) pool_size: usize,
     pub(crate) log: Option<PathBuf>,
     pub(crate) dump_plan_on_failure: Option<PathBuf>,
+    pub(crate) explore: bool,
+    pub(crate) max_interleavings: usize,
+    pub(crate) sqlite_path: Option<PathBuf>,
+    pub(crate) journal_mode: JournalMode,
+    pub(crate) synchronous: Option<Synchronous>,
+    pub(crate) processes: usize,
+    pub(crate) worker_index: Option<usize>,
+    pub(crate) checkout_timeout_ms: u64,
+    pub(crate) run_id: u32,
+    pub(crate) soak: bool,
+    pub(crate) checkpoint_every: Duration,
+    pub(crate) max_checkpoints: Option<u64>,
+    pub(crate) max_rss_slope_kib_per_min: Option<f64>,
+    pub(crate) max_fd_slope_per_min: Option<f64>,
+    pub(crate) leak_sample_every: Duration,
+    pub(crate) migrate_plan: Vec<PathBuf>,
+    pub(crate) tui: bool,
+    pub(crate) output: OutputFormat,
+    pub(crate) output_file: Option<PathBuf>,
 }
 
 impl SimConfig {
+    /// Backend settings for a single run (plan, generated, or soak).
+    pub(crate) fn sqlite_backend(&self) -> SqliteBackendConfig {
+        let base = match &self.sqlite_path {
+            Some(path) => SqliteBackendConfig::file(path, self.pool_size),
+            None => SqliteBackendConfig::in_memory(self.pool_size),
+        };
+        base.with_journal_mode(self.journal_mode)
+            .with_synchronous(self.synchronous)
+            .with_checkout_timeout(Duration::from_millis(self.checkout_timeout_ms))
+            .with_jitter_seed(self.seed)
+    }
+
+    /// Whether the step log may use stdout: not while the dashboard or a JSON report owns it.
+    pub(crate) fn logs_to_stdout(&self) -> bool {
+        let json_on_stdout = self.output == OutputFormat::Json && self.output_file.is_none();
+        !self.tui && !json_on_stdout
+    }
+
     pub(crate) fn from_args(args: Args) -> Self {
+        let seed = args.seed.unwrap_or_else(random_seed);
         SimConfig {
             backend: args.backend,
             plan: args.plan,
             property: args.property,
             generate: args.generate,
             steps: args.steps.unwrap_or(1_000),
-            seed: args.seed.unwrap_or_else(random_seed),
+            seed,
             tasks: args.tasks.max(1),
             ddl_rate: clamp_rate(args.ddl_rate),
             busy_rate: clamp_rate(args.busy_rate),
@@ -81,6 +181,25 @@ impl SimConfig {
             pool_size: args.pool_size,
             log: args.log,
             dump_plan_on_failure: args.dump_plan_on_failure,
+            explore: args.explore,
+            max_interleavings: args.max_interleavings.max(1),
+            sqlite_path: args.sqlite_path,
+            journal_mode: args.journal_mode,
+            synchronous: args.synchronous,
+            processes: args.processes.max(1),
+            worker_index: args.worker_index,
+            checkout_timeout_ms: args.checkout_timeout_ms,
+            run_id: args.run_id.unwrap_or(seed as u32),
+            soak: args.soak,
+            checkpoint_every: args.checkpoint_every,
+            max_checkpoints: args.max_checkpoints,
+            max_rss_slope_kib_per_min: args.max_rss_slope_kib_per_min,
+            max_fd_slope_per_min: args.max_fd_slope_per_min,
+            leak_sample_every: args.leak_sample_every,
+            migrate_plan: args.migrate_plan,
+            tui: args.tui,
+            output: args.output,
+            output_file: args.output_file,
         }
     }
 }
@@ -88,12 +207,8 @@ impl SimConfig {
 fn clamp_rate(value: f64) -> f64 {
     if value.is_nan() {
         0.0
-    } else if value < 0.0 {
-        0.0
-    } else if value > 1.0 {
-        1.0
     } else {
-        value
+        value.clamp(0.0, 1.0)
     }
 }
 
@@ -1,54 +1,314 @@
 use bb8::Pool;
+use clap::ValueEnum;
+use serde::Serialize;
 use sql_middleware::middleware::{
     ConfigAndPool, DatabaseType, MiddlewarePool, MiddlewarePoolConnection,
 };
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
 use std::time::Duration;
 
+use crate::clock::FakeClock;
+use crate::report::FailureKind;
+
+use sql_middleware::clock::Clock;
+use sql_middleware::jitter::SeededJitter;
 use sql_middleware::sqlite::{SqliteConnection, apply_wal_pragmas};
 use sql_middleware::sqlite::config::SqliteManager;
 use sql_middleware::sqlite::params::Params;
 use sql_middleware::sqlite::query::build_result_set;
 use sql_middleware::RowValues;
 use sql_middleware::SqlMiddlewareDbError;
+use sql_middleware::{PlaceholderStyle, TranslationMode, translate_placeholders};
+use sql_middleware::error::ErrorKind;
 
 #[derive(Debug)]
 pub(crate) enum BackendError {
     Init(String),
+    /// The plan asked for something its task's state does not allow, or carried a bad value.
+    Plan(String),
+    /// An action's result did not match the plan's `expect_error`.
+    Mismatch(String),
+    /// A query result did not match the plan's `expect`.
+    Oracle(String),
     Sql(SqlMiddlewareDbError),
 }
 
 impl std::fmt::Display for BackendError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            BackendError::Init(message) => write!(f, "{message}"),
+            BackendError::Init(message)
+            | BackendError::Plan(message)
+            | BackendError::Mismatch(message)
+            | BackendError::Oracle(message) => write!(f, "{message}"),
             BackendError::Sql(err) => write!(f, "{err}"),
         }
     }
 }
 
+impl BackendError {
+    /// Error-kind classification; simulator-side failures are always `Other`.
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            BackendError::Init(_)
+            | BackendError::Plan(_)
+            | BackendError::Mismatch(_)
+            | BackendError::Oracle(_) => ErrorKind::Other,
+            BackendError::Sql(err) => err.kind(),
+        }
+    }
+
+    /// Failure class of a step that failed with this error. A SQL error the plan did not
+    /// expect counts as a mismatch, except checkout timeouts, which get their own class.
+    pub(crate) fn failure_kind(&self) -> FailureKind {
+        match self {
+            BackendError::Init(_) => FailureKind::Setup,
+            BackendError::Plan(_) => FailureKind::Usage,
+            BackendError::Mismatch(_) => FailureKind::Mismatch,
+            BackendError::Oracle(_) => FailureKind::Oracle,
+            BackendError::Sql(SqlMiddlewareDbError::ConnectionError(message))
+                if message.contains("checkout timed out") =>
+            {
+                FailureKind::Timeout
+            }
+            BackendError::Sql(_) => FailureKind::Mismatch,
+        }
+    }
+}
+
 impl From<SqlMiddlewareDbError> for BackendError {
     fn from(err: SqlMiddlewareDbError) -> Self {
         BackendError::Sql(err)
     }
 }
 
+/// `PRAGMA journal_mode` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+impl JournalMode {
+    fn pragma(self) -> &'static str {
+        match self {
+            JournalMode::Delete => "DELETE",
+            JournalMode::Truncate => "TRUNCATE",
+            JournalMode::Persist => "PERSIST",
+            JournalMode::Memory => "MEMORY",
+            JournalMode::Wal => "WAL",
+            JournalMode::Off => "OFF",
+        }
+    }
+}
+
+/// `PRAGMA synchronous` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Synchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl Synchronous {
+    fn pragma(self) -> &'static str {
+        match self {
+            Synchronous::Off => "OFF",
+            Synchronous::Normal => "NORMAL",
+            Synchronous::Full => "FULL",
+            Synchronous::Extra => "EXTRA",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct SqliteBackendConfig {
     pub(crate) db_path: String,
+    /// `db_path` is a file, recreated (with its `-wal`/`-shm` files) when the backend starts.
+    pub(crate) file_backed: bool,
+    /// Another process prepared the file: open it as is instead of recreating it.
+    pub(crate) shared: bool,
+    pub(crate) journal_mode: JournalMode,
+    /// Left at SQLite's default when `None`.
+    pub(crate) synchronous: Option<Synchronous>,
     pub(crate) pool_size: usize,
+    pub(crate) checkout_timeout: Duration,
+    /// Seed for the pool's retry jitter, so backoff delays repeat from run to run.
+    pub(crate) jitter_seed: u64,
 }
 
 impl SqliteBackendConfig {
-    pub(crate) fn in_memory(pool_size: usize) -> Self {
+    const DEFAULT_CHECKOUT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub(crate) fn with_checkout_timeout(mut self, checkout_timeout: Duration) -> Self {
+        self.checkout_timeout = checkout_timeout;
+        self
+    }
+
+    pub(crate) fn with_jitter_seed(mut self, jitter_seed: u64) -> Self {
+        self.jitter_seed = jitter_seed;
+        self
+    }
+
+    pub(crate) fn with_journal_mode(mut self, journal_mode: JournalMode) -> Self {
+        self.journal_mode = journal_mode;
+        self
+    }
+
+    pub(crate) fn with_synchronous(mut self, synchronous: Option<Synchronous>) -> Self {
+        self.synchronous = synchronous;
+        self
+    }
+
+    fn with_path(db_path: String, file_backed: bool, pool_size: usize) -> Self {
         Self {
-            db_path: "file::memory:?cache=shared".to_string(),
+            db_path,
+            file_backed,
+            shared: false,
+            journal_mode: JournalMode::Wal,
+            synchronous: None,
             pool_size,
+            checkout_timeout: Self::DEFAULT_CHECKOUT_TIMEOUT,
+            jitter_seed: 0,
+        }
+    }
+
+    /// A database file at `path`. Any existing file there is deleted when the backend starts.
+    pub(crate) fn file(path: &Path, pool_size: usize) -> Self {
+        Self::with_path(path.display().to_string(), true, pool_size)
+    }
+
+    /// Open the file as left by [`prepare_shared_file`](Self::prepare_shared_file), for worker
+    /// processes that share one database.
+    pub(crate) fn shared(mut self) -> Self {
+        self.shared = true;
+        self
+    }
+
+    /// Recreate the database file and set its persistent journal mode, before worker processes
+    /// open it with [`shared`](Self::shared).
+    pub(crate) fn prepare_shared_file(&self) -> Result<(), BackendError> {
+        self.remove_files()?;
+        let conn = rusqlite::Connection::open(&self.db_path)
+            .map_err(|err| BackendError::Init(format!("failed to create {}: {err}", self.db_path)))?;
+        if self.journal_mode == JournalMode::Wal {
+            conn.query_row("PRAGMA journal_mode = WAL;", [], |_| Ok(()))
+                .map_err(|err| BackendError::Init(format!("failed to enable WAL: {err}")))?;
+        }
+        Ok(())
+    }
+
+    /// The same settings against a fresh database for exploration run `idx`: a separate
+    /// in-memory database, or `<path>.<idx>` for a file-backed one.
+    pub(crate) fn for_interleaving(&self, idx: usize) -> Self {
+        let mut config = self.clone();
+        config.db_path = if self.file_backed {
+            format!("{}.{idx}", self.db_path)
+        } else {
+            Self::named_memory(&format!("sim_explore_{idx}"), self.pool_size).db_path
+        };
+        config
+    }
+
+    /// Statements each new connection runs before use.
+    fn init_sql(&self) -> Option<String> {
+        let mut sql = String::new();
+        // WAL is a property of the database file and is set once below; the other modes only
+        // last for the connection that sets them.
+        if self.journal_mode != JournalMode::Wal {
+            sql.push_str(&format!("PRAGMA journal_mode = {};", self.journal_mode.pragma()));
+        }
+        if let Some(synchronous) = self.synchronous {
+            sql.push_str(&format!("PRAGMA synchronous = {};", synchronous.pragma()));
+        }
+        (!sql.is_empty()).then_some(sql)
+    }
+
+    fn remove_files(&self) -> Result<(), BackendError> {
+        for suffix in ["", "-wal", "-shm", "-journal"] {
+            let path = format!("{}{suffix}", self.db_path);
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(BackendError::Init(format!("failed to remove {path}: {err}")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn in_memory(pool_size: usize) -> Self {
+        Self::with_path("file::memory:?cache=shared".to_string(), false, pool_size)
+    }
+
+    /// A private shared-cache in-memory database, so separate runs do not see each other's data.
+    pub(crate) fn named_memory(name: &str, pool_size: usize) -> Self {
+        Self::with_path(format!("file:{name}?mode=memory&cache=shared"), false, pool_size)
+    }
+}
+
+type CheckoutFuture =
+    Pin<Box<dyn Future<Output = Result<MiddlewarePoolConnection, SqlMiddlewareDbError>> + Send>>;
+
+/// A checkout that has joined the pool's wait queue but not been taken yet.
+pub(crate) enum PendingCheckout {
+    Waiting(CheckoutFuture),
+    /// The pool had a free connection when the checkout was requested.
+    Ready(Box<Result<MiddlewarePoolConnection, SqlMiddlewareDbError>>),
+}
+
+impl std::fmt::Debug for PendingCheckout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PendingCheckout::Waiting(_) => f.write_str("Waiting"),
+            PendingCheckout::Ready(result) => f.debug_tuple("Ready").field(&result.is_ok()).finish(),
         }
     }
 }
 
+/// Bind parameters for one statement, and whether its `$N` placeholders go through translation.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Bind<'a> {
+    pub(crate) params: &'a [RowValues],
+    pub(crate) translate: bool,
+}
+
+impl Bind<'_> {
+    /// Plain SQL text, run as a batch like before plans could bind parameters.
+    fn is_batch(&self) -> bool {
+        self.params.is_empty() && !self.translate
+    }
+
+    fn translation(&self) -> TranslationMode {
+        if self.translate {
+            TranslationMode::ForceOn
+        } else {
+            TranslationMode::ForceOff
+        }
+    }
+
+    /// `sql` as the connection will run it inside a transaction, where the query builder (and
+    /// so its translation step) is not available.
+    fn tx_sql<'s>(&self, sql: &'s str) -> std::borrow::Cow<'s, str> {
+        translate_placeholders(sql, PlaceholderStyle::Sqlite, self.translate)
+    }
+}
+
 pub(crate) struct SqliteBackend {
     pool: ConfigAndPool,
+    clock: Arc<FakeClock>,
+    checkout_timeout: Duration,
 }
 
 impl SqliteBackend {
@@ -56,9 +316,19 @@ impl SqliteBackend {
 
     pub(crate) async fn new(config: SqliteBackendConfig) -> Result<Self, BackendError> {
         let pool_size = config.pool_size.max(1) as u32;
-        let manager = SqliteManager::new(config.db_path);
+        if config.file_backed && !config.shared {
+            config.remove_files()?;
+        }
+        let mut manager = SqliteManager::new(config.db_path.clone());
+        if let Some(sql) = config.init_sql() {
+            manager = manager.with_init_sql(sql);
+        }
+        // Keep every connection idle and skip checkout validation so a checkout either completes
+        // on first poll or the pool is exhausted; the simulated checkout timeout relies on that.
         let pool = Pool::builder()
             .max_size(pool_size)
+            .min_idle(Some(pool_size))
+            .test_on_check_out(false)
             .build(manager)
             .await
             .map_err(|err| BackendError::Init(format!("sqlite pool error: {err}")))?;
@@ -67,20 +337,65 @@ impl SqliteBackend {
             let mut conn = pool.get_owned().await.map_err(|err| {
                 BackendError::Init(format!("sqlite pool checkout error: {err}"))
             })?;
-            apply_wal_pragmas(&mut conn).await?;
+            if config.journal_mode == JournalMode::Wal && !config.shared {
+                apply_wal_pragmas(&mut conn).await?;
+            }
         }
 
+        let clock = Arc::new(FakeClock::new());
+        let pool = ConfigAndPool::from_pool(MiddlewarePool::Sqlite(pool), DatabaseType::Sqlite, false)
+            .with_clock(clock.clone())
+            .with_jitter(Arc::new(SeededJitter::new(config.jitter_seed)));
         Ok(Self {
-            pool: ConfigAndPool {
-                pool: MiddlewarePool::Sqlite(pool),
-                db_type: DatabaseType::Sqlite,
-                translate_placeholders: false,
-            },
+            pool,
+            clock,
+            checkout_timeout: config.checkout_timeout,
         })
     }
 
     pub(crate) async fn checkout(&self) -> Result<MiddlewarePoolConnection, BackendError> {
-        Ok(self.pool.get_connection().await?)
+        Ok(self
+            .pool
+            .get_connection_timeout(self.checkout_timeout)
+            .await?)
+    }
+
+    /// Start a checkout and poll it once, which queues it behind earlier waiters when the pool
+    /// is exhausted. Unlike [`checkout`](Self::checkout) it has no timeout yet; that starts when
+    /// [`finish_checkout`](Self::finish_checkout) takes it.
+    pub(crate) async fn request_checkout(&self) -> PendingCheckout {
+        let pool = self.pool.clone();
+        let mut checkout: CheckoutFuture = Box::pin(async move { pool.get_connection().await });
+        std::future::poll_fn(|cx| {
+            Poll::Ready(match checkout.as_mut().poll(cx) {
+                Poll::Ready(result) => Some(Box::new(result)),
+                Poll::Pending => None,
+            })
+        })
+        .await
+        .map_or(PendingCheckout::Waiting(checkout), PendingCheckout::Ready)
+    }
+
+    /// Take the connection the pool handed a requested checkout, failing after the checkout
+    /// timeout if it has not been handed one.
+    pub(crate) async fn finish_checkout(
+        &self,
+        pending: PendingCheckout,
+    ) -> Result<MiddlewarePoolConnection, BackendError> {
+        let checkout = match pending {
+            PendingCheckout::Ready(result) => return Ok((*result)?),
+            PendingCheckout::Waiting(checkout) => checkout,
+        };
+        tokio::select! {
+            biased;
+            conn = checkout => Ok(conn?),
+            () = self.clock.sleep(self.checkout_timeout) => Err(BackendError::Sql(
+                SqlMiddlewareDbError::ConnectionError(format!(
+                    "pool checkout timed out after {:?} (no connection was handed to this waiter)",
+                    self.checkout_timeout
+                )),
+            )),
+        }
     }
 
     fn sqlite_conn_mut(
@@ -98,15 +413,7 @@ impl SqliteBackend {
     }
 
     fn is_busy_error(err: &BackendError) -> bool {
-        match err {
-            BackendError::Sql(SqlMiddlewareDbError::SqliteError(
-                rusqlite::Error::SqliteFailure(code, _),
-            )) => matches!(
-                code.code,
-                rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
-            ),
-            _ => false,
-        }
+        err.kind() == ErrorKind::Busy
     }
 
     pub(crate) async fn begin(
@@ -122,7 +429,7 @@ impl SqliteBackend {
             match result {
                 Ok(()) => return Ok(()),
                 Err(err) if Self::is_busy_error(&err) && attempt < Self::BUSY_RETRIES => {
-                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    self.pool.clock().sleep(Duration::from_millis(delay_ms)).await;
                     delay_ms = (delay_ms * 2).min(100);
                     continue;
                 }
@@ -145,7 +452,7 @@ impl SqliteBackend {
             match result {
                 Ok(()) => return Ok(()),
                 Err(err) if Self::is_busy_error(&err) && attempt < Self::BUSY_RETRIES => {
-                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    self.pool.clock().sleep(Duration::from_millis(delay_ms)).await;
                     delay_ms = (delay_ms * 2).min(100);
                     continue;
                 }
@@ -168,7 +475,7 @@ impl SqliteBackend {
             match result {
                 Ok(()) => return Ok(()),
                 Err(err) if Self::is_busy_error(&err) && attempt < Self::BUSY_RETRIES => {
-                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    self.pool.clock().sleep(Duration::from_millis(delay_ms)).await;
                     delay_ms = (delay_ms * 2).min(100);
                     continue;
                 }
@@ -182,25 +489,42 @@ impl SqliteBackend {
         &self,
         conn: &mut MiddlewarePoolConnection,
         sql: &str,
+        bind: Bind<'_>,
         in_tx: bool,
     ) -> Result<(), BackendError> {
         let mut delay_ms = 5u64;
         for attempt in 0..=Self::BUSY_RETRIES {
-            let result = if in_tx {
-                let sqlite_conn = Self::sqlite_conn_mut(conn)?;
-                sqlite_conn
-                    .execute_batch_in_tx(sql)
-                    .await
-                    .map_err(BackendError::from)
-            } else {
-                conn.execute_batch(sql)
+            let result = match (in_tx, bind.is_batch()) {
+                (true, true) => {
+                    let sqlite_conn = Self::sqlite_conn_mut(conn)?;
+                    sqlite_conn
+                        .execute_batch_in_tx(sql)
+                        .await
+                        .map_err(BackendError::from)
+                }
+                (true, false) => {
+                    let sqlite_conn = Self::sqlite_conn_mut(conn)?;
+                    let params = Params::convert(bind.params).map_err(BackendError::from)?;
+                    sqlite_conn
+                        .execute_dml_in_tx(&bind.tx_sql(sql), params.as_values())
+                        .await
+                        .map(|_| ())
+                        .map_err(BackendError::from)
+                }
+                (false, true) => conn.execute_batch(sql).await.map_err(BackendError::from),
+                (false, false) => conn
+                    .query(sql)
+                    .params(bind.params)
+                    .translation(bind.translation())
+                    .dml()
                     .await
-                    .map_err(BackendError::from)
+                    .map(|_| ())
+                    .map_err(BackendError::from),
             };
             match result {
                 Ok(()) => return Ok(()),
                 Err(err) if Self::is_busy_error(&err) && attempt < Self::BUSY_RETRIES => {
-                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    self.pool.clock().sleep(Duration::from_millis(delay_ms)).await;
                     delay_ms = (delay_ms * 2).min(100);
                     continue;
                 }
@@ -214,20 +538,22 @@ impl SqliteBackend {
         &self,
         conn: &mut MiddlewarePoolConnection,
         sql: &str,
+        bind: Bind<'_>,
         in_tx: bool,
     ) -> Result<sql_middleware::ResultSet, BackendError> {
         let mut delay_ms = 5u64;
         for attempt in 0..=Self::BUSY_RETRIES {
             let result = if in_tx {
                 let sqlite_conn = Self::sqlite_conn_mut(conn)?;
-                let params: &[RowValues] = &[];
-                let params = Params::convert(params).map_err(BackendError::from)?;
+                let params = Params::convert(bind.params).map_err(BackendError::from)?;
                 sqlite_conn
-                    .execute_select_in_tx(sql, params.as_values(), build_result_set)
+                    .execute_select_in_tx(&bind.tx_sql(sql), params.as_values(), build_result_set)
                     .await
                     .map_err(BackendError::from)
             } else {
                 conn.query(sql)
+                    .params(bind.params)
+                    .translation(bind.translation())
                     .select()
                     .await
                     .map_err(BackendError::from)
@@ -235,7 +561,7 @@ impl SqliteBackend {
             match result {
                 Ok(result) => return Ok(result),
                 Err(err) if Self::is_busy_error(&err) && attempt < Self::BUSY_RETRIES => {
-                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    self.pool.clock().sleep(Duration::from_millis(delay_ms)).await;
                     delay_ms = (delay_ms * 2).min(100);
                     continue;
                 }
@@ -249,6 +575,23 @@ impl SqliteBackend {
         if ms == 0 {
             return;
         }
-        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+        self.pool.clock().sleep(Duration::from_millis(ms)).await;
+    }
+
+    /// Pool occupancy as `(connections, idle_connections)`.
+    pub(crate) fn pool_state(&self) -> (u32, u32) {
+        match &self.pool.pool {
+            MiddlewarePool::Sqlite(pool) => {
+                let state = pool.state();
+                (state.connections, state.idle_connections)
+            }
+            #[allow(unreachable_patterns)]
+            _ => (0, 0),
+        }
+    }
+
+    /// Simulated time elapsed since the backend was created.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
     }
 }
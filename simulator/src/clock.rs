@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sql_middleware::clock::{Clock, Sleep};
+
+/// Simulated clock: sleeping advances simulated time immediately instead of waiting.
+///
+/// The runner is sequential, so nothing else could advance the clock while a step sleeps; jumping
+/// straight to the deadline keeps timeouts and backoff deterministic and free of real delays.
+#[derive(Debug)]
+pub(crate) struct FakeClock {
+    origin: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl FakeClock {
+    pub(crate) fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().expect("fake clock lock")
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        // Advance on first poll, not on creation: a timeout raced against a ready checkout is
+        // never polled and must not move the clock.
+        let elapsed = Arc::clone(&self.elapsed);
+        Box::pin(async move {
+            *elapsed.lock().expect("fake clock lock") += duration;
+        })
+    }
+
+    fn sleep_blocking(&self, duration: Duration) {
+        *self.elapsed.lock().expect("fake clock lock") += duration;
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+
+use crate::plan::Action;
+
+/// How one step ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum EventOutcome {
+    Ok,
+    /// The action failed with the error its `expect_error` asked for.
+    ExpectedError,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Event {
+    pub(crate) step: usize,
+    pub(crate) task: usize,
+    pub(crate) action: &'static str,
+    /// SQL for `execute`/`query`, the duration for `sleep`, empty otherwise.
+    pub(crate) detail: String,
+    pub(crate) outcome: EventOutcome,
+}
+
+/// Running counters and the most recent steps of a run.
+///
+/// The runner records every step here; the `--tui` dashboard renders it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventLog {
+    pub(crate) steps: usize,
+    pub(crate) expected_errors: usize,
+    pub(crate) failures: usize,
+    pub(crate) recent: VecDeque<Event>,
+}
+
+impl EventLog {
+    pub(crate) const RECENT: usize = 12;
+
+    pub(crate) fn record(
+        &mut self,
+        step: usize,
+        task: usize,
+        action: &Action,
+        outcome: EventOutcome,
+    ) {
+        self.steps += 1;
+        match outcome {
+            EventOutcome::Ok => {}
+            EventOutcome::ExpectedError => self.expected_errors += 1,
+            EventOutcome::Failed(_) => self.failures += 1,
+        }
+        if self.recent.len() == Self::RECENT {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(Event {
+            step,
+            task,
+            action: action.label(),
+            detail: match action {
+                Action::Execute { sql, .. } | Action::Query { sql, .. } => sql.clone(),
+                Action::Sleep { ms } => format!("{ms}ms"),
+                _ => String::new(),
+            },
+            outcome,
+        });
+    }
+}
@@ -0,0 +1,174 @@
+use crate::backends::sqlite::SqliteBackendConfig;
+use crate::plan::{Action, Interaction, PLAN_VERSION, Plan};
+use crate::runner::{self, RunError};
+
+/// Bounds for interleaving exploration.
+#[derive(Debug, Clone)]
+pub(crate) struct ExploreConfig {
+    pub(crate) max_interleavings: usize,
+    pub(crate) pool_size: usize,
+    /// Settings each interleaving's fresh database is created with.
+    pub(crate) backend: SqliteBackendConfig,
+    pub(crate) run_id: u32,
+}
+
+/// The first interleaving that violated an oracle.
+#[derive(Debug)]
+pub(crate) struct ExploreFailure {
+    pub(crate) interleaving: usize,
+    pub(crate) plan: Plan,
+    pub(crate) error: RunError,
+}
+
+#[derive(Debug)]
+pub(crate) struct ExploreSummary {
+    pub(crate) interleavings: usize,
+    pub(crate) exhausted: bool,
+}
+
+/// Run alternative interleavings of `plan`, keeping each task's own actions in plan order.
+///
+/// Interleavings are enumerated depth-first with sleep sets: once a task's next action has been
+/// explored at a branch point, sibling branches skip it until a conflicting action runs, so
+/// reorderings of independent actions (e.g., two reads, or a sleep and anything) are tried only
+/// once. The first interleaving is always the plan's own order. Each interleaving runs against a
+/// fresh database: a separate in-memory one, or `<path>.<idx>` with `--sqlite-path`.
+pub(crate) async fn explore_plan(
+    plan: &Plan,
+    config: ExploreConfig,
+) -> Result<ExploreSummary, Box<ExploreFailure>> {
+    let (schedules, exhausted) = enumerate_schedules(plan, &config);
+    for (idx, schedule) in schedules.iter().enumerate() {
+        let candidate = Plan {
+            version: PLAN_VERSION,
+            interactions: schedule
+                .iter()
+                .map(|&step| plan.interactions[step].clone())
+                .collect(),
+        };
+        let backend = config.backend.for_interleaving(idx);
+        tracing::info!("explore interleaving={idx}");
+        if let Err(error) =
+            runner::run_plan_sqlite(candidate.clone(), backend, config.run_id, None).await
+        {
+            return Err(Box::new(ExploreFailure {
+                interleaving: idx,
+                plan: candidate,
+                error,
+            }));
+        }
+    }
+    Ok(ExploreSummary {
+        interleavings: schedules.len(),
+        exhausted,
+    })
+}
+
+struct Node {
+    cursors: Vec<usize>,
+    held: usize,
+    prefix: Vec<usize>,
+    sleep: Vec<usize>,
+}
+
+/// Enumerate up to `max_interleavings` schedules (as plan step indexes).
+///
+/// Returns whether the search space was exhausted within the bound.
+fn enumerate_schedules(plan: &Plan, config: &ExploreConfig) -> (Vec<Vec<usize>>, bool) {
+    let per_task = steps_by_task(&plan.interactions);
+    let limit = config.max_interleavings.max(1);
+    let mut schedules = Vec::new();
+    let mut stack = vec![Node {
+        cursors: vec![0; per_task.len()],
+        held: 0,
+        prefix: Vec::with_capacity(plan.interactions.len()),
+        sleep: Vec::new(),
+    }];
+
+    while let Some(node) = stack.pop() {
+        if node.prefix.len() == plan.interactions.len() {
+            schedules.push(node.prefix);
+            if schedules.len() >= limit {
+                return (schedules, stack.is_empty());
+            }
+            continue;
+        }
+
+        // Candidates ordered by original plan position so the first schedule replays the plan.
+        let mut enabled: Vec<(usize, usize)> = per_task
+            .iter()
+            .enumerate()
+            .filter_map(|(task, steps)| steps.get(node.cursors[task]).map(|&step| (step, task)))
+            .filter(|&(step, _)| {
+                !matches!(plan.interactions[step].action, Action::Checkout)
+                    || node.held < config.pool_size.max(1)
+            })
+            .collect();
+        enabled.sort_unstable();
+
+        let mut explored: Vec<usize> = Vec::new();
+        let mut children = Vec::new();
+        for (step, task) in enabled {
+            if node.sleep.contains(&task) {
+                continue;
+            }
+            let action = &plan.interactions[step].action;
+            let sleep = node
+                .sleep
+                .iter()
+                .chain(explored.iter())
+                .copied()
+                .filter(|&other| {
+                    let other_step = per_task[other][node.cursors[other]];
+                    !conflicts(action, &plan.interactions[other_step].action)
+                })
+                .collect();
+            let mut cursors = node.cursors.clone();
+            cursors[task] += 1;
+            let held = match action {
+                Action::Checkout => node.held + 1,
+                Action::Return => node.held.saturating_sub(1),
+                _ => node.held,
+            };
+            let mut prefix = node.prefix.clone();
+            prefix.push(step);
+            children.push(Node {
+                cursors,
+                held,
+                prefix,
+                sleep,
+            });
+            explored.push(task);
+        }
+        // Push in reverse so the earliest plan step is explored first.
+        stack.extend(children.into_iter().rev());
+    }
+
+    (schedules, true)
+}
+
+fn steps_by_task(interactions: &[Interaction]) -> Vec<Vec<usize>> {
+    let mut per_task: Vec<Vec<usize>> = Vec::new();
+    for (step, interaction) in interactions.iter().enumerate() {
+        if per_task.len() <= interaction.task {
+            per_task.resize_with(interaction.task + 1, Vec::new);
+        }
+        per_task[interaction.task].push(step);
+    }
+    per_task
+}
+
+/// Whether swapping two actions from different tasks can change the outcome.
+fn conflicts(a: &Action, b: &Action) -> bool {
+    let pool_op = |action: &Action| {
+        matches!(
+            action,
+            Action::Checkout | Action::RequestCheckout | Action::Return
+        )
+    };
+    match (a, b) {
+        (Action::Sleep { .. }, _) | (_, Action::Sleep { .. }) => false,
+        (Action::Query { .. }, Action::Query { .. }) => false,
+        _ => pool_op(a) == pool_op(b),
+    }
+}
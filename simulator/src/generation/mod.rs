@@ -2,8 +2,7 @@ use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 
 use crate::args::{BackendKind, SimConfig};
-use crate::plan::{Action, Interaction, Plan};
-use crate::properties::PropertyKind;
+use crate::plan::{Action, Interaction, PLAN_VERSION, Plan};
 
 #[derive(Debug, Clone, Copy)]
 struct TaskState {
@@ -18,57 +17,119 @@ struct GenState {
 
 pub(crate) fn generate_plan(config: &SimConfig) -> Result<Plan, String> {
     let steps = config.steps.max(1);
-    let tasks = config.tasks.max(1);
-    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let prefix = plan_prefix(config)?;
+    if prefix.len() >= steps {
+        return Ok(Plan {
+            version: PLAN_VERSION,
+            interactions: prefix.into_iter().take(steps).collect(),
+        });
+    }
 
+    let mut generator = Generator::new(config, &prefix);
     let mut interactions = Vec::with_capacity(steps);
-    let mut task_state = vec![
-        TaskState {
-            has_conn: false,
-            in_tx: false
-        };
-        tasks
-    ];
-    let mut gen_state = GenState { next_id: 1 };
+    interactions.extend(prefix);
+    while interactions.len() < steps {
+        interactions.push(generator.next_interaction()?);
+    }
 
+    Ok(Plan {
+        version: PLAN_VERSION,
+        interactions,
+    })
+}
+
+/// Bootstrap DDL plus the `--property` plan, which every generated plan starts with.
+pub(crate) fn plan_prefix(config: &SimConfig) -> Result<Vec<Interaction>, String> {
     let mut prefix = Vec::new();
     prefix.extend(bootstrap_plan());
     if let Some(property) = config.property {
-        let required_tasks = property_required_tasks(property);
-        if tasks < required_tasks {
+        if config.pool_size < property.min_pool_size() {
+            return Err(format!(
+                "property {:?} requires a pool of at least {} connections",
+                property,
+                property.min_pool_size()
+            ));
+        }
+        let required_tasks = property.required_tasks(config.pool_size);
+        if config.tasks.max(1) < required_tasks {
             return Err(format!(
                 "property {:?} requires at least {} tasks",
                 property, required_tasks
             ));
         }
-        prefix.extend(property.build_plan().interactions);
+        prefix.extend(property.build_plan(config.pool_size).interactions);
     }
+    Ok(prefix)
+}
 
-    let prefix_len = prefix.len();
-    if prefix_len >= steps {
-        return Ok(Plan {
-            interactions: prefix.into_iter().take(steps).collect(),
-        });
-    }
-    interactions.extend(prefix);
+/// Resumable random interaction stream.
+///
+/// `generate_plan` takes a fixed number of interactions from it; soak mode pulls from it
+/// indefinitely and uses [`Generator::drain`] to quiesce all tasks at checkpoints.
+pub(crate) struct Generator {
+    config: SimConfig,
+    rng: ChaCha8Rng,
+    task_state: Vec<TaskState>,
+    gen_state: GenState,
+    in_flight_tx: usize,
+}
 
-    let mut in_flight_tx = 0usize;
-    for action in interactions.iter() {
-        apply_generated_action(&mut task_state, action, &mut in_flight_tx);
+impl Generator {
+    /// A generator seeded from `config`, positioned after `prefix` has run.
+    pub(crate) fn new(config: &SimConfig, prefix: &[Interaction]) -> Self {
+        let mut generator = Self {
+            config: config.clone(),
+            rng: ChaCha8Rng::seed_from_u64(config.seed),
+            task_state: vec![
+                TaskState {
+                    has_conn: false,
+                    in_tx: false
+                };
+                config.tasks.max(1)
+            ],
+            gen_state: GenState { next_id: 1 },
+            in_flight_tx: 0,
+        };
+        for action in prefix {
+            generator.apply(action);
+        }
+        generator
     }
 
-    while interactions.len() < steps {
-        let task_id = rng.random_range(0..tasks);
-        let task = task_state
+    pub(crate) fn next_interaction(&mut self) -> Result<Interaction, String> {
+        let task_id = self.rng.random_range(0..self.task_state.len());
+        let task = self
+            .task_state
             .get(task_id)
             .ok_or_else(|| format!("missing task state for {task_id}"))?;
-        let op = next_op(task, in_flight_tx, config, &mut rng);
-        let action = build_action(task_id, op, &mut gen_state);
-        apply_generated_action(&mut task_state, &action, &mut in_flight_tx);
-        interactions.push(action);
+        let op = next_op(task, self.in_flight_tx, &self.config, &mut self.rng);
+        let action = build_action(task_id, op, &mut self.gen_state);
+        self.apply(&action);
+        Ok(action)
     }
 
-    Ok(Plan { interactions })
+    /// Interactions that commit every open transaction and return every held connection.
+    ///
+    /// Afterwards no task holds a connection, so the next interactions start from a quiet pool.
+    pub(crate) fn drain(&mut self) -> Vec<Interaction> {
+        let mut interactions = Vec::new();
+        for (task_id, task) in self.task_state.iter().enumerate() {
+            if task.in_tx {
+                interactions.push(interaction(task_id, Action::Commit));
+            }
+            if task.has_conn {
+                interactions.push(interaction(task_id, Action::Return));
+            }
+        }
+        for action in &interactions {
+            self.apply(action);
+        }
+        interactions
+    }
+
+    fn apply(&mut self, action: &Interaction) {
+        apply_generated_action(&mut self.task_state, action, &mut self.in_flight_tx);
+    }
 }
 
 fn bootstrap_plan() -> Vec<Interaction> {
@@ -79,6 +140,8 @@ fn bootstrap_plan() -> Vec<Interaction> {
             0,
             Action::Execute {
                 sql: format!("CREATE TABLE IF NOT EXISTS {table} (id INTEGER, value TEXT);"),
+                params: Vec::new(),
+                translate: false,
                 expect_error: None,
             },
         ),
@@ -86,15 +149,6 @@ fn bootstrap_plan() -> Vec<Interaction> {
     ]
 }
 
-fn property_required_tasks(property: PropertyKind) -> usize {
-    match property {
-        PropertyKind::PoolCheckoutReturn => 2,
-        PropertyKind::TxCommitVisible => 2,
-        PropertyKind::TxRollbackInvisible => 2,
-        PropertyKind::RetryAfterBusy => 2,
-    }
-}
-
 #[derive(Debug, Clone)]
 enum GenOp {
     Checkout,
@@ -192,16 +246,22 @@ fn build_action(task: usize, op: GenOp, gen_state: &mut GenState) -> Interaction
                 sql: format!(
                     "INSERT INTO sim_gen (id, value) VALUES ({id}, 'v{id}');"
                 ),
+                params: Vec::new(),
+                translate: false,
                 expect_error: None,
             }
         }
         GenOp::Query => Action::Query {
             sql: "SELECT id, value FROM sim_gen ORDER BY id LIMIT 5;".to_string(),
             expect: None,
+            params: Vec::new(),
+            translate: false,
             expect_error: None,
         },
         GenOp::Ddl => Action::Execute {
             sql: "CREATE TABLE IF NOT EXISTS sim_gen (id INTEGER, value TEXT);".to_string(),
+            params: Vec::new(),
+            translate: false,
             expect_error: None,
         },
         GenOp::Sleep(ms) => Action::Sleep { ms },
@@ -239,7 +299,10 @@ fn apply_generated_action(
                 }
                 task.in_tx = false;
             }
-            Action::Execute { .. } | Action::Query { .. } | Action::Sleep { .. } => {}
+            Action::RequestCheckout
+            | Action::Execute { .. }
+            | Action::Query { .. }
+            | Action::Sleep { .. } => {}
         }
     }
 }
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+/// Resident memory and open file descriptors of this process at one moment.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ProcessSample {
+    pub(crate) rss_kib: u64,
+    pub(crate) open_fds: u64,
+}
+
+impl ProcessSample {
+    /// Read the current process's usage from `/proc`; `None` where that is unavailable.
+    pub(crate) fn current() -> Option<Self> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let rss_kib = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())?;
+        let open_fds = std::fs::read_dir("/proc/self/fd").ok()?.count() as u64;
+        Some(Self { rss_kib, open_fds })
+    }
+}
+
+/// Growth limits, per minute of wall-clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LeakLimits {
+    pub(crate) rss_kib_per_min: Option<f64>,
+    pub(crate) fds_per_min: Option<f64>,
+}
+
+impl LeakLimits {
+    pub(crate) fn enabled(&self) -> bool {
+        self.rss_kib_per_min.is_some() || self.fds_per_min.is_some()
+    }
+}
+
+/// Fewest samples a trend is judged on; fewer points make the fitted slope mostly noise.
+const MIN_SAMPLES: usize = 4;
+
+/// Watches RSS and FD counts across a long run and fails when either keeps climbing.
+///
+/// Samples taken before [`LeakOracle::start_window`] are ignored so pool warm-up, caches, and the
+/// first SQLite worker threads don't read as growth. The trend is a least-squares slope over all
+/// samples since then, so one-off spikes that are later freed barely move it.
+#[derive(Debug)]
+pub(crate) struct LeakOracle {
+    limits: LeakLimits,
+    sample_every: Duration,
+    next_sample: Duration,
+    /// `(elapsed seconds, sample)`; empty until the window starts.
+    samples: Vec<(f64, ProcessSample)>,
+    window_started: bool,
+}
+
+impl LeakOracle {
+    pub(crate) fn new(limits: LeakLimits, sample_every: Duration) -> Self {
+        Self {
+            limits,
+            sample_every,
+            next_sample: Duration::ZERO,
+            samples: Vec::new(),
+            window_started: false,
+        }
+    }
+
+    /// Begin recording samples, e.g. once the first checkpoint has passed.
+    pub(crate) fn start_window(&mut self, elapsed: Duration) {
+        if self.limits.enabled() && !self.window_started {
+            self.window_started = true;
+            self.next_sample = elapsed;
+            self.poll(elapsed);
+        }
+    }
+
+    /// Take a sample if one is due.
+    pub(crate) fn poll(&mut self, elapsed: Duration) {
+        if !self.window_started || elapsed < self.next_sample {
+            return;
+        }
+        self.next_sample = elapsed + self.sample_every;
+        if let Some(sample) = ProcessSample::current() {
+            self.samples.push((elapsed.as_secs_f64(), sample));
+        }
+    }
+
+    /// Fitted growth per minute as `(rss KiB, open FDs)`, once there are enough samples.
+    pub(crate) fn slopes(&self) -> Option<(f64, f64)> {
+        if self.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let per_min = |value: fn(&ProcessSample) -> u64| {
+            let points: Vec<(f64, f64)> = self
+                .samples
+                .iter()
+                .map(|(secs, sample)| (*secs, value(sample) as f64))
+                .collect();
+            slope(&points) * 60.0
+        };
+        Some((per_min(|s| s.rss_kib), per_min(|s| s.open_fds)))
+    }
+
+    /// Fail when a fitted slope exceeds its limit.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        let Some((rss, fds)) = self.slopes() else {
+            return Ok(());
+        };
+        let span = self.samples.last().map_or(0.0, |(secs, _)| *secs) - self.samples[0].0;
+        if let Some(limit) = self.limits.rss_kib_per_min
+            && rss > limit
+        {
+            return Err(format!(
+                "RSS grew {rss:.1} KiB/min over {} samples spanning {span:.0}s (limit {limit} KiB/min)",
+                self.samples.len()
+            ));
+        }
+        if let Some(limit) = self.limits.fds_per_min
+            && fds > limit
+        {
+            return Err(format!(
+                "open FDs grew {fds:.2}/min over {} samples spanning {span:.0}s (limit {limit}/min)",
+                self.samples.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Least-squares slope of `y` over `x`; `0` when `x` does not vary.
+fn slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (x, y) in points {
+        cov += (x - mean_x) * (y - mean_y);
+        var += (x - mean_x) * (x - mean_x);
+    }
+    if var == 0.0 { 0.0 } else { cov / var }
+}
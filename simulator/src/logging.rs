@@ -8,20 +8,24 @@ use tracing_subscriber::fmt::writer::MakeWriter;
 #[derive(Clone)]
 pub(crate) struct LogWriter {
     file: Option<Arc<Mutex<File>>>,
+    stdout: bool,
 }
 
 impl LogWriter {
-    pub(crate) fn new(path: Option<PathBuf>) -> io::Result<Self> {
+    /// Log to `path` (if any), and to stdout unless `stdout` is false (the `--tui` dashboard owns
+    /// the terminal).
+    pub(crate) fn new(path: Option<PathBuf>, stdout: bool) -> io::Result<Self> {
         let file = match path {
             Some(path) => Some(Arc::new(Mutex::new(File::create(path)?))),
             None => None,
         };
-        Ok(Self { file })
+        Ok(Self { file, stdout })
     }
 }
 
 pub(crate) struct LogWriterGuard {
     file: Option<Arc<Mutex<File>>>,
+    stdout: bool,
 }
 
 impl<'a> MakeWriter<'a> for LogWriter {
@@ -30,14 +34,16 @@ impl<'a> MakeWriter<'a> for LogWriter {
     fn make_writer(&'a self) -> Self::Writer {
         LogWriterGuard {
             file: self.file.clone(),
+            stdout: self.stdout,
         }
     }
 }
 
 impl Write for LogWriterGuard {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let mut stdout = io::stdout();
-        stdout.write_all(buf)?;
+        if self.stdout {
+            io::stdout().write_all(buf)?;
+        }
         if let Some(file) = &self.file {
             let mut handle = file.lock().expect("log file lock");
             handle.write_all(buf)?;
@@ -46,7 +52,9 @@ impl Write for LogWriterGuard {
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        io::stdout().flush()?;
+        if self.stdout {
+            io::stdout().flush()?;
+        }
         if let Some(file) = &self.file {
             let mut handle = file.lock().expect("log file lock");
             handle.flush()?;
@@ -1,24 +1,49 @@
 mod args;
 mod backends;
+mod clock;
+mod events;
+mod explore;
 mod generation;
+mod leak;
 mod logging;
+mod multiprocess;
 mod plan;
 mod properties;
+mod report;
 mod runner;
+mod schema;
+mod soak;
+mod template;
+mod tui;
+
+use std::io::IsTerminal;
+use std::panic::AssertUnwindSafe;
 
 use clap::Parser;
 use tracing::Level;
 
 use crate::args::{Args, SimConfig};
 use crate::logging::LogWriter;
+use crate::report::{Failure, FailureKind, OutputFormat, Report, RunOutcome, Summary};
+use crate::tui::Dashboard;
 
 fn main() {
     let args = Args::parse();
     let config = SimConfig::from_args(args);
-    let writer = LogWriter::new(config.log.clone()).unwrap_or_else(|err| {
-        eprintln!("failed to open log file: {err}");
-        std::process::exit(1);
-    });
+    if let Some(index) = config.worker_index {
+        // Stdout is the IPC channel and the log file belongs to the coordinator, so workers
+        // set up no logging at all.
+        if let Err(err) = multiprocess::run_worker(&config, index) {
+            eprintln!("worker {index}: {err}");
+            std::process::exit(FailureKind::Setup.exit_code());
+        }
+        return;
+    }
+    let writer =
+        LogWriter::new(config.log.clone(), config.logs_to_stdout()).unwrap_or_else(|err| {
+            eprintln!("failed to open log file: {err}");
+            std::process::exit(FailureKind::Setup.exit_code());
+        });
 
     tracing_subscriber::fmt()
         .with_writer(writer)
@@ -29,81 +54,385 @@ fn main() {
     let config_json = serde_json::to_string_pretty(&config).unwrap_or_else(|_| "{}".to_string());
     tracing::info!("config: {}", config_json);
 
+    if !config.migrate_plan.is_empty() {
+        migrate_plans(&config.migrate_plan);
+        return;
+    }
+
     if config.plan.is_some() && config.generate {
-        eprintln!("--plan and --generate are mutually exclusive");
-        std::process::exit(1);
+        usage_error("--plan and --generate are mutually exclusive");
     }
     if config.plan.is_some() && config.property.is_some() {
-        eprintln!("--plan and --property are mutually exclusive");
-        std::process::exit(1);
+        usage_error("--plan and --property are mutually exclusive");
+    }
+
+    if config.tui && config.explore {
+        usage_error("--tui cannot be combined with --explore");
     }
+    if config.tui && !std::io::stderr().is_terminal() {
+        usage_error("--tui needs a terminal on stderr");
+    }
+
+    if config.processes > 1 {
+        if config.sqlite_path.is_none() {
+            usage_error("--processes needs a shared database file; pass --sqlite-path");
+        }
+        if config.explore || config.soak || config.tui {
+            usage_error("--processes cannot be combined with --explore, --soak, or --tui");
+        }
+    }
+
+    if config.soak && (config.plan.is_some() || config.explore) {
+        usage_error("--soak cannot be combined with --plan or --explore");
+    }
+    if let Some(property) = config.property
+        && config.pool_size < property.min_pool_size()
+    {
+        usage_error(&format!(
+            "--property {property:?} needs --pool-size {} or more",
+            property.min_pool_size()
+        ));
+    }
+    if !config.soak && !config.generate && config.plan.is_none() && config.property.is_none() {
+        usage_error("missing required flag: --plan, --property, or --generate");
+    }
+
+    let outcome = std::panic::catch_unwind(AssertUnwindSafe(|| dispatch(&config)))
+        .unwrap_or_else(|payload| RunOutcome::panic(payload.as_ref()));
+    finish(&config, mode(&config), outcome);
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(FailureKind::Usage.exit_code());
+}
 
+/// Run whichever of soak, generate, plan, or property the flags select.
+fn dispatch(config: &SimConfig) -> RunOutcome {
+    if config.soak {
+        return run_soak(config);
+    }
     if config.generate {
-        match generation::generate_plan(&config) {
-            Ok(plan) => {
-                run_plan(plan, config.pool_size, config.dump_plan_on_failure.as_deref());
-            }
+        return match generation::generate_plan(config) {
+            Ok(plan) => run_plan(plan, config),
             Err(err) => {
                 eprintln!("failed to generate plan: {err}");
-                std::process::exit(1);
+                RunOutcome::error(FailureKind::Usage, format!("failed to generate plan: {err}"))
             }
-        }
-        return;
+        };
     }
-
-    if let Some(plan_path) = config.plan.clone() {
-        let plan = match runner::load_plan(&plan_path) {
-            Ok(plan) => plan,
+    if let Some(plan_path) = &config.plan {
+        return match runner::load_plan(plan_path) {
+            Ok(plan) => run_plan(plan, config),
             Err(err) => {
                 eprintln!("failed to load plan: {err}");
-                std::process::exit(1);
+                RunOutcome::error(FailureKind::Usage, format!("failed to load plan: {err}"))
             }
         };
-        run_plan(plan, config.pool_size, config.dump_plan_on_failure.as_deref());
-        return;
     }
+    let property = config.property.expect("main checked a run mode was given");
+    run_plan(property.build_plan(config.pool_size), config)
+}
 
-    if let Some(property) = config.property {
-        let plan = property.build_plan();
-        run_plan(plan, config.pool_size, config.dump_plan_on_failure.as_deref());
-        return;
+/// Report mode; `--explore` runs report as `explore` whatever their plan came from.
+fn mode(config: &SimConfig) -> &'static str {
+    if config.soak {
+        "soak"
+    } else if config.explore {
+        "explore"
+    } else if config.generate {
+        "generate"
+    } else if config.plan.is_some() {
+        "plan"
+    } else {
+        "property"
     }
+}
 
-    eprintln!("missing required flag: --plan, --property, or --generate");
-    std::process::exit(1);
+/// Emit the `--output json` report, if requested, and exit with the run's exit code.
+fn finish(config: &SimConfig, mode: &'static str, outcome: RunOutcome) -> ! {
+    let exit_code = outcome.exit_code();
+    if config.output == OutputFormat::Json {
+        let report = Report {
+            mode,
+            status: outcome.status(),
+            exit_code,
+            seed: config.seed,
+            run_id: config.run_id,
+            outcome: &outcome,
+            config,
+        };
+        if let Err(err) = report.write(config.output_file.as_deref()) {
+            eprintln!("{err}");
+            std::process::exit(FailureKind::Setup.exit_code());
+        }
+    }
+    std::process::exit(exit_code);
 }
 
-fn run_plan(plan: plan::Plan, pool_size: usize, dump_path: Option<&std::path::Path>) {
-    let runtime = tokio::runtime::Builder::new_current_thread()
+fn run_plan(plan: plan::Plan, config: &SimConfig) -> RunOutcome {
+    let dump_path = config.dump_plan_on_failure.as_deref();
+    let runtime = match tokio::runtime::Builder::new_current_thread()
         .enable_time()
         .build()
-        .unwrap_or_else(|err| {
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
             eprintln!("failed to start async runtime: {err}");
-            std::process::exit(1);
-        });
+            return RunOutcome::error(
+                FailureKind::Setup,
+                format!("failed to start async runtime: {err}"),
+            );
+        }
+    };
+
+    if config.explore {
+        let explore_config = explore::ExploreConfig {
+            max_interleavings: config.max_interleavings,
+            pool_size: config.pool_size,
+            backend: config.sqlite_backend(),
+            run_id: config.run_id,
+        };
+        return match runtime.block_on(explore::explore_plan(&plan, explore_config)) {
+            Ok(summary) => {
+                tracing::info!(
+                    "exploration complete: interleavings={} exhausted={}",
+                    summary.interleavings,
+                    summary.exhausted
+                );
+                RunOutcome {
+                    summary: Summary {
+                        interleavings: Some(summary.interleavings),
+                        exhausted: Some(summary.exhausted),
+                        ..Summary::default()
+                    },
+                    ..RunOutcome::default()
+                }
+            }
+            Err(failure) => {
+                let dumped_plan = dump_path.and_then(|path| report_dump(path, &failure.plan));
+                eprintln!(
+                    "interleaving {} failed at step {} (task {}): {}",
+                    failure.interleaving,
+                    failure.error.step,
+                    failure.error.task,
+                    failure.error.reason
+                );
+                RunOutcome {
+                    summary: Summary {
+                        interleavings: Some(failure.interleaving),
+                        ..Summary::default()
+                    },
+                    failures: vec![Failure {
+                        interleaving: Some(failure.interleaving),
+                        ..Failure::from(&failure.error)
+                    }],
+                    dumped_plan,
+                    ..RunOutcome::default()
+                }
+            }
+        };
+    }
+
+    if config.processes > 1 {
+        return match multiprocess::run_plan(&plan, config) {
+            Ok(summary) => {
+                tracing::info!(
+                    "plan complete: steps={} processes={}",
+                    summary.steps,
+                    config.processes
+                );
+                RunOutcome {
+                    summary: Summary {
+                        steps: Some(summary.steps),
+                        processes: Some(config.processes),
+                        ..Summary::default()
+                    },
+                    ..RunOutcome::default()
+                }
+            }
+            Err(err) => {
+                let dumped_plan = dump_path.and_then(|path| report_dump(path, &plan));
+                eprintln!(
+                    "plan failed at step {} (task {}): {}",
+                    err.step, err.task, err.reason
+                );
+                RunOutcome {
+                    summary: Summary {
+                        steps: Some(err.step),
+                        processes: Some(config.processes),
+                        ..Summary::default()
+                    },
+                    failures: vec![Failure::from(&err)],
+                    dumped_plan,
+                    ..RunOutcome::default()
+                }
+            }
+        };
+    }
+
     let plan_for_dump = plan.clone();
-    match runtime.block_on(runner::run_plan_sqlite(plan, pool_size)) {
+    let backend = config.sqlite_backend();
+    let dashboard = config.tui.then(Dashboard::start);
+    let result = runtime.block_on(runner::run_plan_sqlite(
+        plan,
+        backend,
+        config.run_id,
+        dashboard.as_ref(),
+    ));
+    if let Some(dashboard) = dashboard {
+        dashboard.finish(match &result {
+            Ok(_) => "plan complete".to_string(),
+            Err(err) => format!("plan failed at step {}", err.step),
+        });
+    }
+    match result {
         Ok(summary) => {
-            tracing::info!("plan complete: steps={}", summary.steps);
+            tracing::info!(
+                "plan complete: steps={} sim_time_ms={}",
+                summary.steps,
+                summary.sim_time.as_millis()
+            );
+            RunOutcome {
+                summary: Summary {
+                    steps: Some(summary.steps),
+                    sim_time_ms: Some(summary.sim_time.as_millis()),
+                    ..Summary::default()
+                },
+                ..RunOutcome::default()
+            }
         }
         Err(err) => {
-            if let Some(path) = dump_path {
-                if let Err(dump_err) = dump_plan(path, &plan_for_dump) {
-                    eprintln!("failed to dump plan to {}: {dump_err}", path.display());
-                } else {
-                    eprintln!(
-                        "dumped failing plan to {} (replay with --plan {})",
-                        path.display(),
-                        path.display()
-                    );
-                }
-            }
+            let dumped_plan = dump_path.and_then(|path| report_dump(path, &plan_for_dump));
             eprintln!(
                 "plan failed at step {} (task {}): {}",
                 err.step, err.task, err.reason
             );
-            std::process::exit(1);
+            RunOutcome {
+                summary: Summary {
+                    steps: Some(err.step),
+                    ..Summary::default()
+                },
+                failures: vec![Failure::from(&err)],
+                dumped_plan,
+                ..RunOutcome::default()
+            }
+        }
+    }
+}
+
+fn run_soak(config: &SimConfig) -> RunOutcome {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("failed to start async runtime: {err}");
+            return RunOutcome::error(
+                FailureKind::Setup,
+                format!("failed to start async runtime: {err}"),
+            );
         }
+    };
+
+    let dashboard = config.tui.then(Dashboard::start);
+    let result = runtime.block_on(soak::run_soak(config, dashboard.as_ref()));
+    if let Some(dashboard) = dashboard {
+        dashboard.finish(match &result {
+            Ok(_) => "soak complete".to_string(),
+            Err(failure) => format!("soak failed at step {}", failure.step),
+        });
+    }
+    match result {
+        Ok(summary) => {
+            tracing::info!(
+                "soak complete: checkpoints={} steps={}",
+                summary.checkpoints,
+                summary.steps
+            );
+            RunOutcome {
+                summary: Summary {
+                    steps: Some(summary.steps),
+                    checkpoints: Some(summary.checkpoints),
+                    ..Summary::default()
+                },
+                ..RunOutcome::default()
+            }
+        }
+        Err(failure) => {
+            eprintln!(
+                "soak failed at step {} after {}s: {}",
+                failure.step,
+                failure.failed_at.as_secs(),
+                failure.reason
+            );
+            if failure.last_checkpoint == 0 {
+                eprintln!("no checkpoint passed before the failure");
+            } else {
+                eprintln!(
+                    "invariants last held at checkpoint {} ({}s); the failure is in the window after it",
+                    failure.last_checkpoint,
+                    failure.last_checkpoint_at.as_secs()
+                );
+            }
+            RunOutcome {
+                summary: Summary {
+                    steps: Some(failure.step),
+                    checkpoints: Some(failure.last_checkpoint),
+                    ..Summary::default()
+                },
+                failures: vec![Failure {
+                    kind: failure.kind,
+                    step: failure.step,
+                    reason: failure.reason,
+                    last_checkpoint: Some(failure.last_checkpoint),
+                    failed_at_s: Some(failure.failed_at.as_secs()),
+                    ..Failure::default()
+                }],
+                ..RunOutcome::default()
+            }
+        }
+    }
+}
+
+fn migrate_plans(paths: &[std::path::PathBuf]) {
+    let mut failed = false;
+    for path in paths {
+        match plan::Plan::migrate_file(path) {
+            Ok(from_version) if from_version == plan::PLAN_VERSION => {
+                eprintln!("{}: already version {from_version}", path.display());
+            }
+            Ok(from_version) => {
+                eprintln!(
+                    "{}: migrated from version {from_version} to {}",
+                    path.display(),
+                    plan::PLAN_VERSION
+                );
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Dump `plan` to `path`, returning the path when the dump succeeded.
+fn report_dump(path: &std::path::Path, plan: &plan::Plan) -> Option<std::path::PathBuf> {
+    if let Err(dump_err) = dump_plan(path, plan) {
+        eprintln!("failed to dump plan to {}: {dump_err}", path.display());
+        None
+    } else {
+        eprintln!(
+            "dumped failing plan to {} (replay with --plan {})",
+            path.display(),
+            path.display()
+        );
+        Some(path.to_path_buf())
     }
 }
 
@@ -0,0 +1,284 @@
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::panic::AssertUnwindSafe;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::args::SimConfig;
+use crate::plan::{Interaction, Plan};
+use crate::report::{FailureKind, panic_message};
+use crate::runner::{PlanRunner, RunError, RunSummary};
+
+/// Coordinator to worker, one JSON object per line on the worker's stdin.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    /// Sent first. Workers take the coordinator's seed and run id rather than drawing their own,
+    /// so `{{table}}` and friends resolve to the same names in every process.
+    Init {
+        seed: u64,
+        run_id: u32,
+    },
+    Step {
+        interaction: Interaction,
+    },
+}
+
+/// Worker to coordinator, one JSON object per line on the worker's stdout.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply {
+    /// The backend is open and the worker is waiting for steps.
+    Ready,
+    Done,
+    Failed {
+        kind: FailureKind,
+        reason: String,
+    },
+}
+
+/// A worker process and the pipes the coordinator drives it through.
+struct Worker {
+    index: usize,
+    child: Child,
+    stdin: Option<BufWriter<ChildStdin>>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    /// Re-run this binary with the coordinator's arguments plus `--worker-index`.
+    fn spawn(index: usize) -> Result<Self, String> {
+        let exe = std::env::current_exe()
+            .map_err(|err| format!("failed to locate simulator binary: {err}"))?;
+        let mut child = Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .arg("--worker-index")
+            .arg(index.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|err| format!("failed to start process {index}: {err}"))?;
+        let stdin = child.stdin.take().map(BufWriter::new);
+        let stdout = child
+            .stdout
+            .take()
+            .map(BufReader::new)
+            .ok_or_else(|| format!("process {index} has no stdout"))?;
+        Ok(Self {
+            index,
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn send(&mut self, request: &Request) -> Result<(), String> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| format!("process {} stdin is closed", self.index))?;
+        write_line(stdin, request).map_err(|err| format!("process {}: {err}", self.index))
+    }
+
+    fn recv(&mut self) -> Result<Reply, String> {
+        let mut line = String::new();
+        match self.stdout.read_line(&mut line) {
+            Ok(0) => Err(format!("process {} exited unexpectedly", self.index)),
+            Ok(_) => serde_json::from_str(&line)
+                .map_err(|err| format!("process {} sent an invalid reply: {err}", self.index)),
+            Err(err) => Err(format!("process {}: {err}", self.index)),
+        }
+    }
+
+    /// Close stdin so the worker sees EOF, then wait for it to exit.
+    fn finish(mut self) -> Result<(), String> {
+        drop(self.stdin.take());
+        let status = self
+            .child
+            .wait()
+            .map_err(|err| format!("failed to wait for process {}: {err}", self.index))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("process {} exited with {status}", self.index))
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+        if matches!(self.child.try_wait(), Ok(None)) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+fn write_line<W: Write, T: Serialize>(out: &mut W, message: &T) -> Result<(), String> {
+    let line = serde_json::to_string(message).map_err(|err| err.to_string())?;
+    writeln!(out, "{line}")
+        .and_then(|()| out.flush())
+        .map_err(|err| err.to_string())
+}
+
+/// Run `plan` with its tasks spread over `config.processes` worker processes.
+///
+/// Task `t` runs in process `t % N`, so each task keeps its connection and transaction state in
+/// one process while tasks in different processes contend through the shared database file. Steps
+/// are still sent one at a time in plan order; the coordinator waits for each reply before
+/// sending the next, which keeps the interleaving exactly the plan's.
+#[allow(clippy::result_large_err)] // same error type as the in-process runner
+pub(crate) fn run_plan(plan: &Plan, config: &SimConfig) -> Result<RunSummary, RunError> {
+    let setup_error = |reason: String| RunError {
+        kind: FailureKind::Setup,
+        step: 0,
+        task: 0,
+        action: crate::plan::Action::Sleep { ms: 0 },
+        reason,
+    };
+    config
+        .sqlite_backend()
+        .prepare_shared_file()
+        .map_err(|err| setup_error(format!("backend init failed: {err}")))?;
+
+    let mut workers = Vec::with_capacity(config.processes);
+    for index in 0..config.processes {
+        let mut worker = Worker::spawn(index).map_err(setup_error)?;
+        worker
+            .send(&Request::Init {
+                seed: config.seed,
+                run_id: config.run_id,
+            })
+            .map_err(setup_error)?;
+        match worker.recv().map_err(setup_error)? {
+            Reply::Ready => workers.push(worker),
+            Reply::Failed { kind, reason } => {
+                return Err(RunError {
+                    kind,
+                    ..setup_error(format!("process {index}: {reason}"))
+                });
+            }
+            Reply::Done => {
+                return Err(setup_error(format!(
+                    "process {index} replied before it was ready"
+                )));
+            }
+        }
+    }
+
+    for (step, interaction) in plan.interactions.iter().enumerate() {
+        let process = interaction.task % workers.len();
+        let worker = &mut workers[process];
+        let failed = |kind: FailureKind, reason: String| RunError {
+            kind,
+            step,
+            task: interaction.task,
+            action: interaction.action.clone(),
+            reason,
+        };
+        worker
+            .send(&Request::Step {
+                interaction: interaction.clone(),
+            })
+            .map_err(|reason| failed(FailureKind::Setup, reason))?;
+        match worker
+            .recv()
+            .map_err(|reason| failed(FailureKind::Setup, reason))?
+        {
+            Reply::Done => {}
+            Reply::Failed { kind, reason } => {
+                return Err(failed(kind, format!("process {process}: {reason}")));
+            }
+            Reply::Ready => {
+                return Err(failed(
+                    FailureKind::Setup,
+                    format!("process {process} sent an unexpected reply"),
+                ));
+            }
+        }
+        tracing::info!(
+            "plan_step={} task={} process={} action={}",
+            step,
+            interaction.task,
+            process,
+            interaction.action.label()
+        );
+    }
+
+    for worker in workers {
+        worker.finish().map_err(setup_error)?;
+    }
+    Ok(RunSummary {
+        steps: plan.interactions.len(),
+        sim_time: std::time::Duration::ZERO,
+    })
+}
+
+/// Body of a `--worker-index` process: open the shared database, then apply steps read from
+/// stdin until it closes. Stdout carries replies only, so the worker never logs. A step that
+/// panics is reported as a failed step so the coordinator can classify it.
+pub(crate) fn run_worker(config: &SimConfig, index: usize) -> Result<(), String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .map_err(|err| format!("failed to start async runtime: {err}"))?;
+    let mut stdout = std::io::stdout().lock();
+    let mut requests = std::io::stdin().lock().lines().map(|line| {
+        let line = line.map_err(|err| format!("failed to read request: {err}"))?;
+        serde_json::from_str::<Request>(&line).map_err(|err| format!("invalid request: {err}"))
+    });
+
+    let Some(Request::Init { seed, run_id }) = requests.next().transpose()? else {
+        return Err("expected an init request first".to_string());
+    };
+    let backend = config
+        .sqlite_backend()
+        .shared()
+        .with_jitter_seed(seed.wrapping_add(index as u64));
+    let mut runner = match runtime.block_on(PlanRunner::sqlite(backend, run_id)) {
+        Ok(runner) => runner,
+        Err(err) => {
+            return write_line(
+                &mut stdout,
+                &Reply::Failed {
+                    kind: err.kind,
+                    reason: err.reason,
+                },
+            );
+        }
+    };
+    write_line(&mut stdout, &Reply::Ready)?;
+
+    for request in requests {
+        let Request::Step { interaction } = request? else {
+            return Err("unexpected second init request".to_string());
+        };
+        let step = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            match runtime.block_on(runner.step(&interaction)) {
+                Ok(()) => Reply::Done,
+                Err(err) => Reply::Failed {
+                    kind: err.kind,
+                    reason: err.reason,
+                },
+            }
+        }));
+        let reply = match step {
+            Ok(reply) => reply,
+            Err(payload) => {
+                write_line(
+                    &mut stdout,
+                    &Reply::Failed {
+                        kind: FailureKind::Panic,
+                        reason: format!("panicked: {}", panic_message(payload.as_ref())),
+                    },
+                )?;
+                // The runner may be mid-step; don't reuse it.
+                return Ok(());
+            }
+        };
+        write_line(&mut stdout, &reply)?;
+    }
+    Ok(())
+}
@@ -1,9 +1,17 @@
 use serde::{Deserialize, Serialize};
+use sql_middleware::error::ErrorKind;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
+use crate::schema;
+
+/// Plan format version written by this simulator; see [`crate::schema`] for older versions.
+pub(crate) const PLAN_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Plan {
+    pub(crate) version: u32,
     pub(crate) interactions: Vec<Interaction>,
 }
 
@@ -17,38 +25,167 @@ pub(crate) struct Interaction {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub(crate) enum Action {
     Checkout,
+    /// Join the pool's wait queue without blocking the plan; the task's next `checkout` takes
+    /// the connection the pool handed it, or times out if none has been.
+    RequestCheckout,
     Return,
     Begin,
     Commit,
     Rollback,
     Execute {
         sql: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        params: Vec<serde_json::Value>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        translate: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         expect_error: Option<ErrorExpectation>,
     },
     Query {
         sql: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        params: Vec<serde_json::Value>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        translate: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         expect: Option<QueryExpectation>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         expect_error: Option<ErrorExpectation>,
     },
     Sleep { ms: u64 },
 }
 
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub(crate) struct QueryExpectation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) row_count: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) column_count: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which error an action must fail with.
+///
+/// `class` matches the middleware's error-kind classification and survives backends rewording
+/// their messages; `contains` is a substring match on the message. When both are set both must
+/// match. `backends` replaces the default matcher for the named backend (e.g. `"sqlite"`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub(crate) struct ErrorExpectation {
-    pub(crate) contains: String,
+    #[serde(flatten)]
+    pub(crate) default: ErrorMatcher,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) backends: BTreeMap<String, ErrorMatcher>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub(crate) struct ErrorMatcher {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) class: Option<ErrorClass>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) contains: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ErrorClass {
+    Busy,
+    UniqueViolation,
+    Syntax,
+}
+
+impl ErrorClass {
+    pub(crate) fn kind(self) -> ErrorKind {
+        match self {
+            ErrorClass::Busy => ErrorKind::Busy,
+            ErrorClass::UniqueViolation => ErrorKind::UniqueViolation,
+            ErrorClass::Syntax => ErrorKind::Syntax,
+        }
+    }
+}
+
+impl ErrorExpectation {
+    pub(crate) fn class(class: ErrorClass) -> Self {
+        Self {
+            default: ErrorMatcher {
+                class: Some(class),
+                contains: None,
+            },
+            backends: BTreeMap::new(),
+        }
+    }
+
+    /// The matcher that applies on `backend`.
+    pub(crate) fn for_backend(&self, backend: &str) -> &ErrorMatcher {
+        self.backends.get(backend).unwrap_or(&self.default)
+    }
+}
+
+impl std::fmt::Display for ErrorMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.class, &self.contains) {
+            (Some(class), Some(contains)) => write!(f, "{class:?} error containing {contains:?}"),
+            (Some(class), None) => write!(f, "{class:?} error"),
+            (None, Some(contains)) => write!(f, "error containing {contains:?}"),
+            (None, None) => write!(f, "any error"),
+        }
+    }
+}
+
+impl Action {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Action::Checkout => "checkout",
+            Action::RequestCheckout => "request_checkout",
+            Action::Return => "return",
+            Action::Begin => "begin",
+            Action::Commit => "commit",
+            Action::Rollback => "rollback",
+            Action::Execute { .. } => "execute",
+            Action::Query { .. } => "query",
+            Action::Sleep { .. } => "sleep",
+        }
+    }
 }
 
 impl Plan {
+    /// Load a plan, validating it first and upgrading older format versions in memory.
     pub(crate) fn from_json_path(path: &Path) -> Result<Self, String> {
-        let content = fs::read_to_string(path)
-            .map_err(|err| format!("failed to read plan file {}: {err}", path.display()))?;
-        serde_json::from_str(&content)
-            .map_err(|err| format!("failed to parse plan JSON {}: {err}", path.display()))
+        let (plan, from_version) = load_versioned(path)?;
+        if from_version < PLAN_VERSION {
+            tracing::warn!(
+                "plan {} uses format version {from_version}; upgrade it with --migrate-plan",
+                path.display()
+            );
+        }
+        Ok(plan)
     }
+
+    /// Rewrite a plan file in the current format version.
+    ///
+    /// Returns the version the file had, so callers can tell whether anything changed.
+    pub(crate) fn migrate_file(path: &Path) -> Result<u32, String> {
+        let (plan, from_version) = load_versioned(path)?;
+        let content = serde_json::to_string_pretty(&plan)
+            .map_err(|err| format!("failed to serialize plan: {err}"))?;
+        fs::write(path, content + "\n")
+            .map_err(|err| format!("failed to write plan file {}: {err}", path.display()))?;
+        Ok(from_version)
+    }
+}
+
+fn load_versioned(path: &Path) -> Result<(Plan, u32), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read plan file {}: {err}", path.display()))?;
+    let mut value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|err| format!("failed to parse plan JSON {}: {err}", path.display()))?;
+    let from_version = schema::validate(&value)
+        .map_err(|err| format!("invalid plan {}:\n{err}", path.display()))?;
+    schema::migrate(&mut value, from_version);
+    let plan = serde_json::from_value(value)
+        .map_err(|err| format!("failed to parse plan JSON {}: {err}", path.display()))?;
+    Ok((plan, from_version))
 }
@@ -1,7 +1,9 @@
 use clap::ValueEnum;
 use serde::Serialize;
 
-use crate::plan::{Action, ErrorExpectation, Interaction, Plan, QueryExpectation};
+use crate::plan::{
+    Action, ErrorClass, ErrorExpectation, Interaction, PLAN_VERSION, Plan, QueryExpectation,
+};
 
 #[derive(Debug, Clone, Copy, ValueEnum, Serialize)]
 pub(crate) enum PropertyKind {
@@ -9,15 +11,46 @@ pub(crate) enum PropertyKind {
     TxCommitVisible,
     TxRollbackInvisible,
     RetryAfterBusy,
+    LongTxStarvation,
+    PoolFairness,
+    TranslationRoundTrip,
 }
 
+/// Tasks queued behind a full pool in the `PoolFairness` plan.
+const FAIRNESS_WAITERS: usize = 3;
+
 impl PropertyKind {
-    pub(crate) fn build_plan(self) -> Plan {
+    /// The property's plan, sized for a pool of `pool_size` connections.
+    pub(crate) fn build_plan(self, pool_size: usize) -> Plan {
         match self {
             PropertyKind::PoolCheckoutReturn => pool_checkout_return_plan(),
             PropertyKind::TxCommitVisible => tx_commit_visible_plan(),
             PropertyKind::TxRollbackInvisible => tx_rollback_invisible_plan(),
             PropertyKind::RetryAfterBusy => retry_after_busy_plan(),
+            PropertyKind::LongTxStarvation => long_tx_starvation_plan(pool_size),
+            PropertyKind::PoolFairness => pool_fairness_plan(pool_size),
+            PropertyKind::TranslationRoundTrip => translation_round_trip_plan(),
+        }
+    }
+
+    /// Smallest pool the property can pass on.
+    pub(crate) fn min_pool_size(self) -> usize {
+        match self {
+            PropertyKind::LongTxStarvation => 2,
+            _ => 1,
+        }
+    }
+
+    /// Distinct tasks the plan uses for a pool of `pool_size` connections.
+    pub(crate) fn required_tasks(self, pool_size: usize) -> usize {
+        match self {
+            PropertyKind::PoolCheckoutReturn
+            | PropertyKind::TxCommitVisible
+            | PropertyKind::TxRollbackInvisible
+            | PropertyKind::RetryAfterBusy => 2,
+            PropertyKind::TranslationRoundTrip => 1,
+            PropertyKind::LongTxStarvation => 2 * pool_size.max(2) - 1,
+            PropertyKind::PoolFairness => pool_size.max(1) + FAIRNESS_WAITERS,
         }
     }
 }
@@ -25,12 +58,15 @@ impl PropertyKind {
 fn pool_checkout_return_plan() -> Plan {
     let table = "sim_pool_checkout_return";
     Plan {
+        version: PLAN_VERSION,
         interactions: vec![
             interaction(0, Action::Checkout),
             interaction(
                 0,
                 Action::Execute {
                     sql: format!("CREATE TABLE IF NOT EXISTS {table} (id INTEGER);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -38,6 +74,8 @@ fn pool_checkout_return_plan() -> Plan {
                 0,
                 Action::Execute {
                     sql: format!("INSERT INTO {table} (id) VALUES (1);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -51,6 +89,8 @@ fn pool_checkout_return_plan() -> Plan {
                         row_count: Some(1),
                         column_count: Some(1),
                     }),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -62,12 +102,15 @@ fn pool_checkout_return_plan() -> Plan {
 fn tx_commit_visible_plan() -> Plan {
     let table = "sim_tx_commit_visible";
     Plan {
+        version: PLAN_VERSION,
         interactions: vec![
             interaction(0, Action::Checkout),
             interaction(
                 0,
                 Action::Execute {
                     sql: format!("CREATE TABLE IF NOT EXISTS {table} (id INTEGER);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -76,6 +119,8 @@ fn tx_commit_visible_plan() -> Plan {
                 0,
                 Action::Execute {
                     sql: format!("INSERT INTO {table} (id) VALUES (1);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -90,6 +135,8 @@ fn tx_commit_visible_plan() -> Plan {
                         row_count: Some(1),
                         column_count: Some(1),
                     }),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -101,12 +148,15 @@ fn tx_commit_visible_plan() -> Plan {
 fn tx_rollback_invisible_plan() -> Plan {
     let table = "sim_tx_rollback_invisible";
     Plan {
+        version: PLAN_VERSION,
         interactions: vec![
             interaction(0, Action::Checkout),
             interaction(
                 0,
                 Action::Execute {
                     sql: format!("CREATE TABLE IF NOT EXISTS {table} (id INTEGER);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -115,6 +165,8 @@ fn tx_rollback_invisible_plan() -> Plan {
                 0,
                 Action::Execute {
                     sql: format!("INSERT INTO {table} (id) VALUES (1);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -129,6 +181,8 @@ fn tx_rollback_invisible_plan() -> Plan {
                         row_count: Some(0),
                         column_count: Some(1),
                     }),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -140,12 +194,15 @@ fn tx_rollback_invisible_plan() -> Plan {
 fn retry_after_busy_plan() -> Plan {
     let table = "sim_retry_after_busy";
     Plan {
+        version: PLAN_VERSION,
         interactions: vec![
             interaction(0, Action::Checkout),
             interaction(
                 0,
                 Action::Execute {
                     sql: format!("CREATE TABLE IF NOT EXISTS {table} (id INTEGER);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -153,6 +210,8 @@ fn retry_after_busy_plan() -> Plan {
                 0,
                 Action::Execute {
                     sql: "BEGIN IMMEDIATE;".to_string(),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -160,6 +219,8 @@ fn retry_after_busy_plan() -> Plan {
                 0,
                 Action::Execute {
                     sql: format!("INSERT INTO {table} (id) VALUES (1);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -168,9 +229,9 @@ fn retry_after_busy_plan() -> Plan {
                 1,
                 Action::Execute {
                     sql: format!("INSERT INTO {table} (id) VALUES (2);"),
-                    expect_error: Some(ErrorExpectation {
-                        contains: "locked".to_string(),
-                    }),
+                    params: Vec::new(),
+                    translate: false,
+                    expect_error: Some(ErrorExpectation::class(ErrorClass::Busy)),
                 },
             ),
             interaction(0, Action::Commit),
@@ -178,6 +239,8 @@ fn retry_after_busy_plan() -> Plan {
                 1,
                 Action::Execute {
                     sql: format!("INSERT INTO {table} (id) VALUES (2);"),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -189,6 +252,8 @@ fn retry_after_busy_plan() -> Plan {
                         row_count: Some(1),
                         column_count: Some(1),
                     }),
+                    params: Vec::new(),
+                    translate: false,
                     expect_error: None,
                 },
             ),
@@ -198,6 +263,293 @@ fn retry_after_busy_plan() -> Plan {
     }
 }
 
+/// Task 0 holds a write transaction open across a long sleep while every other pool slot is busy
+/// and more tasks queue for a connection. Each waiter must get the slot another task returns,
+/// while the transaction is still open: a long transaction costs the pool one connection, not
+/// the whole pool.
+///
+/// Needs a pool of at least two connections; with one, the transaction holds the only slot.
+fn long_tx_starvation_plan(pool_size: usize) -> Plan {
+    let table = "sim_long_tx_starvation";
+    let reads = "sim_long_tx_starvation_reads";
+    let slots = pool_size.max(2);
+    let read = |task| {
+        interaction(
+            task,
+            Action::Query {
+                sql: format!("SELECT id FROM {reads};"),
+                expect: Some(QueryExpectation {
+                    row_count: Some(0),
+                    column_count: Some(1),
+                }),
+                params: Vec::new(),
+                translate: false,
+                expect_error: None,
+            },
+        )
+    };
+
+    let mut interactions = vec![interaction(0, Action::Checkout)];
+    for sql in [
+        format!("CREATE TABLE IF NOT EXISTS {table} (id INTEGER);"),
+        format!("CREATE TABLE IF NOT EXISTS {reads} (id INTEGER);"),
+    ] {
+        interactions.push(interaction(
+            0,
+            Action::Execute {
+                sql,
+                params: Vec::new(),
+                translate: false,
+                expect_error: None,
+            },
+        ));
+    }
+    interactions.push(interaction(0, Action::Begin));
+    interactions.push(interaction(
+        0,
+        Action::Execute {
+            sql: format!("INSERT INTO {table} (id) VALUES (1);"),
+            params: Vec::new(),
+            translate: false,
+            expect_error: None,
+        },
+    ));
+    let holders = 1..slots;
+    let waiters = slots..2 * slots - 1;
+    interactions.extend(
+        holders
+            .clone()
+            .map(|task| interaction(task, Action::Checkout)),
+    );
+    interactions.extend(
+        waiters
+            .clone()
+            .map(|task| interaction(task, Action::RequestCheckout)),
+    );
+    interactions.push(interaction(0, Action::Sleep { ms: 5_000 }));
+    for (holder, waiter) in holders.zip(waiters) {
+        interactions.push(read(holder));
+        interactions.push(interaction(holder, Action::Return));
+        interactions.push(interaction(waiter, Action::Checkout));
+        interactions.push(read(waiter));
+        interactions.push(interaction(waiter, Action::Return));
+    }
+    interactions.push(interaction(
+        0,
+        Action::Execute {
+            sql: format!("INSERT INTO {table} (id) VALUES (2);"),
+            params: Vec::new(),
+            translate: false,
+            expect_error: None,
+        },
+    ));
+    interactions.push(interaction(0, Action::Commit));
+    interactions.push(interaction(0, Action::Return));
+    interactions.push(interaction(1, Action::Checkout));
+    interactions.push(interaction(
+        1,
+        Action::Query {
+            sql: format!("SELECT id FROM {table} ORDER BY id;"),
+            expect: Some(QueryExpectation {
+                row_count: Some(2),
+                column_count: Some(1),
+            }),
+            params: Vec::new(),
+            translate: false,
+            expect_error: None,
+        },
+    ));
+    interactions.push(interaction(1, Action::Return));
+    Plan {
+        version: PLAN_VERSION,
+        interactions,
+    }
+}
+
+/// Every slot is checked out, then [`FAIRNESS_WAITERS`] tasks queue for a connection in order.
+/// Each returned connection must go to the longest-waiting task: a waiter's `checkout` only
+/// succeeds if the pool handed it the connection, so serving a later waiter first times out.
+///
+/// Only queued waiters are ordered. A task checking out while a returned connection sits idle
+/// can still take it ahead of them, which is why this is "roughly" FIFO.
+fn pool_fairness_plan(pool_size: usize) -> Plan {
+    let slots = pool_size.max(1);
+    let mut holders: std::collections::VecDeque<usize> = (0..slots).collect();
+    let waiters = slots..slots + FAIRNESS_WAITERS;
+
+    let mut interactions: Vec<Interaction> = holders
+        .iter()
+        .map(|&task| interaction(task, Action::Checkout))
+        .collect();
+    interactions.extend(
+        waiters
+            .clone()
+            .map(|task| interaction(task, Action::RequestCheckout)),
+    );
+    for waiter in waiters {
+        if let Some(holder) = holders.pop_front() {
+            interactions.push(interaction(holder, Action::Return));
+        }
+        interactions.push(interaction(waiter, Action::Checkout));
+        interactions.push(interaction(
+            waiter,
+            Action::Query {
+                sql: "SELECT 1;".to_string(),
+                expect: Some(QueryExpectation {
+                    row_count: Some(1),
+                    column_count: Some(1),
+                }),
+                params: Vec::new(),
+                translate: false,
+                expect_error: None,
+            },
+        ));
+        holders.push_back(waiter);
+    }
+    interactions.extend(
+        holders
+            .into_iter()
+            .map(|task| interaction(task, Action::Return)),
+    );
+    Plan {
+        version: PLAN_VERSION,
+        interactions,
+    }
+}
+
+/// Statements for [`translation_round_trip_plan`]: `$N` text, the same statement hand-written
+/// with `SQLite`'s `?N`, and its parameters. `{table}` stands for the target table. Each covers a
+/// case the placeholder scanner must get right: placeholders in string literals and comments,
+/// reused and reordered numbers, and `$1` next to `$10`.
+const ROUND_TRIP_STATEMENTS: &[(&str, &str, &str)] = &[
+    (
+        "INSERT INTO {table} (id, note, amount, flag) VALUES ($1, $2, $3, $4);",
+        "INSERT INTO {table} (id, note, amount, flag) VALUES (?1, ?2, ?3, ?4);",
+        r#"[1, "plain", 1.5, true]"#,
+    ),
+    (
+        "INSERT INTO {table} (id, note, amount, flag) VALUES ($1, 'costs $2 -- not a comment', $2, $3);",
+        "INSERT INTO {table} (id, note, amount, flag) VALUES (?1, 'costs $2 -- not a comment', ?2, ?3);",
+        "[2, 2.5, false]",
+    ),
+    (
+        "INSERT INTO {table} (id, note, flag) /* $9 is not a parameter */ VALUES ($1, $2, $1 > 10);",
+        "INSERT INTO {table} (id, note, flag) /* $9 is not a parameter */ VALUES (?1, ?2, ?1 > 10);",
+        r#"[3, "it's $1"]"#,
+    ),
+    (
+        "INSERT INTO {table} (id, note) VALUES ($1, $2) -- neither is $3",
+        "INSERT INTO {table} (id, note) VALUES (?1, ?2) -- neither is $3",
+        r#"[4, "?1 stays text"]"#,
+    ),
+    (
+        "INSERT INTO {table} (id, note) VALUES ($2, $1);",
+        "INSERT INTO {table} (id, note) VALUES (?2, ?1);",
+        r#"["swapped", 5]"#,
+    ),
+    (
+        "INSERT INTO {table} (id, note) VALUES ($10, $1 || $2 || $3 || $4 || $5 || $6 || $7 || $8 || $9);",
+        "INSERT INTO {table} (id, note) VALUES (?10, ?1 || ?2 || ?3 || ?4 || ?5 || ?6 || ?7 || ?8 || ?9);",
+        r#"["a", "b", "c", "d", "e", "f", "g", "h", "i", 6]"#,
+    ),
+];
+
+/// Writes the same parameterized statements into two tables, once as `$N` text through the
+/// middleware's placeholder translation and once hand-written in `SQLite`'s native `?N` style,
+/// then requires the tables to hold identical rows. A filtered read runs both ways too and must
+/// return the same count.
+fn translation_round_trip_plan() -> Plan {
+    let translated = "sim_translation_translated";
+    let native = "sim_translation_native";
+    let mut interactions = vec![interaction(0, Action::Checkout)];
+    for table in [translated, native] {
+        interactions.push(interaction(
+            0,
+            Action::Execute {
+                sql: format!(
+                    "CREATE TABLE IF NOT EXISTS {table} (id INTEGER, note TEXT, amount REAL, flag INTEGER);"
+                ),
+                params: Vec::new(),
+                translate: false,
+                expect_error: None,
+            },
+        ));
+    }
+    for (dollar, question, params) in ROUND_TRIP_STATEMENTS {
+        let params: Vec<serde_json::Value> =
+            serde_json::from_str(params).expect("round-trip parameters are valid JSON");
+        for (table, sql, translate) in [(translated, dollar, true), (native, question, false)] {
+            interactions.push(interaction(
+                0,
+                Action::Execute {
+                    sql: sql.replace("{table}", table),
+                    params: params.clone(),
+                    translate,
+                    expect_error: None,
+                },
+            ));
+        }
+    }
+
+    let read = |table: &str, sql: &str, translate| {
+        interaction(
+            0,
+            Action::Query {
+                sql: sql.replace("{table}", table),
+                params: vec![serde_json::json!(2), serde_json::json!(3.0)],
+                translate,
+                expect: Some(QueryExpectation {
+                    row_count: Some(5),
+                    column_count: Some(2),
+                }),
+                expect_error: None,
+            },
+        )
+    };
+    interactions.push(read(
+        translated,
+        "SELECT id, note FROM {table} WHERE id >= $1 AND note <> '$1' AND (amount IS NULL OR amount < $2) ORDER BY id;",
+        true,
+    ));
+    interactions.push(read(
+        native,
+        "SELECT id, note FROM {table} WHERE id >= ?1 AND note <> '$1' AND (amount IS NULL OR amount < ?2) ORDER BY id;",
+        false,
+    ));
+
+    let rows = ROUND_TRIP_STATEMENTS.len();
+    for (sql, row_count) in [
+        (format!("SELECT * FROM {translated};"), rows),
+        (
+            format!("SELECT * FROM {translated} EXCEPT SELECT * FROM {native};"),
+            0,
+        ),
+        (
+            format!("SELECT * FROM {native} EXCEPT SELECT * FROM {translated};"),
+            0,
+        ),
+    ] {
+        interactions.push(interaction(
+            0,
+            Action::Query {
+                sql,
+                params: Vec::new(),
+                translate: false,
+                expect: Some(QueryExpectation {
+                    row_count: Some(row_count),
+                    column_count: Some(4),
+                }),
+                expect_error: None,
+            },
+        ));
+    }
+    interactions.push(interaction(0, Action::Return));
+    Plan {
+        version: PLAN_VERSION,
+        interactions,
+    }
+}
+
 fn interaction(task: usize, action: Action) -> Interaction {
     Interaction { task, action }
 }
@@ -0,0 +1,199 @@
+use std::any::Any;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::args::SimConfig;
+use crate::runner::RunError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OutputFormat {
+    /// Step log on stdout, failures on stderr.
+    Text,
+    /// One JSON document describing the run, on stdout or in `--output-file`.
+    Json,
+}
+
+/// Why a run failed. Each class exits with its own code so wrapper scripts can tell
+/// infrastructure trouble from middleware bugs without reading the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FailureKind {
+    /// A query expectation or soak invariant did not hold.
+    #[default]
+    Oracle,
+    /// The flags, plan, or generator were invalid.
+    Usage,
+    /// The backend, runtime, or a worker process could not be set up.
+    Setup,
+    /// An action failed when it should have succeeded, succeeded when it should have failed, or
+    /// failed with a different error than the plan expected.
+    Mismatch,
+    /// The simulator panicked.
+    Panic,
+    /// A pool checkout timed out.
+    Timeout,
+}
+
+impl FailureKind {
+    /// Process exit code; `2` matches clap's own usage errors.
+    pub(crate) fn exit_code(self) -> i32 {
+        match self {
+            FailureKind::Oracle => 1,
+            FailureKind::Usage => 2,
+            FailureKind::Setup => 3,
+            FailureKind::Mismatch => 4,
+            FailureKind::Panic => 5,
+            FailureKind::Timeout => 6,
+        }
+    }
+}
+
+/// What a run produced: its counters, and its failure if it had one.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct RunOutcome {
+    pub(crate) summary: Summary,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) failures: Vec<Failure>,
+    /// The run could not start or finish (a plan that failed to load, a broken runtime, ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error_kind: Option<FailureKind>,
+    /// Where `--dump-plan-on-failure` wrote the failing plan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dumped_plan: Option<PathBuf>,
+}
+
+impl RunOutcome {
+    pub(crate) fn error(kind: FailureKind, message: impl Into<String>) -> Self {
+        Self {
+            error: Some(message.into()),
+            error_kind: Some(kind),
+            ..Self::default()
+        }
+    }
+
+    /// A panic caught while running, with the panic message when it has one.
+    pub(crate) fn panic(payload: &(dyn Any + Send)) -> Self {
+        Self::error(
+            FailureKind::Panic,
+            format!("simulator panicked: {}", panic_message(payload)),
+        )
+    }
+
+    /// `0` for a passing run, otherwise the code of the error or the first failure.
+    pub(crate) fn exit_code(&self) -> i32 {
+        self.error_kind
+            .or_else(|| self.failures.first().map(|failure| failure.kind))
+            .map_or(0, FailureKind::exit_code)
+    }
+
+    pub(crate) fn status(&self) -> Status {
+        if self.error.is_some() {
+            Status::Error
+        } else if self.failures.is_empty() {
+            Status::Passed
+        } else {
+            Status::Failed
+        }
+    }
+}
+
+/// The message a panic was raised with, when it has one.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Status {
+    Passed,
+    Failed,
+    Error,
+}
+
+/// Counters from the run; only those that apply to its mode are present.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Summary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) steps: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sim_time_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) interleavings: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) exhausted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) checkpoints: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) processes: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Failure {
+    pub(crate) kind: FailureKind,
+    pub(crate) step: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) task: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) action: Option<&'static str>,
+    pub(crate) reason: String,
+    /// `--explore`: the interleaving that failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) interleaving: Option<usize>,
+    /// `--soak`: the last checkpoint that passed (0 when none did).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_checkpoint: Option<u64>,
+    /// `--soak`: wall-clock seconds into the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) failed_at_s: Option<u64>,
+}
+
+impl From<&RunError> for Failure {
+    fn from(err: &RunError) -> Self {
+        Self {
+            kind: err.kind,
+            step: err.step,
+            task: Some(err.task),
+            action: Some(err.action.label()),
+            reason: err.reason.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+/// The `--output json` document.
+#[derive(Debug, Serialize)]
+pub(crate) struct Report<'a> {
+    pub(crate) mode: &'static str,
+    pub(crate) status: Status,
+    pub(crate) exit_code: i32,
+    pub(crate) seed: u64,
+    pub(crate) run_id: u32,
+    #[serde(flatten)]
+    pub(crate) outcome: &'a RunOutcome,
+    pub(crate) config: &'a SimConfig,
+}
+
+impl Report<'_> {
+    /// Write the report to `path`, or to stdout when there is none.
+    pub(crate) fn write(&self, path: Option<&Path>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("failed to serialize report: {err}"))?;
+        match path {
+            Some(path) => std::fs::write(path, json + "\n")
+                .map_err(|err| format!("failed to write report to {}: {err}", path.display())),
+            None => {
+                println!("{json}");
+                Ok(())
+            }
+        }
+    }
+}
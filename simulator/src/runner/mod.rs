@@ -1,11 +1,18 @@
 use std::path::Path;
 
-use crate::backends::sqlite::{BackendError, SqliteBackend, SqliteBackendConfig};
-use crate::plan::{Action, ErrorExpectation, Plan, QueryExpectation};
-use sql_middleware::ResultSet;
+use crate::backends::sqlite::{
+    BackendError, Bind, PendingCheckout, SqliteBackend, SqliteBackendConfig,
+};
+use crate::events::{EventLog, EventOutcome};
+use crate::plan::{Action, ErrorExpectation, ErrorMatcher, Interaction, Plan, QueryExpectation};
+use crate::report::FailureKind;
+use crate::template::TemplateContext;
+use crate::tui::Dashboard;
+use sql_middleware::{ResultSet, RowValues};
 
 #[derive(Debug)]
 pub(crate) struct RunError {
+    pub(crate) kind: FailureKind,
     pub(crate) step: usize,
     pub(crate) task: usize,
     pub(crate) action: Action,
@@ -15,160 +22,257 @@ pub(crate) struct RunError {
 #[derive(Debug)]
 pub(crate) struct RunSummary {
     pub(crate) steps: usize,
+    pub(crate) sim_time: std::time::Duration,
 }
 
 #[derive(Debug, Default)]
 struct TaskState {
     conn: Option<sql_middleware::MiddlewarePoolConnection>,
+    /// Set by `request_checkout` until the task's next `checkout`.
+    pending: Option<PendingCheckout>,
     in_tx: bool,
 }
 
-pub(crate) async fn run_plan_sqlite(plan: Plan, pool_size: usize) -> Result<RunSummary, RunError> {
-    let backend = SqliteBackend::new(SqliteBackendConfig::in_memory(pool_size))
-        .await
-        .map_err(|err| RunError {
-            step: 0,
-            task: 0,
-            action: Action::Sleep { ms: 0 },
-            reason: format!("backend init failed: {err}"),
-        })?;
-    run_plan(plan, backend).await
+pub(crate) async fn run_plan_sqlite(
+    plan: Plan,
+    config: SqliteBackendConfig,
+    run_id: u32,
+    dashboard: Option<&Dashboard>,
+) -> Result<RunSummary, RunError> {
+    let mut runner = PlanRunner::sqlite(config, run_id).await?;
+    for interaction in &plan.interactions {
+        let result = runner.step(interaction).await;
+        if let Some(dashboard) = dashboard {
+            dashboard.update(&runner);
+        }
+        result?;
+    }
+    if let Some(dashboard) = dashboard {
+        dashboard.flush(&runner);
+    }
+    Ok(runner.summary())
 }
 
 pub(crate) fn load_plan(path: &Path) -> Result<Plan, String> {
     Plan::from_json_path(path)
 }
 
-async fn run_plan(plan: Plan, backend: SqliteBackend) -> Result<RunSummary, RunError> {
-    let task_count = plan
-        .interactions
-        .iter()
-        .map(|interaction| interaction.task)
-        .max()
-        .map(|id| id + 1)
-        .unwrap_or(0);
+/// Runs interactions one at a time against a single backend, keeping per-task connections and
+/// transactions between steps.
+pub(crate) struct PlanRunner {
+    backend: SqliteBackend,
+    templates: TemplateContext,
+    tasks: Vec<TaskState>,
+    steps: usize,
+    events: EventLog,
+}
 
-    let mut tasks = Vec::with_capacity(task_count);
-    for _ in 0..task_count {
-        tasks.push(TaskState::default());
+impl PlanRunner {
+    pub(crate) async fn sqlite(config: SqliteBackendConfig, run_id: u32) -> Result<Self, RunError> {
+        let backend = SqliteBackend::new(config).await.map_err(|err| RunError {
+            kind: FailureKind::Setup,
+            step: 0,
+            task: 0,
+            action: Action::Sleep { ms: 0 },
+            reason: format!("backend init failed: {err}"),
+        })?;
+        Ok(Self {
+            backend,
+            templates: TemplateContext::new(run_id),
+            tasks: Vec::new(),
+            steps: 0,
+            events: EventLog::default(),
+        })
     }
-    let mut backend = backend;
 
-    let total_steps = plan.interactions.len();
-
-    for (step, interaction) in plan.interactions.into_iter().enumerate() {
+    pub(crate) async fn step(&mut self, interaction: &Interaction) -> Result<(), RunError> {
+        let step = self.steps;
         let task_id = interaction.task;
-        let action = interaction.action;
-
-        let task = tasks.get_mut(task_id).ok_or_else(|| RunError {
-            step,
-            task: task_id,
-            action: action.clone(),
-            reason: "unknown task id".to_string(),
-        })?;
-
-        if let Err(err) = apply_action(&mut backend, task, &action).await {
-            return Err(RunError {
+        let action = self
+            .templates
+            .resolve_action(&interaction.action, task_id)
+            .map_err(|reason| RunError {
+                kind: FailureKind::Usage,
                 step,
                 task: task_id,
-                action: action.clone(),
-                reason: err.to_string(),
-            });
+                action: interaction.action.clone(),
+                reason,
+            })?;
+
+        if self.tasks.len() <= task_id {
+            self.tasks.resize_with(task_id + 1, TaskState::default);
         }
+        let task = &mut self.tasks[task_id];
+
+        let outcome = match apply_action(&mut self.backend, task, &action).await {
+            Ok(true) => EventOutcome::Ok,
+            Ok(false) => EventOutcome::ExpectedError,
+            Err(err) => {
+                let reason = err.to_string();
+                self.events
+                    .record(step, task_id, &action, EventOutcome::Failed(reason.clone()));
+                return Err(RunError {
+                    kind: err.failure_kind(),
+                    step,
+                    task: task_id,
+                    action: action.clone(),
+                    reason,
+                });
+            }
+        };
+        self.events.record(step, task_id, &action, outcome);
 
         tracing::info!(
             "plan_step={} task={} action={}",
             step,
             task_id,
-            action_label(&action)
+            action.label()
         );
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Per-task `(holds_connection, in_transaction)`, indexed by task id.
+    pub(crate) fn task_states(&self) -> impl Iterator<Item = (bool, bool)> + '_ {
+        self.tasks
+            .iter()
+            .map(|task| (task.conn.is_some(), task.in_tx))
     }
 
-    Ok(RunSummary { steps: total_steps })
+    pub(crate) fn events(&self) -> &EventLog {
+        &self.events
+    }
+
+    /// Tasks currently holding a pooled connection.
+    pub(crate) fn held_connections(&self) -> usize {
+        self.tasks.iter().filter(|task| task.conn.is_some()).count()
+    }
+
+    pub(crate) fn backend(&self) -> &SqliteBackend {
+        &self.backend
+    }
+
+    pub(crate) fn summary(&self) -> RunSummary {
+        RunSummary {
+            steps: self.steps,
+            sim_time: self.backend.elapsed(),
+        }
+    }
 }
 
+/// Returns `Ok(false)` when the action failed with the error it expected.
 async fn apply_action(
     backend: &mut SqliteBackend,
     task: &mut TaskState,
     action: &Action,
-) -> Result<(), BackendError> {
+) -> Result<bool, BackendError> {
     match action {
         Action::Checkout => {
             if task.conn.is_some() {
-                return Err(BackendError::Init(
+                return Err(BackendError::Plan(
                     "checkout requested while task already has a connection".to_string(),
                 ));
             }
-            let conn = backend.checkout().await?;
+            let conn = match task.pending.take() {
+                Some(pending) => backend.finish_checkout(pending).await?,
+                None => backend.checkout().await?,
+            };
             task.conn = Some(conn);
             task.in_tx = false;
         }
+        Action::RequestCheckout => {
+            if task.conn.is_some() || task.pending.is_some() {
+                return Err(BackendError::Plan(
+                    "checkout requested while task already has or awaits a connection".to_string(),
+                ));
+            }
+            task.pending = Some(backend.request_checkout().await);
+        }
         Action::Return => {
             if task.in_tx {
-                return Err(BackendError::Init(
+                return Err(BackendError::Plan(
                     "return requested while task is in a transaction".to_string(),
                 ));
             }
             let conn = task.conn.take().ok_or_else(|| {
-                BackendError::Init("return requested without a connection".to_string())
+                BackendError::Plan("return requested without a connection".to_string())
             })?;
             drop(conn);
         }
         Action::Begin => {
             if task.in_tx {
-                return Err(BackendError::Init(
+                return Err(BackendError::Plan(
                     "begin requested while already in a transaction".to_string(),
                 ));
             }
             let conn = task.conn.as_mut().ok_or_else(|| {
-                BackendError::Init("begin requested without a connection".to_string())
+                BackendError::Plan("begin requested without a connection".to_string())
             })?;
             backend.begin(conn).await?;
             task.in_tx = true;
         }
         Action::Commit => {
             if !task.in_tx {
-                return Err(BackendError::Init(
+                return Err(BackendError::Plan(
                     "commit requested without an active transaction".to_string(),
                 ));
             }
             let conn = task.conn.as_mut().ok_or_else(|| {
-                BackendError::Init("commit requested without a connection".to_string())
+                BackendError::Plan("commit requested without a connection".to_string())
             })?;
             backend.commit(conn).await?;
             task.in_tx = false;
         }
         Action::Rollback => {
             if !task.in_tx {
-                return Err(BackendError::Init(
+                return Err(BackendError::Plan(
                     "rollback requested without an active transaction".to_string(),
                 ));
             }
             let conn = task.conn.as_mut().ok_or_else(|| {
-                BackendError::Init("rollback requested without a connection".to_string())
+                BackendError::Plan("rollback requested without a connection".to_string())
             })?;
             backend.rollback(conn).await?;
             task.in_tx = false;
         }
-        Action::Execute { sql, expect_error } => {
+        Action::Execute {
+            sql,
+            params,
+            translate,
+            expect_error,
+        } => {
             let conn = task.conn.as_mut().ok_or_else(|| {
-                BackendError::Init("execute requested without a connection".to_string())
+                BackendError::Plan("execute requested without a connection".to_string())
             })?;
-            let result = backend.execute(conn, sql, task.in_tx).await;
-            handle_action_result(result, expect_error)?;
+            let params = bind_params(params)?;
+            let bind = Bind {
+                params: &params,
+                translate: *translate,
+            };
+            let result = backend.execute(conn, sql, bind, task.in_tx).await;
+            if handle_action_result(result, expect_error)?.is_none() {
+                return Ok(false);
+            }
         }
         Action::Query {
             sql,
+            params,
+            translate,
             expect,
             expect_error,
         } => {
             let conn = task.conn.as_mut().ok_or_else(|| {
-                BackendError::Init("query requested without a connection".to_string())
+                BackendError::Plan("query requested without a connection".to_string())
             })?;
-            let result = backend.query(conn, sql, task.in_tx).await;
+            let params = bind_params(params)?;
+            let bind = Bind {
+                params: &params,
+                translate: *translate,
+            };
+            let result = backend.query(conn, sql, bind, task.in_tx).await;
             let result = match handle_action_result(result, expect_error)? {
                 Some(result) => result,
-                None => return Ok(()),
+                None => return Ok(false),
             };
             let summary = summarize_result(&result);
             if let Some(expect) = expect {
@@ -184,20 +288,27 @@ async fn apply_action(
             backend.sleep(*ms).await;
         }
     }
-    Ok(())
+    Ok(true)
 }
 
-fn action_label(action: &Action) -> &'static str {
-    match action {
-        Action::Checkout => "checkout",
-        Action::Return => "return",
-        Action::Begin => "begin",
-        Action::Commit => "commit",
-        Action::Rollback => "rollback",
-        Action::Execute { .. } => "execute",
-        Action::Query { .. } => "query",
-        Action::Sleep { .. } => "sleep",
-    }
+/// Plan parameters as bind values: JSON null, booleans, integers, floats, and strings.
+fn bind_params(params: &[serde_json::Value]) -> Result<Vec<RowValues>, BackendError> {
+    params
+        .iter()
+        .map(|value| match value {
+            serde_json::Value::Null => Ok(RowValues::Null),
+            serde_json::Value::Bool(flag) => Ok(RowValues::Bool(*flag)),
+            serde_json::Value::String(text) => Ok(RowValues::Text(text.as_str().into())),
+            serde_json::Value::Number(number) => number
+                .as_i64()
+                .map(RowValues::Int)
+                .or_else(|| number.as_f64().map(RowValues::Float))
+                .ok_or_else(|| BackendError::Plan(format!("unsupported parameter {number}"))),
+            other => Err(BackendError::Plan(format!(
+                "unsupported parameter {other}; expected null, a boolean, a number, or a string"
+            ))),
+        })
+        .collect()
 }
 
 struct QuerySummary {
@@ -216,53 +327,63 @@ fn summarize_result(result: &ResultSet) -> QuerySummary {
     }
 }
 
+/// Backend name used to pick `ErrorExpectation::backends` overrides.
+const BACKEND_NAME: &str = "sqlite";
+
 fn handle_action_result<T>(
     result: Result<T, BackendError>,
     expect_error: &Option<ErrorExpectation>,
 ) -> Result<Option<T>, BackendError> {
-    match (result, expect_error) {
+    let matcher = expect_error
+        .as_ref()
+        .map(|expect| expect.for_backend(BACKEND_NAME));
+    match (result, matcher) {
         (Ok(value), None) => Ok(Some(value)),
-        (Ok(_), Some(expect)) => Err(BackendError::Init(format!(
-            "expected error containing {:?}, but action succeeded",
-            expect.contains
+        (Ok(_), Some(matcher)) => Err(BackendError::Mismatch(format!(
+            "expected {matcher}, but action succeeded"
         ))),
         (Err(err), None) => Err(err),
-        (Err(err), Some(expect)) => {
-            if error_matches(&err, expect) {
+        (Err(err), Some(matcher)) => {
+            if error_matches(&err, matcher) {
                 Ok(None)
             } else {
-                Err(BackendError::Init(format!(
-                    "error mismatch: expected {:?}, got {}",
-                    expect.contains, err
+                Err(BackendError::Mismatch(format!(
+                    "error mismatch: expected {matcher}, got {:?} error: {err}",
+                    err.kind()
                 )))
             }
         }
     }
 }
 
-fn error_matches(err: &BackendError, expect: &ErrorExpectation) -> bool {
-    err.to_string().contains(&expect.contains)
+fn error_matches(err: &BackendError, matcher: &ErrorMatcher) -> bool {
+    let class_ok = matcher.class.is_none_or(|class| err.kind() == class.kind());
+    let contains_ok = matcher
+        .contains
+        .as_ref()
+        .is_none_or(|contains| err.to_string().contains(contains));
+    class_ok && contains_ok
 }
 
 fn verify_query_expectation(
     expect: &QueryExpectation,
     summary: &QuerySummary,
 ) -> Result<(), BackendError> {
-    if let Some(row_count) = expect.row_count {
-        if summary.row_count != row_count {
-            return Err(BackendError::Init(format!(
-                "query row_count mismatch: expected {row_count}, got {}",
-                summary.row_count
-            )));
-        }
+    if let Some(row_count) = expect.row_count
+        && summary.row_count != row_count
+    {
+        return Err(BackendError::Oracle(format!(
+            "query row_count mismatch: expected {row_count}, got {}",
+            summary.row_count
+        )));
     }
-    if let Some(column_count) = expect.column_count {
-        if summary.column_count != column_count {
-            return Err(BackendError::Init(format!(
-                "query column_count mismatch: expected {column_count}, got {}",
-                summary.column_count
-            )));
-        }
+    if let Some(column_count) = expect.column_count
+        && summary.column_count != column_count
+    {
+        return Err(BackendError::Oracle(format!(
+            "query column_count mismatch: expected {column_count}, got {}",
+            summary.column_count
+        )));
     }
     Ok(())
 }
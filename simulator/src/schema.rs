@@ -0,0 +1,434 @@
+//! Structural validation and format migration for plan JSON.
+//!
+//! Plans are checked as raw JSON before deserializing, so a hand-edited plan fails with the path
+//! of every problem (`interactions[3].action.type: unknown action "chekout"`) instead of serde's
+//! first complaint or a runner error halfway through the run. `plans/plan.schema.json` describes
+//! the same structure for editors.
+//!
+//! Format versions:
+//! - 1: the original format, without a `version` key.
+//! - 2: adds `"version": 2`. Actions are unchanged; migrating stamps the version.
+
+use serde_json::{Map, Value};
+
+use crate::plan::PLAN_VERSION;
+
+/// Task ids above this are rejected rather than allocating state for every id below them.
+const MAX_TASK_ID: u64 = 4_095;
+const MAX_REPORTED_ERRORS: usize = 20;
+
+const ACTION_TYPES: &[&str] = &[
+    "checkout",
+    "request_checkout",
+    "return",
+    "begin",
+    "commit",
+    "rollback",
+    "execute",
+    "query",
+    "sleep",
+];
+const ERROR_CLASSES: &[&str] = &["busy", "unique_violation", "syntax"];
+
+/// Check `plan` against the plan schema and return its format version.
+///
+/// Besides structure, each task's actions must follow the connection/transaction protocol the
+/// runner enforces (checkout before SQL, no return inside a transaction, ...). The error lists
+/// every problem found, one per line.
+pub(crate) fn validate(plan: &Value) -> Result<u32, String> {
+    let mut errors = Errors::default();
+    let version = check_plan(plan, &mut errors);
+    errors.into_result().map(|()| version)
+}
+
+/// Upgrade a validated plan from `from_version` to [`PLAN_VERSION`] in place.
+pub(crate) fn migrate(plan: &mut Value, from_version: u32) {
+    if from_version < PLAN_VERSION
+        && let Some(obj) = plan.as_object_mut()
+    {
+        // 1 -> 2: only the version key is new.
+        obj.insert("version".to_string(), Value::from(PLAN_VERSION));
+    }
+}
+
+#[derive(Default)]
+struct Errors {
+    messages: Vec<String>,
+}
+
+impl Errors {
+    fn push(&mut self, path: &str, message: impl Into<String>) {
+        self.messages.push(format!("{path}: {}", message.into()));
+    }
+
+    fn into_result(self) -> Result<(), String> {
+        if self.messages.is_empty() {
+            return Ok(());
+        }
+        let total = self.messages.len();
+        let mut report: Vec<String> = self
+            .messages
+            .into_iter()
+            .take(MAX_REPORTED_ERRORS)
+            .collect();
+        if total > MAX_REPORTED_ERRORS {
+            report.push(format!("... and {} more", total - MAX_REPORTED_ERRORS));
+        }
+        Err(report.join("\n"))
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct TaskFlow {
+    has_conn: bool,
+    waiting: bool,
+    in_tx: bool,
+}
+
+fn check_plan(plan: &Value, errors: &mut Errors) -> u32 {
+    let Some(obj) = plan.as_object() else {
+        errors.push(
+            "$",
+            "expected an object with \"version\" and \"interactions\"",
+        );
+        return PLAN_VERSION;
+    };
+    check_keys(obj, "$", &["$schema", "version", "interactions"], errors);
+
+    let version = match obj.get("version") {
+        None => 1,
+        Some(value) => match value.as_u64().and_then(|v| u32::try_from(v).ok()) {
+            Some(version) if (1..=PLAN_VERSION).contains(&version) => version,
+            Some(version) if version > PLAN_VERSION => {
+                errors.push(
+                    "$.version",
+                    format!(
+                        "version {version} is newer than this simulator supports ({PLAN_VERSION})"
+                    ),
+                );
+                PLAN_VERSION
+            }
+            _ => {
+                errors.push(
+                    "$.version",
+                    format!("expected an integer from 1 to {PLAN_VERSION}, got {value}"),
+                );
+                PLAN_VERSION
+            }
+        },
+    };
+
+    match obj.get("interactions") {
+        None => errors.push("$", "missing field \"interactions\""),
+        Some(Value::Array(interactions)) => {
+            let mut flows = Vec::new();
+            for (idx, interaction) in interactions.iter().enumerate() {
+                check_interaction(
+                    interaction,
+                    &format!("interactions[{idx}]"),
+                    &mut flows,
+                    errors,
+                );
+            }
+        }
+        Some(other) => errors.push("$.interactions", format!("expected an array, got {other}")),
+    }
+    version
+}
+
+fn check_interaction(value: &Value, path: &str, flows: &mut Vec<TaskFlow>, errors: &mut Errors) {
+    let Some(obj) = value.as_object() else {
+        errors.push(path, "expected an object with \"task\" and \"action\"");
+        return;
+    };
+    check_keys(obj, path, &["task", "action"], errors);
+
+    let task = match obj.get("task") {
+        None => {
+            errors.push(path, "missing field \"task\"");
+            None
+        }
+        Some(task) => match task.as_u64() {
+            Some(id) if id <= MAX_TASK_ID => usize::try_from(id).ok(),
+            Some(id) => {
+                errors.push(
+                    &format!("{path}.task"),
+                    format!("task id {id} is out of range (max {MAX_TASK_ID})"),
+                );
+                None
+            }
+            None => {
+                errors.push(
+                    &format!("{path}.task"),
+                    format!("task id must be a non-negative integer, got {task}"),
+                );
+                None
+            }
+        },
+    };
+
+    let action_type = match obj.get("action") {
+        None => {
+            errors.push(path, "missing field \"action\"");
+            None
+        }
+        Some(action) => check_action(action, &format!("{path}.action"), errors),
+    };
+
+    if let (Some(task), Some(action_type)) = (task, action_type) {
+        if flows.len() <= task {
+            flows.resize(task + 1, TaskFlow::default());
+        }
+        if let Err(message) = step_flow(&mut flows[task], action_type) {
+            errors.push(path, format!("task {task} {message}"));
+        }
+    }
+}
+
+/// Validate an action object and return its type when the type is known.
+fn check_action<'a>(value: &'a Value, path: &str, errors: &mut Errors) -> Option<&'a str> {
+    let Some(obj) = value.as_object() else {
+        errors.push(path, "expected an object with a \"type\"");
+        return None;
+    };
+    let action_type = match obj.get("type") {
+        None => {
+            errors.push(path, "missing field \"type\"");
+            return None;
+        }
+        Some(Value::String(action_type)) => action_type.as_str(),
+        Some(other) => {
+            errors.push(
+                &format!("{path}.type"),
+                format!("expected a string, got {other}"),
+            );
+            return None;
+        }
+    };
+
+    match action_type {
+        "checkout" | "request_checkout" | "return" | "begin" | "commit" | "rollback" => {
+            check_keys(obj, path, &["type"], errors);
+        }
+        "execute" => {
+            check_keys(
+                obj,
+                path,
+                &["type", "sql", "params", "translate", "expect_error"],
+                errors,
+            );
+            check_string(obj, path, "sql", true, errors);
+            check_params(obj, path, errors);
+            check_error_expectation(
+                obj.get("expect_error"),
+                &format!("{path}.expect_error"),
+                errors,
+            );
+        }
+        "query" => {
+            check_keys(
+                obj,
+                path,
+                &[
+                    "type",
+                    "sql",
+                    "params",
+                    "translate",
+                    "expect",
+                    "expect_error",
+                ],
+                errors,
+            );
+            check_string(obj, path, "sql", true, errors);
+            check_params(obj, path, errors);
+            check_query_expectation(obj.get("expect"), &format!("{path}.expect"), errors);
+            check_error_expectation(
+                obj.get("expect_error"),
+                &format!("{path}.expect_error"),
+                errors,
+            );
+        }
+        "sleep" => {
+            check_keys(obj, path, &["type", "ms"], errors);
+            check_count(obj, path, "ms", true, errors);
+        }
+        unknown => {
+            errors.push(
+                &format!("{path}.type"),
+                format!(
+                    "unknown action {unknown:?}; expected one of {}",
+                    ACTION_TYPES.join(", ")
+                ),
+            );
+            return None;
+        }
+    }
+    Some(action_type)
+}
+
+/// `params` must be an array of scalars; `translate` must be a boolean.
+fn check_params(obj: &Map<String, Value>, path: &str, errors: &mut Errors) {
+    match obj.get("params") {
+        None => {}
+        Some(Value::Array(params)) => {
+            for (idx, param) in params.iter().enumerate() {
+                if param.is_array() || param.is_object() {
+                    errors.push(
+                        &format!("{path}.params[{idx}]"),
+                        format!("expected null, a boolean, a number, or a string, got {param}"),
+                    );
+                }
+            }
+        }
+        Some(other) => errors.push(
+            &format!("{path}.params"),
+            format!("expected an array, got {other}"),
+        ),
+    }
+    match obj.get("translate") {
+        None | Some(Value::Bool(_)) => {}
+        Some(other) => errors.push(
+            &format!("{path}.translate"),
+            format!("expected a boolean, got {other}"),
+        ),
+    }
+}
+
+fn check_query_expectation(value: Option<&Value>, path: &str, errors: &mut Errors) {
+    let Some(value) = value.filter(|value| !value.is_null()) else {
+        return;
+    };
+    let Some(obj) = value.as_object() else {
+        errors.push(path, format!("expected an object, got {value}"));
+        return;
+    };
+    check_keys(obj, path, &["row_count", "column_count"], errors);
+    check_count(obj, path, "row_count", false, errors);
+    check_count(obj, path, "column_count", false, errors);
+}
+
+fn check_error_expectation(value: Option<&Value>, path: &str, errors: &mut Errors) {
+    let Some(value) = value.filter(|value| !value.is_null()) else {
+        return;
+    };
+    let Some(obj) = value.as_object() else {
+        errors.push(path, format!("expected an object, got {value}"));
+        return;
+    };
+    check_keys(obj, path, &["class", "contains", "backends"], errors);
+    check_error_matcher(obj, path, errors);
+    match obj.get("backends") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(backends)) => {
+            for (backend, matcher) in backends {
+                let path = format!("{path}.backends.{backend}");
+                match matcher.as_object() {
+                    Some(matcher) => {
+                        check_keys(matcher, &path, &["class", "contains"], errors);
+                        check_error_matcher(matcher, &path, errors);
+                    }
+                    None => errors.push(&path, format!("expected an object, got {matcher}")),
+                }
+            }
+        }
+        Some(other) => errors.push(
+            &format!("{path}.backends"),
+            format!("expected an object keyed by backend name, got {other}"),
+        ),
+    }
+}
+
+fn check_error_matcher(obj: &Map<String, Value>, path: &str, errors: &mut Errors) {
+    check_string(obj, path, "contains", false, errors);
+    match obj.get("class") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(class)) if ERROR_CLASSES.contains(&class.as_str()) => {}
+        Some(other) => errors.push(
+            &format!("{path}.class"),
+            format!(
+                "unknown error class {other}; expected one of {}",
+                ERROR_CLASSES.join(", ")
+            ),
+        ),
+    }
+}
+
+fn check_keys(obj: &Map<String, Value>, path: &str, allowed: &[&str], errors: &mut Errors) {
+    for key in obj.keys() {
+        if !allowed.contains(&key.as_str()) {
+            errors.push(
+                path,
+                format!("unknown field {key:?}; expected {}", allowed.join(", ")),
+            );
+        }
+    }
+}
+
+fn check_string(
+    obj: &Map<String, Value>,
+    path: &str,
+    field: &str,
+    required: bool,
+    errors: &mut Errors,
+) {
+    match obj.get(field) {
+        None if required => errors.push(path, format!("missing field {field:?}")),
+        None | Some(Value::String(_)) => {}
+        Some(Value::Null) if !required => {}
+        Some(other) => errors.push(
+            &format!("{path}.{field}"),
+            format!("expected a string, got {other}"),
+        ),
+    }
+}
+
+fn check_count(
+    obj: &Map<String, Value>,
+    path: &str,
+    field: &str,
+    required: bool,
+    errors: &mut Errors,
+) {
+    match obj.get(field) {
+        None if required => errors.push(path, format!("missing field {field:?}")),
+        None => {}
+        Some(Value::Null) if !required => {}
+        Some(value) if value.as_u64().is_some() => {}
+        Some(other) => errors.push(
+            &format!("{path}.{field}"),
+            format!("expected a non-negative integer, got {other}"),
+        ),
+    }
+}
+
+/// Apply one action to a task's connection/transaction state, mirroring the runner's checks.
+fn step_flow(flow: &mut TaskFlow, action_type: &str) -> Result<(), &'static str> {
+    match action_type {
+        "checkout" if flow.has_conn => {
+            return Err("checks out a connection while already holding one");
+        }
+        "checkout" => {
+            flow.has_conn = true;
+            flow.waiting = false;
+        }
+        "request_checkout" if flow.has_conn || flow.waiting => {
+            return Err("requests a connection while holding or waiting for one");
+        }
+        "request_checkout" => flow.waiting = true,
+        "return" if !flow.has_conn => return Err("returns a connection it does not hold"),
+        "return" if flow.in_tx => return Err("returns its connection inside a transaction"),
+        "return" => flow.has_conn = false,
+        "begin" if !flow.has_conn => return Err("begins a transaction without a connection"),
+        "begin" if flow.in_tx => return Err("begins a transaction inside a transaction"),
+        "begin" => flow.in_tx = true,
+        "commit" | "rollback" if !flow.in_tx => {
+            return Err("commits or rolls back without an open transaction");
+        }
+        "commit" | "rollback" => flow.in_tx = false,
+        "execute" | "query" if !flow.has_conn => {
+            return Err("runs SQL without a checked-out connection");
+        }
+        _ => {}
+    }
+    Ok(())
+}
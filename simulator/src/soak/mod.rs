@@ -0,0 +1,284 @@
+use std::time::{Duration, Instant};
+
+use crate::args::SimConfig;
+use crate::backends::sqlite::Bind;
+use crate::generation::{self, Generator};
+use crate::leak::{LeakLimits, LeakOracle};
+use crate::plan::{Action, Interaction};
+use crate::report::FailureKind;
+use crate::runner::{PlanRunner, RunError};
+use crate::tui::Dashboard;
+
+/// The first failure of a soak run, with the window it happened in.
+#[derive(Debug)]
+pub(crate) struct SoakFailure {
+    pub(crate) kind: FailureKind,
+    /// Last checkpoint that passed (0 when none did) and the wall-clock time it finished at.
+    pub(crate) last_checkpoint: u64,
+    pub(crate) last_checkpoint_at: Duration,
+    /// Wall-clock time of the failure.
+    pub(crate) failed_at: Duration,
+    /// Steps run before the failure.
+    pub(crate) step: usize,
+    pub(crate) reason: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct SoakSummary {
+    pub(crate) checkpoints: u64,
+    pub(crate) steps: usize,
+}
+
+/// Expected `sim_gen` contents, derived from the generated interactions that ran.
+#[derive(Debug, Default)]
+struct DataModel {
+    committed: u64,
+    /// Per task: inserts made in the open transaction, `None` outside one.
+    pending: Vec<Option<u64>>,
+}
+
+impl DataModel {
+    fn observe(&mut self, interaction: &Interaction) {
+        if self.pending.len() <= interaction.task {
+            self.pending.resize(interaction.task + 1, None);
+        }
+        let pending = &mut self.pending[interaction.task];
+        match &interaction.action {
+            Action::Begin => *pending = Some(0),
+            Action::Commit => self.committed += pending.take().unwrap_or(0),
+            Action::Rollback | Action::Return => *pending = None,
+            Action::Execute {
+                sql,
+                expect_error: None,
+                ..
+            } if sql.starts_with("INSERT INTO sim_gen ") => match pending {
+                Some(count) => *count += 1,
+                None => self.committed += 1,
+            },
+            Action::Checkout
+            | Action::RequestCheckout
+            | Action::Execute { .. }
+            | Action::Query { .. }
+            | Action::Sleep { .. } => {}
+        }
+    }
+}
+
+struct Soak<'a> {
+    runner: PlanRunner,
+    dashboard: Option<&'a Dashboard>,
+    model: DataModel,
+    leak: LeakOracle,
+    started: Instant,
+    last_checkpoint: u64,
+    last_checkpoint_at: Duration,
+}
+
+/// Run generated interactions until a checkpoint fails or `max_checkpoints` pass.
+///
+/// Every `checkpoint_every` of wall-clock time, generation pauses and each task commits its open
+/// transaction and returns its connection. The checkpoint then checks that the pool is fully idle
+/// and that `sim_gen` holds exactly the rows the committed inserts predict, logs a report, and
+/// resumes generation. All work runs against one in-memory database for the whole soak.
+///
+/// The interaction stream is determined by the seed; where checkpoints fall in it depends on
+/// wall-clock speed, so reports include the step count.
+pub(crate) async fn run_soak(
+    config: &SimConfig,
+    dashboard: Option<&Dashboard>,
+) -> Result<SoakSummary, Box<SoakFailure>> {
+    let started = Instant::now();
+    let backend = config.sqlite_backend();
+    let runner = PlanRunner::sqlite(backend, config.run_id)
+        .await
+        .map_err(|err| failure_at(started, 0, Duration::ZERO, &err))?;
+    let mut soak = Soak {
+        runner,
+        dashboard,
+        model: DataModel::default(),
+        leak: LeakOracle::new(
+            LeakLimits {
+                rss_kib_per_min: config.max_rss_slope_kib_per_min,
+                fds_per_min: config.max_fd_slope_per_min,
+            },
+            config.leak_sample_every,
+        ),
+        started,
+        last_checkpoint: 0,
+        last_checkpoint_at: Duration::ZERO,
+    };
+
+    let prefix = generation::plan_prefix(config)
+        .map_err(|reason| soak.failure(FailureKind::Usage, reason))?;
+    let mut generator = Generator::new(config, &prefix);
+    for interaction in &prefix {
+        soak.run(interaction).await?;
+    }
+
+    loop {
+        let deadline = Instant::now() + config.checkpoint_every;
+        while Instant::now() < deadline {
+            let interaction = generator
+                .next_interaction()
+                .map_err(|reason| soak.failure(FailureKind::Usage, reason))?;
+            soak.run(&interaction).await?;
+            soak.leak.poll(started.elapsed());
+        }
+        for interaction in generator.drain() {
+            soak.run(&interaction).await?;
+        }
+
+        soak.checkpoint(config.pool_size).await?;
+        if config
+            .max_checkpoints
+            .is_some_and(|max| soak.last_checkpoint >= max)
+        {
+            return Ok(SoakSummary {
+                checkpoints: soak.last_checkpoint,
+                steps: soak.runner.summary().steps,
+            });
+        }
+    }
+}
+
+impl Soak<'_> {
+    async fn run(&mut self, interaction: &Interaction) -> Result<(), Box<SoakFailure>> {
+        let result = self.runner.step(interaction).await;
+        if let Some(dashboard) = self.dashboard {
+            dashboard.update(&self.runner);
+        }
+        result.map_err(|err| {
+            failure_at(
+                self.started,
+                self.last_checkpoint,
+                self.last_checkpoint_at,
+                &err,
+            )
+        })?;
+        self.model.observe(interaction);
+        Ok(())
+    }
+
+    async fn checkpoint(&mut self, pool_size: usize) -> Result<(), Box<SoakFailure>> {
+        let rows = self.verify(pool_size).await.map_err(|reason| {
+            self.failure(
+                FailureKind::Oracle,
+                format!("checkpoint invariant failed: {reason}"),
+            )
+        })?;
+        let elapsed = self.started.elapsed();
+        if self.last_checkpoint == 0 {
+            // Everything up to the first checkpoint is warm-up for the leak oracle.
+            self.leak.start_window(elapsed);
+        } else {
+            self.leak.poll(elapsed);
+            self.leak.check().map_err(|reason| {
+                self.failure(FailureKind::Oracle, format!("leak oracle failed: {reason}"))
+            })?;
+        }
+        self.last_checkpoint += 1;
+        self.last_checkpoint_at = elapsed;
+        let summary = self.runner.summary();
+        let trend = self
+            .leak
+            .slopes()
+            .map(|(rss, fds)| format!(" rss_kib_per_min={rss:.1} fds_per_min={fds:.2}"))
+            .unwrap_or_default();
+        tracing::info!(
+            "soak_checkpoint={} elapsed_s={} steps={} sim_time_ms={} rows={}{} status=ok",
+            self.last_checkpoint,
+            self.last_checkpoint_at.as_secs(),
+            summary.steps,
+            summary.sim_time.as_millis(),
+            rows,
+            trend
+        );
+        if let Some(dashboard) = self.dashboard {
+            dashboard.flush(&self.runner);
+            dashboard.note(format!(
+                "soak checkpoint {} passed at {}s ({rows} rows)",
+                self.last_checkpoint,
+                self.last_checkpoint_at.as_secs()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the quiesced state; returns the verified `sim_gen` row count.
+    async fn verify(&self, pool_size: usize) -> Result<u64, String> {
+        let held = self.runner.held_connections();
+        if held > 0 {
+            return Err(format!("{held} tasks still hold connections after drain"));
+        }
+        let (connections, idle) = self.runner.backend().pool_state();
+        if idle != connections || connections as usize != pool_size.max(1) {
+            return Err(format!(
+                "pool not idle after drain: {idle} idle of {connections} connections (pool size {pool_size})"
+            ));
+        }
+
+        let backend = self.runner.backend();
+        let mut conn = backend
+            .checkout()
+            .await
+            .map_err(|err| format!("verification checkout failed: {err}"))?;
+        let result = backend
+            .query(
+                &mut conn,
+                "SELECT COUNT(*), COUNT(DISTINCT id) FROM sim_gen;",
+                Bind::default(),
+                false,
+            )
+            .await
+            .map_err(|err| format!("verification query failed: {err}"))?;
+        let count = |index| {
+            result
+                .results
+                .first()
+                .and_then(|row| row.get_by_index(index))
+                .and_then(|value| value.as_int())
+                .copied()
+                .ok_or_else(|| "verification query returned no count".to_string())
+        };
+        let (rows, distinct) = (count(0)?, count(1)?);
+        let expected = self.model.committed;
+        if u64::try_from(rows).ok() != Some(expected) {
+            return Err(format!(
+                "sim_gen has {rows} rows, committed inserts predict {expected}"
+            ));
+        }
+        if distinct != rows {
+            return Err(format!(
+                "sim_gen has {rows} rows but only {distinct} distinct ids"
+            ));
+        }
+        Ok(expected)
+    }
+
+    fn failure(&self, kind: FailureKind, reason: String) -> Box<SoakFailure> {
+        Box::new(SoakFailure {
+            kind,
+            last_checkpoint: self.last_checkpoint,
+            last_checkpoint_at: self.last_checkpoint_at,
+            failed_at: self.started.elapsed(),
+            step: self.runner.summary().steps,
+            reason,
+        })
+    }
+}
+
+fn failure_at(
+    started: Instant,
+    last_checkpoint: u64,
+    last_checkpoint_at: Duration,
+    err: &RunError,
+) -> Box<SoakFailure> {
+    Box::new(SoakFailure {
+        kind: err.kind,
+        last_checkpoint,
+        last_checkpoint_at,
+        failed_at: started.elapsed(),
+        step: err.step,
+        reason: format!("task {}: {}", err.task, err.reason),
+    })
+}
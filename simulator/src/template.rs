@@ -0,0 +1,90 @@
+use crate::plan::Action;
+
+/// Run-time values for `{{...}}` variables in plan SQL.
+///
+/// - `{{table}}` / `{{table:name}}`: `sim_<run_id>` / `name_<run_id>`, a table private to this run.
+/// - `{{task_id}}`: the id of the task running the action.
+/// - `{{unique_id}}`: a fresh integer on every use, unique across runs with distinct run ids.
+///
+/// Plans are stored and dumped unresolved, so the same plan can be replayed with another
+/// `--run-id` against the same database without colliding with earlier runs.
+#[derive(Debug, Clone)]
+pub(crate) struct TemplateContext {
+    run_id: u32,
+    next_unique: u64,
+}
+
+impl TemplateContext {
+    const UNIQUE_IDS_PER_RUN: u64 = 1_000_000;
+
+    pub(crate) fn new(run_id: u32) -> Self {
+        Self {
+            run_id,
+            next_unique: 0,
+        }
+    }
+
+    /// Resolve the variables in an action's SQL; other actions are returned unchanged.
+    pub(crate) fn resolve_action(
+        &mut self,
+        action: &Action,
+        task: usize,
+    ) -> Result<Action, String> {
+        let mut resolved = action.clone();
+        match &mut resolved {
+            Action::Execute { sql, .. } | Action::Query { sql, .. } => {
+                *sql = self.resolve(sql, task)?;
+            }
+            Action::Checkout
+            | Action::RequestCheckout
+            | Action::Return
+            | Action::Begin
+            | Action::Commit
+            | Action::Rollback
+            | Action::Sleep { .. } => {}
+        }
+        Ok(resolved)
+    }
+
+    fn resolve(&mut self, sql: &str, task: usize) -> Result<String, String> {
+        let mut out = String::with_capacity(sql.len());
+        let mut rest = sql;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| format!("unterminated template variable in {sql:?}"))?;
+            out.push_str(&self.value(after[..end].trim(), task)?);
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    fn value(&mut self, name: &str, task: usize) -> Result<String, String> {
+        match name.split_once(':') {
+            None if name == "table" => Ok(format!("sim_{}", self.run_id)),
+            Some(("table", base)) if is_identifier(base) => Ok(format!("{base}_{}", self.run_id)),
+            None if name == "task_id" => Ok(task.to_string()),
+            None if name == "unique_id" => {
+                self.next_unique += 1;
+                if self.next_unique >= Self::UNIQUE_IDS_PER_RUN {
+                    return Err("template {{unique_id}} exhausted for this run".to_string());
+                }
+                Ok(
+                    (u64::from(self.run_id) * Self::UNIQUE_IDS_PER_RUN + self.next_unique)
+                        .to_string(),
+                )
+            }
+            _ => Err(format!("unknown template variable {{{{{name}}}}}")),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
@@ -0,0 +1,254 @@
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::events::{EventLog, EventOutcome};
+use crate::runner::PlanRunner;
+
+const REDRAW_EVERY: Duration = Duration::from_millis(250);
+/// How often the runner copies its state for the dashboard; keeps per-step overhead negligible.
+const SNAPSHOT_EVERY: Duration = Duration::from_millis(50);
+/// Time without a completed step after which the dashboard flags the run as stalled.
+const STALL_AFTER: Duration = Duration::from_secs(2);
+const TASKS_PER_ROW: usize = 8;
+const DETAIL_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    events: EventLog,
+    tasks: Vec<(bool, bool)>,
+    /// `(connections, idle_connections)`.
+    pool: (u32, u32),
+    sim_time: Duration,
+    last_step_at: Option<Instant>,
+    note: Option<String>,
+    status: Option<String>,
+}
+
+struct Shared {
+    snapshot: Mutex<Snapshot>,
+    done: AtomicBool,
+}
+
+impl Shared {
+    fn snapshot(&self) -> std::sync::MutexGuard<'_, Snapshot> {
+        self.snapshot.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Live terminal dashboard for `--tui`.
+///
+/// The runner pushes a snapshot of its [`EventLog`], task states, and pool occupancy at most every
+/// 50ms; a separate thread redraws the screen (on stderr) four times a second from the latest
+/// snapshot. Because drawing does not depend on the runner making progress, a run stuck inside a
+/// step keeps the clock ticking and is flagged as stalled after two seconds.
+pub(crate) struct Dashboard {
+    shared: Arc<Shared>,
+    last_snapshot: Cell<Option<Instant>>,
+    renderer: Option<JoinHandle<()>>,
+}
+
+impl Dashboard {
+    pub(crate) fn start() -> Self {
+        let shared = Arc::new(Shared {
+            snapshot: Mutex::new(Snapshot::default()),
+            done: AtomicBool::new(false),
+        });
+        let started = Instant::now();
+        let renderer = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || {
+                let mut rate = Rate::new(started);
+                let _ = write!(io::stderr(), "\x1b[?25l");
+                while !shared.done.load(Ordering::Acquire) {
+                    let frame = render(&shared.snapshot(), started, &mut rate);
+                    let _ = write!(io::stderr(), "{frame}");
+                    std::thread::sleep(REDRAW_EVERY);
+                }
+                let frame = render(&shared.snapshot(), started, &mut rate);
+                let _ = write!(io::stderr(), "{frame}\x1b[?25h");
+            })
+        };
+        Self {
+            shared,
+            last_snapshot: Cell::new(None),
+            renderer: Some(renderer),
+        }
+    }
+
+    /// Copy the runner's state for the next redraw (throttled, except after a failed step).
+    pub(crate) fn update(&self, runner: &PlanRunner) {
+        let now = Instant::now();
+        let due = self
+            .last_snapshot
+            .get()
+            .is_none_or(|last| now.duration_since(last) >= SNAPSHOT_EVERY);
+        if due || runner.events().failures > 0 {
+            self.flush(runner);
+        }
+    }
+
+    /// Copy the runner's state now, e.g. after its last step.
+    pub(crate) fn flush(&self, runner: &PlanRunner) {
+        let now = Instant::now();
+        self.last_snapshot.set(Some(now));
+        let mut snapshot = self.shared.snapshot();
+        snapshot.events.clone_from(runner.events());
+        snapshot.tasks.clear();
+        snapshot.tasks.extend(runner.task_states());
+        snapshot.pool = runner.backend().pool_state();
+        snapshot.sim_time = runner.summary().sim_time;
+        snapshot.last_step_at = Some(now);
+    }
+
+    /// Show a one-line message (e.g. the latest soak checkpoint) under the header.
+    pub(crate) fn note(&self, note: String) {
+        self.shared.snapshot().note = Some(note);
+    }
+
+    /// Draw the final frame with `status` and restore the terminal.
+    pub(crate) fn finish(mut self, status: String) {
+        self.shared.snapshot().status = Some(status);
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shared.done.store(true, Ordering::Release);
+        if let Some(renderer) = self.renderer.take() {
+            let _ = renderer.join();
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Steps per second between consecutive redraws.
+struct Rate {
+    at: Instant,
+    steps: usize,
+    per_sec: f64,
+}
+
+impl Rate {
+    fn new(at: Instant) -> Self {
+        Self {
+            at,
+            steps: 0,
+            per_sec: 0.0,
+        }
+    }
+
+    fn sample(&mut self, steps: usize) -> f64 {
+        let now = Instant::now();
+        let secs = now.duration_since(self.at).as_secs_f64();
+        if secs > 0.0 {
+            self.per_sec = steps.saturating_sub(self.steps) as f64 / secs;
+        }
+        self.at = now;
+        self.steps = steps;
+        self.per_sec
+    }
+}
+
+fn render(snapshot: &Snapshot, started: Instant, rate: &mut Rate) -> String {
+    let events = &snapshot.events;
+    let mut per_sec = rate.sample(events.steps);
+    if snapshot.status.is_some() {
+        // The final frame follows the previous one closely; report the whole-run average.
+        per_sec = events.steps as f64 / started.elapsed().as_secs_f64().max(f64::EPSILON);
+    }
+    let mut out = String::from("\x1b[H\x1b[2J");
+
+    let status = snapshot.status.as_deref().unwrap_or("running");
+    let _ = writeln!(out, "sql-middleware simulator  [{status}]");
+    let _ = writeln!(
+        out,
+        "elapsed {:.1}s   steps {}   throughput {per_sec:.0} steps/s   sim time {:.1}s",
+        started.elapsed().as_secs_f64(),
+        events.steps,
+        snapshot.sim_time.as_secs_f64()
+    );
+    let since = snapshot
+        .last_step_at
+        .map_or_else(|| started.elapsed(), |at| at.elapsed());
+    if snapshot.status.is_none() && since >= STALL_AFTER {
+        let _ = writeln!(
+            out,
+            "\x1b[31mSTALLED: no step completed for {:.1}s\x1b[0m",
+            since.as_secs_f64()
+        );
+    } else {
+        let _ = writeln!(out, "last step {:.1}s ago", since.as_secs_f64());
+    }
+    if let Some(note) = &snapshot.note {
+        let _ = writeln!(out, "{note}");
+    }
+
+    let (connections, idle) = snapshot.pool;
+    let in_use = connections.saturating_sub(idle) as usize;
+    let _ = writeln!(
+        out,
+        "\npool    [{}{}]  {in_use}/{connections} checked out",
+        "#".repeat(in_use),
+        ".".repeat(idle as usize)
+    );
+    let _ = writeln!(
+        out,
+        "errors  expected {}   failed {}",
+        events.expected_errors, events.failures
+    );
+
+    let _ = writeln!(
+        out,
+        "\ntasks   (T in transaction, C holding a connection, . idle)"
+    );
+    for (row, tasks) in snapshot.tasks.chunks(TASKS_PER_ROW).enumerate() {
+        out.push_str("       ");
+        for (offset, &(has_conn, in_tx)) in tasks.iter().enumerate() {
+            let state = match (has_conn, in_tx) {
+                (_, true) => 'T',
+                (true, false) => 'C',
+                (false, false) => '.',
+            };
+            let _ = write!(out, " {:>4} {state}", row * TASKS_PER_ROW + offset);
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(out, "\nrecent");
+    for event in &events.recent {
+        let outcome = match &event.outcome {
+            EventOutcome::Ok => "ok".to_string(),
+            EventOutcome::ExpectedError => "expected error".to_string(),
+            EventOutcome::Failed(reason) => format!("\x1b[31mFAILED: {reason}\x1b[0m"),
+        };
+        let _ = writeln!(
+            out,
+            "  #{:<8} task {:<4} {:<9} {:<width$}  {outcome}",
+            event.step,
+            event.task,
+            event.action,
+            truncate(&event.detail, DETAIL_WIDTH),
+            width = DETAIL_WIDTH
+        );
+    }
+    out
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    let flat = text.replace(['\n', '\r'], " ");
+    if flat.chars().count() <= width {
+        return flat;
+    }
+    let mut cut: String = flat.chars().take(width.saturating_sub(3)).collect();
+    cut.push_str("...");
+    cut
+}
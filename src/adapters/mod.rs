@@ -1,2 +1,4 @@
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
 pub(crate) mod params;
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mssql"))]
 pub(crate) mod result_set;
@@ -0,0 +1,513 @@
+//! Statement-level audit trail for DML.
+//!
+//! A pool configured with [`PoolOptions::audit`](crate::pool::PoolOptions::audit) audits its
+//! tables: every `INSERT`, `UPDATE` or `DELETE` run through
+//! [`QueryBuilder::dml`](crate::QueryBuilder::dml) against one of them also appends a row to
+//! [`AUDIT_TABLE`]: the table, the operation, the SQL as written, the rows affected, the actor
+//! from [`QueryOptions::actor`](crate::QueryOptions::actor) and the time in epoch milliseconds.
+//!
+//! ```rust,no_run
+//! use sql_middleware::audit::AuditTables;
+//! use sql_middleware::pool::PoolOptions;
+//! use sql_middleware::prelude::*;
+//!
+//! # async fn demo(cap: ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+//! let cap = cap.with_pool_options(PoolOptions::default().audit(AuditTables::new(["accounts"])));
+//! let mut conn = cap.get_connection().await?;
+//! sql_middleware::audit::create_table(&mut conn).await?;
+//!
+//! conn.query("UPDATE accounts SET balance = balance - 10 WHERE id = $1")
+//!     .params((7,))
+//!     .actor("alice")
+//!     .dml()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! The audit row is written in the same transaction as the statement:
+//! - On a pooled connection or a typed connection outside a transaction, the statement and the
+//!   audit row run together in a transaction opened for them, so the connection must not already
+//!   be inside a transaction opened with raw SQL, and [`prepare`](crate::QueryBuilder::prepare)
+//!   is ignored.
+//! - On a transaction handle, the audit row joins that transaction and is rolled back with it.
+//!
+//! Pooled connections and transaction handles begun on one (`sqlite::begin_transaction`,
+//! `begin_postgres_transaction` and its counterparts) use their pool's tables, and so do the
+//! helpers built on them: `execute_dml_atomic`, `bulk_insert` and group commit. Handles begun on
+//! a raw driver client and typed connections do not know their pool; give them the tables with
+//! [`QueryBuilder::audit`](crate::QueryBuilder::audit).
+//!
+//! The table is read from the start of the statement (`INSERT INTO t`, `UPDATE t`,
+//! `DELETE FROM t`), without a schema. Statements starting with `WITH`, batches run through
+//! `execute_batch`, and changes made by triggers are not audited; use [`cdc`](crate::cdc) for
+//! row-level capture.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+#[cfg(any_backend)]
+use std::time::SystemTime;
+
+#[cfg(any_backend)]
+use crate::clock::epoch_millis;
+#[cfg(any_backend)]
+use crate::error::SqlMiddlewareDbError;
+#[cfg(any_backend)]
+use crate::pool::MiddlewarePoolConnection;
+#[cfg(any_backend)]
+use crate::types::{DatabaseType, RowValues};
+
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
+use bb8::PooledConnection;
+
+#[cfg(feature = "postgres")]
+use crate::postgres;
+#[cfg(feature = "postgres")]
+use crate::postgres::typed::PgManager;
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+#[cfg(feature = "sqlite")]
+use crate::sqlite::config::SqliteManager;
+#[cfg(feature = "sqlite")]
+use crate::sqlite::connection::execute_cached;
+#[cfg(feature = "turso")]
+use crate::turso;
+#[cfg(feature = "turso")]
+use crate::turso::TursoManager;
+
+/// Table receiving audit rows (created by [`create_table`]).
+pub const AUDIT_TABLE: &str = "sql_middleware_audit";
+
+/// Tables whose DML is audited. Names match without regard to case and without a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditTables(Arc<BTreeSet<String>>);
+
+impl AuditTables {
+    #[must_use]
+    pub fn new<I, S>(tables: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(
+            tables
+                .into_iter()
+                .map(|table| table.into().to_ascii_lowercase())
+                .collect(),
+        ))
+    }
+
+    /// Whether DML on `table` is audited.
+    #[must_use]
+    pub fn contains(&self, table: &str) -> bool {
+        self.0.contains(&table.to_ascii_lowercase())
+    }
+}
+
+/// Kind of audited statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditOp {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl fmt::Display for AuditOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AuditOp::Insert => "INSERT",
+            AuditOp::Update => "UPDATE",
+            AuditOp::Delete => "DELETE",
+        })
+    }
+}
+
+/// Create [`AUDIT_TABLE`] if it does not exist.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError` if the table cannot be created.
+#[cfg(any_backend)]
+pub async fn create_table(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    let db_type = conn.database_type();
+    conn.execute_batch(&create_table_sql(&db_type)).await
+}
+
+#[cfg(any_backend)]
+fn create_table_sql(db_type: &DatabaseType) -> String {
+    let rest = "table_name VARCHAR(255) NOT NULL, op VARCHAR(6) NOT NULL";
+    let counts = "rows_affected BIGINT NOT NULL, actor VARCHAR(255) NULL, \
+                  recorded_at_ms BIGINT NOT NULL";
+    match db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => format!(
+            "CREATE TABLE IF NOT EXISTS {AUDIT_TABLE} \
+             (id BIGSERIAL PRIMARY KEY, {rest}, statement TEXT NOT NULL, {counts})"
+        ),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => format!(
+            "IF OBJECT_ID(N'{AUDIT_TABLE}', N'U') IS NULL CREATE TABLE {AUDIT_TABLE} \
+             (id BIGINT IDENTITY(1,1) PRIMARY KEY, {rest}, statement NVARCHAR(MAX) NOT NULL, \
+             {counts})"
+        ),
+        #[allow(unreachable_patterns)]
+        _ => format!(
+            "CREATE TABLE IF NOT EXISTS {AUDIT_TABLE} \
+             (id INTEGER PRIMARY KEY AUTOINCREMENT, {rest}, statement TEXT NOT NULL, {counts})"
+        ),
+    }
+}
+
+/// The audit row one statement will write, prepared before the statement runs.
+#[cfg(any_backend)]
+#[derive(Clone)]
+pub(crate) struct Entry {
+    sql: String,
+    table: Arc<str>,
+    op: AuditOp,
+    statement: Arc<str>,
+    actor: Option<Arc<str>>,
+}
+
+#[cfg(any_backend)]
+impl Entry {
+    /// The entry for `statement`, if it changes one of `tables`.
+    pub(crate) fn for_statement(
+        db_type: &DatabaseType,
+        statement: &str,
+        actor: Option<&Arc<str>>,
+        tables: Option<&AuditTables>,
+    ) -> Option<Entry> {
+        let tables = tables?;
+        let (op, table) = dml_target(statement)?;
+        if !tables.contains(&table) {
+            return None;
+        }
+        let p = |idx| db_type.placeholder(idx);
+        Some(Entry {
+            sql: format!(
+                "INSERT INTO {AUDIT_TABLE} \
+                 (table_name, op, statement, rows_affected, actor, recorded_at_ms) \
+                 VALUES ({}, {}, {}, {}, {}, {})",
+                p(1),
+                p(2),
+                p(3),
+                p(4),
+                p(5),
+                p(6)
+            ),
+            table: table.into(),
+            op,
+            statement: statement.into(),
+            actor: actor.cloned(),
+        })
+    }
+
+    pub(crate) fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Parameters for [`sql`](Self::sql) once the statement has affected `rows`.
+    pub(crate) fn params(&self, rows: usize) -> Result<Vec<RowValues>, SqlMiddlewareDbError> {
+        Ok(vec![
+            RowValues::Text(self.table.clone()),
+            RowValues::Text(self.op.to_string().into()),
+            RowValues::Text(self.statement.clone()),
+            RowValues::Int(i64::try_from(rows).unwrap_or(i64::MAX)),
+            self.actor.clone().map_or(RowValues::Null, RowValues::Text),
+            RowValues::Int(epoch_millis(SystemTime::now())?),
+        ])
+    }
+}
+
+/// Run `query` and its audit row together in one transaction on `conn`.
+#[cfg(any_backend)]
+pub(crate) async fn dml_on_connection(
+    conn: &mut MiddlewarePoolConnection,
+    query: &str,
+    params: &[RowValues],
+    entry: &Entry,
+) -> Result<usize, SqlMiddlewareDbError> {
+    match conn {
+        #[cfg(feature = "postgres")]
+        MiddlewarePoolConnection::Postgres { .. } => {
+            let tx = conn.begin_postgres_transaction().await?.untracked();
+            dml_in_postgres_tx(tx, query, params, entry).await
+        }
+        #[cfg(feature = "sqlite")]
+        MiddlewarePoolConnection::Sqlite { .. } => {
+            let mut tx = sqlite::begin_transaction(conn).await?;
+            let result = async {
+                let rows = tx.execute_prepared(&tx.prepare(query)?, params).await?;
+                let audit = tx.prepare(entry.sql())?;
+                tx.execute_prepared(&audit, &entry.params(rows)?).await?;
+                Ok(rows)
+            }
+            .await;
+            match result {
+                Ok(rows) => {
+                    tx.commit().await?;
+                    Ok(rows)
+                }
+                Err(err) => {
+                    tx.rollback().await?;
+                    Err(err)
+                }
+            }
+        }
+        #[cfg(feature = "mssql")]
+        MiddlewarePoolConnection::Mssql { .. } => {
+            let mut tx = conn.begin_mssql_transaction().await?;
+            let result = async {
+                let rows = tx.execute_dml(query, params).await?;
+                tx.execute_dml(entry.sql(), &entry.params(rows)?).await?;
+                Ok(rows)
+            }
+            .await;
+            match result {
+                Ok(rows) => {
+                    tx.commit().await?;
+                    Ok(rows)
+                }
+                Err(err) => {
+                    tx.rollback().await?;
+                    Err(err)
+                }
+            }
+        }
+        #[cfg(feature = "turso")]
+        MiddlewarePoolConnection::Turso { .. } => {
+            let tx = conn
+                .begin_turso_transaction(turso::TxMode::Interactive)
+                .await?;
+            dml_in_turso_tx(tx, query, params, entry).await
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(SqlMiddlewareDbError::Unimplemented(
+            "This database type is not enabled in the current build".to_string(),
+        )),
+    }
+}
+
+/// Run `query` and its audit row together in one transaction on a Postgres connection.
+#[cfg(feature = "postgres")]
+pub(crate) async fn dml_on_postgres(
+    client: &mut PooledConnection<'static, PgManager>,
+    query: &str,
+    params: &[RowValues],
+    entry: &Entry,
+) -> Result<usize, SqlMiddlewareDbError> {
+    // Dropping the tokio-postgres transaction on error rolls it back.
+    let tx = postgres::begin_transaction(client).await?.untracked();
+    dml_in_postgres_tx(tx, query, params, entry).await
+}
+
+/// Run `query` and its audit row in `tx`, then commit it.
+#[cfg(feature = "postgres")]
+async fn dml_in_postgres_tx(
+    tx: postgres::Tx<'_>,
+    query: &str,
+    params: &[RowValues],
+    entry: &Entry,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let rows = tx.execute_dml(query, params).await?;
+    tx.execute_dml(entry.sql(), &entry.params(rows)?).await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// Run `query` and its audit row together in one transaction on a Turso connection.
+#[cfg(feature = "turso")]
+pub(crate) async fn dml_on_turso(
+    conn: &mut PooledConnection<'static, TursoManager>,
+    query: &str,
+    params: &[RowValues],
+    entry: &Entry,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let tx = turso::begin_transaction(conn).await?;
+    dml_in_turso_tx(tx, query, params, entry).await
+}
+
+/// Run `query` and its audit row in `tx`, then commit it, or roll back on error.
+#[cfg(feature = "turso")]
+async fn dml_in_turso_tx(
+    tx: turso::Tx<'_>,
+    query: &str,
+    params: &[RowValues],
+    entry: &Entry,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let result = async {
+        let rows = tx.execute_dml(query, params).await?;
+        tx.execute_dml(entry.sql(), &entry.params(rows)?).await?;
+        Ok(rows)
+    }
+    .await;
+    match result {
+        Ok(rows) => {
+            tx.commit().await?;
+            Ok(rows)
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            Err(err)
+        }
+    }
+}
+
+/// Run `query` and its audit row together on a typed `SQLite` connection, in a transaction
+/// unless one is already open.
+#[cfg(feature = "sqlite")]
+pub(crate) async fn dml_on_typed_sqlite(
+    conn: &mut PooledConnection<'static, SqliteManager>,
+    query: &str,
+    params: &[RowValues],
+    entry: &Entry,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let query = query.to_owned();
+    let params = params.to_vec();
+    let entry = entry.clone();
+    sqlite::connection::run_blocking(Arc::clone(&*conn), move |guard| {
+        let run = |conn: &rusqlite::Connection| {
+            let rows = execute_cached(conn, &query, &params)?;
+            execute_cached(conn, entry.sql(), &entry.params(rows)?)?;
+            Ok(rows)
+        };
+        if guard.is_autocommit() {
+            let tx = guard
+                .transaction()
+                .map_err(SqlMiddlewareDbError::SqliteError)?;
+            let rows = run(&tx)?;
+            tx.commit().map_err(SqlMiddlewareDbError::SqliteError)?;
+            Ok(rows)
+        } else {
+            run(guard)
+        }
+    })
+    .await
+}
+
+/// The operation and unqualified table of a single `INSERT`, `UPDATE` or `DELETE`.
+#[cfg(any_backend)]
+fn dml_target(sql: &str) -> Option<(AuditOp, String)> {
+    let mut words = Words(sql);
+    let op = match words.next()?.to_ascii_uppercase().as_str() {
+        "INSERT" | "REPLACE" => AuditOp::Insert,
+        "UPDATE" => AuditOp::Update,
+        "DELETE" => AuditOp::Delete,
+        _ => return None,
+    };
+    let mut name = words.next()?;
+    // INSERT OR REPLACE INTO t, UPDATE OR IGNORE t, DELETE FROM t, UPDATE TOP (1) t, ...
+    loop {
+        match name.to_ascii_uppercase().as_str() {
+            "OR" => {
+                words.next()?;
+            }
+            "INTO" | "FROM" | "ONLY" => {}
+            "TOP" => {
+                words.next()?;
+            }
+            _ => break,
+        }
+        name = words.next()?;
+    }
+    let last = name.rsplit('.').next()?;
+    let table = last.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'));
+    (!table.is_empty()).then(|| (op, table.to_owned()))
+}
+
+/// Words of a statement, skipping comments; a quoted or bracketed name is one word.
+#[cfg(any_backend)]
+struct Words<'a>(&'a str);
+
+#[cfg(any_backend)]
+impl<'a> Iterator for Words<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            let rest = self.0.trim_start();
+            if let Some(comment) = rest.strip_prefix("--") {
+                self.0 = comment.split_once('\n').map_or("", |(_, after)| after);
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                self.0 = comment.split_once("*/").map_or("", |(_, after)| after);
+            } else {
+                self.0 = rest;
+                break;
+            }
+        }
+        let mut end = 0;
+        let mut quote = None;
+        for (idx, ch) in self.0.char_indices() {
+            match quote {
+                Some(close) if ch == close => quote = None,
+                Some(_) => {}
+                None if matches!(ch, '"' | '`') => quote = Some(ch),
+                None if ch == '[' => quote = Some(']'),
+                None if ch.is_whitespace() || matches!(ch, '(' | ';' | ',') => {
+                    // `(1)` after TOP is its own word.
+                    if idx == 0 && ch == '(' {
+                        end = self.0[1..]
+                            .find(')')
+                            .map_or(self.0.len(), |close| close + 2);
+                    }
+                    break;
+                }
+                None => {}
+            }
+            end = idx + ch.len_utf8();
+        }
+        if end == 0 {
+            return None;
+        }
+        let (word, rest) = self.0.split_at(end);
+        self.0 = rest;
+        Some(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_changed_table() {
+        let cases = [
+            (
+                "INSERT INTO accounts (id) VALUES (1)",
+                AuditOp::Insert,
+                "accounts",
+            ),
+            (
+                "insert or replace into \"Accounts\" values (1)",
+                AuditOp::Insert,
+                "Accounts",
+            ),
+            (
+                "-- note\nUPDATE public.accounts SET x = 1",
+                AuditOp::Update,
+                "accounts",
+            ),
+            (
+                "UPDATE TOP (5) [dbo].[accounts] SET x = 1",
+                AuditOp::Update,
+                "accounts",
+            ),
+            (
+                "/* c */ DELETE FROM accounts WHERE id = 1",
+                AuditOp::Delete,
+                "accounts",
+            ),
+            ("DELETE accounts WHERE id = 1", AuditOp::Delete, "accounts"),
+            (
+                "INSERT INTO accounts(id) VALUES (1)",
+                AuditOp::Insert,
+                "accounts",
+            ),
+        ];
+        for (sql, op, table) in cases {
+            assert_eq!(dml_target(sql), Some((op, table.to_owned())), "{sql}");
+        }
+        assert_eq!(dml_target("SELECT 1"), None);
+        assert_eq!(dml_target("WITH x AS (SELECT 1) DELETE FROM t"), None);
+    }
+}
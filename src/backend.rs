@@ -0,0 +1,107 @@
+//! The contract every backend module implements.
+//!
+//! Each backend module (`postgres`, `sqlite`, `mssql`, `turso`) exposes the same set of free
+//! functions and types: `execute_batch`, `execute_dml`, `execute_select`, `Params`,
+//! `build_result_set`, `begin_transaction`, `Tx` and `Prepared`. [`BackendApi`] captures that
+//! contract so generic code can be written once, and so a backend that drifts from it stops
+//! compiling. Each module provides a marker type implementing it, e.g.
+//! `sqlite::SqliteBackend`.
+//!
+//! `build_result_set` is not a trait method: its input is the driver's own row or statement type,
+//! which has no common shape across backends.
+//!
+//! ```rust
+//! # #[cfg(feature = "sqlite")]
+//! # {
+//! use sql_middleware::backend::BackendApi;
+//! use sql_middleware::prelude::*;
+//!
+//! async fn count_rows<B: BackendApi>(
+//!     conn: &mut B::Connection,
+//!     table: &str,
+//! ) -> Result<usize, SqlMiddlewareDbError> {
+//!     let rs = B::execute_select(conn, &format!("SELECT 1 FROM {table}"), &[]).await?;
+//!     Ok(rs.results.len())
+//! }
+//! # let _ = count_rows::<sql_middleware::sqlite::SqliteBackend>;
+//! # }
+//! ```
+
+use std::future::Future;
+
+use crate::middleware::{
+    DatabaseType, ParamConverter, ResultSet, RowValues, SqlMiddlewareDbError, TxOutcome,
+};
+
+/// Operations and types every backend module provides.
+///
+/// Methods delegate to the module's free functions and `Tx` methods of the same name, so the
+/// trait adds no behavior of its own.
+pub trait BackendApi {
+    /// The backend this implementation drives.
+    const DATABASE_TYPE: DatabaseType;
+
+    /// Connection handle the module's `execute_*` functions take.
+    type Connection;
+    /// Handle [`begin_transaction`](BackendApi::begin_transaction) borrows. This is
+    /// `Connection` except on `SQLite`, whose transactions take the whole pooled connection so
+    /// they can hand it back on commit or rollback.
+    type TxConnection;
+    /// The module's parameter conversion type.
+    type Params<'a>: ParamConverter<'a>;
+    /// The module's explicit transaction handle.
+    type Tx<'c>;
+    /// The module's transaction-scoped prepared statement.
+    type Prepared;
+
+    /// Run one or more statements without parameters.
+    fn execute_batch(
+        conn: &mut Self::Connection,
+        sql: &str,
+    ) -> impl Future<Output = Result<(), SqlMiddlewareDbError>>;
+
+    /// Run a query and collect its rows.
+    fn execute_select(
+        conn: &mut Self::Connection,
+        sql: &str,
+        params: &[RowValues],
+    ) -> impl Future<Output = Result<ResultSet, SqlMiddlewareDbError>>;
+
+    /// Run a statement and return the number of affected rows.
+    fn execute_dml(
+        conn: &mut Self::Connection,
+        sql: &str,
+        params: &[RowValues],
+    ) -> impl Future<Output = Result<usize, SqlMiddlewareDbError>>;
+
+    /// Start a transaction on `conn`.
+    fn begin_transaction(
+        conn: &mut Self::TxConnection,
+    ) -> impl Future<Output = Result<Self::Tx<'_>, SqlMiddlewareDbError>>;
+
+    /// Prepare `sql` for repeated execution inside `tx`.
+    fn prepare(
+        tx: &mut Self::Tx<'_>,
+        sql: &str,
+    ) -> impl Future<Output = Result<Self::Prepared, SqlMiddlewareDbError>>;
+
+    /// Execute a prepared statement inside `tx` and return the number of affected rows.
+    fn execute_prepared(
+        tx: &mut Self::Tx<'_>,
+        prepared: &mut Self::Prepared,
+        params: &[RowValues],
+    ) -> impl Future<Output = Result<usize, SqlMiddlewareDbError>>;
+
+    /// Run a prepared query inside `tx` and collect its rows.
+    fn query_prepared(
+        tx: &mut Self::Tx<'_>,
+        prepared: &mut Self::Prepared,
+        params: &[RowValues],
+    ) -> impl Future<Output = Result<ResultSet, SqlMiddlewareDbError>>;
+
+    /// Commit `tx`.
+    fn commit(tx: Self::Tx<'_>) -> impl Future<Output = Result<TxOutcome, SqlMiddlewareDbError>>;
+
+    /// Roll back `tx`.
+    fn rollback(tx: Self::Tx<'_>) -> impl Future<Output = Result<TxOutcome, SqlMiddlewareDbError>>;
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use crate::SqlMiddlewareDbError;
+use crate::middleware::{ConfigAndPool, MiddlewarePoolConnection};
+
+/// Rows per multi-row `INSERT` when seeding.
+const INSERT_CHUNK: usize = 500;
+
+/// Table layouts the lookup-style benchmarks seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatasetSchema {
+    /// `test (id, name, score, active)`: row `id` holds `name-{id}`, `id * 0.5`, and `id` even.
+    /// Used by the single-row lookup and pool checkout benches.
+    Lookup,
+    /// `bench_throughput (id, name, score)`: row `id` holds `name-{id}` and `(id % 100).5`.
+    /// Used by the concurrent throughput bench.
+    Throughput,
+}
+
+impl DatasetSchema {
+    /// Name of the table this schema creates.
+    #[must_use]
+    pub fn table(self) -> &'static str {
+        match self {
+            DatasetSchema::Lookup => "test",
+            DatasetSchema::Throughput => "bench_throughput",
+        }
+    }
+
+    /// `SELECT` of every column by primary key, with a `?1` placeholder.
+    #[must_use]
+    pub fn select_by_id(self) -> &'static str {
+        match self {
+            DatasetSchema::Lookup => "SELECT id, name, score, active FROM test WHERE id = ?1",
+            DatasetSchema::Throughput => {
+                "SELECT id, name, score FROM bench_throughput WHERE id = ?1"
+            }
+        }
+    }
+
+    fn create_sql(self) -> &'static str {
+        match self {
+            DatasetSchema::Lookup => {
+                "CREATE TABLE test (
+                    id      INTEGER PRIMARY KEY,
+                    name    TEXT NOT NULL,
+                    score   REAL NOT NULL,
+                    active  INTEGER NOT NULL
+                );"
+            }
+            DatasetSchema::Throughput => {
+                "CREATE TABLE bench_throughput (
+                    id      BIGINT PRIMARY KEY,
+                    name    TEXT NOT NULL,
+                    score   DOUBLE PRECISION NOT NULL
+                );"
+            }
+        }
+    }
+
+    fn insert_prefix(self) -> &'static str {
+        match self {
+            DatasetSchema::Lookup => "INSERT INTO test (id, name, score, active) VALUES ",
+            DatasetSchema::Throughput => "INSERT INTO bench_throughput (id, name, score) VALUES ",
+        }
+    }
+
+    fn write_row(self, sql: &mut String, id: usize) {
+        let written = match self {
+            DatasetSchema::Lookup => write!(
+                sql,
+                "({id}, 'name-{id}', {}.{}, {})",
+                id / 2,
+                if id.is_multiple_of(2) { 0 } else { 5 },
+                u8::from(id.is_multiple_of(2))
+            ),
+            DatasetSchema::Throughput => write!(sql, "({id}, 'name-{id}', {}.5)", id % 100),
+        };
+        written.expect("writing to string");
+    }
+}
+
+/// Drop and recreate `schema`'s table, then fill it with rows `1..=rows`.
+///
+/// Everything goes through one `execute_batch` of multi-row `INSERT` literals ([`INSERT_CHUNK`]
+/// rows each), so seeding works the same on every backend and runs as a single transaction where
+/// the backend wraps batches in one (`SQLite`, Postgres).
+///
+/// # Errors
+/// Returns any error from the backend while recreating the table or inserting rows.
+pub async fn seed_rows(
+    conn: &mut MiddlewarePoolConnection,
+    rows: usize,
+    schema: DatasetSchema,
+) -> Result<(), SqlMiddlewareDbError> {
+    let mut sql = format!(
+        "DROP TABLE IF EXISTS {}; {}\n",
+        schema.table(),
+        schema.create_sql()
+    );
+    for start in (1..=rows).step_by(INSERT_CHUNK) {
+        let end = (start + INSERT_CHUNK - 1).min(rows);
+        sql.push_str(schema.insert_prefix());
+        for id in start..=end {
+            schema.write_row(&mut sql, id);
+            sql.push_str(if id == end { ";\n" } else { "," });
+        }
+    }
+    conn.execute_batch(&sql).await
+}
+
+/// Ids `1..=rows` in a deterministic order derived from `seed`.
+///
+/// Benches shuffle lookups so they don't walk the primary key sequentially; a fixed seed keeps
+/// the order identical between runs and between the variants being compared.
+///
+/// # Panics
+/// Panics if `rows` does not fit in an `i64`.
+#[must_use]
+pub fn shuffled_ids(rows: usize, seed: u64) -> Vec<i64> {
+    let rows = i64::try_from(rows).expect("row count fits in i64");
+    let mut ids: Vec<i64> = (1..=rows).collect();
+    ids.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    ids
+}
+
+/// Lookups per iteration: `BENCH_LOOKUPS`, then `BENCH_ROWS`, then `default`.
+#[must_use]
+pub fn lookup_count(default: usize) -> usize {
+    ["BENCH_LOOKUPS", "BENCH_ROWS"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok()?.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Remove a database file along with its `-wal` and `-shm` companions, ignoring missing files.
+pub fn remove_database_files(path: &Path) {
+    let owned = path.to_string_lossy().into_owned();
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{owned}-wal"));
+    let _ = fs::remove_file(format!("{owned}-shm"));
+}
+
+/// An on-disk dataset plus the shuffled ids benches look up.
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    path: String,
+    ids: Vec<i64>,
+}
+
+impl Dataset {
+    #[must_use]
+    pub fn new(path: &Path, ids: Vec<i64>) -> Self {
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            ids,
+        }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    #[must_use]
+    pub fn ids(&self) -> &[i64] {
+        &self.ids
+    }
+}
+
+/// Recreate the `SQLite` file at `path` in WAL mode and seed it with `rows` rows of `schema`.
+///
+/// # Errors
+/// Returns any error from building the pool, switching to WAL, or seeding.
+pub async fn prepare_sqlite_dataset(
+    path: &Path,
+    rows: usize,
+    schema: DatasetSchema,
+) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    remove_database_files(path);
+    let config_and_pool = ConfigAndPool::sqlite_builder(path.to_string_lossy().into_owned())
+        .build()
+        .await?;
+    let mut conn = config_and_pool.get_connection().await?;
+    // Journal mode can't change inside the transaction `execute_batch` opens.
+    conn.with_blocking_sqlite(|connection| {
+        connection
+            .execute_batch("PRAGMA journal_mode = WAL;")
+            .map_err(SqlMiddlewareDbError::SqliteError)
+    })
+    .await?;
+    seed_rows(&mut conn, rows, schema).await?;
+    Ok(config_and_pool)
+}
+
+/// Recreate the Turso database at `path` and seed it with `rows` rows of `schema`.
+///
+/// # Errors
+/// Returns any error from opening the database or seeding.
+#[cfg(feature = "turso")]
+pub async fn prepare_turso_dataset(
+    path: &Path,
+    rows: usize,
+    schema: DatasetSchema,
+) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    remove_database_files(path);
+    let config_and_pool = ConfigAndPool::turso_builder(path.to_string_lossy().into_owned())
+        .build()
+        .await?;
+    let mut conn = config_and_pool.get_connection().await?;
+    seed_rows(&mut conn, rows, schema).await?;
+    Ok(config_and_pool)
+}
@@ -1,4 +1,6 @@
 pub mod common;
+pub mod dataset;
 #[cfg(feature = "postgres")]
 pub mod postgres;
 pub mod sqlite;
+pub mod translation;
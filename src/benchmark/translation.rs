@@ -0,0 +1,24 @@
+use std::borrow::Cow;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::translation::{PlaceholderStyle, QueryOptions};
+use crate::types::RowValues;
+
+/// Run the query builder's translation step without a connection.
+///
+/// `style` stands in for the connection's placeholder style (`None` for backends that never
+/// translate) and `pool_default` for the pool's `translate_placeholders` setting. The early
+/// exits (no params, no style, translation resolved off) are the same code the builder runs.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ParameterError` when the statement mixes unnumbered `?` with
+/// numbered placeholders.
+pub fn translate_query_for_target<'a>(
+    style: Option<PlaceholderStyle>,
+    pool_default: bool,
+    query: &'a str,
+    params: &[RowValues],
+    options: &QueryOptions,
+) -> Result<Cow<'a, str>, SqlMiddlewareDbError> {
+    crate::query_builder::translate_query(style, pool_default, query, params, options)
+}
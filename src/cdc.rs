@@ -0,0 +1,360 @@
+//! Row-level change data capture with triggers.
+//!
+//! [`enable_for_table`] installs `AFTER INSERT/UPDATE/DELETE` triggers that append one row per
+//! changed row to [`CHANGE_TABLE`], holding the table name, the operation and the row as JSON (the
+//! new row for inserts and updates, the old row for deletes). [`poll_changes`] reads the log in
+//! commit order, so a consumer only needs to remember the [`ChangeCursor`] of the last change it
+//! processed.
+//!
+//! Log ids are handed out when a row is written, not when its transaction commits, so a reader
+//! that only remembered the last id would skip a change whose transaction committed after a
+//! later id was read. The cursor avoids that per backend:
+//! - Postgres: each change records its transaction id, and a poll returns only changes from
+//!   transactions older than the oldest one still running (`pg_snapshot_xmin`), ordered by
+//!   transaction id and then log id. A long-running transaction anywhere on the server delays
+//!   changes committed after it began until it ends; none are lost.
+//! - SQL Server: each change gets a `rowversion`, and a poll returns only versions below
+//!   `MIN_ACTIVE_ROWVERSION()`, which no open transaction can still write under.
+//! - `SQLite`/Turso: writers are serialized, so log ids already follow commit order.
+//!
+//! Trigger mechanics per backend:
+//! - Postgres: one shared PL/pgSQL function, `to_jsonb` of the row.
+//! - `SQLite`/Turso: one trigger per operation, `json_object` over the columns present when CDC
+//!   was enabled (call it again after adding columns). `BLOB` columns cannot be captured.
+//! - SQL Server: one statement-level trigger reading `inserted`/`deleted` with `FOR JSON`.
+//!
+//! This suits low-volume feeds (cache invalidation, audit, syncing a few tables); every write to a
+//! captured table also writes to the log in the same transaction.
+
+use std::fmt;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::ident::quote;
+use crate::pool::MiddlewarePoolConnection;
+use crate::translation::TranslationMode;
+use crate::types::{DatabaseType, RowValues};
+
+/// Table receiving captured changes (created by [`enable_for_table`]).
+pub const CHANGE_TABLE: &str = "sql_middleware_changes";
+
+/// Kind of change captured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeOp {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ChangeOp {
+    fn parse(op: &str) -> Option<Self> {
+        match op {
+            "INSERT" => Some(ChangeOp::Insert),
+            "UPDATE" => Some(ChangeOp::Update),
+            "DELETE" => Some(ChangeOp::Delete),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChangeOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeOp::Insert => "INSERT",
+            ChangeOp::Update => "UPDATE",
+            ChangeOp::Delete => "DELETE",
+        })
+    }
+}
+
+/// A position in the change log, in commit order; pass the last one seen to [`poll_changes`].
+///
+/// `ChangeCursor::default()` is the start of the log. Persist both fields to resume a feed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChangeCursor {
+    /// The writing transaction's id on Postgres, the row version on SQL Server, `0` on
+    /// `SQLite`/Turso.
+    pub commit: i64,
+    /// The change's id in the log.
+    pub id: i64,
+}
+
+/// One captured row change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeRecord {
+    /// The change's id in the log.
+    pub id: i64,
+    /// Where this change sits in commit order; pass the last one seen to [`poll_changes`].
+    pub cursor: ChangeCursor,
+    pub table: String,
+    pub op: ChangeOp,
+    /// The new row for inserts and updates, the old row for deletes.
+    pub row: serde_json::Value,
+}
+
+/// Install change-capture triggers on `table`, creating the change log if needed.
+///
+/// Safe to call again; existing triggers are replaced.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError` if the log table or triggers cannot be created, or
+/// `ExecutionError` if `table` has no columns (e.g., it does not exist on `SQLite`).
+pub async fn enable_for_table(
+    conn: &mut MiddlewarePoolConnection,
+    table: &str,
+) -> Result<(), SqlMiddlewareDbError> {
+    let db_type = conn.database_type();
+    conn.execute_batch(&create_log_sql(&db_type)).await?;
+    match db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => {
+            conn.execute_batch(&format!(
+                "CREATE OR REPLACE FUNCTION sql_middleware_cdc() RETURNS trigger AS $cdc$
+                 BEGIN
+                     INSERT INTO {CHANGE_TABLE} (table_name, op, row_data)
+                     VALUES (TG_TABLE_NAME, TG_OP,
+                             CASE WHEN TG_OP = 'DELETE' THEN to_jsonb(OLD) ELSE to_jsonb(NEW) END::text);
+                     RETURN NULL;
+                 END;
+                 $cdc$ LANGUAGE plpgsql;
+                 DROP TRIGGER IF EXISTS {trigger} ON {table};
+                 CREATE TRIGGER {trigger} AFTER INSERT OR UPDATE OR DELETE ON {table}
+                     FOR EACH ROW EXECUTE FUNCTION sql_middleware_cdc();",
+                trigger = quote(&trigger_name(table, "cdc"), &db_type)?,
+                table = quote(table, &db_type)?,
+            ))
+            .await
+        }
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => {
+            // CREATE TRIGGER must be the only statement in its batch.
+            let literal = sql_literal(table);
+            conn.execute_batch(&format!(
+                "CREATE OR ALTER TRIGGER {trigger} ON {table_ident} AFTER INSERT, UPDATE, DELETE AS
+                 BEGIN
+                     SET NOCOUNT ON;
+                     INSERT INTO {CHANGE_TABLE} (table_name, op, row_data)
+                     SELECT {literal},
+                            CASE WHEN EXISTS (SELECT 1 FROM deleted) THEN 'UPDATE' ELSE 'INSERT' END,
+                            (SELECT i.* FOR JSON PATH, WITHOUT_ARRAY_WRAPPER)
+                     FROM inserted i;
+                     INSERT INTO {CHANGE_TABLE} (table_name, op, row_data)
+                     SELECT {literal}, 'DELETE', (SELECT d.* FOR JSON PATH, WITHOUT_ARRAY_WRAPPER)
+                     FROM deleted d
+                     WHERE NOT EXISTS (SELECT 1 FROM inserted);
+                 END",
+                trigger = quote(&trigger_name(table, "cdc"), &db_type)?,
+                table_ident = quote(table, &db_type)?,
+            ))
+            .await
+        }
+        #[allow(unreachable_patterns)]
+        _ => {
+            let columns = sqlite_columns(conn, table).await?;
+            if columns.is_empty() {
+                return Err(SqlMiddlewareDbError::ExecutionError(format!(
+                    "cannot enable CDC on {table}: no columns found"
+                )));
+            }
+            let json = |alias: &str| -> Result<String, SqlMiddlewareDbError> {
+                let pairs = columns
+                    .iter()
+                    .map(|col| Ok(format!("{}, {alias}.{}", sql_literal(col), quote(col, &db_type)?)))
+                    .collect::<Result<Vec<_>, SqlMiddlewareDbError>>()?
+                    .join(", ");
+                Ok(format!("json_object({pairs})"))
+            };
+            let mut sql = String::new();
+            for (op, event, alias) in [
+                ("INSERT", "INSERT", "NEW"),
+                ("UPDATE", "UPDATE", "NEW"),
+                ("DELETE", "DELETE", "OLD"),
+            ] {
+                let trigger = quote(
+                    &trigger_name(table, &format!("cdc_{}", op.to_lowercase())),
+                    &db_type,
+                )?;
+                sql.push_str(&format!(
+                    "DROP TRIGGER IF EXISTS {trigger};
+                     CREATE TRIGGER {trigger} AFTER {event} ON {table_ident} BEGIN
+                         INSERT INTO {CHANGE_TABLE} (table_name, op, row_data)
+                         VALUES ({literal}, '{op}', {row});
+                     END;",
+                    table_ident = quote(table, &db_type)?,
+                    literal = sql_literal(table),
+                    row = json(alias)?,
+                ));
+            }
+            conn.execute_batch(&sql).await
+        }
+    }
+}
+
+/// Remove the triggers installed by [`enable_for_table`]. Logged changes are kept.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError` if dropping the triggers fails.
+pub async fn disable_for_table(
+    conn: &mut MiddlewarePoolConnection,
+    table: &str,
+) -> Result<(), SqlMiddlewareDbError> {
+    let db_type = conn.database_type();
+    let sql: String = match db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => format!(
+            "DROP TRIGGER IF EXISTS {} ON {}",
+            quote(&trigger_name(table, "cdc"), &db_type)?,
+            quote(table, &db_type)?
+        ),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => format!(
+            "DROP TRIGGER IF EXISTS {}",
+            quote(&trigger_name(table, "cdc"), &db_type)?
+        ),
+        #[allow(unreachable_patterns)]
+        _ => ["insert", "update", "delete"]
+            .iter()
+            .map(|op| {
+                Ok(format!(
+                    "DROP TRIGGER IF EXISTS {};",
+                    quote(&trigger_name(table, &format!("cdc_{op}")), &db_type)?
+                ))
+            })
+            .collect::<Result<_, SqlMiddlewareDbError>>()?,
+    };
+    conn.execute_batch(&sql).await
+}
+
+/// Read up to `limit` committed changes after `since`, in commit order.
+///
+/// Start with `ChangeCursor::default()` and pass the last returned [`ChangeRecord::cursor`] on the
+/// next call. A change appears only once no transaction that could still log an earlier one is
+/// open; see the [module docs](crate::cdc).
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError` if the query fails or a logged row is not valid JSON.
+pub async fn poll_changes(
+    conn: &mut MiddlewarePoolConnection,
+    since: ChangeCursor,
+    limit: usize,
+) -> Result<Vec<ChangeRecord>, SqlMiddlewareDbError> {
+    let db_type = conn.database_type();
+    let columns = "id, table_name, op, row_data";
+    let (sql, params) = match db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => (
+            format!(
+                "SELECT txid AS commit_order, {columns} FROM {CHANGE_TABLE} \
+                 WHERE (txid, id) > ($1, $2) \
+                   AND txid < pg_snapshot_xmin(pg_current_snapshot())::text::bigint \
+                 ORDER BY txid, id LIMIT {limit}"
+            ),
+            vec![RowValues::Int(since.commit), RowValues::Int(since.id)],
+        ),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => (
+            format!(
+                "SELECT TOP ({limit}) CAST(rv AS BIGINT) AS commit_order, {columns} \
+                 FROM {CHANGE_TABLE} \
+                 WHERE rv > CAST(@P1 AS BINARY(8)) AND rv < MIN_ACTIVE_ROWVERSION() ORDER BY rv"
+            ),
+            vec![RowValues::Int(since.commit)],
+        ),
+        #[allow(unreachable_patterns)]
+        _ => (
+            format!(
+                "SELECT 0 AS commit_order, {columns} FROM {CHANGE_TABLE} \
+                 WHERE id > ?1 ORDER BY id LIMIT {limit}"
+            ),
+            vec![RowValues::Int(since.id)],
+        ),
+    };
+    let rows = conn
+        .query(&sql)
+        .params(&params)
+        .translation(TranslationMode::ForceOff)
+        .select()
+        .await?;
+
+    rows.results
+        .iter()
+        .map(|row| {
+            let text = |name: &str| {
+                row.get(name)
+                    .and_then(RowValues::as_text)
+                    .unwrap_or_default()
+            };
+            let int = |name: &str| {
+                row.get(name)
+                    .and_then(RowValues::as_int)
+                    .copied()
+                    .unwrap_or_default()
+            };
+            let op = text("op");
+            Ok(ChangeRecord {
+                id: int("id"),
+                cursor: ChangeCursor {
+                    commit: int("commit_order"),
+                    id: int("id"),
+                },
+                table: text("table_name").to_string(),
+                op: ChangeOp::parse(op).ok_or_else(|| {
+                    SqlMiddlewareDbError::ExecutionError(format!("unknown change op {op:?}"))
+                })?,
+                row: serde_json::from_str(text("row_data")).map_err(|e| {
+                    SqlMiddlewareDbError::ExecutionError(format!("invalid change row JSON: {e}"))
+                })?,
+            })
+        })
+        .collect()
+}
+
+fn create_log_sql(db_type: &DatabaseType) -> String {
+    let rest = "table_name VARCHAR(255) NOT NULL, op VARCHAR(6) NOT NULL";
+    match db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => format!(
+            "CREATE TABLE IF NOT EXISTS {CHANGE_TABLE} \
+             (id BIGSERIAL PRIMARY KEY, \
+              txid BIGINT NOT NULL DEFAULT pg_current_xact_id()::text::bigint, \
+              {rest}, row_data TEXT NOT NULL)"
+        ),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => format!(
+            "IF OBJECT_ID(N'{CHANGE_TABLE}', N'U') IS NULL CREATE TABLE {CHANGE_TABLE} \
+             (id BIGINT IDENTITY(1,1) PRIMARY KEY, rv ROWVERSION NOT NULL, {rest}, \
+              row_data NVARCHAR(MAX) NOT NULL)"
+        ),
+        #[allow(unreachable_patterns)]
+        _ => format!(
+            "CREATE TABLE IF NOT EXISTS {CHANGE_TABLE} \
+             (id INTEGER PRIMARY KEY AUTOINCREMENT, {rest}, row_data TEXT NOT NULL)"
+        ),
+    }
+}
+
+async fn sqlite_columns(
+    conn: &mut MiddlewarePoolConnection,
+    table: &str,
+) -> Result<Vec<String>, SqlMiddlewareDbError> {
+    let rows = conn
+        .query(&format!(
+            "PRAGMA table_info({})",
+            quote(table, &conn.database_type())?
+        ))
+        .select()
+        .await?;
+    Ok(rows
+        .results
+        .iter()
+        .filter_map(|row| row.get("name").and_then(RowValues::as_text))
+        .map(str::to_string)
+        .collect())
+}
+
+fn trigger_name(table: &str, suffix: &str) -> String {
+    format!("{}_{suffix}", table.replace('.', "_"))
+}
+
+fn sql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
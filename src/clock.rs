@@ -0,0 +1,145 @@
+//! Time source used by the middleware for timeouts and retry delays.
+//!
+//! Production code uses [`SystemClock`] (tokio time, so `tokio::time::pause` and
+//! `#[tokio::test(start_paused = true)]` control it). Tests and the simulator can install
+//! [`MockClock`] or their own [`Clock`] on a
+//! [`ConfigAndPool`](crate::ConfigAndPool); connections checked out from that pool use it for
+//! checkout timeouts and busy-retry backoff instead of wall-clock sleeps.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
+#[cfg(any_backend)]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Boxed future returned by [`Clock::sleep`].
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Source of "now" and of delays.
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Current instant according to this clock.
+    fn now(&self) -> Instant;
+
+    /// Resolve once `duration` has elapsed on this clock.
+    fn sleep(&self, duration: Duration) -> Sleep;
+
+    /// Block the current thread for `duration` (used from `Drop` paths that cannot await).
+    fn sleep_blocking(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Clock backed by `tokio::time` (honours `tokio::time::pause` in tests).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Manually driven clock for tests: time only moves when [`MockClock::advance`] is called.
+///
+/// Pending [`Clock::sleep`] futures resolve once mock time reaches their deadline. Blocking sleeps
+/// cannot wait for another caller to advance time, so they advance the clock themselves.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    origin: Instant,
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    elapsed: Duration,
+    waiters: Vec<Waker>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            state: Arc::new(Mutex::new(MockState::default())),
+        }
+    }
+
+    /// Move mock time forward and wake sleepers whose deadline has passed.
+    pub fn advance(&self, duration: Duration) {
+        let waiters = {
+            let mut state = lock(&self.state);
+            state.elapsed += duration;
+            std::mem::take(&mut state.waiters)
+        };
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    /// Total mock time elapsed since creation.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        lock(&self.state).elapsed
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let state = Arc::clone(&self.state);
+        let deadline = self.elapsed() + duration;
+        Box::pin(std::future::poll_fn(move |cx| {
+            let mut state = lock(&state);
+            if state.elapsed >= deadline {
+                Poll::Ready(())
+            } else {
+                state.waiters.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }))
+    }
+
+    fn sleep_blocking(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+fn lock(state: &Mutex<MockState>) -> std::sync::MutexGuard<'_, MockState> {
+    match state.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Milliseconds since the Unix epoch, for wall-clock deadlines stored in the database.
+#[cfg(any_backend)]
+pub(crate) fn epoch_millis(at: SystemTime) -> Result<i64, crate::error::SqlMiddlewareDbError> {
+    at.duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|since| i64::try_from(since.as_millis()).ok())
+        .ok_or_else(|| {
+            crate::error::SqlMiddlewareDbError::ExecutionError(format!("time out of range: {at:?}"))
+        })
+}
+
+/// Shared default clock.
+#[must_use]
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
@@ -0,0 +1,113 @@
+//! `CockroachDB` support on top of the Postgres wire backend.
+//!
+//! Connect with [`ConfigAndPool::new_postgres`](crate::ConfigAndPool::new_postgres) as usual;
+//! everything that works against Postgres works against `CockroachDB`. What differs is contention:
+//! CRDB runs every transaction `SERIALIZABLE` and aborts losers with SQLSTATE `40001`
+//! (`RETRY_SERIALIZABLE` and friends), expecting the client to retry. [`run_transaction`] does
+//! that with the `SAVEPOINT cockroach_restart` protocol, which keeps the transaction's place in the
+//! cluster's priority queue across attempts instead of starting over.
+//!
+//! ```rust,no_run
+//! use sql_middleware::cockroach::run_transaction;
+//! use sql_middleware::prelude::*;
+//!
+//! # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+//! let mut conn = cap.get_connection().await?;
+//! let MiddlewarePoolConnection::Postgres { client, .. } = &mut conn else {
+//!     unreachable!()
+//! };
+//! let moved = run_transaction(client, 5, async |tx| {
+//!     tx.execute_dml(
+//!         "UPDATE accounts SET balance = balance - $1 WHERE id = $2",
+//!         &[RowValues::Int(100), RowValues::Int(1)],
+//!     )
+//!     .await?;
+//!     tx.execute_dml(
+//!         "UPDATE accounts SET balance = balance + $1 WHERE id = $2",
+//!         &[RowValues::Int(100), RowValues::Int(2)],
+//!     )
+//!     .await
+//! })
+//! .await?;
+//! # let _ = moved;
+//! # Ok(()) }
+//! ```
+
+use std::ops::DerefMut;
+
+use tokio_postgres::Client;
+use tokio_postgres::error::SqlState;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::postgres::{Tx, begin_transaction};
+
+/// Savepoint name `CockroachDB` recognizes as the client-side retry marker.
+pub const RESTART_SAVEPOINT: &str = "cockroach_restart";
+
+/// Whether `err` is a serialization failure (`40001`) that the transaction should be retried for.
+#[must_use]
+pub fn is_retry_error(err: &SqlMiddlewareDbError) -> bool {
+    match err {
+        SqlMiddlewareDbError::PostgresError(err) => {
+            err.code() == Some(&SqlState::T_R_SERIALIZATION_FAILURE)
+        }
+        _ => false,
+    }
+}
+
+/// Run `body` in a transaction, retrying it up to `max_retries` times on serialization failures.
+///
+/// The transaction opens `SAVEPOINT cockroach_restart` before the first attempt. When `body` or
+/// the savepoint release fails with `40001`, it rolls back to the savepoint and calls `body` again
+/// on the same transaction; any other error rolls the transaction back and is returned. `body`
+/// may run several times, so keep side effects outside the database idempotent.
+///
+/// The protocol is plain SQL, so this also works (and simply never retries much) against Postgres.
+///
+/// # Errors
+/// Returns the error from `body`, the last serialization failure once retries are exhausted, or
+/// the error from beginning, releasing, or committing the transaction.
+pub async fn run_transaction<C, T, F>(
+    conn: &mut C,
+    max_retries: u32,
+    mut body: F,
+) -> Result<T, SqlMiddlewareDbError>
+where
+    C: DerefMut<Target = Client>,
+    F: AsyncFnMut(&Tx<'_>) -> Result<T, SqlMiddlewareDbError>,
+{
+    let tx = begin_transaction(conn).await?.untracked();
+    tx.execute_batch(&format!("SAVEPOINT {RESTART_SAVEPOINT}"))
+        .await?;
+    let mut retries = 0;
+    loop {
+        let attempt = match body(&tx).await {
+            Ok(value) => tx
+                .execute_batch(&format!("RELEASE SAVEPOINT {RESTART_SAVEPOINT}"))
+                .await
+                .map(|()| value),
+            Err(err) => Err(err),
+        };
+        match attempt {
+            Ok(value) => {
+                tx.commit().await?;
+                return Ok(value);
+            }
+            Err(err) if is_retry_error(&err) && retries < max_retries => {
+                retries += 1;
+                tracing::debug!(
+                    target: "sql_middleware::cockroach",
+                    retries,
+                    "retrying transaction after serialization failure"
+                );
+                tx.execute_batch(&format!("ROLLBACK TO SAVEPOINT {RESTART_SAVEPOINT}"))
+                    .await?;
+            }
+            Err(err) => {
+                // The original error matters more than a failed rollback on a broken connection.
+                let _ = tx.rollback().await;
+                return Err(err);
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Case-insensitive text comparison across backends.
+//!
+//! The backends disagree on whether `'Ann' = 'ann'`: Postgres, `SQLite` and Turso compare text
+//! case-sensitively, while SQL Server usually does not, because most servers are installed with
+//! a case-insensitive default collation. Each also spells "ignore case" differently: a `citext`
+//! column or `lower()` on Postgres, `COLLATE NOCASE` on `SQLite`, `COLLATE Latin1_General_CI_AS`
+//! on SQL Server. [`DatabaseType::collation`] describes what a backend offers, and the helpers
+//! here write the matching SQL:
+//!
+//! ```rust
+//! use sql_middleware::prelude::*;
+//!
+//! # fn demo(db_type: &DatabaseType) -> Result<(), SqlMiddlewareDbError> {
+//! let ddl = db_type.case_insensitive_index("users_email_ci", "users", "email")?;
+//! let filter = QueryBuilder::case_insensitive_compare(db_type, "email", "$1");
+//! let sql = format!("SELECT id FROM users WHERE {filter}");
+//! # let _ = (ddl, sql);
+//! # Ok(()) }
+//! ```
+//!
+//! The comparison and the index are built to match, so the index serves the comparison.
+
+use crate::ident::quote;
+use crate::middleware::SqlMiddlewareDbError;
+#[cfg(any_backend)]
+use crate::query_builder::QueryBuilder;
+use crate::types::DatabaseType;
+
+/// SQL Server collation used for case-insensitive comparisons.
+#[cfg(feature = "mssql")]
+const MSSQL_CI: &str = "Latin1_General_CI_AS";
+
+/// How a backend compares text, from [`DatabaseType::collation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collation {
+    /// Whether `=` on text is case-sensitive under the backend's usual defaults. SQL Server's
+    /// answer depends on the server and database collation; `false` is the common setup.
+    pub case_sensitive_by_default: bool,
+    /// Collation that compares case-insensitively in a `COLLATE` clause, when one is always
+    /// available. Postgres needs a nondeterministic ICU collation created first, so it has none.
+    pub case_insensitive_collation: Option<&'static str>,
+    /// Whether the case-insensitive comparison folds non-ASCII letters. `SQLite`'s `NOCASE`
+    /// only folds `A`-`Z`.
+    pub folds_non_ascii: bool,
+    /// Column type that compares case-insensitively on its own: `citext` on Postgres, which
+    /// needs `CREATE EXTENSION citext`.
+    pub case_insensitive_type: Option<&'static str>,
+}
+
+impl DatabaseType {
+    /// Describe how this backend compares text.
+    #[must_use]
+    pub fn collation(&self) -> Collation {
+        match self {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => Collation {
+                case_sensitive_by_default: true,
+                case_insensitive_collation: None,
+                folds_non_ascii: true,
+                case_insensitive_type: Some("citext"),
+            },
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => Collation {
+                case_sensitive_by_default: false,
+                case_insensitive_collation: Some(MSSQL_CI),
+                folds_non_ascii: true,
+                case_insensitive_type: None,
+            },
+            // SQLite and Turso.
+            #[allow(unreachable_patterns)]
+            _ => Collation {
+                case_sensitive_by_default: true,
+                case_insensitive_collation: Some("NOCASE"),
+                folds_non_ascii: false,
+                case_insensitive_type: None,
+            },
+        }
+    }
+
+    /// Column type for `CREATE TABLE` that compares case-insensitively.
+    ///
+    /// `base_type` (such as `TEXT` or `NVARCHAR(255)`) gets a `COLLATE` clause on `SQLite`,
+    /// Turso and SQL Server. Postgres returns `citext` in its place, which needs the `citext`
+    /// extension.
+    #[must_use]
+    pub fn case_insensitive_column(&self, base_type: &str) -> String {
+        match self {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => "citext".to_string(),
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => format!("{base_type} COLLATE {MSSQL_CI}"),
+            #[allow(unreachable_patterns)]
+            _ => format!("{base_type} COLLATE NOCASE"),
+        }
+    }
+
+    /// `CREATE INDEX` statement that serves
+    /// [`QueryBuilder::case_insensitive_compare`] on `table.column`.
+    ///
+    /// Postgres indexes `lower(column)`, and `SQLite` and Turso index the column with
+    /// `COLLATE NOCASE`. SQL Server indexes cannot carry their own collation, so the index is
+    /// on the plain column and only helps when the column was declared with
+    /// [`case_insensitive_column`](Self::case_insensitive_column).
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ParameterError` if a name cannot be quoted (see
+    /// [`ident::quote`](crate::ident::quote)).
+    pub fn case_insensitive_index(
+        &self,
+        index: &str,
+        table: &str,
+        column: &str,
+    ) -> Result<String, SqlMiddlewareDbError> {
+        let column = quote(column, self)?;
+        let key = match self {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => format!("lower({column})"),
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => column,
+            #[allow(unreachable_patterns)]
+            _ => format!("{column} COLLATE NOCASE"),
+        };
+        Ok(format!(
+            "CREATE INDEX {} ON {} ({key})",
+            quote(index, self)?,
+            quote(table, self)?
+        ))
+    }
+}
+
+#[cfg(any_backend)]
+impl QueryBuilder<'_, '_> {
+    /// SQL condition comparing `column` and `value` without regard to case on `db_type`.
+    ///
+    /// Both arguments are SQL expressions and are inserted as written: pass a placeholder such
+    /// as `$1` for the value, and quote the column with [`ident::quote`](crate::ident::quote)
+    /// if it comes from outside the program.
+    ///
+    /// ```rust
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # #[cfg(feature = "sqlite")]
+    /// assert_eq!(
+    ///     QueryBuilder::case_insensitive_compare(&DatabaseType::Sqlite, "email", "?1"),
+    ///     "email COLLATE NOCASE = ?1"
+    /// );
+    /// # #[cfg(feature = "postgres")]
+    /// assert_eq!(
+    ///     QueryBuilder::case_insensitive_compare(&DatabaseType::Postgres, "email", "$1"),
+    ///     "lower(email) = lower($1)"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn case_insensitive_compare(db_type: &DatabaseType, column: &str, value: &str) -> String {
+        match db_type {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => format!("lower({column}) = lower({value})"),
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => format!("{column} COLLATE {MSSQL_CI} = {value}"),
+            #[allow(unreachable_patterns)]
+            _ => format!("{column} COLLATE NOCASE = {value}"),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Structured comparison of result sets.
+//!
+//! Backends disagree on the exact shape of "the same" value: SQLite hands back timestamps as text,
+//! Postgres may return `1.0` where SQLite returns `1`, and timestamp precision varies. The helpers
+//! here compare values by meaning rather than by variant and report differences per row and cell.
+
+use chrono::{NaiveDateTime, Timelike};
+
+use crate::results::ResultSet;
+use crate::types::RowValues;
+
+/// Precision used when comparing timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampPrecision {
+    /// Ignore fractional seconds.
+    Seconds,
+    /// Compare up to milliseconds.
+    Millis,
+    /// Compare up to microseconds (Postgres `timestamp` precision).
+    #[default]
+    Micros,
+    /// Compare the full value.
+    Nanos,
+}
+
+impl TimestampPrecision {
+    fn truncate(self, ts: NaiveDateTime) -> NaiveDateTime {
+        let nanos = ts.nanosecond();
+        let kept = match self {
+            TimestampPrecision::Seconds => 0,
+            TimestampPrecision::Millis => nanos - nanos % 1_000_000,
+            TimestampPrecision::Micros => nanos - nanos % 1_000,
+            TimestampPrecision::Nanos => nanos,
+        };
+        ts.with_nanosecond(kept).unwrap_or(ts)
+    }
+}
+
+/// Options for [`diff_result_sets`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// Maximum absolute difference for two numbers to count as equal.
+    pub float_epsilon: f64,
+    /// Precision used when comparing timestamps.
+    pub timestamp_precision: TimestampPrecision,
+    /// Match rows regardless of position instead of comparing row `i` with row `i`.
+    pub ignore_order: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            float_epsilon: 1e-9,
+            timestamp_precision: TimestampPrecision::default(),
+            ignore_order: false,
+        }
+    }
+}
+
+impl DiffOptions {
+    #[must_use]
+    pub fn with_float_epsilon(mut self, float_epsilon: f64) -> Self {
+        self.float_epsilon = float_epsilon;
+        self
+    }
+
+    #[must_use]
+    pub fn with_timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.timestamp_precision = precision;
+        self
+    }
+
+    #[must_use]
+    pub fn with_ignore_order(mut self, ignore_order: bool) -> Self {
+        self.ignore_order = ignore_order;
+        self
+    }
+}
+
+/// A single cell that differs between two rows compared by position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellMismatch {
+    /// Row index (shared by both result sets).
+    pub row: usize,
+    /// Column name, taken from the left-hand result set.
+    pub column: String,
+    /// Value in the left-hand result set.
+    pub left: RowValues,
+    /// Value in the right-hand result set.
+    pub right: RowValues,
+}
+
+/// Differences between two result sets.
+///
+/// Row indexes point into the original `results` vectors of the compared sets.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResultSetDiff {
+    /// Column names of both sides when they differ.
+    pub column_mismatch: Option<(Vec<String>, Vec<String>)>,
+    /// Rows present in the left set but not in the right set.
+    pub missing_rows: Vec<usize>,
+    /// Rows present in the right set but not in the left set.
+    pub extra_rows: Vec<usize>,
+    /// Cells that differ between rows at the same position.
+    pub cell_mismatches: Vec<CellMismatch>,
+}
+
+impl ResultSetDiff {
+    /// `true` when the two result sets compared equal.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.column_mismatch.is_none()
+            && self.missing_rows.is_empty()
+            && self.extra_rows.is_empty()
+            && self.cell_mismatches.is_empty()
+    }
+}
+
+/// Compare two result sets and describe how `b` differs from `a`.
+///
+/// Values are compared by meaning: integers and floats compare numerically within
+/// `float_epsilon`, text that parses as a timestamp compares against timestamps at
+/// `timestamp_precision`, and `0`/`1` compare equal to booleans.
+///
+/// With `ignore_order`, rows are matched as a multiset and leftovers are reported as missing or
+/// extra; otherwise rows are compared by position and differing cells are listed individually.
+///
+/// # Examples
+/// ```rust
+/// use sql_middleware::compare::{DiffOptions, diff_result_sets};
+/// use sql_middleware::prelude::*;
+/// use std::sync::Arc;
+///
+/// let cols = Arc::new(vec!["id".to_string()]);
+/// let mut a = ResultSet::with_capacity(1);
+/// a.set_column_names(cols.clone());
+/// a.add_row_values(vec![RowValues::Int(1)]);
+/// let mut b = ResultSet::with_capacity(1);
+/// b.set_column_names(cols);
+/// b.add_row_values(vec![RowValues::Float(1.0)]);
+///
+/// assert!(diff_result_sets(&a, &b, DiffOptions::default()).is_empty());
+/// ```
+#[must_use]
+pub fn diff_result_sets(a: &ResultSet, b: &ResultSet, options: DiffOptions) -> ResultSetDiff {
+    let left_columns = column_names(a);
+    let right_columns = column_names(b);
+    let mut diff = ResultSetDiff::default();
+    if left_columns != right_columns {
+        diff.column_mismatch = Some((left_columns.clone(), right_columns));
+    }
+
+    if options.ignore_order {
+        diff_unordered(a, b, options, &mut diff);
+    } else {
+        diff_positional(a, b, options, &left_columns, &mut diff);
+    }
+    diff
+}
+
+/// Compare two values using the same rules as [`diff_result_sets`].
+#[must_use]
+pub fn values_equal(left: &RowValues, right: &RowValues, options: DiffOptions) -> bool {
+    match (left, right) {
+        (RowValues::Null, RowValues::Null) => true,
+        (RowValues::Null, _) | (_, RowValues::Null) => false,
+        (RowValues::Float(_), RowValues::Int(_) | RowValues::Float(_))
+        | (RowValues::Int(_), RowValues::Float(_)) => match (as_f64(left), as_f64(right)) {
+            (Some(l), Some(r)) => (l - r).abs() <= options.float_epsilon,
+            _ => false,
+        },
+        (RowValues::Bool(l), other) | (other, RowValues::Bool(l)) => other.as_bool() == Some(l),
+        (RowValues::Timestamp(_), _) | (_, RowValues::Timestamp(_)) => {
+            match (left.as_timestamp(), right.as_timestamp()) {
+                (Some(l), Some(r)) => {
+                    options.timestamp_precision.truncate(l)
+                        == options.timestamp_precision.truncate(r)
+                }
+                _ => false,
+            }
+        }
+        _ => left == right,
+    }
+}
+
+fn diff_positional(
+    a: &ResultSet,
+    b: &ResultSet,
+    options: DiffOptions,
+    columns: &[String],
+    diff: &mut ResultSetDiff,
+) {
+    for (idx, (left, right)) in a.results.iter().zip(b.results.iter()).enumerate() {
+        let width = left.rows.len().max(right.rows.len());
+        for col in 0..width {
+            let l = left.rows.get(col).unwrap_or(&RowValues::Null);
+            let r = right.rows.get(col).unwrap_or(&RowValues::Null);
+            if !values_equal(l, r, options) {
+                diff.cell_mismatches.push(CellMismatch {
+                    row: idx,
+                    column: columns
+                        .get(col)
+                        .cloned()
+                        .unwrap_or_else(|| format!("#{col}")),
+                    left: l.clone(),
+                    right: r.clone(),
+                });
+            }
+        }
+    }
+
+    let shared = a.results.len().min(b.results.len());
+    diff.missing_rows.extend(shared..a.results.len());
+    diff.extra_rows.extend(shared..b.results.len());
+}
+
+fn diff_unordered(a: &ResultSet, b: &ResultSet, options: DiffOptions, diff: &mut ResultSetDiff) {
+    let mut matched = vec![false; b.results.len()];
+    for (idx, left) in a.results.iter().enumerate() {
+        let found = b.results.iter().enumerate().position(|(j, right)| {
+            !matched[j]
+                && left.rows.len() == right.rows.len()
+                && left
+                    .rows
+                    .iter()
+                    .zip(right.rows.iter())
+                    .all(|(l, r)| values_equal(l, r, options))
+        });
+        match found {
+            Some(j) => matched[j] = true,
+            None => diff.missing_rows.push(idx),
+        }
+    }
+    diff.extra_rows.extend(
+        matched
+            .iter()
+            .enumerate()
+            .filter_map(|(j, seen)| (!seen).then_some(j)),
+    );
+}
+
+fn column_names(rs: &ResultSet) -> Vec<String> {
+    rs.get_column_names()
+        .or_else(|| rs.results.first().map(|row| &row.column_names))
+        .map(|names| names.as_ref().clone())
+        .unwrap_or_default()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn as_f64(value: &RowValues) -> Option<f64> {
+    match value {
+        RowValues::Int(i) => Some(*i as f64),
+        RowValues::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{DiffOptions, TimestampPrecision, values_equal};
+    use crate::types::RowValues;
+
+    fn ts(nanos: u32) -> chrono::NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 2)
+            .and_then(|date| date.and_hms_nano_opt(3, 4, 5, nanos))
+            .unwrap()
+    }
+
+    #[test]
+    fn numbers_compare_within_epsilon() {
+        let options = DiffOptions::default().with_float_epsilon(0.01);
+        assert!(values_equal(
+            &RowValues::Float(1.005),
+            &RowValues::Int(1),
+            options
+        ));
+        assert!(values_equal(
+            &RowValues::Float(0.1 + 0.2),
+            &RowValues::Float(0.3),
+            options
+        ));
+        assert!(!values_equal(
+            &RowValues::Float(1.02),
+            &RowValues::Int(1),
+            options
+        ));
+        // The default epsilon still absorbs rounding noise but not real differences.
+        let exact = DiffOptions::default();
+        assert!(values_equal(
+            &RowValues::Float(0.1 + 0.2),
+            &RowValues::Float(0.3),
+            exact
+        ));
+        assert!(!values_equal(
+            &RowValues::Float(1.005),
+            &RowValues::Int(1),
+            exact
+        ));
+        assert!(!values_equal(
+            &RowValues::Float(1.0),
+            &RowValues::Null,
+            exact
+        ));
+    }
+
+    #[test]
+    fn timestamps_compare_at_the_chosen_precision() {
+        let (a, b) = (ts(123_456_789), ts(123_456_000));
+        let at = |precision| DiffOptions::default().with_timestamp_precision(precision);
+        assert!(!values_equal(
+            &RowValues::Timestamp(a),
+            &RowValues::Timestamp(b),
+            at(TimestampPrecision::Nanos)
+        ));
+        assert!(values_equal(
+            &RowValues::Timestamp(a),
+            &RowValues::Timestamp(b),
+            at(TimestampPrecision::Micros)
+        ));
+        assert!(!values_equal(
+            &RowValues::Timestamp(a),
+            &RowValues::Timestamp(ts(0)),
+            at(TimestampPrecision::Millis)
+        ));
+        assert!(values_equal(
+            &RowValues::Timestamp(a),
+            &RowValues::Timestamp(ts(0)),
+            at(TimestampPrecision::Seconds)
+        ));
+        assert_eq!(TimestampPrecision::Millis.truncate(a), ts(123_000_000));
+    }
+
+    #[test]
+    fn text_timestamps_compare_against_native_ones() {
+        let text = RowValues::Text("2024-01-02 03:04:05.123".into());
+        let native = RowValues::Timestamp(ts(123_456_789));
+        let at = |precision| DiffOptions::default().with_timestamp_precision(precision);
+        assert!(values_equal(&text, &native, at(TimestampPrecision::Millis)));
+        assert!(!values_equal(
+            &text,
+            &native,
+            at(TimestampPrecision::Micros)
+        ));
+    }
+}
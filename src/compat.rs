@@ -0,0 +1,36 @@
+//! Deprecated spellings of renamed APIs, kept so downstream crates can upgrade in steps.
+//!
+//! Everything here forwards to its replacement and carries a `#[deprecated]` note naming it, so
+//! the compiler points at each call site to change. Nothing here gains new behaviour, and the
+//! aliases will be removed in a later release.
+//!
+//! | Old | New |
+//! | --- | --- |
+//! | `MiddlewarePool::get_connection(&pool, translate)` | [`cap.get_connection()`](crate::ConfigAndPool::get_connection) |
+//! | `sql_middleware::typed_api` | [`sql_middleware::typed`](crate::typed) |
+//! | features `typed-postgres`, `typed-turso` | `postgres`, `turso` |
+//!
+//! `ConfigAndPool::get_connection` is the one checkout path: it applies the pool's
+//! translation default, clock, concurrency limits and circuit breaker, which the old associated
+//! function on `MiddlewarePool` skipped. The `typed_api` module alias and the `typed-*` features
+//! cannot carry deprecation warnings and are listed here for completeness.
+
+use crate::clock::SystemClock;
+use crate::middleware::{MiddlewarePool, MiddlewarePoolConnection, SqlMiddlewareDbError};
+
+impl MiddlewarePool {
+    /// Check out a connection straight from the pool.
+    ///
+    /// # Errors
+    /// Returns the backend's pool error if no connection can be checked out.
+    #[deprecated(
+        since = "0.6.0",
+        note = "use `ConfigAndPool::get_connection`, which also applies the pool's limits and circuit breaker"
+    )]
+    pub async fn get_connection(
+        pool: &MiddlewarePool,
+        translate_placeholders: bool,
+    ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+        pool.checkout(translate_placeholders, &SystemClock).await
+    }
+}
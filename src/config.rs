@@ -0,0 +1,278 @@
+//! Backend options as data: serde support and config-file loading.
+//!
+//! Every backend's options type (`PostgresOptions`, `SqliteOptions`, `MssqlOptions`,
+//! `TursoOptions`) implements `Serialize` and `Deserialize`, so services can embed them in their
+//! own configuration instead of mirroring them field-for-field. [`BackendConfig`] tags them with
+//! the backend they target, and [`ConfigAndPool::from_config_file`] builds a pool straight from a
+//! JSON or TOML file (JSON needs the default `json` feature, TOML the `toml` feature):
+//!
+//! ```toml
+//! backend = "sqlite"
+//! db_path = "app.db"
+//! translate_placeholders = true
+//! ```
+//!
+//! Serializing redacts passwords, so an options value can be logged or echoed back safely;
+//! a redacted file has to be given the real password again before it can connect.
+//! Runtime-only settings such as a Postgres notice handler are not part of the file format.
+//!
+//! Each options type also has `from_env(prefix)`, which reads the same settings from
+//! environment variables named `{prefix}HOST`, `{prefix}PORT` and so on, and reports every
+//! missing or malformed variable in one `ConfigError`.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::middleware::{ConfigAndPool, SqlMiddlewareDbError};
+
+/// What a serialized password is replaced with.
+pub const REDACTED: &str = "<redacted>";
+
+/// Options for one backend, tagged with the backend's name under the `backend` key.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum BackendConfig {
+    #[cfg(feature = "postgres")]
+    Postgres(crate::postgres::PostgresOptions),
+    #[cfg(feature = "sqlite")]
+    Sqlite(crate::sqlite::SqliteOptions),
+    #[cfg(feature = "mssql")]
+    Mssql(crate::mssql::MssqlOptions),
+    #[cfg(feature = "turso")]
+    Turso(crate::turso::TursoOptions),
+}
+
+impl BackendConfig {
+    /// Parse a config from JSON text.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if the text is not a valid config.
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, SqlMiddlewareDbError> {
+        serde_json::from_str(text)
+            .map_err(|e| SqlMiddlewareDbError::ConfigError(format!("invalid JSON config: {e}")))
+    }
+
+    /// Parse a config from TOML text.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if the text is not a valid config.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, SqlMiddlewareDbError> {
+        toml::from_str(text)
+            .map_err(|e| SqlMiddlewareDbError::ConfigError(format!("invalid TOML config: {e}")))
+    }
+
+    /// Read and parse a config file, choosing the format from its `.json` or `.toml` extension.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if the file cannot be read, has another
+    /// extension, or is not a valid config.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SqlMiddlewareDbError> {
+        let path = path.as_ref();
+        #[cfg_attr(not(any(feature = "json", feature = "toml")), allow(unused_variables))]
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SqlMiddlewareDbError::ConfigError(format!(
+                "cannot read config file {}: {e}",
+                path.display()
+            ))
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "json")]
+            Some("json") => Self::from_json(&text),
+            #[cfg(not(feature = "json"))]
+            Some("json") => Err(SqlMiddlewareDbError::ConfigError(
+                "JSON config files require the `json` feature".to_string(),
+            )),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&text),
+            #[cfg(not(feature = "toml"))]
+            Some("toml") => Err(SqlMiddlewareDbError::ConfigError(
+                "TOML config files require the `toml` feature".to_string(),
+            )),
+            _ => Err(SqlMiddlewareDbError::ConfigError(format!(
+                "config file {} must end in .json or .toml",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl ConfigAndPool {
+    /// Build a pool for whichever backend `config` names.
+    ///
+    /// # Errors
+    /// Returns the same errors as the backend's `new_*` constructor.
+    pub async fn from_config(config: BackendConfig) -> Result<Self, SqlMiddlewareDbError> {
+        match config {
+            #[cfg(feature = "postgres")]
+            BackendConfig::Postgres(opts) => Self::new_postgres(opts).await,
+            #[cfg(feature = "sqlite")]
+            BackendConfig::Sqlite(opts) => Self::new_sqlite(opts).await,
+            #[cfg(feature = "mssql")]
+            BackendConfig::Mssql(opts) => Self::new_mssql(opts).await,
+            #[cfg(feature = "turso")]
+            BackendConfig::Turso(opts) => Self::new_turso(opts).await,
+        }
+    }
+
+    /// Load a [`BackendConfig`] from a `.json` or `.toml` file and build its pool.
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo() -> Result<(), SqlMiddlewareDbError> {
+    /// let cap = ConfigAndPool::from_config_file("config/db.json").await?;
+    /// # let _ = cap;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if the file cannot be loaded, or the
+    /// backend's own error if the pool cannot be built.
+    pub async fn from_config_file(path: impl AsRef<Path>) -> Result<Self, SqlMiddlewareDbError> {
+        Self::from_config(BackendConfig::from_file(path)?).await
+    }
+}
+
+/// Serialize a password as [`REDACTED`].
+#[cfg(feature = "mssql")]
+pub(crate) fn redact<S: serde::Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
+/// Serialize a set password as [`REDACTED`], keeping an unset one unset.
+#[cfg(feature = "postgres")]
+#[allow(clippy::ref_option)]
+pub(crate) fn redact_opt<S: serde::Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_some(REDACTED),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// An optional `Duration` written as whole milliseconds.
+#[cfg(feature = "postgres")]
+pub(crate) mod opt_millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[allow(clippy::ref_option)]
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => {
+                serializer.serialize_some(&u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
+
+/// Collects settings from prefixed variables, remembering every missing or malformed one so
+/// they can be reported together.
+#[cfg(any_backend)]
+pub(crate) struct EnvReader<F> {
+    prefix: String,
+    lookup: F,
+    missing: Vec<String>,
+    invalid: Vec<String>,
+}
+
+#[cfg(any_backend)]
+impl<F: Fn(&str) -> Option<String>> EnvReader<F> {
+    pub(crate) fn new(prefix: &str, lookup: F) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            lookup,
+            missing: Vec::new(),
+            invalid: Vec::new(),
+        }
+    }
+
+    /// The value of `{prefix}{name}`, if set and non-empty.
+    pub(crate) fn optional(&self, name: &str) -> Option<String> {
+        (self.lookup)(&format!("{}{name}", self.prefix)).filter(|value| !value.is_empty())
+    }
+
+    /// The value of `{prefix}{name}`, recording it as missing when unset.
+    pub(crate) fn required(&mut self, name: &str) -> String {
+        self.optional(name).unwrap_or_else(|| {
+            self.missing.push(format!("{}{name}", self.prefix));
+            String::new()
+        })
+    }
+
+    /// `{prefix}{name}` parsed as `T`, recording it as invalid when it does not parse.
+    pub(crate) fn parsed<T: std::str::FromStr>(&mut self, name: &str) -> Option<T> {
+        let raw = self.optional(name)?;
+        raw.parse().ok().or_else(|| {
+            self.invalid.push(format!("{}{name}={raw:?}", self.prefix));
+            None
+        })
+    }
+
+    /// Like [`parsed`](Self::parsed), recording the variable as missing when unset.
+    #[cfg(feature = "postgres")]
+    pub(crate) fn required_parsed<T: std::str::FromStr>(&mut self, name: &str) -> Option<T> {
+        if self.optional(name).is_none() {
+            self.missing.push(format!("{}{name}", self.prefix));
+            return None;
+        }
+        self.parsed(name)
+    }
+
+    /// `{prefix}{name}` as a boolean: `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`.
+    pub(crate) fn flag(&mut self, name: &str) -> Option<bool> {
+        let raw = self.optional(name)?;
+        match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Some(true),
+            "false" | "0" | "no" | "off" => Some(false),
+            _ => {
+                self.invalid.push(format!("{}{name}={raw:?}", self.prefix));
+                None
+            }
+        }
+    }
+
+    /// Fail with every missing and malformed variable, or succeed if there were none.
+    pub(crate) fn finish(self) -> Result<(), SqlMiddlewareDbError> {
+        let mut problems = Vec::new();
+        if !self.missing.is_empty() {
+            problems.push(format!(
+                "missing environment variables: {}",
+                self.missing.join(", ")
+            ));
+        }
+        if !self.invalid.is_empty() {
+            problems.push(format!(
+                "invalid environment variables: {}",
+                self.invalid.join(", ")
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SqlMiddlewareDbError::ConfigError(problems.join("; ")))
+        }
+    }
+}
+
+/// Read a variable from the process environment.
+#[cfg(any_backend)]
+pub(crate) fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
@@ -0,0 +1,180 @@
+//! Credentials fetched at connect time instead of fixed in the pool config.
+//!
+//! Install a [`CredentialsProvider`] on the Postgres or SQL Server builder and every new pooled
+//! connection asks it for a user and password, so secrets can live in Vault or a cloud secrets
+//! manager and be rotated without rebuilding the pool. Connections already open keep the
+//! credentials they authenticated with.
+//!
+//! A provider may say how long its answer stays good with [`Credentials::valid_for`]; until then
+//! the pool reuses it rather than calling the provider for every connection. Without that hint
+//! the provider is asked each time a connection is opened.
+//!
+//! [`TokenProvider`] covers short-lived tokens (RDS IAM auth tokens and the like): it wraps a
+//! token-generating closure and only calls it again once the previous token's lifetime is up.
+//!
+//! If a fetch fails, the pool logs it and falls back to the last credentials it fetched, or to
+//! the user and password in the static config when there are none. The connection attempt then
+//! reports the driver's authentication error if those are no longer accepted.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::middleware::SqlMiddlewareDbError;
+
+/// Boxed future returned by [`CredentialsProvider::fetch`].
+pub type FetchCredentials =
+    Pin<Box<dyn Future<Output = Result<Credentials, SqlMiddlewareDbError>> + Send + 'static>>;
+
+/// A user and secret for opening one connection.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// Overrides the configured user when set.
+    pub user: Option<String>,
+    /// Password or token.
+    pub password: String,
+    /// How long these credentials may be reused for new connections; `None` fetches again for
+    /// every connection.
+    pub valid_for: Option<Duration>,
+}
+
+impl Credentials {
+    #[must_use]
+    pub fn new(user: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            user: Some(user.into()),
+            password: password.into(),
+            valid_for: None,
+        }
+    }
+
+    /// Credentials that only replace the password, keeping the configured user.
+    #[must_use]
+    pub fn password(password: impl Into<String>) -> Self {
+        Self {
+            user: None,
+            password: password.into(),
+            valid_for: None,
+        }
+    }
+
+    /// Reuse these credentials for new connections for up to `valid_for`.
+    #[must_use]
+    pub fn with_valid_for(mut self, valid_for: Duration) -> Self {
+        self.valid_for = Some(valid_for);
+        self
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("user", &self.user)
+            .field("password", &crate::config::REDACTED)
+            .field("valid_for", &self.valid_for)
+            .finish()
+    }
+}
+
+/// Source of connection credentials, called when the pool opens a connection.
+pub trait CredentialsProvider: Send + Sync + fmt::Debug {
+    /// Fetch the credentials to use for the next connection.
+    fn fetch(&self) -> FetchCredentials;
+}
+
+/// A provider plus the last credentials it returned.
+#[cfg_attr(not(any(feature = "postgres", feature = "mssql")), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub(crate) struct CredentialSource {
+    provider: Arc<dyn CredentialsProvider>,
+    last: Arc<Mutex<Option<(Credentials, Instant)>>>,
+}
+
+#[cfg_attr(not(any(feature = "postgres", feature = "mssql")), allow(dead_code))]
+impl CredentialSource {
+    pub(crate) fn new(provider: Arc<dyn CredentialsProvider>) -> Self {
+        Self {
+            provider,
+            last: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Credentials for a new connection: the cached ones while their `valid_for` lasts,
+    /// otherwise a fresh fetch, falling back to the cached ones if the fetch fails.
+    pub(crate) async fn current(&self) -> Option<Credentials> {
+        let cached = self
+            .last
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        if let Some((creds, fetched_at)) = &cached
+            && creds
+                .valid_for
+                .is_some_and(|valid_for| fetched_at.elapsed() < valid_for)
+        {
+            return Some(creds.clone());
+        }
+        match self.provider.fetch().await {
+            Ok(creds) => {
+                *self
+                    .last
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) =
+                    Some((creds.clone(), Instant::now()));
+                Some(creds)
+            }
+            Err(err) => {
+                tracing::warn!(
+                    target: "sql_middleware::credentials",
+                    error = %err,
+                    "credentials fetch failed; using previous credentials"
+                );
+                cached.map(|(creds, _)| creds)
+            }
+        }
+    }
+}
+
+/// A [`CredentialsProvider`] for short-lived auth tokens, such as RDS IAM tokens.
+///
+/// `generate` is called for a new token when a connection is opened and the last token is older
+/// than `lifetime`. The token is sent as the password under the configured user. Pass a
+/// `lifetime` a little shorter than the token's real expiry so a connection is never opened
+/// with a token about to lapse.
+pub struct TokenProvider<F> {
+    generate: F,
+    lifetime: Duration,
+}
+
+impl<F, Fut> TokenProvider<F>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String, SqlMiddlewareDbError>> + Send + 'static,
+{
+    #[must_use]
+    pub fn new(lifetime: Duration, generate: F) -> Self {
+        Self { generate, lifetime }
+    }
+}
+
+impl<F> fmt::Debug for TokenProvider<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenProvider")
+            .field("lifetime", &self.lifetime)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, Fut> CredentialsProvider for TokenProvider<F>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String, SqlMiddlewareDbError>> + Send + 'static,
+{
+    fn fetch(&self) -> FetchCredentials {
+        let token = (self.generate)();
+        let lifetime = self.lifetime;
+        Box::pin(async move { Ok(Credentials::password(token.await?).with_valid_for(lifetime)) })
+    }
+}
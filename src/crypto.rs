@@ -0,0 +1,393 @@
+//! Application-layer encryption for sensitive columns.
+//!
+//! A [`ColumnCrypto`] names the `table.column` pairs that hold encrypted data and gets its keys
+//! from a [`KeyProvider`]. Values bound to those columns are encrypted with AES-256-GCM before
+//! they reach the database, and read back as ciphertext blobs that
+//! [`ColumnCrypto::decrypt_result_set`] turns into the original values. The database only ever
+//! stores ciphertext, so this works the same on every backend, with or without transparent data
+//! encryption.
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use sql_middleware::crypto::{ColumnCrypto, StaticKeys};
+//! use sql_middleware::prelude::*;
+//!
+//! # async fn demo(conn: &mut MiddlewarePoolConnection, key: [u8; 32]) -> Result<(), SqlMiddlewareDbError> {
+//! let crypto = ColumnCrypto::new(Arc::new(StaticKeys::new(1, key))).column("users", "ssn");
+//!
+//! conn.query("INSERT INTO users (id, ssn) VALUES ($1, $2)")
+//!     .params((7, "123-45-6789"))
+//!     .encrypt(&crypto, "users", &[(2, "ssn")])?
+//!     .dml()
+//!     .await?;
+//!
+//! let mut rows = conn.query("SELECT id, ssn FROM users").select().await?;
+//! crypto.decrypt_result_set("users", &mut rows)?;
+//! # Ok(()) }
+//! ```
+//!
+//! Each value is stored as a blob: a format byte, the id of the key that encrypted it, a random
+//! 96-bit nonce and the ciphertext with its tag. The `table.column` name is authenticated along
+//! with the value, so ciphertext copied into another column fails to decrypt. Store encrypted
+//! columns as `BYTEA`, `BLOB` or `VARBINARY(MAX)`. Encryption is randomized, so encrypted
+//! columns cannot be searched, indexed or compared in SQL.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use chrono::NaiveDateTime;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::query_builder::QueryBuilder;
+use crate::results::ResultSet;
+use crate::types::RowValues;
+
+/// AES-256 key bytes.
+pub type Key = [u8; 32];
+
+/// Leading byte of every encrypted value, for future format changes.
+const FORMAT: u8 = 1;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = 1 + 4 + NONCE_LEN;
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Source of encryption keys, such as a KMS client or a secrets file.
+///
+/// Keys have numeric ids so they can be rotated: new values are encrypted with the current key,
+/// and values written under an older key still decrypt while the provider can return it.
+pub trait KeyProvider: Send + Sync {
+    /// Id and bytes of the key new values are encrypted with.
+    ///
+    /// # Errors
+    /// Returns the provider's error if the key cannot be fetched.
+    fn current_key(&self) -> Result<(u32, Key), SqlMiddlewareDbError>;
+
+    /// The key with id `key_id`, or `None` if the provider does not know it.
+    ///
+    /// # Errors
+    /// Returns the provider's error if the key cannot be fetched.
+    fn key(&self, key_id: u32) -> Result<Option<Key>, SqlMiddlewareDbError>;
+}
+
+/// [`KeyProvider`] holding its keys in memory.
+#[derive(Clone)]
+pub struct StaticKeys {
+    current: u32,
+    keys: Vec<(u32, Key)>,
+}
+
+impl StaticKeys {
+    /// Encrypt and decrypt with `key`, identified as `key_id`.
+    #[must_use]
+    pub fn new(key_id: u32, key: Key) -> Self {
+        Self {
+            current: key_id,
+            keys: vec![(key_id, key)],
+        }
+    }
+
+    /// Also decrypt values written with a retired key.
+    #[must_use]
+    pub fn with_old_key(mut self, key_id: u32, key: Key) -> Self {
+        self.keys.push((key_id, key));
+        self
+    }
+}
+
+impl fmt::Debug for StaticKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticKeys")
+            .field("current", &self.current)
+            .field(
+                "key_ids",
+                &self.keys.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl KeyProvider for StaticKeys {
+    fn current_key(&self) -> Result<(u32, Key), SqlMiddlewareDbError> {
+        match self.key(self.current)? {
+            Some(key) => Ok((self.current, key)),
+            None => Err(SqlMiddlewareDbError::ConfigError(format!(
+                "no key with id {}",
+                self.current
+            ))),
+        }
+    }
+
+    fn key(&self, key_id: u32) -> Result<Option<Key>, SqlMiddlewareDbError> {
+        Ok(self
+            .keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, key)| *key))
+    }
+}
+
+/// Encrypted columns and the keys that protect them; see the [module docs](crate::crypto).
+///
+/// Clones share the key provider.
+#[derive(Clone)]
+pub struct ColumnCrypto {
+    keys: Arc<dyn KeyProvider>,
+    columns: HashSet<(String, String)>,
+}
+
+impl fmt::Debug for ColumnCrypto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnCrypto")
+            .field("columns", &self.columns)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ColumnCrypto {
+    /// Start with no encrypted columns, taking keys from `keys`.
+    #[must_use]
+    pub fn new(keys: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            keys,
+            columns: HashSet::new(),
+        }
+    }
+
+    /// Encrypt `table.column`. Names match without regard to case.
+    #[must_use]
+    pub fn column(mut self, table: &str, column: &str) -> Self {
+        self.columns
+            .insert((table.to_ascii_lowercase(), column.to_ascii_lowercase()));
+        self
+    }
+
+    /// Whether `table.column` is encrypted.
+    #[must_use]
+    pub fn is_encrypted(&self, table: &str, column: &str) -> bool {
+        self.columns
+            .contains(&(table.to_ascii_lowercase(), column.to_ascii_lowercase()))
+    }
+
+    /// Encrypt `value` for `table.column`, returning the blob to bind. NULL stays NULL.
+    ///
+    /// Text, blob, integer, float, boolean, timestamp and JSON values can be encrypted, and
+    /// decrypt to the same variant.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if `table.column` is not encrypted or the key
+    /// provider fails, or `ParameterError` for a value that cannot be encrypted.
+    pub fn encrypt(
+        &self,
+        table: &str,
+        column: &str,
+        value: &RowValues,
+    ) -> Result<RowValues, SqlMiddlewareDbError> {
+        self.check_column(table, column)?;
+        if value.is_null() {
+            return Ok(RowValues::Null);
+        }
+        let plaintext = encode(value)?;
+        let (key_id, key) = self.keys.current_key()?;
+        let cipher = Aes256Gcm::new(&key.into());
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = associated_data(table, column);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                SqlMiddlewareDbError::ExecutionError(format!(
+                    "could not encrypt a value for {table}.{column}"
+                ))
+            })?;
+
+        let mut stored = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        stored.push(FORMAT);
+        stored.extend_from_slice(&key_id.to_be_bytes());
+        stored.extend_from_slice(&nonce);
+        stored.extend_from_slice(&ciphertext);
+        Ok(RowValues::Blob(stored.into()))
+    }
+
+    /// Decrypt a value read from `table.column`. NULL stays NULL.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if `table.column` is not encrypted, the key it
+    /// was written with is unknown, or the key provider fails; `ExecutionError` if the value is
+    /// not ciphertext written by [`encrypt`](Self::encrypt) for this column, or was altered.
+    pub fn decrypt(
+        &self,
+        table: &str,
+        column: &str,
+        value: &RowValues,
+    ) -> Result<RowValues, SqlMiddlewareDbError> {
+        self.check_column(table, column)?;
+        let stored = match value {
+            RowValues::Null => return Ok(RowValues::Null),
+            RowValues::Blob(bytes) => bytes,
+            _ => return Err(not_ciphertext(table, column)),
+        };
+        if stored.len() < HEADER_LEN || stored[0] != FORMAT {
+            return Err(not_ciphertext(table, column));
+        }
+        let mut id = [0; 4];
+        id.copy_from_slice(&stored[1..5]);
+        let key_id = u32::from_be_bytes(id);
+        let key = self.keys.key(key_id)?.ok_or_else(|| {
+            SqlMiddlewareDbError::ConfigError(format!(
+                "{table}.{column} holds a value encrypted with unknown key {key_id}"
+            ))
+        })?;
+        let cipher = Aes256Gcm::new(&key.into());
+        let aad = associated_data(table, column);
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&stored[5..HEADER_LEN]),
+                Payload {
+                    msg: &stored[HEADER_LEN..],
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| not_ciphertext(table, column))?;
+        decode(&plaintext).ok_or_else(|| not_ciphertext(table, column))
+    }
+
+    /// Decrypt, in place, every column of `result_set` that is encrypted in `table`.
+    ///
+    /// Columns are matched by their name in the result, so select encrypted columns under their
+    /// own names rather than aliases.
+    ///
+    /// # Errors
+    /// As [`decrypt`](Self::decrypt).
+    pub fn decrypt_result_set(
+        &self,
+        table: &str,
+        result_set: &mut ResultSet,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        let Some(names) = result_set.get_column_names().cloned().or_else(|| {
+            result_set
+                .results
+                .first()
+                .map(|row| row.column_names.clone())
+        }) else {
+            return Ok(());
+        };
+        let encrypted: Vec<(usize, &str)> = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| self.is_encrypted(table, name))
+            .map(|(idx, name)| (idx, name.as_str()))
+            .collect();
+        for row in &mut result_set.results {
+            for &(idx, name) in &encrypted {
+                if let Some(value) = row.rows.get_mut(idx) {
+                    *value = self.decrypt(table, name, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_column(&self, table: &str, column: &str) -> Result<(), SqlMiddlewareDbError> {
+        if self.is_encrypted(table, column) {
+            Ok(())
+        } else {
+            Err(SqlMiddlewareDbError::ConfigError(format!(
+                "{table}.{column} is not an encrypted column"
+            )))
+        }
+    }
+}
+
+impl QueryBuilder<'_, '_> {
+    /// Encrypt the parameters bound to encrypted columns of `table`.
+    ///
+    /// `columns` pairs a 1-based placeholder number with the column it is bound to, such as
+    /// `&[(2, "ssn")]` for `$2`/`?2`. Call it after [`params`](Self::params).
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ParameterError` if a placeholder number has no parameter,
+    /// or the errors of [`ColumnCrypto::encrypt`].
+    pub fn encrypt(
+        mut self,
+        crypto: &ColumnCrypto,
+        table: &str,
+        columns: &[(usize, &str)],
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        for &(position, column) in columns {
+            let value = position
+                .checked_sub(1)
+                .and_then(|idx| self.params.to_mut().get_mut(idx))
+                .ok_or_else(|| {
+                    SqlMiddlewareDbError::ParameterError(format!(
+                        "no parameter {position} to encrypt for {table}.{column}"
+                    ))
+                })?;
+            *value = crypto.encrypt(table, column, value)?;
+        }
+        Ok(self)
+    }
+}
+
+fn associated_data(table: &str, column: &str) -> String {
+    format!(
+        "{}.{}",
+        table.to_ascii_lowercase(),
+        column.to_ascii_lowercase()
+    )
+}
+
+fn not_ciphertext(table: &str, column: &str) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ExecutionError(format!(
+        "{table}.{column} holds a value that does not decrypt with its key"
+    ))
+}
+
+/// Plaintext encoding: a tag byte for the variant, then its bytes.
+fn encode(value: &RowValues) -> Result<Vec<u8>, SqlMiddlewareDbError> {
+    let (tag, bytes): (u8, Vec<u8>) = match value {
+        RowValues::Text(text) => (1, text.as_bytes().to_vec()),
+        RowValues::Blob(bytes) => (2, bytes.to_vec()),
+        RowValues::Int(int) => (3, int.to_be_bytes().to_vec()),
+        RowValues::Float(float) => (4, float.to_bits().to_be_bytes().to_vec()),
+        RowValues::Bool(flag) => (5, vec![u8::from(*flag)]),
+        RowValues::Timestamp(at) => (6, at.format(TIMESTAMP_FORMAT).to_string().into_bytes()),
+        #[cfg(feature = "json")]
+        RowValues::JSON(json) => (7, json.to_string().into_bytes()),
+        _ => {
+            return Err(SqlMiddlewareDbError::ParameterError(
+                "only text, blob, numeric, boolean, timestamp and JSON values can be encrypted"
+                    .into(),
+            ));
+        }
+    };
+    let mut plaintext = Vec::with_capacity(1 + bytes.len());
+    plaintext.push(tag);
+    plaintext.extend_from_slice(&bytes);
+    Ok(plaintext)
+}
+
+fn decode(plaintext: &[u8]) -> Option<RowValues> {
+    let (&tag, bytes) = plaintext.split_first()?;
+    Some(match tag {
+        1 => RowValues::Text(std::str::from_utf8(bytes).ok()?.into()),
+        2 => RowValues::Blob(bytes.into()),
+        3 => RowValues::Int(i64::from_be_bytes(bytes.try_into().ok()?)),
+        4 => RowValues::Float(f64::from_bits(u64::from_be_bytes(bytes.try_into().ok()?))),
+        5 => RowValues::Bool(bytes.first()? != &0),
+        6 => RowValues::Timestamp(
+            NaiveDateTime::parse_from_str(std::str::from_utf8(bytes).ok()?, TIMESTAMP_FORMAT)
+                .ok()?,
+        ),
+        #[cfg(feature = "json")]
+        7 => RowValues::JSON(serde_json::from_slice(bytes).ok()?),
+        _ => return None,
+    })
+}
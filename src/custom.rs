@@ -0,0 +1,115 @@
+//! Application-defined scalar types.
+//!
+//! Some columns have no [`RowValues`] counterpart: Postgres enums, ranges and geometric types, or
+//! SQL Server `hierarchyid`. Wrap such a value in a type implementing [`CustomValue`] and pass it
+//! as [`RowValues::Custom`]; it is bound as whatever [`CustomValue::encode`] returns. To read the
+//! type back, register a decoder for its Postgres type name with [`register_custom_type`].
+//!
+//! ```rust
+//! use sql_middleware::custom::{CustomValue, register_custom_type};
+//! use sql_middleware::prelude::*;
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Mood(String);
+//!
+//! impl CustomValue for Mood {
+//!     fn type_name(&self) -> &str {
+//!         "mood"
+//!     }
+//!     fn encode(&self) -> RowValues {
+//!         RowValues::Text(self.0.as_str().into())
+//!     }
+//! }
+//!
+//! // Postgres sends enum labels as UTF-8 text.
+//! register_custom_type("mood", |raw| {
+//!     let label = std::str::from_utf8(raw)
+//!         .map_err(|e| SqlMiddlewareDbError::ExecutionError(e.to_string()))?;
+//!     Ok(Box::new(Mood(label.to_string())))
+//! });
+//!
+//! let param = RowValues::Custom(Box::new(Mood("happy".into())));
+//! assert_eq!(param.as_custom::<Mood>(), Some(&Mood("happy".into())));
+//! ```
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{LazyLock, RwLock};
+
+use crate::error::SqlMiddlewareDbError;
+use crate::types::RowValues;
+
+/// A value of a type the middleware does not model itself.
+///
+/// Implement `type_name` and `encode`; `Clone` and downcasting come from the blanket
+/// [`CustomValueClone`] impl. Two custom values are equal when their type names and encodings
+/// are equal.
+pub trait CustomValue: CustomValueClone + fmt::Debug + Send + Sync {
+    /// The database type this value stands for, e.g. `mood` or `int4range`.
+    fn type_name(&self) -> &str;
+
+    /// The built-in value to bind in its place.
+    ///
+    /// `SQLite`, Turso, and SQL Server bind it as they would that value. On Postgres, a `Text` or
+    /// `Blob` encoding sent to a column of another type is written as the type's binary wire
+    /// format, which for enums is the label itself. Returning another `Custom` encodes it in turn.
+    fn encode(&self) -> RowValues;
+}
+
+/// Object-safe cloning and downcasting for [`CustomValue`]; implemented for every `Clone` type.
+pub trait CustomValueClone {
+    /// Clone into a new box.
+    fn clone_box(&self) -> Box<dyn CustomValue>;
+    /// Borrow as `Any` so callers can downcast to the concrete type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: CustomValue + Clone + 'static> CustomValueClone for T {
+    fn clone_box(&self) -> Box<dyn CustomValue> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn CustomValue> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl PartialEq for dyn CustomValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_name() == other.type_name() && self.encode() == other.encode()
+    }
+}
+
+/// Turns a column's raw bytes into a custom value. On Postgres these are the binary wire format.
+pub type CustomDecoder = fn(&[u8]) -> Result<Box<dyn CustomValue>, SqlMiddlewareDbError>;
+
+static DECODERS: LazyLock<RwLock<HashMap<String, CustomDecoder>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Decode Postgres columns of type `type_name` with `decoder`, replacing any earlier registration.
+///
+/// Registered types come back as [`RowValues::Custom`]; built-in types such as `int4` or `text`
+/// keep their usual mapping.
+pub fn register_custom_type(type_name: &str, decoder: CustomDecoder) {
+    let mut decoders = match DECODERS.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    decoders.insert(type_name.to_string(), decoder);
+}
+
+#[cfg(feature = "postgres")]
+pub(crate) fn decoder_for(type_name: &str) -> Option<CustomDecoder> {
+    let decoders = match DECODERS.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    decoders.get(type_name).copied()
+}
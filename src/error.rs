@@ -1,5 +1,11 @@
+#[cfg(any_backend)]
+use std::time::Duration;
+
 use thiserror::Error;
 
+#[cfg(any_backend)]
+use crate::pool::PoolStats;
+
 #[cfg(any(feature = "postgres", feature = "mssql"))]
 use bb8;
 
@@ -30,12 +36,44 @@ pub enum SqlMiddlewareDbError {
     MssqlError(#[from] tiberius::error::Error),
 
     #[cfg(feature = "postgres")]
-    #[error(transparent)]
-    PoolErrorPostgres(#[from] bb8::RunError<tokio_postgres::Error>),
+    #[error("Postgres pool checkout failed after {waited:?} ({state}): {source}")]
+    PoolErrorPostgres {
+        source: Box<bb8::RunError<tokio_postgres::Error>>,
+        /// How long the caller waited before the checkout failed.
+        waited: Duration,
+        /// Pool occupancy at the moment of failure.
+        state: PoolStats,
+    },
+
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite pool checkout failed after {waited:?} ({state}): {source}")]
+    PoolErrorSqlite {
+        source: Box<bb8::RunError<SqlMiddlewareDbError>>,
+        /// How long the caller waited before the checkout failed.
+        waited: Duration,
+        /// Pool occupancy at the moment of failure.
+        state: PoolStats,
+    },
 
     #[cfg(feature = "mssql")]
-    #[error(transparent)]
-    PoolErrorMssql(#[from] bb8::RunError<Bb8TiberiusError>),
+    #[error("SQL Server pool checkout failed after {waited:?} ({state}): {source}")]
+    PoolErrorMssql {
+        source: Box<bb8::RunError<Bb8TiberiusError>>,
+        /// How long the caller waited before the checkout failed.
+        waited: Duration,
+        /// Pool occupancy at the moment of failure.
+        state: PoolStats,
+    },
+
+    #[cfg(feature = "turso")]
+    #[error("Turso pool checkout failed after {waited:?} ({state}): {source}")]
+    PoolErrorTurso {
+        source: Box<bb8::RunError<turso::Error>>,
+        /// How long the caller waited before the checkout failed.
+        waited: Duration,
+        /// Pool occupancy at the moment of failure.
+        state: PoolStats,
+    },
 
     #[cfg(feature = "turso")]
     #[error(transparent)]
@@ -56,10 +94,190 @@ pub enum SqlMiddlewareDbError {
     #[error("Unimplemented feature: {0}")]
     Unimplemented(String),
 
+    /// The pool's circuit breaker is open; no connection was attempted.
+    #[error("Circuit breaker open; retry after {retry_after:?}")]
+    CircuitOpen {
+        /// Time until the breaker lets a probe through.
+        retry_after: std::time::Duration,
+    },
+
+    /// A pooled connection's worker queue was full and its policy is to reject; retry later or
+    /// spread the work over more connections.
+    #[error("Connection overloaded: {limit} operations already queued")]
+    Overloaded {
+        /// The configured cap on queued operations.
+        limit: usize,
+    },
+
+    /// The pool's rate limit was exhausted and its policy is to reject; see
+    /// [`PoolOptions::rate_limit`](crate::pool::PoolOptions::rate_limit).
+    #[error("Rate limit exceeded; retry after {retry_after:?}")]
+    RateLimited {
+        /// Time until the next token is available.
+        retry_after: std::time::Duration,
+    },
+
+    /// A [`Saga`](crate::saga::Saga) step failed; the steps before it were compensated.
+    #[error(
+        "Saga step `{step}` failed ({} compensation(s) failed): {source}",
+        .compensation_failures.len()
+    )]
+    SagaAborted {
+        /// Name of the step that failed.
+        step: String,
+        /// Why it failed.
+        source: Box<SqlMiddlewareDbError>,
+        /// Completed steps whose compensation also failed, in the order they were attempted;
+        /// these need manual repair.
+        compensation_failures: Vec<(String, SqlMiddlewareDbError)>,
+    },
+
+    /// A batch run with a timeout or cancellation was stopped before it finished.
+    #[error("Batch {reason} during statement {index} ({statement}); rolled back: {rolled_back}")]
+    BatchStopped {
+        /// Zero-based position of the statement that was running.
+        index: usize,
+        /// That statement's SQL.
+        statement: String,
+        /// Whether the timeout or the cancellation stopped it.
+        reason: BatchStopReason,
+        /// Whether an open transaction (the batch's own, or one the script began) was rolled
+        /// back. Otherwise the statements before `index` stay committed.
+        rolled_back: bool,
+    },
+
+    /// [`update_versioned`](crate::MiddlewarePoolConnection::update_versioned) matched no row:
+    /// someone else changed the row since it was read, or it no longer exists.
+    #[error("Stale version: no row in {table} with {key_column} = {key:?} at version {expected}")]
+    StaleVersion {
+        /// Table that was updated.
+        table: String,
+        /// Key column the row was looked up by.
+        key_column: String,
+        /// Key value of the row.
+        key: crate::types::RowValues,
+        /// Version the caller expected the row to have.
+        expected: i64,
+    },
+
     #[error("Other database error: {0}")]
     Other(String),
 }
 
+/// Why a batch was stopped, in [`SqlMiddlewareDbError::BatchStopped`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStopReason {
+    /// [`QueryOptions::timeout`](crate::QueryOptions::timeout) elapsed.
+    TimedOut,
+    /// The cancellation future resolved.
+    Cancelled,
+}
+
+impl std::fmt::Display for BatchStopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BatchStopReason::TimedOut => "timed out",
+            BatchStopReason::Cancelled => "cancelled",
+        })
+    }
+}
+
+/// Backend-independent category of a [`SqlMiddlewareDbError`], from [`SqlMiddlewareDbError::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Lock contention that may clear on retry: `SQLITE_BUSY`/`SQLITE_LOCKED`, Postgres lock
+    /// timeouts, deadlocks and serialization failures, SQL Server lock timeouts and deadlocks.
+    Busy,
+    /// A unique or primary-key constraint rejected the row.
+    UniqueViolation,
+    /// The statement did not parse.
+    Syntax,
+    /// Anything else, including errors from backends without a classification (Turso).
+    Other,
+}
+
+impl SqlMiddlewareDbError {
+    /// Classify this error by driver error code, so callers do not match on message text.
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "postgres")]
+            SqlMiddlewareDbError::PostgresError(err) => postgres_kind(err),
+            #[cfg(feature = "sqlite")]
+            SqlMiddlewareDbError::SqliteError(err) => sqlite_kind(err),
+            #[cfg(feature = "mssql")]
+            SqlMiddlewareDbError::MssqlError(err) => mssql_kind(err),
+            SqlMiddlewareDbError::SagaAborted { source, .. } => source.kind(),
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn postgres_kind(err: &tokio_postgres::Error) -> ErrorKind {
+    use tokio_postgres::error::SqlState;
+
+    match err.code() {
+        Some(code)
+            if *code == SqlState::LOCK_NOT_AVAILABLE
+                || *code == SqlState::T_R_DEADLOCK_DETECTED
+                || *code == SqlState::T_R_SERIALIZATION_FAILURE =>
+        {
+            ErrorKind::Busy
+        }
+        Some(code) if *code == SqlState::UNIQUE_VIOLATION => ErrorKind::UniqueViolation,
+        Some(code) if *code == SqlState::SYNTAX_ERROR => ErrorKind::Syntax,
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_kind(err: &rusqlite::Error) -> ErrorKind {
+    use rusqlite::ErrorCode;
+
+    let rusqlite::Error::SqliteFailure(failure, message) = err else {
+        return ErrorKind::Other;
+    };
+    match failure.code {
+        ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => ErrorKind::Busy,
+        ErrorCode::ConstraintViolation
+            if matches!(
+                failure.extended_code,
+                rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+                    | rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+            ) =>
+        {
+            ErrorKind::UniqueViolation
+        }
+        // Parse errors share the generic SQLITE_ERROR code; only the message tells them apart.
+        ErrorCode::Unknown
+            if message
+                .as_deref()
+                .is_some_and(|message| message.contains("syntax error")) =>
+        {
+            ErrorKind::Syntax
+        }
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(feature = "mssql")]
+fn mssql_kind(err: &tiberius::error::Error) -> ErrorKind {
+    let tiberius::error::Error::Server(token) = err else {
+        return ErrorKind::Other;
+    };
+    match token.code() {
+        // Deadlock victim, lock request timeout.
+        1205 | 1222 => ErrorKind::Busy,
+        // Duplicate key in a unique index / unique constraint.
+        2601 | 2627 => ErrorKind::UniqueViolation,
+        // Incorrect syntax near a token / keyword.
+        102 | 156 => ErrorKind::Syntax,
+        _ => ErrorKind::Other,
+    }
+}
+
 #[cfg(feature = "sqlite")]
 impl From<bb8::RunError<SqlMiddlewareDbError>> for SqlMiddlewareDbError {
     fn from(err: bb8::RunError<SqlMiddlewareDbError>) -> Self {
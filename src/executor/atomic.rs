@@ -0,0 +1,169 @@
+//! Several DML statements committed together or not at all.
+
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::MiddlewarePoolConnection;
+use crate::translation::TranslationMode;
+use crate::types::RowValues;
+
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+#[cfg(feature = "turso")]
+use crate::turso::TxMode;
+
+impl MiddlewarePoolConnection {
+    /// Run DML statements in order inside one transaction and return each one's affected rows.
+    ///
+    /// Meant for writes that only make sense together, such as a parent row and its children. If
+    /// any statement fails, the transaction is rolled back and that statement's error is
+    /// returned, so none of the writes persist. Placeholder translation follows the pool's
+    /// default, as it does for [`query`](MiddlewarePoolConnection::query).
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let counts = conn
+    ///     .execute_dml_atomic(&[
+    ///         ("INSERT INTO orders (id) VALUES ($1)", vec![RowValues::Int(7)]),
+    ///         (
+    ///             "INSERT INTO order_lines (order_id, sku) VALUES ($1, $2), ($1, $3)",
+    ///             vec![
+    ///                 RowValues::Int(7),
+    ///                 RowValues::Text("a".into()),
+    ///                 RowValues::Text("b".into()),
+    ///             ],
+    ///         ),
+    ///     ])
+    ///     .await?;
+    /// assert_eq!(counts, vec![1, 2]);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the error from beginning the transaction, from the first statement that fails
+    /// (after rolling back), or from committing.
+    pub async fn execute_dml_atomic<S, P>(
+        &mut self,
+        statements: &[(S, P)],
+    ) -> Result<Vec<usize>, SqlMiddlewareDbError>
+    where
+        S: AsRef<str>,
+        P: AsRef<[RowValues]>,
+    {
+        if statements.is_empty() {
+            return Ok(Vec::new());
+        }
+        let translation = if self.translation_default() {
+            TranslationMode::ForceOn
+        } else {
+            TranslationMode::ForceOff
+        };
+        let result = self.run_atomic(statements, translation).await;
+        self.record_result(&result);
+        result
+    }
+
+    /// Body of [`execute_dml_atomic`](Self::execute_dml_atomic), run before its outcome is
+    /// recorded.
+    async fn run_atomic<S, P>(
+        &mut self,
+        statements: &[(S, P)],
+        translation: TranslationMode,
+    ) -> Result<Vec<usize>, SqlMiddlewareDbError>
+    where
+        S: AsRef<str>,
+        P: AsRef<[RowValues]>,
+    {
+        let mut counts = Vec::with_capacity(statements.len());
+
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { .. } => {
+                let tx = self.begin_postgres_transaction().await?;
+                for (sql, params) in statements {
+                    let step = tx
+                        .query_builder(sql.as_ref())
+                        .params(params.as_ref())
+                        .translation(translation)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => counts.push(rows),
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                let mut tx = sqlite::begin_transaction(self).await?;
+                for (sql, params) in statements {
+                    let step = tx
+                        .query_builder(sql.as_ref())
+                        .params(params.as_ref())
+                        .translation(translation)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => counts.push(rows),
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => {
+                let mut tx = self.begin_mssql_transaction().await?;
+                for (sql, params) in statements {
+                    let step = tx
+                        .query_builder(sql.as_ref())
+                        .params(params.as_ref())
+                        .translation(translation)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => counts.push(rows),
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { .. } => {
+                let tx = self.begin_turso_transaction(TxMode::Interactive).await?;
+                for (sql, params) in statements {
+                    let step = tx
+                        .query_builder(sql.as_ref())
+                        .params(params.as_ref())
+                        .translation(translation)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => counts.push(rows),
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(SqlMiddlewareDbError::Unimplemented(
+                    "This database type is not enabled in the current build".to_string(),
+                ));
+            }
+        }
+        Ok(counts)
+    }
+}
@@ -0,0 +1,396 @@
+#[cfg(any_backend)]
+use std::future::Future;
+#[cfg(any_backend)]
+use std::pin::pin;
+#[cfg(any_backend)]
+use std::time::Duration;
+
+#[cfg(any_backend)]
+use crate::clock::{Clock, Sleep, system_clock};
+#[cfg(any_backend)]
+use crate::error::{BatchStopReason, SqlMiddlewareDbError};
+#[cfg(any_backend)]
+use crate::pool::MiddlewarePoolConnection;
+#[cfg(feature = "mssql")]
+use crate::translation::core::scanner::code_words;
+#[cfg(any_backend)]
+use crate::translation::{QueryOptions, split_statements};
+
+#[cfg(any_backend)]
+use super::progress::{execute_counted, opens_or_ends_tx};
+
+/// How a batch's statements are grouped into transactions.
+///
+/// The default is [`Single`](BatchTxMode::Single) on every backend, and it is what plain
+/// [`execute_batch`](MiddlewarePoolConnection::execute_batch) uses.
+///
+/// On SQL Server the script is not split into statements, since `;` also ends statements inside
+/// T-SQL blocks such as `IF ... BEGIN ... END`. It is sent as one T-SQL batch, so under
+/// [`Autocommit`](BatchTxMode::Autocommit) whether the statements after a failing one still run
+/// follows SQL Server's batch rules; `SET XACT_ABORT ON` makes every error stop the batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchTxMode {
+    /// Run the whole batch in one transaction: the statements commit together, and a failure
+    /// rolls all of them back. `BEGIN`/`COMMIT` in the script are rejected.
+    #[default]
+    Single,
+    /// Commit each statement on its own. A failure stops the batch and keeps the statements
+    /// before it. `BEGIN`/`COMMIT` in the script are rejected.
+    Autocommit,
+    /// Run the script as written, so only its own `BEGIN`/`COMMIT` group statements. A failure
+    /// while a transaction the script opened is still open rolls that transaction back.
+    Script,
+}
+
+/// Options for a batch, set with [`QueryOptions::batch`].
+///
+/// # Examples
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+///
+/// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+/// let options = QueryOptions::default()
+///     .batch(BatchOptions::default().transactional(BatchTxMode::Autocommit));
+/// conn.execute_batch_with("INSERT INTO log VALUES (1); INSERT INTO log VALUES (2);", options)
+///     .await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchOptions {
+    pub transactional: BatchTxMode,
+}
+
+impl BatchOptions {
+    #[must_use]
+    pub fn transactional(mut self, mode: BatchTxMode) -> Self {
+        self.transactional = mode;
+        self
+    }
+}
+
+/// How a statement of a batch ended.
+#[cfg(any_backend)]
+enum Step {
+    Finished(Result<usize, SqlMiddlewareDbError>),
+    Stopped(BatchStopReason),
+}
+
+#[cfg(any_backend)]
+impl MiddlewarePoolConnection {
+    /// Execute a batch like [`execute_batch`](MiddlewarePoolConnection::execute_batch), honoring
+    /// [`QueryOptions::timeout`] and [`QueryOptions::batch`].
+    ///
+    /// Without a timeout, a [`BatchTxMode::Single`] batch (the default) goes to the driver in one
+    /// call. Otherwise the batch runs as
+    /// [`execute_batch_until`](MiddlewarePoolConnection::execute_batch_until) describes.
+    ///
+    /// # Errors
+    /// Returns [`SqlMiddlewareDbError::BatchStopped`] when the timeout elapses, or the error of the
+    /// first statement that fails. [`BatchTxMode::Single`] and [`BatchTxMode::Autocommit`] return
+    /// `ExecutionError` without running anything if the script contains `BEGIN`, `COMMIT` or
+    /// `ROLLBACK`.
+    pub async fn execute_batch_with(
+        &mut self,
+        sql: &str,
+        options: QueryOptions,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        let mode = options.batch.unwrap_or_default().transactional;
+        if options.timeout.is_none() && mode == BatchTxMode::Single {
+            reject_tx_control(self, &self.batch_statements(sql), mode)?;
+            return self.execute_batch_single(sql).await;
+        }
+        self.execute_batch_until(sql, options, std::future::pending())
+            .await
+    }
+
+    /// Execute a batch that stops when `cancel` resolves or [`QueryOptions::timeout`] elapses.
+    ///
+    /// The statements are split with [`split_statements`] and run one at a time, grouped as
+    /// [`QueryOptions::batch`] says ([`BatchTxMode::Single`] by default). SQL Server scripts are
+    /// not split; see [`BatchTxMode`].
+    ///
+    /// When the batch is stopped, the statement in flight is cancelled (a Postgres cancel
+    /// request, or `sqlite3_interrupt`). The remaining statements are skipped, and an open
+    /// transaction is rolled back. SQL Server and Turso cannot cancel a running statement, so
+    /// they finish it first. Any `cancel` future works, e.g. a `oneshot::Receiver` mapped to `()`
+    /// or `CancellationToken::cancelled()`.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection, script: &str) -> Result<(), SqlMiddlewareDbError> {
+    /// let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    /// let options = QueryOptions::default().timeout(Duration::from_secs(600));
+    /// match conn.execute_batch_until(script, options, async { let _ = stopped.await; }).await {
+    ///     Err(SqlMiddlewareDbError::BatchStopped { index, reason, .. }) => {
+    ///         eprintln!("{reason} during statement {index}");
+    ///     }
+    ///     other => other?,
+    /// }
+    /// # drop(stop);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`SqlMiddlewareDbError::BatchStopped`] with the index of the statement that was in
+    /// flight, or the error of the first statement that fails (after the same rollback).
+    /// [`BatchTxMode::Single`] and [`BatchTxMode::Autocommit`] return `ExecutionError` without
+    /// running anything if the script contains `BEGIN`, `COMMIT` or `ROLLBACK`.
+    pub async fn execute_batch_until(
+        &mut self,
+        sql: &str,
+        options: QueryOptions,
+        cancel: impl Future<Output = ()>,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        let mode = options.batch.unwrap_or_default().transactional;
+        run_batch(self, sql, mode, options.timeout, cancel).await
+    }
+
+    /// Whether batches are run statement by statement. SQL Server scripts are sent whole, since
+    /// `split_statements` would cut T-SQL blocks apart at their inner `;`.
+    fn splits_batches(&self) -> bool {
+        match self {
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => false,
+            #[allow(unreachable_patterns)]
+            _ => true,
+        }
+    }
+
+    /// The units [`run_batch`] executes one at a time.
+    fn batch_statements<'s>(&self, sql: &'s str) -> Vec<&'s str> {
+        if self.splits_batches() {
+            split_statements(sql)
+        } else {
+            Some(sql.trim())
+                .filter(|script| !script.is_empty())
+                .into_iter()
+                .collect()
+        }
+    }
+
+    /// The first statement of a batch (on SQL Server, the first keyword) that begins or ends a
+    /// transaction.
+    fn find_tx_control<'s>(&self, statements: &[&'s str]) -> Option<&'s str> {
+        match self {
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => {
+                statements.iter().find_map(|script| tsql_tx_keyword(script))
+            }
+            #[allow(unreachable_patterns)]
+            _ => statements
+                .iter()
+                .copied()
+                .find(|statement| opens_or_ends_tx(statement).is_some()),
+        }
+    }
+
+    /// The pool's clock where the connection carries it, the system clock otherwise.
+    fn clock(&self) -> std::sync::Arc<dyn Clock> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite {
+                conn: Some(conn), ..
+            } => std::sync::Arc::clone(&conn.clock),
+            #[allow(unreachable_patterns)]
+            _ => system_clock(),
+        }
+    }
+
+    /// `BEGIN`, `COMMIT` and `ROLLBACK` in this backend's dialect.
+    fn tx_statements(&self) -> [&'static str; 3] {
+        match self {
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => [
+                "BEGIN TRANSACTION",
+                "COMMIT TRANSACTION",
+                // Errors that abort the batch also end its transaction on the server.
+                "IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION",
+            ],
+            #[allow(unreachable_patterns)]
+            _ => ["BEGIN", "COMMIT", "ROLLBACK"],
+        }
+    }
+}
+
+#[cfg(any_backend)]
+async fn run_batch(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    mode: BatchTxMode,
+    timeout: Option<Duration>,
+    cancel: impl Future<Output = ()>,
+) -> Result<(), SqlMiddlewareDbError> {
+    let statements = conn.batch_statements(sql);
+    reject_tx_control(conn, &statements, mode)?;
+    let wrapped = mode == BatchTxMode::Single && !conn.in_transaction().await?;
+    let interrupter = Interrupter::for_connection(conn)?;
+    let [begin, commit, rollback] = conn.tx_statements();
+    let mut deadline: Sleep = match timeout {
+        Some(timeout) => conn.clock().sleep(timeout),
+        None => Box::pin(std::future::pending()),
+    };
+    let mut cancel = pin!(cancel);
+    // A SQL Server script runs as one unit, so a transaction it opens may still be open when it
+    // fails; the rollback statement checks first.
+    let mut in_script_tx = mode == BatchTxMode::Script
+        && !conn.splits_batches()
+        && conn.find_tx_control(&statements).is_some();
+
+    if wrapped {
+        execute_counted(conn, begin).await?;
+    }
+    for (index, statement) in statements.iter().enumerate() {
+        let step = {
+            let mut run = pin!(execute_counted(conn, statement));
+            tokio::select! {
+                biased;
+                result = &mut run => Step::Finished(result),
+                () = &mut deadline => {
+                    interrupter.interrupt().await;
+                    let _ = run.await;
+                    Step::Stopped(BatchStopReason::TimedOut)
+                }
+                () = &mut cancel => {
+                    interrupter.interrupt().await;
+                    let _ = run.await;
+                    Step::Stopped(BatchStopReason::Cancelled)
+                }
+            }
+        };
+        let err = match step {
+            Step::Finished(Ok(_)) => {
+                if mode == BatchTxMode::Script {
+                    in_script_tx = opens_or_ends_tx(statement).unwrap_or(in_script_tx);
+                }
+                continue;
+            }
+            Step::Finished(Err(err)) => err,
+            Step::Stopped(reason) => SqlMiddlewareDbError::BatchStopped {
+                index,
+                statement: (*statement).to_string(),
+                reason,
+                rolled_back: wrapped || in_script_tx,
+            },
+        };
+        if wrapped || in_script_tx {
+            // SQLite may already have rolled back an interrupted write, so this can fail
+            // with "no transaction is active"; either way nothing was committed.
+            let _ = execute_counted(conn, rollback).await;
+        }
+        return Err(err);
+    }
+    if wrapped {
+        execute_counted(conn, commit).await?;
+    }
+    Ok(())
+}
+
+/// Fail a managed batch whose script begins or ends transactions itself.
+#[cfg(any_backend)]
+fn reject_tx_control(
+    conn: &MiddlewarePoolConnection,
+    statements: &[&str],
+    mode: BatchTxMode,
+) -> Result<(), SqlMiddlewareDbError> {
+    if mode == BatchTxMode::Script {
+        return Ok(());
+    }
+    match conn.find_tx_control(statements) {
+        Some(statement) => Err(SqlMiddlewareDbError::ExecutionError(format!(
+            "{mode:?} batches manage transactions themselves; remove `{statement}` or use \
+             BatchTxMode::Script"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The first keyword of a T-SQL transaction statement in `script`: `BEGIN TRAN[SACTION]`,
+/// `BEGIN DISTRIBUTED`, `COMMIT` or `ROLLBACK`. `BEGIN ... END`, `TRY` and `CATCH` blocks do not
+/// count.
+#[cfg(feature = "mssql")]
+fn tsql_tx_keyword(script: &str) -> Option<&str> {
+    let words = code_words(script);
+    words.iter().enumerate().find_map(|(idx, word)| {
+        let tx_statement = if word.eq_ignore_ascii_case("begin") {
+            words.get(idx + 1).is_some_and(|next| {
+                ["tran", "transaction", "distributed"]
+                    .iter()
+                    .any(|keyword| next.eq_ignore_ascii_case(keyword))
+            })
+        } else {
+            word.eq_ignore_ascii_case("commit") || word.eq_ignore_ascii_case("rollback")
+        };
+        tx_statement.then_some(*word)
+    })
+}
+
+/// Cancels the statement running on a connection without borrowing it.
+#[cfg(any_backend)]
+enum Interrupter {
+    #[cfg(feature = "postgres")]
+    Postgres(tokio_postgres::CancelToken),
+    #[cfg(feature = "sqlite")]
+    Sqlite(crate::sqlite::config::SharedSqliteConnection),
+    /// The backend has no way to cancel a running statement.
+    None,
+}
+
+#[cfg(any_backend)]
+impl Interrupter {
+    fn for_connection(conn: &mut MiddlewarePoolConnection) -> Result<Self, SqlMiddlewareDbError> {
+        match conn {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                Ok(Interrupter::Postgres(client.cancel_token()))
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                Ok(Interrupter::Sqlite(conn.sqlite_conn_mut()?.conn_handle()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Ok(Interrupter::None),
+        }
+    }
+
+    async fn interrupt(&self) {
+        match self {
+            #[cfg(feature = "postgres")]
+            Interrupter::Postgres(token) => {
+                // Pools connect without TLS (see `PgManager`), so the cancel request does too.
+                if let Err(err) = token.cancel_query(tokio_postgres::NoTls).await {
+                    tracing::warn!(
+                        target: "sql_middleware::batch",
+                        error = %err,
+                        "postgres cancel request failed; waiting for the statement to finish"
+                    );
+                }
+            }
+            #[cfg(feature = "sqlite")]
+            Interrupter::Sqlite(handle) => handle.interrupt(),
+            Interrupter::None => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mssql"))]
+mod tests {
+    use super::tsql_tx_keyword;
+
+    #[test]
+    fn tsql_blocks_are_not_transactions() {
+        let script = "IF OBJECT_ID('t') IS NULL BEGIN CREATE TABLE t (id INT); END; \
+                      BEGIN TRY INSERT INTO t VALUES (1); END TRY BEGIN CATCH END CATCH;";
+        assert_eq!(tsql_tx_keyword(script), None);
+        assert_eq!(
+            tsql_tx_keyword("INSERT INTO t VALUES ('commit'); BEGIN TRAN; COMMIT;"),
+            Some("BEGIN")
+        );
+        assert_eq!(
+            tsql_tx_keyword("-- rollback\nCOMMIT TRANSACTION"),
+            Some("COMMIT")
+        );
+    }
+}
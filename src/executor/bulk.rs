@@ -0,0 +1,366 @@
+//! Multi-row INSERT that stays under each backend's bind-parameter limit.
+
+use crate::error::SqlMiddlewareDbError;
+use crate::ident::quote;
+use crate::params::IntoParams;
+#[cfg(any_backend)]
+use crate::pool::MiddlewarePoolConnection;
+use crate::types::{DatabaseType, RowValues};
+
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+#[cfg(any_backend)]
+use crate::translation::TranslationMode;
+#[cfg(feature = "turso")]
+use crate::turso::TxMode;
+
+impl DatabaseType {
+    /// Maximum number of bind parameters a single statement may use on this backend.
+    ///
+    /// `SQLite` allowed 999 before 3.32.0 and 32766 since; the linked library's version decides.
+    /// SQL Server caps RPC calls at 2100 parameters, two of which `sp_executesql` uses itself.
+    #[must_use]
+    pub fn max_bind_params(&self) -> usize {
+        match self {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => 65_535,
+            #[cfg(feature = "sqlite")]
+            DatabaseType::Sqlite => {
+                if rusqlite::version_number() >= 3_032_000 {
+                    32_766
+                } else {
+                    999
+                }
+            }
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => 2_098,
+            #[cfg(feature = "turso")]
+            DatabaseType::Turso => 32_766,
+            #[allow(unreachable_patterns)]
+            _ => usize::MAX,
+        }
+    }
+
+    /// Maximum number of rows a single `INSERT ... VALUES` may list on this backend.
+    ///
+    /// SQL Server rejects more than 1000 row value expressions in one `VALUES` clause, however few
+    /// parameters they bind. The other backends are limited only by bind parameters.
+    #[must_use]
+    pub fn max_insert_rows(&self) -> usize {
+        match self {
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => 1_000,
+            #[allow(unreachable_patterns)]
+            _ => usize::MAX,
+        }
+    }
+}
+
+/// One INSERT statement and its flattened parameters.
+struct Chunk {
+    sql: String,
+    params: Vec<RowValues>,
+}
+
+/// Split `rows` into INSERT statements that each bind at most `db_type.max_bind_params()` values
+/// and list at most `db_type.max_insert_rows()` rows.
+fn plan_chunks(
+    db_type: &DatabaseType,
+    table: &str,
+    columns: &[&str],
+    rows: &[Vec<RowValues>],
+) -> Result<Vec<Chunk>, SqlMiddlewareDbError> {
+    if columns.is_empty() {
+        return Err(SqlMiddlewareDbError::ConfigError(
+            "bulk_insert needs at least one column".into(),
+        ));
+    }
+    if let Some(idx) = rows.iter().position(|row| row.len() != columns.len()) {
+        return Err(SqlMiddlewareDbError::ExecutionError(format!(
+            "bulk_insert row {idx} has {} values for {} columns",
+            rows[idx].len(),
+            columns.len()
+        )));
+    }
+    let rows_per_chunk = db_type.max_bind_params() / columns.len();
+    if rows_per_chunk == 0 {
+        return Err(SqlMiddlewareDbError::ConfigError(format!(
+            "bulk_insert with {} columns exceeds the {db_type:?} limit of {} parameters per statement",
+            columns.len(),
+            db_type.max_bind_params()
+        )));
+    }
+    let rows_per_chunk = rows_per_chunk.min(db_type.max_insert_rows());
+
+    let column_list = columns
+        .iter()
+        .map(|column| quote(column, db_type))
+        .collect::<Result<Vec<_>, _>>()?
+        .join(", ");
+    let prefix = format!(
+        "INSERT INTO {} ({column_list}) VALUES ",
+        quote(table, db_type)?
+    );
+
+    Ok(rows
+        .chunks(rows_per_chunk)
+        .map(|chunk| {
+            let mut sql = prefix.clone();
+            let mut next = 1;
+            for (row_idx, _) in chunk.iter().enumerate() {
+                if row_idx > 0 {
+                    sql.push_str(", ");
+                }
+                sql.push('(');
+                for col_idx in 0..columns.len() {
+                    if col_idx > 0 {
+                        sql.push_str(", ");
+                    }
+                    sql.push_str(&db_type.placeholder(next));
+                    next += 1;
+                }
+                sql.push(')');
+            }
+            Chunk {
+                sql,
+                params: chunk.iter().flatten().cloned().collect(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(any_backend)]
+impl MiddlewarePoolConnection {
+    /// Insert `rows` into `table` with multi-row `INSERT ... VALUES` statements.
+    ///
+    /// Rows are split so no statement binds more parameters or lists more rows than the backend
+    /// allows (see [`DatabaseType::max_bind_params`] and [`DatabaseType::max_insert_rows`]), and
+    /// every statement runs inside one transaction, so either all rows are inserted or none are.
+    /// Placeholders are emitted in the backend's native style; translation settings do not apply.
+    /// Returns the number of rows inserted.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if `columns` is empty or too wide for a single
+    /// row, `ExecutionError` if a row's length differs from `columns`, or the backend error from
+    /// any statement (after rolling back).
+    pub async fn bulk_insert(
+        &mut self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<RowValues>],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        let chunks = plan_chunks(&self.database_type(), table, columns, rows)?;
+        if chunks.is_empty() {
+            return Ok(0);
+        }
+
+        let result = self.insert_chunks(&chunks).await;
+        self.record_result(&result);
+        result
+    }
+
+    /// Run every chunk in one transaction and return the rows inserted.
+    ///
+    /// The chunks go through the transaction's query builder with translation off, since they are
+    /// written in the backend's native placeholder style.
+    async fn insert_chunks(&mut self, chunks: &[Chunk]) -> Result<usize, SqlMiddlewareDbError> {
+        let mut inserted = 0;
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { .. } => {
+                let tx = self.begin_postgres_transaction().await?.untracked();
+                for chunk in chunks {
+                    // Dropping the tokio-postgres transaction on error rolls it back.
+                    inserted += tx
+                        .query_builder(&chunk.sql)
+                        .params(&chunk.params)
+                        .translation(TranslationMode::ForceOff)
+                        .dml()
+                        .await?;
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                let mut tx = sqlite::begin_transaction(self).await?;
+                for chunk in chunks {
+                    let step = tx
+                        .query_builder(&chunk.sql)
+                        .params(&chunk.params)
+                        .translation(TranslationMode::ForceOff)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => inserted += rows,
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => {
+                let mut tx = self.begin_mssql_transaction().await?;
+                for chunk in chunks {
+                    let step = tx
+                        .query_builder(&chunk.sql)
+                        .params(&chunk.params)
+                        .translation(TranslationMode::ForceOff)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => inserted += rows,
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { .. } => {
+                let tx = self.begin_turso_transaction(TxMode::Interactive).await?;
+                for chunk in chunks {
+                    let step = tx
+                        .query_builder(&chunk.sql)
+                        .params(&chunk.params)
+                        .translation(TranslationMode::ForceOff)
+                        .dml()
+                        .await;
+                    match step {
+                        Ok(rows) => inserted += rows,
+                        Err(err) => {
+                            tx.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                tx.commit().await?;
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(SqlMiddlewareDbError::Unimplemented(
+                    "This database type is not enabled in the current build".to_string(),
+                ));
+            }
+        }
+        Ok(inserted)
+    }
+}
+
+/// Fluent front end for [`MiddlewarePoolConnection::bulk_insert`].
+///
+/// Rows can be anything [`IntoParams`] accepts, such as tuples of plain values, and are chunked
+/// into multi-row `INSERT ... VALUES` statements under the backend's parameter limit when the
+/// builder runs.
+///
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+///
+/// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+/// let users = [(1_i64, "ann"), (2, "bob")];
+/// let inserted = InsertBuilder::new("users")
+///     .columns(["id", "name"])
+///     .rows(users)
+///     .execute(conn)
+///     .await?;
+/// assert_eq!(inserted, 2);
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InsertBuilder {
+    table: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<RowValues>>,
+}
+
+impl InsertBuilder {
+    /// Start an insert into `table`.
+    #[must_use]
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the columns each row provides values for, in order.
+    #[must_use]
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Append one row.
+    #[must_use]
+    pub fn row<'q>(mut self, row: impl IntoParams<'q>) -> Self {
+        self.rows.push(row.into_params().into_owned());
+        self
+    }
+
+    /// Append every row from `rows`.
+    #[must_use]
+    pub fn rows<'q, I>(mut self, rows: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: IntoParams<'q>,
+    {
+        self.rows
+            .extend(rows.into_iter().map(|row| row.into_params().into_owned()));
+        self
+    }
+
+    /// The statements and parameters [`execute`](Self::execute) would run on `db_type`.
+    ///
+    /// # Errors
+    /// The same validation errors as [`execute`](Self::execute).
+    pub fn statements(
+        &self,
+        db_type: &DatabaseType,
+    ) -> Result<Vec<(String, Vec<RowValues>)>, SqlMiddlewareDbError> {
+        let chunks = plan_chunks(db_type, &self.table, &self.column_refs(), &self.rows)?;
+        Ok(chunks
+            .into_iter()
+            .map(|chunk| (chunk.sql, chunk.params))
+            .collect())
+    }
+
+    /// Insert the rows in one transaction and return how many were inserted.
+    ///
+    /// # Errors
+    /// See [`MiddlewarePoolConnection::bulk_insert`].
+    #[cfg(any_backend)]
+    pub async fn execute(
+        self,
+        conn: &mut MiddlewarePoolConnection,
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        conn.bulk_insert(&self.table, &self.column_refs(), &self.rows)
+            .await
+    }
+
+    fn column_refs(&self) -> Vec<&str> {
+        self.columns.iter().map(String::as_str).collect()
+    }
+}
+
+#[cfg(all(test, feature = "mssql"))]
+mod tests {
+    use super::plan_chunks;
+    use crate::types::{DatabaseType, RowValues};
+
+    #[test]
+    fn mssql_chunks_stop_at_1000_rows() {
+        let rows: Vec<Vec<RowValues>> = (0..2_500).map(|i| vec![RowValues::Int(i)]).collect();
+        let chunks = plan_chunks(&DatabaseType::Mssql, "t", &["a"], &rows).unwrap();
+        let sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.params.len()).collect();
+        assert_eq!(sizes, vec![1_000, 1_000, 500]);
+        assert!(chunks[0].sql.ends_with("(@P1000)"));
+    }
+}
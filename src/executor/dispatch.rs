@@ -1,7 +1,8 @@
 use crate::error::SqlMiddlewareDbError;
-use crate::pool::MiddlewarePoolConnection;
+use crate::pool::{MiddlewarePoolConnection, echo};
 use crate::query_builder::QueryBuilder;
 use crate::results::ResultSet;
+use crate::translation::QueryOptions;
 use crate::types::RowValues;
 
 #[cfg(feature = "mssql")]
@@ -50,12 +51,35 @@ pub fn query<'a>(target: impl Into<QueryTarget<'a>>, sql: &'a str) -> QueryBuild
 }
 
 impl MiddlewarePoolConnection {
-    /// Executes a batch of SQL queries within a transaction by delegating to the specific database module.
+    /// Execute a batch of SQL statements in one transaction.
+    ///
+    /// This is [`execute_batch_with`](Self::execute_batch_with) with default options, so every
+    /// backend runs the batch as [`BatchTxMode::Single`](crate::BatchTxMode::Single): the
+    /// statements commit together, a failure rolls all of them back, and `BEGIN`/`COMMIT` in the
+    /// script are rejected. Use [`BatchTxMode::Script`](crate::BatchTxMode::Script) for scripts
+    /// that manage their own transactions. If the connection is already in a transaction, the
+    /// batch runs inside it.
     ///
     /// # Errors
-    /// Returns an error if the selected backend cannot execute the batch or the database responds with an error.
+    /// Returns `ExecutionError` if the script contains `BEGIN`, `COMMIT` or `ROLLBACK`, or the
+    /// backend's error if a statement fails.
     pub async fn execute_batch(&mut self, query: &str) -> Result<(), SqlMiddlewareDbError> {
-        match self {
+        self.execute_batch_with(query, QueryOptions::default())
+            .await
+    }
+
+    /// Run `query` with the driver's own batch call, in one transaction.
+    pub(crate) async fn execute_batch_single(
+        &mut self,
+        query: &str,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        if self.in_transaction().await? {
+            // A second BEGIN would fail, or its COMMIT would end the caller's transaction early.
+            return self.run_control(query).await;
+        }
+        self.throttle().await?;
+        echo::statement(self.debug_echo_flag(), query, query, &[]);
+        let result = match self {
             #[cfg(feature = "postgres")]
             MiddlewarePoolConnection::Postgres {
                 client: pg_client, ..
@@ -68,16 +92,42 @@ impl MiddlewarePoolConnection {
             #[cfg(feature = "mssql")]
             MiddlewarePoolConnection::Mssql {
                 conn: mssql_client, ..
-            } => mssql::execute_batch(mssql_client, query).await,
+            } => {
+                let mut tx = mssql::begin_transaction(mssql_client).await?;
+                match tx.execute_batch(query).await {
+                    Ok(()) => tx.commit().await.map(|_| ()),
+                    Err(err) => {
+                        // Some errors already ended the transaction on the server, so this may
+                        // fail; either way nothing was committed.
+                        let _ = tx.rollback().await;
+                        Err(err)
+                    }
+                }
+            }
             #[cfg(feature = "turso")]
             MiddlewarePoolConnection::Turso {
                 conn: turso_conn, ..
-            } => turso::execute_batch(turso_conn, query).await,
+            } => {
+                if turso_conn.is_autocommit()? {
+                    let tx = turso::begin_transaction(turso_conn).await?;
+                    match tx.execute_batch(query).await {
+                        Ok(()) => tx.commit().await.map(|_| ()),
+                        Err(err) => {
+                            tx.rollback().await?;
+                            Err(err)
+                        }
+                    }
+                } else {
+                    turso::execute_batch(turso_conn, query).await
+                }
+            }
             #[allow(unreachable_patterns)]
             _ => Err(SqlMiddlewareDbError::Unimplemented(
                 "This database type is not enabled in the current build".to_string(),
             )),
-        }
+        };
+        self.record_result(&result);
+        result
     }
 
     /// Start a fluent query builder that can translate placeholders before executing.
@@ -109,7 +159,8 @@ pub(crate) async fn execute_select_dispatch(
     query: &str,
     params: &[RowValues],
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
-    match conn {
+    conn.throttle().await?;
+    let result = match conn {
         #[cfg(feature = "postgres")]
         MiddlewarePoolConnection::Postgres {
             client: pg_client, ..
@@ -131,7 +182,9 @@ pub(crate) async fn execute_select_dispatch(
         _ => Err(SqlMiddlewareDbError::Unimplemented(
             "This database type is not enabled in the current build".to_string(),
         )),
-    }
+    };
+    conn.record_result(&result);
+    result
 }
 
 pub(crate) async fn execute_select_prepared_dispatch(
@@ -139,7 +192,8 @@ pub(crate) async fn execute_select_prepared_dispatch(
     query: &str,
     params: &[RowValues],
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
-    match conn {
+    conn.throttle().await?;
+    let result = match conn {
         #[cfg(feature = "postgres")]
         MiddlewarePoolConnection::Postgres {
             client: pg_client, ..
@@ -161,7 +215,46 @@ pub(crate) async fn execute_select_prepared_dispatch(
         _ => Err(SqlMiddlewareDbError::Unimplemented(
             "This database type is not enabled in the current build".to_string(),
         )),
-    }
+    };
+    conn.record_result(&result);
+    result
+}
+
+pub(crate) async fn execute_select_multi_dispatch(
+    conn: &mut MiddlewarePoolConnection,
+    query: &str,
+    params: &[RowValues],
+) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+    conn.throttle().await?;
+    let result = match conn {
+        #[cfg(feature = "postgres")]
+        MiddlewarePoolConnection::Postgres {
+            client: pg_client, ..
+        } => postgres::execute_select_multi(pg_client, query, params).await,
+        #[cfg(feature = "sqlite")]
+        MiddlewarePoolConnection::Sqlite { .. } => {
+            let sqlite_client = conn.sqlite_conn_mut()?;
+            sqlite::execute_select(sqlite_client, query, params)
+                .await
+                .map(|result_set| vec![result_set])
+        }
+        #[cfg(feature = "mssql")]
+        MiddlewarePoolConnection::Mssql {
+            conn: mssql_client, ..
+        } => mssql::execute_select_multi(mssql_client, query, params).await,
+        #[cfg(feature = "turso")]
+        MiddlewarePoolConnection::Turso {
+            conn: turso_conn, ..
+        } => turso::execute_select(turso_conn, query, params)
+            .await
+            .map(|result_set| vec![result_set]),
+        #[allow(unreachable_patterns)]
+        _ => Err(SqlMiddlewareDbError::Unimplemented(
+            "This database type is not enabled in the current build".to_string(),
+        )),
+    };
+    conn.record_result(&result);
+    result
 }
 
 pub(crate) async fn execute_dml_dispatch(
@@ -169,12 +262,15 @@ pub(crate) async fn execute_dml_dispatch(
     query: &str,
     params: &[RowValues],
 ) -> Result<usize, SqlMiddlewareDbError> {
-    match conn {
+    conn.throttle().await?;
+    let result = match conn {
         #[cfg(feature = "postgres")]
         MiddlewarePoolConnection::Postgres {
             client: pg_client, ..
-        } => postgres::execute_dml_on_client(pg_client, query, params, "postgres execute error")
-            .await,
+        } => {
+            postgres::execute_dml_on_client(pg_client, query, params, "postgres execute error")
+                .await
+        }
         #[cfg(feature = "sqlite")]
         MiddlewarePoolConnection::Sqlite { .. } => {
             let sqlite_client = conn.sqlite_conn_mut()?;
@@ -192,7 +288,9 @@ pub(crate) async fn execute_dml_dispatch(
         _ => Err(SqlMiddlewareDbError::Unimplemented(
             "This database type is not enabled in the current build".to_string(),
         )),
-    }
+    };
+    conn.record_result(&result);
+    result
 }
 
 pub(crate) async fn execute_dml_prepared_dispatch(
@@ -200,7 +298,8 @@ pub(crate) async fn execute_dml_prepared_dispatch(
     query: &str,
     params: &[RowValues],
 ) -> Result<usize, SqlMiddlewareDbError> {
-    match conn {
+    conn.throttle().await?;
+    let result = match conn {
         #[cfg(feature = "postgres")]
         MiddlewarePoolConnection::Postgres {
             client: pg_client, ..
@@ -222,5 +321,7 @@ pub(crate) async fn execute_dml_prepared_dispatch(
         _ => Err(SqlMiddlewareDbError::Unimplemented(
             "This database type is not enabled in the current build".to_string(),
         )),
-    }
+    };
+    conn.record_result(&result);
+    result
 }
@@ -1,10 +1,28 @@
+#[cfg(any_backend)]
+mod atomic;
+mod batch;
+mod bulk;
+#[cfg(any_backend)]
 mod dispatch;
+#[cfg(any_backend)]
+mod progress;
+#[cfg(any_backend)]
 mod targets;
+#[cfg(any_backend)]
+mod versioned;
 
+pub use batch::{BatchOptions, BatchTxMode};
+pub use bulk::InsertBuilder;
+#[cfg(any_backend)]
 pub use dispatch::{execute_batch, query};
+#[cfg(any_backend)]
 pub(crate) use dispatch::{
     execute_dml_dispatch, execute_dml_prepared_dispatch, execute_select_dispatch,
-    execute_select_prepared_dispatch,
+    execute_select_multi_dispatch, execute_select_prepared_dispatch,
 };
+#[cfg(any_backend)]
+pub use progress::{BatchProgress, BatchStream};
+#[cfg(any_backend)]
 pub(crate) use targets::QueryTargetKind;
+#[cfg(any_backend)]
 pub use targets::{BatchTarget, QueryTarget};
@@ -0,0 +1,192 @@
+use std::time::{Duration, Instant};
+
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::MiddlewarePoolConnection;
+use crate::translation::core::scanner::code_words;
+use crate::translation::split_statements;
+
+/// One statement of a [`BatchStream`] finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// Zero-based position of the statement in the batch.
+    pub index: usize,
+    /// Number of statements in the batch.
+    pub total: usize,
+    /// The statement's SQL, trimmed and without its `;`.
+    pub statement: String,
+    /// Rows inserted, updated, or deleted by the statement. Postgres also counts rows a query
+    /// returned, and `SQLite` counts rows changed by triggers.
+    pub rows_affected: usize,
+    /// How long the statement took.
+    pub elapsed: Duration,
+    /// Time since the stream started.
+    pub since_start: Duration,
+}
+
+/// Statements of a batch, executed one at a time as the stream is polled.
+///
+/// Created by [`MiddlewarePoolConnection::execute_batch_stream`]. Each call to
+/// [`next`](BatchStream::next) runs the next statement and reports it. After an error the
+/// stream yields nothing more.
+pub struct BatchStream<'c> {
+    conn: &'c mut MiddlewarePoolConnection,
+    statements: Vec<String>,
+    next: usize,
+    started: Instant,
+    /// Whether the script has opened a transaction it has not yet ended.
+    in_script_tx: bool,
+    done: bool,
+}
+
+impl BatchStream<'_> {
+    /// Number of statements in the batch.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Run the next statement, or return `None` once every statement has run or one has failed.
+    ///
+    /// If a statement fails while a transaction the script opened is still open, that transaction
+    /// is rolled back before the error is returned.
+    pub async fn next(&mut self) -> Option<Result<BatchProgress, SqlMiddlewareDbError>> {
+        if self.done {
+            return None;
+        }
+        let Some(statement) = self.statements.get(self.next) else {
+            self.done = true;
+            return None;
+        };
+        let statement_started = Instant::now();
+        match execute_counted(self.conn, statement).await {
+            Ok(rows_affected) => {
+                self.in_script_tx = opens_or_ends_tx(statement).unwrap_or(self.in_script_tx);
+                let progress = BatchProgress {
+                    index: self.next,
+                    total: self.statements.len(),
+                    statement: statement.clone(),
+                    rows_affected,
+                    elapsed: statement_started.elapsed(),
+                    since_start: self.started.elapsed(),
+                };
+                self.next += 1;
+                Some(Ok(progress))
+            }
+            Err(err) => {
+                self.done = true;
+                if self.in_script_tx {
+                    let _ = execute_counted(self.conn, "ROLLBACK").await;
+                    self.in_script_tx = false;
+                }
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl MiddlewarePoolConnection {
+    /// Execute a batch one statement at a time, reporting progress as each statement finishes.
+    ///
+    /// Meant for long schema loads and data fixups: the statements are split with
+    /// [`split_statements`] and nothing runs until the stream is polled. Unlike
+    /// [`execute_batch`](MiddlewarePoolConnection::execute_batch), the batch is not wrapped in a
+    /// transaction, so each statement's work is committed by the time it is reported. Put
+    /// `BEGIN`/`COMMIT` in the script to group statements; dropping the stream between them leaves
+    /// that transaction open on the connection.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let script = "CREATE TABLE big (id INTEGER); INSERT INTO big SELECT 1; CREATE INDEX big_id ON big (id);";
+    /// let mut stream = conn.execute_batch_stream(script);
+    /// while let Some(step) = stream.next().await {
+    ///     let step = step?;
+    ///     println!("{}/{} in {:?}", step.index + 1, step.total, step.elapsed);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[must_use = "the batch runs only as the stream is polled"]
+    pub fn execute_batch_stream(&mut self, sql: &str) -> BatchStream<'_> {
+        BatchStream {
+            conn: self,
+            statements: split_statements(sql)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            next: 0,
+            started: Instant::now(),
+            in_script_tx: false,
+            done: false,
+        }
+    }
+}
+
+/// `Some(true)` for statements that start a transaction, `Some(false)` for ones that end it.
+pub(super) fn opens_or_ends_tx(statement: &str) -> Option<bool> {
+    // Words outside comments and literals, so a leading `-- begin ...` comment doesn't count.
+    let mut words = code_words(statement).into_iter();
+    let first = words.next()?.to_ascii_lowercase();
+    match first.as_str() {
+        "begin" | "start" => Some(true),
+        "commit" | "end" => Some(false),
+        // `ROLLBACK TO [SAVEPOINT]` keeps the transaction open.
+        "rollback"
+            if !words
+                .next()
+                .is_some_and(|word| word.eq_ignore_ascii_case("to")) =>
+        {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+/// Run one statement outside any middleware-managed transaction.
+pub(super) async fn execute_counted(
+    conn: &mut MiddlewarePoolConnection,
+    statement: &str,
+) -> Result<usize, SqlMiddlewareDbError> {
+    match conn {
+        #[cfg(feature = "postgres")]
+        MiddlewarePoolConnection::Postgres { client, .. } => {
+            let mut rows = 0;
+            for message in client.simple_query(statement).await? {
+                if let tokio_postgres::SimpleQueryMessage::CommandComplete(count) = message {
+                    rows += count;
+                }
+            }
+            crate::postgres::query::convert_affected_rows(rows, "Invalid rows affected count")
+        }
+        #[cfg(feature = "sqlite")]
+        MiddlewarePoolConnection::Sqlite { .. } => {
+            conn.sqlite_conn_mut()?.execute_counted(statement).await
+        }
+        #[cfg(feature = "mssql")]
+        MiddlewarePoolConnection::Mssql { conn, .. } => {
+            let result = tiberius::Query::new(statement)
+                .execute(&mut **conn)
+                .await
+                .map_err(|e| {
+                    SqlMiddlewareDbError::ExecutionError(format!("MSSQL execute error: {e}"))
+                })?;
+            crate::mssql::query::convert_affected_rows(result.rows_affected().iter().sum())
+        }
+        #[cfg(feature = "turso")]
+        MiddlewarePoolConnection::Turso { conn, .. } => {
+            let rows = conn.execute(statement, ()).await.map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("Turso execute error: {e}"))
+            })?;
+            usize::try_from(rows).map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!(
+                    "Turso affected rows conversion error: {e}"
+                ))
+            })
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(SqlMiddlewareDbError::Unimplemented(
+            "This database type is not enabled in the current build".to_string(),
+        )),
+    }
+}
@@ -1,5 +1,7 @@
+use crate::audit::AuditTables;
 use crate::pool::MiddlewarePoolConnection;
 use crate::translation::PlaceholderStyle;
+use crate::types::DatabaseType;
 
 #[cfg(feature = "mssql")]
 use crate::mssql;
@@ -8,6 +10,8 @@ use crate::postgres;
 #[cfg(feature = "postgres")]
 use crate::postgres::typed::PgManager;
 #[cfg(feature = "sqlite")]
+use crate::sqlite::SqliteConnection;
+#[cfg(feature = "sqlite")]
 use crate::sqlite::config::SqliteManager;
 #[cfg(feature = "turso")]
 use crate::turso;
@@ -39,6 +43,8 @@ pub enum BatchTarget<'a> {
 pub struct QueryTarget<'a> {
     pub(crate) kind: QueryTargetKind<'a>,
     translation_default: bool,
+    /// Audited tables of the pool behind this target, where the target knows them.
+    audit: Option<AuditTables>,
 }
 
 pub(crate) enum QueryTargetKind<'a> {
@@ -69,10 +75,14 @@ pub(crate) enum QueryTargetKind<'a> {
     TypedTursoTx {
         conn: &'a mut PooledConnection<'static, TursoManager>,
     },
+    /// Reborrowed from the caller's transaction, so the caller can still finish it afterwards.
     #[cfg(feature = "mssql")]
-    MssqlTx(&'a mut mssql::transaction::Tx<'a>),
+    MssqlTx(mssql::transaction::Tx<'a>),
     #[cfg(feature = "turso")]
     TursoTx(&'a turso::transaction::Tx<'a>),
+    /// Connection inside an open [`sqlite::Tx`](crate::sqlite::Tx).
+    #[cfg(feature = "sqlite")]
+    SqliteTx(&'a mut SqliteConnection),
 }
 
 impl<'a> From<&'a mut MiddlewarePoolConnection> for BatchTarget<'a> {
@@ -106,6 +116,7 @@ impl<'a> From<&'a mut MiddlewarePoolConnection> for QueryTarget<'a> {
     fn from(conn: &'a mut MiddlewarePoolConnection) -> Self {
         QueryTarget {
             translation_default: conn.translation_default(),
+            audit: conn.audit_tables().cloned(),
             kind: QueryTargetKind::Connection(conn),
         }
     }
@@ -124,6 +135,7 @@ impl<'a> QueryTarget<'a> {
         };
         QueryTarget {
             translation_default: false,
+            audit: None,
             kind,
         }
     }
@@ -142,6 +154,7 @@ impl<'a> QueryTarget<'a> {
         };
         QueryTarget {
             translation_default: false,
+            audit: None,
             kind,
         }
     }
@@ -152,17 +165,34 @@ impl<'a> From<&'a postgres::transaction::Tx<'a>> for QueryTarget<'a> {
     fn from(tx: &'a postgres::transaction::Tx<'a>) -> Self {
         QueryTarget {
             translation_default: false,
+            audit: tx.pool().audit_tables().cloned(),
             kind: QueryTargetKind::PostgresTx(tx),
         }
     }
 }
 
 #[cfg(feature = "mssql")]
-impl<'a> From<&'a mut mssql::transaction::Tx<'a>> for QueryTarget<'a> {
-    fn from(tx: &'a mut mssql::transaction::Tx<'a>) -> Self {
+impl<'a> From<&'a mut mssql::transaction::Tx<'_>> for QueryTarget<'a> {
+    fn from(tx: &'a mut mssql::transaction::Tx<'_>) -> Self {
         QueryTarget {
             translation_default: false,
-            kind: QueryTargetKind::MssqlTx(tx),
+            audit: tx.pool().audit_tables().cloned(),
+            kind: QueryTargetKind::MssqlTx(tx.reborrow()),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> QueryTarget<'a> {
+    pub(crate) fn from_sqlite_tx(
+        conn: &'a mut SqliteConnection,
+        translation_default: bool,
+        audit: Option<AuditTables>,
+    ) -> Self {
+        QueryTarget {
+            translation_default,
+            audit,
+            kind: QueryTargetKind::SqliteTx(conn),
         }
     }
 }
@@ -172,6 +202,7 @@ impl<'a> From<&'a turso::transaction::Tx<'a>> for QueryTarget<'a> {
     fn from(tx: &'a turso::transaction::Tx<'a>) -> Self {
         QueryTarget {
             translation_default: false,
+            audit: tx.pool().audit_tables().cloned(),
             kind: QueryTargetKind::TursoTx(tx),
         }
     }
@@ -190,6 +221,7 @@ impl<'a> QueryTarget<'a> {
         };
         QueryTarget {
             translation_default: true,
+            audit: None,
             kind,
         }
     }
@@ -201,6 +233,55 @@ impl QueryTarget<'_> {
         self.translation_default
     }
 
+    /// Statement echo flag of the pool behind this target; typed connections and transactions
+    /// begun on a raw driver client have none.
+    pub(crate) fn debug_echo_flag(&self) -> Option<&std::sync::atomic::AtomicBool> {
+        match &self.kind {
+            QueryTargetKind::Connection(conn) => conn.debug_echo_flag(),
+            #[cfg(feature = "postgres")]
+            QueryTargetKind::PostgresTx(tx) => tx.pool().debug_echo_flag(),
+            #[cfg(feature = "mssql")]
+            QueryTargetKind::MssqlTx(tx) => tx.pool().debug_echo_flag(),
+            #[cfg(feature = "turso")]
+            QueryTargetKind::TursoTx(tx) => tx.pool().debug_echo_flag(),
+            #[cfg(feature = "sqlite")]
+            QueryTargetKind::SqliteTx(conn) => conn.pool.debug_echo_flag(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Audited tables of the pool behind this target; typed connections and transactions begun on
+    /// a raw driver client have none.
+    pub(crate) fn audit_tables(&self) -> Option<&AuditTables> {
+        self.audit.as_ref()
+    }
+
+    /// Soft-delete column of the pool behind this target, or the default.
+    pub(crate) fn soft_delete_column(&self) -> &str {
+        match &self.kind {
+            QueryTargetKind::Connection(conn) => conn.soft_delete_column(),
+            #[allow(unreachable_patterns)]
+            _ => crate::query_builder::DEFAULT_SOFT_DELETE_COLUMN,
+        }
+    }
+
+    /// Whether this target runs inside a transaction the caller opened.
+    #[must_use]
+    pub(crate) fn in_transaction(&self) -> bool {
+        match &self.kind {
+            QueryTargetKind::Connection(_) => false,
+            #[cfg(feature = "postgres")]
+            QueryTargetKind::TypedPostgres { .. } => false,
+            #[cfg(feature = "sqlite")]
+            QueryTargetKind::TypedSqlite { .. } => false,
+            #[cfg(feature = "turso")]
+            QueryTargetKind::TypedTurso { .. } => false,
+            #[allow(unreachable_patterns)]
+            _ => true,
+        }
+    }
+
     #[must_use]
     pub(crate) fn translation_target(&self) -> Option<PlaceholderStyle> {
         match &self.kind {
@@ -221,12 +302,35 @@ impl QueryTarget<'_> {
             QueryTargetKind::TypedTurso { .. } => Some(PlaceholderStyle::Sqlite),
             #[cfg(feature = "turso")]
             QueryTargetKind::TypedTursoTx { .. } => Some(PlaceholderStyle::Sqlite),
+            #[cfg(feature = "sqlite")]
+            QueryTargetKind::SqliteTx(_) => Some(PlaceholderStyle::Sqlite),
             #[cfg(feature = "mssql")]
-            QueryTargetKind::MssqlTx(_) => None,
+            QueryTargetKind::MssqlTx(_) => Some(PlaceholderStyle::Mssql),
             #[allow(unreachable_patterns)]
             _ => None,
         }
     }
+
+    #[must_use]
+    pub(crate) fn database_type(&self) -> DatabaseType {
+        match &self.kind {
+            QueryTargetKind::Connection(conn) => conn.database_type(),
+            #[cfg(feature = "postgres")]
+            QueryTargetKind::PostgresTx(_)
+            | QueryTargetKind::TypedPostgres { .. }
+            | QueryTargetKind::TypedPostgresTx { .. } => DatabaseType::Postgres,
+            #[cfg(feature = "sqlite")]
+            QueryTargetKind::TypedSqlite { .. }
+            | QueryTargetKind::TypedSqliteTx { .. }
+            | QueryTargetKind::SqliteTx(_) => DatabaseType::Sqlite,
+            #[cfg(feature = "turso")]
+            QueryTargetKind::TursoTx(_)
+            | QueryTargetKind::TypedTurso { .. }
+            | QueryTargetKind::TypedTursoTx { .. } => DatabaseType::Turso,
+            #[cfg(feature = "mssql")]
+            QueryTargetKind::MssqlTx(_) => DatabaseType::Mssql,
+        }
+    }
 }
 
 pub(crate) fn translation_target(conn: &MiddlewarePoolConnection) -> Option<PlaceholderStyle> {
@@ -238,7 +342,7 @@ pub(crate) fn translation_target(conn: &MiddlewarePoolConnection) -> Option<Plac
         #[cfg(feature = "turso")]
         MiddlewarePoolConnection::Turso { .. } => Some(PlaceholderStyle::Sqlite),
         #[cfg(feature = "mssql")]
-        MiddlewarePoolConnection::Mssql { .. } => None,
+        MiddlewarePoolConnection::Mssql { .. } => Some(PlaceholderStyle::Mssql),
         #[allow(unreachable_patterns)]
         _ => None,
     }
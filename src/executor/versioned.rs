@@ -0,0 +1,93 @@
+//! Optimistic concurrency with a version column.
+
+use crate::error::SqlMiddlewareDbError;
+use crate::ident::quote;
+use crate::pool::MiddlewarePoolConnection;
+use crate::translation::TranslationMode;
+use crate::types::RowValues;
+
+/// Column holding the row version, an integer bumped by every versioned update.
+const VERSION_COLUMN: &str = "version";
+
+impl MiddlewarePoolConnection {
+    /// Update one row only if it is still at `expected_version`, and bump its version.
+    ///
+    /// Runs `UPDATE table SET col = ..., version = version + 1 WHERE key = ... AND version = ...`
+    /// with the backend's native placeholders and returns the row's new version. `table` needs an
+    /// integer `version` column; `key` is the key column and the row's value in it. Names are
+    /// quoted with [`ident::quote`](crate::ident::quote).
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let version = 3; // read together with the row
+    /// match conn
+    ///     .update_versioned(
+    ///         "accounts",
+    ///         ("id", RowValues::Int(7)),
+    ///         version,
+    ///         &[("balance", RowValues::Int(90))],
+    ///     )
+    ///     .await
+    /// {
+    ///     Ok(new_version) => assert_eq!(new_version, 4),
+    ///     Err(SqlMiddlewareDbError::StaleVersion { .. }) => { /* reload and retry */ }
+    ///     Err(err) => return Err(err),
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::StaleVersion` if no row has that key at `expected_version`,
+    /// `ParameterError` if a name cannot be quoted, or the backend error from the update.
+    pub async fn update_versioned(
+        &mut self,
+        table: &str,
+        key: (&str, RowValues),
+        expected_version: i64,
+        set_columns: &[(&str, RowValues)],
+    ) -> Result<i64, SqlMiddlewareDbError> {
+        let db_type = self.database_type();
+        let (key_column, key_value) = key;
+        let version = quote(VERSION_COLUMN, &db_type)?;
+
+        let mut assignments = Vec::with_capacity(set_columns.len() + 1);
+        let mut params = Vec::with_capacity(set_columns.len() + 2);
+        for (column, value) in set_columns {
+            params.push(value.clone());
+            assignments.push(format!(
+                "{} = {}",
+                quote(column, &db_type)?,
+                db_type.placeholder(params.len())
+            ));
+        }
+        assignments.push(format!("{version} = {version} + 1"));
+        params.push(key_value.clone());
+        params.push(RowValues::Int(expected_version));
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {} = {} AND {version} = {}",
+            quote(table, &db_type)?,
+            assignments.join(", "),
+            quote(key_column, &db_type)?,
+            db_type.placeholder(params.len() - 1),
+            db_type.placeholder(params.len())
+        );
+
+        let updated = self
+            .query(&sql)
+            .params(&params)
+            .translation(TranslationMode::ForceOff)
+            .dml()
+            .await?;
+        if updated == 0 {
+            return Err(SqlMiddlewareDbError::StaleVersion {
+                table: table.to_owned(),
+                key_column: key_column.to_owned(),
+                key: key_value,
+                expected: expected_version,
+            });
+        }
+        Ok(expected_version + 1)
+    }
+}
@@ -0,0 +1,426 @@
+//! Parquet export of query results.
+//!
+//! [`ResultSet::to_parquet`] writes an already-fetched result; [`select_to_parquet`] runs a SELECT
+//! and streams its rows to a file in row groups of [`BATCH_ROWS`], so exports larger than memory
+//! do not have to be materialized as a `ResultSet` first.
+//!
+//! Column types come from [`SchemaHints`] where given and are otherwise inferred from the first
+//! non-null value in the column (all-null columns become nullable strings). Every column is
+//! nullable. `RowValues::JSON` is written as its JSON text.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BinaryBuilder, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::MiddlewarePoolConnection;
+use crate::results::{CustomDbRow, ResultSet};
+use crate::types::RowValues;
+
+/// Rows per record batch (and so per row group) when streaming with [`select_to_parquet`].
+pub const BATCH_ROWS: usize = 8192;
+
+/// Parquet column type for an exported column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetColumnType {
+    Int64,
+    Float64,
+    Utf8,
+    Boolean,
+    Binary,
+    /// Microseconds since the epoch, no time zone (from `RowValues::Timestamp`).
+    TimestampMicros,
+}
+
+impl ParquetColumnType {
+    fn data_type(self) -> DataType {
+        match self {
+            Self::Int64 => DataType::Int64,
+            Self::Float64 => DataType::Float64,
+            Self::Utf8 => DataType::Utf8,
+            Self::Boolean => DataType::Boolean,
+            Self::Binary => DataType::Binary,
+            Self::TimestampMicros => DataType::Timestamp(TimeUnit::Microsecond, None),
+        }
+    }
+
+    fn infer(value: &RowValues) -> Option<Self> {
+        match value {
+            RowValues::Int(_) => Some(Self::Int64),
+            RowValues::Float(_) => Some(Self::Float64),
+            RowValues::Text(_) => Some(Self::Utf8),
+            #[cfg(feature = "json")]
+            RowValues::JSON(_) => Some(Self::Utf8),
+            RowValues::Bool(_) => Some(Self::Boolean),
+            RowValues::Blob(_) => Some(Self::Binary),
+            RowValues::Timestamp(_) => Some(Self::TimestampMicros),
+            RowValues::Null => None,
+            RowValues::Custom(custom) => Self::infer(&custom.encode()),
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(_) => Some(Self::Binary),
+        }
+    }
+}
+
+/// Column types to use instead of inferring them from the data.
+///
+/// ```rust
+/// use sql_middleware::export::{ParquetColumnType, SchemaHints};
+///
+/// let hints = SchemaHints::new()
+///     .column("amount", ParquetColumnType::Float64)
+///     .column("created_at", ParquetColumnType::TimestampMicros);
+/// # let _ = hints;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SchemaHints {
+    columns: HashMap<String, ParquetColumnType>,
+}
+
+impl SchemaHints {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write column `name` as `ty`.
+    #[must_use]
+    pub fn column(mut self, name: &str, ty: ParquetColumnType) -> Self {
+        self.columns.insert(name.to_string(), ty);
+        self
+    }
+}
+
+impl ResultSet {
+    /// Write this result set to `writer` as one Parquet file.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if a value does not fit its column type, or
+    /// `SqlMiddlewareDbError::Other` if Parquet encoding or writing fails.
+    pub fn to_parquet<W: Write + Send>(
+        &self,
+        writer: W,
+        schema_hints: &SchemaHints,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        let column_names = self
+            .get_column_names()
+            .map(|names| names.as_slice())
+            .unwrap_or_default();
+        let mut sink = ParquetSink::new(writer, column_names, schema_hints, &self.results)?;
+        sink.write(&self.results)?;
+        sink.close()
+    }
+}
+
+/// Run `sql` on `conn` and write its rows to a new Parquet file at `path`; returns the row count.
+///
+/// `sql` uses the backend's native placeholders (`$1` for Postgres, `?1` for `SQLite`); placeholder
+/// translation is not applied. Postgres reads through a portal inside a read-only transaction and
+/// `SQLite` writes from its worker thread, both [`BATCH_ROWS`] rows at a time. SQL Server and
+/// Turso fetch the whole result before writing it.
+///
+/// # Errors
+/// Returns query errors from the backend, `SqlMiddlewareDbError::ExecutionError` if a value does
+/// not fit its column type, or `SqlMiddlewareDbError::Other` if the file cannot be written.
+pub async fn select_to_parquet(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    params: &[RowValues],
+    path: impl AsRef<Path>,
+    schema_hints: &SchemaHints,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let path = path.as_ref().to_path_buf();
+    match conn {
+        #[cfg(feature = "postgres")]
+        MiddlewarePoolConnection::Postgres { .. } => {
+            crate::pool::echo::statement(conn.debug_echo_flag(), sql, sql, params);
+            let result = async {
+                let tx = conn.begin_postgres_transaction().await?.untracked();
+                postgres_to_parquet(tx, sql, params, &path, schema_hints).await
+            }
+            .await;
+            conn.record_result(&result);
+            result
+        }
+        #[cfg(feature = "sqlite")]
+        MiddlewarePoolConnection::Sqlite { .. } => {
+            let sql_params = crate::sqlite::Params::convert(params)?;
+            conn.throttle().await?;
+            crate::pool::echo::statement(conn.debug_echo_flag(), sql, sql, params);
+            let sql = sql.to_string();
+            let schema_hints = schema_hints.clone();
+            let result = conn
+                .with_blocking_sqlite(move |raw| {
+                    sqlite_to_parquet(raw, &sql, &sql_params, &path, &schema_hints)
+                })
+                .await;
+            conn.record_result(&result);
+            result
+        }
+        #[allow(unreachable_patterns)]
+        _ => {
+            let result_set = conn.query(sql).params(params).select().await?;
+            let file = File::create(&path).map_err(export_error)?;
+            result_set.to_parquet(file, schema_hints)?;
+            Ok(result_set.results.len())
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+async fn postgres_to_parquet(
+    tx: crate::postgres::Tx<'_>,
+    sql: &str,
+    params: &[RowValues],
+    path: &Path,
+    schema_hints: &SchemaHints,
+) -> Result<usize, SqlMiddlewareDbError> {
+    tx.execute_batch("SET TRANSACTION READ ONLY").await?;
+    let prepared = tx.prepare(sql).await?;
+    let mut portal = tx.bind(&prepared, params).await?;
+
+    let mut chunk = tx.fetch(&mut portal, BATCH_ROWS).await?;
+    let column_names = chunk.get_column_names().cloned().unwrap_or_default();
+    let file = File::create(path).map_err(export_error)?;
+    let mut sink = ParquetSink::new(file, &column_names, schema_hints, &chunk.results)?;
+    let mut total = 0;
+    loop {
+        sink.write(&chunk.results)?;
+        total += chunk.results.len();
+        if portal.is_exhausted() {
+            break;
+        }
+        chunk = tx.fetch(&mut portal, BATCH_ROWS).await?;
+    }
+    sink.close()?;
+    tx.commit().await?;
+    Ok(total)
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_to_parquet(
+    raw: &mut rusqlite::Connection,
+    sql: &str,
+    params: &crate::sqlite::Params,
+    path: &Path,
+    schema_hints: &SchemaHints,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let mut stmt = raw.prepare(sql)?;
+    let column_names = Arc::new(
+        stmt.column_names()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+    );
+    let mut rows = stmt.query(&params.as_refs()[..])?;
+
+    let mut batch = next_sqlite_batch(&mut rows, &column_names)?;
+    let file = File::create(path).map_err(export_error)?;
+    let mut sink = ParquetSink::new(file, &column_names, schema_hints, &batch)?;
+    let mut total = 0;
+    loop {
+        sink.write(&batch)?;
+        total += batch.len();
+        if batch.len() < BATCH_ROWS {
+            break;
+        }
+        batch = next_sqlite_batch(&mut rows, &column_names)?;
+    }
+    sink.close()?;
+    Ok(total)
+}
+
+/// Read up to [`BATCH_ROWS`] rows; a shorter batch means the query is exhausted.
+#[cfg(feature = "sqlite")]
+fn next_sqlite_batch(
+    rows: &mut rusqlite::Rows<'_>,
+    column_names: &Arc<Vec<String>>,
+) -> Result<Vec<CustomDbRow>, SqlMiddlewareDbError> {
+    let mut batch = Vec::with_capacity(BATCH_ROWS);
+    while batch.len() < BATCH_ROWS {
+        let Some(row) = rows.next()? else {
+            break;
+        };
+        let values = (0..column_names.len())
+            .map(|idx| crate::sqlite::query::sqlite_extract_value_sync(row, idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        batch.push(CustomDbRow::new(Arc::clone(column_names), values));
+    }
+    Ok(batch)
+}
+
+/// Arrow schema plus the open Parquet writer; fed one batch of rows at a time.
+struct ParquetSink<W: Write + Send> {
+    schema: SchemaRef,
+    types: Vec<ParquetColumnType>,
+    writer: ArrowWriter<W>,
+}
+
+impl<W: Write + Send> ParquetSink<W> {
+    /// Fix the schema from `schema_hints`, falling back to the first non-null value in `sample`.
+    fn new(
+        writer: W,
+        column_names: &[String],
+        schema_hints: &SchemaHints,
+        sample: &[CustomDbRow],
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let types: Vec<_> = column_names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                schema_hints
+                    .columns
+                    .get(name)
+                    .copied()
+                    .or_else(|| {
+                        sample
+                            .iter()
+                            .find_map(|row| row.rows.get(idx).and_then(ParquetColumnType::infer))
+                    })
+                    .unwrap_or(ParquetColumnType::Utf8)
+            })
+            .collect();
+        let fields: Vec<_> = column_names
+            .iter()
+            .zip(&types)
+            .map(|(name, ty)| Field::new(name, ty.data_type(), true))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let writer =
+            ArrowWriter::try_new(writer, Arc::clone(&schema), None).map_err(export_error)?;
+        Ok(Self {
+            schema,
+            types,
+            writer,
+        })
+    }
+
+    fn write(&mut self, rows: &[CustomDbRow]) -> Result<(), SqlMiddlewareDbError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let columns = self
+            .types
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| build_column(*ty, self.schema.field(idx).name(), idx, rows))
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch =
+            RecordBatch::try_new(Arc::clone(&self.schema), columns).map_err(export_error)?;
+        self.writer.write(&batch).map_err(export_error)
+    }
+
+    fn close(self) -> Result<(), SqlMiddlewareDbError> {
+        self.writer.close().map_err(export_error)?;
+        Ok(())
+    }
+}
+
+fn build_column(
+    ty: ParquetColumnType,
+    name: &str,
+    idx: usize,
+    rows: &[CustomDbRow],
+) -> Result<ArrayRef, SqlMiddlewareDbError> {
+    // Custom values are written as their encoding.
+    let values = rows.iter().map(|row| match row.rows.get(idx) {
+        Some(RowValues::Custom(custom)) => Cow::Owned(custom.encode()),
+        Some(value) => Cow::Borrowed(value),
+        None => Cow::Borrowed(&RowValues::Null),
+    });
+    let mismatch = |value: &RowValues| {
+        SqlMiddlewareDbError::ExecutionError(format!(
+            "parquet export: column {name} is {ty:?} but got {value:?}"
+        ))
+    };
+    let array: ArrayRef = match ty {
+        ParquetColumnType::Int64 => {
+            let mut builder = Int64Builder::with_capacity(rows.len());
+            for value in values {
+                match &*value {
+                    RowValues::Int(v) => builder.append_value(*v),
+                    RowValues::Null => builder.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ParquetColumnType::Float64 => {
+            let mut builder = Float64Builder::with_capacity(rows.len());
+            for value in values {
+                match &*value {
+                    RowValues::Float(v) => builder.append_value(*v),
+                    #[allow(clippy::cast_precision_loss)]
+                    RowValues::Int(v) => builder.append_value(*v as f64),
+                    RowValues::Null => builder.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ParquetColumnType::Utf8 => {
+            let mut builder = StringBuilder::new();
+            for value in values {
+                match &*value {
+                    RowValues::Text(v) => builder.append_value(v),
+                    #[cfg(feature = "json")]
+                    RowValues::JSON(v) => builder.append_value(v.to_string()),
+                    RowValues::Null => builder.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ParquetColumnType::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(rows.len());
+            for value in values {
+                match &*value {
+                    RowValues::Bool(v) => builder.append_value(*v),
+                    RowValues::Null => builder.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ParquetColumnType::Binary => {
+            let mut builder = BinaryBuilder::new();
+            for value in values {
+                match &*value {
+                    RowValues::Blob(v) => builder.append_value(v),
+                    #[cfg(feature = "geo")]
+                    RowValues::Geometry(v) => builder.append_value(v),
+                    RowValues::Null => builder.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ParquetColumnType::TimestampMicros => {
+            let mut builder = TimestampMicrosecondBuilder::with_capacity(rows.len());
+            for value in values {
+                match &*value {
+                    RowValues::Timestamp(v) => builder.append_value(v.and_utc().timestamp_micros()),
+                    RowValues::Null => builder.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    };
+    Ok(array)
+}
+
+fn export_error(err: impl std::fmt::Display) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::Other(format!("parquet export failed: {err}"))
+}
@@ -0,0 +1,500 @@
+//! Spatial values.
+//!
+//! With the `geo` feature, `geometry`/`geography` columns come back as [`RowValues::Geometry`]
+//! holding WKB bytes instead of failing or collapsing to text:
+//!
+//! - Postgres (PostGIS) columns of type `geometry` or `geography` are read as EWKB, which is WKB
+//!   with an optional SRID.
+//! - `SQLite` columns declared as `GEOMETRY`, `POINT`, `POLYGON`, and the like are read from
+//!   `SpatiaLite`'s internal format or a `GeoPackage` blob and rewritten as plain WKB; other blobs
+//!   in those columns are assumed to be WKB already.
+//!
+//! A `Geometry` parameter is bound as its bytes: directly to a PostGIS column, and as a blob
+//! elsewhere. [`geom_from_wkb_sql`] and [`geom_from_wkt_sql`] wrap a placeholder in each
+//! backend's constructor when the column needs a native geometry. The `geo-types` feature adds
+//! `to_wkb`, `from_wkb`, and conversions to and from `geo_types::Geometry`.
+//!
+//! ```rust,no_run
+//! use sql_middleware::geo::geom_from_wkt_sql;
+//! use sql_middleware::prelude::*;
+//!
+//! # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+//! let geom = geom_from_wkt_sql(&conn.database_type(), 2, Some(4326));
+//! // On Postgres: INSERT INTO places (name, geom) VALUES ($1, ST_GeomFromText($2, 4326))
+//! let sql = format!("INSERT INTO places (name, geom) VALUES ($1, {geom})");
+//! conn.query(&sql)
+//!     .params(&[RowValues::Text("home".into()), RowValues::Text("POINT(1 2)".into())])
+//!     .dml()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+
+use crate::error::SqlMiddlewareDbError;
+use crate::types::DatabaseType;
+#[cfg(feature = "geo-types")]
+use crate::types::RowValues;
+
+/// `SQLite` declared types whose blobs are read as geometries.
+#[cfg(feature = "sqlite")]
+const SQLITE_GEOMETRY_TYPES: &[&str] = &[
+    "GEOMETRY",
+    "POINT",
+    "LINESTRING",
+    "POLYGON",
+    "MULTIPOINT",
+    "MULTILINESTRING",
+    "MULTIPOLYGON",
+    "GEOMETRYCOLLECTION",
+];
+
+/// `SpatiaLite` blob markers.
+const SPATIALITE_MBR_END: u8 = 0x7C;
+const SPATIALITE_ENTITY: u8 = 0x69;
+const SPATIALITE_END: u8 = 0xFE;
+/// Offset of the class type, after the start byte, endianness, SRID, and MBR.
+const SPATIALITE_HEADER: usize = 39;
+
+#[cfg(feature = "postgres")]
+pub(crate) fn is_postgres_geometry_type(type_name: &str) -> bool {
+    type_name == "geometry" || type_name == "geography"
+}
+
+#[cfg(feature = "sqlite")]
+pub(crate) fn is_sqlite_geometry_type(decl_type: &str) -> bool {
+    SQLITE_GEOMETRY_TYPES
+        .iter()
+        .any(|ty| decl_type.eq_ignore_ascii_case(ty))
+}
+
+/// SQL that builds a geometry from WKT bound at parameter `idx`, e.g. `ST_GeomFromText($1, 4326)`.
+///
+/// Uses PostGIS on Postgres, `SpatiaLite` functions on `SQLite` and Turso, and the `geometry`
+/// type on SQL Server (which requires an SRID, so `None` becomes `0` there).
+#[must_use]
+pub fn geom_from_wkt_sql(db: &DatabaseType, idx: usize, srid: Option<i32>) -> String {
+    constructor_sql(
+        db,
+        idx,
+        srid,
+        ["ST_GeomFromText", "GeomFromText", "STGeomFromText"],
+    )
+}
+
+/// SQL that builds a geometry from WKB bound at parameter `idx`, e.g. `GeomFromWKB(?1)`.
+///
+/// Use it to store a [`RowValues::Geometry`](crate::RowValues::Geometry) in a `SpatiaLite` or SQL
+/// Server geometry column; PostGIS columns accept the bytes without it.
+#[must_use]
+pub fn geom_from_wkb_sql(db: &DatabaseType, idx: usize, srid: Option<i32>) -> String {
+    constructor_sql(
+        db,
+        idx,
+        srid,
+        ["ST_GeomFromWKB", "GeomFromWKB", "STGeomFromWKB"],
+    )
+}
+
+/// `names` are the PostGIS, `SpatiaLite`, and SQL Server functions, in that order.
+fn constructor_sql(db: &DatabaseType, idx: usize, srid: Option<i32>, names: [&str; 3]) -> String {
+    let (function, srid) = match db {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => (names[0].to_string(), srid),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => (format!("geometry::{}", names[2]), Some(srid.unwrap_or(0))),
+        #[allow(unreachable_patterns)]
+        _ => (names[1].to_string(), srid),
+    };
+    let placeholder = db.placeholder(idx);
+    match srid {
+        Some(srid) => format!("{function}({placeholder}, {srid})"),
+        None => format!("{function}({placeholder})"),
+    }
+}
+
+/// Rewrite a geometry blob read from `SQLite` as WKB.
+///
+/// `SpatiaLite` and `GeoPackage` blobs are converted; anything else is returned unchanged on the
+/// assumption that it is already WKB.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ExecutionError` for a truncated or compressed `SpatiaLite` blob.
+pub fn sqlite_blob_to_wkb(blob: &[u8]) -> Result<Vec<u8>, SqlMiddlewareDbError> {
+    if is_spatialite(blob) {
+        return spatialite_to_wkb(blob);
+    }
+    if blob.len() >= 8 && blob.starts_with(b"GP") {
+        let envelope = match (blob[3] >> 1) & 0x07 {
+            0 => 0,
+            1 => 32,
+            2 | 3 => 48,
+            4 => 64,
+            other => return Err(geo_error(&format!("bad GeoPackage envelope code {other}"))),
+        };
+        return blob
+            .get(8 + envelope..)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| geo_error("truncated GeoPackage header"));
+    }
+    Ok(blob.to_vec())
+}
+
+fn is_spatialite(blob: &[u8]) -> bool {
+    blob.len() > SPATIALITE_HEADER + 4
+        && blob[0] == 0x00
+        && matches!(blob[1], 0x00 | 0x01)
+        && blob[SPATIALITE_HEADER - 1] == SPATIALITE_MBR_END
+        && blob[blob.len() - 1] == SPATIALITE_END
+}
+
+fn spatialite_to_wkb(blob: &[u8]) -> Result<Vec<u8>, SqlMiddlewareDbError> {
+    let order = blob[1];
+    let mut reader = Reader::new(&blob[SPATIALITE_HEADER..blob.len() - 1], order == 0x01);
+    let mut out = Vec::with_capacity(blob.len());
+    copy_spatialite_geometry(&mut reader, order, &mut out)?;
+    Ok(out)
+}
+
+/// Copy one class type and body, replacing `SpatiaLite` entity markers with WKB headers.
+fn copy_spatialite_geometry(
+    reader: &mut Reader<'_>,
+    order: u8,
+    out: &mut Vec<u8>,
+) -> Result<(), SqlMiddlewareDbError> {
+    let (class, class_bytes) = reader.u32_raw()?;
+    if class > 1_000_000 {
+        return Err(geo_error(
+            "compressed SpatiaLite geometries are not supported",
+        ));
+    }
+    let dims = 2 + match class / 1000 {
+        0 => 0,
+        1 | 2 => 1,
+        3 => 2,
+        _ => return Err(geo_error(&format!("unknown SpatiaLite class {class}"))),
+    };
+    out.push(order);
+    out.extend_from_slice(class_bytes);
+    match class % 1000 {
+        1 => out.extend_from_slice(reader.take(dims * 8)?),
+        2 => copy_points(reader, dims, out)?,
+        3 => {
+            let (rings, raw) = reader.u32_raw()?;
+            out.extend_from_slice(raw);
+            for _ in 0..rings {
+                copy_points(reader, dims, out)?;
+            }
+        }
+        4..=7 => {
+            let (count, raw) = reader.u32_raw()?;
+            out.extend_from_slice(raw);
+            for _ in 0..count {
+                if reader.u8()? != SPATIALITE_ENTITY {
+                    return Err(geo_error("missing SpatiaLite entity marker"));
+                }
+                copy_spatialite_geometry(reader, order, out)?;
+            }
+        }
+        kind => {
+            return Err(geo_error(&format!(
+                "unknown SpatiaLite geometry type {kind}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn copy_points(
+    reader: &mut Reader<'_>,
+    dims: usize,
+    out: &mut Vec<u8>,
+) -> Result<(), SqlMiddlewareDbError> {
+    let (count, raw) = reader.u32_raw()?;
+    out.extend_from_slice(raw);
+    out.extend_from_slice(reader.take(count as usize * dims * 8)?);
+    Ok(())
+}
+
+fn geo_error(msg: &str) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ExecutionError(format!("geometry decode error: {msg}"))
+}
+
+/// Byte cursor over a geometry body.
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8], little_endian: bool) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            little_endian,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SqlMiddlewareDbError> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.buf.get(self.pos..end))
+            .ok_or_else(|| geo_error("truncated geometry"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, SqlMiddlewareDbError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read a `u32` and also return its bytes, for copying it unchanged.
+    fn u32_raw(&mut self) -> Result<(u32, &'a [u8]), SqlMiddlewareDbError> {
+        let raw = self.take(4)?;
+        let bytes: [u8; 4] = raw.try_into().expect("four bytes");
+        let value = if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        };
+        Ok((value, raw))
+    }
+
+    #[cfg(feature = "geo-types")]
+    fn u32(&mut self) -> Result<u32, SqlMiddlewareDbError> {
+        Ok(self.u32_raw()?.0)
+    }
+
+    #[cfg(feature = "geo-types")]
+    fn f64(&mut self) -> Result<f64, SqlMiddlewareDbError> {
+        let bytes: [u8; 8] = self.take(8)?.try_into().expect("eight bytes");
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+}
+
+#[cfg(feature = "geo-types")]
+mod wkb {
+    use geo_types::{
+        Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+        Point, Polygon,
+    };
+
+    use super::{Reader, geo_error};
+    use crate::error::SqlMiddlewareDbError;
+
+    const EWKB_Z: u32 = 0x8000_0000;
+    const EWKB_M: u32 = 0x4000_0000;
+    const EWKB_SRID: u32 = 0x2000_0000;
+
+    /// Encode `geometry` as little-endian 2D WKB.
+    #[must_use]
+    pub fn to_wkb(geometry: &Geometry<f64>) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_geometry(geometry, &mut out);
+        out
+    }
+
+    /// Decode WKB or PostGIS EWKB. Z and M values and any SRID are dropped.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if the bytes are not valid WKB.
+    pub fn from_wkb(bytes: &[u8]) -> Result<Geometry<f64>, SqlMiddlewareDbError> {
+        read_geometry(&mut Reader::new(bytes, true))
+    }
+
+    fn write_header(kind: u32, out: &mut Vec<u8>) {
+        out.push(0x01);
+        out.extend_from_slice(&kind.to_le_bytes());
+    }
+
+    fn write_count(count: usize, out: &mut Vec<u8>) {
+        let count = u32::try_from(count).expect("geometry part count fits in u32");
+        out.extend_from_slice(&count.to_le_bytes());
+    }
+
+    fn write_coords(coords: &[Coord<f64>], out: &mut Vec<u8>) {
+        write_count(coords.len(), out);
+        for c in coords {
+            out.extend_from_slice(&c.x.to_le_bytes());
+            out.extend_from_slice(&c.y.to_le_bytes());
+        }
+    }
+
+    fn write_polygon(polygon: &Polygon<f64>, out: &mut Vec<u8>) {
+        write_header(3, out);
+        write_count(1 + polygon.interiors().len(), out);
+        write_coords(&polygon.exterior().0, out);
+        for ring in polygon.interiors() {
+            write_coords(&ring.0, out);
+        }
+    }
+
+    fn write_geometry(geometry: &Geometry<f64>, out: &mut Vec<u8>) {
+        match geometry {
+            Geometry::Point(p) => {
+                write_header(1, out);
+                out.extend_from_slice(&p.x().to_le_bytes());
+                out.extend_from_slice(&p.y().to_le_bytes());
+            }
+            Geometry::Line(line) => {
+                write_header(2, out);
+                write_coords(&[line.start, line.end], out);
+            }
+            Geometry::LineString(ls) => {
+                write_header(2, out);
+                write_coords(&ls.0, out);
+            }
+            Geometry::Polygon(polygon) => write_polygon(polygon, out),
+            Geometry::Rect(rect) => write_polygon(&rect.to_polygon(), out),
+            Geometry::Triangle(triangle) => write_polygon(&triangle.to_polygon(), out),
+            Geometry::MultiPoint(mp) => {
+                write_header(4, out);
+                write_count(mp.0.len(), out);
+                for p in &mp.0 {
+                    write_geometry(&Geometry::Point(*p), out);
+                }
+            }
+            Geometry::MultiLineString(mls) => {
+                write_header(5, out);
+                write_count(mls.0.len(), out);
+                for ls in &mls.0 {
+                    write_header(2, out);
+                    write_coords(&ls.0, out);
+                }
+            }
+            Geometry::MultiPolygon(mp) => {
+                write_header(6, out);
+                write_count(mp.0.len(), out);
+                for polygon in &mp.0 {
+                    write_polygon(polygon, out);
+                }
+            }
+            Geometry::GeometryCollection(gc) => {
+                write_header(7, out);
+                write_count(gc.0.len(), out);
+                for g in &gc.0 {
+                    write_geometry(g, out);
+                }
+            }
+        }
+    }
+
+    /// Read one geometry; each one, including collection members, sets its own byte order.
+    fn read_geometry(reader: &mut Reader<'_>) -> Result<Geometry<f64>, SqlMiddlewareDbError> {
+        reader.little_endian = reader.u8()? == 0x01;
+        let raw = reader.u32()?;
+        if raw & EWKB_SRID != 0 {
+            reader.u32()?;
+        }
+        let base = raw & 0x0FFF_FFFF;
+        let extra = usize::from(raw & EWKB_Z != 0)
+            + usize::from(raw & EWKB_M != 0)
+            + match base / 1000 {
+                1 | 2 => 1,
+                3 => 2,
+                _ => 0,
+            };
+
+        let coord = |reader: &mut Reader<'_>| -> Result<Coord<f64>, SqlMiddlewareDbError> {
+            let c = Coord {
+                x: reader.f64()?,
+                y: reader.f64()?,
+            };
+            for _ in 0..extra {
+                reader.f64()?;
+            }
+            Ok(c)
+        };
+        let line = |reader: &mut Reader<'_>| -> Result<LineString<f64>, SqlMiddlewareDbError> {
+            let count = reader.u32()?;
+            (0..count)
+                .map(|_| coord(reader))
+                .collect::<Result<_, _>>()
+                .map(LineString)
+        };
+
+        Ok(match base % 1000 {
+            1 => Geometry::Point(Point(coord(reader)?)),
+            2 => Geometry::LineString(line(reader)?),
+            3 => {
+                let count = reader.u32()?;
+                let mut rings = (0..count)
+                    .map(|_| line(reader))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter();
+                let exterior = rings.next().unwrap_or(LineString(Vec::new()));
+                Geometry::Polygon(Polygon::new(exterior, rings.collect()))
+            }
+            kind @ 4..=7 => {
+                let count = reader.u32()?;
+                let parts = (0..count)
+                    .map(|_| read_geometry(reader))
+                    .collect::<Result<Vec<_>, _>>()?;
+                collect_parts(kind, parts)?
+            }
+            kind => return Err(geo_error(&format!("unknown WKB geometry type {kind}"))),
+        })
+    }
+
+    fn collect_parts(
+        kind: u32,
+        parts: Vec<Geometry<f64>>,
+    ) -> Result<Geometry<f64>, SqlMiddlewareDbError> {
+        let mismatch = || geo_error(&format!("unexpected member in WKB collection type {kind}"));
+        Ok(match kind {
+            4 => Geometry::MultiPoint(MultiPoint(
+                parts
+                    .into_iter()
+                    .map(|g| match g {
+                        Geometry::Point(p) => Ok(p),
+                        _ => Err(mismatch()),
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            5 => Geometry::MultiLineString(MultiLineString(
+                parts
+                    .into_iter()
+                    .map(|g| match g {
+                        Geometry::LineString(ls) => Ok(ls),
+                        _ => Err(mismatch()),
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            6 => Geometry::MultiPolygon(MultiPolygon(
+                parts
+                    .into_iter()
+                    .map(|g| match g {
+                        Geometry::Polygon(p) => Ok(p),
+                        _ => Err(mismatch()),
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => Geometry::GeometryCollection(GeometryCollection(parts)),
+        })
+    }
+}
+
+#[cfg(feature = "geo-types")]
+pub use wkb::{from_wkb, to_wkb};
+
+#[cfg(feature = "geo-types")]
+impl From<&geo_types::Geometry<f64>> for RowValues {
+    fn from(geometry: &geo_types::Geometry<f64>) -> Self {
+        RowValues::Geometry(to_wkb(geometry).into())
+    }
+}
+
+#[cfg(feature = "geo-types")]
+impl RowValues {
+    /// Decode a [`Geometry`](RowValues::Geometry) value; `Ok(None)` for other variants.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if the bytes are not valid WKB.
+    pub fn to_geo(&self) -> Result<Option<geo_types::Geometry<f64>>, SqlMiddlewareDbError> {
+        self.as_geometry().map(from_wkb).transpose()
+    }
+}
@@ -0,0 +1,101 @@
+//! Safe interpolation of table and column names into SQL.
+//!
+//! Identifiers cannot be bound as parameters, so dynamic DDL and maintenance statements end up
+//! as `format!("DELETE FROM {table}")`, which runs whatever SQL the name smuggles in. Pass names
+//! through [`quote`] first, or reject anything but plain names with [`validate_table`] and
+//! [`validate_column`] when they come from outside the program:
+//!
+//! ```rust
+//! use sql_middleware::ident;
+//! use sql_middleware::prelude::*;
+//!
+//! # fn demo(db_type: &DatabaseType) -> Result<(), SqlMiddlewareDbError> {
+//! let sql = format!("DELETE FROM {}", ident::quote("audit.events", db_type)?);
+//! assert!(ident::validate_table("events; DROP TABLE users").is_err());
+//! # let _ = sql;
+//! # Ok(()) }
+//! ```
+//!
+//! Quoting makes a name case-sensitive on Postgres: `quote("Users", ..)` does not match a table
+//! created as `CREATE TABLE Users`, which Postgres folds to `users`.
+
+use crate::middleware::SqlMiddlewareDbError;
+use crate::query_utils::quote_ident;
+use crate::types::DatabaseType;
+
+/// Quote `ident` for `db_type`, keeping `schema.table` qualification.
+///
+/// Each dot-separated part is wrapped in the backend's quote characters (`"..."`, or `[...]` on
+/// SQL Server) with embedded quote characters doubled, so the result is always read as a name.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ParameterError` if `ident` or one of its parts is empty, or if
+/// it contains a NUL character.
+pub fn quote(ident: &str, db_type: &DatabaseType) -> Result<String, SqlMiddlewareDbError> {
+    if ident.contains('\0') {
+        return Err(invalid(ident, "contains a NUL character"));
+    }
+    if ident.split('.').any(str::is_empty) {
+        return Err(invalid(ident, "has an empty part"));
+    }
+    match db_type {
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => Ok(ident
+            .split('.')
+            .map(|part| format!("[{}]", part.replace(']', "]]")))
+            .collect::<Vec<_>>()
+            .join(".")),
+        #[allow(unreachable_patterns)]
+        _ => Ok(quote_ident(ident)),
+    }
+}
+
+/// Whether `part` is a plain identifier: an ASCII letter or `_`, then ASCII letters, digits or
+/// `_`. Plain names need no quoting on any backend.
+#[must_use]
+pub fn is_plain(part: &str) -> bool {
+    let mut chars = part.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Accept a table name made of plain parts, optionally qualified as `schema.table` or
+/// `database.schema.table`.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ParameterError` naming the rejected table.
+pub fn validate_table(name: &str) -> Result<(), SqlMiddlewareDbError> {
+    let parts = name.split('.').count();
+    if parts > 3 {
+        return Err(invalid(name, "has more than three parts"));
+    }
+    if name.split('.').all(is_plain) {
+        Ok(())
+    } else {
+        Err(invalid(
+            name,
+            "is not a plain name (letters, digits and `_`, not starting with a digit)",
+        ))
+    }
+}
+
+/// Accept a single plain column name.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ParameterError` naming the rejected column.
+pub fn validate_column(name: &str) -> Result<(), SqlMiddlewareDbError> {
+    if is_plain(name) {
+        Ok(())
+    } else {
+        Err(invalid(
+            name,
+            "is not a plain name (letters, digits and `_`, not starting with a digit)",
+        ))
+    }
+}
+
+fn invalid(name: &str, why: &str) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ParameterError(format!("identifier {name:?} {why}"))
+}
@@ -0,0 +1,89 @@
+//! Randomness used to spread out retry delays.
+//!
+//! Backoff delays (`SQLite` busy-retry during rollback) and, when enabled, circuit-breaker
+//! cool-downs are jittered so that connections failing together don't retry in lockstep. The
+//! random source is the pool's [`Jitter`], set with
+//! [`ConfigAndPool::with_jitter`](crate::ConfigAndPool::with_jitter). Production pools seed it
+//! from process entropy; tests and the simulator install a [`SeededJitter`] so that, together
+//! with a [`MockClock`](crate::clock::MockClock), retry sequences repeat exactly:
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use sql_middleware::clock::MockClock;
+//! use sql_middleware::jitter::SeededJitter;
+//! use sql_middleware::prelude::*;
+//!
+//! # async fn demo() -> Result<(), SqlMiddlewareDbError> {
+//! let cap = ConfigAndPool::new_sqlite_memory("jitter_demo")
+//!     .await?
+//!     .with_clock(Arc::new(MockClock::new()))
+//!     .with_jitter(Arc::new(SeededJitter::new(42)));
+//! # let _ = cap;
+//! # Ok(()) }
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Source of random numbers for backoff jitter.
+pub trait Jitter: Send + Sync + fmt::Debug {
+    /// Next value, uniformly distributed over `u64`.
+    fn next_u64(&self) -> u64;
+}
+
+/// Deterministic jitter: the same seed yields the same sequence of values.
+///
+/// Uses `SplitMix64`, which is fast and statistically fine for spreading delays; it is not meant
+/// for anything security-sensitive. Clones share their position in the sequence.
+#[derive(Debug, Clone)]
+pub struct SeededJitter {
+    state: Arc<AtomicU64>,
+}
+
+impl SeededJitter {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Arc::new(AtomicU64::new(seed)),
+        }
+    }
+}
+
+impl Jitter for SeededJitter {
+    fn next_u64(&self) -> u64 {
+        const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut z = self
+            .state
+            .fetch_add(GAMMA, Ordering::Relaxed)
+            .wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Shared default jitter, seeded from process entropy.
+#[must_use]
+pub fn system_jitter() -> Arc<dyn Jitter> {
+    Arc::new(SeededJitter::new(RandomState::new().hash_one(0_u64)))
+}
+
+/// `delay` scaled to a random point in `[delay / 2, delay]` ("equal jitter").
+#[cfg(feature = "sqlite")]
+pub(crate) fn equal_jitter(delay: Duration, jitter: &dyn Jitter) -> Duration {
+    let half = delay / 2;
+    half + up_to(delay - half, jitter)
+}
+
+/// A random duration in `[0, max]`.
+pub(crate) fn up_to(max: Duration, jitter: &dyn Jitter) -> Duration {
+    let nanos = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_nanos(jitter.next_u64() % nanos.saturating_add(1))
+}
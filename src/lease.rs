@@ -0,0 +1,394 @@
+//! Named leases for simple leader election.
+//!
+//! Whoever holds a lease is the leader; the holder keeps it alive with [`Lease::renew`] well
+//! inside the TTL and gives it up with [`Lease::release`]. If the holder dies or stops renewing,
+//! the next [`Lease::acquire`] takes over.
+//!
+//! On Postgres a lease is a session-level advisory lock (`pg_try_advisory_lock`) held on a
+//! connection of its own, outside the pool. The lock goes away with that session, so a crashed
+//! holder or a dropped [`Lease`] frees it at once. The session's `idle_session_timeout` is set to
+//! the TTL, so a holder that stops renewing is disconnected by the server (Postgres 14 and later;
+//! on older servers a live but stalled holder keeps the lock). Each renewal also writes the holder
+//! and expiry to [`LEASE_TABLE`] as a heartbeat, for monitoring only.
+//!
+//! The other backends keep the lease itself as a row in [`LEASE_TABLE`] naming its holder and an
+//! expiry. Expiry uses each caller's wall clock, so hosts competing for a lease should keep their
+//! clocks in sync to well within the TTL.
+//!
+//! The table is created on the first acquire through each pool.
+
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::epoch_millis;
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::translation::TranslationMode;
+use crate::types::{DatabaseType, RowValues};
+
+#[cfg(feature = "postgres")]
+use crate::pool::MiddlewarePool;
+
+/// Table holding one row per lease (created on first use).
+pub const LEASE_TABLE: &str = "sql_middleware_leases";
+
+/// A lease currently held by this process.
+///
+/// Dropping it without [`release`](Self::release) frees a Postgres lease as soon as its session
+/// closes; on the other backends the row stays until it expires.
+#[derive(Debug)]
+pub struct Lease {
+    cap: ConfigAndPool,
+    name: String,
+    holder: String,
+    ttl: Duration,
+    expires_at: SystemTime,
+    /// Session holding the advisory lock on Postgres.
+    #[cfg(feature = "postgres")]
+    session: Option<tokio_postgres::Client>,
+}
+
+impl Lease {
+    /// Try to take the lease `name` for `holder` for `ttl`.
+    ///
+    /// Succeeds when nobody holds the lease, the current lease has expired, or (except on
+    /// Postgres, where the lock belongs to the first `Lease`'s session) `holder` already holds it,
+    /// which extends it. Returns `None` while another holder's lease is live.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the lease table cannot be created or queried, or the
+    /// Postgres lock session cannot connect.
+    pub async fn acquire(
+        cap: &ConfigAndPool,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<Lease>, SqlMiddlewareDbError> {
+        let mut conn = cap.get_connection().await?;
+        let db_type = conn.database_type();
+        if !cap.lease_table_ready.load(Ordering::Acquire) {
+            conn.execute_batch(&create_table_sql(&db_type)).await?;
+            cap.lease_table_ready.store(true, Ordering::Release);
+        }
+
+        match &cap.pool {
+            #[cfg(feature = "postgres")]
+            MiddlewarePool::Postgres(pool) => {
+                drop(conn);
+                let session = pool.dedicated_connection().await?;
+                return advisory::acquire(session, cap, name, holder, ttl).await;
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+
+        let now = epoch_millis(SystemTime::now())?;
+        let expires_at = SystemTime::now() + ttl;
+        let expires_ms = epoch_millis(expires_at)?;
+        let p = |idx| db_type.placeholder(idx);
+
+        // Take over an expired lease or extend our own.
+        let take_over = format!(
+            "UPDATE {LEASE_TABLE} SET holder = {}, expires_at_ms = {} \
+             WHERE name = {} AND (expires_at_ms < {} OR holder = {})",
+            p(1),
+            p(2),
+            p(3),
+            p(4),
+            p(5)
+        );
+        let taken = dml(
+            &mut conn,
+            &take_over,
+            &[
+                RowValues::Text(holder.into()),
+                RowValues::Int(expires_ms),
+                RowValues::Text(name.into()),
+                RowValues::Int(now),
+                RowValues::Text(holder.into()),
+            ],
+        )
+        .await?;
+
+        if taken == 0 {
+            // Casts give Postgres concrete types for the bare SELECT-list parameters.
+            let insert = format!(
+                "INSERT INTO {LEASE_TABLE} (name, holder, expires_at_ms) \
+                 SELECT CAST({} AS VARCHAR(255)), CAST({} AS VARCHAR(255)), CAST({} AS BIGINT) \
+                 WHERE NOT EXISTS (SELECT 1 FROM {LEASE_TABLE} WHERE name = {})",
+                p(1),
+                p(2),
+                p(3),
+                p(4)
+            );
+            let params = [
+                RowValues::Text(name.into()),
+                RowValues::Text(holder.into()),
+                RowValues::Int(expires_ms),
+                RowValues::Text(name.into()),
+            ];
+            match dml(&mut conn, &insert, &params).await {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                // A concurrent insert won the primary key; that holder has the lease.
+                Err(err) => {
+                    return if current_holder(&mut conn, &db_type, name).await?.is_some() {
+                        Ok(None)
+                    } else {
+                        Err(err)
+                    };
+                }
+            }
+        }
+
+        Ok(Some(Lease {
+            cap: cap.clone(),
+            name: name.to_string(),
+            holder: holder.to_string(),
+            ttl,
+            expires_at,
+            #[cfg(feature = "postgres")]
+            session: None,
+        }))
+    }
+
+    /// Name of the lease.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Identity recorded as the holder.
+    #[must_use]
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// When the lease lapses unless renewed.
+    #[must_use]
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// Extend the lease by its TTL from now.
+    ///
+    /// Returns `false` if the lease was lost (it expired and someone else took it, the row was
+    /// removed, or the Postgres lock session closed); the caller should stop acting as leader.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the update fails.
+    pub async fn renew(&mut self) -> Result<bool, SqlMiddlewareDbError> {
+        #[cfg(feature = "postgres")]
+        if let Some(session) = &self.session {
+            let expires_at = SystemTime::now() + self.ttl;
+            let renewed =
+                advisory::heartbeat(session, &self.name, &self.holder, expires_at).await?;
+            if renewed {
+                self.expires_at = expires_at;
+            }
+            return Ok(renewed);
+        }
+        let mut conn = self.cap.get_connection().await?;
+        let db_type = conn.database_type();
+        let expires_at = SystemTime::now() + self.ttl;
+        let p = |idx| db_type.placeholder(idx);
+        let sql = format!(
+            "UPDATE {LEASE_TABLE} SET expires_at_ms = {} WHERE name = {} AND holder = {}",
+            p(1),
+            p(2),
+            p(3)
+        );
+        let renewed = dml(
+            &mut conn,
+            &sql,
+            &[
+                RowValues::Int(epoch_millis(expires_at)?),
+                RowValues::Text(self.name.as_str().into()),
+                RowValues::Text(self.holder.as_str().into()),
+            ],
+        )
+        .await?;
+        if renewed > 0 {
+            self.expires_at = expires_at;
+        }
+        Ok(renewed > 0)
+    }
+
+    /// Give the lease up so another holder can acquire it immediately.
+    ///
+    /// Returns `false` if this holder no longer held it.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the delete fails.
+    pub async fn release(self) -> Result<bool, SqlMiddlewareDbError> {
+        #[cfg(feature = "postgres")]
+        if let Some(session) = &self.session {
+            return advisory::release(session, &self.name, &self.holder).await;
+        }
+        let mut conn = self.cap.get_connection().await?;
+        let db_type = conn.database_type();
+        let sql = format!(
+            "DELETE FROM {LEASE_TABLE} WHERE name = {} AND holder = {}",
+            db_type.placeholder(1),
+            db_type.placeholder(2)
+        );
+        let deleted = dml(
+            &mut conn,
+            &sql,
+            &[
+                RowValues::Text(self.name.into()),
+                RowValues::Text(self.holder.into()),
+            ],
+        )
+        .await?;
+        Ok(deleted > 0)
+    }
+}
+
+fn create_table_sql(db_type: &DatabaseType) -> String {
+    let columns = "name VARCHAR(255) NOT NULL PRIMARY KEY, holder VARCHAR(255) NOT NULL, \
+                   expires_at_ms BIGINT NOT NULL";
+    match db_type {
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => format!(
+            "IF OBJECT_ID(N'{LEASE_TABLE}', N'U') IS NULL CREATE TABLE {LEASE_TABLE} ({columns})"
+        ),
+        #[allow(unreachable_patterns)]
+        _ => format!("CREATE TABLE IF NOT EXISTS {LEASE_TABLE} ({columns})"),
+    }
+}
+
+async fn current_holder(
+    conn: &mut MiddlewarePoolConnection,
+    db_type: &DatabaseType,
+    name: &str,
+) -> Result<Option<String>, SqlMiddlewareDbError> {
+    let sql = format!(
+        "SELECT holder FROM {LEASE_TABLE} WHERE name = {}",
+        db_type.placeholder(1)
+    );
+    let params = [RowValues::Text(name.into())];
+    let rows = conn
+        .query(&sql)
+        .params(&params)
+        .translation(TranslationMode::ForceOff)
+        .select()
+        .await?;
+    Ok(rows
+        .results
+        .first()
+        .and_then(|row| row.get("holder"))
+        .and_then(RowValues::as_text)
+        .map(str::to_string))
+}
+
+async fn dml(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    params: &[RowValues],
+) -> Result<usize, SqlMiddlewareDbError> {
+    conn.query(sql)
+        .params(params)
+        .translation(TranslationMode::ForceOff)
+        .dml()
+        .await
+}
+
+/// Postgres leases: a session-level advisory lock plus a heartbeat row.
+#[cfg(feature = "postgres")]
+mod advisory {
+    use std::time::{Duration, SystemTime};
+
+    use tokio_postgres::Client;
+
+    use super::{LEASE_TABLE, Lease};
+    use crate::clock::epoch_millis;
+    use crate::error::SqlMiddlewareDbError;
+    use crate::pool::ConfigAndPool;
+
+    /// Advisory lock key for the lease named by `$1`, kept apart from application locks by
+    /// hashing the table name in.
+    const LOCK_KEY: &str = "hashtextextended('sql_middleware_leases:' || $1, 0)";
+
+    pub(super) async fn acquire(
+        session: Client,
+        cap: &ConfigAndPool,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Option<Lease>, SqlMiddlewareDbError> {
+        let locked: bool = session
+            .query_one(
+                &format!("SELECT pg_try_advisory_lock({LOCK_KEY})"),
+                &[&name],
+            )
+            .await?
+            .try_get(0)?;
+        if !locked {
+            return Ok(None);
+        }
+        // Older servers don't have the setting; their stalled holders keep the lock until the
+        // session ends.
+        let timeout = format!("{}ms", ttl.as_millis().max(1));
+        let _ = session
+            .execute(
+                "SELECT set_config('idle_session_timeout', $1, false)",
+                &[&timeout],
+            )
+            .await;
+
+        let expires_at = SystemTime::now() + ttl;
+        if !heartbeat(&session, name, holder, expires_at).await? {
+            return Ok(None);
+        }
+        Ok(Some(Lease {
+            cap: cap.clone(),
+            name: name.to_string(),
+            holder: holder.to_string(),
+            ttl,
+            expires_at,
+            session: Some(session),
+        }))
+    }
+
+    /// Record the holder and expiry; `false` once the session (and so the lock) is gone.
+    pub(super) async fn heartbeat(
+        session: &Client,
+        name: &str,
+        holder: &str,
+        expires_at: SystemTime,
+    ) -> Result<bool, SqlMiddlewareDbError> {
+        let sql = format!(
+            "INSERT INTO {LEASE_TABLE} (name, holder, expires_at_ms) VALUES ($1, $2, $3) \
+             ON CONFLICT (name) DO UPDATE \
+             SET holder = EXCLUDED.holder, expires_at_ms = EXCLUDED.expires_at_ms"
+        );
+        let expires_ms = epoch_millis(expires_at)?;
+        match session.execute(&sql, &[&name, &holder, &expires_ms]).await {
+            Ok(_) => Ok(true),
+            Err(_) if session.is_closed() => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub(super) async fn release(
+        session: &Client,
+        name: &str,
+        holder: &str,
+    ) -> Result<bool, SqlMiddlewareDbError> {
+        if session.is_closed() {
+            return Ok(false);
+        }
+        session
+            .execute(
+                &format!("DELETE FROM {LEASE_TABLE} WHERE name = $1 AND holder = $2"),
+                &[&name, &holder],
+            )
+            .await?;
+        let unlocked: bool = session
+            .query_one(&format!("SELECT pg_advisory_unlock({LOCK_KEY})"), &[&name])
+            .await?
+            .try_get(0)?;
+        Ok(unlocked)
+    }
+}
@@ -1,7 +1,15 @@
 #![doc = include_str!("../docs.md")]
 #![forbid(unsafe_code)]
 
+// The crate also builds without a backend, for consumers who only need translation and
+// `RowValues`. `build.rs` sets `cfg(any_backend)` when at least one backend feature is on; items
+// that need a connection to run (the query builder, dispatch, pool helpers) are gated on it.
+
+// `translation::core` is written against `core` and `alloc` only.
+extern crate alloc;
+
 // Test utilities module
+#[cfg(any_backend)]
 #[path = "test_utils/test_helpers.rs"]
 pub mod test_helpers;
 
@@ -10,12 +18,51 @@ pub mod test_helpers;
 pub mod benchmark;
 
 // Public API modules
+pub mod audit;
+pub mod backend;
+#[cfg(feature = "json")]
+pub mod cdc;
+pub mod clock;
+pub mod compat;
+pub mod config;
+#[cfg(feature = "cockroach")]
+pub mod cockroach;
+pub mod collation;
+pub mod compare;
 pub mod conversion;
+pub mod credentials;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod custom;
+#[cfg(feature = "parquet")]
+pub mod export;
+#[cfg(feature = "geo")]
+pub mod geo;
+pub mod ident;
+pub mod jitter;
+#[cfg(any_backend)]
+pub mod lease;
+#[cfg(any_backend)]
+pub mod metrics;
+pub mod params;
 pub mod prelude;
+#[cfg(any_backend)]
+pub mod queue;
+#[cfg(any_backend)]
+pub mod saga;
+#[cfg(any_backend)]
+pub mod schema;
+#[cfg(any(feature = "postgres", feature = "mssql"))]
+pub mod session;
+#[cfg(any_backend)]
+pub mod sync;
 pub mod translation;
+pub mod tx_drop;
 pub mod tx_outcome;
+#[cfg(any_backend)]
 pub mod typed;
 /// Back-compat re-export: `typed_api` is now `typed`.
+#[cfg(any_backend)]
 pub use typed as typed_api;
 #[cfg(feature = "postgres")]
 pub mod typed_postgres;
@@ -25,12 +72,15 @@ pub mod typed_sqlite;
 pub mod typed_turso;
 
 // Core modules (public for docs/advanced use)
+#[cfg(any_backend)]
 pub(crate) mod adapters;
 pub mod error;
 pub(crate) mod executor;
 pub mod middleware;
 pub mod pool;
 pub mod query;
+#[cfg(any(feature = "sqlite", feature = "turso"))]
+pub(crate) mod runtime;
 
 // Internal modules (types are re-exported; modules stay private)
 pub(crate) mod query_builder;
@@ -50,10 +100,14 @@ pub mod turso;
 
 // Direct exports for frequently used types
 pub use middleware::{
-    AnyConnWrapper, BatchTarget, ConfigAndPool, ConversionMode, CustomDbRow, DatabaseType,
-    MiddlewarePool, MiddlewarePoolConnection, ParamConverter, QueryAndParams, QueryBuilder,
-    QueryTarget, ResultSet, RowValues, SqlMiddlewareDbError, TxOutcome, execute_batch,
+    AnyConnWrapper, BatchOptions, BatchTxMode, ConfigAndPool, ConversionMode, CustomDbRow,
+    DatabaseType, InsertBuilder, MiddlewarePool, MiddlewarePoolConnection, ParamConverter,
+    QueryAndParams, ResultSet, ResultSetBuilder, RowValues, SqlMiddlewareDbError, TxOutcome,
 };
+#[cfg(any_backend)]
+pub use middleware::{BatchTarget, QueryBuilder, QueryOutcome, QueryTarget, execute_batch};
+#[cfg(any(feature = "postgres", feature = "mssql"))]
+pub use session::SessionTx;
 #[cfg(feature = "mssql")]
 pub use middleware::{MssqlOptions, MssqlOptionsBuilder};
 #[cfg(feature = "postgres")]
@@ -65,6 +119,9 @@ pub use middleware::{TursoOptions, TursoOptionsBuilder};
 
 // Re-export from modules for convenience
 pub use conversion::convert_sql_params;
+pub use params::{IntoParams, IntoRowValues};
+pub use results::FromRowValues;
 pub use translation::{
-    PlaceholderStyle, PrepareMode, QueryOptions, TranslationMode, translate_placeholders,
+    PlaceholderStyle, PrepareMode, QueryOptions, TranslationMode, has_order_by,
+    translate_placeholders,
 };
@@ -0,0 +1,258 @@
+//! Per-statement metrics keyed by SQL fingerprint.
+//!
+//! When enabled, every statement run through a [`QueryBuilder`](crate::QueryBuilder)
+//! (`select`, `select_multi`, `dml`) is recorded under its
+//! [`fingerprint`](crate::translation::fingerprint): call, row and error counts plus a latency
+//! histogram. [`snapshot`] reports the hottest statements first, which is usually enough to find
+//! hot or bad queries without server-side extensions.
+//!
+//! Collection is process-wide and off by default; while disabled, recording costs one atomic
+//! load per statement.
+//!
+//! [`explain_slow_queries`] separately captures SELECTs over a latency threshold together with
+//! their query plan.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::translation::fingerprint;
+
+/// Latency buckets are powers of two in microseconds; the last one is open-ended (≥ ~67s).
+const BUCKETS: usize = 27;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REGISTRY: LazyLock<Mutex<HashMap<String, Entry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Aggregated metrics for one fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementStats {
+    pub fingerprint: String,
+    pub calls: u64,
+    /// Rows returned (SELECT) or affected (DML) by successful calls.
+    pub rows: u64,
+    pub errors: u64,
+    pub total_time: Duration,
+    pub max_time: Duration,
+    /// 95th-percentile latency, rounded up to a power-of-two bucket (so within 2x).
+    pub p95: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    calls: u64,
+    rows: u64,
+    errors: u64,
+    total_time: Duration,
+    max_time: Duration,
+    histogram: [u64; BUCKETS],
+}
+
+impl Entry {
+    fn p95(&self) -> Duration {
+        let target = self.calls - self.calls / 20;
+        let mut seen = 0;
+        for (bucket, count) in self.histogram.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return bucket_upper_bound(bucket).min(self.max_time);
+            }
+        }
+        self.max_time
+    }
+}
+
+fn bucket_of(elapsed: Duration) -> usize {
+    let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+    let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+    bucket.min(BUCKETS - 1)
+}
+
+fn bucket_upper_bound(bucket: usize) -> Duration {
+    if bucket + 1 >= BUCKETS {
+        Duration::MAX
+    } else {
+        Duration::from_micros(1 << bucket)
+    }
+}
+
+fn registry() -> MutexGuard<'static, HashMap<String, Entry>> {
+    match REGISTRY.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Start recording statement metrics.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stop recording; collected metrics are kept until [`reset`].
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Whether statement metrics are being recorded.
+#[must_use]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Drop all collected metrics.
+pub fn reset() {
+    registry().clear();
+}
+
+/// Metrics per fingerprint, highest total time first.
+#[must_use]
+pub fn snapshot() -> Vec<StatementStats> {
+    let mut stats: Vec<_> = registry()
+        .iter()
+        .map(|(fingerprint, entry)| StatementStats {
+            fingerprint: fingerprint.clone(),
+            calls: entry.calls,
+            rows: entry.rows,
+            errors: entry.errors,
+            total_time: entry.total_time,
+            max_time: entry.max_time,
+            p95: entry.p95(),
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.total_time
+            .cmp(&a.total_time)
+            .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+    });
+    stats
+}
+
+/// Record one statement; `rows` is `None` when it failed.
+pub(crate) fn record(sql: &str, elapsed: Duration, rows: Option<usize>) {
+    if !is_enabled() {
+        return;
+    }
+    let key = fingerprint(sql);
+    let mut registry = registry();
+    let entry = registry.entry(key).or_default();
+    entry.calls += 1;
+    match rows {
+        Some(rows) => entry.rows += rows as u64,
+        None => entry.errors += 1,
+    }
+    entry.total_time += elapsed;
+    entry.max_time = entry.max_time.max(elapsed);
+    entry.histogram[bucket_of(elapsed)] += 1;
+}
+
+/// Slow-query capture settings; see [`explain_slow_queries`].
+#[derive(Debug, Clone, Copy)]
+struct SlowQueryConfig {
+    threshold: Duration,
+    min_explain_interval: Duration,
+}
+
+/// How many captured slow queries [`recent_slow_queries`] keeps.
+const SLOW_QUERY_HISTORY: usize = 32;
+
+static SLOW_CAPTURE: AtomicBool = AtomicBool::new(false);
+static SLOW_QUERIES: LazyLock<Mutex<SlowQueryState>> =
+    LazyLock::new(|| Mutex::new(SlowQueryState::default()));
+
+#[derive(Debug, Default)]
+struct SlowQueryState {
+    config: Option<SlowQueryConfig>,
+    last_explain: Option<Instant>,
+    recent: VecDeque<SlowQuery>,
+}
+
+fn slow_queries() -> MutexGuard<'static, SlowQueryState> {
+    match SLOW_QUERIES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// A SELECT that ran longer than the slow-query threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowQuery {
+    pub fingerprint: String,
+    pub sql: String,
+    pub elapsed: Duration,
+    /// Output of the backend's `EXPLAIN` (non-`ANALYZE`), one plan line per row; `None` when the
+    /// explain was rate-limited, failed, or the backend has no parameterized explain (SQL Server).
+    pub plan: Option<String>,
+}
+
+/// Log `QueryBuilder::select` calls slower than `threshold` and attach the backend's plan.
+///
+/// Each slow SELECT is emitted as a `tracing` warning (target `sql_middleware::slow_query`) and
+/// kept in [`recent_slow_queries`]. The plan comes from re-running the statement under `EXPLAIN`
+/// (Postgres) or `EXPLAIN QUERY PLAN` (`SQLite`/Turso) on the same connection, at most once per
+/// `min_explain_interval` process-wide; explain failures are ignored.
+pub fn explain_slow_queries(threshold: Duration, min_explain_interval: Duration) {
+    slow_queries().config = Some(SlowQueryConfig {
+        threshold,
+        min_explain_interval,
+    });
+    SLOW_CAPTURE.store(true, Ordering::Relaxed);
+}
+
+/// Stop slow-query capture.
+pub fn disable_slow_query_capture() {
+    SLOW_CAPTURE.store(false, Ordering::Relaxed);
+    slow_queries().config = None;
+}
+
+/// Most recent slow queries, oldest first.
+#[must_use]
+pub fn recent_slow_queries() -> Vec<SlowQuery> {
+    slow_queries().recent.iter().cloned().collect()
+}
+
+pub(crate) fn slow_capture_enabled() -> bool {
+    SLOW_CAPTURE.load(Ordering::Relaxed)
+}
+
+/// Whether a SELECT that took `elapsed` is slow, and if so whether it may be explained now
+/// (claiming the rate-limit slot when it may). A query that cannot be `explainable` never claims
+/// the slot.
+pub(crate) fn check_slow(elapsed: Duration, explainable: bool) -> Option<bool> {
+    let mut state = slow_queries();
+    let config = state.config?;
+    if elapsed < config.threshold {
+        return None;
+    }
+    let now = Instant::now();
+    let may_explain = explainable
+        && state
+            .last_explain
+            .is_none_or(|last| now.duration_since(last) >= config.min_explain_interval);
+    if may_explain {
+        state.last_explain = Some(now);
+    }
+    Some(may_explain)
+}
+
+pub(crate) fn record_slow(sql: &str, elapsed: Duration, plan: Option<String>) {
+    let slow = SlowQuery {
+        fingerprint: fingerprint(sql),
+        sql: sql.to_string(),
+        elapsed,
+        plan,
+    };
+    tracing::warn!(
+        target: "sql_middleware::slow_query",
+        elapsed_ms = u64::try_from(slow.elapsed.as_millis()).unwrap_or(u64::MAX),
+        fingerprint = %slow.fingerprint,
+        plan = slow.plan.as_deref().unwrap_or("<not captured>"),
+        "slow query"
+    );
+    let mut state = slow_queries();
+    if state.recent.len() == SLOW_QUERY_HISTORY {
+        state.recent.pop_front();
+    }
+    state.recent.push_back(slow);
+}
@@ -1,12 +1,20 @@
 // Re-export all the types and traits from the sub-modules
 pub use crate::error::SqlMiddlewareDbError;
-pub use crate::executor::{BatchTarget, QueryTarget, execute_batch, query};
+pub use crate::executor::{BatchOptions, BatchTxMode, InsertBuilder};
+#[cfg(any_backend)]
+pub use crate::executor::{
+    BatchProgress, BatchStream, BatchTarget, QueryTarget, execute_batch, query,
+};
 pub use crate::pool::{AnyConnWrapper, ConfigAndPool, MiddlewarePool, MiddlewarePoolConnection};
 pub use crate::query::QueryAndParams;
-pub use crate::query_builder::QueryBuilder;
-pub use crate::results::{CustomDbRow, ResultSet};
+pub use crate::query_builder::DEFAULT_SOFT_DELETE_COLUMN;
+#[cfg(any_backend)]
+pub use crate::query_builder::{QueryBuilder, QueryOutcome};
+pub use crate::results::{CustomDbRow, FromRowValues, ResultSet, ResultSetBuilder};
 pub use crate::translation::{
-    PlaceholderStyle, PrepareMode, QueryOptions, TranslationMode, translate_placeholders,
+    PlaceholderStyle, PrepareMode, QueryOptions, StatementKind, TranslationError, TranslationMode,
+    has_order_by, split_statements, statement_kind, translate_placeholders,
+    try_translate_parameterized, try_translate_placeholders,
 };
 pub use crate::tx_outcome::TxOutcome;
 pub use crate::types::{ConversionMode, DatabaseType, ParamConverter, RowValues};
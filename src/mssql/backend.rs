@@ -0,0 +1,74 @@
+use crate::backend::BackendApi;
+use crate::middleware::{DatabaseType, ResultSet, RowValues, SqlMiddlewareDbError, TxOutcome};
+
+use super::config::MssqlClient;
+use super::params::Params;
+use super::transaction::{Prepared, Tx};
+
+/// [`BackendApi`] implementation for the `mssql` module.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MssqlBackend;
+
+impl BackendApi for MssqlBackend {
+    const DATABASE_TYPE: DatabaseType = DatabaseType::Mssql;
+
+    type Connection = MssqlClient;
+    type TxConnection = MssqlClient;
+    type Params<'a> = Params<'a>;
+    type Tx<'c> = Tx<'c>;
+    type Prepared = Prepared;
+
+    async fn execute_batch(conn: &mut MssqlClient, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        super::execute_batch(conn, sql).await
+    }
+
+    async fn execute_select(
+        conn: &mut MssqlClient,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        super::execute_select(conn, sql, params).await
+    }
+
+    async fn execute_dml(
+        conn: &mut MssqlClient,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        super::execute_dml(conn, sql, params).await
+    }
+
+    async fn begin_transaction(
+        conn: &mut MssqlClient,
+    ) -> Result<Self::Tx<'_>, SqlMiddlewareDbError> {
+        super::begin_transaction(conn).await
+    }
+
+    async fn prepare(tx: &mut Self::Tx<'_>, sql: &str) -> Result<Prepared, SqlMiddlewareDbError> {
+        tx.prepare(sql)
+    }
+
+    async fn execute_prepared(
+        tx: &mut Self::Tx<'_>,
+        prepared: &mut Prepared,
+        params: &[RowValues],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        tx.execute_prepared(prepared, params).await
+    }
+
+    async fn query_prepared(
+        tx: &mut Self::Tx<'_>,
+        prepared: &mut Prepared,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        tx.query_prepared(prepared, params).await
+    }
+
+    async fn commit(tx: Self::Tx<'_>) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        tx.commit().await
+    }
+
+    async fn rollback(tx: Self::Tx<'_>) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        tx.rollback().await
+    }
+}
@@ -1,22 +1,58 @@
-use bb8::Pool;
+use std::future::Future;
+use std::sync::Arc;
+
+use bb8::{ManageConnection, Pool};
 use bb8_tiberius::{ConnectionManager, rt};
+use serde::{Deserialize, Serialize};
 use tiberius::{AuthMethod, Config as TiberiusConfig};
 
+use crate::config::{EnvReader, process_env};
+use crate::credentials::{CredentialSource, CredentialsProvider};
 use crate::middleware::{ConfigAndPool, DatabaseType, MiddlewarePool, SqlMiddlewareDbError};
 
 /// Type alias for SQL Server client
 pub type MssqlClient = rt::Client;
 
+/// Run on each checkout: ends a transaction an earlier holder left open, then checks the
+/// connection answers.
+const CHECKOUT_PROBE: &str = "IF SESSION_CONTEXT(N'sql_middleware.retired') = 1 \
+     THROW 50000, N'connection was retired after a failed reset', 1; \
+     IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION; SELECT 1;";
+
+/// Marks a session so [`CHECKOUT_PROBE`] fails and the pool closes it; read-only, so no reset
+/// can clear it.
+pub(crate) const MSSQL_RETIRE: &str =
+    "EXEC sp_set_session_context @key = N'sql_middleware.retired', @value = 1, @read_only = 1;";
+
+/// Default cap on pooled SQL Server connections.
+pub const DEFAULT_MSSQL_POOL_SIZE: u32 = 20;
+
 /// Options for configuring an MSSQL pool.
-#[derive(Debug, Clone)]
+///
+/// Serializes with the password redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MssqlOptions {
     pub server: String,
     pub database: String,
     pub user: String,
+    #[serde(serialize_with = "crate::config::redact")]
     pub password: String,
+    #[serde(default)]
     pub port: Option<u16>,
+    #[serde(default)]
     pub instance_name: Option<String>,
+    #[serde(default)]
     pub translate_placeholders: bool,
+    /// Most connections the pool opens at once.
+    #[serde(default = "default_max_size")]
+    pub max_size: u32,
+    /// Supplies the user and password for each new connection, overriding `user`/`password`.
+    #[serde(skip)]
+    pub credentials: Option<Arc<dyn CredentialsProvider>>,
+}
+
+fn default_max_size() -> u32 {
+    DEFAULT_MSSQL_POOL_SIZE
 }
 
 impl MssqlOptions {
@@ -38,7 +74,48 @@ impl MssqlOptions {
             port,
             instance_name,
             translate_placeholders: false,
+            max_size: DEFAULT_MSSQL_POOL_SIZE,
+            credentials: None,
+        }
+    }
+
+    /// Read options from environment variables named `{prefix}` followed by:
+    ///
+    /// - `SERVER`, `DATABASE`, `USER`, `PASSWORD` (required)
+    /// - `PORT`, `INSTANCE_NAME`, `POOL_SIZE`, `TRANSLATE_PLACEHOLDERS` (optional)
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env(prefix: &str) -> Result<Self, SqlMiddlewareDbError> {
+        Self::from_env_with(prefix, process_env)
+    }
+
+    /// Like [`from_env`](Self::from_env), looking variables up with `lookup` instead of in the
+    /// process environment.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env_with(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let mut env = EnvReader::new(prefix, lookup);
+        let mut opts = Self::new(
+            env.required("SERVER"),
+            env.required("DATABASE"),
+            env.required("USER"),
+            env.required("PASSWORD"),
+            env.parsed("PORT"),
+            env.optional("INSTANCE_NAME"),
+        );
+        if let Some(max_size) = env.parsed("POOL_SIZE") {
+            opts.max_size = max_size;
         }
+        opts.translate_placeholders = env.flag("TRANSLATE_PLACEHOLDERS").unwrap_or(false);
+        env.finish()?;
+        Ok(opts)
     }
 
     #[must_use]
@@ -58,6 +135,20 @@ impl MssqlOptions {
         self.instance_name = instance_name;
         self
     }
+
+    /// Cap the pool at `max_size` connections; checkouts beyond it wait for one to be returned.
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Fetch the user and password from `provider` for each new connection.
+    #[must_use]
+    pub fn with_credentials(mut self, provider: Arc<dyn CredentialsProvider>) -> Self {
+        self.credentials = Some(provider);
+        self
+    }
 }
 
 /// Fluent builder for MSSQL options.
@@ -100,6 +191,21 @@ impl MssqlOptionsBuilder {
         self
     }
 
+    /// Cap the pool at `max_size` connections; checkouts beyond it wait for one to be returned.
+    #[must_use]
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.opts.max_size = max_size;
+        self
+    }
+
+    /// Fetch the user and password from `provider` for each new connection, so rotated secrets
+    /// are picked up without rebuilding the pool.
+    #[must_use]
+    pub fn credentials(mut self, provider: Arc<dyn CredentialsProvider>) -> Self {
+        self.opts.credentials = Some(provider);
+        self
+    }
+
     #[must_use]
     pub fn finish(self) -> MssqlOptions {
         self.opts
@@ -132,19 +238,19 @@ impl ConfigAndPool {
     /// Asynchronous initializer for `ConfigAndPool` with SQL Server (MSSQL).
     ///
     /// # Errors
-    /// Returns `SqlMiddlewareDbError::ConnectionError` if MSSQL manager/pool creation fails.
+    /// Returns `SqlMiddlewareDbError::ConfigError` if `max_size` is 0, or
+    /// `SqlMiddlewareDbError::ConnectionError` if MSSQL manager/pool creation fails.
     #[allow(clippy::unused_async)]
     pub async fn new_mssql(opts: MssqlOptions) -> Result<Self, SqlMiddlewareDbError> {
-        let config = build_tiberius_config(&opts);
-
-        let manager = ConnectionManager::build(config).map_err(|e| {
-            SqlMiddlewareDbError::ConnectionError(format!(
-                "Failed to configure SQL Server manager: {e}"
-            ))
-        })?;
+        if opts.max_size == 0 {
+            return Err(SqlMiddlewareDbError::ConfigError(
+                "SQL Server pool max_size must be at least 1".to_string(),
+            ));
+        }
+        let manager = MssqlManager::new(&opts)?;
 
         let pool = Pool::builder()
-            .max_size(20)
+            .max_size(opts.max_size)
             .build(manager)
             .await
             .map_err(|e| {
@@ -153,14 +259,88 @@ impl ConfigAndPool {
                 ))
             })?;
 
-        Ok(ConfigAndPool {
-            pool: MiddlewarePool::Mssql(pool),
-            db_type: DatabaseType::Mssql,
-            translate_placeholders: opts.translate_placeholders,
+        Ok(ConfigAndPool::from_pool(
+            MiddlewarePool::Mssql(pool),
+            DatabaseType::Mssql,
+            opts.translate_placeholders,
+        ))
+    }
+}
+
+/// bb8 manager for SQL Server connections.
+///
+/// Delegates to `bb8_tiberius`, optionally re-authenticating each new connection with
+/// credentials fetched from a [`CredentialsProvider`].
+pub struct MssqlManager {
+    config: TiberiusConfig,
+    inner: ConnectionManager,
+    /// User paired with fetched credentials that do not name one.
+    user: String,
+    credentials: Option<CredentialSource>,
+}
+
+impl MssqlManager {
+    /// Manager connecting with `opts`.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConnectionError` if the resulting config is rejected.
+    pub fn new(opts: &MssqlOptions) -> Result<Self, SqlMiddlewareDbError> {
+        let config = build_tiberius_config(opts);
+        let inner = ConnectionManager::build(config.clone()).map_err(|e| {
+            SqlMiddlewareDbError::ConnectionError(format!(
+                "Failed to configure SQL Server manager: {e}"
+            ))
+        })?;
+        Ok(Self {
+            config,
+            inner,
+            user: opts.user.clone(),
+            credentials: opts.credentials.clone().map(CredentialSource::new),
         })
     }
 }
 
+impl ManageConnection for MssqlManager {
+    type Connection = MssqlClient;
+    type Error = bb8_tiberius::Error;
+
+    #[allow(clippy::manual_async_fn)]
+    fn connect(&self) -> impl Future<Output = Result<Self::Connection, Self::Error>> + Send {
+        let mut config = self.config.clone();
+        let user = self.user.clone();
+        let credentials = self.credentials.clone();
+        async move {
+            if let Some(source) = credentials
+                && let Some(creds) = source.current().await
+            {
+                let user = creds.user.unwrap_or(user);
+                config.authentication(AuthMethod::sql_server(user, creds.password));
+            }
+            ConnectionManager::new(config).connect().await
+        }
+    }
+
+    /// Checks the connection and rolls back any transaction left open on it, such as one whose
+    /// [`Tx`](crate::mssql::Tx) was dropped where it could not roll back.
+    #[allow(clippy::manual_async_fn)]
+    fn is_valid(
+        &self,
+        conn: &mut Self::Connection,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async move {
+            conn.simple_query(CHECKOUT_PROBE)
+                .await?
+                .into_results()
+                .await?;
+            Ok(())
+        }
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        self.inner.has_broken(conn)
+    }
+}
+
 fn build_tiberius_config(opts: &MssqlOptions) -> TiberiusConfig {
     let mut config = TiberiusConfig::new();
     config.host(&opts.server);
@@ -1,5 +1,5 @@
 use super::config::MssqlClient;
-use super::query::{bind_query_params, build_result_set, convert_affected_rows};
+use super::query::{bind_query_params, build_result_set, build_result_sets, convert_affected_rows};
 use crate::middleware::{ResultSet, RowValues, SqlMiddlewareDbError};
 
 /// Execute a batch of SQL statements for SQL Server.
@@ -36,6 +36,20 @@ pub async fn execute_select(
     build_result_set(mssql_client, query, params).await
 }
 
+/// Execute a query and return every result set it produces (e.g., from a stored procedure).
+///
+/// # Errors
+///
+/// Returns `SqlMiddlewareDbError::ExecutionError` if execution or result processing fails.
+#[allow(dead_code)]
+pub async fn execute_select_multi(
+    mssql_client: &mut MssqlClient,
+    query: &str,
+    params: &[RowValues],
+) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+    build_result_sets(mssql_client, query, params).await
+}
+
 /// Execute a DML query (INSERT, UPDATE, DELETE) with parameters.
 ///
 /// # Errors
@@ -1,12 +1,14 @@
 //! SQL Server backend glue (mirrors the layout used by the other backends).
 //!
 //! Submodules:
+//! - `backend`: [`BackendApi`](crate::backend::BackendApi) implementation
 //! - `config`: connection configuration and pool setup (builder pattern)
 //! - `params`: parameter conversion between middleware and SQL Server types
 //! - `query`: result extraction, building, and query binding
 //! - `executor`: database operation execution
 //! - `client`: raw client creation utilities
 
+pub mod backend;
 pub mod client;
 pub mod config;
 pub mod executor;
@@ -16,10 +18,11 @@ pub mod query;
 pub mod transaction;
 
 // Re-export the public API
+pub use backend::MssqlBackend;
 pub use client::create_mssql_client;
-pub use config::{MssqlClient, MssqlOptions, MssqlOptionsBuilder};
-pub use executor::{execute_batch, execute_dml, execute_select};
+pub use config::{MssqlClient, MssqlManager, MssqlOptions, MssqlOptionsBuilder};
+pub use executor::{execute_batch, execute_dml, execute_select, execute_select_multi};
 pub use params::Params;
 pub use prepared::MssqlNonTxPreparedStatement;
-pub use query::build_result_set;
+pub use query::{build_result_set, build_result_sets};
 pub use transaction::{Prepared, Tx, begin_transaction};
@@ -2,7 +2,10 @@ use std::borrow::Cow;
 use std::fmt::Write;
 use tiberius::{ColumnData, ToSql};
 
-use crate::middleware::{ConversionMode, ParamConverter, RowValues, SqlMiddlewareDbError};
+use crate::custom::CustomValue;
+use crate::middleware::{
+    ConversionMode, DatabaseType, ParamConverter, RowValues, SqlMiddlewareDbError,
+};
 
 /// Container for SQL Server parameters with lifetime tracking
 #[allow(dead_code)]
@@ -41,8 +44,11 @@ impl<'a> ParamConverter<'a> for Params<'a> {
 
     fn convert_sql_params(
         params: &'a [RowValues],
-        _mode: ConversionMode,
+        mode: ConversionMode,
     ) -> Result<Self::Converted, SqlMiddlewareDbError> {
+        if mode.is_strict() {
+            DatabaseType::Mssql.check_strict_params(params)?;
+        }
         Self::convert(params)
     }
 
@@ -58,7 +64,7 @@ impl ToSql for RowValues {
         match self {
             RowValues::Int(i) => ColumnData::I64(Some(*i)),
             RowValues::Float(f) => ColumnData::F64(Some(*f)),
-            RowValues::Text(s) => ColumnData::String(Some(Cow::from(s.as_str()))),
+            RowValues::Text(s) => ColumnData::String(Some(Cow::from(&**s))),
             RowValues::Bool(b) => ColumnData::Bit(Some(*b)),
             RowValues::Timestamp(dt) => {
                 // Use thread_local storage for efficient timestamp formatting
@@ -76,8 +82,32 @@ impl ToSql for RowValues {
                 })
             }
             RowValues::Null => ColumnData::String(None),
+            #[cfg(feature = "json")]
             RowValues::JSON(jsval) => ColumnData::String(Some(Cow::from(jsval.to_string()))),
-            RowValues::Blob(bytes) => ColumnData::Binary(Some(Cow::from(bytes.as_slice()))),
+            RowValues::Blob(bytes) => ColumnData::Binary(Some(Cow::from(&**bytes))),
+            RowValues::Custom(custom) => custom_column_data(&**custom),
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(wkb) => ColumnData::Binary(Some(Cow::from(&**wkb))),
         }
     }
 }
+
+/// Column data for a custom value's encoding, owned because the encoding is a temporary.
+fn custom_column_data(custom: &dyn CustomValue) -> ColumnData<'static> {
+    match custom.encode() {
+        RowValues::Text(s) => ColumnData::String(Some(Cow::Owned(s.to_string()))),
+        RowValues::Blob(bytes) => ColumnData::Binary(Some(Cow::Owned(bytes.to_vec()))),
+        RowValues::Custom(inner) => custom_column_data(&*inner),
+        RowValues::Int(i) => ColumnData::I64(Some(i)),
+        RowValues::Float(f) => ColumnData::F64(Some(f)),
+        RowValues::Bool(b) => ColumnData::Bit(Some(b)),
+        RowValues::Timestamp(dt) => ColumnData::String(Some(Cow::Owned(
+            dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+        ))),
+        RowValues::Null => ColumnData::String(None),
+        #[cfg(feature = "json")]
+        RowValues::JSON(jsval) => ColumnData::String(Some(Cow::Owned(jsval.to_string()))),
+        #[cfg(feature = "geo")]
+        RowValues::Geometry(wkb) => ColumnData::Binary(Some(Cow::Owned(wkb.to_vec()))),
+    }
+}
@@ -1,11 +1,12 @@
 use chrono::NaiveDateTime;
 use futures_util::TryStreamExt;
-use tiberius::Query;
+use tiberius::{Query, QueryItem};
 
 use super::config::MssqlClient;
 use crate::adapters::result_set::{column_count, init_result_set};
+use crate::custom::CustomValue;
+use crate::middleware::{ConversionMode, DatabaseType, ResultSet, RowValues, SqlMiddlewareDbError};
 use crate::query_utils::extract_column_names;
-use crate::middleware::{ResultSet, RowValues, SqlMiddlewareDbError};
 
 /// Build a result set from a SQL Server query execution
 ///
@@ -16,6 +17,23 @@ pub async fn build_result_set(
     query: &str,
     params: &[RowValues],
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
+    build_result_set_with_mode(client, query, params, ConversionMode::Query).await
+}
+
+/// [`build_result_set`], with `ConversionMode::Strict` rejecting lossy parameters and values.
+///
+/// # Errors
+/// Same as [`build_result_set`], plus `ParameterError`/`ExecutionError` for values strict mode
+/// rejects.
+pub(crate) async fn build_result_set_with_mode(
+    client: &mut MssqlClient,
+    query: &str,
+    params: &[RowValues],
+    mode: ConversionMode,
+) -> Result<ResultSet, SqlMiddlewareDbError> {
+    if mode.is_strict() {
+        DatabaseType::Mssql.check_strict_params(params)?;
+    }
     // Use the shared function to prepare and bind the query
     let query_builder = bind_query_params(query, params);
 
@@ -48,6 +66,10 @@ pub async fn build_result_set(
         let mut row_values = Vec::with_capacity(col_count);
 
         for i in 0..col_count {
+            if mode.is_strict() {
+                row_values.push(extract_value_strict(&row_result, i)?);
+                continue;
+            }
             // Extract values from the row
             if let Some(value) = extract_value(&row_result, i) {
                 row_values.push(value);
@@ -62,6 +84,48 @@ pub async fn build_result_set(
     Ok(result_set)
 }
 
+/// Build one result set per result returned by a SQL Server batch or stored procedure.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ExecutionError` if query execution, parameter conversion, or result processing fails.
+pub async fn build_result_sets(
+    client: &mut MssqlClient,
+    query: &str,
+    params: &[RowValues],
+) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+    let query_builder = bind_query_params(query, params);
+
+    let mut stream = query_builder.query(client).await.map_err(|e| {
+        SqlMiddlewareDbError::ExecutionError(format!("SQL Server query error: {e}"))
+    })?;
+
+    // Each result starts with a metadata item carrying its columns, followed by its rows.
+    let mut result_sets: Vec<ResultSet> = Vec::new();
+    while let Some(item) = stream.try_next().await.map_err(|e| {
+        SqlMiddlewareDbError::ExecutionError(format!("SQL Server row fetch error: {e}"))
+    })? {
+        match item {
+            QueryItem::Metadata(meta) => {
+                let column_names = extract_column_names(meta.columns().iter(), |col| col.name());
+                result_sets.push(init_result_set(column_names, 10));
+            }
+            QueryItem::Row(row) => {
+                let result_set = result_sets.last_mut().ok_or_else(|| {
+                    SqlMiddlewareDbError::ExecutionError(
+                        "SQL Server returned a row before its column metadata".to_string(),
+                    )
+                })?;
+                let row_values = (0..row.len())
+                    .map(|i| extract_value(&row, i).unwrap_or(RowValues::Null))
+                    .collect();
+                result_set.add_row_values(row_values);
+            }
+        }
+    }
+
+    Ok(result_sets)
+}
+
 /// Extract a value from a row at a specific index
 fn extract_value(row: &tiberius::Row, idx: usize) -> Option<RowValues> {
     // Since Tiberius Row API is a bit complex and varies by version,
@@ -102,12 +166,12 @@ fn extract_value(row: &tiberius::Row, idx: usize) -> Option<RowValues> {
         }
 
         // Otherwise, just return as text
-        return Some(RowValues::Text(val.to_string()));
+        return Some(RowValues::Text(val.into()));
     }
 
     // Try bytes (binary data)
     if let Ok(Some(val)) = row.try_get::<&[u8], _>(idx) {
-        return Some(RowValues::Blob(val.to_vec()));
+        return Some(RowValues::Blob(val.into()));
     }
 
     // Check if the value is NULL
@@ -119,23 +183,66 @@ fn extract_value(row: &tiberius::Row, idx: usize) -> Option<RowValues> {
     None
 }
 
+/// Extract a value without the lossy fallbacks of [`extract_value`]: text is never reinterpreted
+/// as a timestamp, and a column type with no `RowValues` mapping is an error instead of NULL.
+fn extract_value_strict(
+    row: &tiberius::Row,
+    idx: usize,
+) -> Result<RowValues, SqlMiddlewareDbError> {
+    if let Ok(val) = row.try_get::<u8, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, |v| RowValues::Int(i64::from(v))));
+    }
+    if let Ok(val) = row.try_get::<i16, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, |v| RowValues::Int(i64::from(v))));
+    }
+    if let Ok(val) = row.try_get::<i32, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, |v| RowValues::Int(i64::from(v))));
+    }
+    if let Ok(val) = row.try_get::<i64, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, RowValues::Int));
+    }
+    if let Ok(val) = row.try_get::<f32, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, |v| RowValues::Float(f64::from(v))));
+    }
+    if let Ok(val) = row.try_get::<f64, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, RowValues::Float));
+    }
+    if let Ok(val) = row.try_get::<bool, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, RowValues::Bool));
+    }
+    if let Ok(val) = row.try_get::<&str, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, |v| RowValues::Text(v.into())));
+    }
+    if let Ok(val) = row.try_get::<&[u8], _>(idx) {
+        return Ok(val.map_or(RowValues::Null, |v| RowValues::Blob(v.into())));
+    }
+    if let Ok(val) = row.try_get::<NaiveDateTime, _>(idx) {
+        return Ok(val.map_or(RowValues::Null, RowValues::Timestamp));
+    }
+    let column = &row.columns()[idx];
+    Err(SqlMiddlewareDbError::ExecutionError(format!(
+        "strict mode: column `{}` has SQL Server type {:?}, which has no lossless RowValues mapping",
+        column.name(),
+        column.column_type()
+    )))
+}
+
 /// Bind parameters directly to the query for SQL Server.
 ///
 /// # Errors
 ///
 /// This function does not execute and therefore cannot error; callers must handle execution errors.
 #[must_use]
-pub fn bind_query_params<'a>(query: &'a str, params: &[RowValues]) -> Query<'a> {
+pub fn bind_query_params<'a>(query: &'a str, params: &'a [RowValues]) -> Query<'a> {
     // Create the query builder
     let mut query_builder = Query::new(query);
 
-    // Bind parameters directly - not using OwnedParam as intermediary
-    // since tiberius Query will own the data
+    // Text and binary values are bound by reference; the query borrows `params` until it runs.
     for param in params {
         match param {
             RowValues::Int(i) => query_builder.bind(*i),
             RowValues::Float(f) => query_builder.bind(*f),
-            RowValues::Text(s) => query_builder.bind(s.clone()),
+            RowValues::Text(s) => query_builder.bind(&**s),
             RowValues::Bool(b) => query_builder.bind(*b),
             RowValues::Timestamp(dt) => {
                 // Format timestamps efficiently
@@ -143,15 +250,40 @@ pub fn bind_query_params<'a>(query: &'a str, params: &[RowValues]) -> Query<'a>
                 query_builder.bind(formatted);
             }
             RowValues::Null => query_builder.bind(Option::<String>::None),
+            #[cfg(feature = "json")]
             RowValues::JSON(jsval) => query_builder.bind(jsval.to_string()),
-            RowValues::Blob(bytes) => query_builder.bind(bytes.clone()),
+            RowValues::Blob(bytes) => query_builder.bind(&**bytes),
+            RowValues::Custom(custom) => bind_custom(&mut query_builder, &**custom),
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(wkb) => query_builder.bind(&**wkb),
         }
     }
 
     query_builder
 }
 
+/// Bind a custom value's encoding, which is owned because it is built on demand.
+fn bind_custom(query_builder: &mut Query<'_>, custom: &dyn CustomValue) {
+    match custom.encode() {
+        RowValues::Int(i) => query_builder.bind(i),
+        RowValues::Float(f) => query_builder.bind(f),
+        RowValues::Text(s) => query_builder.bind(s.to_string()),
+        RowValues::Bool(b) => query_builder.bind(b),
+        RowValues::Timestamp(dt) => {
+            query_builder.bind(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string());
+        }
+        RowValues::Null => query_builder.bind(Option::<String>::None),
+        #[cfg(feature = "json")]
+        RowValues::JSON(jsval) => query_builder.bind(jsval.to_string()),
+        RowValues::Blob(bytes) => query_builder.bind(bytes.to_vec()),
+        RowValues::Custom(inner) => bind_custom(query_builder, &*inner),
+        #[cfg(feature = "geo")]
+        RowValues::Geometry(wkb) => query_builder.bind(wkb.to_vec()),
+    }
+}
+
 pub(crate) fn convert_affected_rows(rows: u64) -> Result<usize, SqlMiddlewareDbError> {
-    usize::try_from(rows)
-        .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("Invalid rows affected count: {e}")))
+    usize::try_from(rows).map_err(|e| {
+        SqlMiddlewareDbError::ExecutionError(format!("Invalid rows affected count: {e}"))
+    })
 }
@@ -1,18 +1,29 @@
 use tiberius::Query;
 
-use crate::middleware::{ResultSet, RowValues, SqlMiddlewareDbError};
+use crate::executor::QueryTarget;
+use crate::middleware::{ConversionMode, ResultSet, RowValues, SqlMiddlewareDbError};
+use crate::pool::checkout::PoolLink;
+use crate::query_builder::QueryBuilder;
+use crate::tx_drop::DropGuard;
 use crate::tx_outcome::TxOutcome;
 
 use super::config::MssqlClient;
-use super::query::{build_result_set, convert_affected_rows};
+use super::query::{
+    build_result_set, build_result_set_with_mode, build_result_sets, convert_affected_rows,
+};
 
 /// Lightweight transaction wrapper for SQL Server.
 ///
-/// Dropping a `Tx` without calling [`commit`](Tx::commit) or [`rollback`](Tx::rollback)
-/// leaves the connection mid-transaction. Always finish the transaction explicitly.
+/// Dropping a `Tx` without calling [`commit`](Tx::commit) or [`rollback`](Tx::rollback) rolls
+/// it back on a multi-threaded tokio runtime. Elsewhere the drop cannot wait for the rollback;
+/// the connection stays mid-transaction until the pool next checks it out, which rolls it back
+/// before handing it over. Either way the drop is reported (see [`tx_drop`](crate::tx_drop)).
 pub struct Tx<'a> {
     client: &'a mut MssqlClient,
     open: bool,
+    /// Pool the handle was begun from; none when begun on a raw client.
+    pool: PoolLink,
+    guard: DropGuard,
 }
 
 /// Prepared statement wrapper for SQL Server.
@@ -25,21 +36,66 @@ pub struct Prepared {
 
 /// Begin a new transaction on the provided SQL Server connection.
 ///
+/// The handle cannot see the pool the client came from, so its statements are not rate-limited;
+/// [`MiddlewarePoolConnection::begin_mssql_transaction`](crate::pool::MiddlewarePoolConnection::begin_mssql_transaction)
+/// begins one that is.
+///
 /// # Errors
 ///
 /// Returns `SqlMiddlewareDbError::ExecutionError` if issuing the BEGIN statement fails.
-pub async fn begin_transaction(client: &mut MssqlClient) -> Result<Tx<'_>, SqlMiddlewareDbError> {
-    Query::new("BEGIN TRANSACTION")
-        .execute(client)
-        .await
-        .map_err(|e| {
-            SqlMiddlewareDbError::ExecutionError(format!("MSSQL begin transaction error: {e}"))
-        })?;
+#[track_caller]
+pub fn begin_transaction(
+    client: &mut MssqlClient,
+) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+    let guard = DropGuard::new("mssql");
+    async move {
+        Query::new("BEGIN TRANSACTION")
+            .execute(&mut *client)
+            .await
+            .map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("MSSQL begin transaction error: {e}"))
+            })?;
 
-    Ok(Tx { client, open: true })
+        Ok(Tx {
+            client,
+            open: true,
+            pool: PoolLink::default(),
+            guard: guard.arm(),
+        })
+    }
 }
 
 impl Tx<'_> {
+    /// Stop reporting this transaction if it is dropped, for crate helpers that roll back by
+    /// dropping it.
+    pub(crate) fn untracked(mut self) -> Self {
+        self.guard.disarm();
+        self
+    }
+
+    /// Link the handle to `pool`: take a token from its rate limit before each statement
+    /// and echo what its query builder runs.
+    pub(crate) fn linked(mut self, pool: PoolLink) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// The pool this handle was begun from, if any.
+    pub(crate) fn pool(&self) -> &PoolLink {
+        &self.pool
+    }
+
+    /// A handle to the same open transaction borrowing this one, for query targets. It never
+    /// finishes the transaction, so dropping it neither rolls back nor reports.
+    pub(crate) fn reborrow(&mut self) -> Tx<'_> {
+        Tx {
+            client: &mut *self.client,
+            open: false,
+            pool: self.pool.clone(),
+            guard: DropGuard::untracked(),
+        }
+    }
+
     /// Prepare a SQL statement tied to this transaction.
     ///
     /// # Errors
@@ -56,6 +112,7 @@ impl Tx<'_> {
     ///
     /// Returns `SqlMiddlewareDbError::ExecutionError` if execution fails.
     pub async fn execute_batch(&mut self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
         Query::new(sql).execute(self.client).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("MSSQL tx execute_batch error: {e}"))
         })?;
@@ -71,6 +128,7 @@ impl Tx<'_> {
         query: &str,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
         let query_builder = super::query::bind_query_params(query, params);
         let exec_result = query_builder.execute(self.client).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("MSSQL tx execute error: {e}"))
@@ -90,6 +148,7 @@ impl Tx<'_> {
         prepared: &Prepared,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
         let query_builder = super::query::bind_query_params(&prepared.sql, params);
         let exec_result = query_builder.execute(self.client).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("MSSQL tx execute error: {e}"))
@@ -109,6 +168,7 @@ impl Tx<'_> {
         prepared: &Prepared,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
         build_result_set(self.client, &prepared.sql, params).await
     }
 
@@ -121,15 +181,49 @@ impl Tx<'_> {
         query: &str,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
         build_result_set(self.client, query, params).await
     }
 
+    /// [`Tx::query`] with an explicit conversion mode.
+    pub(crate) async fn query_with_mode(
+        &mut self,
+        query: &str,
+        params: &[RowValues],
+        mode: ConversionMode,
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
+        build_result_set_with_mode(self.client, query, params, mode).await
+    }
+
+    /// Start a [`QueryBuilder`] that runs inside this transaction.
+    ///
+    /// Placeholders are not translated unless the builder asks for it with
+    /// [`translation`](QueryBuilder::translation).
+    pub fn query_builder<'q>(&mut self, sql: &'q str) -> QueryBuilder<'_, 'q> {
+        QueryBuilder::new_target(QueryTarget::from(self), sql)
+    }
+
+    /// Execute a query inside the transaction and return every result set it produces.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if execution or result construction fails.
+    pub async fn query_multi(
+        &mut self,
+        query: &str,
+        params: &[RowValues],
+    ) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
+        build_result_sets(self.client, query, params).await
+    }
+
     /// Commit the transaction.
     ///
     /// # Errors
     ///
     /// Returns `SqlMiddlewareDbError` if commit fails.
     pub async fn commit(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
         if self.open {
             Query::new("COMMIT TRANSACTION")
                 .execute(self.client)
@@ -148,6 +242,7 @@ impl Tx<'_> {
     ///
     /// Returns `SqlMiddlewareDbError` if rollback fails.
     pub async fn rollback(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
         if self.open {
             Query::new("ROLLBACK TRANSACTION")
                 .execute(self.client)
@@ -160,3 +255,23 @@ impl Tx<'_> {
         Ok(TxOutcome::without_restored_connection())
     }
 }
+
+impl Drop for Tx<'_> {
+    fn drop(&mut self) {
+        use tokio::runtime::{Handle, RuntimeFlavor};
+
+        if !self.open {
+            return;
+        }
+        // The client is borrowed, so the rollback cannot be spawned; wait for it where the
+        // runtime allows blocking this thread. Otherwise the pool's checkout probe rolls back.
+        if let Ok(handle) = Handle::try_current()
+            && handle.runtime_flavor() == RuntimeFlavor::MultiThread
+        {
+            let client = &mut *self.client;
+            let _ = tokio::task::block_in_place(|| {
+                handle.block_on(Query::new("ROLLBACK TRANSACTION").execute(client))
+            });
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Query parameters from plain Rust values.
+//!
+//! [`QueryBuilder::params`](crate::QueryBuilder::params) takes anything implementing
+//! [`IntoParams`]: a `RowValues` slice, array or `Vec` as before, or a tuple of values that
+//! implement [`IntoRowValues`], so the common case needs no hand-built `Vec<RowValues>`:
+//!
+//! ```rust,no_run
+//! use sql_middleware::prelude::*;
+//!
+//! # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+//! let nickname: Option<&str> = None;
+//! conn.query("INSERT INTO users (id, name, nickname, active) VALUES ($1, $2, $3, $4)")
+//!     .params((7_i64, "ann", nickname, true))
+//!     .dml()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! A one-element tuple needs its trailing comma: `.params((id,))`. Tuples of up to 16 values
+//! are supported; build a `Vec<RowValues>` beyond that.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+
+use crate::types::RowValues;
+
+/// A Rust value that converts to one [`RowValues`].
+///
+/// `None` becomes [`RowValues::Null`]. Implement it for your own types to pass them straight to
+/// `.params(...)`.
+pub trait IntoRowValues {
+    fn into_row_values(self) -> RowValues;
+}
+
+impl IntoRowValues for RowValues {
+    fn into_row_values(self) -> RowValues {
+        self
+    }
+}
+
+impl IntoRowValues for &RowValues {
+    fn into_row_values(self) -> RowValues {
+        self.clone()
+    }
+}
+
+macro_rules! int_into_row_values {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoRowValues for $ty {
+                fn into_row_values(self) -> RowValues {
+                    RowValues::Int(i64::from(self))
+                }
+            }
+        )*
+    };
+}
+
+int_into_row_values!(i8, i16, i32, i64, u8, u16, u32);
+
+impl IntoRowValues for f64 {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Float(self)
+    }
+}
+
+impl IntoRowValues for f32 {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Float(f64::from(self))
+    }
+}
+
+impl IntoRowValues for bool {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Bool(self)
+    }
+}
+
+impl IntoRowValues for &str {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Text(self.into())
+    }
+}
+
+impl IntoRowValues for String {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Text(self.into())
+    }
+}
+
+impl IntoRowValues for &String {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Text(self.as_str().into())
+    }
+}
+
+impl IntoRowValues for Arc<str> {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Text(self)
+    }
+}
+
+impl IntoRowValues for &[u8] {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Blob(self.into())
+    }
+}
+
+impl IntoRowValues for Vec<u8> {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Blob(self.into())
+    }
+}
+
+impl IntoRowValues for NaiveDateTime {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Timestamp(self)
+    }
+}
+
+#[cfg(feature = "json")]
+impl IntoRowValues for serde_json::Value {
+    fn into_row_values(self) -> RowValues {
+        RowValues::JSON(self)
+    }
+}
+
+/// Bound as its hyphenated text form, which Postgres casts to `uuid` and the other backends
+/// store as text.
+#[cfg(feature = "uuid")]
+impl IntoRowValues for uuid::Uuid {
+    fn into_row_values(self) -> RowValues {
+        RowValues::Text(self.hyphenated().to_string().into())
+    }
+}
+
+impl<T: IntoRowValues> IntoRowValues for Option<T> {
+    fn into_row_values(self) -> RowValues {
+        self.map_or(RowValues::Null, IntoRowValues::into_row_values)
+    }
+}
+
+/// A statement's parameter list: borrowed `RowValues` or a tuple of [`IntoRowValues`] values.
+pub trait IntoParams<'q> {
+    fn into_params(self) -> Cow<'q, [RowValues]>;
+}
+
+impl<'q> IntoParams<'q> for &'q [RowValues] {
+    fn into_params(self) -> Cow<'q, [RowValues]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'q, const N: usize> IntoParams<'q> for &'q [RowValues; N] {
+    fn into_params(self) -> Cow<'q, [RowValues]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'q> IntoParams<'q> for &'q Vec<RowValues> {
+    fn into_params(self) -> Cow<'q, [RowValues]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'q> IntoParams<'q> for Vec<RowValues> {
+    fn into_params(self) -> Cow<'q, [RowValues]> {
+        Cow::Owned(self)
+    }
+}
+
+impl<'q> IntoParams<'q> for () {
+    fn into_params(self) -> Cow<'q, [RowValues]> {
+        Cow::Borrowed(&[])
+    }
+}
+
+macro_rules! tuple_into_params {
+    ($($name:ident),+) => {
+        impl<'q, $($name: IntoRowValues),+> IntoParams<'q> for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_params(self) -> Cow<'q, [RowValues]> {
+                let ($($name,)+) = self;
+                Cow::Owned(vec![$($name.into_row_values()),+])
+            }
+        }
+    };
+}
+
+tuple_into_params!(A);
+tuple_into_params!(A, B);
+tuple_into_params!(A, B, C);
+tuple_into_params!(A, B, C, D);
+tuple_into_params!(A, B, C, D, E);
+tuple_into_params!(A, B, C, D, E, F);
+tuple_into_params!(A, B, C, D, E, F, G);
+tuple_into_params!(A, B, C, D, E, F, G, H);
+tuple_into_params!(A, B, C, D, E, F, G, H, I);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J, K);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J, K, L);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+tuple_into_params!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
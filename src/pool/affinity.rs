@@ -0,0 +1,92 @@
+//! Connection affinity: one pooled connection shared by a whole workflow.
+//!
+//! Temp tables, `SQLite` `ATTACH`ments, and Postgres session settings live on a single
+//! connection. Code that checks out a fresh connection for each step can land on a different one
+//! mid-flow and lose that state. [`ConfigAndPool::lease_connection`] pins one connection behind a
+//! cloneable [`LeasedConnection`] handle that can be passed to other call sites and tasks. Every
+//! clone reaches the same connection, one caller at a time, until
+//! [`LeasedConnection::release`] hands it back to the pool.
+//!
+//! Whatever state the workflow left on the connection (temp tables, attachments) goes back to
+//! the pool with it; drop it before releasing if later borrowers must not see it.
+
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+use super::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::error::SqlMiddlewareDbError;
+
+/// A pooled connection pinned to one workflow, from [`ConfigAndPool::lease_connection`].
+///
+/// Clones share the connection. It returns to the pool when [`release`](Self::release) is
+/// called or the last clone drops.
+#[derive(Clone)]
+pub struct LeasedConnection {
+    conn: Arc<Mutex<Option<MiddlewarePoolConnection>>>,
+}
+
+impl fmt::Debug for LeasedConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeasedConnection")
+            .field("holders", &Arc::strong_count(&self.conn))
+            .finish_non_exhaustive()
+    }
+}
+
+impl LeasedConnection {
+    /// Borrow the leased connection, waiting while another holder is using it.
+    ///
+    /// Keep the guard only as long as needed; other holders wait until it drops.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConnectionError` if the lease has been released.
+    pub async fn lock(
+        &self,
+    ) -> Result<MappedMutexGuard<'_, MiddlewarePoolConnection>, SqlMiddlewareDbError> {
+        MutexGuard::try_map(self.conn.lock().await, Option::as_mut).map_err(|_| {
+            SqlMiddlewareDbError::ConnectionError("leased connection was released".to_string())
+        })
+    }
+
+    /// Return the connection to the pool now, ending the lease for every clone.
+    ///
+    /// Waits for the current holder, if any, to finish. Releasing twice is a no-op.
+    pub async fn release(self) {
+        self.conn.lock().await.take();
+    }
+
+    /// Whether the lease has been released.
+    pub async fn is_released(&self) -> bool {
+        self.conn.lock().await.is_none()
+    }
+}
+
+impl ConfigAndPool {
+    /// Check out a connection and pin it behind a shareable [`LeasedConnection`].
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+    /// let lease = cap.lease_connection().await?;
+    /// lease
+    ///     .lock()
+    ///     .await?
+    ///     .execute_batch("CREATE TEMP TABLE staging (id INTEGER)")
+    ///     .await?;
+    /// // ... hand `lease.clone()` to other steps; they all see `staging` ...
+    /// lease.release().await;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Bubbles up pool checkout errors.
+    pub async fn lease_connection(&self) -> Result<LeasedConnection, SqlMiddlewareDbError> {
+        let conn = self.get_connection().await?;
+        Ok(LeasedConnection {
+            conn: Arc::new(Mutex::new(Some(conn))),
+        })
+    }
+}
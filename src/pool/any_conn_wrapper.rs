@@ -28,4 +28,11 @@ pub enum AnyConnWrapper<'a> {
     /// Turso database connection
     #[cfg(feature = "turso")]
     Turso(&'a turso::Connection),
+    /// Placeholder that keeps `'a` in use when no backend feature is enabled; never constructed.
+    #[cfg(not(any_backend))]
+    #[doc(hidden)]
+    Unavailable(
+        std::marker::PhantomData<&'a mut ()>,
+        std::convert::Infallible,
+    ),
 }
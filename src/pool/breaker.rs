@@ -0,0 +1,272 @@
+//! Circuit breaker around backend failures.
+//!
+//! After `failure_threshold` consecutive connection failures the breaker opens and
+//! [`ConfigAndPool::get_connection`] fails fast with
+//! [`SqlMiddlewareDbError::CircuitOpen`] instead of waiting out checkout timeouts. Once `open_for`
+//! has passed on the pool's clock, the next checkout is a probe: it runs `SELECT 1`, closing the
+//! breaker on success and reopening it on failure. Other callers keep failing fast while the probe
+//! is in flight.
+//!
+//! Connection failures are counted automatically at checkout and for statements a checked-out
+//! connection runs through the query builder or `execute_batch`; a statement that succeeds resets
+//! the count. Helpers that run several statements on the connection (`execute_dml_atomic`,
+//! `bulk_insert`, group commit, `with_session_context`, `copy_table`) count once per call.
+//! Checking a connection out is not a success by itself, and only a successful probe closes an
+//! open breaker. Report work done elsewhere (e.g., on backend transaction handles) with
+//! [`ConfigAndPool::record_result`].
+//!
+//! [`ConfigAndPool::with_circuit_breaker_jitter`] lengthens each open period by a random share of
+//! `open_for`, drawn from the pool's [`Jitter`], so pools that tripped together don't all probe at
+//! the same instant.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::checkout::PoolContext;
+use super::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::error::SqlMiddlewareDbError;
+use crate::jitter::{Jitter, up_to};
+
+/// Observable breaker state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally.
+    Closed,
+    /// Requests fail fast.
+    Open,
+    /// One probe checkout is deciding whether to close again.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A probe is in flight; if it is abandoned (e.g., its future is dropped), another probe is
+    /// allowed at `retry_at`.
+    HalfOpen {
+        retry_at: Instant,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    /// Largest extra share of `open_for` added to an open period.
+    jitter_ratio: f64,
+    state: Mutex<State>,
+}
+
+#[derive(Clone, Copy)]
+enum Admit {
+    Normal,
+    Probe,
+}
+
+impl CircuitBreaker {
+    fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            jitter_ratio: 0.0,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// How long the breaker stays open this time.
+    fn open_period(&self, jitter: &dyn Jitter) -> Duration {
+        if self.jitter_ratio > 0.0 {
+            self.open_for + up_to(self.open_for.mul_f64(self.jitter_ratio), jitter)
+        } else {
+            self.open_for
+        }
+    }
+
+    fn admit(&self, now: Instant, jitter: &dyn Jitter) -> Result<Admit, SqlMiddlewareDbError> {
+        let mut state = self.lock();
+        match *state {
+            State::Closed { .. } => Ok(Admit::Normal),
+            State::Open { until } | State::HalfOpen { retry_at: until } if now >= until => {
+                *state = State::HalfOpen {
+                    retry_at: now + self.open_period(jitter),
+                };
+                Ok(Admit::Probe)
+            }
+            State::Open { until } | State::HalfOpen { retry_at: until } => {
+                Err(SqlMiddlewareDbError::CircuitOpen {
+                    retry_after: until - now,
+                })
+            }
+        }
+    }
+
+    /// A statement succeeded: reset the failure streak, leaving an open breaker to its probe.
+    fn record_success(&self) {
+        let mut state = self.lock();
+        if let State::Closed { .. } = *state {
+            *state = State::Closed { failures: 0 };
+        }
+    }
+
+    /// The probe succeeded.
+    fn close(&self) {
+        *self.lock() = State::Closed { failures: 0 };
+    }
+
+    fn record_failure(&self, now: Instant, jitter: &dyn Jitter) {
+        let mut state = self.lock();
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            // A failed probe (or a late failure while open) reopens immediately.
+            State::Open { .. } | State::HalfOpen { .. } => self.failure_threshold,
+        };
+        *state = if failures >= self.failure_threshold {
+            State::Open {
+                until: now + self.open_period(jitter),
+            }
+        } else {
+            State::Closed { failures }
+        };
+    }
+
+    fn state(&self) -> CircuitState {
+        match *self.lock() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+/// Whether an error says the backend is unreachable, as opposed to a bad query.
+fn is_connection_failure(err: &SqlMiddlewareDbError) -> bool {
+    match err {
+        SqlMiddlewareDbError::ConnectionError(_) => true,
+        #[cfg(feature = "postgres")]
+        SqlMiddlewareDbError::PoolErrorPostgres { .. } => true,
+        #[cfg(feature = "postgres")]
+        SqlMiddlewareDbError::PostgresError(err) => err.is_closed(),
+        #[cfg(feature = "sqlite")]
+        SqlMiddlewareDbError::PoolErrorSqlite { .. } => true,
+        #[cfg(feature = "mssql")]
+        SqlMiddlewareDbError::PoolErrorMssql { .. } => true,
+        #[cfg(feature = "turso")]
+        SqlMiddlewareDbError::PoolErrorTurso { .. } => true,
+        #[cfg(feature = "mssql")]
+        SqlMiddlewareDbError::MssqlError(err) => {
+            matches!(err, tiberius::error::Error::Io { .. })
+        }
+        _ => false,
+    }
+}
+
+impl ConfigAndPool {
+    /// Fail fast after `failure_threshold` consecutive connection failures, for `open_for`.
+    ///
+    /// See the [module docs](crate::pool::breaker) for the half-open probe.
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, open_for: Duration) -> Self {
+        Arc::make_mut(&mut self.context).breaker =
+            Some(Arc::new(CircuitBreaker::new(failure_threshold, open_for)));
+        self
+    }
+
+    /// Lengthen each open period of the circuit breaker by a random amount up to
+    /// `ratio * open_for`, drawn from the pool's jitter source.
+    ///
+    /// Call after [`with_circuit_breaker`](Self::with_circuit_breaker); does nothing without a
+    /// breaker. `ratio` is clamped to `0.0..=1.0`; NaN disables jitter.
+    #[must_use]
+    pub fn with_circuit_breaker_jitter(mut self, ratio: f64) -> Self {
+        if let Some(breaker) = &self.context.breaker {
+            let mut jittered = CircuitBreaker::new(breaker.failure_threshold, breaker.open_for);
+            jittered.jitter_ratio = if ratio.is_nan() {
+                0.0
+            } else {
+                ratio.clamp(0.0, 1.0)
+            };
+            Arc::make_mut(&mut self.context).breaker = Some(Arc::new(jittered));
+        }
+        self
+    }
+
+    /// Current breaker state, or `None` when no breaker is configured.
+    #[must_use]
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.context.breaker.as_deref().map(CircuitBreaker::state)
+    }
+
+    /// Feed the outcome of work done on a checked-out connection to the circuit breaker.
+    ///
+    /// Only connection failures (lost connections, pool errors) count; query errors such as
+    /// constraint violations leave the breaker alone. Does nothing without a breaker. Statements
+    /// run through the query builder or `execute_batch` are recorded already.
+    pub fn record_result<T>(&self, result: &Result<T, SqlMiddlewareDbError>) {
+        self.context.record_result(result);
+    }
+
+    /// Checkout gated by the circuit breaker, if one is configured.
+    pub(super) async fn checkout_guarded(
+        &self,
+        bulkhead: Option<&'static str>,
+    ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+        let Some(breaker) = &self.context.breaker else {
+            return self.checkout(bulkhead).await;
+        };
+        let admit = breaker.admit(self.context.clock.now(), self.context.jitter.as_ref())?;
+        let result = match (self.checkout(bulkhead).await, admit) {
+            // `run_control` leaves the breaker alone, so the outcome is recorded once, below.
+            (Ok(mut conn), Admit::Probe) => conn.run_control("SELECT 1").await.map(|()| conn),
+            (result, _) => result,
+        };
+        match (&result, admit) {
+            (Ok(_), Admit::Probe) => breaker.close(),
+            (Err(_), Admit::Probe) => self.context.record_failure(breaker),
+            (Err(err), Admit::Normal) if is_connection_failure(err) => {
+                self.context.record_failure(breaker);
+            }
+            (_, Admit::Normal) => {}
+        }
+        result
+    }
+}
+
+impl PoolContext {
+    /// Feed one outcome to the circuit breaker, if there is one.
+    pub(crate) fn record_result<T>(&self, result: &Result<T, SqlMiddlewareDbError>) {
+        let Some(breaker) = &self.breaker else {
+            return;
+        };
+        match result {
+            Ok(_) => breaker.record_success(),
+            Err(err) if is_connection_failure(err) => self.record_failure(breaker),
+            Err(_) => {}
+        }
+    }
+
+    fn record_failure(&self, breaker: &CircuitBreaker) {
+        breaker.record_failure(self.clock.now(), self.jitter.as_ref());
+    }
+}
+
+#[cfg(any_backend)]
+impl MiddlewarePoolConnection {
+    /// Feed a statement's outcome to the pool's circuit breaker, if there is one.
+    pub(crate) fn record_result<T>(&self, result: &Result<T, SqlMiddlewareDbError>) {
+        if let Some(context) = self.context() {
+            context.pool.record_result(result);
+        }
+    }
+}
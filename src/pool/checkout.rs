@@ -0,0 +1,130 @@
+//! Checking connections out of a [`ConfigAndPool`] with the pool's settings attached.
+//!
+//! A pool keeps its connection-facing settings in one shared context. Every checkout attaches
+//! the current context to the connection, together with the concurrency permits the checkout
+//! holds, as a [`ConnectionContext`]. Changing the pool's settings later gives new checkouts a new
+//! context; connections already checked out keep the one they started with.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use super::breaker::CircuitBreaker;
+use super::limits::HeldLimits;
+use super::rate_limit::TokenBucket;
+use super::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::audit::AuditTables;
+use crate::clock::{Clock, system_clock};
+use crate::error::SqlMiddlewareDbError;
+use crate::jitter::{Jitter, system_jitter};
+
+/// Settings a pool shares with the connections checked out of it.
+#[derive(Debug, Clone)]
+pub(crate) struct PoolContext {
+    /// Time source for checkout timeouts, retry delays and rate-limit waits
+    pub(crate) clock: Arc<dyn Clock>,
+    /// Random source for retry jitter
+    pub(crate) jitter: Arc<dyn Jitter>,
+    /// Soft-delete column set with [`super::PoolOptions::soft_delete_column`]
+    pub(crate) soft_delete_column: Option<Arc<str>>,
+    /// Statement rate limit set with [`super::PoolOptions::rate_limit`]
+    pub(crate) rate_limit: Option<Arc<TokenBucket>>,
+    /// Statement echo toggled with [`ConfigAndPool::set_debug_echo`], shared by clones
+    pub(crate) debug_echo: Arc<AtomicBool>,
+    /// Audited tables set with [`super::PoolOptions::audit`]
+    pub(crate) audit: Option<AuditTables>,
+    /// Circuit breaker set with [`ConfigAndPool::with_circuit_breaker`]
+    pub(crate) breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl PoolContext {
+    /// Defaults for a new pool: system clock and jitter, echo from the environment.
+    pub(crate) fn new() -> Self {
+        Self {
+            clock: system_clock(),
+            jitter: system_jitter(),
+            soft_delete_column: None,
+            rate_limit: None,
+            debug_echo: super::echo::from_env(),
+            audit: None,
+            breaker: None,
+        }
+    }
+
+    /// Wait for the pool's rate limiter, if any, before sending a statement.
+    pub(crate) async fn throttle(&self) -> Result<(), SqlMiddlewareDbError> {
+        match &self.rate_limit {
+            Some(bucket) => bucket.acquire(self.clock.as_ref()).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// The pool a transaction handle was begun from, as carried by the handle; the default, for
+/// handles begun on a raw driver client, belongs to no pool.
+///
+/// A linked handle takes a token from the pool's rate limit before each statement, and its query
+/// builder echoes statements and audits DML on the pool's audited tables.
+#[cfg(any_backend)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PoolLink(Option<Arc<PoolContext>>);
+
+#[cfg(any_backend)]
+impl PoolLink {
+    /// A link to the pool `context` belongs to.
+    pub(crate) fn of(context: &Arc<PoolContext>) -> Self {
+        Self(Some(Arc::clone(context)))
+    }
+
+    /// Wait for the pool's rate limiter, if any, before sending a statement.
+    pub(crate) async fn throttle(&self) -> Result<(), SqlMiddlewareDbError> {
+        match &self.0 {
+            Some(context) => context.throttle().await,
+            None => Ok(()),
+        }
+    }
+
+    /// The pool's statement echo flag, if linked.
+    pub(crate) fn debug_echo_flag(&self) -> Option<&AtomicBool> {
+        self.0.as_deref().map(|context| &*context.debug_echo)
+    }
+
+    /// The pool's audited tables, if linked to a pool that audits any.
+    #[cfg(any(feature = "postgres", feature = "mssql", feature = "turso"))]
+    pub(crate) fn audit_tables(&self) -> Option<&AuditTables> {
+        self.0.as_deref()?.audit.as_ref()
+    }
+}
+
+/// A pool's settings and the concurrency permits one checkout holds, as attached to a
+/// checked-out connection. Opaque; configure the pool with [`super::PoolOptions`].
+#[derive(Debug)]
+pub struct ConnectionContext {
+    pub(crate) pool: Arc<PoolContext>,
+    // Only builder queries read the limits, and there are none without a backend.
+    #[cfg_attr(not(any_backend), allow(dead_code))]
+    pub(crate) limits: HeldLimits,
+}
+
+impl ConfigAndPool {
+    /// Take this pool's concurrency permits, with one of `bulkhead` if given, then check a
+    /// connection out, attach this pool's context and reset its session.
+    pub(super) async fn checkout(
+        &self,
+        bulkhead: Option<&'static str>,
+    ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+        let limits = HeldLimits::acquire(&self.limits, bulkhead).await?;
+        let pool_ref = self.pool.get().await?;
+        let mut conn = pool_ref
+            .checkout(self.translate_placeholders, self.context.clock.as_ref())
+            .await?;
+        conn.set_context(ConnectionContext {
+            pool: Arc::clone(&self.context),
+            limits,
+        });
+        if let Err(err) = conn.reset_session_with(self.reset_on_return).await {
+            conn.retire().await;
+            return Err(err);
+        }
+        Ok(conn)
+    }
+}
@@ -1,47 +1,61 @@
 mod mssql;
 mod postgres;
+mod reset;
+#[cfg(feature = "sqlite")]
 mod sqlite;
 mod turso;
 
+pub use reset::ResetMode;
+
+#[cfg(feature = "mssql")]
+use crate::mssql::config::MssqlManager;
 #[cfg(feature = "postgres")]
 use crate::postgres::typed::PgManager;
-#[cfg(any(feature = "postgres", feature = "mssql"))]
+#[cfg(any(feature = "postgres", feature = "mssql", feature = "turso"))]
 use bb8::PooledConnection;
-#[cfg(feature = "mssql")]
-use bb8_tiberius::ConnectionManager;
 
+use super::checkout::ConnectionContext;
+#[cfg(any_backend)]
+use super::checkout::PoolLink;
 use super::types::MiddlewarePool;
+use crate::clock::Clock;
 use crate::error::SqlMiddlewareDbError;
 #[cfg(feature = "sqlite")]
 use crate::sqlite::SqliteConnection;
+use crate::types::DatabaseType;
 
 #[cfg(feature = "turso")]
-use ::turso::Connection as TursoConnection;
+use crate::turso::TursoManager;
 
 pub enum MiddlewarePoolConnection {
     #[cfg(feature = "postgres")]
     Postgres {
         client: PooledConnection<'static, PgManager>,
         translate_placeholders: bool,
+        context: Option<ConnectionContext>,
     },
     #[cfg(feature = "sqlite")]
     Sqlite {
         conn: Option<SqliteConnection>,
         translate_placeholders: bool,
+        context: Option<ConnectionContext>,
     },
     #[cfg(feature = "mssql")]
     Mssql {
-        conn: PooledConnection<'static, ConnectionManager>,
+        conn: PooledConnection<'static, MssqlManager>,
         translate_placeholders: bool,
+        context: Option<ConnectionContext>,
     },
     #[cfg(feature = "turso")]
     Turso {
-        conn: TursoConnection,
+        conn: PooledConnection<'static, TursoManager>,
         translate_placeholders: bool,
+        context: Option<ConnectionContext>,
     },
 }
 
 // Manual Debug implementation because some pool variants do not expose `Debug`
+#[cfg(any_backend)]
 impl std::fmt::Debug for MiddlewarePoolConnection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -60,39 +74,139 @@ impl std::fmt::Debug for MiddlewarePoolConnection {
     }
 }
 
+// Without a backend the enum has no variants, so there is nothing to format.
+#[cfg(not(any_backend))]
+impl std::fmt::Debug for MiddlewarePoolConnection {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {}
+    }
+}
+
 impl MiddlewarePool {
     /// Get a connection from the pool
     ///
     /// # Errors
-    /// Returns `SqlMiddlewareDbError::PoolErrorPostgres` or `SqlMiddlewareDbError::PoolErrorSqlite` if the pool fails to provide a connection.
-    pub async fn get_connection(
-        pool: &MiddlewarePool,
+    /// Returns `SqlMiddlewareDbError::PoolErrorPostgres`, `PoolErrorSqlite`, or `PoolErrorMssql` (with the wait time and a
+    /// `PoolStats` snapshot) if the pool fails to provide a connection.
+    #[cfg(any_backend)]
+    pub(crate) async fn checkout(
+        &self,
         translate_placeholders: bool,
+        // Only the `SQLite` checkout waits on the clock, while probing for an idle worker.
+        #[cfg_attr(not(feature = "sqlite"), allow(unused_variables))] clock: &dyn Clock,
     ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
-        match pool {
+        match self {
             #[cfg(feature = "postgres")]
             MiddlewarePool::Postgres(pool) => {
                 postgres::get_connection(pool, translate_placeholders).await
             }
             #[cfg(feature = "sqlite")]
             MiddlewarePool::Sqlite(pool) => {
-                sqlite::get_connection(pool, translate_placeholders).await
+                sqlite::get_connection(pool, translate_placeholders, clock).await
             }
             #[cfg(feature = "mssql")]
             MiddlewarePool::Mssql(pool) => {
                 mssql::get_connection(pool, translate_placeholders).await
             }
             #[cfg(feature = "turso")]
-            MiddlewarePool::Turso(db) => turso::get_connection(db, translate_placeholders),
-            #[allow(unreachable_patterns)]
-            _ => Err(SqlMiddlewareDbError::Unimplemented(
-                "This database type is not enabled in the current build".to_string(),
-            )),
+            MiddlewarePool::Turso(pool) => {
+                turso::get_connection(pool, translate_placeholders).await
+            }
         }
     }
+
+    /// Without a backend the enum has no variants, so there is no pool to check out from.
+    #[cfg(not(any_backend))]
+    pub(crate) async fn checkout(
+        &self,
+        _translate_placeholders: bool,
+        _clock: &dyn Clock,
+    ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+        match *self {}
+    }
 }
 
 impl MiddlewarePoolConnection {
+    /// Attach the pool's context and this checkout's permits.
+    #[cfg(any_backend)]
+    pub(crate) fn set_context(&mut self, attached: ConnectionContext) {
+        let slot: &mut Option<ConnectionContext> = match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { context, .. } => context,
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { conn, context, .. } => {
+                // SQLite sleeps between busy retries on the pool's clock and jitter.
+                if let Some(conn) = conn {
+                    conn.set_clock(std::sync::Arc::clone(&attached.pool.clock));
+                    conn.set_jitter(std::sync::Arc::clone(&attached.pool.jitter));
+                }
+                context
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { context, .. } => context,
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { context, .. } => context,
+        };
+        *slot = Some(attached);
+    }
+
+    /// Without a backend the enum has no variants, so there is nothing to attach to.
+    #[cfg(not(any_backend))]
+    pub(crate) fn set_context(&mut self, _attached: ConnectionContext) {
+        match *self {}
+    }
+
+    /// The pool context attached at checkout, if this connection came from a `ConfigAndPool`.
+    pub(crate) fn context(&self) -> Option<&ConnectionContext> {
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { context, .. } => context.as_ref(),
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { context, .. } => context.as_ref(),
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { context, .. } => context.as_ref(),
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { context, .. } => context.as_ref(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// The pool's audited tables, if it audits any.
+    #[cfg(any_backend)]
+    pub(crate) fn audit_tables(&self) -> Option<&crate::audit::AuditTables> {
+        self.context()?.pool.audit.as_ref()
+    }
+
+    /// The pool's statement echo flag, if attached.
+    pub(crate) fn debug_echo_flag(&self) -> Option<&std::sync::atomic::AtomicBool> {
+        self.context().map(|context| &*context.pool.debug_echo)
+    }
+
+    /// Wait for the pool's rate limiter, if any, before sending a statement.
+    pub(crate) async fn throttle(&self) -> Result<(), SqlMiddlewareDbError> {
+        match self.context() {
+            Some(context) => context.pool.throttle().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Link to this connection's pool, for a transaction handle begun on it.
+    #[cfg(any_backend)]
+    pub(crate) fn pool_link(&self) -> PoolLink {
+        self.context()
+            .map(|context| PoolLink::of(&context.pool))
+            .unwrap_or_default()
+    }
+
+    /// Column marking soft-deleted rows on this connection's pool (default `deleted_at`).
+    #[must_use]
+    pub fn soft_delete_column(&self) -> &str {
+        self.context()
+            .and_then(|context| context.pool.soft_delete_column.as_deref())
+            .unwrap_or(crate::query_builder::DEFAULT_SOFT_DELETE_COLUMN)
+    }
+
     /// Pool-default translation toggle attached to this connection.
     #[must_use]
     pub fn translation_default(&self) -> bool {
@@ -117,6 +231,25 @@ impl MiddlewarePoolConnection {
                 translate_placeholders,
                 ..
             } => *translate_placeholders,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("no database backend is enabled"),
+        }
+    }
+
+    /// Backend this connection talks to.
+    #[must_use]
+    pub fn database_type(&self) -> DatabaseType {
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { .. } => DatabaseType::Postgres,
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => DatabaseType::Sqlite,
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => DatabaseType::Mssql,
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { .. } => DatabaseType::Turso,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("no database backend is enabled"),
         }
     }
 }
@@ -1,7 +1,9 @@
 #[cfg(feature = "mssql")]
-use bb8::Pool;
+use crate::mssql::config::MssqlManager;
+#[cfg(feature = "mssql")]
+use crate::mssql::{Tx, begin_transaction};
 #[cfg(feature = "mssql")]
-use bb8_tiberius::ConnectionManager;
+use bb8::Pool;
 
 #[cfg(feature = "mssql")]
 use crate::error::SqlMiddlewareDbError;
@@ -11,15 +13,52 @@ use super::MiddlewarePoolConnection;
 
 #[cfg(feature = "mssql")]
 pub(super) async fn get_connection(
-    pool: &Pool<ConnectionManager>,
+    pool: &Pool<MssqlManager>,
     translate_placeholders: bool,
 ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
-    let conn = pool
-        .get_owned()
-        .await
-        .map_err(SqlMiddlewareDbError::PoolErrorMssql)?;
+    let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+        SqlMiddlewareDbError::PoolErrorMssql {
+            source: Box::new(source),
+            waited,
+            state,
+        }
+    })
+    .await?;
     Ok(MiddlewarePoolConnection::Mssql {
         conn,
         translate_placeholders,
+        context: None,
     })
 }
+
+#[cfg(feature = "mssql")]
+impl MiddlewarePoolConnection {
+    /// Begin a [`Tx`] on this connection's SQL Server client, rate-limited like the connection.
+    ///
+    /// `BEGIN` and every statement on the handle take a token from the pool's
+    /// [rate limit](crate::pool::rate_limit); committing and rolling back do not. Statements run
+    /// through its query builder are [echoed](crate::pool::echo) like the connection's. Otherwise
+    /// this is [`begin_transaction`] on the client.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` if the connection is not SQL Server-backed,
+    /// `RateLimited` if the pool rejects the `BEGIN`, or the error from starting the transaction.
+    #[track_caller]
+    pub fn begin_mssql_transaction(
+        &mut self,
+    ) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+        let pool = self.pool_link();
+        let begin = match self {
+            MiddlewarePoolConnection::Mssql { conn, .. } => Ok(begin_transaction(conn)),
+            #[allow(unreachable_patterns)]
+            _ => Err(SqlMiddlewareDbError::Unimplemented(
+                "begin_mssql_transaction is only available for SQL Server connections".to_string(),
+            )),
+        };
+        async move {
+            let begin = begin?;
+            pool.throttle().await?;
+            Ok(begin.await?.linked(pool))
+        }
+    }
+}
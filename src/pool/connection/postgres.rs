@@ -5,6 +5,8 @@ use bb8::Pool;
 use crate::error::SqlMiddlewareDbError;
 #[cfg(feature = "postgres")]
 use crate::postgres::typed::PgManager;
+#[cfg(feature = "postgres")]
+use crate::postgres::{Tx, begin_transaction};
 
 #[cfg(feature = "postgres")]
 use super::MiddlewarePoolConnection;
@@ -14,12 +16,49 @@ pub(super) async fn get_connection(
     pool: &Pool<PgManager>,
     translate_placeholders: bool,
 ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
-    let conn = pool
-        .get_owned()
-        .await
-        .map_err(SqlMiddlewareDbError::PoolErrorPostgres)?;
+    let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+        SqlMiddlewareDbError::PoolErrorPostgres {
+            source: Box::new(source),
+            waited,
+            state,
+        }
+    })
+    .await?;
     Ok(MiddlewarePoolConnection::Postgres {
         client: conn,
         translate_placeholders,
+        context: None,
     })
 }
+
+#[cfg(feature = "postgres")]
+impl MiddlewarePoolConnection {
+    /// Begin a [`Tx`] on this connection's Postgres client, rate-limited like the connection.
+    ///
+    /// `BEGIN` and every statement on the handle take a token from the pool's
+    /// [rate limit](crate::pool::rate_limit); committing and rolling back do not. Statements run
+    /// through its query builder are [echoed](crate::pool::echo) like the connection's. Otherwise
+    /// this is [`begin_transaction`] on the client.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` if the connection is not Postgres-backed,
+    /// `RateLimited` if the pool rejects the `BEGIN`, or the error from starting the transaction.
+    #[track_caller]
+    pub fn begin_postgres_transaction(
+        &mut self,
+    ) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+        let pool = self.pool_link();
+        let begin = match self {
+            MiddlewarePoolConnection::Postgres { client, .. } => Ok(begin_transaction(client)),
+            #[allow(unreachable_patterns)]
+            _ => Err(SqlMiddlewareDbError::Unimplemented(
+                "begin_postgres_transaction is only available for Postgres connections".to_string(),
+            )),
+        };
+        async move {
+            let begin = begin?;
+            pool.throttle().await?;
+            Ok(begin.await?.linked(pool))
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::error::SqlMiddlewareDbError;
+
+use super::MiddlewarePoolConnection;
+
+/// Everything `DISCARD ALL` does except deallocating prepared statements, which would break
+/// statements tokio-postgres still holds for this connection. Runs after the `ROLLBACK`.
+#[cfg(feature = "postgres")]
+const POSTGRES_RESET: &str = "CLOSE ALL; SET SESSION AUTHORIZATION DEFAULT; RESET ALL; \
+     UNLISTEN *; SELECT pg_advisory_unlock_all(); DISCARD TEMP; DISCARD SEQUENCES;";
+
+/// Ends the session, so the client reports itself closed and the pool drops it.
+#[cfg(feature = "postgres")]
+const POSTGRES_RETIRE: &str = "SELECT pg_terminate_backend(pg_backend_pid())";
+
+#[cfg(feature = "mssql")]
+const MSSQL_RESET: &str = "IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION;";
+
+/// How thoroughly a connection's session state is reset; see
+/// [`PoolOptions::reset_on_return`](crate::pool::PoolOptions::reset_on_return).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Leave the session as the last caller left it.
+    #[default]
+    None,
+    /// Roll back a transaction left open by raw SQL and clear state that leaks between callers:
+    /// - Postgres: cursors, session settings (`SET`), `LISTEN` registrations, advisory locks,
+    ///   temporary tables and sequence caches. Prepared statements are kept.
+    /// - `SQLite`: temporary tables, views and triggers.
+    /// - SQL Server: session context keys set through
+    ///   [`with_session_context`](MiddlewarePoolConnection::with_session_context) in this
+    ///   process (SQL Server cannot list the keys a session holds).
+    /// - Turso: only the open transaction.
+    Fast,
+    /// Everything [`Fast`](Self::Fast) does, plus:
+    /// - Postgres: `DISCARD ALL`, which also deallocates prepared statements. Statements prepared
+    ///   before the reset can no longer be executed.
+    /// - `SQLite`: per-connection pragmas such as `foreign_keys`, `query_only` and
+    ///   `busy_timeout` go back to the values the connection had once it was opened and set up.
+    Full,
+}
+
+impl MiddlewarePoolConnection {
+    /// Put this connection back in the state a fresh checkout would find it in, as
+    /// [`ResetMode::Fast`] describes.
+    ///
+    /// # Errors
+    /// Returns the backend error if a reset statement fails, or
+    /// `SqlMiddlewareDbError::ExecutionError` if a `SQLite` transaction still holds the
+    /// connection.
+    pub async fn reset_session(&mut self) -> Result<(), SqlMiddlewareDbError> {
+        self.reset_session_with(ResetMode::Fast).await
+    }
+
+    /// Reset this connection's session state as `mode` describes.
+    ///
+    /// # Errors
+    /// As [`reset_session`](Self::reset_session).
+    pub async fn reset_session_with(
+        &mut self,
+        mode: ResetMode,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        if mode == ResetMode::None {
+            return Ok(());
+        }
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                // Run outside the transaction `execute_batch` would open: the reset statements
+                // act on the session, and DISCARD ALL refuses to run in a transaction. ROLLBACK
+                // ends one left open (or aborted) by raw SQL; without one it only warns.
+                client.batch_execute("ROLLBACK").await?;
+                if mode == ResetMode::Full {
+                    client.batch_execute("DISCARD ALL").await?;
+                    // Type lookups are cached as prepared statements, which DISCARD ALL removed.
+                    client.clear_type_cache();
+                } else {
+                    client.batch_execute(POSTGRES_RESET).await?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                let pragmas = if mode == ResetMode::Full {
+                    self.sqlite_conn_mut()?
+                        .conn_handle()
+                        .session_pragmas()
+                        .to_vec()
+                } else {
+                    Vec::new()
+                };
+                self.with_blocking_sqlite(move |raw| reset_sqlite(raw, &pragmas))
+                    .await
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => {
+                self.run_control(MSSQL_RESET).await?;
+                crate::session::clear_all_session_context(self).await
+            }
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { conn, .. } => {
+                // Fails with "no transaction is active" on a clean connection.
+                let _ = conn.execute_batch("ROLLBACK").await;
+                Ok(())
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(SqlMiddlewareDbError::Unimplemented(
+                "This database type is not enabled in the current build".to_string(),
+            )),
+        }
+    }
+
+    /// Reset this connection and return it to the pool, reporting whether that worked.
+    ///
+    /// Dropping a connection returns it as-is, and Drop cannot await a rollback or a reset.
+    /// Call `close` at the end of async work to clean up deterministically and see any error.
+    /// It runs a [`ResetMode::Fast`] reset; call
+    /// [`reset_session_with`](Self::reset_session_with) first for a full one. A connection that
+    /// fails to reset is retired: the pool closes it instead of handing it out again.
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+    /// let mut conn = cap.get_connection().await?;
+    /// conn.execute_batch("CREATE TEMP TABLE scratch (id INTEGER)").await?;
+    /// conn.close().await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the error from [`reset_session`](Self::reset_session).
+    pub async fn close(mut self) -> Result<(), SqlMiddlewareDbError> {
+        let result = self.reset_session().await;
+        if result.is_err() {
+            self.retire().await;
+        }
+        result
+    }
+
+    /// Keep the pool from handing this connection out again.
+    ///
+    /// `SQLite` connections are flagged broken. A Postgres session is terminated, so its client
+    /// reports itself closed. A SQL Server session gets a read-only session-context key that
+    /// makes the pool's checkout probe fail. Turso resets cannot fail, so there is nothing to
+    /// retire.
+    pub(crate) async fn retire(&mut self) {
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                // The session ends mid-statement, so this always reports an error.
+                let _ = client.batch_execute(POSTGRES_RETIRE).await;
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite {
+                conn: Some(conn), ..
+            } => conn.conn_handle().mark_broken(),
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { conn, .. } => {
+                if let Ok(stream) = conn.simple_query(crate::mssql::config::MSSQL_RETIRE).await {
+                    let _ = stream.into_results().await;
+                }
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn reset_sqlite(
+    raw: &mut rusqlite::Connection,
+    pragmas: &[(&'static str, i64)],
+) -> Result<(), SqlMiddlewareDbError> {
+    if !raw.is_autocommit() {
+        raw.execute_batch("ROLLBACK")?;
+    }
+    let objects = {
+        let mut stmt = raw.prepare(
+            "SELECT type, name FROM temp.sqlite_master \
+             WHERE type IN ('trigger', 'view', 'table') \
+             ORDER BY CASE type WHEN 'trigger' THEN 0 WHEN 'view' THEN 1 ELSE 2 END",
+        )?;
+        stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?
+    };
+    for (kind, name) in objects {
+        let name = name.replace('"', "\"\"");
+        raw.execute_batch(&format!("DROP {kind} IF EXISTS temp.\"{name}\""))?;
+    }
+    for (name, value) in pragmas {
+        // Some pragmas (busy_timeout) echo the new value back as a row.
+        raw.query_row(&format!("PRAGMA {name} = {value}"), [], |_| Ok(()))
+            .or_else(|err| match err {
+                rusqlite::Error::QueryReturnedNoRows => Ok(()),
+                err => Err(err),
+            })?;
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
+use std::time::Duration;
+
+use crate::clock::Clock;
 use crate::error::SqlMiddlewareDbError;
-use crate::sqlite::config::SqliteManager;
+use crate::sqlite::config::{SqliteManager, SqlitePooledConnection};
 use crate::sqlite::{SqliteConnection, SqlitePreparedStatement};
 
 use super::MiddlewarePoolConnection;
@@ -8,17 +11,68 @@ use super::MiddlewarePoolConnection;
 pub(super) async fn get_connection(
     pool: &bb8::Pool<SqliteManager>,
     translate_placeholders: bool,
+    clock: &dyn Clock,
 ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
-    let conn = pool.get_owned().await.map_err(|e| {
-        SqlMiddlewareDbError::ConnectionError(format!("sqlite checkout error: {e}"))
-    })?;
+    let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+        SqlMiddlewareDbError::PoolErrorSqlite {
+            source: Box::new(source),
+            waited,
+            state,
+        }
+    })
+    .await?;
+    let conn = least_busy(pool, conn, clock).await;
     let worker_conn = SqliteConnection::new(conn);
     Ok(MiddlewarePoolConnection::Sqlite {
         conn: Some(worker_conn),
         translate_placeholders,
+        context: None,
     })
 }
 
+/// Idle connections to try when the first checkout's worker is still busy.
+#[cfg(feature = "sqlite")]
+const MAX_BUSY_PROBES: usize = 4;
+
+/// How long one probe may wait for an idle connection before settling for what it has, on the
+/// pool's clock.
+#[cfg(feature = "sqlite")]
+const PROBE_WAIT: Duration = Duration::from_millis(5);
+
+/// Swap `conn` for the idle connection with the fewest pending worker jobs.
+///
+/// bb8 hands out idle connections in queue order, so a connection whose worker is still running
+/// an abandoned statement (its caller timed out) can be returned next and make the new caller
+/// wait behind it. When that happens, check out other idle connections and keep the least busy;
+/// the rest go straight back to the pool.
+#[cfg(feature = "sqlite")]
+async fn least_busy(
+    pool: &bb8::Pool<SqliteManager>,
+    mut conn: SqlitePooledConnection,
+    clock: &dyn Clock,
+) -> SqlitePooledConnection {
+    let mut skipped = Vec::new();
+    while conn.pending_jobs() > 0
+        && skipped.len() < MAX_BUSY_PROBES
+        && pool.state().idle_connections > 0
+    {
+        let candidate = tokio::select! {
+            biased;
+            candidate = pool.get_owned() => candidate,
+            () = clock.sleep(PROBE_WAIT) => break,
+        };
+        let Ok(candidate) = candidate else {
+            break;
+        };
+        if candidate.pending_jobs() < conn.pending_jobs() {
+            skipped.push(std::mem::replace(&mut conn, candidate));
+        } else {
+            skipped.push(candidate);
+        }
+    }
+    conn
+}
+
 #[cfg(feature = "sqlite")]
 impl MiddlewarePoolConnection {
     /// Run synchronous `SQLite` work on the underlying worker-owned connection.
@@ -33,7 +87,9 @@ impl MiddlewarePoolConnection {
     /// connection handle.
     ///
     /// # Errors
-    /// Returns [`SqlMiddlewareDbError::Unimplemented`] when the connection is not `SQLite`.
+    /// Returns [`SqlMiddlewareDbError::Unimplemented`] when the connection is not `SQLite`, and
+    /// [`SqlMiddlewareDbError::Other`] with the panic message when `func` panics. After a panic
+    /// the connection refuses further work and the pool discards it when it is returned.
     ///
     /// # Examples
     /// ```rust,no_run
@@ -58,6 +114,23 @@ impl MiddlewarePoolConnection {
         conn.with_connection(func).await
     }
 
+    /// Attach another database file to this pooled connection as schema `alias`.
+    ///
+    /// Only this connection sees the attachment, and it stays attached after the connection goes
+    /// back to the pool. Prefer `SqliteOptionsBuilder::attach` when every checkout needs it.
+    ///
+    /// # Errors
+    /// Returns [`SqlMiddlewareDbError::Unimplemented`] when the connection is not `SQLite`, or the
+    /// `SQLite` error if the ATTACH fails (e.g., the alias is already in use).
+    pub async fn attach(&mut self, alias: &str, path: &str) -> Result<(), SqlMiddlewareDbError> {
+        let alias = alias.to_string();
+        let path = path.to_string();
+        self.with_blocking_sqlite(move |raw| {
+            crate::sqlite::attach::attach_database(raw, &alias, &path)
+        })
+        .await
+    }
+
     /// Prepare a `SQLite` statement and obtain a reusable handle backed by the worker thread.
     ///
     /// # Errors
@@ -117,6 +190,7 @@ impl MiddlewarePoolConnection {
             MiddlewarePoolConnection::Sqlite {
                 mut conn,
                 translate_placeholders,
+                ..
             } => conn
                 .take()
                 .map(|conn| (conn, translate_placeholders))
@@ -141,6 +215,7 @@ impl MiddlewarePoolConnection {
         MiddlewarePoolConnection::Sqlite {
             conn: Some(conn),
             translate_placeholders,
+            context: None,
         }
     }
 }
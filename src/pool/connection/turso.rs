@@ -1,24 +1,32 @@
 #[cfg(feature = "turso")]
 use crate::error::SqlMiddlewareDbError;
 #[cfg(feature = "turso")]
-use crate::turso::TursoNonTxPreparedStatement;
+use crate::turso::TursoManager;
 #[cfg(feature = "turso")]
-use turso::Connection as TursoConnection;
+use crate::turso::TursoNonTxPreparedStatement;
 #[cfg(feature = "turso")]
-use turso::Database;
+use crate::turso::{Tx, TxMode, begin_transaction_with};
 
 #[cfg(feature = "turso")]
 use super::MiddlewarePoolConnection;
 
 #[cfg(feature = "turso")]
-pub(super) fn get_connection(
-    db: &Database,
+pub(super) async fn get_connection(
+    pool: &bb8::Pool<TursoManager>,
     translate_placeholders: bool,
 ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
-    let conn: TursoConnection = db.connect().map_err(SqlMiddlewareDbError::from)?;
+    let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+        SqlMiddlewareDbError::PoolErrorTurso {
+            source: Box::new(source),
+            waited,
+            state,
+        }
+    })
+    .await?;
     Ok(MiddlewarePoolConnection::Turso {
         conn,
         translate_placeholders,
+        context: None,
     })
 }
 
@@ -35,10 +43,48 @@ impl MiddlewarePoolConnection {
         match self {
             MiddlewarePoolConnection::Turso {
                 conn: turso_conn, ..
-            } => TursoNonTxPreparedStatement::prepare(turso_conn.clone(), query).await,
+            } => TursoNonTxPreparedStatement::prepare((**turso_conn).clone(), query).await,
+            #[allow(unreachable_patterns)]
             _ => Err(SqlMiddlewareDbError::Unimplemented(
                 "prepare_turso_statement is only available for Turso connections".to_string(),
             )),
         }
     }
 }
+
+#[cfg(feature = "turso")]
+impl MiddlewarePoolConnection {
+    /// Begin a [`Tx`] in `mode` on this connection's Turso connection, rate-limited like the
+    /// connection.
+    ///
+    /// `BEGIN` and every statement sent on the handle take a token from the pool's
+    /// [rate limit](crate::pool::rate_limit); committing and rolling back do not, and a
+    /// [`TxMode::Deferred`] transaction sends nothing before it commits. Statements run through
+    /// its query builder are [echoed](crate::pool::echo) like the connection's. Otherwise this is
+    /// [`begin_transaction_with`] on the connection.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` if the connection is not Turso-backed,
+    /// `RateLimited` if the pool rejects the `BEGIN`, or the error from starting the transaction.
+    #[track_caller]
+    pub fn begin_turso_transaction(
+        &mut self,
+        mode: TxMode,
+    ) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+        let pool = self.pool_link();
+        let begin = match self {
+            MiddlewarePoolConnection::Turso { conn, .. } => Ok(begin_transaction_with(conn, mode)),
+            #[allow(unreachable_patterns)]
+            _ => Err(SqlMiddlewareDbError::Unimplemented(
+                "begin_turso_transaction is only available for Turso connections".to_string(),
+            )),
+        };
+        async move {
+            let begin = begin?;
+            if mode == TxMode::Interactive {
+                pool.throttle().await?;
+            }
+            Ok(begin.await?.linked(pool))
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Per-pool echo of every statement, for debugging.
+//!
+//! With echo on, each statement a pooled connection runs through the query builder or
+//! `execute_batch` is logged to `tracing` at DEBUG level (target `sql_middleware::echo`) with the
+//! SQL as written, the SQL after placeholder translation when that changed it, and a summary of
+//! the parameters. Turn it on with [`ConfigAndPool::set_debug_echo`] at any time, including for
+//! connections already checked out, or start pools with it on by setting
+//! [`DEBUG_ECHO_ENV`]. A backend transaction handle echoes what its query builder runs when it was
+//! begun from a pooled connection (e.g. with
+//! [`MiddlewarePoolConnection::begin_postgres_transaction`](super::MiddlewarePoolConnection));
+//! handles begun on a raw driver client are not echoed.
+//!
+//! Parameter summaries show numbers, booleans and timestamps in full, at most
+//! [`TEXT_PREVIEW`] characters of text, and only the length of blobs.
+
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::ConfigAndPool;
+use crate::types::RowValues;
+
+/// Environment variable that turns echo on for pools created while it is set to anything but
+/// `0` or `false`.
+pub const DEBUG_ECHO_ENV: &str = "SQL_MIDDLEWARE_DEBUG_ECHO";
+
+/// Characters of a text parameter shown in the summary.
+pub const TEXT_PREVIEW: usize = 32;
+
+/// Initial echo flag for a new pool, from [`DEBUG_ECHO_ENV`].
+pub(crate) fn from_env() -> Arc<AtomicBool> {
+    let on = std::env::var(DEBUG_ECHO_ENV)
+        .is_ok_and(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "0" | "false"));
+    Arc::new(AtomicBool::new(on))
+}
+
+impl ConfigAndPool {
+    /// Log every statement this pool's connections run; see the [module docs](crate::pool::echo).
+    ///
+    /// Takes effect at once for this pool, its clones and connections already checked out.
+    pub fn set_debug_echo(&self, on: bool) {
+        self.context.debug_echo.store(on, Ordering::Relaxed);
+    }
+
+    /// Whether statement echo is on for this pool.
+    #[must_use]
+    pub fn debug_echo(&self) -> bool {
+        self.context.debug_echo.load(Ordering::Relaxed)
+    }
+}
+
+/// Log one statement if `flag` is set.
+pub(crate) fn statement(
+    flag: Option<&AtomicBool>,
+    sql: &str,
+    translated: &str,
+    params: &[RowValues],
+) {
+    if !flag.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+        return;
+    }
+    let params = summarize(params);
+    if translated == sql {
+        tracing::debug!(target: "sql_middleware::echo", %sql, %params, "statement");
+    } else {
+        tracing::debug!(target: "sql_middleware::echo", %sql, %translated, %params, "statement");
+    }
+}
+
+/// `[1] 7, [2] 'ann', [3] NULL`, shortening text and hiding blob contents.
+pub(crate) fn summarize(params: &[RowValues]) -> String {
+    let mut out = String::new();
+    for (idx, value) in params.iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+        let _ = write!(out, "[{}] ", idx + 1);
+        let _ = match value {
+            RowValues::Int(int) => write!(out, "{int}"),
+            RowValues::Float(float) => write!(out, "{float}"),
+            RowValues::Bool(flag) => write!(out, "{flag}"),
+            RowValues::Timestamp(at) => write!(out, "{at}"),
+            RowValues::Null => write!(out, "NULL"),
+            RowValues::Text(text) => {
+                let chars = text.chars().count();
+                if chars > TEXT_PREVIEW {
+                    let preview: String = text.chars().take(TEXT_PREVIEW).collect();
+                    write!(out, "'{preview}...' ({chars} chars)")
+                } else {
+                    write!(out, "'{text}'")
+                }
+            }
+            RowValues::Blob(bytes) => write!(out, "<blob {} bytes>", bytes.len()),
+            #[cfg(feature = "json")]
+            RowValues::JSON(_) => write!(out, "<json>"),
+            RowValues::Custom(custom) => write!(out, "<{}>", custom.type_name()),
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(bytes) => write!(out, "<geometry {} bytes>", bytes.len()),
+        };
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_shortens_text_and_hides_blobs() {
+        let long = "x".repeat(40);
+        let summary = summarize(&[
+            RowValues::Int(7),
+            RowValues::Text("ann".into()),
+            RowValues::Text(long.as_str().into()),
+            RowValues::Blob(vec![0; 3].into()),
+            RowValues::Null,
+        ]);
+        assert_eq!(
+            summary,
+            format!(
+                "[1] 7, [2] 'ann', [3] '{}...' (40 chars), [4] <blob 3 bytes>, [5] NULL",
+                "x".repeat(32)
+            )
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Group commit: coalesce small independent writes into shared transactions.
+//!
+//! Committing is the expensive part of a tiny write: an fsync on `SQLite`, a WAL flush and a
+//! round trip on Postgres. A [`GroupCommit`] handle queues writes that arrive within a short
+//! window and runs them together in one transaction on one connection, so a burst of counter
+//! bumps pays for one commit instead of hundreds. Each caller still awaits its own row count.
+//!
+//! There is no background task. The first write into an empty queue becomes the leader: it
+//! waits out the window on the pool's [`Clock`](crate::clock::Clock) and runs one batch of what
+//! has queued. If more is waiting, it hands leadership to a caller still waiting for its own
+//! write, which flushes the next batch right away, and so on until the queue is empty. A caller
+//! leads for one batch unless nobody else is left to take over, so steady traffic does not keep
+//! one caller busy flushing others' writes. A leader that is cancelled hands over the same way;
+//! queued writes fail only when every caller that could flush them is gone. A write whose caller
+//! is cancelled before its batch starts is dropped, not run.
+//!
+//! Writes in a group succeed or fail independently. If the shared transaction fails, it is
+//! rolled back and each write in it is run again in its own transaction, so only the write that
+//! caused the failure reports an error. Only send statements that may be retried that way, and
+//! that do not depend on each other's effects within the window.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::sync::{Notify, oneshot};
+
+use super::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::error::SqlMiddlewareDbError;
+use crate::params::IntoParams;
+use crate::translation::TranslationMode;
+use crate::types::RowValues;
+
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+#[cfg(feature = "turso")]
+use crate::turso::TxMode;
+
+/// Largest number of writes run in one transaction unless [`GroupCommit::max_batch`] says
+/// otherwise.
+pub const DEFAULT_MAX_BATCH: usize = 256;
+
+/// Handle that batches DML from many callers into shared transactions; see the
+/// [module docs](crate::pool::group_commit).
+///
+/// Clones share one queue. Create it once per pool with [`ConfigAndPool::group_commit`] and hand
+/// clones to the tasks that write.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use sql_middleware::prelude::*;
+///
+/// # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+/// let writes = cap.group_commit(Duration::from_millis(2));
+/// let mut tasks = Vec::new();
+/// for _ in 0..100 {
+///     let writes = writes.clone();
+///     tasks.push(tokio::spawn(async move {
+///         writes
+///             .dml("UPDATE counters SET hits = hits + 1 WHERE name = $1", ("home",))
+///             .await
+///     }));
+/// }
+/// for task in tasks {
+///     assert_eq!(task.await.expect("write task panicked")?, 1);
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct GroupCommit {
+    cap: ConfigAndPool,
+    window: Duration,
+    max_batch: usize,
+    queue: Arc<Mutex<Queue>>,
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: Vec<Write>,
+    /// Someone leads: a caller flushing, or one handed leadership that has yet to start.
+    leading: bool,
+    /// The waiting caller leadership was handed to, until it starts leading.
+    designated: Option<u64>,
+    next_id: u64,
+}
+
+struct Write {
+    id: u64,
+    sql: String,
+    params: Vec<RowValues>,
+    done: oneshot::Sender<Result<usize, SqlMiddlewareDbError>>,
+    /// Wakes the caller waiting for this write when leadership is handed to it.
+    wake: Arc<Notify>,
+}
+
+impl Queue {
+    /// Pass leadership to a caller other than `except` still waiting for a queued write, or give
+    /// it up if there is none.
+    ///
+    /// Returns whether someone took over.
+    fn hand_off(&mut self, except: u64) -> bool {
+        let next = self
+            .pending
+            .iter()
+            .find(|write| write.id != except && !write.done.is_closed());
+        let Some(next) = next else {
+            self.designated = None;
+            return false;
+        };
+        self.designated = Some(next.id);
+        next.wake.notify_one();
+        true
+    }
+
+    /// Hand leadership on for a caller that stops leading early; with no one to take over, fail
+    /// what is left, since nobody can flush it.
+    fn abandon(&mut self, id: u64) {
+        if self.hand_off(id) {
+            return;
+        }
+        self.leading = false;
+        for write in self.pending.drain(..) {
+            let _ = write.done.send(Err(SqlMiddlewareDbError::ExecutionError(
+                "group commit was cancelled before running this write".into(),
+            )));
+        }
+    }
+}
+
+impl ConfigAndPool {
+    /// Start a [`GroupCommit`] coalescer that gathers writes for `window` before running them.
+    ///
+    /// A window of a millisecond or two is usually enough to collect a burst; every write waits
+    /// up to that long before it runs.
+    #[must_use]
+    pub fn group_commit(&self, window: Duration) -> GroupCommit {
+        GroupCommit {
+            cap: self.clone(),
+            window,
+            max_batch: DEFAULT_MAX_BATCH,
+            queue: Arc::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for GroupCommit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GroupCommit")
+            .field("db_type", &self.cap.db_type)
+            .field("window", &self.window)
+            .field("max_batch", &self.max_batch)
+            .field("pending", &lock(&self.queue).pending.len())
+            .finish()
+    }
+}
+
+impl GroupCommit {
+    /// Cap how many writes share one transaction (default [`DEFAULT_MAX_BATCH`]).
+    ///
+    /// A larger backlog is flushed in several transactions, one after another.
+    #[must_use]
+    pub fn max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch.max(1);
+        self
+    }
+
+    /// Queue one DML statement and return its affected-row count once its group has committed.
+    ///
+    /// Placeholders are translated according to the pool's default, as with
+    /// [`MiddlewarePoolConnection::query`].
+    ///
+    /// # Errors
+    /// Returns the statement's own error, or the checkout error if no connection could be had.
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if the caller leading the group was
+    /// cancelled while the write was running; it may or may not have committed.
+    pub async fn dml<'q>(
+        &self,
+        sql: impl Into<String>,
+        params: impl IntoParams<'q>,
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        let (done, mut result) = oneshot::channel();
+        let wake = Arc::new(Notify::new());
+        let (id, mut lead) = {
+            let mut queue = lock(&self.queue);
+            let id = queue.next_id;
+            queue.next_id += 1;
+            queue.pending.push(Write {
+                id,
+                sql: sql.into(),
+                params: params.into_params().into_owned(),
+                done,
+                wake: Arc::clone(&wake),
+            });
+            (id, !std::mem::replace(&mut queue.leading, true))
+        };
+        let _waiter = Waiter {
+            queue: &self.queue,
+            id,
+        };
+        // The first leader gathers a window; later ones flush what queued meanwhile.
+        let mut wait = true;
+        loop {
+            if lead {
+                self.lead(id, std::mem::take(&mut wait)).await;
+            }
+            tokio::select! {
+                biased;
+                outcome = &mut result => {
+                    return outcome.unwrap_or_else(|_| {
+                        Err(SqlMiddlewareDbError::ExecutionError(
+                            "group commit was cancelled while running this write; it may have \
+                             committed"
+                                .into(),
+                        ))
+                    });
+                }
+                () = wake.notified() => lead = true,
+            }
+        }
+    }
+
+    /// Flush one batch, waiting out the window first if `wait`, then hand leadership on. Keep
+    /// flushing only while no other caller can take over.
+    async fn lead(&self, id: u64, wait: bool) {
+        let mut leader = Leader {
+            queue: &self.queue,
+            id,
+            active: true,
+        };
+        lock(&self.queue).designated = None;
+        if wait {
+            self.cap.context.clock.sleep(self.window).await;
+        }
+        loop {
+            let batch = {
+                let mut queue = lock(&self.queue);
+                // Writes whose callers gave up are dropped rather than run.
+                queue.pending.retain(|write| !write.done.is_closed());
+                if queue.pending.is_empty() {
+                    queue.leading = false;
+                    leader.active = false;
+                    return;
+                }
+                let take = queue.pending.len().min(self.max_batch);
+                queue.pending.drain(..take).collect::<Vec<_>>()
+            };
+            self.flush(batch).await;
+            let mut queue = lock(&self.queue);
+            if queue.pending.is_empty() {
+                queue.leading = false;
+                leader.active = false;
+                return;
+            }
+            if queue.hand_off(id) {
+                leader.active = false;
+                return;
+            }
+        }
+    }
+
+    async fn flush(&self, batch: Vec<Write>) {
+        let translation = if self.cap.translate_placeholders {
+            TranslationMode::ForceOn
+        } else {
+            TranslationMode::ForceOff
+        };
+        let mut conn = self.cap.get_connection().await;
+        if let Ok(conn) = conn.as_mut()
+            && batch.len() > 1
+        {
+            let together = run_together(conn, &batch, translation).await;
+            conn.record_result(&together);
+            if let Ok(counts) = together {
+                for (write, count) in batch.into_iter().zip(counts) {
+                    let _ = write.done.send(Ok(count));
+                }
+                return;
+            }
+        }
+        for write in batch {
+            let result = match conn.as_mut() {
+                Ok(conn) => run_alone(conn, &write).await,
+                // Each caller gets its own checkout error.
+                Err(_) => match self.cap.get_connection().await {
+                    Ok(mut conn) => run_alone(&mut conn, &write).await,
+                    Err(err) => Err(err),
+                },
+            };
+            let _ = write.done.send(result);
+        }
+    }
+}
+
+/// Hands leadership on if the leading caller is cancelled mid-flush.
+struct Leader<'a> {
+    queue: &'a Mutex<Queue>,
+    id: u64,
+    active: bool,
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        if self.active {
+            lock(self.queue).abandon(self.id);
+        }
+    }
+}
+
+/// Hands leadership on if a caller is cancelled after it was handed leadership but before it
+/// started leading.
+struct Waiter<'a> {
+    queue: &'a Mutex<Queue>,
+    id: u64,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        let mut queue = lock(self.queue);
+        if queue.designated == Some(self.id) {
+            queue.abandon(self.id);
+        }
+    }
+}
+
+async fn run_alone(
+    conn: &mut MiddlewarePoolConnection,
+    write: &Write,
+) -> Result<usize, SqlMiddlewareDbError> {
+    conn.query(&write.sql).params(&write.params).dml().await
+}
+
+/// Run every write in one transaction and return their row counts, or roll back on the first
+/// error.
+async fn run_together(
+    conn: &mut MiddlewarePoolConnection,
+    batch: &[Write],
+    translation: TranslationMode,
+) -> Result<Vec<usize>, SqlMiddlewareDbError> {
+    let mut counts = Vec::with_capacity(batch.len());
+    match conn {
+        #[cfg(feature = "postgres")]
+        MiddlewarePoolConnection::Postgres { .. } => {
+            // Dropping the tokio-postgres transaction on error rolls it back.
+            let tx = conn.begin_postgres_transaction().await?.untracked();
+            for write in batch {
+                let query = tx.query_builder(&write.sql).params(&write.params);
+                counts.push(query.translation(translation).dml().await?);
+            }
+            tx.commit().await?;
+        }
+        #[cfg(feature = "sqlite")]
+        MiddlewarePoolConnection::Sqlite { .. } => {
+            let mut tx = sqlite::begin_transaction(conn).await?;
+            for write in batch {
+                let query = tx.query_builder(&write.sql).params(&write.params);
+                match query.translation(translation).dml().await {
+                    Ok(count) => counts.push(count),
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(err);
+                    }
+                }
+            }
+            tx.commit().await?;
+        }
+        #[cfg(feature = "mssql")]
+        MiddlewarePoolConnection::Mssql { .. } => {
+            let mut tx = conn.begin_mssql_transaction().await?;
+            for write in batch {
+                let query = tx.query_builder(&write.sql).params(&write.params);
+                match query.translation(translation).dml().await {
+                    Ok(count) => counts.push(count),
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(err);
+                    }
+                }
+            }
+            tx.commit().await?;
+        }
+        #[cfg(feature = "turso")]
+        MiddlewarePoolConnection::Turso { .. } => {
+            let tx = conn.begin_turso_transaction(TxMode::Interactive).await?;
+            for write in batch {
+                let query = tx.query_builder(&write.sql).params(&write.params);
+                match query.translation(translation).dml().await {
+                    Ok(count) => counts.push(count),
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(err);
+                    }
+                }
+            }
+            tx.commit().await?;
+        }
+    }
+    Ok(counts)
+}
+
+fn lock(queue: &Mutex<Queue>) -> MutexGuard<'_, Queue> {
+    match queue.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
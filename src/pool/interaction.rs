@@ -49,7 +49,8 @@ impl MiddlewarePoolConnection {
     /// Interact with the connection synchronously
     ///
     /// # Errors
-    /// Returns `SqlMiddlewareDbError::Unimplemented` for unsupported database types.
+    /// Returns `SqlMiddlewareDbError::Unimplemented` for unsupported database types, and
+    /// `SqlMiddlewareDbError::Other` when `f` panics on the `SQLite` worker.
     #[allow(unused_variables)]
     pub async fn interact_sync<F, R>(&self, f: F) -> Result<R, SqlMiddlewareDbError>
     where
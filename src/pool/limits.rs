@@ -0,0 +1,305 @@
+//! Concurrency limits (bulkheads) for pooled connections.
+//!
+//! A bulkhead caps how many connections one class of work may hold at once, so heavy report
+//! queries cannot take every pooled connection and starve OLTP traffic. Limits are configured with
+//! [`PoolOptions`]:
+//! - `max_concurrent_queries` caps every checkout. [`ConfigAndPool::get_connection`] waits for a
+//!   permit before checking a connection out, and the connection holds it until it drops.
+//! - A bulkhead tag is held for a connection's lifetime when checked out with
+//!   [`ConfigAndPool::get_connection_with`], or for one query when the builder is given
+//!   [`QueryOptions::bulkhead`].
+//!
+//! With [`ConfigAndPool::get_connection_with`], permits are taken before the connection is
+//! checked out, the global one before a tag's, so two callers never wait on each other's. Work
+//! waiting for a full bulkhead holds its global slot meanwhile, but no pooled connection, so
+//! untagged work is never starved by it.
+//!
+//! A per-query tag is different: the builder already holds its connection, and waits for the tag
+//! with it checked out. It caps how many such queries run at once, but not how many connections
+//! their callers hold, so it does not protect the pool. Check out with
+//! [`ConfigAndPool::get_connection_with`] when the tag should bound connections.
+
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::rate_limit::{RateLimitPolicy, TokenBucket};
+use super::{ConfigAndPool, MiddlewarePoolConnection, ResetMode};
+use crate::audit::AuditTables;
+use crate::error::SqlMiddlewareDbError;
+#[cfg(any_backend)]
+use crate::executor::{QueryTarget, QueryTargetKind};
+use crate::translation::QueryOptions;
+
+/// Pool-level concurrency and rate limits, session reset and conventions, applied with
+/// [`ConfigAndPool::with_pool_options`]. Settings left unset keep the pool's current values.
+///
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+/// use sql_middleware::pool::PoolOptions;
+///
+/// # async fn demo() -> Result<(), SqlMiddlewareDbError> {
+/// let cap = ConfigAndPool::new_sqlite(SqliteOptions::new("app.db".into()))
+///     .await?
+///     .with_pool_options(PoolOptions::default().max_concurrent_queries(8).bulkhead("reports", 2));
+/// let mut conn = cap
+///     .get_connection_with(QueryOptions::default().bulkhead("reports"))
+///     .await?;
+/// conn.query("SELECT 1").select().await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PoolOptions {
+    max_concurrent_queries: Option<usize>,
+    bulkheads: Vec<(String, usize)>,
+    reset_on_return: Option<ResetMode>,
+    soft_delete_column: Option<Arc<str>>,
+    rate_limit: Option<(u32, u32, RateLimitPolicy)>,
+    audit: Option<AuditTables>,
+}
+
+impl PoolOptions {
+    /// Cap connections checked out from the pool at once, across all tags. Zero is treated as
+    /// one.
+    #[must_use]
+    pub fn max_concurrent_queries(mut self, n: usize) -> Self {
+        self.max_concurrent_queries = Some(n);
+        self
+    }
+
+    /// Cap connections held for work tagged `tag` (see [`QueryOptions::bulkhead`]). Setting a tag
+    /// again replaces its limit. Zero is treated as one.
+    #[must_use]
+    pub fn bulkhead(mut self, tag: &str, n: usize) -> Self {
+        self.bulkheads.push((tag.to_string(), n));
+        self
+    }
+
+    /// Reset session state as `mode` describes on connections that come back to the pool. What
+    /// is cleared differs by backend; see [`ResetMode::Fast`]. The default is
+    /// [`ResetMode::None`].
+    ///
+    /// Drop cannot run the reset, so it happens when the connection is next checked out, before
+    /// the new caller sees it. [`MiddlewarePoolConnection::close`] resets right away. If the
+    /// reset fails, the connection is retired instead of reused and the checkout fails with the
+    /// reset's error.
+    #[must_use]
+    pub fn reset_on_return(mut self, mode: ResetMode) -> Self {
+        self.reset_on_return = Some(mode);
+        self
+    }
+
+    /// Name the column that marks soft-deleted rows in this pool's tables, for
+    /// [`QueryBuilder::filter_deleted`](crate::QueryBuilder::filter_deleted) and
+    /// [`MiddlewarePoolConnection::soft_delete`]. The default is `deleted_at`.
+    #[must_use]
+    pub fn soft_delete_column(mut self, column: &str) -> Self {
+        self.soft_delete_column = Some(Arc::from(column));
+        self
+    }
+
+    /// Let connections from this pool send at most `qps` statements per second on average, with
+    /// bursts of up to `burst`. Statements past the limit wait for their turn; see
+    /// [`rate_limit`](super::rate_limit). Zero for either is treated as one.
+    #[must_use]
+    pub fn rate_limit(self, qps: u32, burst: u32) -> Self {
+        self.rate_limit_with(qps, burst, RateLimitPolicy::Wait)
+    }
+
+    /// Like [`rate_limit`](Self::rate_limit), choosing what happens past the limit.
+    #[must_use]
+    pub fn rate_limit_with(mut self, qps: u32, burst: u32, policy: RateLimitPolicy) -> Self {
+        self.rate_limit = Some((qps, burst, policy));
+        self
+    }
+
+    /// Audit builder DML against `tables` on this pool's connections; see [`audit`](crate::audit).
+    #[must_use]
+    pub fn audit(mut self, tables: AuditTables) -> Self {
+        self.audit = Some(tables);
+        self
+    }
+}
+
+/// Semaphores built from [`PoolOptions`], shared by clones of a `ConfigAndPool`.
+#[derive(Debug, Default)]
+pub(crate) struct QueryLimits {
+    global: Option<Arc<Semaphore>>,
+    tags: HashMap<String, Arc<Semaphore>>,
+}
+
+impl QueryLimits {
+    /// These limits with the ones `options` sets replaced; the rest keep their semaphores.
+    fn merged(&self, options: &PoolOptions) -> Self {
+        let mut tags = self.tags.clone();
+        for (tag, n) in &options.bulkheads {
+            tags.insert(tag.clone(), Arc::new(Semaphore::new((*n).max(1))));
+        }
+        Self {
+            global: options
+                .max_concurrent_queries
+                .map(|n| Arc::new(Semaphore::new(n.max(1))))
+                .or_else(|| self.global.clone()),
+            tags,
+        }
+    }
+
+    /// Wait for a global permit, if `max_concurrent_queries` is set.
+    async fn acquire_global(&self) -> Result<Option<OwnedSemaphorePermit>, SqlMiddlewareDbError> {
+        match &self.global {
+            Some(semaphore) => acquire(semaphore).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn acquire_tag(&self, tag: &str) -> Result<OwnedSemaphorePermit, SqlMiddlewareDbError> {
+        let semaphore = self.tags.get(tag).ok_or_else(|| {
+            SqlMiddlewareDbError::ConfigError(format!("no bulkhead configured for {tag:?}"))
+        })?;
+        acquire(semaphore).await
+    }
+}
+
+async fn acquire(semaphore: &Arc<Semaphore>) -> Result<OwnedSemaphorePermit, SqlMiddlewareDbError> {
+    Arc::clone(semaphore)
+        .acquire_owned()
+        .await
+        .map_err(|_| SqlMiddlewareDbError::ConnectionError("query limiter closed".into()))
+}
+
+/// A pool's concurrency limits as attached to a checked-out connection, with the permits it
+/// holds until it drops.
+#[derive(Debug)]
+// Only builder queries read the limits, and there are none without a backend.
+#[cfg_attr(not(any_backend), allow(dead_code))]
+pub(crate) struct HeldLimits {
+    limits: Arc<QueryLimits>,
+    /// Bulkhead this connection was checked out under, if any.
+    bulkhead: Option<&'static str>,
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+impl HeldLimits {
+    /// Wait for a global permit, then for one of `bulkhead` if given, to hold for the life of a
+    /// connection not yet checked out.
+    pub(crate) async fn acquire(
+        limits: &Arc<QueryLimits>,
+        bulkhead: Option<&'static str>,
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let mut permits: Vec<_> = limits.acquire_global().await?.into_iter().collect();
+        if let Some(tag) = bulkhead {
+            permits.push(limits.acquire_tag(tag).await?);
+        }
+        Ok(Self {
+            limits: Arc::clone(limits),
+            bulkhead,
+            _permits: permits,
+        })
+    }
+
+    /// Wait for a `tag` permit to run one query, unless this connection already holds one.
+    #[cfg(any_backend)]
+    pub(crate) async fn enter(
+        &self,
+        tag: Option<&'static str>,
+    ) -> Result<Option<OwnedSemaphorePermit>, SqlMiddlewareDbError> {
+        match tag {
+            Some(tag) if self.bulkhead != Some(tag) => self.limits.acquire_tag(tag).await.map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A pooled connection holding a bulkhead permit; derefs to [`MiddlewarePoolConnection`].
+#[derive(Debug)]
+pub struct LimitedConnection {
+    conn: MiddlewarePoolConnection,
+}
+
+impl Deref for LimitedConnection {
+    type Target = MiddlewarePoolConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl DerefMut for LimitedConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+#[cfg(any_backend)]
+impl QueryTarget<'_> {
+    /// Wait for the permit of the bulkhead `tag`, held while one builder query runs.
+    ///
+    /// Only pooled connections know their pool's limits; other targets reject a tag rather than
+    /// ignore it.
+    pub(crate) async fn enter_bulkhead(
+        &mut self,
+        tag: Option<&'static str>,
+    ) -> Result<Option<OwnedSemaphorePermit>, SqlMiddlewareDbError> {
+        let Some(tag) = tag else {
+            return Ok(None);
+        };
+        match &mut self.kind {
+            QueryTargetKind::Connection(conn) => match conn.context() {
+                Some(context) => context.limits.enter(Some(tag)).await,
+                None => Err(no_limits(tag)),
+            },
+            #[allow(unreachable_patterns)]
+            _ => Err(no_limits(tag)),
+        }
+    }
+}
+
+#[cfg(any_backend)]
+fn no_limits(tag: &str) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ConfigError(format!(
+        "bulkhead {tag:?} needs a connection from ConfigAndPool::get_connection"
+    ))
+}
+
+impl ConfigAndPool {
+    /// Apply `options` over this pool's concurrency and rate limits, session reset, soft-delete
+    /// and audit settings. Settings `options` leaves unset keep their current values.
+    ///
+    /// Clones made afterwards share the limits; clones made before keep the old ones.
+    #[must_use]
+    pub fn with_pool_options(mut self, options: PoolOptions) -> Self {
+        self.limits = Arc::new(self.limits.merged(&options));
+        if let Some(mode) = options.reset_on_return {
+            self.reset_on_return = mode;
+        }
+        let context = Arc::make_mut(&mut self.context);
+        if let Some(column) = options.soft_delete_column {
+            context.soft_delete_column = Some(column);
+        }
+        if let Some((qps, burst, policy)) = options.rate_limit {
+            context.rate_limit = Some(Arc::new(TokenBucket::new(qps, burst, policy)));
+        }
+        if let Some(tables) = options.audit {
+            context.audit = Some(tables);
+        }
+        self
+    }
+
+    /// Get a pooled connection once `max_concurrent_queries` and `options.bulkhead` allow it.
+    ///
+    /// The bulkhead permit is held until the returned connection drops, and covers builder
+    /// queries on it tagged with the same bulkhead.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if `options.bulkhead` names a tag with no
+    /// configured limit, or bubbles up pool checkout errors.
+    pub async fn get_connection_with(
+        &self,
+        options: QueryOptions,
+    ) -> Result<LimitedConnection, SqlMiddlewareDbError> {
+        let conn = self.checkout_guarded(options.bulkhead).await?;
+        Ok(LimitedConnection { conn })
+    }
+}
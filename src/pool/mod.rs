@@ -1,14 +1,46 @@
+pub mod affinity;
 pub mod any_conn_wrapper;
+pub mod breaker;
+pub mod checkout;
 pub mod connection;
+pub mod echo;
+#[cfg(any_backend)]
+pub mod group_commit;
 pub mod interaction;
+pub mod limits;
+#[cfg(any_backend)]
+mod oneshot;
+pub mod rate_limit;
+pub mod stats;
+#[cfg(any_backend)]
+pub mod temp_table;
+pub mod transaction;
+pub mod two_phase;
 pub mod types;
 
+pub use affinity::LeasedConnection;
 pub use any_conn_wrapper::AnyConnWrapper;
-pub use connection::MiddlewarePoolConnection;
+pub use breaker::CircuitState;
+pub use connection::{MiddlewarePoolConnection, ResetMode};
+#[cfg(any_backend)]
+pub use group_commit::GroupCommit;
+pub use limits::{LimitedConnection, PoolOptions};
+pub use rate_limit::{RateLimitPolicy, RateLimitStats};
+pub use stats::PoolStats;
+pub use transaction::NestedTransaction;
 pub use types::MiddlewarePool;
 
+use std::sync::Arc;
+#[cfg(any_backend)]
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
 use crate::SqlMiddlewareDbError;
+use crate::clock::Clock;
+use crate::jitter::Jitter;
 use crate::types::DatabaseType;
+use checkout::PoolContext;
+use limits::QueryLimits;
 
 /// Configuration plus connection pool for a database backend.
 ///
@@ -32,13 +64,74 @@ pub struct ConfigAndPool {
     pub db_type: DatabaseType,
     /// Whether placeholder translation is enabled by default for this pool
     pub translate_placeholders: bool,
+    /// Settings attached to checked-out connections; copied on write, so clones made before a
+    /// change keep the old settings
+    pub(crate) context: Arc<PoolContext>,
+    /// Concurrency limits set with [`ConfigAndPool::with_pool_options`]
+    pub(crate) limits: Arc<QueryLimits>,
+    /// Session reset set with [`PoolOptions::reset_on_return`]
+    pub(crate) reset_on_return: ResetMode,
+    /// Whether [`LEASE_TABLE`](crate::lease::LEASE_TABLE) has been created, shared by clones
+    #[cfg(any_backend)]
+    pub(crate) lease_table_ready: Arc<AtomicBool>,
 }
 
 impl ConfigAndPool {
+    /// Wrap an already-built pool, using the system clock.
+    ///
+    /// Backend constructors call this; use it directly when building a pool by hand (e.g., with
+    /// custom bb8 settings).
+    #[must_use]
+    pub fn from_pool(
+        pool: MiddlewarePool,
+        db_type: DatabaseType,
+        translate_placeholders: bool,
+    ) -> Self {
+        Self {
+            pool,
+            db_type,
+            translate_placeholders,
+            context: Arc::new(PoolContext::new()),
+            limits: Arc::default(),
+            reset_on_return: ResetMode::None,
+            #[cfg(any_backend)]
+            lease_table_ready: Arc::default(),
+        }
+    }
+
+    /// Clock used for checkout timeouts and retry delays.
+    #[must_use]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.context.clock
+    }
+
+    /// Random source used to jitter retry delays and circuit-breaker cool-downs.
+    #[must_use]
+    pub fn jitter(&self) -> &Arc<dyn Jitter> {
+        &self.context.jitter
+    }
+
+    /// Replace the clock used for checkout timeouts and retry delays.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        Arc::make_mut(&mut self.context).clock = clock;
+        self
+    }
+
+    /// Replace the random source used to jitter retry delays and circuit-breaker cool-downs.
+    ///
+    /// Install a [`SeededJitter`](crate::jitter::SeededJitter) to make retry timing reproducible.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: Arc<dyn Jitter>) -> Self {
+        Arc::make_mut(&mut self.context).jitter = jitter;
+        self
+    }
+
     /// Get a pooled connection and attach pool-level defaults to it.
     ///
     /// # Errors
-    /// Bubbles up pool checkout errors for the active backend.
+    /// Bubbles up pool checkout errors for the active backend, or returns
+    /// `SqlMiddlewareDbError::CircuitOpen` while a circuit breaker is open.
     ///
     /// # Examples
     /// ```rust,no_run
@@ -51,7 +144,32 @@ impl ConfigAndPool {
     /// # Ok(()) }
     /// ```
     pub async fn get_connection(&self) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
-        let pool_ref = self.pool.get().await?;
-        MiddlewarePool::get_connection(pool_ref, self.translate_placeholders).await
+        self.checkout_guarded(None).await
+    }
+
+    /// Get a pooled connection, giving up after `timeout` on this pool's clock.
+    ///
+    /// The checkout is polled before the timer, so a checkout that completes in the same poll as
+    /// the timer still wins.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConnectionError` when the timeout elapses first, or bubbles
+    /// up pool checkout errors for the active backend.
+    pub async fn get_connection_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+        tokio::select! {
+            biased;
+            conn = self.get_connection() => conn,
+            () = self.context.clock.sleep(timeout) => {
+                let timed_out = Err(SqlMiddlewareDbError::ConnectionError(format!(
+                    "pool checkout timed out after {timeout:?}"
+                )));
+                // The abandoned checkout never reported to the circuit breaker.
+                self.record_result(&timed_out);
+                timed_out
+            }
+        }
     }
 }
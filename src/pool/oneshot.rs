@@ -0,0 +1,59 @@
+//! One-statement helpers on [`ConfigAndPool`].
+//!
+//! Each call checks out a connection, runs a single statement with the pool's default placeholder
+//! translation, and returns the connection to the pool. Use
+//! [`get_connection`](ConfigAndPool::get_connection) when several statements must share a
+//! connection or a transaction.
+
+use super::ConfigAndPool;
+use crate::error::SqlMiddlewareDbError;
+use crate::results::ResultSet;
+use crate::types::RowValues;
+
+impl ConfigAndPool {
+    /// Run a query on a pooled connection and collect its rows.
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+    /// let rows = cap
+    ///     .select("SELECT name FROM users WHERE id = ?1", &[RowValues::Int(1)])
+    ///     .await?;
+    /// # let _ = rows;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if checkout fails or the query fails.
+    pub async fn select(
+        &self,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        let mut conn = self.get_connection().await?;
+        conn.query(sql).params(params).select().await
+    }
+
+    /// Run a statement on a pooled connection and return the number of affected rows.
+    ///
+    /// # Errors
+    /// Returns an error if checkout fails or the statement fails.
+    pub async fn dml(
+        &self,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        let mut conn = self.get_connection().await?;
+        conn.query(sql).params(params).dml().await
+    }
+
+    /// Run one or more statements without parameters on a pooled connection.
+    ///
+    /// # Errors
+    /// Returns an error if checkout fails or any statement fails.
+    pub async fn batch(&self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        let mut conn = self.get_connection().await?;
+        conn.execute_batch(sql).await
+    }
+}
@@ -0,0 +1,242 @@
+//! Token-bucket rate limiting of statements per pool.
+//!
+//! [`PoolOptions::rate_limit`](super::PoolOptions::rate_limit) gives a pool a bucket of `burst`
+//! tokens refilled at `qps` per second. Every statement a connection from that pool sends (query
+//! builder `select`/`dml`, `execute_batch`, closure transactions' control statements, and each
+//! statement of helpers such as `execute_dml_atomic`, `bulk_insert` and group commit) takes a
+//! token first; when the bucket is empty the statement waits for its turn, or fails with
+//! [`SqlMiddlewareDbError::RateLimited`] under [`RateLimitPolicy::Reject`]. This keeps one
+//! misbehaving batch job from saturating a shared database.
+//!
+//! Backend transaction handles are limited too when they are begun from a pooled connection:
+//! `BEGIN` and every statement on the handle take a token, while `COMMIT` and `ROLLBACK` never
+//! wait, so a transaction can always be ended. The `SQLite` handle always comes from a pooled
+//! connection; for the others use
+//! [`MiddlewarePoolConnection::begin_postgres_transaction`](super::MiddlewarePoolConnection)
+//! and its `mssql`/`turso` counterparts. Handles begun on a raw driver client cannot see the
+//! pool and are not limited.
+//!
+//! Waits use the pool's [`Clock`], so `MockClock` and paused tokio time drive them in tests. A
+//! wait that is cancelled (its future dropped, e.g. by a timeout) gives its token back.
+//! [`ConfigAndPool::rate_limit_stats`] reports how often and for how long statements waited.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::ConfigAndPool;
+use crate::clock::Clock;
+use crate::error::SqlMiddlewareDbError;
+
+/// What happens to a statement sent while the pool's bucket is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitPolicy {
+    /// Wait until a token is available.
+    #[default]
+    Wait,
+    /// Fail at once with [`SqlMiddlewareDbError::RateLimited`].
+    Reject,
+}
+
+/// Counters for a pool's rate limit; see [`ConfigAndPool::rate_limit_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RateLimitStats {
+    /// Statements that had to wait for a token.
+    pub throttled: u64,
+    /// Total time statements spent waiting for tokens.
+    pub throttled_time: Duration,
+    /// Statements rejected under [`RateLimitPolicy::Reject`].
+    pub rejected: u64,
+}
+
+/// Bucket shared by all connections of a pool.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    qps: f64,
+    burst: f64,
+    policy: RateLimitPolicy,
+    state: Mutex<BucketState>,
+    throttled: AtomicU64,
+    throttled_nanos: AtomicU64,
+    rejected: AtomicU64,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Tokens available; negative when waiters have reserved tokens not yet refilled.
+    tokens: f64,
+    /// When `tokens` was last brought up to date; `None` until the first statement.
+    updated: Option<Instant>,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(qps: u32, burst: u32, policy: RateLimitPolicy) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            qps: f64::from(qps.max(1)),
+            burst,
+            policy,
+            state: Mutex::new(BucketState {
+                tokens: burst,
+                updated: None,
+            }),
+            throttled: AtomicU64::new(0),
+            throttled_nanos: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BucketState> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Take a token at `now`, returning how long the caller must wait before using it.
+    ///
+    /// Waiting callers reserve their token up front, so they are served in arrival order.
+    fn reserve(&self, now: Instant) -> Result<Duration, SqlMiddlewareDbError> {
+        let mut state = self.lock();
+        if let Some(updated) = state.updated {
+            let refill = now.saturating_duration_since(updated).as_secs_f64() * self.qps;
+            state.tokens = (state.tokens + refill).min(self.burst);
+        }
+        state.updated = Some(now);
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+        let wait = Duration::from_secs_f64((1.0 - state.tokens) / self.qps);
+        if self.policy == RateLimitPolicy::Reject {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(SqlMiddlewareDbError::RateLimited { retry_after: wait });
+        }
+        state.tokens -= 1.0;
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        self.throttled_nanos.fetch_add(
+            u64::try_from(wait.as_nanos()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+        Ok(wait)
+    }
+
+    /// Wait for a token on `clock`, or fail under [`RateLimitPolicy::Reject`].
+    ///
+    /// If the returned future is dropped while waiting, the reserved token goes back to the bucket.
+    pub(crate) async fn acquire(&self, clock: &dyn Clock) -> Result<(), SqlMiddlewareDbError> {
+        let wait = self.reserve(clock.now())?;
+        if !wait.is_zero() {
+            let reservation = Reservation {
+                bucket: self,
+                kept: false,
+            };
+            clock.sleep(wait).await;
+            reservation.keep();
+        }
+        Ok(())
+    }
+
+    /// Give back a token reserved by a wait that never finished.
+    fn refund(&self) {
+        let mut state = self.lock();
+        state.tokens = (state.tokens + 1.0).min(self.burst);
+    }
+
+    fn stats(&self) -> RateLimitStats {
+        RateLimitStats {
+            throttled: self.throttled.load(Ordering::Relaxed),
+            throttled_time: Duration::from_nanos(self.throttled_nanos.load(Ordering::Relaxed)),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A token reserved by a waiting caller; returned to the bucket unless the wait completes.
+struct Reservation<'a> {
+    bucket: &'a TokenBucket,
+    kept: bool,
+}
+
+impl Reservation<'_> {
+    fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.bucket.refund();
+        }
+    }
+}
+
+impl ConfigAndPool {
+    /// Counters for this pool's rate limit, or `None` if it has none.
+    #[must_use]
+    pub fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        self.context
+            .rate_limit
+            .as_ref()
+            .map(|bucket| bucket.stats())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refills_at_qps_up_to_burst() {
+        let bucket = TokenBucket::new(10, 2, RateLimitPolicy::Wait);
+        let start = Instant::now();
+        assert_eq!(bucket.reserve(start).unwrap(), Duration::ZERO);
+        assert_eq!(bucket.reserve(start).unwrap(), Duration::ZERO);
+        // Empty: the next two wait 100ms and 200ms for their tokens.
+        assert_eq!(bucket.reserve(start).unwrap(), Duration::from_millis(100));
+        assert_eq!(bucket.reserve(start).unwrap(), Duration::from_millis(200));
+        // A long idle period refills to `burst`, not beyond.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later).unwrap(), Duration::ZERO);
+        assert_eq!(bucket.reserve(later).unwrap(), Duration::ZERO);
+        assert!(bucket.reserve(later).unwrap() > Duration::ZERO);
+
+        let stats = bucket.stats();
+        assert_eq!(stats.throttled, 3);
+        assert_eq!(stats.rejected, 0);
+    }
+
+    #[test]
+    fn reject_does_not_take_a_token() {
+        let bucket = TokenBucket::new(4, 1, RateLimitPolicy::Reject);
+        let start = Instant::now();
+        bucket.reserve(start).unwrap();
+        let err = bucket.reserve(start).unwrap_err();
+        assert!(matches!(
+            err,
+            SqlMiddlewareDbError::RateLimited { retry_after } if retry_after == Duration::from_millis(250)
+        ));
+        assert_eq!(
+            bucket.reserve(start + Duration::from_millis(250)).unwrap(),
+            Duration::ZERO
+        );
+        assert_eq!(bucket.stats().rejected, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_wait_returns_its_token() {
+        let clock = crate::clock::system_clock();
+        let bucket = TokenBucket::new(10, 1, RateLimitPolicy::Wait);
+        bucket.acquire(clock.as_ref()).await.unwrap();
+
+        let waiting =
+            tokio::time::timeout(Duration::from_millis(50), bucket.acquire(clock.as_ref()));
+        assert!(waiting.await.is_err(), "wait is cut short by the timeout");
+        // Half a token has refilled; without the refund the next caller would also queue behind
+        // the abandoned reservation and wait 150ms.
+        let wait = bucket.reserve(clock.now()).unwrap();
+        assert!(wait <= Duration::from_millis(51), "{wait:?}");
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt;
+#[cfg(any_backend)]
+use std::time::{Duration, Instant};
+
+#[cfg(any_backend)]
+use bb8::{ManageConnection, Pool, PooledConnection, RunError};
+
+#[cfg(any_backend)]
+use crate::error::SqlMiddlewareDbError;
+
+/// Snapshot of pool occupancy, captured when a checkout fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStats {
+    /// Connections currently managed by the pool (idle + checked out).
+    pub connections: u32,
+    /// Idle connections available for checkout.
+    pub idle_connections: u32,
+    /// Checkouts that had to wait for a connection so far.
+    pub get_waited: u64,
+    /// Checkouts that timed out so far.
+    pub get_timed_out: u64,
+    /// Connections closed because they were broken.
+    pub connections_closed_broken: u64,
+}
+
+#[cfg(any_backend)]
+impl PoolStats {
+    pub(crate) fn of<M: ManageConnection>(pool: &Pool<M>) -> Self {
+        let state = pool.state();
+        Self {
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            get_waited: state.statistics.get_waited,
+            get_timed_out: state.statistics.get_timed_out,
+            connections_closed_broken: state.statistics.connections_closed_broken,
+        }
+    }
+}
+
+impl fmt::Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "connections={} idle={} waited={} timed_out={} closed_broken={}",
+            self.connections,
+            self.idle_connections,
+            self.get_waited,
+            self.get_timed_out,
+            self.connections_closed_broken
+        )
+    }
+}
+
+/// Check out an owned connection, handing failures to `wrap` with the wait time and pool stats.
+#[cfg(any_backend)]
+pub(crate) async fn checkout<M, F>(
+    pool: &Pool<M>,
+    wrap: F,
+) -> Result<PooledConnection<'static, M>, SqlMiddlewareDbError>
+where
+    M: ManageConnection,
+    F: FnOnce(RunError<M::Error>, Duration, PoolStats) -> SqlMiddlewareDbError,
+{
+    let started = Instant::now();
+    pool.get_owned()
+        .await
+        .map_err(|source| wrap(source, started.elapsed(), PoolStats::of(pool)))
+}
@@ -0,0 +1,121 @@
+//! Closure-scoped temporary tables.
+//!
+//! [`MiddlewarePoolConnection::with_temp_table`] creates a uniquely named temporary table on the
+//! connection, runs the closure with that same connection and the table's name, and drops the
+//! table when the closure returns, `Ok` or `Err`. Temp tables belong to one session, so the
+//! closure must use the connection it is given rather than checking out another.
+//!
+//! Per backend:
+//! - Postgres and `SQLite`: `CREATE TEMPORARY TABLE`.
+//! - SQL Server: a `#`-prefixed local temp table, created outside `sp_executesql` so it outlives
+//!   the statement that created it.
+//! - Turso: a regular table, since Turso has no temp tables; it is dropped all the same.
+//!
+//! If the future is cancelled mid-closure the table is not dropped; it goes away when the session
+//! ends (or, on Turso, stays until dropped by hand).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::MiddlewarePoolConnection;
+use crate::error::SqlMiddlewareDbError;
+use crate::types::DatabaseType;
+
+static NEXT_TEMP_TABLE: AtomicU64 = AtomicU64::new(1);
+
+impl MiddlewarePoolConnection {
+    /// Create a temporary table with the column definitions in `schema`, run `f` with this
+    /// connection and the table's name, then drop the table.
+    ///
+    /// `schema` is what goes between the parentheses of `CREATE TABLE`. The table is dropped
+    /// whether `f` succeeds or fails. See the [module docs](crate::pool::temp_table).
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let top: i64 = conn
+    ///     .with_temp_table("id BIGINT, total BIGINT", async |conn, table| {
+    ///         conn.execute_batch(&format!(
+    ///             "INSERT INTO {table} SELECT customer_id, SUM(amount) FROM orders \
+    ///              GROUP BY customer_id"
+    ///         ))
+    ///         .await?;
+    ///         conn.query(&format!("SELECT MAX(total) FROM {table}"))
+    ///             .select_scalar()
+    ///             .await
+    ///     })
+    ///     .await?;
+    /// # let _ = top;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the error from `f`, or from creating or dropping the table. A failed drop after
+    /// `f` fails is ignored in favor of `f`'s error.
+    pub async fn with_temp_table<F, R>(
+        &mut self,
+        schema: &str,
+        f: F,
+    ) -> Result<R, SqlMiddlewareDbError>
+    where
+        F: AsyncFnOnce(&mut MiddlewarePoolConnection, &str) -> Result<R, SqlMiddlewareDbError>,
+    {
+        let db_type = self.database_type();
+        let name = temp_table_name(&db_type);
+        self.create_temp_table(&db_type, &name, schema).await?;
+
+        let result = f(self, &name).await;
+        let drop = self.run_control(&format!("DROP TABLE {name}")).await;
+        match result {
+            Ok(value) => drop.map(|()| value),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn create_temp_table(
+        &mut self,
+        db_type: &DatabaseType,
+        name: &str,
+        schema: &str,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { conn, context, .. } => {
+                if let Some(context) = context {
+                    context.pool.throttle().await?;
+                }
+                conn.simple_query(format!("CREATE TABLE {name} ({schema})"))
+                    .await?
+                    .into_results()
+                    .await?;
+                Ok(())
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                let create = match db_type {
+                    #[cfg(feature = "turso")]
+                    DatabaseType::Turso => "CREATE TABLE",
+                    #[allow(unreachable_patterns)]
+                    _ => "CREATE TEMPORARY TABLE",
+                };
+                self.run_control(&format!("{create} {name} ({schema})"))
+                    .await
+            }
+        }
+    }
+}
+
+/// A name no other temp table from this process uses; `#`-prefixed on SQL Server.
+fn temp_table_name(db_type: &DatabaseType) -> String {
+    let name = format!(
+        "sql_middleware_tmp_{}_{}",
+        std::process::id(),
+        NEXT_TEMP_TABLE.fetch_add(1, Ordering::Relaxed)
+    );
+    match db_type {
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => format!("#{name}"),
+        #[allow(unreachable_patterns)]
+        _ => name,
+    }
+}
@@ -0,0 +1,228 @@
+//! Closure-scoped transactions that nest through savepoints.
+//!
+//! [`MiddlewarePoolConnection::transaction`] begins a transaction, runs the closure, and commits
+//! on `Ok` or rolls back on `Err`. Called again inside the closure (or on a connection already in
+//! a transaction), it opens a savepoint instead, so functions that each want "their own"
+//! transaction compose: an inner failure rolls back only the inner work, and nothing is committed
+//! until the outermost call finishes. Use [`transaction_with`](MiddlewarePoolConnection::transaction_with)
+//! and [`NestedTransaction::Error`] to reject nesting instead.
+//!
+//! How an open transaction is detected:
+//! - Postgres: `transaction_timestamp() <> statement_timestamp()`, which only holds inside an
+//!   explicit transaction block.
+//! - `SQLite`: the connection's autocommit flag.
+//! - SQL Server: `@@TRANCOUNT`. Savepoints use `SAVE TRANSACTION`, which has no release.
+//! - Turso: not detected, so a nested call issues a second `BEGIN`, which Turso rejects.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{MiddlewarePoolConnection, echo};
+use crate::error::SqlMiddlewareDbError;
+use crate::types::DatabaseType;
+
+/// What [`MiddlewarePoolConnection::transaction_with`] does when a transaction is already open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NestedTransaction {
+    /// Run the closure inside a savepoint of the open transaction.
+    #[default]
+    Savepoint,
+    /// Fail with `SqlMiddlewareDbError::ExecutionError`.
+    Error,
+}
+
+static NEXT_SAVEPOINT: AtomicU64 = AtomicU64::new(1);
+
+/// Statements that open, keep, and undo one transaction level.
+pub(crate) struct Level {
+    pub(crate) begin: String,
+    pub(crate) commit: Option<String>,
+    pub(crate) rollback: String,
+}
+
+impl Level {
+    fn transaction() -> Self {
+        Self {
+            begin: "BEGIN TRANSACTION".to_string(),
+            commit: Some("COMMIT".to_string()),
+            rollback: "ROLLBACK".to_string(),
+        }
+    }
+
+    pub(crate) fn savepoint(db_type: DatabaseType) -> Self {
+        let name = format!(
+            "sql_middleware_sp_{}",
+            NEXT_SAVEPOINT.fetch_add(1, Ordering::Relaxed)
+        );
+        match db_type {
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => Self {
+                begin: format!("SAVE TRANSACTION {name}"),
+                commit: None,
+                rollback: format!("ROLLBACK TRANSACTION {name}"),
+            },
+            #[allow(unreachable_patterns)]
+            _ => Self {
+                begin: format!("SAVEPOINT {name}"),
+                commit: Some(format!("RELEASE SAVEPOINT {name}")),
+                rollback: format!("ROLLBACK TO SAVEPOINT {name}; RELEASE SAVEPOINT {name}"),
+            },
+        }
+    }
+}
+
+impl MiddlewarePoolConnection {
+    /// Run `f` in a transaction, or in a savepoint if one is already open.
+    ///
+    /// Commits (or releases the savepoint) when `f` returns `Ok`; rolls back to where this call
+    /// started when it returns `Err`. See the [module docs](crate::pool::transaction).
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// async fn add_user(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    ///     conn.transaction(async |conn| {
+    ///         conn.execute_batch("INSERT INTO users (name) VALUES ('ann')").await
+    ///     })
+    ///     .await
+    /// }
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// // `add_user` commits on its own, or becomes part of an enclosing transaction.
+    /// conn.transaction(async |conn| {
+    ///     add_user(conn).await?;
+    ///     conn.execute_batch("UPDATE stats SET users = users + 1").await
+    /// })
+    /// .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the error from `f`, or from beginning, committing, or rolling back.
+    pub async fn transaction<F, R>(&mut self, f: F) -> Result<R, SqlMiddlewareDbError>
+    where
+        F: AsyncFnOnce(&mut MiddlewarePoolConnection) -> Result<R, SqlMiddlewareDbError>,
+    {
+        self.transaction_with(NestedTransaction::Savepoint, f).await
+    }
+
+    /// Like [`transaction`](Self::transaction), choosing what happens when a transaction is
+    /// already open.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` for a nested call under
+    /// [`NestedTransaction::Error`], or the error from `f`, beginning, committing, or rolling
+    /// back.
+    pub async fn transaction_with<F, R>(
+        &mut self,
+        nested: NestedTransaction,
+        f: F,
+    ) -> Result<R, SqlMiddlewareDbError>
+    where
+        F: AsyncFnOnce(&mut MiddlewarePoolConnection) -> Result<R, SqlMiddlewareDbError>,
+    {
+        let level = if self.in_transaction().await? {
+            match nested {
+                NestedTransaction::Savepoint => Level::savepoint(self.database_type()),
+                NestedTransaction::Error => {
+                    return Err(SqlMiddlewareDbError::ExecutionError(
+                        "transaction already in progress on this connection".into(),
+                    ));
+                }
+            }
+        } else {
+            Level::transaction()
+        };
+
+        self.run_control(&level.begin).await?;
+        match f(self).await {
+            Ok(value) => {
+                if let Some(commit) = &level.commit {
+                    self.run_control(commit).await?;
+                }
+                Ok(value)
+            }
+            Err(err) => {
+                let _ = self.run_control(&level.rollback).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Run a transaction-control statement as is, outside the transaction
+    /// [`execute_batch`](Self::execute_batch) would wrap it in.
+    pub(crate) async fn run_control(&mut self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        self.throttle().await?;
+        echo::statement(self.debug_echo_flag(), sql, sql, &[]);
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                client.batch_execute(sql).await?;
+                Ok(())
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                let sql = sql.to_owned();
+                self.with_blocking_sqlite(move |conn| {
+                    conn.execute_batch(&sql)
+                        .map_err(SqlMiddlewareDbError::SqliteError)
+                })
+                .await
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { conn, .. } => {
+                crate::mssql::execute_batch(conn, sql).await
+            }
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { conn, .. } => {
+                crate::turso::execute_batch(conn, sql).await
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(SqlMiddlewareDbError::Unimplemented(
+                "This database type is not enabled in the current build".to_string(),
+            )),
+        }
+    }
+
+    /// Whether an explicit transaction is open on this connection.
+    ///
+    /// Turso connections always report `false`.
+    ///
+    /// # Errors
+    /// Returns an error if the backend cannot be queried.
+    pub async fn in_transaction(&mut self) -> Result<bool, SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                let row = client
+                    .query_one(
+                        "SELECT transaction_timestamp() <> statement_timestamp()",
+                        &[],
+                    )
+                    .await?;
+                Ok(row.get(0))
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                self.with_blocking_sqlite(|conn| Ok(!conn.is_autocommit()))
+                    .await
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { conn, .. } => {
+                let rows =
+                    crate::mssql::execute_select(conn, "SELECT @@TRANCOUNT AS depth", &[]).await?;
+                Ok(rows
+                    .results
+                    .first()
+                    .and_then(|row| row.get("depth"))
+                    .and_then(crate::types::RowValues::as_int)
+                    .is_some_and(|depth| *depth > 0))
+            }
+            #[cfg(feature = "turso")]
+            MiddlewarePoolConnection::Turso { .. } => Ok(false),
+            #[allow(unreachable_patterns)]
+            _ => Err(SqlMiddlewareDbError::Unimplemented(
+                "This database type is not enabled in the current build".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Finishing prepared (two-phase) transactions from the pool.
+//!
+//! A coordinator prepares each participant's transaction with
+//! [`postgres::Tx::prepare_commit`](crate::postgres::Tx::prepare_commit), then commits or rolls
+//! them all back once every participant has voted. The prepared transaction is not tied to the
+//! connection that prepared it, so the methods here check out any connection from the pool.
+//!
+//! Only Postgres supports this; check [`DatabaseType::supports_prepared_transactions`] before
+//! relying on it. SQL Server needs MSDTC for distributed transactions, which tiberius can't
+//! enlist in, and `SQLite`/Turso have no prepared-transaction state at all.
+//!
+//! SQL Server also gets no emulation with marker rows. A prepared vote has to survive the
+//! session and the coordinator: an open transaction parked on a pooled connection is rolled back
+//! when that connection drops, and a marker committed early makes the work visible before the
+//! decision. Either way a crash between the participants' commits would leave them disagreeing
+//! while `prepared_transactions` reported nothing in doubt.
+
+use super::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::error::SqlMiddlewareDbError;
+use crate::types::DatabaseType;
+
+#[cfg(feature = "postgres")]
+use crate::postgres;
+
+impl ConfigAndPool {
+    /// Commit the prepared transaction `gid`.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` on backends without prepared transactions,
+    /// or an error if checkout fails or no transaction was prepared under `gid`.
+    pub async fn commit_prepared(&self, gid: &str) -> Result<(), SqlMiddlewareDbError> {
+        match self.two_phase_connection().await? {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                postgres::commit_prepared(&client, gid).await
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = gid;
+                Err(unsupported(&self.db_type))
+            }
+        }
+    }
+
+    /// Roll back the prepared transaction `gid`.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` on backends without prepared transactions,
+    /// or an error if checkout fails or no transaction was prepared under `gid`.
+    pub async fn rollback_prepared(&self, gid: &str) -> Result<(), SqlMiddlewareDbError> {
+        match self.two_phase_connection().await? {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                postgres::rollback_prepared(&client, gid).await
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = gid;
+                Err(unsupported(&self.db_type))
+            }
+        }
+    }
+
+    /// Identifiers of transactions prepared in this database and not yet finished.
+    ///
+    /// After a coordinator crash, use this to find in-doubt transactions and resolve each with
+    /// [`ConfigAndPool::commit_prepared`] or [`ConfigAndPool::rollback_prepared`].
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` on backends without prepared transactions,
+    /// or an error if checkout or the catalog query fails.
+    pub async fn prepared_transactions(&self) -> Result<Vec<String>, SqlMiddlewareDbError> {
+        match self.two_phase_connection().await? {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                postgres::prepared_transactions(&client).await
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(unsupported(&self.db_type)),
+        }
+    }
+
+    /// Check out a connection, failing before checkout on backends that can't finish prepared
+    /// transactions.
+    async fn two_phase_connection(&self) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+        if !self.db_type.supports_prepared_transactions() {
+            return Err(unsupported(&self.db_type));
+        }
+        self.get_connection().await
+    }
+}
+
+fn unsupported(db_type: &DatabaseType) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::Unimplemented(format!(
+        "prepared (two-phase) transactions are not supported for {db_type:?}"
+    ))
+}
@@ -9,12 +9,14 @@ use crate::sqlite::config::SqliteManager;
 use bb8::Pool as Bb8SqlitePool;
 
 #[cfg(feature = "mssql")]
-use bb8::Pool as Bb8MssqlPool;
+use crate::mssql::config::MssqlManager;
 #[cfg(feature = "mssql")]
-use bb8_tiberius::ConnectionManager;
+use bb8::Pool as Bb8MssqlPool;
 
 #[cfg(feature = "turso")]
-use turso::Database as TursoDatabase;
+use crate::turso::TursoManager;
+#[cfg(feature = "turso")]
+use bb8::Pool as Bb8TursoPool;
 
 use crate::error::SqlMiddlewareDbError;
 
@@ -32,13 +34,14 @@ pub enum MiddlewarePool {
     Sqlite(Bb8SqlitePool<SqliteManager>),
     /// SQL Server connection pool
     #[cfg(feature = "mssql")]
-    Mssql(Bb8MssqlPool<ConnectionManager>),
-    /// `Turso` pseudo-pool (Database handle)
+    Mssql(Bb8MssqlPool<MssqlManager>),
+    /// `Turso` connection pool
     #[cfg(feature = "turso")]
-    Turso(TursoDatabase),
+    Turso(Bb8TursoPool<TursoManager>),
 }
 
 // Manual Debug implementation because not all pool types expose `Debug`
+#[cfg(any_backend)]
 impl std::fmt::Debug for MiddlewarePool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -49,11 +52,19 @@ impl std::fmt::Debug for MiddlewarePool {
             #[cfg(feature = "mssql")]
             Self::Mssql(_) => f.debug_tuple("Mssql").field(&"<TiberiusPool>").finish(),
             #[cfg(feature = "turso")]
-            Self::Turso(_) => f.debug_tuple("Turso").field(&"<Database>").finish(),
+            Self::Turso(pool) => f.debug_tuple("Turso").field(pool).finish(),
         }
     }
 }
 
+// Without a backend the enum has no variants, so there is nothing to format.
+#[cfg(not(any_backend))]
+impl std::fmt::Debug for MiddlewarePool {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {}
+    }
+}
+
 impl MiddlewarePool {
     /// Return a reference to self instead of cloning the entire pool
     ///
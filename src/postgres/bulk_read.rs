@@ -0,0 +1,101 @@
+//! COPY-based reads of large result sets.
+//!
+//! With [`QueryOptions::bulk_read`](crate::QueryOptions::bulk_read), a SELECT is sent as
+//! `COPY (query) TO STDOUT (FORMAT binary)` and its rows are decoded from the stream as they
+//! arrive. COPY skips the per-row protocol messages of a normal query, which makes reads of many
+//! rows several times faster. Values decode to the same `RowValues` as a normal SELECT.
+
+use std::pin::pin;
+use std::sync::Arc;
+
+use futures_util::TryStreamExt;
+use tokio_postgres::Client;
+use tokio_postgres::binary_copy::{BinaryCopyOutRow, BinaryCopyOutStream};
+use tokio_postgres::types::{FromSql, Type};
+
+use super::query::{PgRow, extract_value};
+use crate::middleware::{ResultSet, SqlMiddlewareDbError};
+use crate::query_utils::extract_column_names;
+
+/// Run `query` through binary COPY on `client` and collect its rows.
+///
+/// `query` must be a single SELECT without parameters or a trailing `;` (see
+/// [`split_statements`](crate::translation::split_statements)); COPY cannot bind parameters.
+///
+/// # Errors
+/// Returns an error if the query cannot be prepared, the COPY fails, or a value cannot be
+/// decoded.
+pub(crate) async fn bulk_read_on_client(
+    client: &Client,
+    query: &str,
+) -> Result<ResultSet, SqlMiddlewareDbError> {
+    // Preparing first gives the column names and the types needed to decode the stream.
+    let stmt = client.prepare(query).await.map_err(|e| {
+        SqlMiddlewareDbError::ExecutionError(format!("postgres prepare error: {e}"))
+    })?;
+    let column_names = extract_column_names(stmt.columns().iter(), |col| col.name());
+    let types: Vec<Type> = stmt
+        .columns()
+        .iter()
+        .map(|col| col.type_().clone())
+        .collect();
+
+    let copy = client
+        .copy_out(copy_statement(query).as_str())
+        .await
+        .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("postgres copy error: {e}")))?;
+    let mut rows = pin!(BinaryCopyOutStream::new(copy, &types));
+
+    let mut result_set = ResultSet::with_capacity(0);
+    result_set.set_column_names(Arc::new(column_names));
+    while let Some(row) = rows
+        .try_next()
+        .await
+        .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("postgres copy error: {e}")))?
+    {
+        let row = CopyRow {
+            row: &row,
+            types: &types,
+        };
+        let mut row_values = Vec::with_capacity(types.len());
+        for idx in 0..types.len() {
+            row_values.push(extract_value(&row, idx)?);
+        }
+        result_set.add_row_values(row_values);
+    }
+    Ok(result_set)
+}
+
+/// `query` wrapped in a binary COPY. The newline keeps `)` out of a trailing line comment.
+fn copy_statement(query: &str) -> String {
+    format!("COPY (\n{query}\n) TO STDOUT (FORMAT binary)")
+}
+
+/// A COPY row with the column types it was decoded with.
+struct CopyRow<'r> {
+    row: &'r BinaryCopyOutRow,
+    types: &'r [Type],
+}
+
+impl PgRow for CopyRow<'_> {
+    fn column_type(&self, idx: usize) -> &Type {
+        &self.types[idx]
+    }
+
+    fn try_get<'a, T: FromSql<'a>>(&'a self, idx: usize) -> Result<T, tokio_postgres::Error> {
+        self.row.try_get(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::copy_statement;
+
+    #[test]
+    fn wraps_query_in_copy() {
+        assert_eq!(
+            copy_statement("SELECT * FROM t -- all rows"),
+            "COPY (\nSELECT * FROM t -- all rows\n) TO STDOUT (FORMAT binary)"
+        );
+    }
+}
@@ -1,14 +1,31 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio_postgres::config::TargetSessionAttrs;
+
+use super::notice::NoticeHandler;
 use super::typed::PgManager;
+use crate::config::{EnvReader, process_env};
+use crate::credentials::{CredentialsProvider, TokenProvider};
 use crate::middleware::{ConfigAndPool, DatabaseType, MiddlewarePool, SqlMiddlewareDbError};
 
+/// Default cap on pooled Postgres connections, matching bb8's default.
+pub const DEFAULT_POSTGRES_POOL_SIZE: u32 = 10;
+
 /// Minimal Postgres configuration (keeps the public API backward-compatible
 /// with the old `deadpool_postgres::Config` usage).
-#[derive(Clone, Debug, Default)]
+///
+/// Serializes with the password redacted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PgConfig {
     pub dbname: Option<String>,
     pub host: Option<String>,
     pub port: Option<u16>,
     pub user: Option<String>,
+    #[serde(serialize_with = "crate::config::redact_opt")]
     pub password: Option<String>,
 }
 
@@ -41,10 +58,42 @@ impl PgConfig {
 }
 
 /// Options for configuring a Postgres pool.
-#[derive(Clone)]
+///
+/// In config files `connect_timeout` is given in milliseconds as `connect_timeout_ms`; the
+/// notice handler cannot be set from a file.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PostgresOptions {
+    #[serde(default)]
     pub config: PgConfig,
+    #[serde(default)]
     pub translate_placeholders: bool,
+    /// Receives server notices; when `None` they are logged via `tracing`.
+    #[serde(skip)]
+    pub notice_handler: Option<NoticeHandler>,
+    /// Further `(host, port)` pairs tried in order when `config.host` cannot be reached.
+    #[serde(default)]
+    pub failover_hosts: Vec<(String, u16)>,
+    /// Only use a server that accepts writes (`target_session_attrs=read-write`), skipping
+    /// standbys; pooled connections to a demoted primary are discarded on checkout.
+    #[serde(default)]
+    pub read_write: bool,
+    /// Per-host connect timeout, so an unreachable host fails over promptly.
+    #[serde(
+        default,
+        rename = "connect_timeout_ms",
+        with = "crate::config::opt_millis"
+    )]
+    pub connect_timeout: Option<Duration>,
+    /// Most connections the pool opens at once.
+    #[serde(default = "default_max_size")]
+    pub max_size: u32,
+    /// Supplies the user and password for each new connection, overriding `config`.
+    #[serde(skip)]
+    pub credentials: Option<Arc<dyn CredentialsProvider>>,
+}
+
+fn default_max_size() -> u32 {
+    DEFAULT_POSTGRES_POOL_SIZE
 }
 
 impl PostgresOptions {
@@ -53,7 +102,83 @@ impl PostgresOptions {
         Self {
             config,
             translate_placeholders: false,
+            notice_handler: None,
+            failover_hosts: Vec::new(),
+            read_write: false,
+            connect_timeout: None,
+            max_size: DEFAULT_POSTGRES_POOL_SIZE,
+            credentials: None,
+        }
+    }
+
+    /// Read options from environment variables named `{prefix}` followed by:
+    ///
+    /// - `HOST`, `PORT`, `DBNAME`, `USER`, `PASSWORD` (required)
+    /// - `POOL_SIZE`, `CONNECT_TIMEOUT_MS`, `READ_WRITE`, `TRANSLATE_PLACEHOLDERS` (optional)
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo() -> Result<(), SqlMiddlewareDbError> {
+    /// // Reads APP_PG_HOST, APP_PG_PORT, ...
+    /// let cap = ConfigAndPool::new_postgres(PostgresOptions::from_env("APP_PG_")?).await?;
+    /// # let _ = cap;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env(prefix: &str) -> Result<Self, SqlMiddlewareDbError> {
+        Self::from_env_with(prefix, process_env)
+    }
+
+    /// Like [`from_env`](Self::from_env), looking variables up with `lookup` instead of in the
+    /// process environment.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env_with(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let mut env = EnvReader::new(prefix, lookup);
+        let config = PgConfig {
+            host: Some(env.required("HOST")),
+            port: env.required_parsed("PORT"),
+            dbname: Some(env.required("DBNAME")),
+            user: Some(env.required("USER")),
+            password: Some(env.required("PASSWORD")),
+        };
+        let mut opts = Self::new(config);
+        if let Some(max_size) = env.parsed("POOL_SIZE") {
+            opts.max_size = max_size;
+        }
+        opts.connect_timeout = env.parsed("CONNECT_TIMEOUT_MS").map(Duration::from_millis);
+        opts.read_write = env.flag("READ_WRITE").unwrap_or(false);
+        opts.translate_placeholders = env.flag("TRANSLATE_PLACEHOLDERS").unwrap_or(false);
+        env.finish()?;
+        Ok(opts)
+    }
+
+    /// The `tokio_postgres` config for these options, including failover hosts.
+    ///
+    /// Hosts are tried in order: `config.host` first, then each failover host.
+    #[must_use]
+    pub fn to_tokio_config(&self) -> tokio_postgres::Config {
+        let mut cfg = self.config.to_tokio_config();
+        for (host, port) in &self.failover_hosts {
+            cfg.host(host);
+            cfg.port(*port);
         }
+        if self.read_write {
+            cfg.target_session_attrs(TargetSessionAttrs::ReadWrite);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            cfg.connect_timeout(timeout);
+        }
+        cfg
     }
 
     #[must_use]
@@ -61,6 +186,44 @@ impl PostgresOptions {
         self.translate_placeholders = translate_placeholders;
         self
     }
+
+    #[must_use]
+    pub fn with_notice_handler(mut self, handler: NoticeHandler) -> Self {
+        self.notice_handler = Some(handler);
+        self
+    }
+
+    #[must_use]
+    pub fn with_failover_host(mut self, host: impl Into<String>, port: u16) -> Self {
+        self.failover_hosts.push((host.into(), port));
+        self
+    }
+
+    #[must_use]
+    pub fn with_read_write(mut self, read_write: bool) -> Self {
+        self.read_write = read_write;
+        self
+    }
+
+    #[must_use]
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Cap the pool at `max_size` connections; checkouts beyond it wait for one to be returned.
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Fetch the user and password from `provider` for each new connection.
+    #[must_use]
+    pub fn with_credentials(mut self, provider: Arc<dyn CredentialsProvider>) -> Self {
+        self.credentials = Some(provider);
+        self
+    }
 }
 
 /// Fluent builder for Postgres options.
@@ -83,6 +246,78 @@ impl PostgresOptionsBuilder {
         self
     }
 
+    /// Deliver server notices (`RAISE NOTICE`, warnings) to `handler` instead of logging them.
+    #[must_use]
+    pub fn notice_handler(mut self, handler: NoticeHandler) -> Self {
+        self.opts.notice_handler = Some(handler);
+        self
+    }
+
+    /// Try `host:port` when the hosts before it cannot be reached.
+    #[must_use]
+    pub fn failover_host(mut self, host: impl Into<String>, port: u16) -> Self {
+        self.opts.failover_hosts.push((host.into(), port));
+        self
+    }
+
+    /// Only connect to a server that accepts writes, skipping standbys.
+    #[must_use]
+    pub fn read_write(mut self, read_write: bool) -> Self {
+        self.opts.read_write = read_write;
+        self
+    }
+
+    /// Give up on each host after `timeout` and move on to the next one.
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.opts.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Cap the pool at `max_size` connections; checkouts beyond it wait for one to be returned.
+    #[must_use]
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.opts.max_size = max_size;
+        self
+    }
+
+    /// Fetch the user and password from `provider` for each new connection, so rotated secrets
+    /// are picked up without rebuilding the pool.
+    #[must_use]
+    pub fn credentials(mut self, provider: Arc<dyn CredentialsProvider>) -> Self {
+        self.opts.credentials = Some(provider);
+        self
+    }
+
+    /// Authenticate with short-lived tokens (e.g. RDS IAM auth tokens) instead of a password.
+    ///
+    /// `generate` is called for a fresh token whenever a connection is opened more than
+    /// `lifetime` after the last token was generated; the token is sent as the password for
+    /// `config.user`.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn rds_iam_token() -> Result<String, SqlMiddlewareDbError> { Ok(String::new()) }
+    /// # async fn demo(cfg: PgConfig) -> Result<(), SqlMiddlewareDbError> {
+    /// // RDS tokens are valid for 15 minutes; refresh a minute early.
+    /// let cap = ConfigAndPool::postgres_builder(cfg)
+    ///     .auth_token(Duration::from_secs(14 * 60), rds_iam_token)
+    ///     .build()
+    ///     .await?;
+    /// # let _ = cap;
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn auth_token<F, Fut>(self, lifetime: Duration, generate: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, SqlMiddlewareDbError>> + Send + 'static,
+    {
+        self.credentials(Arc::new(TokenProvider::new(lifetime, generate)))
+    }
+
     #[must_use]
     pub fn finish(self) -> PostgresOptions {
         self.opts
@@ -110,6 +345,7 @@ impl ConfigAndPool {
     /// Returns `SqlMiddlewareDbError::ConfigError` if required config fields are missing or `SqlMiddlewareDbError::ConnectionError` if pool creation fails.
     #[allow(clippy::unused_async)]
     pub async fn new_postgres(opts: PostgresOptions) -> Result<Self, SqlMiddlewareDbError> {
+        let tokio_config = opts.to_tokio_config();
         let pg_config = opts.config;
         let translate_placeholders = opts.translate_placeholders;
 
@@ -130,25 +366,36 @@ impl ConfigAndPool {
                 "port is required".to_string(),
             ));
         }
-        if pg_config.user.is_none() {
+        if pg_config.user.is_none() && opts.credentials.is_none() {
             return Err(SqlMiddlewareDbError::ConfigError(
                 "user is required".to_string(),
             ));
         }
-        if pg_config.password.is_none() {
+        if pg_config.password.is_none() && opts.credentials.is_none() {
             return Err(SqlMiddlewareDbError::ConfigError(
                 "password is required".to_string(),
             ));
         }
+        if opts.max_size == 0 {
+            return Err(SqlMiddlewareDbError::ConfigError(
+                "Postgres pool max_size must be at least 1".to_string(),
+            ));
+        }
 
         // Attempt to create connection pool
-        let manager = PgManager::new(pg_config.to_tokio_config());
-        let pg_pool = manager.build_pool().await?;
+        let mut manager = PgManager::new(tokio_config);
+        if let Some(handler) = opts.notice_handler {
+            manager = manager.with_notice_handler(handler);
+        }
+        if let Some(provider) = opts.credentials {
+            manager = manager.with_credentials(provider);
+        }
+        let pg_pool = manager.build_pool_with_max_size(opts.max_size).await?;
 
-        Ok(ConfigAndPool {
-            pool: MiddlewarePool::Postgres(pg_pool),
-            db_type: DatabaseType::Postgres,
+        Ok(ConfigAndPool::from_pool(
+            MiddlewarePool::Postgres(pg_pool),
+            DatabaseType::Postgres,
             translate_placeholders,
-        })
+        ))
     }
 }
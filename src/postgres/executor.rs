@@ -11,7 +11,7 @@ pub async fn execute_batch<C>(pg_client: &mut C, query: &str) -> Result<(), SqlM
 where
     C: DerefMut<Target = Client>,
 {
-    let tx: Tx<'_> = begin_transaction(pg_client).await?;
+    let tx: Tx<'_> = begin_transaction(pg_client).await?.untracked();
     tx.execute_batch(query).await?;
     tx.commit().await?;
 
@@ -30,13 +30,31 @@ pub async fn execute_select<C>(
 where
     C: DerefMut<Target = Client>,
 {
-    let tx: Tx<'_> = begin_transaction(pg_client).await?;
+    let tx: Tx<'_> = begin_transaction(pg_client).await?.untracked();
     let prepared = tx.prepare(query).await?;
     let result_set = tx.query_prepared(&prepared, params).await?;
     tx.commit().await?;
     Ok(result_set)
 }
 
+/// Execute a SELECT in its own transaction and return every result set it produces.
+///
+/// # Errors
+/// Returns errors from parameter conversion, transaction operations, query execution, or cursor fetches.
+pub async fn execute_select_multi<C>(
+    pg_client: &mut C,
+    query: &str,
+    params: &[RowValues],
+) -> Result<Vec<ResultSet>, SqlMiddlewareDbError>
+where
+    C: DerefMut<Target = Client>,
+{
+    let tx: Tx<'_> = begin_transaction(pg_client).await?.untracked();
+    let result_sets = tx.query_multi(query, params).await?;
+    tx.commit().await?;
+    Ok(result_sets)
+}
+
 /// Execute a DML query (INSERT, UPDATE, DELETE) with parameters
 ///
 /// # Errors
@@ -49,7 +67,7 @@ pub async fn execute_dml<C>(
 where
     C: DerefMut<Target = Client>,
 {
-    let tx: Tx<'_> = begin_transaction(pg_client).await?;
+    let tx: Tx<'_> = begin_transaction(pg_client).await?.untracked();
     let prepared = tx.prepare(query).await?;
     let rows = tx.execute_prepared(&prepared, params).await?;
     tx.commit().await?;
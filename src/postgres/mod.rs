@@ -1,26 +1,37 @@
 //! `PostgreSQL` backend glue.
 //!
 //! Submodules mirror the SQLite/Turso structure for consistency:
+//! - `backend`: [`BackendApi`](crate::backend::BackendApi) implementation
+//! - `bulk_read`: COPY-based reads of large result sets
 //! - `config`: connection configuration and pool setup
 //! - `params`: parameter conversion between middleware and `PostgreSQL` types
 //! - `query`: result extraction and building
 //! - `executor`: database operation execution
+//! - `notice`: server notices and warnings
+//! - `two_phase`: finishing prepared (two-phase) transactions
 
+pub mod backend;
+pub(crate) mod bulk_read;
 pub mod config;
 pub mod executor;
+pub mod notice;
 pub mod params;
 pub mod query;
 pub mod transaction;
+pub mod two_phase;
 pub mod typed;
 
 // Re-export the public API
+pub use backend::PostgresBackend;
 pub use config::{PgConfig, PostgresOptions, PostgresOptionsBuilder};
-pub use executor::{execute_batch, execute_dml, execute_select};
+pub use executor::{execute_batch, execute_dml, execute_select, execute_select_multi};
+pub use notice::{DbNotice, NoticeHandler};
 pub use params::Params;
 pub use query::{
     build_result_set, execute_dml_on_client, execute_query_on_client,
 };
 pub use transaction::{Prepared, Tx, begin_transaction};
+pub use two_phase::{commit_prepared, prepared_transactions, rollback_prepared};
 pub use typed::{
     Idle as TypedIdle, InTx as TypedInTx, PgConnection as TypedPgConnection, PgManager,
 };
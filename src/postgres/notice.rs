@@ -0,0 +1,63 @@
+//! Server notices (`RAISE NOTICE`, `RAISE WARNING`, deprecation warnings, ...).
+//!
+//! Postgres sends notices asynchronously on the connection rather than as part of a result, so
+//! they are delivered to a per-pool callback installed with
+//! [`PostgresOptionsBuilder::notice_handler`](super::PostgresOptionsBuilder::notice_handler) or
+//! [`PgManager::with_notice_handler`](super::PgManager::with_notice_handler). Without a handler,
+//! notices are emitted as `tracing` events (target `sql_middleware::pg_notice`).
+
+use std::fmt;
+use std::sync::Arc;
+
+use tokio_postgres::error::DbError;
+
+/// Callback invoked for every notice received on a pooled connection.
+///
+/// It runs on the task driving the connection, so it should be quick; hand work off to a channel
+/// if it needs to block.
+pub type NoticeHandler = Arc<dyn Fn(&DbNotice) + Send + Sync>;
+
+/// A notice or warning raised by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbNotice {
+    /// `NOTICE`, `WARNING`, `INFO`, `LOG`, or `DEBUG` (localized by the server).
+    pub severity: String,
+    /// SQLSTATE code, e.g. `00000` for a plain `RAISE NOTICE`.
+    pub code: String,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl From<&DbError> for DbNotice {
+    fn from(err: &DbError) -> Self {
+        Self {
+            severity: err.severity().to_string(),
+            code: err.code().code().to_string(),
+            message: err.message().to_string(),
+            detail: err.detail().map(str::to_string),
+            hint: err.hint().map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for DbNotice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Deliver a notice to `handler`, or log it when there is none.
+pub(crate) fn dispatch(handler: Option<&NoticeHandler>, notice: &DbError) {
+    let notice = DbNotice::from(notice);
+    match handler {
+        Some(handler) => handler(&notice),
+        None => tracing::info!(
+            target: "sql_middleware::pg_notice",
+            severity = %notice.severity,
+            code = %notice.code,
+            "{}",
+            notice.message
+        ),
+    }
+}
@@ -1,7 +1,7 @@
 use std::error::Error;
 
 use crate::middleware::{ConversionMode, ParamConverter, RowValues, SqlMiddlewareDbError};
-use tokio_postgres::types::{IsNull, ToSql, Type, to_sql_checked};
+use tokio_postgres::types::{IsNull, ToSql, Type, WrongType};
 use tokio_util::bytes;
 
 /// Container for Postgres parameters with lifetime tracking
@@ -53,6 +53,8 @@ impl<'a> ParamConverter<'a> for Params<'a> {
         params: &'a [RowValues],
         _mode: ConversionMode,
     ) -> Result<Self::Converted, SqlMiddlewareDbError> {
+        // Strict needs no extra checks: `ToSql` already rejects values that don't match the
+        // column type.
         // Simply delegate to your existing conversion:
         Self::convert(params)
     }
@@ -93,12 +95,18 @@ impl ToSql for RowValues {
                 )))),
             },
             RowValues::Float(f) => (*f).to_sql(ty, out),
-            RowValues::Text(s) => s.to_sql(ty, out),
+            RowValues::Text(s) => (&**s).to_sql(ty, out),
             RowValues::Bool(b) => (*b).to_sql(ty, out),
             RowValues::Timestamp(dt) => dt.to_sql(ty, out),
             RowValues::Null => Ok(IsNull::Yes),
+            #[cfg(feature = "json")]
             RowValues::JSON(jsval) => jsval.to_sql(ty, out),
-            RowValues::Blob(bytes) => bytes.to_sql(ty, out),
+            RowValues::Blob(bytes) => (&**bytes).to_sql(ty, out),
+            // Written as-is so enums and other user types accept their encoding.
+            RowValues::Custom(custom) => custom.encode().to_sql(ty, out),
+            // PostGIS reads (E)WKB as the binary form of geometry and geography.
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(wkb) => (&**wkb).to_sql(ty, out),
         }
     }
 
@@ -118,5 +126,21 @@ impl ToSql for RowValues {
         }
     }
 
-    to_sql_checked!();
+    fn to_sql_checked(
+        &self,
+        ty: &Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        #[cfg(feature = "geo")]
+        if matches!(self, RowValues::Geometry(_))
+            && crate::geo::is_postgres_geometry_type(ty.name())
+        {
+            return self.to_sql(ty, out);
+        }
+        // Custom values stand for types outside the list above, so only they skip the check.
+        if !matches!(self, RowValues::Custom(_)) && !<Self as ToSql>::accepts(ty) {
+            return Err(Box::new(WrongType::new::<Self>(ty.clone())));
+        }
+        self.to_sql(ty, out)
+    }
 }
@@ -1,11 +1,12 @@
 use crate::adapters::params::convert_params;
 use crate::adapters::result_set::{column_count, init_result_set};
+use crate::custom;
 use crate::middleware::{ResultSet, RowValues, SqlMiddlewareDbError};
 use crate::query_utils::extract_column_names;
 use crate::types::ConversionMode;
 use chrono::NaiveDateTime;
-use serde_json::Value;
-use tokio_postgres::{Client, Statement, Transaction, types::ToSql};
+use tokio_postgres::types::{FromSql, Type};
+use tokio_postgres::{Client, GenericClient, Statement, Transaction, types::ToSql};
 
 use super::params::Params as PgParams;
 
@@ -50,12 +51,44 @@ pub async fn build_result_set(
 pub fn postgres_extract_value(
     row: &tokio_postgres::Row,
     idx: usize,
+) -> Result<RowValues, SqlMiddlewareDbError> {
+    extract_value(row, idx)
+}
+
+/// A row whose columns can be read by index: a query row, or a row decoded from binary COPY.
+pub(crate) trait PgRow {
+    fn column_type(&self, idx: usize) -> &Type;
+
+    fn try_get<'a, T: FromSql<'a>>(&'a self, idx: usize) -> Result<T, tokio_postgres::Error>;
+}
+
+impl PgRow for tokio_postgres::Row {
+    fn column_type(&self, idx: usize) -> &Type {
+        self.columns()[idx].type_()
+    }
+
+    fn try_get<'a, T: FromSql<'a>>(&'a self, idx: usize) -> Result<T, tokio_postgres::Error> {
+        tokio_postgres::Row::try_get(self, idx)
+    }
+}
+
+pub(crate) fn extract_value<R: PgRow>(
+    row: &R,
+    idx: usize,
 ) -> Result<RowValues, SqlMiddlewareDbError> {
     // Determine the type of the column and extract accordingly
-    let type_info = row.columns()[idx].type_();
+    let type_info = row.column_type(idx);
 
     // Match on the type based on PostgreSQL type OIDs or names
     // For simplicity, we'll handle common types. You may need to expand this.
+    #[cfg(feature = "geo")]
+    if crate::geo::is_postgres_geometry_type(type_info.name()) {
+        let val: Option<RawValue<'_>> = row.try_get(idx)?;
+        return Ok(val.map_or(RowValues::Null, |raw| RowValues::Geometry(raw.0.into())));
+    }
+    if type_info.name() == "json" || type_info.name() == "jsonb" {
+        return json_value(row, idx, type_info);
+    }
     if type_info.name() == "int2" {
         let val: Option<i16> = row.try_get(idx)?;
         Ok(val.map_or(RowValues::Null, |v| RowValues::Int(i64::from(v))))
@@ -74,22 +107,25 @@ pub fn postgres_extract_value(
     } else if type_info.name() == "timestamp" || type_info.name() == "timestamptz" {
         let val: Option<NaiveDateTime> = row.try_get(idx)?;
         Ok(val.map_or(RowValues::Null, RowValues::Timestamp))
-    } else if type_info.name() == "json" || type_info.name() == "jsonb" {
-        let val: Option<Value> = row.try_get(idx)?;
-        Ok(val.map_or(RowValues::Null, RowValues::JSON))
     } else if type_info.name() == "bytea" {
-        let val: Option<Vec<u8>> = row.try_get(idx)?;
-        Ok(val.map_or(RowValues::Null, RowValues::Blob))
+        let val: Option<&[u8]> = row.try_get(idx)?;
+        Ok(val.map_or(RowValues::Null, |v| RowValues::Blob(v.into())))
     } else if type_info.name() == "text"
         || type_info.name() == "varchar"
         || type_info.name() == "char"
     {
-        let val: Option<String> = row.try_get(idx)?;
-        Ok(val.map_or(RowValues::Null, RowValues::Text))
+        let val: Option<&str> = row.try_get(idx)?;
+        Ok(val.map_or(RowValues::Null, |v| RowValues::Text(v.into())))
+    } else if let Some(decoder) = custom::decoder_for(type_info.name()) {
+        let val: Option<RawValue<'_>> = row.try_get(idx)?;
+        match val {
+            Some(raw) => Ok(RowValues::Custom(decoder(raw.0)?)),
+            None => Ok(RowValues::Null),
+        }
     } else {
         // For other types, attempt to get as string
-        let val: Option<String> = row.try_get(idx)?;
-        Ok(val.map_or(RowValues::Null, RowValues::Text))
+        let val: Option<&str> = row.try_get(idx)?;
+        Ok(val.map_or(RowValues::Null, |v| RowValues::Text(v.into())))
     }
 }
 
@@ -173,6 +209,111 @@ pub(crate) async fn execute_query_prepared_on_client(
     build_result_set_from_statement(&stmt, &rows)
 }
 
+/// Execute a SELECT and expand `refcursor` results into the result sets they point to.
+///
+/// When every column of the result is a `refcursor` (e.g., a function returning
+/// `SETOF refcursor`), each cursor is drained with `FETCH ALL` in row/column order and returned as
+/// its own result set. Any other result is returned as a single result set. Cursors only live until
+/// the end of the transaction, so `client` should be inside one.
+///
+/// # Errors
+/// Returns errors from parameter conversion, query execution, or cursor fetches.
+pub(crate) async fn query_multi_on<C: GenericClient + Sync>(
+    client: &C,
+    query: &str,
+    params: &[RowValues],
+) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+    let converted = convert_params::<PgParams>(params, ConversionMode::Query)?;
+    let rows = client
+        .query(query, converted.as_refs())
+        .await
+        .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("postgres select error: {e}")))?;
+
+    let all_cursors = rows.first().is_some_and(|row| {
+        row.columns()
+            .iter()
+            .all(|col| col.type_() == &Type::REFCURSOR)
+    });
+    if !all_cursors {
+        return Ok(vec![build_result_set_from_rows(&rows)?]);
+    }
+
+    let mut result_sets = Vec::new();
+    for row in &rows {
+        for idx in 0..row.len() {
+            let Some(CursorName(name)) = row.try_get(idx)? else {
+                continue;
+            };
+            let fetch = format!("FETCH ALL FROM \"{}\"", name.replace('"', "\"\""));
+            let cursor_rows = client.query(fetch.as_str(), &[]).await.map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("postgres cursor fetch error: {e}"))
+            })?;
+            result_sets.push(build_result_set_from_rows(&cursor_rows)?);
+        }
+    }
+    Ok(result_sets)
+}
+
+/// Undecoded bytes of a column whose type has a registered [`custom`] decoder.
+#[cfg(feature = "json")]
+fn json_value<R: PgRow>(
+    row: &R,
+    idx: usize,
+    _ty: &Type,
+) -> Result<RowValues, SqlMiddlewareDbError> {
+    let val: Option<serde_json::Value> = row.try_get(idx)?;
+    Ok(val.map_or(RowValues::Null, RowValues::JSON))
+}
+
+/// Without the `json` feature, JSON columns are read as their text.
+#[cfg(not(feature = "json"))]
+fn json_value<R: PgRow>(row: &R, idx: usize, ty: &Type) -> Result<RowValues, SqlMiddlewareDbError> {
+    let val: Option<RawValue<'_>> = row.try_get(idx)?;
+    let Some(RawValue(raw)) = val else {
+        return Ok(RowValues::Null);
+    };
+    // Binary `jsonb` starts with a one-byte format version.
+    let text = if *ty == Type::JSONB {
+        raw.get(1..).unwrap_or_default()
+    } else {
+        raw
+    };
+    let text = std::str::from_utf8(text)
+        .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("invalid JSON text: {e}")))?;
+    Ok(RowValues::Text(text.into()))
+}
+
+struct RawValue<'a>(&'a [u8]);
+
+impl<'a> FromSql<'a> for RawValue<'a> {
+    fn from_sql(
+        _ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(RawValue(raw))
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
+/// Name of a `refcursor` value (sent as text on the wire).
+struct CursorName(String);
+
+impl<'a> FromSql<'a> for CursorName {
+    fn from_sql(
+        _ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(CursorName(std::str::from_utf8(raw)?.to_string()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty == &Type::REFCURSOR
+    }
+}
+
 /// Execute a DML query on a client without managing transactions
 ///
 /// # Errors
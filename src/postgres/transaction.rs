@@ -1,37 +1,105 @@
+use std::collections::HashMap;
 use std::ops::DerefMut;
+use std::sync::{Arc, Mutex};
 
-use tokio_postgres::{Client, Statement, Transaction as PgTransaction};
+use tokio_postgres::{Client, Portal as PgPortal, Statement, Transaction as PgTransaction};
 
 use crate::adapters::params::convert_params;
+use crate::executor::QueryTarget;
 use crate::middleware::{ConversionMode, ResultSet, RowValues, SqlMiddlewareDbError};
+use crate::pool::checkout::PoolLink;
+use crate::tx_drop::DropGuard;
 use crate::tx_outcome::TxOutcome;
 
+use super::bulk_read::bulk_read_on_client;
 use super::{Params, build_result_set};
-use crate::postgres::query::{build_result_set_from_rows, convert_affected_rows};
+use crate::postgres::query::{build_result_set_from_rows, convert_affected_rows, query_multi_on};
+use crate::query_builder::QueryBuilder;
+use crate::query_utils::extract_column_names;
 
 /// Lightweight transaction wrapper for Postgres.
 pub struct Tx<'a> {
     tx: PgTransaction<'a>,
+    /// Statements from [`Tx::prepare_cached`], keyed by SQL text.
+    statements: Mutex<HashMap<String, Statement>>,
+    /// Pool the handle was begun from; none when begun on a raw client.
+    pool: PoolLink,
+    guard: DropGuard,
 }
 
 /// Prepared statement wrapper for Postgres.
+#[derive(Clone)]
 pub struct Prepared {
     stmt: Statement,
 }
 
+/// Open portal (server-side cursor) created by [`Tx::bind`]; read it with [`Tx::fetch`].
+///
+/// The portal lives until the transaction ends.
+pub struct Portal {
+    portal: PgPortal,
+    column_names: Arc<Vec<String>>,
+    exhausted: bool,
+}
+
+impl Portal {
+    /// Whether a fetch has already returned the last row.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
 /// Begin a new transaction on the provided Postgres connection.
 ///
+/// The handle cannot see the pool the client came from, so its statements are not rate-limited;
+/// [`MiddlewarePoolConnection::begin_postgres_transaction`](crate::pool::MiddlewarePoolConnection::begin_postgres_transaction)
+/// begins one that is.
+///
+/// Dropping the returned [`Tx`] unfinished rolls it back and reports this call site; see
+/// [`tx_drop`](crate::tx_drop).
+///
 /// # Errors
 /// Returns an error if creating the transaction fails.
-pub async fn begin_transaction<C>(conn: &mut C) -> Result<Tx<'_>, SqlMiddlewareDbError>
+#[track_caller]
+pub fn begin_transaction<C>(
+    conn: &mut C,
+) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>>
 where
     C: DerefMut<Target = Client>,
 {
-    let tx = conn.deref_mut().transaction().await?;
-    Ok(Tx { tx })
+    let guard = DropGuard::new("postgres");
+    async move {
+        let tx = conn.deref_mut().transaction().await?;
+        Ok(Tx {
+            tx,
+            statements: Mutex::new(HashMap::new()),
+            pool: PoolLink::default(),
+            guard: guard.arm(),
+        })
+    }
 }
 
 impl Tx<'_> {
+    /// Stop reporting this transaction if it is dropped, for crate helpers that rely on the drop
+    /// to roll back when they return early.
+    pub(crate) fn untracked(mut self) -> Self {
+        self.guard.disarm();
+        self
+    }
+
+    /// Link the handle to `pool`: take a token from its rate limit before each statement
+    /// and echo what its query builder runs.
+    pub(crate) fn linked(mut self, pool: PoolLink) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// The pool this handle was begun from, if any.
+    pub(crate) fn pool(&self) -> &PoolLink {
+        &self.pool
+    }
+
     /// Prepare a SQL statement tied to this transaction.
     ///
     /// # Errors
@@ -41,6 +109,89 @@ impl Tx<'_> {
         Ok(Prepared { stmt })
     }
 
+    /// Prepare `sql` once per transaction, reusing the statement on later calls with the same text.
+    ///
+    /// # Errors
+    /// Returns an error if the prepare call fails.
+    pub async fn prepare_cached(&self, sql: &str) -> Result<Prepared, SqlMiddlewareDbError> {
+        if let Some(stmt) = self.cached_statement(sql) {
+            return Ok(Prepared { stmt });
+        }
+        let stmt = self.tx.prepare(sql).await?;
+        self.statements().insert(sql.to_string(), stmt.clone());
+        Ok(Prepared { stmt })
+    }
+
+    /// Number of statements held by the [`Tx::prepare_cached`] cache.
+    #[must_use]
+    pub fn cached_statement_count(&self) -> usize {
+        self.statements().len()
+    }
+
+    fn cached_statement(&self, sql: &str) -> Option<Statement> {
+        self.statements().get(sql).cloned()
+    }
+
+    fn statements(&self) -> std::sync::MutexGuard<'_, HashMap<String, Statement>> {
+        match self.statements.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Bind parameters to a prepared SELECT and open a portal without fetching any rows.
+    ///
+    /// # Errors
+    /// Returns an error if parameter conversion or the bind fails.
+    pub async fn bind(
+        &self,
+        prepared: &Prepared,
+        params: &[RowValues],
+    ) -> Result<Portal, SqlMiddlewareDbError> {
+        let converted = convert_params::<Params>(params, ConversionMode::Query)?;
+        self.pool.throttle().await?;
+        let portal = self.tx.bind(&prepared.stmt, converted.as_refs()).await?;
+        let column_names = extract_column_names(prepared.stmt.columns().iter(), |col| col.name());
+        Ok(Portal {
+            portal,
+            column_names: Arc::new(column_names),
+            exhausted: false,
+        })
+    }
+
+    /// Fetch up to `max_rows` more rows from `portal`.
+    ///
+    /// Returns fewer than `max_rows` rows (possibly none) once the portal is exhausted; later
+    /// calls return an empty `ResultSet` without a round trip.
+    ///
+    /// # Errors
+    /// Returns an error if `max_rows` is zero or above `i32::MAX`, or the fetch fails.
+    pub async fn fetch(
+        &self,
+        portal: &mut Portal,
+        max_rows: usize,
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        let limit = i32::try_from(max_rows)
+            .ok()
+            .filter(|limit| *limit > 0)
+            .ok_or_else(|| {
+                SqlMiddlewareDbError::ParameterError(format!(
+                    "fetch size must be between 1 and {}, got {max_rows}",
+                    i32::MAX
+                ))
+            })?;
+        let mut result_set = if portal.exhausted {
+            ResultSet::with_capacity(0)
+        } else {
+            self.pool.throttle().await?;
+            let rows = self.tx.query_portal(&portal.portal, limit).await?;
+            portal.exhausted = rows.len() < max_rows;
+            build_result_set_from_rows(&rows)?
+        };
+        result_set.set_column_names(Arc::clone(&portal.column_names));
+        Ok(result_set)
+    }
+
     /// Execute a parameterized DML statement and return the affected row count.
     ///
     /// # Errors
@@ -51,7 +202,7 @@ impl Tx<'_> {
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
         let converted = convert_params::<Params>(params, ConversionMode::Execute)?;
-
+        self.pool.throttle().await?;
         let rows = self.tx.execute(&prepared.stmt, converted.as_refs()).await?;
 
         convert_affected_rows(rows, "Invalid rows affected count")
@@ -67,6 +218,7 @@ impl Tx<'_> {
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
         let converted = convert_params::<Params>(params, ConversionMode::Execute)?;
+        self.pool.throttle().await?;
         let rows = self.tx.execute(query, converted.as_refs()).await?;
         convert_affected_rows(rows, "Invalid rows affected count")
     }
@@ -81,9 +233,34 @@ impl Tx<'_> {
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
         let converted = convert_params::<Params>(params, ConversionMode::Query)?;
+        self.pool.throttle().await?;
         build_result_set(&prepared.stmt, converted.as_refs(), &self.tx).await
     }
 
+    /// Start a [`QueryBuilder`] that runs inside this transaction.
+    ///
+    /// Placeholders are not translated unless the builder asks for it with
+    /// [`translation`](QueryBuilder::translation).
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    /// use sql_middleware::postgres::begin_transaction;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let MiddlewarePoolConnection::Postgres { client, .. } = conn else { return Ok(()) };
+    /// let tx = begin_transaction(client).await?;
+    /// let rows = tx
+    ///     .query_builder("SELECT id FROM t WHERE id = $1")
+    ///     .params(&[RowValues::Int(1)])
+    ///     .select()
+    ///     .await?;
+    /// tx.commit().await?;
+    /// # let _ = rows; Ok(()) }
+    /// ```
+    pub fn query_builder<'q>(&self, sql: &'q str) -> QueryBuilder<'_, 'q> {
+        QueryBuilder::new_target(QueryTarget::from(self), sql)
+    }
+
     /// Execute a parameterized SELECT without preparing and return a `ResultSet`.
     ///
     /// # Errors
@@ -94,15 +271,39 @@ impl Tx<'_> {
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
         let converted = convert_params::<Params>(params, ConversionMode::Query)?;
+        self.pool.throttle().await?;
         let rows = self.tx.query(query, converted.as_refs()).await?;
         build_result_set_from_rows(&rows)
     }
 
+    /// Execute a SELECT and return every result set it produces.
+    ///
+    /// `refcursor` results are fetched inside this transaction; see
+    /// [`QueryBuilder::select_multi`](crate::query_builder::QueryBuilder::select_multi).
+    ///
+    /// # Errors
+    /// Returns an error if parameter conversion, execution, or a cursor fetch fails.
+    pub async fn query_multi(
+        &self,
+        query: &str,
+        params: &[RowValues],
+    ) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
+        query_multi_on(&self.tx, query, params).await
+    }
+
+    /// Read a parameterless SELECT through binary COPY inside this transaction.
+    pub(crate) async fn bulk_read(&self, query: &str) -> Result<ResultSet, SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
+        bulk_read_on_client(self.tx.client(), query).await
+    }
+
     /// Execute a batch of SQL statements inside the transaction.
     ///
     /// # Errors
     /// Returns an error if execution fails.
     pub async fn execute_batch(&self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        self.pool.throttle().await?;
         self.tx.batch_execute(sql).await?;
         Ok(())
     }
@@ -111,7 +312,8 @@ impl Tx<'_> {
     ///
     /// # Errors
     /// Returns an error if commit fails.
-    pub async fn commit(self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+    pub async fn commit(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
         self.tx.commit().await?;
         Ok(TxOutcome::without_restored_connection())
     }
@@ -120,8 +322,37 @@ impl Tx<'_> {
     ///
     /// # Errors
     /// Returns an error if rollback fails.
-    pub async fn rollback(self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+    pub async fn rollback(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
         self.tx.rollback().await?;
         Ok(TxOutcome::without_restored_connection())
     }
+
+    /// Prepare the transaction for two-phase commit under the global identifier `gid`
+    /// (`PREPARE TRANSACTION`).
+    ///
+    /// The work is made durable but stays invisible until it is finished, from any connection and
+    /// even after a server restart, with [`ConfigAndPool::commit_prepared`] or
+    /// [`ConfigAndPool::rollback_prepared`]. The session is free for other work as soon as this
+    /// returns. The server must run with `max_prepared_transactions` above zero.
+    ///
+    /// [`ConfigAndPool::commit_prepared`]: crate::ConfigAndPool::commit_prepared
+    /// [`ConfigAndPool::rollback_prepared`]: crate::ConfigAndPool::rollback_prepared
+    ///
+    /// # Errors
+    /// Returns an error if `gid` is invalid or already in use, or if preparing fails; the
+    /// transaction is rolled back in that case.
+    pub async fn prepare_commit(self, gid: &str) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        let sql = format!(
+            "PREPARE TRANSACTION {}",
+            super::two_phase::gid_literal(gid)?
+        );
+        let Tx { tx, mut guard, .. } = self;
+        guard.disarm();
+        tx.batch_execute(&sql).await?;
+        // PREPARE TRANSACTION ends the session's transaction; dropping the handle would send a
+        // stray ROLLBACK for it.
+        std::mem::forget(tx);
+        Ok(TxOutcome::without_restored_connection())
+    }
 }
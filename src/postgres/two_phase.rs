@@ -0,0 +1,58 @@
+//! Statements for finishing prepared (two-phase) transactions.
+//!
+//! A transaction is prepared with [`Tx::prepare_commit`](super::Tx::prepare_commit) and finished
+//! later, from any session, with `COMMIT PREPARED` or `ROLLBACK PREPARED`. Neither may run inside a
+//! transaction block, so these go straight to the client rather than through
+//! [`execute_batch`](super::execute_batch), which wraps its SQL in a transaction.
+
+use tokio_postgres::Client;
+
+use crate::middleware::SqlMiddlewareDbError;
+
+/// Postgres caps global transaction identifiers at 200 bytes.
+const MAX_GID_LEN: usize = 199;
+
+/// `gid` as a quoted string literal, for statements that don't accept bind parameters.
+pub(crate) fn gid_literal(gid: &str) -> Result<String, SqlMiddlewareDbError> {
+    if gid.is_empty() || gid.len() > MAX_GID_LEN || gid.contains('\0') {
+        return Err(SqlMiddlewareDbError::ParameterError(format!(
+            "transaction id must be 1 to {MAX_GID_LEN} bytes without NUL characters, got {} bytes",
+            gid.len()
+        )));
+    }
+    Ok(format!("'{}'", gid.replace('\'', "''")))
+}
+
+/// Commit the prepared transaction `gid`.
+///
+/// # Errors
+/// Returns an error if `gid` is invalid or names no prepared transaction.
+pub async fn commit_prepared(client: &Client, gid: &str) -> Result<(), SqlMiddlewareDbError> {
+    let sql = format!("COMMIT PREPARED {}", gid_literal(gid)?);
+    client.batch_execute(&sql).await?;
+    Ok(())
+}
+
+/// Roll back the prepared transaction `gid`.
+///
+/// # Errors
+/// Returns an error if `gid` is invalid or names no prepared transaction.
+pub async fn rollback_prepared(client: &Client, gid: &str) -> Result<(), SqlMiddlewareDbError> {
+    let sql = format!("ROLLBACK PREPARED {}", gid_literal(gid)?);
+    client.batch_execute(&sql).await?;
+    Ok(())
+}
+
+/// Identifiers of the transactions prepared in the current database, oldest first.
+///
+/// # Errors
+/// Returns an error if the catalog query fails.
+pub async fn prepared_transactions(client: &Client) -> Result<Vec<String>, SqlMiddlewareDbError> {
+    let rows = client
+        .query(
+            "SELECT gid FROM pg_prepared_xacts WHERE database = current_database() ORDER BY prepared",
+            &[],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
@@ -1,9 +1,13 @@
-use std::{future::Future, marker::PhantomData, sync::atomic::AtomicBool};
+use std::{future::Future, marker::PhantomData, sync::Arc, sync::atomic::AtomicBool};
 
 use bb8::{ManageConnection, Pool, PooledConnection};
-use tokio_postgres::{Client, NoTls};
+use tokio_postgres::config::TargetSessionAttrs;
+use tokio_postgres::{AsyncMessage, Client, NoTls};
 
+use crate::credentials::{CredentialSource, CredentialsProvider};
 use crate::middleware::SqlMiddlewareDbError;
+use crate::postgres::notice::{self, NoticeHandler};
+use crate::tx_drop::DropGuard;
 
 /// Marker types for typestate
 pub enum Idle {}
@@ -12,12 +16,32 @@ pub enum InTx {}
 /// bb8 manager for Postgres clients.
 pub struct PgManager {
     pub(crate) config: tokio_postgres::Config,
+    pub(crate) notice_handler: Option<NoticeHandler>,
+    pub(crate) credentials: Option<CredentialSource>,
 }
 
 impl PgManager {
     #[must_use]
     pub fn new(config: tokio_postgres::Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            notice_handler: None,
+            credentials: None,
+        }
+    }
+
+    /// Ask `provider` for the user and password each time a connection is opened.
+    #[must_use]
+    pub fn with_credentials(mut self, provider: Arc<dyn CredentialsProvider>) -> Self {
+        self.credentials = Some(CredentialSource::new(provider));
+        self
+    }
+
+    /// Deliver server notices (`RAISE NOTICE`, warnings) from every pooled connection to `handler`.
+    #[must_use]
+    pub fn with_notice_handler(mut self, handler: NoticeHandler) -> Self {
+        self.notice_handler = Some(handler);
+        self
     }
 
     /// Build a pool from this manager.
@@ -25,21 +49,50 @@ impl PgManager {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if pool creation fails.
     pub async fn build_pool(self) -> Result<Pool<PgManager>, SqlMiddlewareDbError> {
+        self.build_pool_with_max_size(crate::postgres::config::DEFAULT_POSTGRES_POOL_SIZE)
+            .await
+    }
+
+    /// Build a pool from this manager holding at most `max_size` connections.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if pool creation fails.
+    pub async fn build_pool_with_max_size(
+        self,
+        max_size: u32,
+    ) -> Result<Pool<PgManager>, SqlMiddlewareDbError> {
         Pool::builder()
+            .max_size(max_size)
             .build(self)
             .await
             .map_err(|e| SqlMiddlewareDbError::ConnectionError(format!("postgres pool error: {e}")))
     }
 }
 
+const READ_WRITE_CHECK: &str = "DO $$ BEGIN \
+    IF current_setting('transaction_read_only') = 'on' THEN \
+        RAISE EXCEPTION 'server is read-only'; \
+    END IF; \
+END $$";
+
 impl ManageConnection for PgManager {
     type Connection = Client;
     type Error = tokio_postgres::Error;
 
     #[allow(clippy::manual_async_fn)]
     fn connect(&self) -> impl Future<Output = Result<Self::Connection, Self::Error>> + Send {
-        let cfg = self.config.clone();
+        let mut cfg = self.config.clone();
+        let notice_handler = self.notice_handler.clone();
+        let credentials = self.credentials.clone();
         async move {
+            if let Some(source) = credentials
+                && let Some(creds) = source.current().await
+            {
+                if let Some(user) = &creds.user {
+                    cfg.user(user);
+                }
+                cfg.password(&creds.password);
+            }
             let debug = std::env::var_os("SQL_MIDDLEWARE_PG_DEBUG").is_some();
             if debug {
                 eprintln!(
@@ -49,13 +102,19 @@ impl ManageConnection for PgManager {
                     cfg.get_user()
                 );
             }
-            let (client, connection) = cfg.connect(NoTls).await?;
+            let (client, mut connection) = cfg.connect(NoTls).await?;
             if debug {
                 eprintln!("[sql-mw][pg] connect established");
             }
             tokio::spawn(async move {
-                if let Err(_e) = connection.await {
-                    // drop error
+                // Drive the connection ourselves so notices reach the handler instead of being
+                // dropped; errors end the connection, which the client observes on its next call.
+                while let Some(Ok(message)) =
+                    std::future::poll_fn(|cx| connection.poll_message(cx)).await
+                {
+                    if let AsyncMessage::Notice(db_notice) = message {
+                        notice::dispatch(notice_handler.as_ref(), &db_notice);
+                    }
                 }
             });
             Ok(client)
@@ -67,11 +126,18 @@ impl ManageConnection for PgManager {
         &self,
         conn: &mut Self::Connection,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
-        async move { conn.simple_query("SELECT 1").await.map(|_| ()) }
+        // A primary demoted to standby keeps accepting connections; fail validation so the pool
+        // reconnects and `target_session_attrs` finds the new primary.
+        let sql = if self.config.get_target_session_attrs() == TargetSessionAttrs::ReadWrite {
+            READ_WRITE_CHECK
+        } else {
+            "SELECT 1"
+        };
+        async move { conn.simple_query(sql).await.map(|_| ()) }
     }
 
-    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_closed()
     }
 }
 
@@ -81,6 +147,8 @@ pub struct PgConnection<State> {
     /// True when a transaction is in-flight and needs rollback if dropped.
     pub(crate) needs_rollback: bool,
     pub(crate) _state: PhantomData<State>,
+    /// Reports a transaction dropped unfinished; declared last so it runs after the rollback.
+    pub(crate) tx_guard: DropGuard,
 }
 
 impl PgConnection<Idle> {
@@ -89,9 +157,14 @@ impl PgConnection<Idle> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if acquiring the connection fails.
     pub async fn from_pool(pool: &Pool<PgManager>) -> Result<Self, SqlMiddlewareDbError> {
-        let conn = pool.get_owned().await.map_err(|e| {
-            SqlMiddlewareDbError::ConnectionError(format!("postgres checkout error: {e}"))
-        })?;
+        let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+            SqlMiddlewareDbError::PoolErrorPostgres {
+                source: Box::new(source),
+                waited,
+                state,
+            }
+        })
+        .await?;
         Ok(Self::new(conn, false))
     }
 }
@@ -102,6 +175,7 @@ impl<State> PgConnection<State> {
             conn: Some(conn),
             needs_rollback,
             _state: PhantomData,
+            tx_guard: DropGuard::untracked(),
         }
     }
 
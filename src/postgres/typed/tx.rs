@@ -3,18 +3,29 @@ use tokio::runtime::Handle;
 use crate::middleware::SqlMiddlewareDbError;
 
 use super::core::{Idle, InTx, PgConnection, SKIP_DROP_ROLLBACK};
+use crate::tx_drop::DropGuard;
 
 impl PgConnection<Idle> {
     /// Begin an explicit transaction.
     ///
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if starting the transaction fails.
-    pub async fn begin(mut self) -> Result<PgConnection<InTx>, SqlMiddlewareDbError> {
+    #[track_caller]
+    pub fn begin(self) -> impl Future<Output = Result<PgConnection<InTx>, SqlMiddlewareDbError>> {
+        self.begin_guarded(DropGuard::new("postgres"))
+    }
+
+    pub(crate) async fn begin_guarded(
+        mut self,
+        tx_guard: DropGuard,
+    ) -> Result<PgConnection<InTx>, SqlMiddlewareDbError> {
         let conn = self.take_conn()?;
         conn.simple_query("BEGIN").await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("postgres begin error: {e}"))
         })?;
-        Ok(PgConnection::new(conn, true))
+        let mut tx = PgConnection::new(conn, true);
+        tx.tx_guard = tx_guard.arm();
+        Ok(tx)
     }
 }
 
@@ -40,6 +51,7 @@ impl PgConnection<InTx> {
         sql: &str,
         action: &str,
     ) -> Result<PgConnection<Idle>, SqlMiddlewareDbError> {
+        self.tx_guard.disarm();
         let conn = self.take_conn()?;
         match conn.simple_query(sql).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("postgres {action} error: {e}"))
@@ -4,20 +4,29 @@
 //! to make it easier to get started with the library.
 
 pub use crate::middleware::{
-    AnyConnWrapper, BatchTarget, ConfigAndPool, ConversionMode, CustomDbRow, DatabaseType,
-    MiddlewarePool, MiddlewarePoolConnection, QueryAndParams, QueryBuilder, QueryTarget, ResultSet,
-    RowValues, SqlMiddlewareDbError, TxOutcome, execute_batch, query,
+    AnyConnWrapper, BatchOptions, BatchTxMode, ConfigAndPool, ConversionMode, CustomDbRow,
+    DatabaseType, InsertBuilder, MiddlewarePool, MiddlewarePoolConnection, QueryAndParams,
+    ResultSet, ResultSetBuilder, RowValues, SqlMiddlewareDbError, TxOutcome,
+};
+#[cfg(any_backend)]
+pub use crate::middleware::{
+    BatchTarget, QueryBuilder, QueryOutcome, QueryTarget, execute_batch, query,
 };
 
+pub use crate::config::BackendConfig;
 pub use crate::conversion::convert_sql_params;
+pub use crate::custom::CustomValue;
 #[cfg(feature = "mssql")]
 pub use crate::mssql::{MssqlOptions, MssqlOptionsBuilder};
+pub use crate::params::{IntoParams, IntoRowValues};
 #[cfg(feature = "postgres")]
 pub use crate::postgres::{PgConfig, PostgresOptions, PostgresOptionsBuilder};
+pub use crate::results::FromRowValues;
 #[cfg(feature = "sqlite")]
 pub use crate::sqlite::{SqliteOptions, SqliteOptionsBuilder};
 pub use crate::translation::{
-    PlaceholderStyle, PrepareMode, QueryOptions, TranslationMode, translate_placeholders,
+    PlaceholderStyle, PrepareMode, QueryOptions, TranslationMode, has_order_by,
+    translate_placeholders,
 };
 #[cfg(feature = "turso")]
 pub use crate::turso::{TursoOptions, TursoOptionsBuilder};
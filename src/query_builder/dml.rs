@@ -1,7 +1,11 @@
+use std::time::Instant;
+
+use crate::audit::{self, Entry};
 use crate::error::SqlMiddlewareDbError;
 use crate::executor::{
     QueryTarget, QueryTargetKind, execute_dml_dispatch, execute_dml_prepared_dispatch,
 };
+use crate::metrics;
 use crate::pool::MiddlewarePoolConnection;
 use crate::translation::PrepareMode;
 use crate::types::RowValues;
@@ -10,6 +14,8 @@ use crate::types::RowValues;
 use crate::postgres::typed::PgManager;
 #[cfg(feature = "sqlite")]
 use crate::sqlite::config::SqliteManager;
+#[cfg(feature = "sqlite")]
+use crate::sqlite::transaction::dml_in_tx;
 #[cfg(feature = "turso")]
 use crate::typed_turso::TursoManager;
 #[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
@@ -23,79 +29,187 @@ impl QueryBuilder<'_, '_> {
     /// # Errors
     /// Returns an error if placeholder translation fails or the backend DML execution fails.
     pub async fn dml(self) -> Result<usize, SqlMiddlewareDbError> {
-        let translated = translate_query_for_target(
-            &self.target,
-            self.sql.as_ref(),
-            self.params.as_ref(),
-            self.options,
+        let Self {
+            mut target,
+            sql,
+            params,
+            options,
+            error,
+        } = self;
+        if let Some(err) = error {
+            return Err(err);
+        }
+        if options.strict {
+            target.database_type().check_strict_params(&params)?;
+        }
+        let translated =
+            translate_query_for_target(&target, sql.as_ref(), params.as_ref(), &options)?;
+        let use_prepare = matches!(options.prepare, PrepareMode::Prepared);
+
+        let audit = Entry::for_statement(
+            &target.database_type(),
+            sql.as_ref(),
+            options.actor.as_ref(),
+            options.audit.as_ref().or(target.audit_tables()),
         );
-        let use_prepare = matches!(self.options.prepare, PrepareMode::Prepared);
+        let audit = audit.as_ref();
+        let _bulkhead = target.enter_bulkhead(options.bulkhead).await?;
 
-        match self.target {
-            QueryTarget {
-                kind: QueryTargetKind::Connection(conn),
-                ..
-            } => {
-                dml_on_connection(conn, translated.as_ref(), self.params.as_ref(), use_prepare)
-                    .await
-            }
-            #[cfg(feature = "sqlite")]
-            QueryTarget {
-                kind: QueryTargetKind::TypedSqlite { conn }
-                    | QueryTargetKind::TypedSqliteTx { conn },
-                ..
-            } => dml_typed_sqlite(conn, translated.as_ref(), self.params.as_ref()).await,
-            #[cfg(feature = "postgres")]
-            QueryTarget {
-                kind: QueryTargetKind::TypedPostgres { conn }
-                    | QueryTargetKind::TypedPostgresTx { conn },
-                ..
-            } => dml_typed_postgres(conn, translated.as_ref(), self.params.as_ref(), use_prepare)
-                .await,
-            #[cfg(feature = "turso")]
-            QueryTarget {
-                kind: QueryTargetKind::TypedTurso { conn }
-                    | QueryTargetKind::TypedTursoTx { conn },
-                ..
-            } => dml_typed_turso(conn, translated.as_ref(), self.params.as_ref()).await,
-            #[cfg(feature = "postgres")]
-            QueryTarget {
-                kind: QueryTargetKind::PostgresTx(tx),
-                ..
-            } => {
-                if use_prepare {
-                    let prepared = tx.prepare(translated.as_ref()).await?;
-                    tx.execute_prepared(&prepared, self.params.as_ref()).await
-                } else {
-                    tx.execute_dml(translated.as_ref(), self.params.as_ref()).await
+        let started = Instant::now();
+        let result = async {
+            match target {
+                QueryTarget {
+                    kind: QueryTargetKind::Connection(conn),
+                    ..
+                } => match audit {
+                    Some(audit) => {
+                        audit::dml_on_connection(conn, translated.as_ref(), params.as_ref(), audit)
+                            .await
+                    }
+                    None => {
+                        dml_on_connection(conn, translated.as_ref(), params.as_ref(), use_prepare)
+                            .await
+                    }
+                },
+                #[cfg(feature = "sqlite")]
+                QueryTarget {
+                    kind: QueryTargetKind::TypedSqlite { conn },
+                    ..
+                } => match audit {
+                    Some(audit) => {
+                        audit::dml_on_typed_sqlite(
+                            conn,
+                            translated.as_ref(),
+                            params.as_ref(),
+                            audit,
+                        )
+                        .await
+                    }
+                    None => dml_typed_sqlite(conn, translated.as_ref(), params.as_ref()).await,
+                },
+                #[cfg(feature = "sqlite")]
+                QueryTarget {
+                    kind: QueryTargetKind::TypedSqliteTx { conn },
+                    ..
+                } => {
+                    let rows = dml_typed_sqlite(conn, translated.as_ref(), params.as_ref()).await?;
+                    if let Some(audit) = audit {
+                        dml_typed_sqlite(conn, audit.sql(), &audit.params(rows)?).await?;
+                    }
+                    Ok(rows)
                 }
-            }
-            #[cfg(feature = "mssql")]
-            QueryTarget {
-                kind: QueryTargetKind::MssqlTx(tx),
-                ..
-            } => {
-                if use_prepare {
-                    let prepared = tx.prepare(translated.as_ref())?;
-                    tx.execute_prepared(&prepared, self.params.as_ref()).await
-                } else {
-                    tx.execute_dml(translated.as_ref(), self.params.as_ref()).await
+                #[cfg(feature = "postgres")]
+                QueryTarget {
+                    kind: QueryTargetKind::TypedPostgres { conn },
+                    ..
+                } => match audit {
+                    Some(audit) => {
+                        audit::dml_on_postgres(conn, translated.as_ref(), params.as_ref(), audit)
+                            .await
+                    }
+                    None => {
+                        dml_typed_postgres(conn, translated.as_ref(), params.as_ref(), use_prepare)
+                            .await
+                    }
+                },
+                #[cfg(feature = "postgres")]
+                QueryTarget {
+                    kind: QueryTargetKind::TypedPostgresTx { conn },
+                    ..
+                } => {
+                    let rows =
+                        dml_typed_postgres(conn, translated.as_ref(), params.as_ref(), use_prepare)
+                            .await?;
+                    if let Some(audit) = audit {
+                        dml_typed_postgres(conn, audit.sql(), &audit.params(rows)?, false).await?;
+                    }
+                    Ok(rows)
                 }
-            }
-            #[cfg(feature = "turso")]
-            QueryTarget {
-                kind: QueryTargetKind::TursoTx(tx),
-                ..
-            } => {
-                if use_prepare {
-                    let mut prepared = tx.prepare(translated.as_ref()).await?;
-                    tx.execute_prepared(&mut prepared, self.params.as_ref())
-                        .await
-                } else {
-                    tx.execute_dml(translated.as_ref(), self.params.as_ref()).await
+                #[cfg(feature = "turso")]
+                QueryTarget {
+                    kind: QueryTargetKind::TypedTurso { conn },
+                    ..
+                } => match audit {
+                    Some(audit) => {
+                        audit::dml_on_turso(conn, translated.as_ref(), params.as_ref(), audit).await
+                    }
+                    None => dml_typed_turso(conn, translated.as_ref(), params.as_ref()).await,
+                },
+                #[cfg(feature = "turso")]
+                QueryTarget {
+                    kind: QueryTargetKind::TypedTursoTx { conn },
+                    ..
+                } => {
+                    let rows = dml_typed_turso(conn, translated.as_ref(), params.as_ref()).await?;
+                    if let Some(audit) = audit {
+                        dml_typed_turso(conn, audit.sql(), &audit.params(rows)?).await?;
+                    }
+                    Ok(rows)
+                }
+                #[cfg(feature = "postgres")]
+                QueryTarget {
+                    kind: QueryTargetKind::PostgresTx(tx),
+                    ..
+                } => {
+                    let rows = if use_prepare {
+                        let prepared = tx.prepare(translated.as_ref()).await?;
+                        tx.execute_prepared(&prepared, params.as_ref()).await?
+                    } else {
+                        tx.execute_dml(translated.as_ref(), params.as_ref()).await?
+                    };
+                    if let Some(audit) = audit {
+                        tx.execute_dml(audit.sql(), &audit.params(rows)?).await?;
+                    }
+                    Ok(rows)
+                }
+                #[cfg(feature = "mssql")]
+                QueryTarget {
+                    kind: QueryTargetKind::MssqlTx(mut tx),
+                    ..
+                } => {
+                    let rows = if use_prepare {
+                        let prepared = tx.prepare(translated.as_ref())?;
+                        tx.execute_prepared(&prepared, params.as_ref()).await?
+                    } else {
+                        tx.execute_dml(translated.as_ref(), params.as_ref()).await?
+                    };
+                    if let Some(audit) = audit {
+                        tx.execute_dml(audit.sql(), &audit.params(rows)?).await?;
+                    }
+                    Ok(rows)
+                }
+                #[cfg(feature = "turso")]
+                QueryTarget {
+                    kind: QueryTargetKind::TursoTx(tx),
+                    ..
+                } => {
+                    let rows = if use_prepare {
+                        let mut prepared = tx.prepare(translated.as_ref()).await?;
+                        tx.execute_prepared(&mut prepared, params.as_ref()).await?
+                    } else {
+                        tx.execute_dml(translated.as_ref(), params.as_ref()).await?
+                    };
+                    if let Some(audit) = audit {
+                        tx.execute_dml(audit.sql(), &audit.params(rows)?).await?;
+                    }
+                    Ok(rows)
+                }
+                #[cfg(feature = "sqlite")]
+                QueryTarget {
+                    kind: QueryTargetKind::SqliteTx(conn),
+                    ..
+                } => {
+                    let rows = dml_in_tx(conn, translated.as_ref(), params.as_ref()).await?;
+                    if let Some(audit) = audit {
+                        dml_in_tx(conn, audit.sql(), &audit.params(rows)?).await?;
+                    }
+                    Ok(rows)
                 }
             }
         }
+        .await;
+        metrics::record(&sql, started.elapsed(), result.as_ref().ok().copied());
+        result
     }
 }
 
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use crate::executor::QueryTarget;
+use crate::metrics;
+use crate::results::ResultSet;
+use crate::translation::{PlaceholderStyle, is_select};
+use crate::types::RowValues;
+
+use super::select::select_on_target;
+
+/// Record a slow SELECT and, when the rate limit allows, its plan from the same target.
+///
+/// Inside a transaction the query is recorded without a plan: an EXPLAIN there would run in the
+/// caller's transaction, and a failed one aborts a Postgres transaction.
+pub(super) async fn explain_if_slow(
+    target: &mut QueryTarget<'_>,
+    sql: &str,
+    translated: &str,
+    params: &[RowValues],
+    elapsed: Duration,
+) {
+    if !metrics::slow_capture_enabled() || !is_select(sql) {
+        return;
+    }
+    let prefix = match target.translation_target() {
+        Some(PlaceholderStyle::Postgres) => "EXPLAIN",
+        Some(PlaceholderStyle::Sqlite) => "EXPLAIN QUERY PLAN",
+        Some(PlaceholderStyle::Mssql) | None => "",
+    };
+    let explainable = !prefix.is_empty() && !target.in_transaction();
+    let Some(may_explain) = metrics::check_slow(elapsed, explainable) else {
+        return;
+    };
+    let plan = if may_explain {
+        let explain = format!("{prefix} {translated}");
+        match select_on_target(target, &explain, params, false, false).await {
+            Ok(plan) => Some(render_plan(&plan)),
+            Err(err) => {
+                tracing::debug!(target: "sql_middleware::slow_query", %err, "explain failed");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    metrics::record_slow(sql, elapsed, plan);
+}
+
+/// One line per plan row: `detail` (`SQLite`) or `QUERY PLAN` (Postgres), else every column.
+pub(crate) fn render_plan(plan: &ResultSet) -> String {
+    plan.results
+        .iter()
+        .map(|row| {
+            match row
+                .get("detail")
+                .or_else(|| row.get("QUERY PLAN"))
+                .and_then(RowValues::as_text)
+            {
+                Some(line) => line.to_string(),
+                None => row
+                    .rows
+                    .iter()
+                    .map(|value| match value {
+                        RowValues::Text(text) => text.to_string(),
+                        other => format!("{other:?}"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,21 +1,52 @@
+#[cfg(any_backend)]
 use std::borrow::Cow;
 
+#[cfg(any_backend)]
+use crate::error::SqlMiddlewareDbError;
+#[cfg(any_backend)]
 use crate::executor::QueryTarget;
-use crate::pool::MiddlewarePoolConnection;
-use crate::translation::{PrepareMode, QueryOptions, TranslationMode, translate_placeholders};
+#[cfg(any_backend)]
+use crate::params::IntoParams;
+#[cfg(any_backend)]
+use crate::pool::{MiddlewarePoolConnection, echo};
+#[cfg(any_backend)]
+use crate::translation::{
+    PlaceholderStyle, PrepareMode, QueryOptions, TranslationMode, try_translate_parameterized,
+};
+#[cfg(any_backend)]
 use crate::types::RowValues;
 
+#[cfg(any_backend)]
 mod dml;
+#[cfg(any_backend)]
+pub(crate) mod explain;
+#[cfg(any_backend)]
+mod run;
+#[cfg(any_backend)]
 mod select;
+#[cfg(any_backend)]
+mod soft_delete;
+
+#[cfg(any_backend)]
+pub use run::QueryOutcome;
+
+/// Column marking soft-deleted rows unless
+/// [`PoolOptions::soft_delete_column`](crate::pool::PoolOptions::soft_delete_column) names
+/// another.
+pub const DEFAULT_SOFT_DELETE_COLUMN: &str = "deleted_at";
 
 /// Fluent builder for query execution with optional placeholder translation.
+#[cfg(any_backend)]
 pub struct QueryBuilder<'conn, 'q> {
     pub(crate) target: QueryTarget<'conn>,
     pub(crate) sql: Cow<'q, str>,
     pub(crate) params: Cow<'q, [RowValues]>,
     pub(crate) options: QueryOptions,
+    /// Error from a builder step, returned when the query runs.
+    pub(crate) error: Option<SqlMiddlewareDbError>,
 }
 
+#[cfg(any_backend)]
 impl<'conn, 'q> QueryBuilder<'conn, 'q> {
     pub(crate) fn new(conn: &'conn mut MiddlewarePoolConnection, sql: &'q str) -> Self {
         Self {
@@ -23,6 +54,7 @@ impl<'conn, 'q> QueryBuilder<'conn, 'q> {
             sql: Cow::Borrowed(sql),
             params: Cow::Borrowed(&[]),
             options: QueryOptions::default(),
+            error: None,
         }
     }
 
@@ -32,13 +64,15 @@ impl<'conn, 'q> QueryBuilder<'conn, 'q> {
             sql: Cow::Borrowed(sql),
             params: Cow::Borrowed(&[]),
             options: QueryOptions::default(),
+            error: None,
         }
     }
 
-    /// Provide parameters for this statement.
+    /// Provide parameters for this statement: a `RowValues` slice, or a tuple of plain values
+    /// such as `(id, "ann", true)` (see [`crate::params`]).
     #[must_use]
-    pub fn params(mut self, params: &'q [RowValues]) -> Self {
-        self.params = Cow::Borrowed(params);
+    pub fn params(mut self, params: impl IntoParams<'q>) -> Self {
+        self.params = params.into_params();
         self
     }
 
@@ -83,22 +117,94 @@ impl<'conn, 'q> QueryBuilder<'conn, 'q> {
         self.options.prepare = PrepareMode::Prepared;
         self
     }
+
+    /// Sort SELECT rows by every column when the SQL has no `ORDER BY`.
+    ///
+    /// See [`QueryOptions::stable_order`].
+    #[must_use]
+    pub fn stable_order(mut self) -> Self {
+        self.options.stable_order = true;
+        self
+    }
+
+    /// Reject values that would be silently coerced.
+    ///
+    /// See [`QueryOptions::strict`].
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.options.strict = true;
+        self
+    }
+
+    /// Record `actor` as the author of this statement in the [`audit`](crate::audit) trail.
+    ///
+    /// See [`QueryOptions::actor`].
+    #[must_use]
+    pub fn actor(mut self, actor: impl Into<std::sync::Arc<str>>) -> Self {
+        self.options.actor = Some(actor.into());
+        self
+    }
+
+    /// Audit this DML against `tables` when the target does not know its pool's.
+    ///
+    /// See [`QueryOptions::audit`].
+    #[must_use]
+    pub fn audit(mut self, tables: &crate::audit::AuditTables) -> Self {
+        self.options.audit = Some(tables.clone());
+        self
+    }
+
+    /// Read a large Postgres SELECT through binary COPY.
+    ///
+    /// See [`QueryOptions::bulk_read`].
+    #[must_use]
+    pub fn bulk_read(mut self) -> Self {
+        self.options.bulk_read = true;
+        self
+    }
 }
 
+#[cfg(any_backend)]
 pub(super) fn translate_query_for_target<'a>(
     target: &QueryTarget<'_>,
     query: &'a str,
     params: &[RowValues],
-    options: QueryOptions,
-) -> Cow<'a, str> {
+    options: &QueryOptions,
+) -> Result<Cow<'a, str>, SqlMiddlewareDbError> {
+    let translated = translate_query(
+        target.translation_target(),
+        target.translation_default(),
+        query,
+        params,
+        options,
+    )?;
+    echo::statement(target.debug_echo_flag(), query, &translated, params);
+    Ok(translated)
+}
+
+/// Target-independent half of [`translate_query_for_target`], split out so the
+/// benchmarks can drive the early-exit paths without a live connection.
+///
+/// Only statements with parameters are translated, so on Postgres too their bare `?` are numbered
+/// when they have no numbered placeholders. Statements mixing unnumbered `?` with numbered
+/// placeholders fail with `SqlMiddlewareDbError::ParameterError`.
+#[cfg(any_backend)]
+pub(crate) fn translate_query<'a>(
+    style: Option<PlaceholderStyle>,
+    pool_default: bool,
+    query: &'a str,
+    params: &[RowValues],
+    options: &QueryOptions,
+) -> Result<Cow<'a, str>, SqlMiddlewareDbError> {
     if params.is_empty() {
-        return Cow::Borrowed(query);
+        return Ok(Cow::Borrowed(query));
     }
 
-    let Some(style) = target.translation_target() else {
-        return Cow::Borrowed(query);
+    let Some(style) = style else {
+        return Ok(Cow::Borrowed(query));
     };
 
-    let enabled = options.translation.resolve(target.translation_default());
-    translate_placeholders(query, style, enabled)
+    let enabled = options.translation.resolve(pool_default);
+    try_translate_parameterized(query, style, enabled)
+        .map_err(|err| SqlMiddlewareDbError::ParameterError(err.to_string()))
 }
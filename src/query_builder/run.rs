@@ -0,0 +1,114 @@
+use std::time::Instant;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::executor::{QueryTarget, QueryTargetKind};
+use crate::metrics;
+use crate::results::ResultSet;
+use crate::translation::{StatementKind, statement_kind};
+
+use super::QueryBuilder;
+
+/// What [`QueryBuilder::run`] did, by the kind of statement it detected.
+#[derive(Debug, Clone)]
+pub enum QueryOutcome {
+    /// The statement returned rows.
+    Rows(ResultSet),
+    /// A single statement that returns no rows ran, affecting this many rows.
+    RowsAffected(usize),
+    /// Several statements ran as one batch.
+    Batch,
+}
+
+impl QueryOutcome {
+    /// The rows, if the statement returned any.
+    #[must_use]
+    pub fn rows(&self) -> Option<&ResultSet> {
+        match self {
+            QueryOutcome::Rows(result_set) => Some(result_set),
+            _ => None,
+        }
+    }
+
+    /// Rows affected by a single non-query statement.
+    #[must_use]
+    pub fn rows_affected(&self) -> Option<usize> {
+        match self {
+            QueryOutcome::RowsAffected(rows) => Some(*rows),
+            _ => None,
+        }
+    }
+}
+
+impl QueryBuilder<'_, '_> {
+    /// Execute the SQL as a query, a DML statement or a batch, whichever it looks like.
+    ///
+    /// The kind comes from [`statement_kind`]: statements that return rows run through
+    /// [`select`](Self::select), other single statements through [`dml`](Self::dml), and
+    /// scripts with several statements as a batch, the way `execute_batch` runs them on the
+    /// same target. For SQL forwarded from users, where the caller cannot know in advance:
+    /// ```rust,no_run
+    /// # use sql_middleware::prelude::*;
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+    /// match conn.query(sql).run().await? {
+    ///     QueryOutcome::Rows(rows) => println!("{} rows", rows.results.len()),
+    ///     QueryOutcome::RowsAffected(n) => println!("{n} rows affected"),
+    ///     QueryOutcome::Batch => println!("done"),
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ParameterError` if parameters are given for a batch, which
+    /// cannot bind them; otherwise the errors of `select`, `dml` or the backend's batch execution.
+    pub async fn run(mut self) -> Result<QueryOutcome, SqlMiddlewareDbError> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        match statement_kind(&self.sql) {
+            StatementKind::Select => self.select().await.map(QueryOutcome::Rows),
+            StatementKind::Dml => self.dml().await.map(QueryOutcome::RowsAffected),
+            StatementKind::Batch => {
+                if !self.params.is_empty() {
+                    return Err(SqlMiddlewareDbError::ParameterError(
+                        "parameters cannot be bound to a batch of several statements".into(),
+                    ));
+                }
+                let _bulkhead = self.target.enter_bulkhead(self.options.bulkhead).await?;
+                let started = Instant::now();
+                let result = batch_on_target(self.target, &self.sql).await;
+                metrics::record(&self.sql, started.elapsed(), None);
+                result.map(|()| QueryOutcome::Batch)
+            }
+        }
+    }
+}
+
+async fn batch_on_target(target: QueryTarget<'_>, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+    match target.kind {
+        QueryTargetKind::Connection(conn) => conn.execute_batch(sql).await,
+        #[cfg(feature = "sqlite")]
+        QueryTargetKind::TypedSqlite { conn } | QueryTargetKind::TypedSqliteTx { conn } => {
+            crate::sqlite::connection::batch(conn, sql).await
+        }
+        #[cfg(feature = "postgres")]
+        QueryTargetKind::TypedPostgres { conn } => {
+            crate::postgres::executor::execute_batch(conn, sql).await
+        }
+        #[cfg(feature = "postgres")]
+        QueryTargetKind::TypedPostgresTx { conn } => conn.batch_execute(sql).await.map_err(|e| {
+            SqlMiddlewareDbError::ExecutionError(format!("postgres tx batch error: {e}"))
+        }),
+        #[cfg(feature = "turso")]
+        QueryTargetKind::TypedTurso { conn } | QueryTargetKind::TypedTursoTx { conn } => {
+            crate::typed_turso::dml(conn, sql, &[]).await.map(|_| ())
+        }
+        #[cfg(feature = "postgres")]
+        QueryTargetKind::PostgresTx(tx) => tx.execute_batch(sql).await,
+        #[cfg(feature = "mssql")]
+        QueryTargetKind::MssqlTx(mut tx) => tx.execute_batch(sql).await,
+        #[cfg(feature = "turso")]
+        QueryTargetKind::TursoTx(tx) => tx.execute_batch(sql).await,
+        #[cfg(feature = "sqlite")]
+        QueryTargetKind::SqliteTx(conn) => conn.execute_batch_in_tx(sql).await,
+    }
+}
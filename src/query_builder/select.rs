@@ -1,22 +1,32 @@
+use std::borrow::Cow;
+use std::time::Instant;
+
 use crate::error::SqlMiddlewareDbError;
 use crate::executor::{
-    QueryTarget, QueryTargetKind, execute_select_dispatch, execute_select_prepared_dispatch,
+    QueryTarget, QueryTargetKind, execute_select_dispatch, execute_select_multi_dispatch,
+    execute_select_prepared_dispatch,
 };
+use crate::metrics;
 use crate::pool::MiddlewarePoolConnection;
-use crate::translation::PrepareMode;
-use crate::results::ResultSet;
-use crate::types::RowValues;
+use crate::results::{CustomDbRow, FromRowValues, ResultSet};
+use crate::translation::core::scanner::code_words;
+use crate::translation::{PrepareMode, has_order_by, is_select, split_statements};
+use crate::types::{DatabaseType, RowValues};
 
 #[cfg(feature = "postgres")]
 use crate::postgres::typed::PgManager;
 #[cfg(feature = "sqlite")]
 use crate::sqlite::config::SqliteManager;
+#[cfg(feature = "sqlite")]
+use crate::sqlite::transaction::select_in_tx;
 #[cfg(feature = "turso")]
 use crate::typed_turso::TursoManager;
+#[cfg(feature = "mssql")]
+use crate::types::ConversionMode;
 #[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
 use bb8::PooledConnection;
 
-use super::{QueryBuilder, translate_query_for_target};
+use super::{QueryBuilder, explain, translate_query_for_target};
 
 impl QueryBuilder<'_, '_> {
     /// Execute a SELECT and return the result set.
@@ -24,81 +34,330 @@ impl QueryBuilder<'_, '_> {
     /// # Errors
     /// Returns an error if placeholder translation fails or the backend query execution fails.
     pub async fn select(self) -> Result<ResultSet, SqlMiddlewareDbError> {
-        let translated = translate_query_for_target(
-            &self.target,
-            self.sql.as_ref(),
-            self.params.as_ref(),
-            self.options,
-        );
-        let use_prepare = matches!(self.options.prepare, PrepareMode::Prepared);
-
-        match self.target {
-            QueryTarget {
-                kind: QueryTargetKind::Connection(conn),
-                ..
-            } => {
-                select_on_connection(conn, translated.as_ref(), self.params.as_ref(), use_prepare)
-                    .await
+        let Self {
+            mut target,
+            sql,
+            params,
+            options,
+            error,
+        } = self;
+        if let Some(err) = error {
+            return Err(err);
+        }
+        if options.strict {
+            target.database_type().check_strict_params(&params)?;
+        }
+        let translated =
+            translate_query_for_target(&target, sql.as_ref(), params.as_ref(), &options)?;
+        let use_prepare = matches!(options.prepare, PrepareMode::Prepared);
+        let sort_rows = options.stable_order && !has_order_by(sql.as_ref());
+        let _bulkhead = target.enter_bulkhead(options.bulkhead).await?;
+
+        let started = Instant::now();
+        let bulk = if options.bulk_read && params.is_empty() {
+            bulk_read_on_target(&mut target, translated.as_ref()).await
+        } else {
+            Ok(None)
+        };
+        let result = match bulk {
+            Ok(Some(result_set)) => Ok(result_set),
+            Ok(None) => {
+                select_on_target(
+                    &mut target,
+                    translated.as_ref(),
+                    params.as_ref(),
+                    use_prepare,
+                    options.strict,
+                )
+                .await
             }
-            #[cfg(feature = "sqlite")]
-            QueryTarget {
-                kind: QueryTargetKind::TypedSqlite { conn }
-                    | QueryTargetKind::TypedSqliteTx { conn },
-                ..
-            } => select_typed_sqlite(conn, translated.as_ref(), self.params.as_ref()).await,
-            #[cfg(feature = "postgres")]
-            QueryTarget {
-                kind: QueryTargetKind::TypedPostgres { conn }
-                    | QueryTargetKind::TypedPostgresTx { conn },
-                ..
-            } => select_typed_postgres(conn, translated.as_ref(), self.params.as_ref(), use_prepare)
-                .await,
-            #[cfg(feature = "turso")]
-            QueryTarget {
-                kind: QueryTargetKind::TypedTurso { conn }
-                    | QueryTargetKind::TypedTursoTx { conn },
-                ..
-            } => select_typed_turso(conn, translated.as_ref(), self.params.as_ref()).await,
-            #[cfg(feature = "postgres")]
-            QueryTarget {
-                kind: QueryTargetKind::PostgresTx(tx),
-                ..
-            } => {
-                if use_prepare {
-                    let prepared = tx.prepare(translated.as_ref()).await?;
-                    tx.query_prepared(&prepared, self.params.as_ref()).await
-                } else {
-                    tx.query(translated.as_ref(), self.params.as_ref()).await
+            Err(err) => Err(err),
+        };
+        let elapsed = started.elapsed();
+        metrics::record(
+            &sql,
+            elapsed,
+            result.as_ref().ok().map(|rs| rs.results.len()),
+        );
+        let mut result_set = result?;
+        explain::explain_if_slow(&mut target, &sql, &translated, &params, elapsed).await;
+
+        if sort_rows {
+            result_set.sort_rows();
+        }
+        Ok(result_set)
+    }
+
+    /// Execute a SELECT and return its first row, or `None` when it returns no rows.
+    ///
+    /// On Postgres, `SQLite` and Turso a single plain `SELECT` with no `LIMIT`, `OFFSET`,
+    /// `FETCH`, `TOP`, `FOR` or `INTO` of its own is sent with `LIMIT 1` appended, so the
+    /// backend stops after the first row. Other statements run as written and the remaining rows
+    /// are dropped.
+    ///
+    /// # Errors
+    /// Same as [`select`](Self::select).
+    pub async fn select_one(mut self) -> Result<Option<CustomDbRow>, SqlMiddlewareDbError> {
+        if supports_limit(&self.target.database_type())
+            && let Some(limited) = limit_one(&self.sql)
+        {
+            self.sql = Cow::Owned(limited);
+        }
+        Ok(self.select().await?.results.into_iter().next())
+    }
+
+    /// Execute a SELECT and return the first column of its first row as a `T`.
+    ///
+    /// Use `Option<T>` when the value may be NULL:
+    /// ```rust,no_run
+    /// # use sql_middleware::prelude::*;
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let users: i64 = conn.query("SELECT COUNT(*) FROM users").select_scalar().await?;
+    /// let newest: Option<String> = conn
+    ///     .query("SELECT MAX(name) FROM users")
+    ///     .select_scalar()
+    ///     .await?;
+    /// # let _ = (users, newest);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if the query returns no rows or no
+    /// columns, or the value is not a `T`; otherwise the same errors as
+    /// [`select`](Self::select).
+    pub async fn select_scalar<T: FromRowValues>(self) -> Result<T, SqlMiddlewareDbError> {
+        let row = self.select_one().await?.ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError("select_scalar: query returned no rows".into())
+        })?;
+        let value = row.get_by_index(0).ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError("select_scalar: query returned no columns".into())
+        })?;
+        T::from_row_values(value)
+    }
+
+    /// Execute a statement that may yield several result sets and return all of them, in order.
+    ///
+    /// - SQL Server: one result set per result of the batch or stored procedure.
+    /// - Postgres: when every returned column is a `refcursor` (a function returning cursors),
+    ///   each cursor is fetched as its own result set; otherwise the single result is returned.
+    ///   Outside an explicit transaction the query runs in its own so the cursors stay open.
+    /// - `SQLite`/Turso: statements yield a single result set, returned as a one-element vector.
+    ///
+    /// The prepare option is ignored.
+    ///
+    /// # Errors
+    /// Returns an error if placeholder translation fails or the backend query execution fails.
+    pub async fn select_multi(self) -> Result<Vec<ResultSet>, SqlMiddlewareDbError> {
+        let Self {
+            mut target,
+            sql,
+            params,
+            options,
+            error,
+        } = self;
+        if let Some(err) = error {
+            return Err(err);
+        }
+        if options.strict {
+            target.database_type().check_strict_params(&params)?;
+        }
+        let translated =
+            translate_query_for_target(&target, sql.as_ref(), params.as_ref(), &options)?;
+        let query = translated.as_ref();
+        let params = params.as_ref();
+        let sort_rows = options.stable_order && !has_order_by(sql.as_ref());
+        let _bulkhead = target.enter_bulkhead(options.bulkhead).await?;
+
+        let started = Instant::now();
+        let result =
+            async {
+                match target {
+                    QueryTarget {
+                        kind: QueryTargetKind::Connection(conn),
+                        ..
+                    } => execute_select_multi_dispatch(conn, query, params).await,
+                    #[cfg(feature = "sqlite")]
+                    QueryTarget {
+                        kind:
+                            QueryTargetKind::TypedSqlite { conn }
+                            | QueryTargetKind::TypedSqliteTx { conn },
+                        ..
+                    } => select_typed_sqlite(conn, query, params)
+                        .await
+                        .map(|result_set| vec![result_set]),
+                    #[cfg(feature = "postgres")]
+                    QueryTarget {
+                        kind: QueryTargetKind::TypedPostgres { conn },
+                        ..
+                    } => crate::postgres::execute_select_multi(conn, query, params).await,
+                    #[cfg(feature = "postgres")]
+                    QueryTarget {
+                        kind: QueryTargetKind::TypedPostgresTx { conn },
+                        ..
+                    } => crate::postgres::query::query_multi_on(&**conn, query, params).await,
+                    #[cfg(feature = "turso")]
+                    QueryTarget {
+                        kind:
+                            QueryTargetKind::TypedTurso { conn }
+                            | QueryTargetKind::TypedTursoTx { conn },
+                        ..
+                    } => select_typed_turso(conn, query, params)
+                        .await
+                        .map(|result_set| vec![result_set]),
+                    #[cfg(feature = "postgres")]
+                    QueryTarget {
+                        kind: QueryTargetKind::PostgresTx(tx),
+                        ..
+                    } => tx.query_multi(query, params).await,
+                    #[cfg(feature = "mssql")]
+                    QueryTarget {
+                        kind: QueryTargetKind::MssqlTx(mut tx),
+                        ..
+                    } => tx.query_multi(query, params).await,
+                    #[cfg(feature = "turso")]
+                    QueryTarget {
+                        kind: QueryTargetKind::TursoTx(tx),
+                        ..
+                    } => tx
+                        .execute_select(query, params)
+                        .await
+                        .map(|result_set| vec![result_set]),
+                    #[cfg(feature = "sqlite")]
+                    QueryTarget {
+                        kind: QueryTargetKind::SqliteTx(conn),
+                        ..
+                    } => select_in_tx(conn, query, params)
+                        .await
+                        .map(|result_set| vec![result_set]),
                 }
             }
-            #[cfg(feature = "mssql")]
-            QueryTarget {
-                kind: QueryTargetKind::MssqlTx(tx),
-                ..
-            } => {
-                if use_prepare {
-                    let prepared = tx.prepare(translated.as_ref())?;
-                    tx.query_prepared(&prepared, self.params.as_ref()).await
-                } else {
-                    tx.query(translated.as_ref(), self.params.as_ref()).await
-                }
+            .await;
+        metrics::record(
+            &sql,
+            started.elapsed(),
+            result
+                .as_ref()
+                .ok()
+                .map(|sets| sets.iter().map(|rs| rs.results.len()).sum()),
+        );
+        let mut result_sets = result?;
+
+        if sort_rows {
+            for result_set in &mut result_sets {
+                result_set.sort_rows();
             }
-            #[cfg(feature = "turso")]
-            QueryTarget {
-                kind: QueryTargetKind::TursoTx(tx),
-                ..
-            } => {
-                if use_prepare {
-                    let mut prepared = tx.prepare(translated.as_ref()).await?;
-                    tx.query_prepared(&mut prepared, self.params.as_ref()).await
-                } else {
-                    tx.execute_select(translated.as_ref(), self.params.as_ref()).await
-                }
+        }
+        Ok(result_sets)
+    }
+}
+
+/// Run a SELECT on any target; borrowed so the caller can follow up on the same target.
+pub(super) async fn select_on_target(
+    target: &mut QueryTarget<'_>,
+    query: &str,
+    params: &[RowValues],
+    use_prepare: bool,
+    strict: bool,
+) -> Result<ResultSet, SqlMiddlewareDbError> {
+    match &mut target.kind {
+        QueryTargetKind::Connection(conn) => {
+            if strict {
+                select_strict_on_connection(conn, query, params, use_prepare).await
+            } else {
+                select_on_connection(conn, query, params, use_prepare).await
+            }
+        }
+        #[cfg(feature = "sqlite")]
+        QueryTargetKind::TypedSqlite { conn } | QueryTargetKind::TypedSqliteTx { conn } => {
+            select_typed_sqlite(conn, query, params).await
+        }
+        #[cfg(feature = "postgres")]
+        QueryTargetKind::TypedPostgres { conn } | QueryTargetKind::TypedPostgresTx { conn } => {
+            select_typed_postgres(conn, query, params, use_prepare).await
+        }
+        #[cfg(feature = "turso")]
+        QueryTargetKind::TypedTurso { conn } | QueryTargetKind::TypedTursoTx { conn } => {
+            select_typed_turso(conn, query, params).await
+        }
+        #[cfg(feature = "postgres")]
+        QueryTargetKind::PostgresTx(tx) => {
+            if use_prepare {
+                let prepared = tx.prepare(query).await?;
+                tx.query_prepared(&prepared, params).await
+            } else {
+                tx.query(query, params).await
+            }
+        }
+        #[cfg(feature = "mssql")]
+        QueryTargetKind::MssqlTx(tx) => {
+            if strict {
+                tx.query_with_mode(query, params, ConversionMode::Strict)
+                    .await
+            } else if use_prepare {
+                let prepared = tx.prepare(query)?;
+                tx.query_prepared(&prepared, params).await
+            } else {
+                tx.query(query, params).await
             }
         }
+        #[cfg(feature = "turso")]
+        QueryTargetKind::TursoTx(tx) => {
+            if use_prepare {
+                let mut prepared = tx.prepare(query).await?;
+                tx.query_prepared(&mut prepared, params).await
+            } else {
+                tx.execute_select(query, params).await
+            }
+        }
+        // Statements inside a SQLite transaction are cached by the worker either way.
+        #[cfg(feature = "sqlite")]
+        QueryTargetKind::SqliteTx(conn) => select_in_tx(conn, query, params).await,
     }
 }
 
+/// Read a single parameterless SELECT through Postgres binary COPY; `None` when the target is
+/// not Postgres or the statement is not one plain SELECT, so the caller runs it as usual.
+#[cfg(feature = "postgres")]
+async fn bulk_read_on_target(
+    target: &mut QueryTarget<'_>,
+    query: &str,
+) -> Result<Option<ResultSet>, SqlMiddlewareDbError> {
+    use crate::postgres::bulk_read::bulk_read_on_client;
+
+    let [statement] = split_statements(query)[..] else {
+        return Ok(None);
+    };
+    if !is_select(statement) {
+        return Ok(None);
+    }
+    match &mut target.kind {
+        QueryTargetKind::Connection(conn) => {
+            let client = match &**conn {
+                MiddlewarePoolConnection::Postgres { client, .. } => client,
+                #[allow(unreachable_patterns)]
+                _ => return Ok(None),
+            };
+            conn.throttle().await?;
+            bulk_read_on_client(client, statement).await.map(Some)
+        }
+        QueryTargetKind::TypedPostgres { conn } | QueryTargetKind::TypedPostgresTx { conn } => {
+            bulk_read_on_client(conn, statement).await.map(Some)
+        }
+        QueryTargetKind::PostgresTx(tx) => tx.bulk_read(statement).await.map(Some),
+        #[allow(unreachable_patterns)]
+        _ => Ok(None),
+    }
+}
+
+/// Without Postgres there is no COPY; every statement runs as usual.
+#[cfg(not(feature = "postgres"))]
+async fn bulk_read_on_target(
+    _target: &mut QueryTarget<'_>,
+    _query: &str,
+) -> Result<Option<ResultSet>, SqlMiddlewareDbError> {
+    Ok(None)
+}
+
 async fn select_on_connection(
     conn: &mut MiddlewarePoolConnection,
     query: &str,
@@ -112,6 +371,28 @@ async fn select_on_connection(
     }
 }
 
+/// Strict SELECT on a pooled connection. Only SQL Server reads results differently; the other
+/// backends map every value exactly already.
+async fn select_strict_on_connection(
+    conn: &mut MiddlewarePoolConnection,
+    query: &str,
+    params: &[RowValues],
+    use_prepare: bool,
+) -> Result<ResultSet, SqlMiddlewareDbError> {
+    #[cfg(feature = "mssql")]
+    #[allow(irrefutable_let_patterns)]
+    if let MiddlewarePoolConnection::Mssql { conn, .. } = &mut *conn {
+        return crate::mssql::query::build_result_set_with_mode(
+            conn,
+            query,
+            params,
+            ConversionMode::Strict,
+        )
+        .await;
+    }
+    select_on_connection(conn, query, params, use_prepare).await
+}
+
 #[cfg(feature = "sqlite")]
 async fn select_typed_sqlite(
     conn: &mut PooledConnection<'static, SqliteManager>,
@@ -143,3 +424,31 @@ async fn select_typed_turso(
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
     crate::typed_turso::select(conn, query, params).await
 }
+
+/// Whether `LIMIT n` is valid syntax on `db_type` (SQL Server uses `TOP`).
+fn supports_limit(db_type: &DatabaseType) -> bool {
+    match db_type {
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => false,
+        #[allow(unreachable_patterns)]
+        _ => true,
+    }
+}
+
+/// `sql` with `LIMIT 1` appended, if it is a single plain SELECT where that cannot change which
+/// row comes first.
+fn limit_one(sql: &str) -> Option<String> {
+    let [statement] = split_statements(sql)[..] else {
+        return None;
+    };
+    if !is_select(statement) {
+        return None;
+    }
+    let limited = code_words(statement).iter().any(|word| {
+        ["limit", "offset", "fetch", "top", "for", "into"]
+            .iter()
+            .any(|clause| word.eq_ignore_ascii_case(clause))
+    });
+    // A newline keeps the clause out of a trailing line comment.
+    (!limited).then(|| format!("{statement}\nLIMIT 1"))
+}
@@ -0,0 +1,236 @@
+//! Soft-delete convention: rows are marked deleted by setting a timestamp column instead of
+//! being removed.
+
+use std::borrow::Cow;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::ident::{is_plain, quote};
+use crate::pool::MiddlewarePoolConnection;
+use crate::query_utils::quote_ident;
+use crate::translation::core::scanner::top_level_words;
+use crate::translation::{TranslationMode, split_statements};
+use crate::types::RowValues;
+
+use super::QueryBuilder;
+
+/// Clauses that end a `WHERE` clause, or mark where one would go.
+const AFTER_WHERE: &[&str] = &[
+    "group",
+    "having",
+    "window",
+    "order",
+    "limit",
+    "offset",
+    "fetch",
+    "union",
+    "intersect",
+    "except",
+    "for",
+    "returning",
+    "option",
+];
+
+impl QueryBuilder<'_, '_> {
+    /// Keep only rows whose soft-delete state is `deleted`.
+    ///
+    /// `filter_deleted(false)` adds `deleted_at IS NULL` to the statement's `WHERE` clause,
+    /// creating one if needed, so live rows are read, updated or deleted; `filter_deleted(true)`
+    /// adds `deleted_at IS NOT NULL` instead. The column is the pool's
+    /// [`soft_delete_column`](crate::pool::PoolOptions::soft_delete_column) on a pooled
+    /// connection and [`DEFAULT_SOFT_DELETE_COLUMN`](super::DEFAULT_SOFT_DELETE_COLUMN) on other
+    /// targets.
+    ///
+    /// ```rust,no_run
+    /// # use sql_middleware::prelude::*;
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// // SELECT id FROM users WHERE (team = $1) AND deleted_at IS NULL ORDER BY id
+    /// let live = conn
+    ///     .query("SELECT id FROM users WHERE team = $1 ORDER BY id")
+    ///     .params(("blue",))
+    ///     .filter_deleted(false)
+    ///     .select()
+    ///     .await?;
+    /// # let _ = live;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// The predicate is added to the outermost query of a single `SELECT`, `UPDATE` or `DELETE`
+    /// and names the column without a table, so with joins it must be unambiguous; a `UNION`
+    /// only filters its first branch. Other SQL, including scripts of several statements, fails
+    /// with `SqlMiddlewareDbError::ExecutionError` when run instead of running unfiltered.
+    #[must_use]
+    pub fn filter_deleted(mut self, deleted: bool) -> Self {
+        let column = self.target.soft_delete_column();
+        let column = if is_plain(column) {
+            column.to_owned()
+        } else {
+            quote_ident(column)
+        };
+        let predicate = if deleted {
+            format!("{column} IS NOT NULL")
+        } else {
+            format!("{column} IS NULL")
+        };
+        match with_predicate(&self.sql, &predicate) {
+            Some(filtered) => self.sql = Cow::Owned(filtered),
+            None => {
+                self.error.get_or_insert_with(|| {
+                    SqlMiddlewareDbError::ExecutionError(
+                        "filter_deleted needs a single SELECT, UPDATE or DELETE statement".into(),
+                    )
+                });
+            }
+        }
+        self
+    }
+}
+
+impl MiddlewarePoolConnection {
+    /// Mark the row of `table` whose `key` column holds the given value as deleted, by setting
+    /// the pool's soft-delete column to `CURRENT_TIMESTAMP`.
+    ///
+    /// Rows already marked keep their original timestamp. Returns the number of rows marked:
+    /// `0` when no live row has that key.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ParameterError` if a name cannot be quoted, or the backend
+    /// error from the update.
+    pub async fn soft_delete(
+        &mut self,
+        table: &str,
+        key: (&str, RowValues),
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        let db_type = self.database_type();
+        let (key_column, key_value) = key;
+        let column = quote(self.soft_delete_column(), &db_type)?;
+        let sql = format!(
+            "UPDATE {} SET {column} = CURRENT_TIMESTAMP WHERE {} = {} AND {column} IS NULL",
+            quote(table, &db_type)?,
+            quote(key_column, &db_type)?,
+            db_type.placeholder(1)
+        );
+        self.query(&sql)
+            .params(&[key_value])
+            .translation(TranslationMode::ForceOff)
+            .dml()
+            .await
+    }
+}
+
+/// `sql` with `predicate` added to its outermost `WHERE` clause, if it is a single `SELECT`,
+/// `UPDATE` or `DELETE`.
+fn with_predicate(sql: &str, predicate: &str) -> Option<String> {
+    let [statement] = split_statements(sql)[..] else {
+        return None;
+    };
+    let words = top_level_words(statement);
+    let filterable = leading_keyword(&words).is_some_and(|keyword| {
+        ["select", "update", "delete"]
+            .iter()
+            .any(|kind| keyword.eq_ignore_ascii_case(kind))
+    });
+    if !filterable {
+        return None;
+    }
+    let where_at = words
+        .iter()
+        .position(|(_, word)| word.eq_ignore_ascii_case("where"));
+    let end = words[where_at.map_or(0, |idx| idx + 1)..]
+        .iter()
+        .find(|(_, word)| {
+            AFTER_WHERE
+                .iter()
+                .any(|clause| word.eq_ignore_ascii_case(clause))
+        })
+        .map_or(statement.len(), |(offset, _)| *offset);
+    let (head, tail) = statement.split_at(end);
+    // Newlines keep the added SQL out of a trailing line comment.
+    Some(match where_at {
+        Some(idx) => {
+            let (offset, word) = words[idx];
+            let (before, cond) = head.split_at(offset + word.len());
+            format!("{before} ({cond}\n) AND {predicate}\n{tail}")
+        }
+        None => format!("{head}\nWHERE {predicate}\n{tail}"),
+    })
+}
+
+/// The keyword saying what kind of statement `words` are, looking past a leading `WITH` clause.
+fn leading_keyword<'a>(words: &[(usize, &'a str)]) -> Option<&'a str> {
+    let (_, first) = words.first()?;
+    if !first.eq_ignore_ascii_case("with") {
+        return Some(first);
+    }
+    // CTE bodies are parenthesized, so the first statement keyword at the top level is the
+    // main statement's.
+    words.iter().map(|(_, word)| *word).find(|word| {
+        [
+            "select", "insert", "update", "delete", "merge", "replace", "values",
+        ]
+        .iter()
+        .any(|kind| word.eq_ignore_ascii_case(kind))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::with_predicate;
+
+    fn filtered(sql: &str) -> Option<String> {
+        with_predicate(sql, "deleted_at IS NULL")
+            .map(|sql| sql.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    #[test]
+    fn adds_the_predicate_to_the_outer_where_clause() {
+        assert_eq!(
+            filtered("SELECT id FROM users").as_deref(),
+            Some("SELECT id FROM users WHERE deleted_at IS NULL")
+        );
+        assert_eq!(
+            filtered("SELECT id FROM users WHERE a = 1 OR b = 2 ORDER BY id;").as_deref(),
+            Some(
+                "SELECT id FROM users WHERE ( a = 1 OR b = 2 ) AND deleted_at IS NULL ORDER BY id"
+            )
+        );
+        assert_eq!(
+            filtered("SELECT id FROM users WHERE id IN (SELECT id FROM t WHERE x LIMIT 1) LIMIT 5")
+                .as_deref(),
+            Some(
+                "SELECT id FROM users WHERE ( id IN (SELECT id FROM t WHERE x LIMIT 1) ) \
+                 AND deleted_at IS NULL LIMIT 5"
+            )
+        );
+        assert_eq!(
+            filtered("UPDATE users SET name = 'order' WHERE id = 1 -- note").as_deref(),
+            Some("UPDATE users SET name = 'order' WHERE ( id = 1 -- note ) AND deleted_at IS NULL")
+        );
+        assert_eq!(
+            filtered("DELETE FROM users RETURNING id").as_deref(),
+            Some("DELETE FROM users WHERE deleted_at IS NULL RETURNING id")
+        );
+        assert_eq!(filtered("INSERT INTO users SELECT * FROM staged"), None);
+        assert_eq!(filtered("SELECT 1; SELECT 2"), None);
+    }
+
+    #[test]
+    fn only_the_leading_keyword_decides() {
+        assert_eq!(
+            filtered("SELECT replace(name, 'a', 'b') FROM users").as_deref(),
+            Some("SELECT replace(name, 'a', 'b') FROM users WHERE deleted_at IS NULL")
+        );
+        assert_eq!(
+            filtered("WITH t AS (SELECT id FROM staged) DELETE FROM users WHERE id IN t")
+                .as_deref(),
+            Some(
+                "WITH t AS (SELECT id FROM staged) DELETE FROM users WHERE ( id IN t ) \
+                 AND deleted_at IS NULL"
+            )
+        );
+        assert_eq!(
+            filtered("WITH t AS (SELECT 1) INSERT INTO users SELECT * FROM t"),
+            None
+        );
+        assert_eq!(filtered("MERGE INTO users USING staged ON 1 = 1"), None);
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(any_backend)]
 pub(crate) fn extract_column_names<I, T, F>(columns: I, name: F) -> Vec<String>
 where
     I: IntoIterator<Item = T>,
@@ -8,3 +9,12 @@ where
         .map(|col| name(&col).to_string())
         .collect()
 }
+
+/// Quote an identifier, keeping `schema.table` qualification.
+pub(crate) fn quote_ident(ident: &str) -> String {
+    ident
+        .split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(".")
+}
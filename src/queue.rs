@@ -0,0 +1,260 @@
+//! Lightweight job queue stored in a database table.
+//!
+//! Jobs are rows in [`JOB_TABLE`]. [`JobQueue::dequeue`] claims the oldest visible job for a
+//! worker and hides it for a visibility timeout; the worker calls [`JobQueue::complete`] when done
+//! or [`JobQueue::release`] to hand it back. A job whose worker dies becomes visible again once the
+//! timeout passes, so delivery is at-least-once.
+//!
+//! Claiming uses `FOR UPDATE SKIP LOCKED` on Postgres, `UPDLOCK, READPAST` on SQL Server, and a
+//! single `UPDATE ... RETURNING` on `SQLite`/Turso (whose writers are already serialized), so
+//! concurrent workers never claim the same job.
+
+use std::time::{Duration, SystemTime};
+
+use crate::clock::epoch_millis;
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::{ConfigAndPool, MiddlewarePoolConnection};
+use crate::results::ResultSet;
+use crate::translation::TranslationMode;
+use crate::types::{DatabaseType, RowValues};
+
+/// Table holding queued jobs for every queue (created by [`JobQueue::open`]).
+pub const JOB_TABLE: &str = "sql_middleware_jobs";
+
+/// A job claimed by a worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub id: i64,
+    pub payload: String,
+    /// How many times the job has been claimed, including this one.
+    pub attempts: i64,
+    /// Worker that claimed the job.
+    pub worker_id: String,
+}
+
+/// Handle to one named queue.
+#[derive(Debug, Clone)]
+pub struct JobQueue {
+    cap: ConfigAndPool,
+    name: String,
+}
+
+impl JobQueue {
+    /// Open the queue `name`, creating the job table if needed.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the table cannot be created.
+    pub async fn open(cap: &ConfigAndPool, name: &str) -> Result<Self, SqlMiddlewareDbError> {
+        let mut conn = cap.get_connection().await?;
+        let db_type = conn.database_type();
+        conn.execute_batch(&create_table_sql(&db_type)).await?;
+        Ok(Self {
+            cap: cap.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    /// Queue name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a job and return its id.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the insert fails.
+    pub async fn enqueue(&self, payload: &str) -> Result<i64, SqlMiddlewareDbError> {
+        let mut conn = self.cap.get_connection().await?;
+        let db_type = conn.database_type();
+        let p = |idx| db_type.placeholder(idx);
+        let sql = match db_type {
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => format!(
+                "INSERT INTO {JOB_TABLE} (queue, payload, attempts) OUTPUT inserted.id \
+                 VALUES ({}, {}, 0)",
+                p(1),
+                p(2)
+            ),
+            #[allow(unreachable_patterns)]
+            _ => format!(
+                "INSERT INTO {JOB_TABLE} (queue, payload, attempts) VALUES ({}, {}, 0) \
+                 RETURNING id",
+                p(1),
+                p(2)
+            ),
+        };
+        let params = [
+            RowValues::Text(self.name.as_str().into()),
+            RowValues::Text(payload.into()),
+        ];
+        let rows = select(&mut conn, &sql, &params).await?;
+        rows.results
+            .first()
+            .and_then(|row| row.get("id"))
+            .and_then(RowValues::as_int)
+            .copied()
+            .ok_or_else(|| {
+                SqlMiddlewareDbError::ExecutionError("enqueue did not return a job id".into())
+            })
+    }
+
+    /// Claim the oldest visible job for `worker_id`, hiding it from other workers for
+    /// `visibility_timeout`.
+    ///
+    /// Returns `None` when the queue has no visible jobs.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the claim fails.
+    pub async fn dequeue(
+        &self,
+        worker_id: &str,
+        visibility_timeout: Duration,
+    ) -> Result<Option<Job>, SqlMiddlewareDbError> {
+        let mut conn = self.cap.get_connection().await?;
+        let db_type = conn.database_type();
+        let now = SystemTime::now();
+        let p = |idx| db_type.placeholder(idx);
+        let visible = format!(
+            "queue = {} AND (locked_until_ms IS NULL OR locked_until_ms < {})",
+            p(3),
+            p(4)
+        );
+        let set = format!(
+            "locked_by = {}, locked_until_ms = {}, attempts = attempts + 1",
+            p(1),
+            p(2)
+        );
+        let sql = match db_type {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => format!(
+                "UPDATE {JOB_TABLE} SET {set} WHERE id = (SELECT id FROM {JOB_TABLE} \
+                 WHERE {visible} ORDER BY id LIMIT 1 FOR UPDATE SKIP LOCKED) \
+                 RETURNING id, payload, attempts"
+            ),
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => format!(
+                "WITH next_job AS (SELECT TOP (1) * FROM {JOB_TABLE} \
+                 WITH (UPDLOCK, READPAST, ROWLOCK) WHERE {visible} ORDER BY id) \
+                 UPDATE next_job SET {set} \
+                 OUTPUT inserted.id, inserted.payload, inserted.attempts"
+            ),
+            #[allow(unreachable_patterns)]
+            _ => format!(
+                "UPDATE {JOB_TABLE} SET {set} WHERE id = (SELECT id FROM {JOB_TABLE} \
+                 WHERE {visible} ORDER BY id LIMIT 1) RETURNING id, payload, attempts"
+            ),
+        };
+        let params = [
+            RowValues::Text(worker_id.into()),
+            RowValues::Int(epoch_millis(now + visibility_timeout)?),
+            RowValues::Text(self.name.as_str().into()),
+            RowValues::Int(epoch_millis(now)?),
+        ];
+        let rows = select(&mut conn, &sql, &params).await?;
+        let Some(row) = rows.results.first() else {
+            return Ok(None);
+        };
+        let field = |name: &str| {
+            row.get(name).ok_or_else(|| {
+                SqlMiddlewareDbError::ExecutionError(format!("dequeue result missing {name}"))
+            })
+        };
+        Ok(Some(Job {
+            id: field("id")?.as_int().copied().unwrap_or_default(),
+            payload: field("payload")?.as_text().unwrap_or_default().to_string(),
+            attempts: field("attempts")?.as_int().copied().unwrap_or_default(),
+            worker_id: worker_id.to_string(),
+        }))
+    }
+
+    /// Delete a finished job.
+    ///
+    /// Returns `false` if the job was no longer claimed by `job.worker_id` (its visibility timeout
+    /// passed and another worker took it).
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the delete fails.
+    pub async fn complete(&self, job: &Job) -> Result<bool, SqlMiddlewareDbError> {
+        let mut conn = self.cap.get_connection().await?;
+        let db_type = conn.database_type();
+        let sql = format!(
+            "DELETE FROM {JOB_TABLE} WHERE id = {} AND locked_by = {}",
+            db_type.placeholder(1),
+            db_type.placeholder(2)
+        );
+        let params = [
+            RowValues::Int(job.id),
+            RowValues::Text(job.worker_id.as_str().into()),
+        ];
+        Ok(dml(&mut conn, &sql, &params).await? > 0)
+    }
+
+    /// Make a claimed job visible again immediately (e.g., after a failure worth retrying).
+    ///
+    /// Returns `false` if the job was no longer claimed by `job.worker_id`.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the update fails.
+    pub async fn release(&self, job: &Job) -> Result<bool, SqlMiddlewareDbError> {
+        let mut conn = self.cap.get_connection().await?;
+        let db_type = conn.database_type();
+        let sql = format!(
+            "UPDATE {JOB_TABLE} SET locked_by = NULL, locked_until_ms = NULL \
+             WHERE id = {} AND locked_by = {}",
+            db_type.placeholder(1),
+            db_type.placeholder(2)
+        );
+        let params = [
+            RowValues::Int(job.id),
+            RowValues::Text(job.worker_id.as_str().into()),
+        ];
+        Ok(dml(&mut conn, &sql, &params).await? > 0)
+    }
+}
+
+fn create_table_sql(db_type: &DatabaseType) -> String {
+    let rest = "queue VARCHAR(255) NOT NULL, locked_by VARCHAR(255) NULL, \
+                locked_until_ms BIGINT NULL, attempts INTEGER NOT NULL";
+    match db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => format!(
+            "CREATE TABLE IF NOT EXISTS {JOB_TABLE} (id BIGSERIAL PRIMARY KEY, payload TEXT NOT NULL, {rest}); \
+             CREATE INDEX IF NOT EXISTS {JOB_TABLE}_queue ON {JOB_TABLE} (queue, id)"
+        ),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => format!(
+            "IF OBJECT_ID(N'{JOB_TABLE}', N'U') IS NULL CREATE TABLE {JOB_TABLE} \
+             (id BIGINT IDENTITY(1,1) PRIMARY KEY, payload NVARCHAR(MAX) NOT NULL, {rest})"
+        ),
+        #[allow(unreachable_patterns)]
+        _ => format!(
+            "CREATE TABLE IF NOT EXISTS {JOB_TABLE} (id INTEGER PRIMARY KEY AUTOINCREMENT, payload TEXT NOT NULL, {rest}); \
+             CREATE INDEX IF NOT EXISTS {JOB_TABLE}_queue ON {JOB_TABLE} (queue, id)"
+        ),
+    }
+}
+
+async fn select(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    params: &[RowValues],
+) -> Result<ResultSet, SqlMiddlewareDbError> {
+    conn.query(sql)
+        .params(params)
+        .translation(TranslationMode::ForceOff)
+        .select()
+        .await
+}
+
+async fn dml(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    params: &[RowValues],
+) -> Result<usize, SqlMiddlewareDbError> {
+    conn.query(sql)
+        .params(params)
+        .translation(TranslationMode::ForceOff)
+        .dml()
+        .await
+}
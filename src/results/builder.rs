@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use super::result_set::ResultSet;
+use crate::error::SqlMiddlewareDbError;
+use crate::types::RowValues;
+
+/// Assemble a [`ResultSet`] from data that did not come from a query.
+///
+/// CSV imports, API responses, or a backend implemented outside this crate can build rows here
+/// and hand the result to anything that takes a `ResultSet`, such as
+/// [`diff_result_sets`](crate::compare::diff_result_sets). Rows must have one value per column;
+/// [`finish`](ResultSetBuilder::finish) checks this.
+///
+/// ```rust
+/// use sql_middleware::prelude::*;
+///
+/// # fn main() -> Result<(), SqlMiddlewareDbError> {
+/// let mut builder = ResultSetBuilder::new();
+/// builder.add_column("id").add_column("name");
+/// builder.push_row(vec![RowValues::Int(1), RowValues::Text("ada".into())]);
+/// let rs = builder.finish()?;
+/// assert_eq!(rs.results[0].get("name").and_then(RowValues::as_text), Some("ada"));
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResultSetBuilder {
+    columns: Vec<String>,
+    rows: Vec<Vec<RowValues>>,
+}
+
+impl ResultSetBuilder {
+    /// Start with no columns and no rows.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a column; columns appear in the order they are added.
+    pub fn add_column(&mut self, name: impl Into<String>) -> &mut Self {
+        self.columns.push(name.into());
+        self
+    }
+
+    /// Append a row of values, one per column.
+    pub fn push_row(&mut self, values: Vec<RowValues>) -> &mut Self {
+        self.rows.push(values);
+        self
+    }
+
+    /// Build the result set. Every row shares a single copy of the column names.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ParameterError` if a row's length does not match the
+    /// number of columns.
+    pub fn finish(self) -> Result<ResultSet, SqlMiddlewareDbError> {
+        if let Some((idx, row)) = self
+            .rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != self.columns.len())
+        {
+            return Err(SqlMiddlewareDbError::ParameterError(format!(
+                "row {idx} has {} values but the result set has {} columns",
+                row.len(),
+                self.columns.len()
+            )));
+        }
+
+        let mut result_set = ResultSet::with_capacity(self.rows.len());
+        result_set.set_column_names(Arc::new(self.columns));
+        for row in self.rows {
+            result_set.add_row_values(row);
+        }
+        Ok(result_set)
+    }
+}
@@ -1,5 +1,9 @@
+pub mod builder;
 pub mod result_set;
 pub mod row;
+pub mod value;
 
+pub use builder::ResultSetBuilder;
 pub use result_set::ResultSet;
 pub use row::CustomDbRow;
+pub use value::FromRowValues;
@@ -112,4 +112,19 @@ impl ResultSet {
         self.results.push(row);
         self.rows_affected += 1;
     }
+
+    /// Sort rows by every column, left to right, using [`RowValues::total_cmp`].
+    ///
+    /// This is the normalization applied by [`QueryOptions::stable_order`](crate::QueryOptions::stable_order);
+    /// call it directly to compare result sets from queries that do not specify an `ORDER BY`.
+    pub fn sort_rows(&mut self) {
+        self.results.sort_by(|a, b| {
+            a.rows
+                .iter()
+                .zip(b.rows.iter())
+                .map(|(left, right)| left.total_cmp(right))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.rows.len().cmp(&b.rows.len()))
+        });
+    }
 }
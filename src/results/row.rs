@@ -1,3 +1,5 @@
+use super::FromRowValues;
+use crate::error::SqlMiddlewareDbError;
 use crate::types::RowValues;
 
 /// A row from a database query result
@@ -96,4 +98,38 @@ impl CustomDbRow {
     pub fn get_by_index(&self, index: usize) -> Option<&RowValues> {
         self.rows.get(index)
     }
+
+    /// Read a nullable column as `Option<T>`: `None` for NULL, otherwise the value converted
+    /// with [`FromRowValues`].
+    ///
+    /// ```rust,no_run
+    /// # use sql_middleware::prelude::*;
+    /// # fn demo(row: &CustomDbRow) -> Result<(), SqlMiddlewareDbError> {
+    /// let nickname: Option<String> = row.get_opt("nickname")?;
+    /// # let _ = nickname;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if there is no such column or its value
+    /// is not a `T`.
+    pub fn get_opt<T: FromRowValues>(
+        &self,
+        column_name: &str,
+    ) -> Result<Option<T>, SqlMiddlewareDbError> {
+        let value = self.get(column_name).ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError(format!("no column named {column_name:?}"))
+        })?;
+        if value.is_null() {
+            return Ok(None);
+        }
+        T::from_row_values(value)
+            .map(Some)
+            .map_err(|err| match err {
+                SqlMiddlewareDbError::ExecutionError(msg) => {
+                    SqlMiddlewareDbError::ExecutionError(format!("column {column_name:?}: {msg}"))
+                }
+                other => other,
+            })
+    }
 }
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::types::RowValues;
+
+/// A Rust type read out of a [`RowValues`], used by [`CustomDbRow::get_opt`] and
+/// `QueryBuilder::select_scalar`.
+///
+/// Conversions accept what every backend returns for the type: `bool` also reads SQLite's
+/// `0`/`1` integers, `NaiveDateTime` also reads SQLite and Turso timestamp text, and `f64` also
+/// reads integers.
+///
+/// [`CustomDbRow::get_opt`]: crate::CustomDbRow::get_opt
+pub trait FromRowValues: Sized {
+    /// Convert `value`. [`RowValues::Null`] is a mismatch for every type but `Option<T>`.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ExecutionError` if `value` holds another type.
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError>;
+}
+
+fn mismatch(expected: &str, value: &RowValues) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ExecutionError(format!(
+        "expected {expected}, found {}",
+        value.variant_name()
+    ))
+}
+
+impl FromRowValues for RowValues {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        Ok(value.clone())
+    }
+}
+
+impl<T: FromRowValues> FromRowValues for Option<T> {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::from_row_values(value).map(Some)
+        }
+    }
+}
+
+impl FromRowValues for i64 {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        value
+            .as_int()
+            .copied()
+            .ok_or_else(|| mismatch("Int", value))
+    }
+}
+
+impl FromRowValues for i32 {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        let int = i64::from_row_values(value)?;
+        i32::try_from(int).map_err(|_| {
+            SqlMiddlewareDbError::ExecutionError(format!("{int} does not fit in an i32"))
+        })
+    }
+}
+
+impl FromRowValues for f64 {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        match value {
+            RowValues::Float(float) => Ok(*float),
+            #[allow(clippy::cast_precision_loss)]
+            RowValues::Int(int) => Ok(*int as f64),
+            _ => Err(mismatch("Float", value)),
+        }
+    }
+}
+
+impl FromRowValues for bool {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        value
+            .as_bool()
+            .copied()
+            .ok_or_else(|| mismatch("Bool", value))
+    }
+}
+
+impl FromRowValues for String {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        value
+            .as_text()
+            .map(str::to_string)
+            .ok_or_else(|| mismatch("Text", value))
+    }
+}
+
+impl FromRowValues for Arc<str> {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        match value {
+            RowValues::Text(text) => Ok(Arc::clone(text)),
+            _ => Err(mismatch("Text", value)),
+        }
+    }
+}
+
+impl FromRowValues for Vec<u8> {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        value
+            .as_blob()
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| mismatch("Blob", value))
+    }
+}
+
+impl FromRowValues for NaiveDateTime {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        value
+            .as_timestamp()
+            .ok_or_else(|| mismatch("Timestamp", value))
+    }
+}
+
+/// Reads JSON columns, and JSON text stored in text columns.
+#[cfg(feature = "json")]
+impl FromRowValues for serde_json::Value {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        match value {
+            RowValues::JSON(json) => Ok(json.clone()),
+            RowValues::Text(text) => serde_json::from_str(text).map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("invalid JSON text: {e}"))
+            }),
+            _ => Err(mismatch("JSON", value)),
+        }
+    }
+}
+
+/// Reads the text form Postgres, `SQLite` and Turso return for UUID columns.
+#[cfg(feature = "uuid")]
+impl FromRowValues for uuid::Uuid {
+    fn from_row_values(value: &RowValues) -> Result<Self, SqlMiddlewareDbError> {
+        let text = value.as_text().ok_or_else(|| mismatch("Text", value))?;
+        uuid::Uuid::parse_str(text)
+            .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("invalid UUID text: {e}")))
+    }
+}
@@ -0,0 +1,43 @@
+//! The few places the `SQLite` and Turso backends touch the async runtime directly.
+//!
+//! Everything else on those paths is executor-neutral: `SQLite` work runs on dedicated worker
+//! threads and replies over `tokio::sync::oneshot`, which (like the rest of `tokio::sync`) needs no
+//! tokio runtime. The remaining runtime calls go through this module so they degrade gracefully
+//! when no multi-threaded tokio runtime is present. bb8 still spawns its pool tasks on tokio, and
+//! [`SystemClock`](crate::clock::SystemClock) uses tokio timers, so pools need a tokio runtime;
+//! supply your own [`Clock`](crate::clock::Clock) to keep timers off it.
+//!
+//! There is no `async-std` or `smol` feature. The pools are bb8, which spawns its reaper and
+//! connection tasks with `tokio::spawn`, and the Postgres and SQL Server drivers (tokio-postgres,
+//! tiberius over `tokio-util` compat) need tokio I/O, so a second runtime would only cover the
+//! worker-thread half of the `SQLite` path.
+
+#[cfg(feature = "sqlite")]
+pub(crate) use tokio::sync::oneshot;
+
+/// Run blocking `f` from code that may be executing inside an async task.
+///
+/// On a multi-threaded tokio runtime the worker thread is handed off with `block_in_place`.
+/// Anywhere else (a current-thread runtime, another executor, a plain thread) `f` runs inline.
+#[cfg(feature = "sqlite")]
+pub(crate) fn block_in_place<R>(f: impl FnOnce() -> R) -> R {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+/// Spawn `fut` on the current tokio runtime without awaiting it; drop it if there is none.
+#[cfg(feature = "turso")]
+pub(crate) fn spawn_detached<F>(fut: F)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(fut);
+    }
+}
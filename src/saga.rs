@@ -0,0 +1,164 @@
+//! Best-effort sagas across several pools.
+//!
+//! No shared transaction spans two databases, so a [`Saga`] runs each step in its own connection
+//! (and whatever transaction the step opens) and remembers how to undo it. If a later step fails,
+//! the completed steps are compensated in reverse order and the failure is returned as
+//! [`SqlMiddlewareDbError::SagaAborted`].
+//!
+//! This is not atomic: other sessions can see a step's effects before it is compensated, and a
+//! compensation that fails (or a process that dies mid-saga) leaves work behind.
+//! `SagaAborted::compensation_failures` names the steps that need manual repair. Compensations
+//! should be idempotent so they can be retried.
+//!
+//! ```rust,no_run
+//! use sql_middleware::prelude::*;
+//! use sql_middleware::saga::Saga;
+//!
+//! # async fn demo(orders: &ConfigAndPool, billing: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+//! let mut saga = Saga::new();
+//! saga.step(
+//!     "reserve order",
+//!     orders,
+//!     async |conn| conn.query("INSERT INTO orders (id) VALUES (7)").dml().await,
+//!     |mut conn, _| async move {
+//!         conn.query("DELETE FROM orders WHERE id = 7").dml().await.map(|_| ())
+//!     },
+//! )
+//! .await?;
+//! saga.step(
+//!     "charge card",
+//!     billing,
+//!     async |conn| conn.query("INSERT INTO charges (order_id) VALUES (7)").dml().await,
+//!     |mut conn, _| async move {
+//!         conn.query("DELETE FROM charges WHERE order_id = 7").dml().await.map(|_| ())
+//!     },
+//! )
+//! .await?;
+//! saga.complete();
+//! # Ok(()) }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::{ConfigAndPool, MiddlewarePoolConnection};
+
+type CompensationFuture = Pin<Box<dyn Future<Output = Result<(), SqlMiddlewareDbError>> + Send>>;
+type Compensate = Box<dyn FnOnce(MiddlewarePoolConnection) -> CompensationFuture + Send>;
+
+/// A completed step and how to undo it.
+struct Completed {
+    name: String,
+    cap: ConfigAndPool,
+    compensate: Compensate,
+}
+
+/// Steps run so far, each with the compensation that undoes it.
+///
+/// Finish with [`Saga::complete`] once every step has succeeded, or [`Saga::abort`] to undo the
+/// completed steps for a reason outside any step. Dropping a saga with completed steps runs no
+/// compensations.
+#[must_use = "drop a saga only through `complete` or `abort`"]
+#[derive(Default)]
+pub struct Saga {
+    completed: Vec<Completed>,
+    aborted: bool,
+}
+
+impl fmt::Debug for Saga {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Saga")
+            .field("completed", &self.completed_steps().collect::<Vec<_>>())
+            .field("aborted", &self.aborted)
+            .finish()
+    }
+}
+
+impl Saga {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names of the steps that have completed, oldest first.
+    pub fn completed_steps(&self) -> impl Iterator<Item = &str> {
+        self.completed.iter().map(|step| step.name.as_str())
+    }
+
+    /// Run `action` on a connection from `cap` and, if it succeeds, register `compensate` to undo
+    /// it.
+    ///
+    /// `compensate` receives a fresh connection from the same pool and a clone of the step's
+    /// result (e.g. the id of the row to delete). If `action` fails, or no connection can be
+    /// checked out for it, the completed steps are compensated newest first before this returns.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::SagaAborted` wrapping the step's error after compensating,
+    /// or `SqlMiddlewareDbError::ExecutionError` if the saga was already aborted.
+    pub async fn step<T, A, C, Fut>(
+        &mut self,
+        name: &str,
+        cap: &ConfigAndPool,
+        action: A,
+        compensate: C,
+    ) -> Result<T, SqlMiddlewareDbError>
+    where
+        T: Clone + Send + 'static,
+        A: AsyncFnOnce(&mut MiddlewarePoolConnection) -> Result<T, SqlMiddlewareDbError>,
+        C: FnOnce(MiddlewarePoolConnection, T) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), SqlMiddlewareDbError>> + Send + 'static,
+    {
+        if self.aborted {
+            return Err(SqlMiddlewareDbError::ExecutionError(format!(
+                "saga step `{name}` started after the saga was aborted"
+            )));
+        }
+        let result = match cap.get_connection().await {
+            Ok(mut conn) => action(&mut conn).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(value) => {
+                let arg = value.clone();
+                self.completed.push(Completed {
+                    name: name.to_string(),
+                    cap: cap.clone(),
+                    compensate: Box::new(move |conn| Box::pin(compensate(conn, arg))),
+                });
+                Ok(value)
+            }
+            Err(err) => Err(self.unwind(name, err).await),
+        }
+    }
+
+    /// Compensate every completed step, newest first, because of `reason`.
+    ///
+    /// Use this when the saga has to stop for a reason outside any step, such as a failed
+    /// business check between steps. Returns the `SagaAborted` error to hand back to the caller.
+    pub async fn abort(mut self, reason: SqlMiddlewareDbError) -> SqlMiddlewareDbError {
+        self.unwind("abort", reason).await
+    }
+
+    /// Finish successfully, discarding the compensations.
+    pub fn complete(self) {}
+
+    async fn unwind(&mut self, step: &str, source: SqlMiddlewareDbError) -> SqlMiddlewareDbError {
+        self.aborted = true;
+        let mut compensation_failures = Vec::new();
+        while let Some(done) = self.completed.pop() {
+            let result = match done.cap.get_connection().await {
+                Ok(conn) => (done.compensate)(conn).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                compensation_failures.push((done.name, err));
+            }
+        }
+        SqlMiddlewareDbError::SagaAborted {
+            step: step.to_string(),
+            source: Box::new(source),
+            compensation_failures,
+        }
+    }
+}
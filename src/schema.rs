@@ -0,0 +1,496 @@
+//! Schema introspection and drift detection across backends.
+//!
+//! [`introspect`] lists the tables and columns of a connection's current schema. [`diff_schemas`]
+//! compares two of those snapshots and [`drift`] does both for two pools, which catches the
+//! classic "SQLite locally, Postgres in prod" mismatch before it shows up as a runtime error.
+//! [`foreign_keys`] lists the references between tables.
+//!
+//! Declared types are normalized to a [`ColumnType`] so that `INTEGER` on SQLite and `bigint` on
+//! Postgres count as the same thing. Names are compared case-insensitively, because Postgres
+//! folds unquoted identifiers to lower case while SQLite keeps them as written.
+
+use crate::middleware::{ConfigAndPool, DatabaseType, MiddlewarePoolConnection};
+use crate::middleware::{RowValues, SqlMiddlewareDbError};
+
+/// Backend-neutral family of a column's declared type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ColumnType {
+    /// `INTEGER`, `bigint`, `int`, `serial`, ...
+    Integer,
+    /// `REAL`, `double precision`, `float`, ...
+    Float,
+    /// `numeric`, `decimal`, `money`.
+    Numeric,
+    /// `TEXT`, `varchar`, `nvarchar`, `uuid`, ...
+    Text,
+    /// `BLOB`, `bytea`, `varbinary`, ...
+    Blob,
+    /// `BOOLEAN`, `bool`, `bit`.
+    Boolean,
+    /// `TIMESTAMP`, `timestamp without time zone`, `datetime2`, ...
+    Timestamp,
+    /// `json`, `jsonb`.
+    Json,
+    /// Anything else, as the lower-cased declared type without its length or precision.
+    Other(String),
+}
+
+impl ColumnType {
+    /// Classify a declared type such as `VARCHAR(255)` or `timestamp with time zone`.
+    #[must_use]
+    pub fn from_declared(declared: &str) -> Self {
+        let lowered = declared.trim().to_ascii_lowercase();
+        let base = lowered
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        match base.as_str() {
+            "integer" | "int" | "int2" | "int4" | "int8" | "smallint" | "bigint" | "tinyint"
+            | "mediumint" | "serial" | "smallserial" | "bigserial" => ColumnType::Integer,
+            "real" | "float" | "float4" | "float8" | "double" | "double precision" => {
+                ColumnType::Float
+            }
+            "numeric" | "decimal" | "money" | "smallmoney" => ColumnType::Numeric,
+            "text" | "varchar" | "char" | "character" | "character varying" | "nvarchar"
+            | "nchar" | "ntext" | "clob" | "string" | "citext" | "uuid" | "uniqueidentifier" => {
+                ColumnType::Text
+            }
+            "blob" | "bytea" | "binary" | "varbinary" | "image" => ColumnType::Blob,
+            "boolean" | "bool" | "bit" => ColumnType::Boolean,
+            "datetime" | "datetime2" | "smalldatetime" | "datetimeoffset" => ColumnType::Timestamp,
+            "json" | "jsonb" => ColumnType::Json,
+            _ if base.starts_with("timestamp") => ColumnType::Timestamp,
+            _ => ColumnType::Other(base),
+        }
+    }
+}
+
+/// One column of an introspected table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    /// The type as the backend reports it.
+    pub declared_type: String,
+    /// [`declared_type`](Column::declared_type) normalized for comparison across backends.
+    pub column_type: ColumnType,
+    /// Whether the column accepts NULL. `SQLite` primary key columns count as `NOT NULL`.
+    pub nullable: bool,
+}
+
+/// One introspected table, with its columns in declaration order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    /// Look up a column by name, ignoring case.
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns
+            .iter()
+            .find(|column| column.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The base tables of one schema, sorted by name. Views are not included.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schema {
+    pub tables: Vec<Table>,
+}
+
+impl Schema {
+    /// Look up a table by name, ignoring case.
+    #[must_use]
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// One column of a foreign key: `table.column` references `referenced_table.referenced_column`.
+///
+/// Composite keys are reported as one entry per column pair. On `SQLite` and Turso,
+/// `referenced_column` is empty when the key names only the parent table and so references its
+/// primary key implicitly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+}
+
+/// A column whose type or nullability differs between the two schemas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMismatch {
+    pub table: String,
+    /// The column as the left-hand schema has it.
+    pub left: Column,
+    /// The column as the right-hand schema has it.
+    pub right: Column,
+}
+
+/// Structural differences between two schemas, from the left-hand side's point of view.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaDrift {
+    /// Tables in the left schema but not the right.
+    pub missing_tables: Vec<String>,
+    /// Tables in the right schema but not the left.
+    pub extra_tables: Vec<String>,
+    /// `(table, column)` pairs in the left schema but not the right.
+    pub missing_columns: Vec<(String, String)>,
+    /// `(table, column)` pairs in the right schema but not the left.
+    pub extra_columns: Vec<(String, String)>,
+    /// Columns on both sides whose [`ColumnType`] differs.
+    pub type_mismatches: Vec<ColumnMismatch>,
+    /// Columns on both sides where one accepts NULL and the other does not.
+    pub nullability_mismatches: Vec<ColumnMismatch>,
+}
+
+impl SchemaDrift {
+    /// `true` when the two schemas have the same structure.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.missing_tables.is_empty()
+            && self.extra_tables.is_empty()
+            && self.missing_columns.is_empty()
+            && self.extra_columns.is_empty()
+            && self.type_mismatches.is_empty()
+            && self.nullability_mismatches.is_empty()
+    }
+}
+
+/// List the base tables and columns of the connection's current schema.
+///
+/// That is `current_schema()` on Postgres, `SCHEMA_NAME()` on SQL Server, and the `main`
+/// database on `SQLite` and Turso (attached databases are not included).
+///
+/// # Errors
+/// Returns the backend's error if a catalog query fails.
+pub async fn introspect(
+    conn: &mut MiddlewarePoolConnection,
+) -> Result<Schema, SqlMiddlewareDbError> {
+    let mut tables: Vec<Table> = match conn.database_type() {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => {
+            let rows = conn
+                .query(
+                    "SELECT c.table_name::text AS table_name, c.column_name::text AS column_name, \
+                     c.data_type::text AS data_type, c.is_nullable::text AS is_nullable \
+                     FROM information_schema.columns c \
+                     JOIN information_schema.tables t \
+                       ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+                     WHERE c.table_schema = current_schema() AND t.table_type = 'BASE TABLE' \
+                     ORDER BY c.table_name, c.ordinal_position",
+                )
+                .select()
+                .await?;
+            group_catalog_rows(&rows)?
+        }
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => {
+            let rows = conn
+                .query(
+                    "SELECT c.TABLE_NAME AS table_name, c.COLUMN_NAME AS column_name, \
+                     c.DATA_TYPE AS data_type, c.IS_NULLABLE AS is_nullable \
+                     FROM INFORMATION_SCHEMA.COLUMNS c \
+                     JOIN INFORMATION_SCHEMA.TABLES t \
+                       ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME \
+                     WHERE c.TABLE_SCHEMA = SCHEMA_NAME() AND t.TABLE_TYPE = 'BASE TABLE' \
+                     ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION",
+                )
+                .select()
+                .await?;
+            group_catalog_rows(&rows)?
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseType::Sqlite => introspect_sqlite_like(conn).await?,
+        #[cfg(feature = "turso")]
+        DatabaseType::Turso => introspect_sqlite_like(conn).await?,
+    };
+    tables.sort_by_key(|table| table.name.to_lowercase());
+    Ok(Schema { tables })
+}
+
+/// List the foreign keys declared on the base tables of the connection's current schema.
+///
+/// The schema is chosen as in [`introspect`]. Entries are sorted by table name.
+///
+/// # Errors
+/// Returns the backend's error if a catalog query fails.
+pub async fn foreign_keys(
+    conn: &mut MiddlewarePoolConnection,
+) -> Result<Vec<ForeignKey>, SqlMiddlewareDbError> {
+    let mut keys: Vec<ForeignKey> = match conn.database_type() {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => {
+            let rows = conn
+                .query(
+                    "SELECT k.table_name::text AS table_name, k.column_name::text AS column_name, \
+                     r.table_name::text AS referenced_table, \
+                     r.column_name::text AS referenced_column \
+                     FROM information_schema.referential_constraints c \
+                     JOIN information_schema.key_column_usage k \
+                       ON k.constraint_schema = c.constraint_schema \
+                      AND k.constraint_name = c.constraint_name \
+                     JOIN information_schema.key_column_usage r \
+                       ON r.constraint_schema = c.unique_constraint_schema \
+                      AND r.constraint_name = c.unique_constraint_name \
+                      AND r.ordinal_position = k.position_in_unique_constraint \
+                     WHERE c.constraint_schema = current_schema() \
+                     ORDER BY k.table_name, k.constraint_name, k.ordinal_position",
+                )
+                .select()
+                .await?;
+            catalog_foreign_keys(&rows)?
+        }
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => {
+            let rows = conn
+                .query(
+                    "SELECT OBJECT_NAME(f.parent_object_id) AS table_name, \
+                     COL_NAME(f.parent_object_id, f.parent_column_id) AS column_name, \
+                     OBJECT_NAME(f.referenced_object_id) AS referenced_table, \
+                     COL_NAME(f.referenced_object_id, f.referenced_column_id) AS referenced_column \
+                     FROM sys.foreign_key_columns f \
+                     WHERE OBJECT_SCHEMA_NAME(f.parent_object_id) = SCHEMA_NAME() \
+                     ORDER BY table_name, f.constraint_object_id, f.constraint_column_id",
+                )
+                .select()
+                .await?;
+            catalog_foreign_keys(&rows)?
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseType::Sqlite => foreign_keys_sqlite_like(conn).await?,
+        #[cfg(feature = "turso")]
+        DatabaseType::Turso => foreign_keys_sqlite_like(conn).await?,
+    };
+    keys.sort_by_key(|key| key.table.to_lowercase());
+    Ok(keys)
+}
+
+/// Compare two schemas and describe how `right` differs from `left`.
+///
+/// Tables and columns are matched by name, ignoring case. Columns match when their
+/// [`ColumnType`]s are equal, whatever the declared spelling.
+///
+/// # Examples
+/// ```rust
+/// use sql_middleware::schema::{Column, ColumnType, Schema, Table, diff_schemas};
+///
+/// let column = |name: &str, declared: &str| Column {
+///     name: name.into(),
+///     declared_type: declared.into(),
+///     column_type: ColumnType::from_declared(declared),
+///     nullable: true,
+/// };
+/// let local = Schema {
+///     tables: vec![Table { name: "users".into(), columns: vec![column("id", "INTEGER")] }],
+/// };
+/// let prod = Schema {
+///     tables: vec![Table { name: "users".into(), columns: vec![column("id", "bigint")] }],
+/// };
+/// assert!(diff_schemas(&local, &prod).is_empty());
+/// ```
+#[must_use]
+pub fn diff_schemas(left: &Schema, right: &Schema) -> SchemaDrift {
+    let mut drift = SchemaDrift::default();
+    for table in &left.tables {
+        let Some(other) = right.table(&table.name) else {
+            drift.missing_tables.push(table.name.clone());
+            continue;
+        };
+        for column in &table.columns {
+            let Some(other_column) = other.column(&column.name) else {
+                drift
+                    .missing_columns
+                    .push((table.name.clone(), column.name.clone()));
+                continue;
+            };
+            let mismatch = || ColumnMismatch {
+                table: table.name.clone(),
+                left: column.clone(),
+                right: other_column.clone(),
+            };
+            if column.column_type != other_column.column_type {
+                drift.type_mismatches.push(mismatch());
+            }
+            if column.nullable != other_column.nullable {
+                drift.nullability_mismatches.push(mismatch());
+            }
+        }
+        for column in &other.columns {
+            if table.column(&column.name).is_none() {
+                drift
+                    .extra_columns
+                    .push((table.name.clone(), column.name.clone()));
+            }
+        }
+    }
+    for table in &right.tables {
+        if left.table(&table.name).is_none() {
+            drift.extra_tables.push(table.name.clone());
+        }
+    }
+    drift
+}
+
+/// Introspect both pools and report how `right`'s schema differs from `left`'s.
+///
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+/// use sql_middleware::schema::drift;
+///
+/// # async fn demo(local: &ConfigAndPool, prod: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+/// let drift = drift(local, prod).await?;
+/// for (table, column) in &drift.missing_columns {
+///     eprintln!("prod is missing {table}.{column}");
+/// }
+/// assert!(drift.is_empty(), "{drift:#?}");
+/// # Ok(()) }
+/// ```
+///
+/// # Errors
+/// Returns an error if either pool cannot hand out a connection or introspection fails.
+pub async fn drift(
+    left: &ConfigAndPool,
+    right: &ConfigAndPool,
+) -> Result<SchemaDrift, SqlMiddlewareDbError> {
+    let left_schema = introspect(&mut left.get_connection().await?).await?;
+    let right_schema = introspect(&mut right.get_connection().await?).await?;
+    Ok(diff_schemas(&left_schema, &right_schema))
+}
+
+#[cfg(any(feature = "sqlite", feature = "turso"))]
+async fn introspect_sqlite_like(
+    conn: &mut MiddlewarePoolConnection,
+) -> Result<Vec<Table>, SqlMiddlewareDbError> {
+    let names = conn
+        .query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' \
+             ORDER BY name",
+        )
+        .select()
+        .await?;
+    let db_type = conn.database_type();
+    let mut tables = Vec::with_capacity(names.results.len());
+    for row in &names.results {
+        let name = text(row.get("name"), "name")?;
+        let pragma = format!(
+            "PRAGMA table_info({})",
+            crate::ident::quote(&name, &db_type)?
+        );
+        let info = conn.query(&pragma).select().await?;
+        let mut columns = Vec::with_capacity(info.results.len());
+        for column in &info.results {
+            let declared_type = text(column.get("type"), "type")?;
+            let not_null = int(column.get("notnull"), "notnull")? != 0;
+            let primary_key = int(column.get("pk"), "pk")? != 0;
+            columns.push(Column {
+                name: text(column.get("name"), "name")?,
+                column_type: ColumnType::from_declared(&declared_type),
+                declared_type,
+                nullable: !not_null && !primary_key,
+            });
+        }
+        tables.push(Table { name, columns });
+    }
+    Ok(tables)
+}
+
+#[cfg(any(feature = "sqlite", feature = "turso"))]
+async fn foreign_keys_sqlite_like(
+    conn: &mut MiddlewarePoolConnection,
+) -> Result<Vec<ForeignKey>, SqlMiddlewareDbError> {
+    let db_type = conn.database_type();
+    let mut keys = Vec::new();
+    for table in introspect_sqlite_like(conn).await? {
+        let pragma = format!(
+            "PRAGMA foreign_key_list({})",
+            crate::ident::quote(&table.name, &db_type)?
+        );
+        let list = conn.query(&pragma).select().await?;
+        for row in &list.results {
+            keys.push(ForeignKey {
+                table: table.name.clone(),
+                column: text(row.get("from"), "from")?,
+                referenced_table: text(row.get("table"), "table")?,
+                referenced_column: text(row.get("to"), "to")?,
+            });
+        }
+    }
+    Ok(keys)
+}
+
+/// Read `table_name`/`column_name`/`referenced_table`/`referenced_column` catalog rows.
+#[cfg(any(feature = "postgres", feature = "mssql"))]
+fn catalog_foreign_keys(
+    rows: &crate::middleware::ResultSet,
+) -> Result<Vec<ForeignKey>, SqlMiddlewareDbError> {
+    rows.results
+        .iter()
+        .map(|row| {
+            Ok(ForeignKey {
+                table: text(row.get("table_name"), "table_name")?,
+                column: text(row.get("column_name"), "column_name")?,
+                referenced_table: text(row.get("referenced_table"), "referenced_table")?,
+                referenced_column: text(row.get("referenced_column"), "referenced_column")?,
+            })
+        })
+        .collect()
+}
+
+/// Group `information_schema`-style rows (one per column, ordered by table) into tables.
+#[cfg(any(feature = "postgres", feature = "mssql"))]
+fn group_catalog_rows(
+    rows: &crate::middleware::ResultSet,
+) -> Result<Vec<Table>, SqlMiddlewareDbError> {
+    let mut tables: Vec<Table> = Vec::new();
+    for row in &rows.results {
+        let table = text(row.get("table_name"), "table_name")?;
+        let declared_type = text(row.get("data_type"), "data_type")?;
+        let column = Column {
+            name: text(row.get("column_name"), "column_name")?,
+            column_type: ColumnType::from_declared(&declared_type),
+            declared_type,
+            nullable: text(row.get("is_nullable"), "is_nullable")?.eq_ignore_ascii_case("YES"),
+        };
+        match tables.last_mut() {
+            Some(last) if last.name == table => last.columns.push(column),
+            _ => tables.push(Table {
+                name: table,
+                columns: vec![column],
+            }),
+        }
+    }
+    Ok(tables)
+}
+
+fn text(value: Option<&RowValues>, column: &str) -> Result<String, SqlMiddlewareDbError> {
+    match value {
+        Some(RowValues::Text(text)) => Ok(text.to_string()),
+        // SQLite reports an untyped column's declared type as NULL through some drivers.
+        Some(RowValues::Null) => Ok(String::new()),
+        other => Err(catalog_error(column, other)),
+    }
+}
+
+#[cfg(any(feature = "sqlite", feature = "turso"))]
+fn int(value: Option<&RowValues>, column: &str) -> Result<i64, SqlMiddlewareDbError> {
+    match value {
+        Some(RowValues::Int(value)) => Ok(*value),
+        Some(RowValues::Bool(value)) => Ok(i64::from(*value)),
+        other => Err(catalog_error(column, other)),
+    }
+}
+
+fn catalog_error(column: &str, value: Option<&RowValues>) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ExecutionError(format!(
+        "unexpected catalog value for `{column}`: {value:?}"
+    ))
+}
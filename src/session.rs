@@ -0,0 +1,271 @@
+//! Per-transaction session context for row-level security.
+//!
+//! RLS policies usually read the tenant (or user) from a session variable. Setting that variable
+//! on a pooled connection is dangerous: if it outlives the request, the next borrower of the
+//! connection inherits it. [`MiddlewarePoolConnection::with_session_context`] scopes the settings
+//! to one transaction instead and clears them before the connection can be reused.
+//!
+//! - Postgres: each setting is applied with `set_config(name, value, true)`, the function form of
+//!   `SET LOCAL`, so it ends with the transaction however the transaction ends. If the future is
+//!   dropped mid-scope, the transaction is rolled back when the connection is next used.
+//! - SQL Server: each setting is applied with `sp_set_session_context` and reset to `NULL` once the
+//!   transaction has committed or rolled back, since SQL Server session context is not
+//!   transactional. A scope that is cancelled before it finishes leaves both the transaction and
+//!   the settings on the connection, like an unfinished [`mssql::Tx`](crate::mssql::Tx).
+//!
+//! `SQLite` and Turso have no session variables and return
+//! [`SqlMiddlewareDbError::Unimplemented`].
+
+use crate::error::SqlMiddlewareDbError;
+use crate::pool::MiddlewarePoolConnection;
+use crate::results::ResultSet;
+use crate::tx_outcome::TxOutcome;
+use crate::types::RowValues;
+
+#[cfg(feature = "mssql")]
+use crate::mssql;
+#[cfg(feature = "postgres")]
+use crate::postgres;
+
+/// SQL Server session context keys set through [`MiddlewarePoolConnection::with_session_context`]
+/// in this process, cleared by a session reset since SQL Server cannot list them.
+#[cfg(feature = "mssql")]
+static MSSQL_KEYS: std::sync::Mutex<std::collections::BTreeSet<String>> =
+    std::sync::Mutex::new(std::collections::BTreeSet::new());
+
+#[cfg(feature = "mssql")]
+fn mssql_keys() -> std::sync::MutexGuard<'static, std::collections::BTreeSet<String>> {
+    match MSSQL_KEYS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Transaction handed to the closure of
+/// [`MiddlewarePoolConnection::with_session_context`], with the settings in effect.
+///
+/// Run statements through its methods; queries issued on the pooled connection itself would run
+/// in their own transaction without the settings.
+pub enum SessionTx<'c> {
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::Tx<'c>),
+    #[cfg(feature = "mssql")]
+    Mssql(mssql::Tx<'c>),
+}
+
+impl SessionTx<'_> {
+    /// Execute a batch of SQL statements inside the transaction.
+    ///
+    /// # Errors
+    /// Returns an error if execution fails.
+    pub async fn execute_batch(&mut self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            SessionTx::Postgres(tx) => tx.execute_batch(sql).await,
+            #[cfg(feature = "mssql")]
+            SessionTx::Mssql(tx) => tx.execute_batch(sql).await,
+        }
+    }
+
+    /// Execute a parameterized DML statement and return the rows affected.
+    ///
+    /// Placeholders are passed through untranslated (`$1` on Postgres, `@P1` on SQL Server).
+    ///
+    /// # Errors
+    /// Returns an error if parameter conversion or execution fails.
+    pub async fn execute_dml(
+        &mut self,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            SessionTx::Postgres(tx) => tx.execute_dml(sql, params).await,
+            #[cfg(feature = "mssql")]
+            SessionTx::Mssql(tx) => tx.execute_dml(sql, params).await,
+        }
+    }
+
+    /// Execute a parameterized SELECT and return its rows.
+    ///
+    /// Placeholders are passed through untranslated (`$1` on Postgres, `@P1` on SQL Server).
+    ///
+    /// # Errors
+    /// Returns an error if parameter conversion or execution fails.
+    pub async fn query(
+        &mut self,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            SessionTx::Postgres(tx) => tx.query(sql, params).await,
+            #[cfg(feature = "mssql")]
+            SessionTx::Mssql(tx) => tx.query(sql, params).await,
+        }
+    }
+
+    async fn apply(&mut self, settings: &[(String, String)]) -> Result<(), SqlMiddlewareDbError> {
+        for (name, value) in settings {
+            let params = [
+                RowValues::Text(name.as_str().into()),
+                RowValues::Text(value.as_str().into()),
+            ];
+            match self {
+                #[cfg(feature = "postgres")]
+                SessionTx::Postgres(tx) => {
+                    tx.query("SELECT set_config($1, $2, true)", &params).await?;
+                }
+                #[cfg(feature = "mssql")]
+                SessionTx::Mssql(tx) => {
+                    mssql_keys().insert(name.clone());
+                    tx.execute_dml(
+                        "EXEC sp_set_session_context @key = @P1, @value = @P2",
+                        &params,
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn commit(self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            SessionTx::Postgres(tx) => tx.commit().await,
+            #[cfg(feature = "mssql")]
+            SessionTx::Mssql(tx) => tx.commit().await,
+        }
+    }
+
+    async fn rollback(self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            SessionTx::Postgres(tx) => tx.rollback().await,
+            #[cfg(feature = "mssql")]
+            SessionTx::Mssql(tx) => tx.rollback().await,
+        }
+    }
+}
+
+impl MiddlewarePoolConnection {
+    /// Run `f` in a transaction with `settings` applied as session context, then clear them.
+    ///
+    /// The transaction commits when `f` returns `Ok` and rolls back when it returns `Err`; either
+    /// way the settings are gone before this returns, so the connection can go back to the pool.
+    /// Setting names and values are sent as bind parameters, never spliced into SQL. See the
+    /// [module docs](crate::session) for how each backend scopes the settings.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+    /// let mut conn = cap.get_connection().await?;
+    /// let invoices = conn
+    ///     .with_session_context([("app.tenant_id", "42")], async |tx| {
+    ///         tx.query("SELECT id, total FROM invoices", &[]).await
+    ///     })
+    ///     .await?;
+    /// # let _ = invoices;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` on backends without session variables, any
+    /// error from beginning the transaction or applying a setting, the error returned by `f`, or
+    /// an error from clearing the settings or committing.
+    pub async fn with_session_context<I, K, V, F, R>(
+        &mut self,
+        settings: I,
+        f: F,
+    ) -> Result<R, SqlMiddlewareDbError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+        F: AsyncFnOnce(&mut SessionTx<'_>) -> Result<R, SqlMiddlewareDbError>,
+    {
+        let settings: Vec<(String, String)> = settings
+            .into_iter()
+            .map(|(name, value)| (name.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+
+        let mut tx = match self {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { .. } => {
+                SessionTx::Postgres(self.begin_postgres_transaction().await?)
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { .. } => {
+                SessionTx::Mssql(self.begin_mssql_transaction().await?)
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(SqlMiddlewareDbError::Unimplemented(
+                    "session context requires a Postgres or SQL Server connection".to_string(),
+                ));
+            }
+        };
+
+        let result = match tx.apply(&settings).await {
+            Ok(()) => f(&mut tx).await,
+            Err(err) => Err(err),
+        };
+        let result = match result {
+            Ok(value) => tx.commit().await.map(|_| value),
+            Err(err) => {
+                let _ = tx.rollback().await;
+                Err(err)
+            }
+        };
+        self.record_result(&result);
+        let cleared = clear_session_context(self, &settings).await;
+        let value = result?;
+        cleared?;
+        Ok(value)
+    }
+}
+
+/// Undo what ending the transaction did not: SQL Server session context outlives transactions.
+async fn clear_session_context(
+    conn: &mut MiddlewarePoolConnection,
+    settings: &[(String, String)],
+) -> Result<(), SqlMiddlewareDbError> {
+    clear_keys(conn, settings.iter().map(|(name, _)| name.as_str())).await
+}
+
+/// Reset every session context key this process has set on a SQL Server connection.
+#[cfg(feature = "mssql")]
+pub(crate) async fn clear_all_session_context(
+    conn: &mut MiddlewarePoolConnection,
+) -> Result<(), SqlMiddlewareDbError> {
+    let keys: Vec<String> = mssql_keys().iter().cloned().collect();
+    clear_keys(conn, keys.iter().map(String::as_str)).await
+}
+
+async fn clear_keys(
+    conn: &mut MiddlewarePoolConnection,
+    keys: impl Iterator<Item = &str>,
+) -> Result<(), SqlMiddlewareDbError> {
+    match conn {
+        #[cfg(feature = "mssql")]
+        MiddlewarePoolConnection::Mssql { conn, .. } => {
+            for name in keys {
+                mssql::execute_dml(
+                    conn,
+                    "EXEC sp_set_session_context @key = @P1, @value = NULL",
+                    &[RowValues::Text(name.into())],
+                )
+                .await?;
+            }
+            Ok(())
+        }
+        #[allow(unreachable_patterns)]
+        _ => {
+            let _ = keys;
+            Ok(())
+        }
+    }
+}
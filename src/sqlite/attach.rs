@@ -0,0 +1,56 @@
+//! `ATTACH DATABASE` support for pooled `SQLite` connections.
+
+use serde::{Deserialize, Serialize};
+
+use crate::middleware::SqlMiddlewareDbError;
+
+/// An additional database file attached under `alias` on every pooled connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqliteAttachment {
+    pub alias: String,
+    pub path: String,
+}
+
+impl SqliteAttachment {
+    #[must_use]
+    pub fn new(alias: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            alias: alias.into(),
+            path: path.into(),
+        }
+    }
+}
+
+/// Attach `path` as schema `alias` on a raw connection.
+///
+/// The path is bound as a parameter; the alias is quoted as an identifier.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ConfigError` for an empty alias, or the `SQLite` error if the
+/// ATTACH fails (e.g., the alias is already in use).
+pub(crate) fn attach_database(
+    conn: &rusqlite::Connection,
+    alias: &str,
+    path: &str,
+) -> Result<(), SqlMiddlewareDbError> {
+    if alias.is_empty() {
+        return Err(SqlMiddlewareDbError::ConfigError(
+            "SQLite attach alias must not be empty".into(),
+        ));
+    }
+    let sql = format!("ATTACH DATABASE ?1 AS \"{}\"", alias.replace('"', "\"\""));
+    conn.execute(&sql, [path])
+        .map(|_| ())
+        .map_err(SqlMiddlewareDbError::SqliteError)
+}
+
+/// Attach every configured database, in configuration order.
+pub(crate) fn attach_all(
+    conn: &rusqlite::Connection,
+    attachments: &[SqliteAttachment],
+) -> Result<(), SqlMiddlewareDbError> {
+    for attachment in attachments {
+        attach_database(conn, &attachment.alias, &attachment.path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::backend::BackendApi;
+use crate::middleware::{
+    DatabaseType, MiddlewarePoolConnection, ResultSet, RowValues, SqlMiddlewareDbError, TxOutcome,
+};
+
+use super::connection::SqliteConnection;
+use super::params::Params;
+use super::transaction::{Prepared, Tx};
+
+/// [`BackendApi`] implementation for the `sqlite` module.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteBackend;
+
+impl BackendApi for SqliteBackend {
+    const DATABASE_TYPE: DatabaseType = DatabaseType::Sqlite;
+
+    type Connection = SqliteConnection;
+    type TxConnection = MiddlewarePoolConnection;
+    type Params<'a> = Params;
+    type Tx<'c> = Tx<'c>;
+    type Prepared = Prepared;
+
+    async fn execute_batch(
+        conn: &mut SqliteConnection,
+        sql: &str,
+    ) -> Result<(), SqlMiddlewareDbError> {
+        super::execute_batch(conn, sql).await
+    }
+
+    async fn execute_select(
+        conn: &mut SqliteConnection,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        super::execute_select(conn, sql, params).await
+    }
+
+    async fn execute_dml(
+        conn: &mut SqliteConnection,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        super::execute_dml(conn, sql, params).await
+    }
+
+    async fn begin_transaction(
+        conn: &mut MiddlewarePoolConnection,
+    ) -> Result<Self::Tx<'_>, SqlMiddlewareDbError> {
+        super::begin_transaction(conn).await
+    }
+
+    async fn prepare(tx: &mut Self::Tx<'_>, sql: &str) -> Result<Prepared, SqlMiddlewareDbError> {
+        tx.prepare(sql)
+    }
+
+    async fn execute_prepared(
+        tx: &mut Self::Tx<'_>,
+        prepared: &mut Prepared,
+        params: &[RowValues],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        tx.execute_prepared(prepared, params).await
+    }
+
+    async fn query_prepared(
+        tx: &mut Self::Tx<'_>,
+        prepared: &mut Prepared,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        tx.query_prepared(prepared, params).await
+    }
+
+    async fn commit(tx: Self::Tx<'_>) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        tx.commit().await
+    }
+
+    async fn rollback(tx: Self::Tx<'_>) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        tx.rollback().await
+    }
+}
@@ -1,11 +1,16 @@
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 
 use bb8::{ManageConnection, Pool, PooledConnection};
 use crossbeam_channel::{Sender, unbounded};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OwnedSemaphorePermit;
 
+use crate::config::{EnvReader, process_env};
 use crate::middleware::{ConfigAndPool, DatabaseType, MiddlewarePool, SqlMiddlewareDbError};
+use crate::sqlite::attach::{SqliteAttachment, attach_all};
+use crate::sqlite::queue::{QueueFullPolicy, WorkerQueue, WorkerQueueLimit};
 
 /// Type alias for the pooled `SQLite` connection wrapper.
 pub type SqlitePooledConnection = PooledConnection<'static, SqliteManager>;
@@ -37,14 +42,66 @@ enum SqliteWorkerMessage {
 pub struct SqliteWorker {
     sender: Sender<SqliteWorkerMessage>,
     broken: Arc<AtomicBool>,
+    /// Jobs sent to the worker that have not finished yet, including the one running.
+    pending: Arc<AtomicUsize>,
+    /// Optional cap on `pending` for caller operations.
+    queue: Option<WorkerQueue>,
     force_rollback_busy_for_tests: AtomicBool,
+    interrupt: Interrupt,
+    /// Values of [`SESSION_PRAGMAS`] once the connection was set up, restored by a full reset.
+    session_pragmas: Vec<(&'static str, i64)>,
+}
+
+/// Per-connection pragmas a caller may change and a full session reset puts back.
+const SESSION_PRAGMAS: &[&str] = &[
+    "automatic_index",
+    "busy_timeout",
+    "cache_size",
+    "cell_size_check",
+    "defer_foreign_keys",
+    "foreign_keys",
+    "ignore_check_constraints",
+    "query_only",
+    "recursive_triggers",
+    "reverse_unordered_selects",
+    "synchronous",
+    "temp_store",
+    "trusted_schema",
+];
+
+/// Current values of [`SESSION_PRAGMAS`] on `conn`.
+fn read_session_pragmas(
+    conn: &rusqlite::Connection,
+) -> Result<Vec<(&'static str, i64)>, SqlMiddlewareDbError> {
+    SESSION_PRAGMAS
+        .iter()
+        .map(|&name| {
+            conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))
+                .map(|value| (name, value))
+                .map_err(SqlMiddlewareDbError::SqliteError)
+        })
+        .collect()
+}
+
+/// `rusqlite::InterruptHandle`, which has no `Debug`.
+struct Interrupt(rusqlite::InterruptHandle);
+
+impl std::fmt::Debug for Interrupt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InterruptHandle")
+    }
 }
 
 impl SqliteWorker {
-    pub(crate) fn start(conn: rusqlite::Connection) -> Arc<Self> {
+    pub(crate) fn start(
+        conn: rusqlite::Connection,
+        queue_limit: Option<WorkerQueueLimit>,
+        session_pragmas: Vec<(&'static str, i64)>,
+    ) -> Arc<Self> {
         let (sender, receiver) = unbounded::<SqliteWorkerMessage>();
         let broken = Arc::new(AtomicBool::new(false));
         let broken_flag = Arc::clone(&broken);
+        let interrupt = Interrupt(conn.get_interrupt_handle());
         let mut conn = Some(conn);
         // Dedicated worker thread to service requests for this pooled connection.
         let _ = thread::Builder::new()
@@ -56,8 +113,10 @@ impl SqliteWorker {
                 for msg in &receiver {
                     match msg {
                         SqliteWorkerMessage::Execute(job) => {
-                            // If a job panics, mark the worker broken and exit to avoid
-                            // leaving the connection in an unknown state.
+                            // A job that panics has already handed its caller an error (see
+                            // `execute_then`) and re-raised the panic here. Mark the worker
+                            // broken and exit so the pool recycles the connection instead of
+                            // reusing one left in an unknown state.
                             let result =
                                 std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                                     job(&mut conn);
@@ -76,16 +135,64 @@ impl SqliteWorker {
         Arc::new(Self {
             sender,
             broken,
+            pending: Arc::new(AtomicUsize::new(0)),
+            queue: queue_limit.map(WorkerQueue::new),
             force_rollback_busy_for_tests: AtomicBool::new(false),
+            interrupt,
+            session_pragmas,
         })
     }
 
-    pub(crate) fn execute<F>(&self, func: F) -> Result<(), SqlMiddlewareDbError>
+    /// Pragma values this connection started with, as `(name, value)`.
+    pub(crate) fn session_pragmas(&self) -> &[(&'static str, i64)] {
+        &self.session_pragmas
+    }
+
+    /// Make the statement running on the worker, if any, fail with `SQLITE_INTERRUPT`.
+    pub(crate) fn interrupt(&self) {
+        self.interrupt.0.interrupt();
+    }
+
+    /// Run `func` on the worker, then pass its result to `deliver`.
+    ///
+    /// The job counts as pending from now until `func` returns or unwinds, so a caller woken by
+    /// `deliver` already sees it finished in [`pending_jobs`](Self::pending_jobs).
+    ///
+    /// If `func` panics, the worker is marked broken, `deliver` gets
+    /// [`SqlMiddlewareDbError::Other`] with the panic message, and the worker thread exits.
+    pub(crate) fn execute_then<F, R, D>(
+        &self,
+        func: F,
+        deliver: D,
+    ) -> Result<(), SqlMiddlewareDbError>
     where
-        F: FnOnce(&mut rusqlite::Connection) + Send + 'static,
+        F: FnOnce(&mut rusqlite::Connection) -> Result<R, SqlMiddlewareDbError> + Send + 'static,
+        D: FnOnce(Result<R, SqlMiddlewareDbError>) + Send + 'static,
     {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        // Dropped once the job ran, or with the message if the send fails.
+        let pending = PendingJob(Arc::clone(&self.pending));
+        let broken = Arc::clone(&self.broken);
         self.sender
-            .send(SqliteWorkerMessage::Execute(Box::new(func)))
+            .send(SqliteWorkerMessage::Execute(Box::new(move |conn| {
+                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| func(conn))) {
+                    Ok(result) => {
+                        drop(pending);
+                        deliver(result);
+                    }
+                    Err(payload) => {
+                        // Broken before the caller hears back, so the connection is never
+                        // handed out again once it is returned.
+                        broken.store(true, Ordering::Relaxed);
+                        drop(pending);
+                        deliver(Err(SqlMiddlewareDbError::Other(format!(
+                            "sqlite worker panicked: {}",
+                            panic_message(payload.as_ref())
+                        ))));
+                        std::panic::resume_unwind(payload);
+                    }
+                }
+            })))
             .map_err(|_| {
                 SqlMiddlewareDbError::ExecutionError(
                     "sqlite worker channel unexpectedly closed".into(),
@@ -93,21 +200,32 @@ impl SqliteWorker {
             })
     }
 
+    /// Take a slot in this worker's queue if it has a [`WorkerQueueLimit`]. Hold the permit until
+    /// the operation finishes.
+    pub(crate) async fn reserve(
+        &self,
+    ) -> Result<Option<OwnedSemaphorePermit>, SqlMiddlewareDbError> {
+        match &self.queue {
+            Some(queue) => queue.reserve().await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Jobs queued on or running in this worker. Non-zero on an idle pooled connection means a
+    /// caller stopped waiting (e.g. a timeout) while its statement was still running.
+    pub(crate) fn pending_jobs(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn execute_blocking<F, R>(&self, func: F) -> Result<R, SqlMiddlewareDbError>
     where
         F: FnOnce(&mut rusqlite::Connection) -> Result<R, SqlMiddlewareDbError> + Send + 'static,
         R: Send + 'static,
     {
         let (resp_tx, resp_rx) = crossbeam_channel::bounded(1);
-        self.sender
-            .send(SqliteWorkerMessage::Execute(Box::new(move |conn| {
-                let _ = resp_tx.send(func(conn));
-            })))
-            .map_err(|_| {
-                SqlMiddlewareDbError::ExecutionError(
-                    "sqlite worker channel unexpectedly closed".into(),
-                )
-            })?;
+        self.execute_then(func, move |result| {
+            let _ = resp_tx.send(result);
+        })?;
         resp_rx.recv().map_err(|_| {
             SqlMiddlewareDbError::ExecutionError(
                 "sqlite worker response channel unexpectedly closed".into(),
@@ -141,6 +259,24 @@ impl SqliteWorker {
     }
 }
 
+/// The message a panic was raised with, when it has one.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
+
+/// Decrements a worker's pending-job count when dropped.
+struct PendingJob(Arc<AtomicUsize>);
+
+impl Drop for PendingJob {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 impl Drop for SqliteWorker {
     fn drop(&mut self) {
         let _ = self.sender.send(SqliteWorkerMessage::Shutdown);
@@ -172,7 +308,7 @@ mod tests {
         .await
         .expect_err("worker panic should surface as an error");
         assert!(
-            err.to_string().contains("worker receive error"),
+            matches!(&err, SqlMiddlewareDbError::Other(message) if message.contains("panicked: boom")),
             "unexpected error for worker panic: {err}"
         );
         assert!(conn.is_broken(), "connection should be marked broken");
@@ -195,10 +331,17 @@ mod tests {
 }
 
 /// Options for configuring a `SQLite` pool.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SqliteOptions {
     pub db_path: String,
+    #[serde(default)]
     pub translate_placeholders: bool,
+    /// Databases attached on every pooled connection, in order.
+    #[serde(default)]
+    pub attachments: Vec<SqliteAttachment>,
+    /// Cap on operations queued per pooled connection; unbounded when `None`.
+    #[serde(default)]
+    pub queue_limit: Option<WorkerQueueLimit>,
 }
 
 impl SqliteOptions {
@@ -207,14 +350,65 @@ impl SqliteOptions {
         Self {
             db_path,
             translate_placeholders: false,
+            attachments: Vec::new(),
+            queue_limit: None,
         }
     }
 
+    /// Read options from environment variables named `{prefix}` followed by:
+    ///
+    /// - `DB_PATH` (required)
+    /// - `TRANSLATE_PLACEHOLDERS` (optional)
+    /// - `QUEUE_LIMIT` and `QUEUE_FULL_POLICY` (`reject` or `wait`, optional)
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env(prefix: &str) -> Result<Self, SqlMiddlewareDbError> {
+        Self::from_env_with(prefix, process_env)
+    }
+
+    /// Like [`from_env`](Self::from_env), looking variables up with `lookup` instead of in the
+    /// process environment.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env_with(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let mut env = EnvReader::new(prefix, lookup);
+        let mut opts = Self::new(env.required("DB_PATH"));
+        opts.translate_placeholders = env.flag("TRANSLATE_PLACEHOLDERS").unwrap_or(false);
+        let policy = env.parsed("QUEUE_FULL_POLICY").unwrap_or_default();
+        opts.queue_limit = env
+            .parsed("QUEUE_LIMIT")
+            .map(|max_pending| WorkerQueueLimit::new(max_pending, policy));
+        env.finish()?;
+        Ok(opts)
+    }
+
     #[must_use]
     pub fn with_translation(mut self, translate_placeholders: bool) -> Self {
         self.translate_placeholders = translate_placeholders;
         self
     }
+
+    /// Attach `path` as schema `alias` on every pooled connection.
+    #[must_use]
+    pub fn with_attachment(mut self, alias: impl Into<String>, path: impl Into<String>) -> Self {
+        self.attachments.push(SqliteAttachment::new(alias, path));
+        self
+    }
+
+    /// Allow at most `max_pending` operations queued on each pooled connection; `policy` decides
+    /// whether further operations fail or wait.
+    #[must_use]
+    pub fn with_queue_limit(mut self, max_pending: usize, policy: QueueFullPolicy) -> Self {
+        self.queue_limit = Some(WorkerQueueLimit::new(max_pending, policy));
+        self
+    }
 }
 
 /// Fluent builder for `SQLite` options.
@@ -237,6 +431,29 @@ impl SqliteOptionsBuilder {
         self
     }
 
+    /// Attach `path` as schema `alias` on every pooled connection.
+    ///
+    /// Attachments run in the order they are added, right after each connection opens, so
+    /// cross-database queries like `SELECT ... FROM main.t JOIN alias.u` work on any checkout.
+    #[must_use]
+    pub fn attach(mut self, alias: impl Into<String>, path: impl Into<String>) -> Self {
+        self.opts
+            .attachments
+            .push(SqliteAttachment::new(alias, path));
+        self
+    }
+
+    /// Allow at most `max_pending` operations queued on each pooled connection.
+    ///
+    /// Each connection runs its statements one at a time on a worker thread. Past the limit,
+    /// [`QueueFullPolicy::Reject`] fails with `SqlMiddlewareDbError::Overloaded` and
+    /// [`QueueFullPolicy::Wait`] waits for an earlier operation to finish.
+    #[must_use]
+    pub fn queue_limit(mut self, max_pending: usize, policy: QueueFullPolicy) -> Self {
+        self.opts.queue_limit = Some(WorkerQueueLimit::new(max_pending, policy));
+        self
+    }
+
     #[must_use]
     pub fn finish(self) -> SqliteOptions {
         self.opts
@@ -263,7 +480,21 @@ impl ConfigAndPool {
     /// # Errors
     /// Returns `SqlMiddlewareDbError::ConnectionError` if pool creation or connection test fails.
     pub async fn new_sqlite(opts: SqliteOptions) -> Result<Self, SqlMiddlewareDbError> {
-        let manager = SqliteManager::new(opts.db_path.clone());
+        if opts.queue_limit.is_some_and(|limit| limit.max_pending == 0) {
+            return Err(SqlMiddlewareDbError::ConfigError(
+                "SQLite queue limit max_pending must be at least 1".to_string(),
+            ));
+        }
+        let manager = SqliteManager::new(opts.db_path.clone())
+            .with_attachments(opts.attachments.clone())
+            .with_queue_limit(opts.queue_limit);
+        Self::new_sqlite_with_manager(manager, &opts).await
+    }
+
+    pub(crate) async fn new_sqlite_with_manager(
+        manager: SqliteManager,
+        opts: &SqliteOptions,
+    ) -> Result<Self, SqlMiddlewareDbError> {
         let pool = manager.build_pool().await?;
 
         // Initialize the database with WAL and a simple health check.
@@ -275,23 +506,64 @@ impl ConfigAndPool {
             crate::sqlite::apply_wal_pragmas(&mut conn).await?;
         }
 
-        Ok(ConfigAndPool {
-            pool: MiddlewarePool::Sqlite(pool),
-            db_type: DatabaseType::Sqlite,
-            translate_placeholders: opts.translate_placeholders,
-        })
+        Ok(ConfigAndPool::from_pool(
+            MiddlewarePool::Sqlite(pool),
+            DatabaseType::Sqlite,
+            opts.translate_placeholders,
+        ))
     }
 }
 
 /// bb8 manager for `SQLite` connections.
 pub struct SqliteManager {
     db_path: String,
+    attachments: Vec<SqliteAttachment>,
+    queue_limit: Option<WorkerQueueLimit>,
+    init_sql: Option<String>,
+    // Held for the pool's lifetime so a shared-cache in-memory database outlives idle reaping.
+    _keeper: Option<std::sync::Mutex<rusqlite::Connection>>,
 }
 
 impl SqliteManager {
     #[must_use]
     pub fn new(db_path: String) -> Self {
-        Self { db_path }
+        Self {
+            db_path,
+            attachments: Vec::new(),
+            queue_limit: None,
+            init_sql: None,
+            _keeper: None,
+        }
+    }
+
+    /// Keep `conn` open for as long as the pool built from this manager lives.
+    pub(crate) fn with_keeper(mut self, conn: rusqlite::Connection) -> Self {
+        self._keeper = Some(std::sync::Mutex::new(conn));
+        self
+    }
+
+    /// Attach these databases on every connection this manager opens.
+    #[must_use]
+    pub fn with_attachments(mut self, attachments: Vec<SqliteAttachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Cap the operations queued on each connection this manager opens.
+    #[must_use]
+    pub fn with_queue_limit(mut self, queue_limit: Option<WorkerQueueLimit>) -> Self {
+        self.queue_limit = queue_limit;
+        self
+    }
+
+    /// Run `sql` on every connection this manager opens, after attaching databases.
+    ///
+    /// Use it for per-connection settings such as `PRAGMA synchronous = NORMAL`, which a
+    /// replacement connection would otherwise come up without.
+    #[must_use]
+    pub fn with_init_sql(mut self, sql: impl Into<String>) -> Self {
+        self.init_sql = Some(sql.into());
+        self
     }
 
     /// Build a pool from this manager.
@@ -314,10 +586,19 @@ impl ManageConnection for SqliteManager {
         &self,
     ) -> impl std::future::Future<Output = Result<Self::Connection, Self::Error>> + Send {
         let path = self.db_path.clone();
+        let attachments = self.attachments.clone();
+        let queue_limit = self.queue_limit;
+        let init_sql = self.init_sql.clone();
         async move {
             let conn =
                 rusqlite::Connection::open(path).map_err(SqlMiddlewareDbError::SqliteError)?;
-            Ok(SqliteWorker::start(conn))
+            attach_all(&conn, &attachments)?;
+            if let Some(sql) = init_sql {
+                conn.execute_batch(&sql)
+                    .map_err(SqlMiddlewareDbError::SqliteError)?;
+            }
+            let session_pragmas = read_session_pragmas(&conn)?;
+            Ok(SqliteWorker::start(conn, queue_limit, session_pragmas))
         }
     }
 
@@ -327,6 +608,11 @@ impl ManageConnection for SqliteManager {
     ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
         let conn = Arc::clone(conn);
         async move {
+            // A worker still running an abandoned job is alive; checking it would wait behind
+            // that job. Checkout picks a less busy connection instead (see `get_connection`).
+            if conn.pending_jobs() > 0 {
+                return Ok(());
+            }
             crate::sqlite::connection::run_blocking(conn, |guard| {
                 guard
                     .query_row("SELECT 1", rusqlite::params![], |_row| Ok(()))
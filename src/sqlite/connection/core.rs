@@ -1,15 +1,23 @@
 use std::fmt;
 use std::sync::Arc;
 
+use crate::clock::{Clock, system_clock};
+use crate::jitter::{Jitter, system_jitter};
 use crate::middleware::SqlMiddlewareDbError;
+use crate::pool::checkout::PoolLink;
 
+use crate::runtime::oneshot;
 use crate::sqlite::config::{SharedSqliteConnection, SqlitePooledConnection};
-use tokio::sync::oneshot;
 
 /// Connection wrapper backed by a bb8 pooled `SQLite` connection.
 pub struct SqliteConnection {
     pub(crate) conn: SqlitePooledConnection,
     pub(crate) in_transaction: bool,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) jitter: Arc<dyn Jitter>,
+    /// Pool whose rate limit `BEGIN` and statements inside a transaction take tokens from, set by
+    /// the handle that began it
+    pub(crate) pool: PoolLink,
 }
 
 impl SqliteConnection {
@@ -17,9 +25,30 @@ impl SqliteConnection {
         Self {
             conn,
             in_transaction: false,
+            clock: system_clock(),
+            jitter: system_jitter(),
+            pool: PoolLink::default(),
         }
     }
 
+    /// Use `clock` for busy-retry backoff on this connection.
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Use `jitter` to randomize busy-retry backoff on this connection.
+    pub(crate) fn set_jitter(&mut self, jitter: Arc<dyn Jitter>) {
+        self.jitter = jitter;
+    }
+
+    /// Take a token from `pool`'s rate limit before `BEGIN` and each statement inside a
+    /// transaction, and echo what the transaction's query builder runs.
+    ///
+    /// Set by whatever begins the transaction, from the pooled connection it was taken from.
+    pub(crate) fn set_pool_link(&mut self, pool: PoolLink) {
+        self.pool = pool;
+    }
+
     /// Run `func` on the pooled rusqlite connection while no other transaction is in flight.
     ///
     /// # Errors
@@ -79,9 +108,16 @@ where
     R: Send + 'static,
 {
     let (tx, rx) = oneshot::channel();
-    conn.execute(move |conn| {
-        let _ = tx.send(func(conn));
-    })?;
+    let slot = conn.reserve().await?;
+    conn.execute_then(
+        move |conn| {
+            let _slot = slot;
+            func(conn)
+        },
+        move |result| {
+            let _ = tx.send(result);
+        },
+    )?;
     rx.await.map_err(|e| {
         SqlMiddlewareDbError::ExecutionError(format!("sqlite worker receive error: {e}"))
     })?
@@ -42,18 +42,28 @@ impl SqliteConnection {
         &mut self,
         query: &str,
         params: &[rusqlite::types::Value],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        self.execute_dml_owned(query, params.to_vec()).await
+    }
+
+    /// Like [`execute_dml`](Self::execute_dml), but takes ownership of `params` and moves them
+    /// to the worker thread instead of copying them.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if preparing or executing the statement fails.
+    pub async fn execute_dml_owned(
+        &mut self,
+        query: &str,
+        params: Vec<rusqlite::types::Value>,
     ) -> Result<usize, SqlMiddlewareDbError> {
         self.ensure_not_in_tx("execute dml")?;
         let sql_owned = query.to_owned();
-        let params_owned = params.to_vec();
         run_blocking(self.conn_handle(), move |guard| {
             let mut stmt = guard
                 .prepare_cached(&sql_owned)
                 .map_err(SqlMiddlewareDbError::SqliteError)?;
-            let refs: Vec<&dyn rusqlite::ToSql> = params_owned
-                .iter()
-                .map(|v| v as &dyn rusqlite::ToSql)
-                .collect();
+            let refs: Vec<&dyn rusqlite::ToSql> =
+                params.iter().map(|v| v as &dyn rusqlite::ToSql).collect();
             let affected = stmt
                 .execute(&refs[..])
                 .map_err(SqlMiddlewareDbError::SqliteError)?;
@@ -62,6 +72,31 @@ impl SqliteConnection {
         .await
     }
 
+    /// Execute `sql` without an implicit transaction and return the rows it changed.
+    ///
+    /// Counts through `sqlite3_total_changes`, so any statement (DDL, `SELECT`, or a script) may be
+    /// passed; only inserts, updates, and deletes add to the count.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if a middleware transaction is open or execution fails.
+    pub(crate) async fn execute_counted(
+        &mut self,
+        sql: &str,
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        self.ensure_not_in_tx("execute batch stream")?;
+        let sql_owned = sql.to_owned();
+        run_blocking(self.conn_handle(), move |guard| {
+            let before = guard.total_changes();
+            guard
+                .execute_batch(&sql_owned)
+                .map_err(SqlMiddlewareDbError::SqliteError)?;
+            usize::try_from(guard.total_changes() - before).map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("Invalid rows affected count: {e}"))
+            })
+        })
+        .await
+    }
+
     /// Execute a DML statement inside an open transaction.
     ///
     /// # Errors
@@ -70,22 +105,33 @@ impl SqliteConnection {
         &mut self,
         query: &str,
         params: &[rusqlite::types::Value],
+    ) -> Result<usize, SqlMiddlewareDbError> {
+        self.execute_dml_in_tx_owned(query, params.to_vec()).await
+    }
+
+    /// Like [`execute_dml_in_tx`](Self::execute_dml_in_tx), but takes ownership of `params` and
+    /// moves them to the worker thread instead of copying them.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if the guard fails or the transaction is not active.
+    pub async fn execute_dml_in_tx_owned(
+        &mut self,
+        query: &str,
+        params: Vec<rusqlite::types::Value>,
     ) -> Result<usize, SqlMiddlewareDbError> {
         if !self.in_transaction {
             return Err(SqlMiddlewareDbError::ExecutionError(
                 "SQLite transaction not active".into(),
             ));
         }
+        self.pool.throttle().await?;
         let sql_owned = query.to_owned();
-        let params_owned = params.to_vec();
         run_blocking(self.conn_handle(), move |guard| {
             let mut stmt = guard
                 .prepare_cached(&sql_owned)
                 .map_err(SqlMiddlewareDbError::SqliteError)?;
-            let refs: Vec<&dyn rusqlite::ToSql> = params_owned
-                .iter()
-                .map(|v| v as &dyn rusqlite::ToSql)
-                .collect();
+            let refs: Vec<&dyn rusqlite::ToSql> =
+                params.iter().map(|v| v as &dyn rusqlite::ToSql).collect();
             let affected = stmt
                 .execute(&refs[..])
                 .map_err(SqlMiddlewareDbError::SqliteError)?;
@@ -104,6 +150,7 @@ impl SqliteConnection {
                 "SQLite transaction not active".into(),
             ));
         }
+        self.pool.throttle().await?;
         let sql_owned = sql.to_owned();
         run_blocking(self.conn_handle(), move |guard| {
             guard
@@ -123,9 +170,8 @@ pub async fn dml(
     query: &str,
     params: &[RowValues],
 ) -> Result<usize, SqlMiddlewareDbError> {
-    let converted = convert_params::<Params>(params, ConversionMode::Execute)?.0;
+    let params_owned = convert_params::<Params>(params, ConversionMode::Execute)?.0;
     let sql_owned = query.to_owned();
-    let params_owned = converted.clone();
     let handle = Arc::clone(&*conn);
     run_blocking(handle, move |guard| {
         let mut stmt = guard
@@ -142,3 +188,48 @@ pub async fn dml(
     })
     .await
 }
+
+/// Execute one statement on a connection the caller already holds, through its statement cache.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError` if converting parameters or executing the statement fails.
+pub(crate) fn execute_cached(
+    conn: &rusqlite::Connection,
+    query: &str,
+    params: &[RowValues],
+) -> Result<usize, SqlMiddlewareDbError> {
+    let params = convert_params::<Params>(params, ConversionMode::Execute)?.0;
+    let refs: Vec<&dyn rusqlite::ToSql> =
+        params.iter().map(|v| v as &dyn rusqlite::ToSql).collect();
+    conn.prepare_cached(query)
+        .and_then(|mut stmt| stmt.execute(&refs[..]))
+        .map_err(SqlMiddlewareDbError::SqliteError)
+}
+
+/// Adapter for query builder batches (typed-sqlite target); wraps the batch in a transaction
+/// when not already inside one.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError` if executing the batch fails.
+pub async fn batch(
+    conn: &mut PooledConnection<'static, SqliteManager>,
+    query: &str,
+) -> Result<(), SqlMiddlewareDbError> {
+    let sql_owned = query.to_owned();
+    let handle = Arc::clone(&*conn);
+    run_blocking(handle, move |guard| {
+        if guard.is_autocommit() {
+            let tx = guard
+                .transaction()
+                .map_err(SqlMiddlewareDbError::SqliteError)?;
+            tx.execute_batch(&sql_owned)
+                .map_err(SqlMiddlewareDbError::SqliteError)?;
+            tx.commit().map_err(SqlMiddlewareDbError::SqliteError)
+        } else {
+            guard
+                .execute_batch(&sql_owned)
+                .map_err(SqlMiddlewareDbError::SqliteError)
+        }
+    })
+    .await
+}
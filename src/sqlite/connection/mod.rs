@@ -5,7 +5,8 @@ mod select;
 mod tx;
 
 pub(crate) use core::run_blocking;
+pub(crate) use dml::execute_cached;
 pub(crate) use tx::{rollback_with_busy_retries, rollback_with_busy_retries_blocking};
 pub use core::{SqliteConnection, apply_wal_pragmas};
-pub use dml::dml;
+pub use dml::{batch, dml};
 pub use select::select;
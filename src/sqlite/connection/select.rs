@@ -21,6 +21,29 @@ impl SqliteConnection {
         params: &[rusqlite::types::Value],
         builder: F,
     ) -> Result<ResultSet, SqlMiddlewareDbError>
+    where
+        F: FnOnce(
+                &mut rusqlite::Statement<'_>,
+                &[rusqlite::types::Value],
+            ) -> Result<ResultSet, SqlMiddlewareDbError>
+            + Send
+            + 'static,
+    {
+        self.execute_select_owned(query, params.to_vec(), builder)
+            .await
+    }
+
+    /// Like [`execute_select`](Self::execute_select), but takes ownership of `params` and moves
+    /// them to the worker thread instead of copying them.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if preparing or executing the query fails.
+    pub async fn execute_select_owned<F>(
+        &mut self,
+        query: &str,
+        params: Vec<rusqlite::types::Value>,
+        builder: F,
+    ) -> Result<ResultSet, SqlMiddlewareDbError>
     where
         F: FnOnce(
                 &mut rusqlite::Statement<'_>,
@@ -30,12 +53,11 @@ impl SqliteConnection {
             + 'static,
     {
         let sql_owned = query.to_owned();
-        let params_owned = params.to_vec();
         run_blocking(self.conn_handle(), move |guard| {
             let mut stmt = guard
                 .prepare_cached(&sql_owned)
                 .map_err(SqlMiddlewareDbError::SqliteError)?;
-            builder(&mut stmt, &params_owned)
+            builder(&mut stmt, &params)
         })
         .await
     }
@@ -50,6 +72,29 @@ impl SqliteConnection {
         params: &[rusqlite::types::Value],
         builder: F,
     ) -> Result<ResultSet, SqlMiddlewareDbError>
+    where
+        F: FnOnce(
+                &mut rusqlite::Statement<'_>,
+                &[rusqlite::types::Value],
+            ) -> Result<ResultSet, SqlMiddlewareDbError>
+            + Send
+            + 'static,
+    {
+        self.execute_select_in_tx_owned(query, params.to_vec(), builder)
+            .await
+    }
+
+    /// Like [`execute_select_in_tx`](Self::execute_select_in_tx), but takes ownership of
+    /// `params` and moves them to the worker thread instead of copying them.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if preparing/executing the query fails or the transaction is not active.
+    pub async fn execute_select_in_tx_owned<F>(
+        &mut self,
+        query: &str,
+        params: Vec<rusqlite::types::Value>,
+        builder: F,
+    ) -> Result<ResultSet, SqlMiddlewareDbError>
     where
         F: FnOnce(
                 &mut rusqlite::Statement<'_>,
@@ -63,13 +108,13 @@ impl SqliteConnection {
                 "SQLite transaction not active".into(),
             ));
         }
+        self.pool.throttle().await?;
         let sql_owned = query.to_owned();
-        let params_owned = params.to_vec();
         run_blocking(self.conn_handle(), move |guard| {
             let mut stmt = guard
                 .prepare_cached(&sql_owned)
                 .map_err(SqlMiddlewareDbError::SqliteError)?;
-            builder(&mut stmt, &params_owned)
+            builder(&mut stmt, &params)
         })
         .await
     }
@@ -89,9 +134,8 @@ pub async fn select(
     query: &str,
     params: &[RowValues],
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
-    let converted = convert_params::<Params>(params, ConversionMode::Query)?.0;
+    let params_owned = convert_params::<Params>(params, ConversionMode::Query)?.0;
     let sql_owned = query.to_owned();
-    let params_owned = converted.clone();
     let handle = Arc::clone(&*conn);
     run_blocking(handle, move |guard| {
         let mut stmt = guard
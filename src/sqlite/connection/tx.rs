@@ -1,17 +1,22 @@
 use crate::middleware::SqlMiddlewareDbError;
 
 use super::{SqliteConnection, run_blocking};
+use crate::clock::Clock;
+use crate::jitter::{Jitter, equal_jitter};
 use crate::sqlite::config::SharedSqliteConnection;
 use std::sync::Arc;
-use std::thread;
 use std::time::Duration;
-use tokio::time::sleep;
 
-const ROLLBACK_BUSY_RETRIES: &[Duration] =
-    &[Duration::from_millis(10), Duration::from_millis(25), Duration::from_millis(50)];
+const ROLLBACK_BUSY_RETRIES: &[Duration] = &[
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+];
 
 pub(crate) async fn rollback_with_busy_retries(
     handle: &SharedSqliteConnection,
+    clock: &dyn Clock,
+    jitter: &dyn Jitter,
 ) -> Result<(), SqlMiddlewareDbError> {
     if handle.force_rollback_busy_for_tests() {
         return Err(SqlMiddlewareDbError::SqliteError(
@@ -41,7 +46,7 @@ pub(crate) async fn rollback_with_busy_retries(
             && err.code == rusqlite::ErrorCode::DatabaseBusy
             && idx + 1 < ROLLBACK_BUSY_RETRIES.len()
         {
-            sleep(delay).await;
+            clock.sleep(equal_jitter(delay, jitter)).await;
             continue;
         }
         return result;
@@ -54,6 +59,8 @@ pub(crate) async fn rollback_with_busy_retries(
 
 pub(crate) fn rollback_with_busy_retries_blocking(
     handle: &SharedSqliteConnection,
+    clock: &dyn Clock,
+    jitter: &dyn Jitter,
 ) -> Result<(), SqlMiddlewareDbError> {
     if handle.force_rollback_busy_for_tests() {
         return Err(SqlMiddlewareDbError::SqliteError(
@@ -82,7 +89,7 @@ pub(crate) fn rollback_with_busy_retries_blocking(
             && err.code == rusqlite::ErrorCode::DatabaseBusy
             && idx + 1 < ROLLBACK_BUSY_RETRIES.len()
         {
-            thread::sleep(delay);
+            clock.sleep_blocking(equal_jitter(delay, jitter));
             continue;
         }
         return result;
@@ -104,6 +111,7 @@ impl SqliteConnection {
                 "SQLite transaction already in progress".into(),
             ));
         }
+        self.pool.throttle().await?;
         run_blocking(self.conn_handle(), move |guard| {
             guard
                 .execute_batch("BEGIN")
@@ -144,7 +152,12 @@ impl SqliteConnection {
                 "SQLite transaction not active".into(),
             ));
         }
-        let result = rollback_with_busy_retries(&self.conn_handle()).await;
+        let result = rollback_with_busy_retries(
+            &self.conn_handle(),
+            self.clock.as_ref(),
+            self.jitter.as_ref(),
+        )
+        .await;
         if result.is_err() {
             self.mark_broken();
             return result;
@@ -29,7 +29,7 @@ pub async fn execute_select(
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
     let params_owned = convert_params::<Params>(params, ConversionMode::Query)?.0;
     sqlite_client
-        .execute_select(query, &params_owned, build_result_set)
+        .execute_select_owned(query, params_owned, build_result_set)
         .await
 }
 
@@ -44,5 +44,5 @@ pub async fn execute_dml(
     params: &[RowValues],
 ) -> Result<usize, SqlMiddlewareDbError> {
     let params_owned = convert_params::<Params>(params, ConversionMode::Execute)?.0;
-    sqlite_client.execute_dml(query, &params_owned).await
+    sqlite_client.execute_dml_owned(query, params_owned).await
 }
@@ -0,0 +1,39 @@
+//! Named shared-cache in-memory `SQLite` databases.
+//!
+//! A shared-cache in-memory database is destroyed as soon as its last connection closes. bb8 may
+//! close every pooled connection (idle reaping, broken workers), so the pool built here also owns
+//! a keeper connection that is only released when the pool itself is dropped.
+
+use crate::middleware::{ConfigAndPool, SqlMiddlewareDbError};
+
+use super::config::{SqliteManager, SqliteOptions};
+
+/// URI for the named shared-cache in-memory database `name`.
+#[must_use]
+pub fn memory_uri(name: &str) -> String {
+    format!("file:{name}?mode=memory&cache=shared")
+}
+
+impl ConfigAndPool {
+    /// Create a pool over the named shared-cache in-memory database `name`.
+    ///
+    /// Every pooled connection sees the same database, and its contents survive for as long as
+    /// the pool (or any clone of this `ConfigAndPool`) is alive, even when no connection is
+    /// checked out. Pools created with the same `name` in one process share the database.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` if `name` is empty or contains URI
+    /// delimiters, or the `SQLite`/pool error if the database cannot be opened.
+    pub async fn new_sqlite_memory(name: &str) -> Result<Self, SqlMiddlewareDbError> {
+        if name.is_empty() || name.contains(['?', '#', '&', '/']) {
+            return Err(SqlMiddlewareDbError::ConfigError(format!(
+                "invalid SQLite in-memory database name: {name:?}"
+            )));
+        }
+        let uri = memory_uri(name);
+        let keeper = rusqlite::Connection::open(&uri).map_err(SqlMiddlewareDbError::SqliteError)?;
+        let opts = SqliteOptions::new(uri.clone());
+        let manager = SqliteManager::new(uri).with_keeper(keeper);
+        Self::new_sqlite_with_manager(manager, &opts).await
+    }
+}
@@ -4,35 +4,55 @@
 //! keeping the async runtime responsive while avoiding deadpool-sqlite.
 //!
 //! Submodules:
+//! - `attach`: `ATTACH DATABASE` support for pooled connections
+//! - `backend`: [`BackendApi`](crate::backend::BackendApi) implementation
 //! - `config`: connection configuration and pool setup
+//! - `memory`: named shared-cache in-memory databases
 //! - `params`: parameter conversion between middleware and `SQLite` types
 //! - `query`: result extraction and building
+//! - `queue`: per-connection worker queue limits
+//! - `snapshot`: consistent read snapshots of WAL databases
 //! - `executor`: database operation execution
 //! - `transaction`: explicit transaction support
 //! - `prepared`: prepared statement helpers
 
+pub mod attach;
+pub mod backend;
 pub mod config;
 pub mod connection;
 pub mod executor;
+pub mod memory;
 pub mod params;
 pub mod prepared;
 pub mod query;
+pub mod queue;
+pub mod snapshot;
 pub mod transaction;
 pub mod typed;
 
 // Re-export the public API
 #[allow(unused_imports)]
+pub use attach::SqliteAttachment;
+#[allow(unused_imports)]
+pub use backend::SqliteBackend;
+#[allow(unused_imports)]
 pub use config::{SqliteOptions, SqliteOptionsBuilder};
 #[allow(unused_imports)]
 pub use connection::{SqliteConnection, apply_wal_pragmas};
 #[allow(unused_imports)]
 pub use executor::{execute_batch, execute_dml, execute_select};
 #[allow(unused_imports)]
+pub use memory::memory_uri;
+#[allow(unused_imports)]
 pub use params::Params;
 pub use prepared::SqlitePreparedStatement;
 #[allow(unused_imports)]
 pub use query::build_result_set;
 #[allow(unused_imports)]
+pub use queue::{QueueFullPolicy, WorkerQueueLimit};
+#[allow(unused_imports)]
+pub use snapshot::Snapshot;
+#[allow(unused_imports)]
 pub use transaction::{Prepared, Tx, begin_transaction};
 #[allow(unused_imports)]
 pub use typed::{Idle, InTx, SqliteTypedConnection};
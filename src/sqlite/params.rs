@@ -1,8 +1,11 @@
 use std::fmt::Write;
 
 use rusqlite;
+use rusqlite::types::{ToSqlOutput, ValueRef};
 
-use crate::middleware::{ConversionMode, ParamConverter, RowValues, SqlMiddlewareDbError};
+use crate::middleware::{
+    ConversionMode, DatabaseType, ParamConverter, RowValues, SqlMiddlewareDbError,
+};
 
 // Thread-local buffer for efficient timestamp formatting
 thread_local! {
@@ -18,10 +21,10 @@ pub fn row_value_to_sqlite_value(value: &RowValues, for_execute: bool) -> rusqli
         RowValues::Text(s) => {
             if for_execute {
                 // For execute, we can move the owned String directly
-                rusqlite::types::Value::Text(s.clone())
+                rusqlite::types::Value::Text(s.to_string())
             } else {
                 // For queries, we need to clone
-                rusqlite::types::Value::Text(s.clone())
+                rusqlite::types::Value::Text(s.to_string())
             }
         }
         RowValues::Bool(b) => rusqlite::types::Value::Integer(i64::from(*b)),
@@ -37,6 +40,7 @@ pub fn row_value_to_sqlite_value(value: &RowValues, for_execute: bool) -> rusqli
             })
         }
         RowValues::Null => rusqlite::types::Value::Null,
+        #[cfg(feature = "json")]
         RowValues::JSON(jval) => {
             // Only serialize once to avoid multiple allocations
             let json_str = jval.to_string();
@@ -45,15 +49,38 @@ pub fn row_value_to_sqlite_value(value: &RowValues, for_execute: bool) -> rusqli
         RowValues::Blob(bytes) => {
             if for_execute {
                 // For execute, we can directly use the bytes
-                rusqlite::types::Value::Blob(bytes.clone())
+                rusqlite::types::Value::Blob(bytes.to_vec())
             } else {
-                rusqlite::types::Value::Blob(bytes.clone())
+                rusqlite::types::Value::Blob(bytes.to_vec())
             }
         }
+        RowValues::Custom(custom) => row_value_to_sqlite_value(&custom.encode(), for_execute),
+        #[cfg(feature = "geo")]
+        RowValues::Geometry(wkb) => rusqlite::types::Value::Blob(wkb.to_vec()),
+    }
+}
+
+/// Bind a `RowValues` directly, borrowing text and blobs instead of copying them.
+///
+/// Useful inside [`with_blocking_sqlite`](crate::pool::MiddlewarePoolConnection::with_blocking_sqlite)
+/// closures, e.g. `stmt.query(rusqlite::params_from_iter(&params))`. Values are bound as
+/// [`row_value_to_sqlite_value`] would convert them.
+impl rusqlite::ToSql for RowValues {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            RowValues::Text(s) => ToSqlOutput::Borrowed(ValueRef::Text(s.as_bytes())),
+            RowValues::Blob(bytes) => ToSqlOutput::Borrowed(ValueRef::Blob(&bytes[..])),
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(wkb) => ToSqlOutput::Borrowed(ValueRef::Blob(&wkb[..])),
+            other => ToSqlOutput::Owned(row_value_to_sqlite_value(other, false)),
+        })
     }
 }
 
 /// Unified `SQLite` parameter container.
+///
+/// The values are owned because statements run on the connection's worker thread; conversion
+/// is the only copy made of each parameter on the way there.
 pub struct Params(pub Vec<rusqlite::types::Value>);
 
 impl Params {
@@ -88,13 +115,19 @@ impl ParamConverter<'_> for Params {
 
     fn convert_sql_params(
         params: &[RowValues],
-        _mode: ConversionMode,
+        mode: ConversionMode,
     ) -> Result<Self::Converted, SqlMiddlewareDbError> {
+        if mode.is_strict() {
+            DatabaseType::Sqlite.check_strict_params(params)?;
+        }
         Self::convert(params)
     }
 
     fn supports_mode(mode: ConversionMode) -> bool {
-        // Single Params type supports both query and execute.
-        matches!(mode, ConversionMode::Query | ConversionMode::Execute)
+        // Single Params type supports every mode.
+        matches!(
+            mode,
+            ConversionMode::Query | ConversionMode::Execute | ConversionMode::Strict
+        )
     }
 }
@@ -24,9 +24,9 @@ impl<'conn> SqlitePreparedStatement<'conn> {
     pub async fn query(&mut self, params: &[RowValues]) -> Result<ResultSet, SqlMiddlewareDbError> {
         let params_owned = convert_params::<Params>(params, ConversionMode::Query)?.0;
         self.connection
-            .execute_select(
+            .execute_select_owned(
                 self.query.as_ref(),
-                &params_owned,
+                params_owned,
                 super::query::build_result_set,
             )
             .await
@@ -39,7 +39,7 @@ impl<'conn> SqlitePreparedStatement<'conn> {
     pub async fn execute(&mut self, params: &[RowValues]) -> Result<usize, SqlMiddlewareDbError> {
         let params_owned = convert_params::<Params>(params, ConversionMode::Execute)?.0;
         self.connection
-            .execute_dml(self.query.as_ref(), &params_owned)
+            .execute_dml_owned(self.query.as_ref(), params_owned)
             .await
     }
 
@@ -19,8 +19,8 @@ pub fn sqlite_extract_value_sync(
         Value::Null => Ok(RowValues::Null),
         Value::Integer(i) => Ok(RowValues::Int(i)),
         Value::Real(f) => Ok(RowValues::Float(f)),
-        Value::Text(s) => Ok(RowValues::Text(s)),
-        Value::Blob(b) => Ok(RowValues::Blob(b)),
+        Value::Text(s) => Ok(RowValues::Text(s.into())),
+        Value::Blob(b) => Ok(RowValues::Blob(b.into())),
     }
 }
 
@@ -36,6 +36,15 @@ pub fn build_result_set(
 ) -> Result<ResultSet, SqlMiddlewareDbError> {
     let param_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
     let column_names = extract_column_names(stmt.column_names().iter(), |name| *name);
+    #[cfg(feature = "geo")]
+    let geometry_columns: Vec<bool> = stmt
+        .columns()
+        .iter()
+        .map(|col| {
+            col.decl_type()
+                .is_some_and(crate::geo::is_sqlite_geometry_type)
+        })
+        .collect();
 
     let mut rows_iter = stmt.query(&param_refs[..])?;
     // Create result set with default capacity
@@ -48,6 +57,13 @@ pub fn build_result_set(
 
         for i in 0..col_count {
             let value = sqlite_extract_value_sync(row, i)?;
+            #[cfg(feature = "geo")]
+            let value = match value {
+                RowValues::Blob(blob) if geometry_columns.get(i) == Some(&true) => {
+                    RowValues::Geometry(crate::geo::sqlite_blob_to_wkb(&blob)?.into())
+                }
+                other => other,
+            };
             row_values.push(value);
         }
 
@@ -0,0 +1,95 @@
+//! Backpressure for the per-connection `SQLite` worker queue.
+//!
+//! Every pooled connection runs its statements on one worker thread, fed by an unbounded channel.
+//! A caller that stops waiting (a timeout, a dropped request) leaves its statement in that
+//! channel, and under bursty traffic with retries these pile up invisibly, so each new operation
+//! waits behind all of them.
+//!
+//! A [`WorkerQueueLimit`] caps the operations queued on a connection and either rejects or delays
+//! the ones past the cap.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::middleware::SqlMiddlewareDbError;
+
+/// What happens to an operation submitted while the connection's queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueFullPolicy {
+    /// Fail at once with [`SqlMiddlewareDbError::Overloaded`].
+    #[default]
+    Reject,
+    /// Wait until an earlier operation on the connection finishes.
+    Wait,
+}
+
+impl FromStr for QueueFullPolicy {
+    type Err = SqlMiddlewareDbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "reject" => Ok(Self::Reject),
+            "wait" => Ok(Self::Wait),
+            _ => Err(SqlMiddlewareDbError::ConfigError(format!(
+                "unknown queue full policy {s:?}; expected \"reject\" or \"wait\""
+            ))),
+        }
+    }
+}
+
+/// Cap on operations queued on or running in one pooled connection's worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerQueueLimit {
+    /// Operations allowed in the queue at once, including the one running.
+    pub max_pending: usize,
+    /// What to do with an operation beyond `max_pending`.
+    #[serde(default)]
+    pub policy: QueueFullPolicy,
+}
+
+impl WorkerQueueLimit {
+    #[must_use]
+    pub fn new(max_pending: usize, policy: QueueFullPolicy) -> Self {
+        Self {
+            max_pending,
+            policy,
+        }
+    }
+}
+
+/// Slots for one worker's queue; a permit is held from submission until the operation finishes.
+#[derive(Debug)]
+pub(crate) struct WorkerQueue {
+    slots: Arc<Semaphore>,
+    limit: WorkerQueueLimit,
+}
+
+impl WorkerQueue {
+    pub(crate) fn new(limit: WorkerQueueLimit) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(limit.max_pending)),
+            limit,
+        }
+    }
+
+    /// Take a queue slot, following the configured policy when none is free.
+    pub(crate) async fn reserve(&self) -> Result<OwnedSemaphorePermit, SqlMiddlewareDbError> {
+        let slots = Arc::clone(&self.slots);
+        match self.limit.policy {
+            QueueFullPolicy::Reject => {
+                slots
+                    .try_acquire_owned()
+                    .map_err(|_| SqlMiddlewareDbError::Overloaded {
+                        limit: self.limit.max_pending,
+                    })
+            }
+            QueueFullPolicy::Wait => slots.acquire_owned().await.map_err(|_| {
+                SqlMiddlewareDbError::ExecutionError("sqlite worker queue closed".into())
+            }),
+        }
+    }
+}
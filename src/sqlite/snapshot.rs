@@ -0,0 +1,190 @@
+use crate::executor::QueryTarget;
+use crate::middleware::{ResultSet, RowValues, SqlMiddlewareDbError};
+use crate::pool::MiddlewarePoolConnection;
+use crate::query_builder::QueryBuilder;
+
+use super::connection::{SqliteConnection, run_blocking};
+use super::transaction::select_in_tx;
+
+/// Statements that open the snapshot: reject writes, begin, and read once so the WAL read mark
+/// is taken now rather than at the first caller query.
+const OPEN: &str = "PRAGMA query_only = ON; BEGIN; SELECT count(*) FROM sqlite_master;";
+
+/// End the snapshot (if it got as far as `BEGIN`) and make the connection writable again.
+fn close(raw: &rusqlite::Connection) -> Result<(), SqlMiddlewareDbError> {
+    raw.execute_batch("PRAGMA query_only = OFF")?;
+    if !raw.is_autocommit() {
+        raw.execute_batch("ROLLBACK")?;
+    }
+    Ok(())
+}
+
+/// Read-only view of a WAL database as of [`MiddlewarePoolConnection::snapshot`].
+///
+/// Every query sees the same committed state, however many writes other connections commit
+/// meanwhile, and those writers are never blocked. The handle owns the pooled connection until
+/// [`release`](Snapshot::release) (or drop) hands it back. Writes through the snapshot fail with
+/// `SQLite`'s "attempt to write a readonly database".
+///
+/// Keep snapshots short-lived: the WAL cannot be checkpointed past the oldest open snapshot, so
+/// a forgotten one lets the `-wal` file grow.
+pub struct Snapshot<'a> {
+    conn: Option<SqliteConnection>,
+    conn_slot: &'a mut MiddlewarePoolConnection,
+}
+
+impl MiddlewarePoolConnection {
+    /// Open a read [`Snapshot`] on this `SQLite` connection.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let mut snapshot = conn.snapshot().await?;
+    /// let orders = snapshot.query("SELECT COUNT(*) FROM orders").select_scalar::<i64>().await?;
+    /// let lines = snapshot.query("SELECT COUNT(*) FROM order_lines").select_scalar::<i64>().await?;
+    /// snapshot.release().await?;
+    /// # let _ = (orders, lines);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`SqlMiddlewareDbError::Unimplemented`] when the connection is not `SQLite`, and
+    /// `ExecutionError` when a transaction is already open or the database is not in
+    /// `journal_mode=WAL` (other journal modes make a long read block writers).
+    pub async fn snapshot(&mut self) -> Result<Snapshot<'_>, SqlMiddlewareDbError> {
+        self.sqlite_conn_mut()?.ensure_not_in_tx("snapshot")?;
+        let pool = self.pool_link();
+        let journal_mode = self
+            .with_blocking_sqlite(|raw| {
+                raw.query_row("PRAGMA journal_mode", [], |row| row.get::<_, String>(0))
+                    .map_err(SqlMiddlewareDbError::SqliteError)
+            })
+            .await?;
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            return Err(SqlMiddlewareDbError::ExecutionError(format!(
+                "snapshots need journal_mode=WAL, but this database uses {journal_mode}"
+            )));
+        }
+
+        #[cfg(any(feature = "postgres", feature = "mssql", feature = "turso"))]
+        let MiddlewarePoolConnection::Sqlite { conn, .. } = self else {
+            unreachable!("sqlite_conn_mut checked the backend");
+        };
+        #[cfg(not(any(feature = "postgres", feature = "mssql", feature = "turso")))]
+        let MiddlewarePoolConnection::Sqlite { conn, .. } = self;
+        let mut conn = conn.take().ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError(
+                "SQLite connection already taken from pool wrapper".into(),
+            )
+        })?;
+        conn.set_pool_link(pool);
+        let opened = run_blocking(conn.conn_handle(), |raw| {
+            raw.execute_batch(OPEN).map_err(|err| {
+                let _ = close(raw);
+                SqlMiddlewareDbError::SqliteError(err)
+            })
+        })
+        .await;
+        let mut snapshot = Snapshot {
+            conn: None,
+            conn_slot: self,
+        };
+        match opened {
+            Ok(()) => {
+                conn.in_transaction = true;
+                snapshot.conn = Some(conn);
+                Ok(snapshot)
+            }
+            Err(err) => {
+                snapshot.rewrap(conn);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl Snapshot<'_> {
+    fn conn_mut(&mut self) -> Result<&mut SqliteConnection, SqlMiddlewareDbError> {
+        self.conn.as_mut().ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError("SQLite snapshot already released".into())
+        })
+    }
+
+    /// Run a SELECT against the snapshot.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if parameter conversion or execution fails.
+    pub async fn select(
+        &mut self,
+        sql: &str,
+        params: &[RowValues],
+    ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        select_in_tx(self.conn_mut()?, sql, params).await
+    }
+
+    /// Start a [`QueryBuilder`] that reads from the snapshot.
+    ///
+    /// Placeholder translation follows the pooled connection's default.
+    pub fn query<'q>(&mut self, sql: &'q str) -> QueryBuilder<'_, 'q> {
+        let translation_default = self.conn_slot.translation_default();
+        let conn = self
+            .conn
+            .as_mut()
+            .expect("sqlite snapshot is open while its handle exists");
+        QueryBuilder::new_target(
+            QueryTarget::from_sqlite_tx(conn, translation_default, None),
+            sql,
+        )
+    }
+
+    /// End the snapshot and return the connection to the pool wrapper.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError` if ending the read transaction fails; the connection is then
+    /// discarded by the pool instead of reused.
+    pub async fn release(mut self) -> Result<(), SqlMiddlewareDbError> {
+        let mut conn = self.conn.take().ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError("SQLite snapshot already released".into())
+        })?;
+        let handle = conn.conn_handle();
+        let result = run_blocking(handle, |raw| close(raw)).await;
+        if result.is_err() {
+            conn.mark_broken();
+        }
+        conn.in_transaction = false;
+        self.rewrap(conn);
+        result
+    }
+
+    fn rewrap(&mut self, conn: SqliteConnection) {
+        #[cfg(any(feature = "postgres", feature = "mssql", feature = "turso"))]
+        let MiddlewarePoolConnection::Sqlite { conn: slot, .. } = self.conn_slot else {
+            return;
+        };
+        #[cfg(not(any(feature = "postgres", feature = "mssql", feature = "turso")))]
+        let MiddlewarePoolConnection::Sqlite { conn: slot, .. } = self.conn_slot;
+        debug_assert!(
+            slot.is_none(),
+            "sqlite conn slot should be empty during snapshot"
+        );
+        *slot = Some(conn);
+    }
+}
+
+impl Drop for Snapshot<'_> {
+    /// Ends an unreleased snapshot before the connection goes back to the pool. Nothing was
+    /// written, so unlike an unfinished [`Tx`](super::Tx) this is not reported.
+    fn drop(&mut self) {
+        if let Some(mut conn) = self.conn.take() {
+            let handle = conn.conn_handle();
+            let closed = handle.execute_blocking(|raw| close(raw));
+            if closed.is_err() {
+                handle.mark_broken();
+            }
+            conn.in_transaction = false;
+            self.rewrap(conn);
+        }
+    }
+}
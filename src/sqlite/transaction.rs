@@ -1,8 +1,12 @@
 use std::sync::Arc;
 
 use crate::adapters::params::convert_params;
+use crate::executor::QueryTarget;
 use crate::middleware::{ConversionMode, ResultSet, RowValues, SqlMiddlewareDbError};
 use crate::pool::MiddlewarePoolConnection;
+use crate::pool::checkout::PoolLink;
+use crate::query_builder::QueryBuilder;
+use crate::tx_drop::DropGuard;
 use crate::tx_outcome::TxOutcome;
 
 use super::connection::SqliteConnection;
@@ -25,6 +29,7 @@ fn rewrap_on_rollback_failure_for_tests() -> bool {
 pub struct Tx<'a> {
     conn: Option<SqliteConnection>,
     conn_slot: &'a mut MiddlewarePoolConnection,
+    guard: DropGuard,
 }
 
 /// Prepared statement tied to a `SQLite` transaction.
@@ -35,33 +40,62 @@ pub struct Prepared {
 /// Begin a transaction, temporarily taking ownership of the pooled `SQLite` connection
 /// until commit/rollback (or drop) returns it to the wrapper.
 ///
+/// `BEGIN` and every statement on the handle take a token from the pool's
+/// [rate limit](crate::pool::rate_limit); committing and rolling back do not.
+///
+/// Dropping the returned [`Tx`] unfinished rolls it back and reports this call site; see
+/// [`tx_drop`](crate::tx_drop).
+///
 /// # Errors
 /// Returns `SqlMiddlewareDbError` if the transaction cannot be started.
-pub async fn begin_transaction(
+#[track_caller]
+pub fn begin_transaction(
+    conn_slot: &mut MiddlewarePoolConnection,
+) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+    let pool = conn_slot.pool_link();
+    begin_with(conn_slot, pool, DropGuard::new("sqlite"))
+}
+
+async fn begin_with(
     conn_slot: &mut MiddlewarePoolConnection,
+    pool: PoolLink,
+    guard: DropGuard,
 ) -> Result<Tx<'_>, SqlMiddlewareDbError> {
     #[cfg(any(feature = "postgres", feature = "mssql", feature = "turso"))]
-    let MiddlewarePoolConnection::Sqlite { conn, .. } = conn_slot else {
+    let MiddlewarePoolConnection::Sqlite { conn: slot, .. } = conn_slot else {
         return Err(SqlMiddlewareDbError::Unimplemented(
             "begin_transaction is only available for SQLite connections".into(),
         ));
     };
     #[cfg(not(any(feature = "postgres", feature = "mssql", feature = "turso")))]
-    let MiddlewarePoolConnection::Sqlite { conn, .. } = conn_slot;
+    let MiddlewarePoolConnection::Sqlite { conn: slot, .. } = conn_slot;
 
-    let mut conn = conn.take().ok_or_else(|| {
+    let mut conn = slot.take().ok_or_else(|| {
         SqlMiddlewareDbError::ExecutionError(
             "SQLite connection already taken from pool wrapper".into(),
         )
     })?;
-    conn.begin().await?;
+    conn.set_pool_link(pool);
+    if let Err(err) = conn.begin().await {
+        // Nothing started (or the rate limit refused it); keep the connection usable.
+        *slot = Some(conn);
+        return Err(err);
+    }
     Ok(Tx {
         conn: Some(conn),
         conn_slot,
+        guard: guard.arm(),
     })
 }
 
 impl Tx<'_> {
+    /// Stop reporting this transaction if it is dropped, for crate helpers that roll back by
+    /// dropping it.
+    pub(crate) fn untracked(mut self) -> Self {
+        self.guard.disarm();
+        self
+    }
+
     fn conn_mut(&mut self) -> Result<&mut SqliteConnection, SqlMiddlewareDbError> {
         self.conn.as_mut().ok_or_else(|| {
             SqlMiddlewareDbError::ExecutionError("SQLite transaction already completed".into())
@@ -92,10 +126,7 @@ impl Tx<'_> {
         prepared: &Prepared,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
-        let converted = convert_params::<Params>(params, ConversionMode::Execute)?;
-        let conn = self.conn_mut()?;
-        conn.execute_dml_in_tx(prepared.sql.as_ref(), &converted.0)
-            .await
+        dml_in_tx(self.conn_mut()?, prepared.sql.as_ref(), params).await
     }
 
     /// Execute a prepared statement as a query within this transaction.
@@ -107,14 +138,39 @@ impl Tx<'_> {
         prepared: &Prepared,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
-        let converted = convert_params::<Params>(params, ConversionMode::Query)?;
-        let conn = self.conn_mut()?;
-        conn.execute_select_in_tx(
-            prepared.sql.as_ref(),
-            &converted.0,
-            super::query::build_result_set,
+        select_in_tx(self.conn_mut()?, prepared.sql.as_ref(), params).await
+    }
+
+    /// Start a [`QueryBuilder`] that runs inside this transaction.
+    ///
+    /// Placeholder translation follows the pooled connection's default, as it does outside the
+    /// transaction.
+    ///
+    /// ```rust,no_run
+    /// use sql_middleware::prelude::*;
+    /// use sql_middleware::sqlite::begin_transaction;
+    ///
+    /// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+    /// let mut tx = begin_transaction(conn).await?;
+    /// tx.query_builder("INSERT INTO t (id) VALUES ($1)")
+    ///     .params(&[RowValues::Int(1)])
+    ///     .translation(TranslationMode::ForceOn)
+    ///     .dml()
+    ///     .await?;
+    /// tx.commit().await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn query_builder<'q>(&mut self, sql: &'q str) -> QueryBuilder<'_, 'q> {
+        let translation_default = self.conn_slot.translation_default();
+        let audit = self.conn_slot.audit_tables().cloned();
+        let conn = self
+            .conn
+            .as_mut()
+            .expect("sqlite transaction is open while its handle exists");
+        QueryBuilder::new_target(
+            QueryTarget::from_sqlite_tx(conn, translation_default, audit),
+            sql,
         )
-        .await
     }
 
     /// Execute a batch inside the open transaction.
@@ -131,6 +187,7 @@ impl Tx<'_> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if committing the transaction fails.
     pub async fn commit(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
         let mut conn = self.conn.take().ok_or_else(|| {
             SqlMiddlewareDbError::ExecutionError("SQLite transaction already completed".into())
         })?;
@@ -141,8 +198,12 @@ impl Tx<'_> {
             }
             Err(err) => {
                 let handle = conn.conn_handle();
-                let rollback_result =
-                    super::connection::rollback_with_busy_retries(&handle).await;
+                let rollback_result = super::connection::rollback_with_busy_retries(
+                    &handle,
+                    conn.clock.as_ref(),
+                    conn.jitter.as_ref(),
+                )
+                .await;
                 if rollback_result.is_ok() || rewrap_on_rollback_failure_for_tests() {
                     conn.in_transaction = false;
                     self.rewrap(conn);
@@ -160,11 +221,18 @@ impl Tx<'_> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if rolling back fails.
     pub async fn rollback(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
         let mut conn = self.conn.take().ok_or_else(|| {
             SqlMiddlewareDbError::ExecutionError("SQLite transaction already completed".into())
         })?;
         let handle = conn.conn_handle();
-        match super::connection::rollback_with_busy_retries(&handle).await {
+        match super::connection::rollback_with_busy_retries(
+            &handle,
+            conn.clock.as_ref(),
+            conn.jitter.as_ref(),
+        )
+        .await
+        {
             Ok(()) => {
                 conn.in_transaction = false;
                 self.rewrap(conn);
@@ -195,16 +263,41 @@ impl Tx<'_> {
     }
 }
 
+/// Run a SELECT on a connection whose transaction is open.
+pub(crate) async fn select_in_tx(
+    conn: &mut SqliteConnection,
+    query: &str,
+    params: &[RowValues],
+) -> Result<ResultSet, SqlMiddlewareDbError> {
+    let converted = convert_params::<Params>(params, ConversionMode::Query)?;
+    conn.execute_select_in_tx_owned(query, converted.0, super::query::build_result_set)
+        .await
+}
+
+/// Run DML on a connection whose transaction is open.
+pub(crate) async fn dml_in_tx(
+    conn: &mut SqliteConnection,
+    query: &str,
+    params: &[RowValues],
+) -> Result<usize, SqlMiddlewareDbError> {
+    let converted = convert_params::<Params>(params, ConversionMode::Execute)?;
+    conn.execute_dml_in_tx_owned(query, converted.0).await
+}
+
 impl Drop for Tx<'_> {
     /// Rolls back on drop to avoid leaking open transactions; the rollback is best-effort and
     /// `SQLite` may report "no transaction is active" if the transaction was already completed
     /// by user code (e.g., via `execute_batch_in_tx`). Such errors are ignored because the goal
     /// is simply to leave the connection in a clean state before returning it to the pool.
+    /// The guard then reports the drop (see [`tx_drop`](crate::tx_drop)).
     fn drop(&mut self) {
         if let Some(mut conn) = self.conn.take() {
             let handle = conn.conn_handle();
-            let rollback_result =
-                super::connection::rollback_with_busy_retries_blocking(&handle);
+            let rollback_result = super::connection::rollback_with_busy_retries_blocking(
+                &handle,
+                conn.clock.as_ref(),
+                conn.jitter.as_ref(),
+            );
             if rollback_result.is_ok() || rewrap_on_rollback_failure_for_tests() {
                 conn.in_transaction = false;
                 self.rewrap(conn);
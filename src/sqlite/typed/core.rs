@@ -5,6 +5,7 @@ use std::sync::atomic::AtomicBool;
 use bb8::{Pool, PooledConnection};
 
 use crate::middleware::SqlMiddlewareDbError;
+use crate::tx_drop::DropGuard;
 
 use crate::sqlite::config::{SharedSqliteConnection, SqliteManager};
 
@@ -17,12 +18,14 @@ pub enum InTx {}
 /// When in `InTx` state, dropping without calling [`commit`](SqliteTypedConnection::<InTx>::commit)
 /// or [`rollback`](SqliteTypedConnection::<InTx>::rollback) will trigger a best-effort synchronous
 /// rollback in `Drop` to keep the pool clean. Prefer finishing transactions explicitly to avoid
-/// surprise blocking work during drop.
+/// surprise blocking work during drop. The drop is also reported through [`crate::tx_drop`].
 pub struct SqliteTypedConnection<State> {
     pub(crate) conn: Option<PooledConnection<'static, SqliteManager>>,
     /// True if in a transaction that needs rollback on drop.
     pub(crate) needs_rollback: bool,
     pub(crate) _state: PhantomData<State>,
+    /// Reports a transaction dropped unfinished; declared last so it runs after the rollback.
+    pub(crate) tx_guard: DropGuard,
 }
 
 impl SqliteTypedConnection<Idle> {
@@ -31,13 +34,19 @@ impl SqliteTypedConnection<Idle> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if acquiring a pooled connection fails.
     pub async fn from_pool(pool: &Pool<SqliteManager>) -> Result<Self, SqlMiddlewareDbError> {
-        let conn = pool.get_owned().await.map_err(|e| {
-            SqlMiddlewareDbError::ConnectionError(format!("sqlite checkout error: {e}"))
-        })?;
+        let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+            SqlMiddlewareDbError::PoolErrorSqlite {
+                source: Box::new(source),
+                waited,
+                state,
+            }
+        })
+        .await?;
         Ok(Self {
             conn: Some(conn),
             needs_rollback: false,
             _state: PhantomData,
+            tx_guard: DropGuard::untracked(),
         })
     }
 }
@@ -62,16 +71,21 @@ impl<State> SqliteTypedConnection<State> {
     }
 }
 
-pub(crate) fn in_tx(conn: PooledConnection<'static, SqliteManager>) -> SqliteTypedConnection<InTx> {
+pub(crate) fn in_tx(
+    conn: PooledConnection<'static, SqliteManager>,
+    tx_guard: DropGuard,
+) -> SqliteTypedConnection<InTx> {
     SqliteTypedConnection {
         conn: Some(conn),
         needs_rollback: true,
         _state: PhantomData,
+        tx_guard,
     }
 }
 
 pub(crate) async fn begin_from_conn(
     conn: PooledConnection<'static, SqliteManager>,
+    tx_guard: DropGuard,
 ) -> Result<SqliteTypedConnection<InTx>, SqlMiddlewareDbError> {
     run_blocking(Arc::clone(&*conn), |guard| {
         guard
@@ -79,7 +93,7 @@ pub(crate) async fn begin_from_conn(
             .map_err(SqlMiddlewareDbError::SqliteError)
     })
     .await?;
-    Ok(in_tx(conn))
+    Ok(in_tx(conn, tx_guard.arm()))
 }
 
 pub(crate) async fn run_blocking<F, R>(
@@ -90,10 +104,17 @@ where
     F: FnOnce(&mut rusqlite::Connection) -> Result<R, SqlMiddlewareDbError> + Send + 'static,
     R: Send + 'static,
 {
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    conn.execute(move |conn| {
-        let _ = tx.send(func(conn));
-    })?;
+    let (tx, rx) = crate::runtime::oneshot::channel();
+    let slot = conn.reserve().await?;
+    conn.execute_then(
+        move |conn| {
+            let _slot = slot;
+            func(conn)
+        },
+        move |result| {
+            let _ = tx.send(result);
+        },
+    )?;
     rx.await.map_err(|e| {
         SqlMiddlewareDbError::ExecutionError(format!("sqlite worker receive error: {e}"))
     })?
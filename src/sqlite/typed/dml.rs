@@ -6,6 +6,7 @@ use crate::middleware::{ConversionMode, RowValues, SqlMiddlewareDbError};
 use super::SqliteTypedConnection;
 use crate::sqlite::config::SqliteManager;
 use crate::sqlite::params::Params;
+use crate::tx_drop::DropGuard;
 
 impl SqliteTypedConnection<super::core::Idle> {
     /// Auto-commit batch (BEGIN/COMMIT around it).
@@ -13,7 +14,8 @@ impl SqliteTypedConnection<super::core::Idle> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if executing the batch fails.
     pub async fn execute_batch(&mut self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
-        let mut tx = super::core::begin_from_conn(self.take_conn()?).await?;
+        let mut tx =
+            super::core::begin_from_conn(self.take_conn()?, DropGuard::untracked()).await?;
         tx.execute_batch(sql).await?;
         let mut idle = tx.commit().await?;
         self.conn = idle.conn.take();
@@ -29,7 +31,8 @@ impl SqliteTypedConnection<super::core::Idle> {
         query: &str,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
-        let mut tx = super::core::begin_from_conn(self.take_conn()?).await?;
+        let mut tx =
+            super::core::begin_from_conn(self.take_conn()?, DropGuard::untracked()).await?;
         let rows = tx.dml(query, params).await?;
         let mut idle = tx.commit().await?;
         self.conn = idle.conn.take();
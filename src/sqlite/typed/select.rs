@@ -11,6 +11,7 @@ use crate::sqlite::params::Params;
 use crate::sqlite::query;
 
 use super::SqliteTypedConnection;
+use crate::tx_drop::DropGuard;
 
 impl SqliteTypedConnection<super::core::Idle> {
     /// Auto-commit SELECT.
@@ -22,7 +23,8 @@ impl SqliteTypedConnection<super::core::Idle> {
         query: &str,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
-        let mut tx = super::core::begin_from_conn(self.take_conn()?).await?;
+        let mut tx =
+            super::core::begin_from_conn(self.take_conn()?, DropGuard::untracked()).await?;
         let rows = tx.select(query, params).await?;
         let mut idle = tx.commit().await?;
         self.conn = idle.conn.take();
@@ -1,25 +1,34 @@
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 
-use tokio::runtime::Handle;
-use tokio::task::block_in_place;
-
 use crate::middleware::SqlMiddlewareDbError;
 
 use super::SqliteTypedConnection;
 use super::core::{SKIP_DROP_ROLLBACK, begin_from_conn, run_blocking};
+use crate::clock::SystemClock;
+use crate::jitter::system_jitter;
 use crate::sqlite::connection::{rollback_with_busy_retries, rollback_with_busy_retries_blocking};
 use crate::sqlite::config::SharedSqliteConnection;
+use crate::tx_drop::DropGuard;
 
 impl SqliteTypedConnection<super::core::Idle> {
     /// Begin an explicit transaction.
     ///
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if transitioning into a transaction fails.
-    pub async fn begin(
+    #[track_caller]
+    pub fn begin(
+        self,
+    ) -> impl Future<Output = Result<SqliteTypedConnection<super::core::InTx>, SqlMiddlewareDbError>>
+    {
+        self.begin_guarded(DropGuard::new("sqlite"))
+    }
+
+    pub(crate) async fn begin_guarded(
         mut self,
+        tx_guard: DropGuard,
     ) -> Result<SqliteTypedConnection<super::core::InTx>, SqlMiddlewareDbError> {
-        begin_from_conn(self.take_conn()?).await
+        begin_from_conn(self.take_conn()?, tx_guard).await
     }
 }
 
@@ -31,6 +40,7 @@ impl SqliteTypedConnection<super::core::InTx> {
     pub async fn commit(
         mut self,
     ) -> Result<SqliteTypedConnection<super::core::Idle>, SqlMiddlewareDbError> {
+        self.tx_guard.disarm();
         let conn_handle = self.conn_handle()?;
         let commit_result = run_blocking(Arc::clone(&conn_handle), |guard| {
             guard
@@ -46,11 +56,16 @@ impl SqliteTypedConnection<super::core::InTx> {
                     conn: Some(conn),
                     needs_rollback: false,
                     _state: std::marker::PhantomData,
+                    tx_guard: DropGuard::untracked(),
                 })
             }
             Err(err) => {
                 // Best-effort rollback; keep needs_rollback = true so Drop can retry if needed.
-                if rollback_with_busy_retries(&conn_handle).await.is_err() {
+                let jitter = system_jitter();
+                if rollback_with_busy_retries(&conn_handle, &SystemClock, jitter.as_ref())
+                    .await
+                    .is_err()
+                {
                     conn_handle.mark_broken();
                 }
                 Err(err)
@@ -65,8 +80,11 @@ impl SqliteTypedConnection<super::core::InTx> {
     pub async fn rollback(
         mut self,
     ) -> Result<SqliteTypedConnection<super::core::Idle>, SqlMiddlewareDbError> {
+        self.tx_guard.disarm();
         let conn_handle = self.conn_handle()?;
-        let rollback_result = rollback_with_busy_retries(&conn_handle).await;
+        let jitter = system_jitter();
+        let rollback_result =
+            rollback_with_busy_retries(&conn_handle, &SystemClock, jitter.as_ref()).await;
 
         match rollback_result {
             Ok(()) => {
@@ -75,6 +93,7 @@ impl SqliteTypedConnection<super::core::InTx> {
                     conn: Some(conn),
                     needs_rollback: false,
                     _state: std::marker::PhantomData,
+                    tx_guard: DropGuard::untracked(),
                 })
             }
             Err(err) => {
@@ -96,12 +115,10 @@ impl<State> Drop for SqliteTypedConnection<State> {
             // Rollback synchronously so the connection is clean before it
             // goes back into the pool. Avoid async fire-and-forget, which
             // could race with the next checkout.
-            let rollback = || rollback_with_busy_retries_blocking(&conn_handle);
-            let result = if Handle::try_current().is_ok() {
-                block_in_place(rollback)
-            } else {
-                rollback()
-            };
+            let jitter = system_jitter();
+            let rollback =
+                || rollback_with_busy_retries_blocking(&conn_handle, &SystemClock, jitter.as_ref());
+            let result = crate::runtime::block_in_place(rollback);
 
             if result.is_err() {
                 conn_handle.mark_broken();
@@ -0,0 +1,376 @@
+//! Copying table data between pools, including across backends.
+//!
+//! [`copy_table`] reads a table from one connection in batches and writes each batch to another
+//! with [`bulk_insert`](MiddlewarePoolConnection::bulk_insert), so promoting a `SQLite` database to
+//! Postgres (or the reverse) needs no hand-written ETL. Reading and writing overlap: the next batch
+//! is fetched while the previous one is inserted.
+//!
+//! Values travel as [`RowValues`]. Because `SQLite` has no boolean, timestamp or JSON storage,
+//! each value is adjusted to the destination column's [`ColumnType`] before it is bound: `0`/`1`
+//! become booleans, timestamp text becomes a timestamp, integers bound to float columns become
+//! floats, and JSON text becomes JSON. Values that do not convert are passed through unchanged and
+//! left for the destination to accept or reject.
+
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::error::SqlMiddlewareDbError;
+use crate::ident::quote;
+use crate::pool::MiddlewarePoolConnection;
+use crate::schema::{ColumnType, Table, introspect};
+use crate::types::RowValues;
+
+/// Rows per batch unless [`CopyOptions::batch_rows`] says otherwise.
+pub const DEFAULT_BATCH_ROWS: usize = 1000;
+
+/// Batches read ahead of the one being inserted.
+const READ_AHEAD: usize = 2;
+
+/// How far a [`copy_table`] has got, reported after each batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyProgress {
+    /// Rows inserted into the destination so far.
+    pub rows_copied: usize,
+    /// Batches inserted so far.
+    pub batches: usize,
+}
+
+type ProgressFn = Box<dyn FnMut(CopyProgress) + Send>;
+
+/// Options for [`copy_table`].
+///
+/// # Examples
+/// ```rust
+/// use sql_middleware::sync::CopyOptions;
+///
+/// let options = CopyOptions::default()
+///     .batch_rows(5_000)
+///     .into_table("archived_orders")
+///     .on_progress(|progress| eprintln!("{} rows copied", progress.rows_copied));
+/// # let _ = options;
+/// ```
+pub struct CopyOptions {
+    /// Rows read and inserted at a time.
+    pub batch_rows: usize,
+    /// Destination table; defaults to the source table's name.
+    pub into_table: Option<String>,
+    /// Columns to copy; defaults to all of the source table's columns.
+    pub columns: Option<Vec<String>>,
+    /// Adjust values to the destination column types (see the [module docs](self)).
+    pub coerce_types: bool,
+    on_progress: Option<ProgressFn>,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            batch_rows: DEFAULT_BATCH_ROWS,
+            into_table: None,
+            columns: None,
+            coerce_types: true,
+            on_progress: None,
+        }
+    }
+}
+
+impl fmt::Debug for CopyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyOptions")
+            .field("batch_rows", &self.batch_rows)
+            .field("into_table", &self.into_table)
+            .field("columns", &self.columns)
+            .field("coerce_types", &self.coerce_types)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl CopyOptions {
+    #[must_use]
+    pub fn batch_rows(mut self, batch_rows: usize) -> Self {
+        self.batch_rows = batch_rows;
+        self
+    }
+
+    #[must_use]
+    pub fn into_table(mut self, table: impl Into<String>) -> Self {
+        self.into_table = Some(table.into());
+        self
+    }
+
+    #[must_use]
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns = Some(columns.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    #[must_use]
+    pub fn coerce_types(mut self, coerce_types: bool) -> Self {
+        self.coerce_types = coerce_types;
+        self
+    }
+
+    /// Call `on_progress` after each batch is inserted.
+    #[must_use]
+    pub fn on_progress(mut self, on_progress: impl FnMut(CopyProgress) + Send + 'static) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+}
+
+/// Rows read from the source, with the column names they share.
+struct Batch {
+    columns: Arc<Vec<String>>,
+    rows: Vec<Vec<RowValues>>,
+}
+
+/// Copy the rows of `table` from `src` into the same-named table on `dst`; returns the row count.
+///
+/// The destination table must already exist. Each batch is inserted in its own transaction, so a
+/// failure leaves the batches before it in place; [`CopyProgress`] says how many. Postgres reads
+/// through a portal in a read-only transaction and `SQLite` from its worker thread, a batch at a
+/// time. SQL Server and Turso read the whole table first.
+///
+/// # Examples
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+/// use sql_middleware::sync::{CopyOptions, copy_table};
+///
+/// # async fn demo(local: &ConfigAndPool, prod: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+/// let mut src = local.get_connection().await?;
+/// let mut dst = prod.get_connection().await?;
+/// let copied = copy_table(&mut src, &mut dst, "users", CopyOptions::default()).await?;
+/// eprintln!("copied {copied} users");
+/// # Ok(()) }
+/// ```
+///
+/// # Errors
+/// Returns `ConfigError` if `batch_rows` is zero, `ExecutionError` if the destination has no such
+/// table, or the first error from reading the source or inserting into the destination.
+pub async fn copy_table(
+    src: &mut MiddlewarePoolConnection,
+    dst: &mut MiddlewarePoolConnection,
+    table: &str,
+    options: CopyOptions,
+) -> Result<usize, SqlMiddlewareDbError> {
+    let CopyOptions {
+        batch_rows,
+        into_table,
+        columns,
+        coerce_types,
+        mut on_progress,
+    } = options;
+    if batch_rows == 0 {
+        return Err(SqlMiddlewareDbError::ConfigError(
+            "copy_table needs batch_rows of at least 1".into(),
+        ));
+    }
+    let dst_table_name = into_table.as_deref().unwrap_or(table);
+    let dst_table = introspect(dst)
+        .await?
+        .table(dst_table_name)
+        .cloned()
+        .ok_or_else(|| {
+            SqlMiddlewareDbError::ExecutionError(format!(
+                "copy_table destination has no table `{dst_table_name}`"
+            ))
+        })?;
+
+    let src_type = src.database_type();
+    let column_list = match &columns {
+        Some(columns) => columns
+            .iter()
+            .map(|column| quote(column, &src_type))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(", "),
+        None => "*".to_string(),
+    };
+    let sql = format!("SELECT {column_list} FROM {}", quote(table, &src_type)?);
+
+    let (sender, receiver) = mpsc::channel(READ_AHEAD);
+    let read = read_batches(src, &sql, batch_rows, sender);
+    let write = async {
+        let mut receiver = receiver;
+        let mut progress = CopyProgress {
+            rows_copied: 0,
+            batches: 0,
+        };
+        while let Some(batch) = receiver.recv().await {
+            let column_names: Vec<&str> = batch.columns.iter().map(String::as_str).collect();
+            let rows = if coerce_types {
+                coerce_rows(&dst_table, &column_names, batch.rows)
+            } else {
+                batch.rows
+            };
+            progress.rows_copied += dst
+                .bulk_insert(dst_table_name, &column_names, &rows)
+                .await?;
+            progress.batches += 1;
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(progress);
+            }
+        }
+        Ok(progress.rows_copied)
+    };
+    let ((), copied) = tokio::try_join!(read, write)?;
+    Ok(copied)
+}
+
+/// Send `sql`'s rows to `sender` in batches of `batch_rows`.
+async fn read_batches(
+    src: &mut MiddlewarePoolConnection,
+    sql: &str,
+    batch_rows: usize,
+    sender: mpsc::Sender<Batch>,
+) -> Result<(), SqlMiddlewareDbError> {
+    match src {
+        #[cfg(feature = "postgres")]
+        MiddlewarePoolConnection::Postgres { .. } => {
+            crate::pool::echo::statement(src.debug_echo_flag(), sql, sql, &[]);
+            let result = async {
+                let tx = src.begin_postgres_transaction().await?.untracked();
+                tx.execute_batch("SET TRANSACTION READ ONLY").await?;
+                let prepared = tx.prepare(sql).await?;
+                let mut portal = tx.bind(&prepared, &[]).await?;
+                loop {
+                    let chunk = tx.fetch(&mut portal, batch_rows).await?;
+                    let columns = chunk.get_column_names().cloned().unwrap_or_default();
+                    let rows: Vec<_> = chunk.results.into_iter().map(|row| row.rows).collect();
+                    if !rows.is_empty() {
+                        send(&sender, Batch { columns, rows }).await?;
+                    }
+                    if portal.is_exhausted() {
+                        break;
+                    }
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+            .await;
+            src.record_result(&result);
+            result
+        }
+        #[cfg(feature = "sqlite")]
+        MiddlewarePoolConnection::Sqlite { .. } => {
+            src.throttle().await?;
+            crate::pool::echo::statement(src.debug_echo_flag(), sql, sql, &[]);
+            let sql = sql.to_string();
+            let result = src
+                .with_blocking_sqlite(move |raw| {
+                    let mut stmt = raw.prepare(&sql)?;
+                    let columns = Arc::new(
+                        stmt.column_names()
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>(),
+                    );
+                    let mut rows = stmt.query([])?;
+                    let mut batch = Vec::with_capacity(batch_rows);
+                    while let Some(row) = rows.next()? {
+                        batch.push(
+                            (0..columns.len())
+                                .map(|idx| {
+                                    crate::sqlite::query::sqlite_extract_value_sync(row, idx)
+                                })
+                                .collect::<Result<Vec<_>, _>>()?,
+                        );
+                        if batch.len() == batch_rows {
+                            let rows =
+                                std::mem::replace(&mut batch, Vec::with_capacity(batch_rows));
+                            send_blocking(&sender, Arc::clone(&columns), rows)?;
+                        }
+                    }
+                    if !batch.is_empty() {
+                        send_blocking(&sender, columns, batch)?;
+                    }
+                    Ok(())
+                })
+                .await;
+            src.record_result(&result);
+            result
+        }
+        #[allow(unreachable_patterns)]
+        _ => {
+            let result_set = src.query(sql).select().await?;
+            let columns = result_set.get_column_names().cloned().unwrap_or_default();
+            let mut rows = result_set
+                .results
+                .into_iter()
+                .map(|row| row.rows)
+                .peekable();
+            while rows.peek().is_some() {
+                let batch = Batch {
+                    columns: Arc::clone(&columns),
+                    rows: rows.by_ref().take(batch_rows).collect(),
+                };
+                send(&sender, batch).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn send(sender: &mpsc::Sender<Batch>, batch: Batch) -> Result<(), SqlMiddlewareDbError> {
+    sender.send(batch).await.map_err(|_| writer_gone())
+}
+
+/// Send from the `SQLite` worker thread, which is outside the async runtime.
+#[cfg(feature = "sqlite")]
+fn send_blocking(
+    sender: &mpsc::Sender<Batch>,
+    columns: Arc<Vec<String>>,
+    rows: Vec<Vec<RowValues>>,
+) -> Result<(), SqlMiddlewareDbError> {
+    sender
+        .blocking_send(Batch { columns, rows })
+        .map_err(|_| writer_gone())
+}
+
+/// The writer stopped early; its own error is the one `copy_table` returns.
+fn writer_gone() -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ExecutionError("copy_table stopped writing".into())
+}
+
+pub(crate) fn coerce_rows(
+    table: &Table,
+    column_names: &[&str],
+    rows: Vec<Vec<RowValues>>,
+) -> Vec<Vec<RowValues>> {
+    let types: Vec<Option<&ColumnType>> = column_names
+        .iter()
+        .map(|name| table.column(name).map(|column| &column.column_type))
+        .collect();
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .zip(&types)
+                .map(|(value, column_type)| match column_type {
+                    Some(column_type) => coerce(value, column_type),
+                    None => value,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Adjust `value` to what a column of `column_type` expects, or return it unchanged.
+fn coerce(value: RowValues, column_type: &ColumnType) -> RowValues {
+    match (column_type, &value) {
+        (ColumnType::Boolean, RowValues::Int(_)) => value
+            .as_bool()
+            .map_or(value.clone(), |b| RowValues::Bool(*b)),
+        (ColumnType::Integer, RowValues::Bool(b)) => RowValues::Int(i64::from(*b)),
+        #[allow(clippy::cast_precision_loss)]
+        (ColumnType::Float | ColumnType::Numeric, RowValues::Int(i)) => RowValues::Float(*i as f64),
+        (ColumnType::Timestamp, RowValues::Text(_)) => value
+            .as_timestamp()
+            .map_or(value.clone(), RowValues::Timestamp),
+        #[cfg(feature = "json")]
+        (ColumnType::Json, RowValues::Text(text)) => {
+            serde_json::from_str(text).map_or(value.clone(), RowValues::JSON)
+        }
+        _ => value,
+    }
+}
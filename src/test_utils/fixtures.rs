@@ -0,0 +1,243 @@
+//! Load fixture files into a database in foreign-key order.
+//!
+//! [`load`] reads every fixture file in a directory:
+//! - `*.json` (and `*.yaml` / `*.yml` with the `yaml` feature): an object mapping table names to
+//!   arrays of rows, each row an object of column values.
+//! - `*.sql`: a batch run as is, before any data file is loaded. Use these for DDL or for data
+//!   that does not fit the row format.
+//!
+//! ```json
+//! {
+//!   "orders": [{ "id": 10, "user_id": 1, "placed": "2024-01-02 03:04:05" }],
+//!   "users": [{ "id": 1, "name": "ann" }]
+//! }
+//! ```
+//!
+//! Tables are inserted parents first, following the foreign keys reported by
+//! [`schema::foreign_keys`](crate::schema::foreign_keys), so file names and key order do not
+//! matter. Rows go through [`bulk_insert`](MiddlewarePoolConnection::bulk_insert), which binds
+//! values with the backend's own placeholders, and values are adjusted to the destination
+//! column type the way [`sync::copy_table`](crate::sync::copy_table) does (timestamp strings,
+//! `0`/`1` booleans, nested objects for JSON columns).
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::middleware::{MiddlewarePoolConnection, RowValues, SqlMiddlewareDbError};
+use crate::schema::{ForeignKey, foreign_keys, introspect};
+use crate::sync::coerce_rows;
+
+/// Rows for one table, merged across fixture files.
+struct TableRows {
+    name: String,
+    rows: Vec<Map<String, Value>>,
+}
+
+/// Load the fixtures in `dir` and return `(table, rows inserted)` in insertion order.
+///
+/// SQL files run first, in file-name order; data files are then merged per table and inserted
+/// parents first. Each insert commits on its own, so a failure leaves the tables before it
+/// loaded. Files with other extensions are ignored.
+///
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+/// use sql_middleware::test_helpers::fixtures;
+///
+/// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+/// let loaded = fixtures::load(conn, "tests/fixtures/orders").await?;
+/// for (table, rows) in &loaded {
+///     println!("{table}: {rows} rows");
+/// }
+/// # Ok(()) }
+/// ```
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::ConfigError` if the directory or a file cannot be read or
+/// parsed, a YAML file is found without the `yaml` feature, a fixture names a table the
+/// database does not have, or the fixture tables reference each other in a cycle. Returns the
+/// backend's error if a statement fails.
+pub async fn load(
+    conn: &mut MiddlewarePoolConnection,
+    dir: impl AsRef<Path>,
+) -> Result<Vec<(String, usize)>, SqlMiddlewareDbError> {
+    let (sql_files, data_files) = fixture_files(dir.as_ref())?;
+    for path in &sql_files {
+        conn.execute_batch(&read(path)?).await?;
+    }
+
+    let mut tables: Vec<TableRows> = Vec::new();
+    for path in &data_files {
+        for parsed in parse_data_file(path)? {
+            match tables
+                .iter_mut()
+                .find(|table| table.name.eq_ignore_ascii_case(&parsed.name))
+            {
+                Some(table) => table.rows.extend(parsed.rows),
+                None => tables.push(parsed),
+            }
+        }
+    }
+    if tables.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let schema = introspect(conn).await?;
+    let keys = foreign_keys(conn).await?;
+    let mut loaded = Vec::with_capacity(tables.len());
+    for table in dependency_order(tables, &keys)? {
+        let Some(target) = schema.table(&table.name) else {
+            return Err(SqlMiddlewareDbError::ConfigError(format!(
+                "fixture table `{}` does not exist",
+                table.name
+            )));
+        };
+        let mut inserted = 0;
+        // Rows that set different columns go in separate inserts, so omitted columns keep their
+        // defaults instead of becoming NULL.
+        for (columns, rows) in group_by_columns(&table.rows) {
+            let rows = coerce_rows(target, &columns, rows);
+            inserted += conn.bulk_insert(&table.name, &columns, &rows).await?;
+        }
+        loaded.push((table.name, inserted));
+    }
+    Ok(loaded)
+}
+
+/// SQL and data files in `dir`, each sorted by file name.
+fn fixture_files(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>), SqlMiddlewareDbError> {
+    let entries = fs::read_dir(dir).map_err(|err| file_error(dir, &err))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|err| file_error(dir, &err))?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let (sql, data): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .filter(|path| {
+            matches!(
+                extension(path).as_deref(),
+                Some("sql" | "json" | "yaml" | "yml")
+            )
+        })
+        .partition(|path| extension(path).as_deref() == Some("sql"));
+    Ok((sql, data))
+}
+
+fn parse_data_file(path: &Path) -> Result<Vec<TableRows>, SqlMiddlewareDbError> {
+    let text = read(path)?;
+    let document: Value = match extension(path).as_deref() {
+        Some("json") => serde_json::from_str(&text).map_err(|err| file_error(path, &err))?,
+        #[cfg(feature = "yaml")]
+        _ => serde_yaml::from_str(&text).map_err(|err| file_error(path, &err))?,
+        #[cfg(not(feature = "yaml"))]
+        _ => {
+            return Err(SqlMiddlewareDbError::ConfigError(format!(
+                "{}: YAML fixtures need the `yaml` feature",
+                path.display()
+            )));
+        }
+    };
+    let Value::Object(tables) = document else {
+        return Err(shape_error(path, "an object of table names"));
+    };
+    tables
+        .into_iter()
+        .map(|(table, rows)| {
+            let Value::Array(rows) = rows else {
+                return Err(shape_error(path, "an array of rows for each table"));
+            };
+            let rows = rows
+                .into_iter()
+                .map(|row| match row {
+                    Value::Object(row) => Ok(row),
+                    _ => Err(shape_error(path, "each row to be an object")),
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(TableRows { name: table, rows })
+        })
+        .collect()
+}
+
+/// Order `tables` so that every table comes after the fixture tables it references.
+///
+/// Ties keep the order the tables were first seen in. Self-references and references to tables
+/// outside the fixtures do not constrain the order.
+fn dependency_order(
+    mut pending: Vec<TableRows>,
+    keys: &[ForeignKey],
+) -> Result<Vec<TableRows>, SqlMiddlewareDbError> {
+    let lower = |name: &str| name.to_lowercase();
+    let mut ordered = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let unplaced: HashSet<String> = pending.iter().map(|table| lower(&table.name)).collect();
+        let ready = pending.iter().position(|table| {
+            keys.iter()
+                .filter(|key| key.table.eq_ignore_ascii_case(&table.name))
+                .map(|key| lower(&key.referenced_table))
+                .all(|parent| parent == lower(&table.name) || !unplaced.contains(&parent))
+        });
+        let Some(index) = ready else {
+            let names: Vec<&str> = pending.iter().map(|table| table.name.as_str()).collect();
+            return Err(SqlMiddlewareDbError::ConfigError(format!(
+                "fixture tables reference each other in a cycle: {}",
+                names.join(", ")
+            )));
+        };
+        ordered.push(pending.remove(index));
+    }
+    Ok(ordered)
+}
+
+/// Split rows into runs that set the same columns, keeping their order.
+fn group_by_columns(rows: &[Map<String, Value>]) -> Vec<(Vec<&str>, Vec<Vec<RowValues>>)> {
+    let mut groups: Vec<(Vec<&str>, Vec<Vec<RowValues>>)> = Vec::new();
+    for row in rows {
+        let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+        let values = row.values().map(row_value).collect();
+        match groups.last_mut() {
+            Some((last, group)) if *last == columns => group.push(values),
+            _ => groups.push((columns, vec![values])),
+        }
+    }
+    groups
+}
+
+fn row_value(value: &Value) -> RowValues {
+    match value {
+        Value::Null => RowValues::Null,
+        Value::Bool(b) => RowValues::Bool(*b),
+        Value::Number(number) => number.as_i64().map_or_else(
+            || RowValues::Float(number.as_f64().unwrap_or(f64::NAN)),
+            RowValues::Int,
+        ),
+        Value::String(text) => RowValues::Text(text.as_str().into()),
+        Value::Array(_) | Value::Object(_) => RowValues::JSON(value.clone()),
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+fn read(path: &Path) -> Result<String, SqlMiddlewareDbError> {
+    fs::read_to_string(path).map_err(|err| file_error(path, &err))
+}
+
+fn file_error(path: &Path, err: &dyn std::fmt::Display) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ConfigError(format!("{}: {err}", path.display()))
+}
+
+fn shape_error(path: &Path, expected: &str) -> SqlMiddlewareDbError {
+    SqlMiddlewareDbError::ConfigError(format!(
+        "{}: fixture files must contain {expected}",
+        path.display()
+    ))
+}
@@ -1,10 +1,103 @@
 //! Helper utilities for testing and development.
 
-use crate::middleware::{CustomDbRow, RowValues};
+use crate::middleware::{
+    CustomDbRow, DatabaseType, MiddlewarePoolConnection, RowValues, SqlMiddlewareDbError,
+};
+use crate::query_builder::explain::render_plan;
 use std::sync::Arc;
 
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mssql"))]
+#[path = "test_transaction.rs"]
+mod test_transaction;
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mssql"))]
+pub use test_transaction::TestTransaction;
+
+#[cfg(feature = "json")]
+#[path = "fixtures.rs"]
+pub mod fixtures;
+
 /// Create a test row with the given column names and values.
 #[must_use]
 pub fn create_test_row(column_names: Vec<String>, values: Vec<RowValues>) -> CustomDbRow {
     CustomDbRow::new(Arc::new(column_names), values)
 }
+
+/// The plan the backend chooses for `sql`, one line per plan row.
+///
+/// Runs `EXPLAIN` on Postgres and `EXPLAIN QUERY PLAN` on `SQLite` and Turso, with the
+/// connection's default placeholder translation.
+///
+/// # Errors
+/// Returns `SqlMiddlewareDbError::Unimplemented` on SQL Server, or the backend's error if the
+/// plan query fails.
+pub async fn explain_plan(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    params: &[RowValues],
+) -> Result<String, SqlMiddlewareDbError> {
+    let prefix: Option<&str> = match conn.database_type() {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => Some("EXPLAIN"),
+        #[cfg(feature = "sqlite")]
+        DatabaseType::Sqlite => Some("EXPLAIN QUERY PLAN"),
+        #[cfg(feature = "turso")]
+        DatabaseType::Turso => Some("EXPLAIN QUERY PLAN"),
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => None,
+    };
+    let Some(prefix) = prefix else {
+        return Err(SqlMiddlewareDbError::Unimplemented(
+            "plan inspection is not supported on SQL Server".into(),
+        ));
+    };
+    let explain = format!("{prefix} {sql}");
+    let plan = conn.query(&explain).params(params).select().await?;
+    Ok(render_plan(&plan))
+}
+
+/// Assert that the plan for `sql` uses the index named `index`.
+///
+/// Lock in index usage for critical queries in a test suite:
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+/// use sql_middleware::test_helpers::assert_plan_uses_index;
+///
+/// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+/// assert_plan_uses_index(
+///     conn,
+///     "SELECT * FROM users WHERE email = ?1",
+///     &[RowValues::Text("a@example.com".into())],
+///     "idx_users_email",
+/// )
+/// .await?;
+/// # Ok(()) }
+/// ```
+///
+/// # Errors
+/// Returns an error if the plan cannot be obtained (see [`explain_plan`]).
+///
+/// # Panics
+/// Panics, printing the plan, if no plan line names `index`.
+pub async fn assert_plan_uses_index(
+    conn: &mut MiddlewarePoolConnection,
+    sql: &str,
+    params: &[RowValues],
+    index: &str,
+) -> Result<(), SqlMiddlewareDbError> {
+    let plan = explain_plan(conn, sql, params).await?;
+    assert!(
+        mentions_identifier(&plan, index),
+        "expected the plan for `{sql}` to use index `{index}`, got:\n{plan}"
+    );
+    Ok(())
+}
+
+/// Whether `text` contains `ident` as a whole identifier, not as part of a longer name.
+fn mentions_identifier(text: &str, ident: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(ident).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + ident.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
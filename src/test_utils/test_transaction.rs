@@ -0,0 +1,166 @@
+use crate::middleware::{DatabaseType, MiddlewarePoolConnection, SqlMiddlewareDbError};
+use crate::pool::transaction::Level;
+use crate::query_builder::QueryBuilder;
+
+#[cfg(feature = "mssql")]
+use crate::mssql;
+#[cfg(feature = "postgres")]
+use crate::postgres;
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+
+/// A transaction that is never committed, for tests against a shared database.
+///
+/// Everything run through it happens inside one outer transaction, which
+/// [`rollback`](TestTransaction::rollback) (or dropping the handle) undoes at the end of the test,
+/// so test cases stay isolated without truncating tables. Work the code under test would commit
+/// goes through [`transaction`](TestTransaction::transaction), which opens a savepoint and keeps
+/// the same commit/rollback behavior one level down.
+///
+/// Postgres, `SQLite` and SQL Server are supported. Prefer calling `rollback` explicitly: SQL Server
+/// can only roll back a dropped transaction on a multi-threaded runtime.
+///
+/// ```rust,no_run
+/// use sql_middleware::prelude::*;
+/// use sql_middleware::test_helpers::TestTransaction;
+///
+/// # async fn demo(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+/// let mut conn = cap.get_connection().await?;
+/// let mut tx = TestTransaction::begin(&mut conn).await?;
+/// tx.transaction(async |tx| tx.execute_batch("INSERT INTO users (name) VALUES ('ann')").await)
+///     .await?;
+/// let users = tx.query("SELECT COUNT(*) FROM users").select_scalar::<i64>().await?;
+/// assert_eq!(users, 1);
+/// tx.rollback().await?; // the shared database is unchanged
+/// # Ok(()) }
+/// ```
+pub struct TestTransaction<'a> {
+    tx: TestTx<'a>,
+}
+
+enum TestTx<'a> {
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::Tx<'a>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlite::Tx<'a>),
+    #[cfg(feature = "mssql")]
+    Mssql(mssql::Tx<'a>),
+}
+
+impl<'a> TestTransaction<'a> {
+    /// Begin the outer transaction on `conn`.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::Unimplemented` on Turso, or the backend's error if the
+    /// transaction cannot begin.
+    pub async fn begin(
+        conn: &'a mut MiddlewarePoolConnection,
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let tx = match conn {
+            #[cfg(feature = "postgres")]
+            MiddlewarePoolConnection::Postgres { client, .. } => {
+                TestTx::Postgres(postgres::begin_transaction(client).await?.untracked())
+            }
+            #[cfg(feature = "sqlite")]
+            MiddlewarePoolConnection::Sqlite { .. } => {
+                TestTx::Sqlite(sqlite::begin_transaction(conn).await?.untracked())
+            }
+            #[cfg(feature = "mssql")]
+            MiddlewarePoolConnection::Mssql { conn, .. } => {
+                TestTx::Mssql(mssql::begin_transaction(conn).await?.untracked())
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(SqlMiddlewareDbError::Unimplemented(
+                    "TestTransaction supports Postgres, SQLite and SQL Server".into(),
+                ));
+            }
+        };
+        Ok(Self { tx })
+    }
+
+    /// Backend this transaction runs on.
+    #[must_use]
+    pub fn database_type(&self) -> DatabaseType {
+        match &self.tx {
+            #[cfg(feature = "postgres")]
+            TestTx::Postgres(_) => DatabaseType::Postgres,
+            #[cfg(feature = "sqlite")]
+            TestTx::Sqlite(_) => DatabaseType::Sqlite,
+            #[cfg(feature = "mssql")]
+            TestTx::Mssql(_) => DatabaseType::Mssql,
+        }
+    }
+
+    /// Start a [`QueryBuilder`] that runs inside the transaction.
+    ///
+    /// Placeholder translation defaults as it does for the backend's own `Tx::query_builder`.
+    pub fn query<'q>(&mut self, sql: &'q str) -> QueryBuilder<'_, 'q> {
+        match &mut self.tx {
+            #[cfg(feature = "postgres")]
+            TestTx::Postgres(tx) => tx.query_builder(sql),
+            #[cfg(feature = "sqlite")]
+            TestTx::Sqlite(tx) => tx.query_builder(sql),
+            #[cfg(feature = "mssql")]
+            TestTx::Mssql(tx) => tx.query_builder(sql),
+        }
+    }
+
+    /// Execute a batch inside the transaction.
+    ///
+    /// # Errors
+    /// Returns the backend's error if a statement fails.
+    pub async fn execute_batch(&mut self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
+        match &mut self.tx {
+            #[cfg(feature = "postgres")]
+            TestTx::Postgres(tx) => tx.execute_batch(sql).await,
+            #[cfg(feature = "sqlite")]
+            TestTx::Sqlite(tx) => tx.execute_batch(sql).await,
+            #[cfg(feature = "mssql")]
+            TestTx::Mssql(tx) => tx.execute_batch(sql).await,
+        }
+    }
+
+    /// Run `f` in a savepoint, standing in for a transaction the code under test would commit.
+    ///
+    /// The savepoint is released when `f` returns `Ok` and rolled back when it returns `Err`,
+    /// like [`MiddlewarePoolConnection::transaction`] one level down. Calls nest.
+    ///
+    /// # Errors
+    /// Returns the error from `f`, or from creating or releasing the savepoint.
+    pub async fn transaction<F, R>(&mut self, f: F) -> Result<R, SqlMiddlewareDbError>
+    where
+        F: AsyncFnOnce(&mut TestTransaction<'a>) -> Result<R, SqlMiddlewareDbError>,
+    {
+        let level = Level::savepoint(self.database_type());
+        self.execute_batch(&level.begin).await?;
+        match f(self).await {
+            Ok(value) => {
+                if let Some(release) = &level.commit {
+                    self.execute_batch(release).await?;
+                }
+                Ok(value)
+            }
+            Err(err) => {
+                let _ = self.execute_batch(&level.rollback).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Roll back everything done in the transaction.
+    ///
+    /// # Errors
+    /// Returns the backend's error if the rollback fails.
+    pub async fn rollback(self) -> Result<(), SqlMiddlewareDbError> {
+        match self.tx {
+            #[cfg(feature = "postgres")]
+            TestTx::Postgres(tx) => tx.rollback().await?,
+            #[cfg(feature = "sqlite")]
+            TestTx::Sqlite(tx) => tx.rollback().await?,
+            #[cfg(feature = "mssql")]
+            TestTx::Mssql(tx) => tx.rollback().await?,
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use super::parsers::{is_block_comment_start, is_line_comment_start, try_start_dollar_quote};
+use super::scanner::{State, scan_digits, step_non_code};
+
+/// What [`analyze`] found in a statement: the placeholders translation would see, the regions it
+/// skips, and anything that makes translating it unsafe.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranslationReport {
+    /// Placeholder-like tokens outside literals and comments, in order.
+    pub placeholders: Vec<PlaceholderSpan>,
+    /// Literals, quoted identifiers, comments and dollar-quoted blocks, in order.
+    pub regions: Vec<SkippedRegion>,
+    /// Constructs the scanner cannot handle safely.
+    pub warnings: Vec<TranslationWarning>,
+}
+
+impl TranslationReport {
+    /// Whether translation can be trusted with this statement (no warnings).
+    #[must_use]
+    pub fn is_safe(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// A placeholder-like token and its byte range in the statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaceholderSpan {
+    pub span: Range<usize>,
+    pub kind: PlaceholderKind,
+}
+
+/// Style of a placeholder-like token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    /// Postgres-style `$N`.
+    Dollar,
+    /// SQLite-style `?N`.
+    Question,
+    /// ODBC-style `?` with no number.
+    Unnumbered,
+    /// `??`, which translation turns into a literal `?` where it numbers unnumbered `?`.
+    Escaped,
+}
+
+/// A part of the statement translation leaves alone, and its byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRegion {
+    pub span: Range<usize>,
+    pub kind: RegionKind,
+}
+
+/// Kind of [`SkippedRegion`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionKind {
+    /// `'...'`
+    SingleQuoted,
+    /// `"..."`
+    DoubleQuoted,
+    /// `-- ...` up to the end of the line.
+    LineComment,
+    /// `/* ... */`, possibly nested.
+    BlockComment,
+    /// `$tag$ ... $tag$`; `tag` is empty for `$$`.
+    DollarQuoted { tag: String },
+}
+
+/// Something [`analyze`] does not trust translation with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranslationWarning {
+    /// A literal, quoted identifier, block comment or dollar-quoted block starting at `offset`
+    /// never ends, so everything after it is skipped.
+    Unterminated { offset: usize, kind: RegionKind },
+    /// Unnumbered `?` at `offset` alongside numbered placeholders.
+    /// [`try_translate_placeholders`](super::try_translate_placeholders) rejects this for `SQLite`
+    /// and SQL Server targets; a Postgres target keeps the `?` as the jsonb operator.
+    MixedPlaceholders { offset: usize },
+    /// Placeholders at `offset` sit between two blocks quoted with the same `$tag$`. If the
+    /// blocks were meant to nest, the scanner closed the outer one early and the placeholders
+    /// belong to the body; Postgres cannot nest a tag inside itself either, so use distinct tags.
+    SameTagDollarQuotes { offset: usize, tag: String },
+}
+
+impl fmt::Display for TranslationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unterminated { offset, kind } => {
+                write!(f, "{kind:?} starting at byte {offset} is never closed")
+            }
+            Self::MixedPlaceholders { offset } => write!(
+                f,
+                "unnumbered `?` at byte {offset} mixed with numbered placeholders"
+            ),
+            Self::SameTagDollarQuotes { offset, tag } => write!(
+                f,
+                "placeholder at byte {offset} between two ${tag}$ blocks; nested blocks need \
+                 distinct tags"
+            ),
+        }
+    }
+}
+
+/// Report the placeholders, skipped regions and unsafe constructs in `sql`, using the same
+/// scanner as [`translate_placeholders`](super::translate_placeholders).
+///
+/// Run it over a query corpus before turning translation on to find the statements it would
+/// get wrong.
+///
+/// ```rust
+/// use sql_middleware::translation::{PlaceholderKind, analyze};
+///
+/// let report = analyze("SELECT '?1', $1 FROM t WHERE a = ?");
+/// assert_eq!(report.regions.len(), 1);
+/// assert_eq!(
+///     report.placeholders.iter().map(|p| p.kind).collect::<Vec<_>>(),
+///     [PlaceholderKind::Dollar, PlaceholderKind::Unnumbered]
+/// );
+/// assert!(!report.is_safe());
+/// ```
+#[must_use]
+pub fn analyze(sql: &str) -> TranslationReport {
+    let mut report = scan(sql);
+
+    let numbered = report
+        .placeholders
+        .iter()
+        .any(|p| matches!(p.kind, PlaceholderKind::Dollar | PlaceholderKind::Question));
+    if numbered
+        && let Some(bare) = report
+            .placeholders
+            .iter()
+            .find(|p| p.kind == PlaceholderKind::Unnumbered)
+    {
+        report.warnings.push(TranslationWarning::MixedPlaceholders {
+            offset: bare.span.start,
+        });
+    }
+
+    let mut same_tag = Vec::new();
+    let dollar_blocks: Vec<(&Range<usize>, &String)> = report
+        .regions
+        .iter()
+        .filter_map(|region| match &region.kind {
+            RegionKind::DollarQuoted { tag } => Some((&region.span, tag)),
+            _ => None,
+        })
+        .collect();
+    for pair in dollar_blocks.windows(2) {
+        let ((first, tag), (second, next_tag)) = (pair[0], pair[1]);
+        if tag != next_tag {
+            continue;
+        }
+        if let Some(p) = report
+            .placeholders
+            .iter()
+            .find(|p| p.span.start >= first.end && p.span.end <= second.start)
+        {
+            same_tag.push(TranslationWarning::SameTagDollarQuotes {
+                offset: p.span.start,
+                tag: tag.clone(),
+            });
+        }
+    }
+    report.warnings.extend(same_tag);
+    report
+}
+
+/// Placeholders and skipped regions, plus unterminated-region warnings.
+pub(crate) fn scan(sql: &str) -> TranslationReport {
+    let mut report = TranslationReport::default();
+    let mut state = State::Normal;
+    let mut region_start = 0;
+    let mut idx = 0;
+    let bytes = sql.as_bytes();
+
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        match state {
+            State::Normal => {
+                region_start = idx;
+                match b {
+                    b'\'' => state = State::SingleQuoted,
+                    b'"' => state = State::DoubleQuoted,
+                    _ if is_line_comment_start(bytes, idx) => state = State::LineComment,
+                    _ if is_block_comment_start(bytes, idx) => state = State::BlockComment(1),
+                    b'$' => {
+                        if let Some((tag, advance)) = try_start_dollar_quote(bytes, idx) {
+                            state = State::DollarQuoted(tag);
+                            idx = advance;
+                        } else if let Some((digits_end, _)) = scan_digits(bytes, idx + 1) {
+                            report.placeholders.push(PlaceholderSpan {
+                                span: idx..digits_end,
+                                kind: PlaceholderKind::Dollar,
+                            });
+                            idx = digits_end - 1;
+                        }
+                    }
+                    b'?' => match bytes.get(idx + 1) {
+                        Some(b'?') => {
+                            report.placeholders.push(PlaceholderSpan {
+                                span: idx..idx + 2,
+                                kind: PlaceholderKind::Escaped,
+                            });
+                            idx += 1;
+                        }
+                        Some(b'|' | b'&') => idx += 1,
+                        _ => {
+                            let (end, kind) = match scan_digits(bytes, idx + 1) {
+                                Some((digits_end, _)) => (digits_end, PlaceholderKind::Question),
+                                None => (idx + 1, PlaceholderKind::Unnumbered),
+                            };
+                            report.placeholders.push(PlaceholderSpan {
+                                span: idx..end,
+                                kind,
+                            });
+                            idx = end - 1;
+                        }
+                    },
+                    _ => {}
+                }
+            }
+            _ => {
+                let kind = region_kind(&state);
+                idx = step_non_code(&mut state, bytes, idx);
+                if matches!(state, State::Normal) {
+                    // The scanner stops one byte short of `*/` and of a closing `$tag$`.
+                    let end = match kind {
+                        RegionKind::BlockComment | RegionKind::DollarQuoted { .. } => idx + 2,
+                        _ => idx + 1,
+                    };
+                    report.regions.push(SkippedRegion {
+                        span: region_start..end.min(sql.len()),
+                        kind,
+                    });
+                }
+            }
+        }
+        idx += 1;
+    }
+
+    if !matches!(state, State::Normal) {
+        let kind = region_kind(&state);
+        if kind != RegionKind::LineComment {
+            report.warnings.push(TranslationWarning::Unterminated {
+                offset: region_start,
+                kind: kind.clone(),
+            });
+        }
+        report.regions.push(SkippedRegion {
+            span: region_start..sql.len(),
+            kind,
+        });
+    }
+    report
+}
+
+fn region_kind(state: &State) -> RegionKind {
+    match state {
+        State::Normal => unreachable!("code is not a skipped region"),
+        State::SingleQuoted => RegionKind::SingleQuoted,
+        State::DoubleQuoted => RegionKind::DoubleQuoted,
+        State::LineComment => RegionKind::LineComment,
+        State::BlockComment(_) => RegionKind::BlockComment,
+        State::DollarQuoted(tag) => RegionKind::DollarQuoted { tag: tag.clone() },
+    }
+}
@@ -0,0 +1,354 @@
+//! The dependency-free part of translation: the literal/comment-aware scanner, placeholder
+//! rewriting, statement splitting and keyword checks.
+//!
+//! Everything here uses only `core` and `alloc`. Tools that cannot take on the rest of the crate
+//! (proc-macros, WASM validators, `no_std` firmware) can compile this directory on its own:
+//!
+//! ```rust,ignore
+//! #![no_std]
+//! extern crate alloc;
+//!
+//! #[path = "vendor/sql-middleware/src/translation/core/mod.rs"]
+//! pub mod sql_core;
+//! ```
+//!
+//! The same items are re-exported from [`crate::translation`].
+
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+mod analysis;
+pub(crate) mod parsers;
+pub(crate) mod scanner;
+
+pub use analysis::{
+    PlaceholderKind, PlaceholderSpan, RegionKind, SkippedRegion, TranslationReport,
+    TranslationWarning, analyze,
+};
+use scanner::{code_words, top_level_words};
+
+/// Target placeholder style for translation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlaceholderStyle {
+    /// PostgreSQL-style placeholders like `$1`.
+    Postgres,
+    /// SQLite-style placeholders like `?1` (also used by Turso).
+    Sqlite,
+    /// SQL Server placeholders like `@P1`. Only a target: `@PN` in the source is left alone.
+    Mssql,
+}
+
+/// Report whether `sql` orders its result: it has an `ORDER BY` outside string literals,
+/// comments and parentheses.
+///
+/// Uses the same lightweight scanner as [`translate_placeholders`]. An `ORDER BY` inside a
+/// subquery, CTE body or window definition does not order the result, so it does not count.
+#[must_use]
+pub fn has_order_by(sql: &str) -> bool {
+    top_level_words(sql)
+        .windows(2)
+        .any(|pair| pair[0].1.eq_ignore_ascii_case("order") && pair[1].1.eq_ignore_ascii_case("by"))
+}
+
+/// Report whether `sql` is a read-only query: it starts with `SELECT`, or with `WITH` and
+/// contains no data-modifying keyword.
+pub(crate) fn is_select(sql: &str) -> bool {
+    let words = code_words(sql);
+    match words.first() {
+        Some(first) if first.eq_ignore_ascii_case("select") => true,
+        Some(first) if first.eq_ignore_ascii_case("with") => !words.iter().any(|word| {
+            ["insert", "update", "delete", "merge"]
+                .iter()
+                .any(|dml| word.eq_ignore_ascii_case(dml))
+        }),
+        _ => false,
+    }
+}
+
+/// How a piece of SQL runs, as [`statement_kind`] classifies it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    /// One statement that returns rows: a query, or DML with `RETURNING` / `OUTPUT`.
+    Select,
+    /// One statement that does not return rows (DML or DDL).
+    Dml,
+    /// Several statements, or none, to run as a batch.
+    Batch,
+}
+
+/// Classify `sql` by its leading keyword and statement count.
+///
+/// Uses the same lightweight scanner as [`translate_placeholders`], so keywords inside literals
+/// and comments are ignored.
+/// - `SELECT`, read-only `WITH`, `VALUES`, `TABLE`, `SHOW`, `EXPLAIN`, `DESCRIBE`, `PRAGMA`,
+///   `CALL` and `EXEC`/`EXECUTE` are [`Select`](StatementKind::Select), as is any statement with
+///   a `RETURNING` clause, or DML with an `OUTPUT` clause.
+/// - Any other single statement is [`Dml`](StatementKind::Dml).
+/// - Anything [`split_statements`] does not split into exactly one statement is
+///   [`Batch`](StatementKind::Batch).
+///
+/// ```rust
+/// use sql_middleware::translation::{StatementKind, statement_kind};
+///
+/// assert_eq!(statement_kind("SELECT 1"), StatementKind::Select);
+/// assert_eq!(statement_kind("INSERT INTO t VALUES (1) RETURNING id"), StatementKind::Select);
+/// assert_eq!(statement_kind("UPDATE t SET a = 'select'"), StatementKind::Dml);
+/// let script = "CREATE TABLE t (a INT); INSERT INTO t VALUES (1);";
+/// assert_eq!(statement_kind(script), StatementKind::Batch);
+/// ```
+#[must_use]
+pub fn statement_kind(sql: &str) -> StatementKind {
+    let [statement] = split_statements(sql)[..] else {
+        return StatementKind::Batch;
+    };
+    if is_select(statement) {
+        return StatementKind::Select;
+    }
+    let words = code_words(statement);
+    let is = |word: &str, keywords: &[&str]| keywords.iter().any(|k| word.eq_ignore_ascii_case(k));
+    let first = words.first().copied().unwrap_or_default();
+    let returns_rows = is(
+        first,
+        &[
+            "values", "table", "show", "explain", "describe", "desc", "pragma", "call", "exec",
+            "execute",
+        ],
+    ) || words.iter().any(|word| is(word, &["returning"]))
+        || (is(first, &["insert", "update", "delete", "merge"])
+            && words.iter().any(|word| is(word, &["output"])));
+    if returns_rows {
+        StatementKind::Select
+    } else {
+        StatementKind::Dml
+    }
+}
+
+/// Split a script into its statements, without the trailing `;`.
+///
+/// Uses the same lightweight scanner as [`translate_placeholders`]: semicolons inside literals,
+/// comments, dollar-quoted blocks, and `CREATE TRIGGER ... BEGIN ... END` bodies don't split.
+/// Statements that are empty or only comments are dropped. T-SQL `GO` separators are not
+/// recognized.
+///
+/// ```rust
+/// use sql_middleware::translation::split_statements;
+///
+/// let script = "CREATE TABLE t (a TEXT); INSERT INTO t VALUES ('x;y'); -- done";
+/// assert_eq!(
+///     split_statements(script),
+///     ["CREATE TABLE t (a TEXT)", "INSERT INTO t VALUES ('x;y')"]
+/// );
+/// ```
+#[must_use]
+pub fn split_statements(sql: &str) -> Vec<&str> {
+    scanner::split_statements(sql)
+}
+
+/// Why [`try_translate_placeholders`] refused a statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationError {
+    /// The statement uses unnumbered `?` placeholders alongside numbered ones, so which
+    /// parameter each refers to is ambiguous. `offset` is the byte offset of the first `?`.
+    MixedPlaceholders { offset: usize },
+}
+
+impl fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MixedPlaceholders { offset } => write!(
+                f,
+                "unnumbered `?` placeholder at byte {offset} in a statement that also uses \
+                 numbered placeholders"
+            ),
+        }
+    }
+}
+
+/// Translate placeholders between Postgres-style `$N` and SQLite-style `?N`, or from either to
+/// SQL Server's `@PN`.
+///
+/// For `SQLite` and SQL Server targets, unnumbered ODBC-style `?` placeholders are numbered in
+/// order of appearance, so `a = ? AND b = ?` becomes `a = ?1 AND b = ?2` (or `@P1`, `@P2`); in
+/// such a statement `??` stands for a literal `?`. A Postgres target does not number them, because
+/// Postgres reads `?` as the jsonb "key exists" operator; its `?`, `??`, `?|` and `?&` are left
+/// alone; [`try_translate_parameterized`] numbers them there too, for statements that take
+/// parameters. When a statement mixes unnumbered and numbered placeholders it is returned
+/// unchanged; use [`try_translate_placeholders`] to get an error instead.
+///
+/// Warning: translation skips quoted strings, comments, and dollar-quoted blocks via a lightweight
+/// state machine; it may still miss edge cases in complex SQL. For dialect-specific SQL (e.g.,
+/// PL/pgSQL bodies), prefer backend-specific SQL instead of relying on translation:
+/// ```rust
+/// # use sql_middleware::prelude::*;
+/// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
+/// let query = match conn {
+///     MiddlewarePoolConnection::Postgres { .. } => r#"$function$
+/// BEGIN
+///     RETURN ($1 ~ $q$[\t\r\n\v\\]$q$);
+/// END;
+/// $function$"#,
+///     MiddlewarePoolConnection::Sqlite { .. } | MiddlewarePoolConnection::Turso { .. } => {
+///         include_str!("../sql/functions/sqlite/03_sp_get_scores.sql")
+///     }
+/// };
+/// # let _ = query;
+/// # Ok(())
+/// # }
+/// ```
+/// Returns a borrowed `Cow` when no changes are needed.
+#[must_use]
+pub fn translate_placeholders(sql: &str, target: PlaceholderStyle, enabled: bool) -> Cow<'_, str> {
+    try_translate_placeholders(sql, target, enabled).unwrap_or(Cow::Borrowed(sql))
+}
+
+/// Like [`translate_placeholders`], but fails on statements for `SQLite` or SQL Server that mix
+/// unnumbered `?` placeholders with numbered ones.
+///
+/// ```rust
+/// use sql_middleware::translation::{PlaceholderStyle, try_translate_placeholders};
+///
+/// let sql = "SELECT * FROM t WHERE a = ? AND b = ?";
+/// assert_eq!(
+///     try_translate_placeholders(sql, PlaceholderStyle::Mssql, true).unwrap(),
+///     "SELECT * FROM t WHERE a = @P1 AND b = @P2"
+/// );
+/// let mixed = "SELECT * FROM t WHERE a = ? AND b = ?2";
+/// assert!(try_translate_placeholders(mixed, PlaceholderStyle::Sqlite, true).is_err());
+/// // For Postgres, `?` is the jsonb operator.
+/// let jsonb = "SELECT * FROM t WHERE data ? 'k' AND id = ?1";
+/// assert_eq!(
+///     try_translate_placeholders(jsonb, PlaceholderStyle::Postgres, true).unwrap(),
+///     "SELECT * FROM t WHERE data ? 'k' AND id = $1"
+/// );
+/// ```
+///
+/// # Errors
+/// Returns [`TranslationError::MixedPlaceholders`] for a statement mixing unnumbered and numbered
+/// placeholders.
+pub fn try_translate_placeholders(
+    sql: &str,
+    target: PlaceholderStyle,
+    enabled: bool,
+) -> Result<Cow<'_, str>, TranslationError> {
+    translate(sql, target, enabled, false)
+}
+
+/// Like [`try_translate_placeholders`], for a statement known to take parameters, as the query
+/// builder sends whenever it has any.
+///
+/// Such a statement must reference its parameters somehow. So for a Postgres target, when it has
+/// no numbered placeholders, its bare `?` are the parameters and are numbered too, and `??`
+/// stands for the jsonb `?` operator. Alongside numbered placeholders, a bare `?` stays the
+/// operator.
+///
+/// ```rust
+/// use sql_middleware::translation::{PlaceholderStyle, try_translate_parameterized};
+///
+/// let sql = "SELECT * FROM t WHERE data ?? 'k' AND id = ?";
+/// assert_eq!(
+///     try_translate_parameterized(sql, PlaceholderStyle::Postgres, true).unwrap(),
+///     "SELECT * FROM t WHERE data ? 'k' AND id = $1"
+/// );
+/// ```
+///
+/// # Errors
+/// Returns [`TranslationError::MixedPlaceholders`] like [`try_translate_placeholders`].
+pub fn try_translate_parameterized(
+    sql: &str,
+    target: PlaceholderStyle,
+    enabled: bool,
+) -> Result<Cow<'_, str>, TranslationError> {
+    translate(sql, target, enabled, true)
+}
+
+/// Shared body of the `try_translate_*` functions; `parameterized` lets a Postgres target number
+/// bare `?` in a statement without numbered placeholders.
+fn translate(
+    sql: &str,
+    target: PlaceholderStyle,
+    enabled: bool,
+    parameterized: bool,
+) -> Result<Cow<'_, str>, TranslationError> {
+    if !enabled {
+        return Ok(Cow::Borrowed(sql));
+    }
+
+    let placeholders = analysis::scan(sql).placeholders;
+    let first_bare = placeholders
+        .iter()
+        .find(|p| p.kind == PlaceholderKind::Unnumbered);
+    let has_dollar = placeholders
+        .iter()
+        .any(|p| p.kind == PlaceholderKind::Dollar);
+    let has_question = placeholders
+        .iter()
+        .any(|p| p.kind == PlaceholderKind::Question);
+    let bare_are_placeholders = match first_bare {
+        None => false,
+        // Postgres reads a bare `?` as the jsonb "key exists" operator.
+        Some(_) if matches!(target, PlaceholderStyle::Postgres) => {
+            parameterized && !has_dollar && !has_question
+        }
+        Some(_) if !has_dollar && !has_question => true,
+        Some(bare) => {
+            return Err(TranslationError::MixedPlaceholders {
+                offset: bare.span.start,
+            });
+        }
+    };
+
+    let mut out: Option<String> = None;
+    let mut copied = 0;
+    let mut next = 0_usize;
+    for PlaceholderSpan { span, kind } in &placeholders {
+        let (start, end) = (span.start, span.end);
+        let replacement = match (kind, target) {
+            (PlaceholderKind::Dollar, PlaceholderStyle::Sqlite) => {
+                Some(("?", &sql[start + 1..end]))
+            }
+            (PlaceholderKind::Question, PlaceholderStyle::Postgres) => {
+                Some(("$", &sql[start + 1..end]))
+            }
+            (PlaceholderKind::Dollar | PlaceholderKind::Question, PlaceholderStyle::Mssql) => {
+                Some(("@P", &sql[start + 1..end]))
+            }
+            (PlaceholderKind::Escaped, _) if bare_are_placeholders => Some(("?", "")),
+            _ => None,
+        };
+        let buf = match (replacement, kind) {
+            (Some(_), _) => out.get_or_insert_with(String::new),
+            (None, PlaceholderKind::Unnumbered) if bare_are_placeholders => {
+                out.get_or_insert_with(String::new)
+            }
+            _ => continue,
+        };
+        buf.push_str(&sql[copied..start]);
+        match replacement {
+            Some((prefix, digits)) => {
+                buf.push_str(prefix);
+                buf.push_str(digits);
+            }
+            None => {
+                next += 1;
+                buf.push_str(match target {
+                    PlaceholderStyle::Postgres => "$",
+                    PlaceholderStyle::Sqlite => "?",
+                    PlaceholderStyle::Mssql => "@P",
+                });
+                buf.push_str(&next.to_string());
+            }
+        }
+        copied = end;
+    }
+
+    Ok(match out {
+        Some(mut buf) => {
+            buf.push_str(&sql[copied..]);
+            Cow::Owned(buf)
+        }
+        None => Cow::Borrowed(sql),
+    })
+}
@@ -1,16 +1,18 @@
-pub(super) fn is_line_comment_start(bytes: &[u8], idx: usize) -> bool {
+use alloc::string::String;
+
+pub(crate) fn is_line_comment_start(bytes: &[u8], idx: usize) -> bool {
     bytes.get(idx) == Some(&b'-') && bytes.get(idx + 1) == Some(&b'-')
 }
 
-pub(super) fn is_block_comment_start(bytes: &[u8], idx: usize) -> bool {
+pub(crate) fn is_block_comment_start(bytes: &[u8], idx: usize) -> bool {
     bytes.get(idx) == Some(&b'/') && bytes.get(idx + 1) == Some(&b'*')
 }
 
-pub(super) fn is_block_comment_end(bytes: &[u8], idx: usize) -> bool {
+pub(crate) fn is_block_comment_end(bytes: &[u8], idx: usize) -> bool {
     bytes.get(idx) == Some(&b'*') && bytes.get(idx + 1) == Some(&b'/')
 }
 
-pub(super) fn try_start_dollar_quote(bytes: &[u8], start: usize) -> Option<(String, usize)> {
+pub(crate) fn try_start_dollar_quote(bytes: &[u8], start: usize) -> Option<(String, usize)> {
     let mut idx = start + 1;
     while idx < bytes.len() && bytes[idx] != b'$' {
         let b = bytes[idx];
@@ -28,7 +30,7 @@ pub(super) fn try_start_dollar_quote(bytes: &[u8], start: usize) -> Option<(Stri
     }
 }
 
-pub(super) fn matches_tag(bytes: &[u8], idx: usize, tag: &str) -> bool {
+pub(crate) fn matches_tag(bytes: &[u8], idx: usize, tag: &str) -> bool {
     let end = idx + 1 + tag.len();
     end < bytes.len()
         && bytes[idx + 1..=end].starts_with(tag.as_bytes())
@@ -0,0 +1,242 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::parsers::{
+    is_block_comment_end, is_block_comment_start, is_line_comment_start, matches_tag,
+    try_start_dollar_quote,
+};
+
+#[derive(Clone)]
+pub(crate) enum State {
+    Normal,
+    SingleQuoted,
+    DoubleQuoted,
+    LineComment,
+    BlockComment(u32),
+    DollarQuoted(String),
+}
+
+pub(crate) fn scan_digits(bytes: &[u8], start: usize) -> Option<(usize, &str)> {
+    let mut idx = start;
+    while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+        idx += 1;
+    }
+    if idx == start {
+        None
+    } else {
+        ::core::str::from_utf8(&bytes[start..idx])
+            .ok()
+            .map(|digits| (idx, digits))
+    }
+}
+
+/// Advance through a quoted/comment state, returning the index of the last byte consumed.
+///
+/// Callers handle `State::Normal` themselves; this only tracks when literals and comments end.
+pub(crate) fn step_non_code(state: &mut State, bytes: &[u8], idx: usize) -> usize {
+    let b = bytes[idx];
+    match state {
+        State::Normal => {}
+        State::SingleQuoted => {
+            if b == b'\'' {
+                if bytes.get(idx + 1) == Some(&b'\'') {
+                    return idx + 1; // skip escaped quote
+                }
+                *state = State::Normal;
+            }
+        }
+        State::DoubleQuoted => {
+            if b == b'"' {
+                if bytes.get(idx + 1) == Some(&b'"') {
+                    return idx + 1; // skip escaped quote
+                }
+                *state = State::Normal;
+            }
+        }
+        State::LineComment => {
+            if b == b'\n' {
+                *state = State::Normal;
+            }
+        }
+        State::BlockComment(depth) => {
+            if is_block_comment_start(bytes, idx) {
+                *depth += 1;
+            } else if is_block_comment_end(bytes, idx) {
+                if *depth == 1 {
+                    *state = State::Normal;
+                } else {
+                    *depth -= 1;
+                }
+            }
+        }
+        State::DollarQuoted(tag) => {
+            if b == b'$' && matches_tag(bytes, idx, tag) {
+                let tag_len = tag.len();
+                *state = State::Normal;
+                return idx + tag_len;
+            }
+        }
+    }
+    idx
+}
+
+/// Collect the bare words (identifiers/keywords) that appear outside literals and comments.
+pub(crate) fn code_words(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut words = Vec::new();
+    let mut state = State::Normal;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        match state {
+            State::Normal => match b {
+                b'\'' => state = State::SingleQuoted,
+                b'"' => state = State::DoubleQuoted,
+                _ if is_line_comment_start(bytes, idx) => state = State::LineComment,
+                _ if is_block_comment_start(bytes, idx) => state = State::BlockComment(1),
+                b'$' => {
+                    if let Some((tag, advance)) = try_start_dollar_quote(bytes, idx) {
+                        state = State::DollarQuoted(tag);
+                        idx = advance;
+                    }
+                }
+                _ if b.is_ascii_alphabetic() || b == b'_' => {
+                    let start = idx;
+                    while idx + 1 < bytes.len()
+                        && (bytes[idx + 1].is_ascii_alphanumeric() || bytes[idx + 1] == b'_')
+                    {
+                        idx += 1;
+                    }
+                    words.push(&sql[start..=idx]);
+                }
+                _ => {}
+            },
+            _ => idx = step_non_code(&mut state, bytes, idx),
+        }
+        idx += 1;
+    }
+
+    words
+}
+
+/// Collect the bare words outside literals, comments and parentheses, with their byte offsets.
+pub(crate) fn top_level_words(sql: &str) -> Vec<(usize, &str)> {
+    let bytes = sql.as_bytes();
+    let mut words = Vec::new();
+    let mut state = State::Normal;
+    let mut depth = 0_u32;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        match state {
+            State::Normal => match b {
+                b'\'' => state = State::SingleQuoted,
+                b'"' => state = State::DoubleQuoted,
+                _ if is_line_comment_start(bytes, idx) => state = State::LineComment,
+                _ if is_block_comment_start(bytes, idx) => state = State::BlockComment(1),
+                b'$' => {
+                    if let Some((tag, advance)) = try_start_dollar_quote(bytes, idx) {
+                        state = State::DollarQuoted(tag);
+                        idx = advance;
+                    }
+                }
+                b'(' => depth += 1,
+                b')' => depth = depth.saturating_sub(1),
+                _ if b.is_ascii_alphabetic() || b == b'_' => {
+                    let start = idx;
+                    while idx + 1 < bytes.len()
+                        && (bytes[idx + 1].is_ascii_alphanumeric() || bytes[idx + 1] == b'_')
+                    {
+                        idx += 1;
+                    }
+                    if depth == 0 {
+                        words.push((start, &sql[start..=idx]));
+                    }
+                }
+                _ => {}
+            },
+            _ => idx = step_non_code(&mut state, bytes, idx),
+        }
+        idx += 1;
+    }
+
+    words
+}
+
+/// Split `sql` at semicolons outside literals, comments, and `CREATE TRIGGER ... BEGIN ... END`
+/// bodies, dropping statements that are empty or only comments.
+pub(crate) fn split_statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut state = State::Normal;
+    let mut start = 0;
+    let mut idx = 0;
+    // Trigger bodies hold `;`-terminated statements between BEGIN and END (CASE also ends in END).
+    let mut first_word: Option<&str> = None;
+    let mut in_trigger = false;
+    let mut depth = 0_u32;
+
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        match state {
+            State::Normal => match b {
+                b'\'' => state = State::SingleQuoted,
+                b'"' => state = State::DoubleQuoted,
+                _ if is_line_comment_start(bytes, idx) => state = State::LineComment,
+                _ if is_block_comment_start(bytes, idx) => state = State::BlockComment(1),
+                b'$' => {
+                    if let Some((tag, advance)) = try_start_dollar_quote(bytes, idx) {
+                        state = State::DollarQuoted(tag);
+                        idx = advance;
+                    }
+                }
+                b';' if depth == 0 => {
+                    push_statement(&mut statements, &sql[start..idx]);
+                    start = idx + 1;
+                    first_word = None;
+                    in_trigger = false;
+                }
+                _ if b.is_ascii_alphabetic() || b == b'_' => {
+                    let word_start = idx;
+                    while idx + 1 < bytes.len()
+                        && (bytes[idx + 1].is_ascii_alphanumeric() || bytes[idx + 1] == b'_')
+                    {
+                        idx += 1;
+                    }
+                    let word = &sql[word_start..=idx];
+                    match first_word {
+                        None => first_word = Some(word),
+                        Some(first) if !in_trigger => {
+                            in_trigger = first.eq_ignore_ascii_case("create")
+                                && word.eq_ignore_ascii_case("trigger");
+                        }
+                        Some(_) => {
+                            if word.eq_ignore_ascii_case("begin")
+                                || word.eq_ignore_ascii_case("case")
+                            {
+                                depth += 1;
+                            } else if word.eq_ignore_ascii_case("end") {
+                                depth = depth.saturating_sub(1);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => idx = step_non_code(&mut state, bytes, idx),
+        }
+        idx += 1;
+    }
+
+    push_statement(&mut statements, &sql[start.min(sql.len())..]);
+    statements
+}
+
+fn push_statement<'a>(statements: &mut Vec<&'a str>, statement: &'a str) {
+    let statement = statement.trim();
+    if !code_words(statement).is_empty() {
+        statements.push(statement);
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::core::parsers::{is_block_comment_start, is_line_comment_start, try_start_dollar_quote};
+use super::core::scanner::{State, step_non_code};
+
+static PARAM_LIST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\?(?: ?, ?\?)+").expect("valid regex"));
+static ROW_LIST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(\?\)(?: ?, ?\(\?\))+").expect("valid regex"));
+
+/// Normalize `sql` so statements differing only in literals, placeholders, case, comments, or
+/// whitespace share one fingerprint.
+///
+/// String, numeric, and dollar-quoted literals and every placeholder style (`$1`, `?1`, `?`,
+/// `@p1`) become `?`; lists of them (`IN (?, ?, ?)`, multi-row `VALUES`) collapse to one;
+/// unquoted words are lowercased; comments are dropped and whitespace runs become one space.
+/// Quoted identifiers are kept as written.
+///
+/// ```rust
+/// use sql_middleware::translation::fingerprint;
+///
+/// assert_eq!(
+///     fingerprint("SELECT * FROM t WHERE id IN (1, 2, 3) AND name = 'x' -- hot"),
+///     fingerprint("select *  from t where id in ($1, $2) and name = $3"),
+/// );
+/// ```
+#[must_use]
+pub fn fingerprint(sql: &str) -> String {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    let mut state = State::Normal;
+    let mut pending_space = false;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        match state {
+            State::Normal => match b {
+                b'\'' => {
+                    state = State::SingleQuoted;
+                    push(&mut out, "?", &mut pending_space);
+                }
+                b'"' => {
+                    let start = idx;
+                    state = State::DoubleQuoted;
+                    idx += 1;
+                    while idx < bytes.len() && matches!(state, State::DoubleQuoted) {
+                        idx = step_non_code(&mut state, bytes, idx) + 1;
+                    }
+                    push(&mut out, &sql[start..idx], &mut pending_space);
+                    continue;
+                }
+                _ if is_line_comment_start(bytes, idx) => {
+                    state = State::LineComment;
+                    pending_space = true;
+                }
+                _ if is_block_comment_start(bytes, idx) => {
+                    state = State::BlockComment(1);
+                    pending_space = true;
+                    idx += 1;
+                }
+                b'$' => {
+                    if let Some((tag, advance)) = try_start_dollar_quote(bytes, idx) {
+                        state = State::DollarQuoted(tag);
+                        idx = advance;
+                        push(&mut out, "?", &mut pending_space);
+                    } else if bytes.get(idx + 1).is_some_and(u8::is_ascii_digit) {
+                        idx = skip_digits(bytes, idx + 1) - 1;
+                        push(&mut out, "?", &mut pending_space);
+                    } else {
+                        push(&mut out, "$", &mut pending_space);
+                    }
+                }
+                b'?' => {
+                    idx = skip_digits(bytes, idx + 1) - 1;
+                    push(&mut out, "?", &mut pending_space);
+                }
+                b'@' if matches!(bytes.get(idx + 1), Some(b'p' | b'P'))
+                    && bytes.get(idx + 2).is_some_and(u8::is_ascii_digit) =>
+                {
+                    idx = skip_digits(bytes, idx + 2) - 1;
+                    push(&mut out, "?", &mut pending_space);
+                }
+                _ if b.is_ascii_digit() => {
+                    while idx + 1 < bytes.len()
+                        && (bytes[idx + 1].is_ascii_alphanumeric() || bytes[idx + 1] == b'.')
+                    {
+                        idx += 1;
+                    }
+                    push(&mut out, "?", &mut pending_space);
+                }
+                _ if b.is_ascii_alphabetic() || b == b'_' => {
+                    let start = idx;
+                    while idx + 1 < bytes.len()
+                        && (bytes[idx + 1].is_ascii_alphanumeric()
+                            || matches!(bytes[idx + 1], b'_' | b'$'))
+                    {
+                        idx += 1;
+                    }
+                    let word = sql[start..=idx].to_ascii_lowercase();
+                    push(&mut out, &word, &mut pending_space);
+                }
+                _ if b.is_ascii_whitespace() => pending_space = true,
+                _ => {
+                    let ch = sql[idx..].chars().next().unwrap_or_default();
+                    let mut buf = [0; 4];
+                    push(&mut out, ch.encode_utf8(&mut buf), &mut pending_space);
+                    idx += ch.len_utf8() - 1;
+                }
+            },
+            _ => {
+                // Closing `*/` and `$tag$` leave their last byte unconsumed; skip it too.
+                let multi_byte_close =
+                    matches!(state, State::BlockComment(_) | State::DollarQuoted(_));
+                idx = step_non_code(&mut state, bytes, idx);
+                if multi_byte_close && matches!(state, State::Normal) {
+                    idx += 1;
+                }
+            }
+        }
+        idx += 1;
+    }
+
+    let out = PARAM_LIST.replace_all(&out, "?");
+    ROW_LIST.replace_all(&out, "(?)").into_owned()
+}
+
+fn push(out: &mut String, token: &str, pending_space: &mut bool) {
+    if *pending_space && !out.is_empty() {
+        out.push(' ');
+    }
+    *pending_space = false;
+    out.push_str(token);
+}
+
+fn skip_digits(bytes: &[u8], start: usize) -> usize {
+    let mut idx = start;
+    while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+        idx += 1;
+    }
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fingerprint;
+
+    #[test]
+    fn replaces_literals_and_placeholders() {
+        assert_eq!(
+            fingerprint("SELECT a FROM t WHERE b = 'it''s' AND c = 4.5 AND d = $1 AND e = @P2"),
+            "select a from t where b = ? and c = ? and d = ? and e = ?"
+        );
+        assert_eq!(fingerprint("select ?1, ?, $$body$$"), "select ?");
+    }
+
+    #[test]
+    fn keeps_a_bare_dollar_that_is_not_a_placeholder() {
+        assert_eq!(fingerprint("select a $ b"), "select a $ b");
+        assert_ne!(fingerprint("select a $ b"), fingerprint("select a ? b"));
+    }
+
+    #[test]
+    fn collapses_lists_whitespace_and_comments() {
+        assert_eq!(
+            fingerprint("insert into t (a, b)\n  values (1, 2), (3, 4) /* bulk */"),
+            "insert into t (a, b) values (?)"
+        );
+        assert_eq!(
+            fingerprint("select * from t where id in (1,2,3) -- note\n"),
+            "select * from t where id in (?)"
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_identifiers_and_words_with_digits() {
+        assert_eq!(
+            fingerprint(r#"SELECT "Col""1", col2 FROM "My Table""#),
+            r#"select "Col""1", col2 from "My Table""#
+        );
+        assert_eq!(fingerprint("select 'é' || naïve"), "select ? || na\u{ef}ve");
+    }
+}
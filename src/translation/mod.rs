@@ -1,22 +1,22 @@
-use std::borrow::Cow;
+mod fingerprint;
 
-mod parsers;
-mod scanner;
+use std::sync::Arc;
+use std::time::Duration;
 
-use parsers::{
-    is_block_comment_end, is_block_comment_start, is_line_comment_start, matches_tag,
-    try_start_dollar_quote,
-};
-use scanner::{State, scan_digits};
+use crate::audit::AuditTables;
+use crate::executor::BatchOptions;
 
-/// Target placeholder style for translation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PlaceholderStyle {
-    /// PostgreSQL-style placeholders like `$1`.
-    Postgres,
-    /// SQLite-style placeholders like `?1` (also used by Turso).
-    Sqlite,
-}
+pub mod core;
+
+#[cfg(any_backend)]
+pub(crate) use self::core::is_select;
+pub use self::core::{
+    PlaceholderKind, PlaceholderSpan, PlaceholderStyle, RegionKind, SkippedRegion, StatementKind,
+    TranslationError, TranslationReport, TranslationWarning, analyze, has_order_by,
+    split_statements, statement_kind, translate_placeholders, try_translate_parameterized,
+    try_translate_placeholders,
+};
+pub use fingerprint::fingerprint;
 
 /// How to resolve translation for a call relative to the pool default.
 ///
@@ -60,10 +60,32 @@ pub enum PrepareMode {
 }
 
 /// Per-call options for query/execute paths.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct QueryOptions {
     pub translation: TranslationMode,
     pub prepare: PrepareMode,
+    /// Sort rows client-side by every column when the SQL has no top-level `ORDER BY`.
+    pub stable_order: bool,
+    /// Bulkhead tag whose limit applies when checking out with
+    /// [`ConfigAndPool::get_connection_with`](crate::ConfigAndPool::get_connection_with), or
+    /// while a builder query given these options runs.
+    pub bulkhead: Option<&'static str>,
+    /// Reject parameters and result values that would change type (see
+    /// [`ConversionMode::Strict`](crate::types::ConversionMode::Strict)).
+    pub strict: bool,
+    /// Stop the work after this long; honoured by
+    /// [`execute_batch_with`](crate::MiddlewarePoolConnection::execute_batch_with).
+    pub timeout: Option<Duration>,
+    /// How a batch groups its statements into transactions; each backend's own behavior when
+    /// `None`. Honoured by
+    /// [`execute_batch_with`](crate::MiddlewarePoolConnection::execute_batch_with).
+    pub batch: Option<BatchOptions>,
+    /// Who is making the change, recorded by the [`audit`](crate::audit) trail.
+    pub actor: Option<Arc<str>>,
+    /// Read SELECT results through Postgres binary COPY (see [`QueryOptions::bulk_read`]).
+    pub bulk_read: bool,
+    /// Tables to [`audit`](crate::audit) on targets that do not know their pool's.
+    pub audit: Option<AuditTables>,
 }
 
 impl Default for QueryOptions {
@@ -71,6 +93,14 @@ impl Default for QueryOptions {
         Self {
             translation: TranslationMode::PoolDefault,
             prepare: PrepareMode::default(),
+            stable_order: false,
+            bulkhead: None,
+            strict: false,
+            timeout: None,
+            batch: None,
+            actor: None,
+            bulk_read: false,
+            audit: None,
         }
     }
 }
@@ -87,136 +117,92 @@ impl QueryOptions {
         self.prepare = prepare;
         self
     }
-}
 
-/// Translate placeholders between Postgres-style `$N` and SQLite-style `?N`.
-///
-/// Warning: translation skips quoted strings, comments, and dollar-quoted blocks via a lightweight
-/// state machine; it may still miss edge cases in complex SQL. For dialect-specific SQL (e.g.,
-/// PL/pgSQL bodies), prefer backend-specific SQL instead of relying on translation:
-/// ```rust
-/// # use sql_middleware::prelude::*;
-/// # async fn demo(conn: &mut MiddlewarePoolConnection) -> Result<(), SqlMiddlewareDbError> {
-/// let query = match conn {
-///     MiddlewarePoolConnection::Postgres { .. } => r#"$function$
-/// BEGIN
-///     RETURN ($1 ~ $q$[\t\r\n\v\\]$q$);
-/// END;
-/// $function$"#,
-///     MiddlewarePoolConnection::Sqlite { .. } | MiddlewarePoolConnection::Turso { .. } => {
-///         include_str!("../sql/functions/sqlite/03_sp_get_scores.sql")
-///     }
-/// };
-/// # let _ = query;
-/// # Ok(())
-/// # }
-/// ```
-/// Returns a borrowed `Cow` when no changes are needed.
-#[must_use]
-pub fn translate_placeholders(sql: &str, target: PlaceholderStyle, enabled: bool) -> Cow<'_, str> {
-    if !enabled {
-        return Cow::Borrowed(sql);
+    /// Return rows in a deterministic order when the query does not specify one.
+    ///
+    /// SELECTs without an `ORDER BY` (see [`has_order_by`]) are sorted client-side with
+    /// [`ResultSet::sort_rows`](crate::results::ResultSet::sort_rows), so results compare equal
+    /// across backends and runs.
+    #[must_use]
+    pub fn stable_order(mut self) -> Self {
+        self.stable_order = true;
+        self
     }
 
-    let mut out: Option<String> = None;
-    let mut state = State::Normal;
-    let mut idx = 0;
-    let bytes = sql.as_bytes();
-
-    while idx < bytes.len() {
-        let b = bytes[idx];
-        let mut replaced = false;
-        match state {
-            State::Normal => match b {
-                b'\'' => state = State::SingleQuoted,
-                b'"' => state = State::DoubleQuoted,
-                _ if is_line_comment_start(bytes, idx) => state = State::LineComment,
-                _ if is_block_comment_start(bytes, idx) => state = State::BlockComment(1),
-                b'$' => {
-                    if let Some((tag, advance)) = try_start_dollar_quote(bytes, idx) {
-                        state = State::DollarQuoted(tag);
-                        idx = advance;
-                    } else if matches!(target, PlaceholderStyle::Sqlite)
-                        && let Some((digits_end, digits)) = scan_digits(bytes, idx + 1)
-                    {
-                        let buf = out.get_or_insert_with(|| sql[..idx].to_string());
-                        buf.push('?');
-                        buf.push_str(digits);
-                        idx = digits_end - 1;
-                        replaced = true;
-                    }
-                }
-                b'?' if matches!(target, PlaceholderStyle::Postgres) => {
-                    if let Some((digits_end, digits)) = scan_digits(bytes, idx + 1) {
-                        let buf = out.get_or_insert_with(|| sql[..idx].to_string());
-                        buf.push('$');
-                        buf.push_str(digits);
-                        idx = digits_end - 1;
-                        replaced = true;
-                    }
-                }
-                _ => {}
-            },
-            State::SingleQuoted => {
-                if b == b'\'' {
-                    if bytes.get(idx + 1) == Some(&b'\'') {
-                        idx += 1; // skip escaped quote
-                    } else {
-                        state = State::Normal;
-                    }
-                }
-            }
-            State::DoubleQuoted => {
-                if b == b'"' {
-                    if bytes.get(idx + 1) == Some(&b'"') {
-                        idx += 1; // skip escaped quote
-                    } else {
-                        state = State::Normal;
-                    }
-                }
-            }
-            State::LineComment => {
-                if b == b'\n' {
-                    state = State::Normal;
-                }
-            }
-            State::BlockComment(depth) => {
-                if is_block_comment_start(bytes, idx) {
-                    state = State::BlockComment(depth + 1);
-                } else if is_block_comment_end(bytes, idx) {
-                    if depth == 1 {
-                        state = State::Normal;
-                    } else {
-                        state = State::BlockComment(depth - 1);
-                    }
-                }
-            }
-            State::DollarQuoted(ref tag) => {
-                if b == b'$' && matches_tag(bytes, idx, tag) {
-                    let tag_len = tag.len();
-                    state = State::Normal;
-                    idx += tag_len;
-                }
-            }
-        }
+    /// Count this work against the bulkhead `tag` (see [`PoolOptions::bulkhead`]).
+    ///
+    /// On a builder query the tag is waited for while the connection stays checked out, so it
+    /// limits running queries, not held connections. Pass these options to
+    /// [`ConfigAndPool::get_connection_with`] to keep tagged work from filling the pool.
+    ///
+    /// [`PoolOptions::bulkhead`]: crate::pool::PoolOptions::bulkhead
+    /// [`ConfigAndPool::get_connection_with`]: crate::ConfigAndPool::get_connection_with
+    #[must_use]
+    pub fn bulkhead(mut self, tag: &'static str) -> Self {
+        self.bulkhead = Some(tag);
+        self
+    }
 
-        if let Some(ref mut buf) = out
-            && !replaced
-        {
-            buf.push(b as char);
-        }
+    /// Fail instead of silently coercing values.
+    ///
+    /// Parameters the backend has no native type for (see
+    /// [`DatabaseType::param_coercion`](crate::types::DatabaseType::param_coercion)) are rejected
+    /// before the statement runs, and SQL Server result columns are read without guessing
+    /// timestamps from text or turning unsupported types into NULL. Postgres, `SQLite`, and Turso
+    /// results already map each value to exactly one `RowValues` variant.
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Give up after `timeout`, cancelling the statement in flight (see
+    /// [`execute_batch_with`](crate::MiddlewarePoolConnection::execute_batch_with)).
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        idx += 1;
+    /// Run batches with `batch`'s transaction mode (see [`BatchTxMode`](crate::BatchTxMode)).
+    #[must_use]
+    pub fn batch(mut self, batch: BatchOptions) -> Self {
+        self.batch = Some(batch);
+        self
     }
 
-    match out {
-        Some(buf) => Cow::Owned(buf),
-        None => Cow::Borrowed(sql),
+    /// Record `actor` as the author of audited DML (see [`audit`](crate::audit)).
+    #[must_use]
+    pub fn actor(mut self, actor: impl Into<Arc<str>>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    /// Read the rows of a large SELECT on Postgres with `COPY (query) TO STDOUT (FORMAT binary)`,
+    /// decoding them as they stream in, instead of row by row over the query protocol.
+    ///
+    /// Reads of many rows are several times faster; the result set is the same. This applies
+    /// only to a single SELECT with no parameters, because COPY cannot bind them. Other
+    /// statements, and other backends, run as usual. The prepare option is ignored.
+    #[must_use]
+    pub fn bulk_read(mut self) -> Self {
+        self.bulk_read = true;
+        self
+    }
+
+    /// Audit DML against `tables`, for transaction handles and typed connections, which do not
+    /// know their pool's [`PoolOptions::audit`](crate::pool::PoolOptions::audit).
+    #[must_use]
+    pub fn audit(mut self, tables: AuditTables) -> Self {
+        self.audit = Some(tables);
+        self
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use super::*;
 
     #[test]
@@ -247,6 +233,163 @@ mod tests {
         assert_eq!(res, "$foo$ select $1 from t $foo$ where a = ?1");
     }
 
+    #[test]
+    fn numbers_unnumbered_placeholders() {
+        let sql = "select * from t where a = ? and b = '?' and c = ? -- ?\n";
+        assert_eq!(
+            translate_placeholders(sql, PlaceholderStyle::Sqlite, true),
+            "select * from t where a = ?1 and b = '?' and c = ?2 -- ?\n"
+        );
+        assert_eq!(
+            translate_placeholders("values (?, ?)", PlaceholderStyle::Sqlite, true),
+            "values (?1, ?2)"
+        );
+    }
+
+    #[test]
+    fn translates_to_mssql() {
+        assert_eq!(
+            translate_placeholders("a = ? and b = '?'", PlaceholderStyle::Mssql, true),
+            "a = @P1 and b = '?'"
+        );
+        assert_eq!(
+            translate_placeholders("a = $1 and b = ?2", PlaceholderStyle::Mssql, true),
+            "a = @P1 and b = @P2"
+        );
+        let native = "a = @P1";
+        assert!(matches!(
+            translate_placeholders(native, PlaceholderStyle::Mssql, true),
+            Cow::Borrowed(_)
+        ));
+        assert!(
+            try_translate_placeholders("a = $1 and b = ?", PlaceholderStyle::Mssql, true).is_err()
+        );
+    }
+
+    #[test]
+    fn keeps_jsonb_operators() {
+        // Postgres never numbers a bare `?`, with or without other placeholders.
+        for sql in [
+            "select * from t where doc ? 'k'",
+            "select * from t where doc ? 'k' and id = $1",
+            "select * from t where doc ?? 'k' and doc ?| array['a'] and doc ?& array['b']",
+        ] {
+            assert_eq!(
+                try_translate_placeholders(sql, PlaceholderStyle::Postgres, true).unwrap(),
+                sql
+            );
+        }
+        assert_eq!(
+            try_translate_placeholders("doc ? 'k' and id = ?1", PlaceholderStyle::Postgres, true)
+                .unwrap(),
+            "doc ? 'k' and id = $1"
+        );
+        // `??` is only unescaped where bare `?` are being numbered.
+        assert_eq!(
+            translate_placeholders("doc ?? 'k' and id = ?", PlaceholderStyle::Sqlite, true),
+            "doc ? 'k' and id = ?1"
+        );
+        assert_eq!(
+            translate_placeholders("doc ?? 'k' and id = $1", PlaceholderStyle::Sqlite, true),
+            "doc ?? 'k' and id = ?1"
+        );
+    }
+
+    #[test]
+    fn numbers_bare_placeholders_for_postgres_with_params() {
+        assert_eq!(
+            try_translate_parameterized("a = ? and b = ?", PlaceholderStyle::Postgres, true)
+                .unwrap(),
+            "a = $1 and b = $2"
+        );
+        assert_eq!(
+            try_translate_parameterized("doc ?? 'k' and id = ?", PlaceholderStyle::Postgres, true)
+                .unwrap(),
+            "doc ? 'k' and id = $1"
+        );
+        // Next to numbered placeholders, a bare `?` is still the jsonb operator.
+        let sql = "doc ? 'k' and id = $1";
+        assert_eq!(
+            try_translate_parameterized(sql, PlaceholderStyle::Postgres, true).unwrap(),
+            sql
+        );
+        assert_eq!(
+            try_translate_parameterized("a = ?", PlaceholderStyle::Sqlite, true).unwrap(),
+            "a = ?1"
+        );
+    }
+
+    #[test]
+    fn rejects_mixed_placeholders() {
+        let err = try_translate_placeholders("a = ?1 and b = ?", PlaceholderStyle::Mssql, true)
+            .unwrap_err();
+        assert_eq!(err, TranslationError::MixedPlaceholders { offset: 15 });
+        assert!(
+            try_translate_placeholders("a = $1 and b = ?", PlaceholderStyle::Sqlite, true).is_err()
+        );
+        // The infallible form leaves such statements alone.
+        let sql = "a = ?1 and b = ?";
+        assert!(matches!(
+            translate_placeholders(sql, PlaceholderStyle::Sqlite, true),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn keeps_non_ascii_text_intact() {
+        let sql = "select 'café' from t where a = $1";
+        assert_eq!(
+            translate_placeholders(sql, PlaceholderStyle::Sqlite, true),
+            "select 'café' from t where a = ?1"
+        );
+    }
+
+    #[test]
+    fn analysis_lists_regions_and_placeholders() {
+        let sql = "select 'a', \"b\" -- c\n/* d */ $x$ e $x$ from t where a = ?1";
+        let report = analyze(sql);
+        let regions: Vec<_> = report
+            .regions
+            .iter()
+            .map(|r| (&sql[r.span.clone()], r.kind.clone()))
+            .collect();
+        assert_eq!(
+            regions,
+            [
+                ("'a'", RegionKind::SingleQuoted),
+                ("\"b\"", RegionKind::DoubleQuoted),
+                ("-- c\n", RegionKind::LineComment),
+                ("/* d */", RegionKind::BlockComment),
+                ("$x$ e $x$", RegionKind::DollarQuoted { tag: "x".into() }),
+            ]
+        );
+        assert_eq!(report.placeholders.len(), 1);
+        assert_eq!(&sql[report.placeholders[0].span.clone()], "?1");
+        assert!(report.is_safe());
+    }
+
+    #[test]
+    fn analysis_flags_unsafe_constructs() {
+        let report = analyze("select $1 where a = 'open");
+        assert_eq!(
+            report.warnings,
+            [TranslationWarning::Unterminated {
+                offset: 20,
+                kind: RegionKind::SingleQuoted
+            }]
+        );
+
+        let nested = "do $$ begin execute $$ select $1 $$; end $$";
+        assert_eq!(
+            analyze(nested).warnings,
+            [TranslationWarning::SameTagDollarQuotes {
+                offset: 30,
+                tag: String::new()
+            }]
+        );
+        assert!(analyze("do $a$ begin execute $b$ select $1 $b$; end $a$").is_safe());
+    }
+
     #[test]
     fn respects_disabled_flag() {
         let sql = "select * from t where a = ?1";
@@ -255,6 +398,61 @@ mod tests {
         assert_eq!(res, sql);
     }
 
+    #[test]
+    fn detects_order_by_outside_literals() {
+        assert!(has_order_by("select a from t order by a"));
+        assert!(has_order_by("SELECT a FROM t\nORDER\n  BY a DESC"));
+        assert!(!has_order_by("select 'order by' from t -- order by a"));
+        assert!(!has_order_by("select a from t /* order by a */"));
+        assert!(!has_order_by("select border, bypass from t"));
+    }
+
+    #[test]
+    fn ignores_nested_order_by() {
+        assert!(!has_order_by(
+            "select a, row_number() over (order by b) as n from t"
+        ));
+        assert!(!has_order_by(
+            "select * from (select a from t order by a limit 5) s"
+        ));
+        assert!(!has_order_by(
+            "with x as (select a from t order by a) select * from x"
+        ));
+        assert!(has_order_by(
+            "select a, row_number() over (order by b) from t order by a"
+        ));
+    }
+
+    #[test]
+    fn detects_read_only_queries() {
+        assert!(is_select("/* hint */ SELECT 1"));
+        assert!(is_select("with x as (select 1) select * from x"));
+        assert!(!is_select("with x as (select 1) delete from t"));
+        assert!(!is_select("explain select 1"));
+        assert!(!is_select("update t set a = 'select'"));
+    }
+
+    #[test]
+    fn splits_statements_outside_literals_and_bodies() {
+        assert_eq!(
+            split_statements("SELECT 1; ; /* only a comment */;\nSELECT ';' AS semi"),
+            ["SELECT 1", "SELECT ';' AS semi"]
+        );
+        let function =
+            "CREATE FUNCTION f() RETURNS int AS $$ BEGIN RETURN 1; END; $$ LANGUAGE plpgsql";
+        assert_eq!(
+            split_statements(&format!("{function}; SELECT f();")),
+            [function, "SELECT f()"]
+        );
+        let trigger = "CREATE TRIGGER t AFTER INSERT ON a BEGIN \
+                       UPDATE b SET n = CASE WHEN n > 0 THEN n + 1 ELSE 1 END; DELETE FROM c; END";
+        assert_eq!(
+            split_statements(&format!("{trigger};\nINSERT INTO a VALUES (1)")),
+            [trigger, "INSERT INTO a VALUES (1)"]
+        );
+        assert_eq!(split_statements("BEGIN; COMMIT;"), ["BEGIN", "COMMIT"]);
+    }
+
     #[test]
     fn translation_mode_resolution() {
         assert!(TranslationMode::ForceOn.resolve(false));
@@ -1,10 +1,26 @@
+use bb8::Pool;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{EnvReader, process_env};
 use crate::middleware::{ConfigAndPool, DatabaseType, MiddlewarePool, SqlMiddlewareDbError};
+use crate::turso::TursoManager;
+
+/// Default cap on pooled Turso connections, matching bb8's default.
+pub const DEFAULT_TURSO_POOL_SIZE: u32 = 10;
 
 /// Options for configuring a Turso database.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TursoOptions {
     pub db_path: String,
+    #[serde(default)]
     pub translate_placeholders: bool,
+    /// Most connections the pool opens against the database at once.
+    #[serde(default = "default_max_size")]
+    pub max_size: u32,
+}
+
+fn default_max_size() -> u32 {
+    DEFAULT_TURSO_POOL_SIZE
 }
 
 impl TursoOptions {
@@ -13,7 +29,40 @@ impl TursoOptions {
         Self {
             db_path,
             translate_placeholders: false,
+            max_size: DEFAULT_TURSO_POOL_SIZE,
+        }
+    }
+
+    /// Read options from environment variables named `{prefix}` followed by:
+    ///
+    /// - `DB_PATH` (required)
+    /// - `POOL_SIZE`, `TRANSLATE_PLACEHOLDERS` (optional)
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env(prefix: &str) -> Result<Self, SqlMiddlewareDbError> {
+        Self::from_env_with(prefix, process_env)
+    }
+
+    /// Like [`from_env`](Self::from_env), looking variables up with `lookup` instead of in the
+    /// process environment.
+    ///
+    /// # Errors
+    /// Returns `SqlMiddlewareDbError::ConfigError` naming every required variable that is unset
+    /// and every variable that does not parse.
+    pub fn from_env_with(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, SqlMiddlewareDbError> {
+        let mut env = EnvReader::new(prefix, lookup);
+        let mut opts = Self::new(env.required("DB_PATH"));
+        if let Some(max_size) = env.parsed("POOL_SIZE") {
+            opts.max_size = max_size;
         }
+        opts.translate_placeholders = env.flag("TRANSLATE_PLACEHOLDERS").unwrap_or(false);
+        env.finish()?;
+        Ok(opts)
     }
 
     #[must_use]
@@ -21,6 +70,13 @@ impl TursoOptions {
         self.translate_placeholders = translate_placeholders;
         self
     }
+
+    /// Cap the pool at `max_size` connections; checkouts beyond it wait for one to be returned.
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
 }
 
 /// Fluent builder for Turso options.
@@ -43,6 +99,13 @@ impl TursoOptionsBuilder {
         self
     }
 
+    /// Cap the pool at `max_size` connections; checkouts beyond it wait for one to be returned.
+    #[must_use]
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.opts.max_size = max_size;
+        self
+    }
+
     #[must_use]
     pub fn finish(self) -> TursoOptions {
         self.opts
@@ -64,15 +127,23 @@ impl ConfigAndPool {
         TursoOptionsBuilder::new(db_path)
     }
 
-    /// Asynchronous initializer for `ConfigAndPool` with Turso (local/in-process).
+    /// Asynchronous initializer for `ConfigAndPool` with Turso (local/in-process) using a
+    /// bb8-backed pool.
+    ///
+    /// Every checkout is validated with `SELECT 1`; a connection that fails it is dropped and
+    /// replaced, so a connection left unusable by an error is recycled rather than handed out.
     ///
     /// # Errors
-    /// Returns `SqlMiddlewareDbError::ConnectionError` if database creation or connection test fails.
+    /// Returns `SqlMiddlewareDbError::ConnectionError` if database creation, pool creation, or
+    /// the connection test fails.
     pub async fn new_turso(opts: TursoOptions) -> Result<Self, SqlMiddlewareDbError> {
-        let db_path = opts.db_path;
-        let translate_placeholders = opts.translate_placeholders;
+        if opts.max_size == 0 {
+            return Err(SqlMiddlewareDbError::ConfigError(
+                "Turso pool max_size must be at least 1".to_string(),
+            ));
+        }
 
-        let db = turso::Builder::new_local(&db_path)
+        let db = turso::Builder::new_local(&opts.db_path)
             .build()
             .await
             .map_err(|e| {
@@ -81,18 +152,33 @@ impl ConfigAndPool {
                 ))
             })?;
 
+        let pool = Pool::builder()
+            .max_size(opts.max_size)
+            .build(TursoManager::new(db))
+            .await
+            .map_err(|e| {
+                SqlMiddlewareDbError::ConnectionError(format!("Failed to create Turso pool: {e}"))
+            })?;
+
         // Smoke-test a connection
-        let conn = db.connect().map_err(|e| {
-            SqlMiddlewareDbError::ConnectionError(format!("Failed to connect Turso database: {e}"))
-        })?;
-
-        // Best-effort pragmas for concurrency (ignore failure on in-memory/unsupported)
-        let _ = conn.execute("PRAGMA journal_mode = WAL", ()).await;
-
-        Ok(ConfigAndPool {
-            pool: MiddlewarePool::Turso(db),
-            db_type: DatabaseType::Turso,
-            translate_placeholders,
-        })
+        {
+            let conn = crate::pool::stats::checkout(&pool, |source, waited, state| {
+                SqlMiddlewareDbError::PoolErrorTurso {
+                    source: Box::new(source),
+                    waited,
+                    state,
+                }
+            })
+            .await?;
+
+            // Best-effort pragmas for concurrency (ignore failure on in-memory/unsupported)
+            let _ = conn.execute("PRAGMA journal_mode = WAL", ()).await;
+        }
+
+        Ok(ConfigAndPool::from_pool(
+            MiddlewarePool::Turso(pool),
+            DatabaseType::Turso,
+            opts.translate_placeholders,
+        ))
     }
 }
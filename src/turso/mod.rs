@@ -1,11 +1,13 @@
 //! Turso backend glue (SQLite-compatible, in-process).
 //!
 //! Mirrors the `SQLite` module layout:
+//! - `backend`: [`BackendApi`](crate::backend::BackendApi) implementation
 //! - `config`: connection configuration and pool setup
 //! - `params`: parameter conversion between middleware and Turso types
 //! - `query`: result extraction and building
 //! - `executor`: database operation execution
 
+pub mod backend;
 pub mod config;
 pub mod executor;
 pub mod params;
@@ -15,10 +17,13 @@ pub mod transaction;
 pub mod typed;
 
 // Re-export the public API for convenience
+pub use backend::TursoBackend;
 pub use config::{TursoOptions, TursoOptionsBuilder};
 pub use executor::{execute_batch, execute_dml, execute_select};
 pub use params::Params;
 pub use prepared::TursoNonTxPreparedStatement;
 pub use query::build_result_set;
-pub use transaction::{Prepared, Tx, begin_transaction};
+pub use transaction::{
+    Prepared, Tx, TxCapabilities, TxMode, begin_transaction, begin_transaction_with,
+};
 pub use typed::{Idle as TypedIdle, InTx as TypedInTx, TursoConnection, TursoManager};
@@ -1,13 +1,17 @@
-use crate::middleware::{ConversionMode, ParamConverter, RowValues, SqlMiddlewareDbError};
+use crate::middleware::{
+    ConversionMode, DatabaseType, ParamConverter, RowValues, SqlMiddlewareDbError,
+};
 
 /// Container for Turso parameters (positional only for now).
+///
+/// Turso's API takes owned values, so text and blobs are copied once here.
 pub struct Params(pub turso::params::Params);
 
 fn row_value_to_turso_value(value: &RowValues, _for_execute: bool) -> turso::Value {
     match value {
         RowValues::Int(i) => turso::Value::Integer(*i),
         RowValues::Float(f) => turso::Value::Real(*f),
-        RowValues::Text(s) => turso::Value::Text(s.clone()),
+        RowValues::Text(s) => turso::Value::Text(s.to_string()),
         RowValues::Bool(b) => turso::Value::Integer(i64::from(*b)),
         //   - Turso’s Value enum supports: Null, Integer, Real, Text, Blob — no datetime/timestamp.
         //   - SQLite’s storage model treats date/time as TEXT/REAL/INTEGER. We serialize RowValues::Timestamp to TEXT for parity across “SQLite-compatible” backends.
@@ -20,8 +24,12 @@ fn row_value_to_turso_value(value: &RowValues, _for_execute: bool) -> turso::Val
             turso::Value::Text(dt.format("%F %T%.f").to_string())
         }
         RowValues::Null => turso::Value::Null,
+        #[cfg(feature = "json")]
         RowValues::JSON(j) => turso::Value::Text(j.to_string()),
-        RowValues::Blob(bytes) => turso::Value::Blob(bytes.clone()),
+        RowValues::Blob(bytes) => turso::Value::Blob(bytes.to_vec()),
+        RowValues::Custom(custom) => row_value_to_turso_value(&custom.encode(), _for_execute),
+        #[cfg(feature = "geo")]
+        RowValues::Geometry(wkb) => turso::Value::Blob(wkb.to_vec()),
     }
 }
 
@@ -59,6 +67,10 @@ impl ParamConverter<'_> for Params {
         match mode {
             ConversionMode::Query => Ok(Params(convert_params(params))),
             ConversionMode::Execute => Ok(Params(convert_params_for_execute(params))),
+            ConversionMode::Strict => {
+                DatabaseType::Turso.check_strict_params(params)?;
+                Ok(Params(convert_params_for_execute(params)))
+            }
         }
     }
 
@@ -34,8 +34,8 @@ pub async fn build_result_set(
                 turso::Value::Null => RowValues::Null,
                 turso::Value::Integer(i) => RowValues::Int(i),
                 turso::Value::Real(f) => RowValues::Float(f),
-                turso::Value::Text(s) => RowValues::Text(s),
-                turso::Value::Blob(b) => RowValues::Blob(b),
+                turso::Value::Text(s) => RowValues::Text(s.into()),
+                turso::Value::Blob(b) => RowValues::Blob(b.into()),
             };
             values.push(rv);
         }
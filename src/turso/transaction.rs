@@ -1,17 +1,95 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use crate::adapters::params::convert_params;
+use crate::executor::QueryTarget;
 use crate::middleware::{ConversionMode, ResultSet, RowValues, SqlMiddlewareDbError};
+use crate::pool::checkout::PoolLink;
+use crate::query_builder::QueryBuilder;
 use crate::query_utils::extract_column_names;
 use crate::turso::params::Params as TursoParams;
+use crate::tx_drop::DropGuard;
 use crate::tx_outcome::TxOutcome;
 
+/// How a Turso transaction runs its statements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TxMode {
+    /// Each statement runs when it is issued, inside `BEGIN` ... `COMMIT`.
+    #[default]
+    Interactive,
+    /// Statements are queued and submitted as one atomic batch at commit.
+    ///
+    /// This is how remote libSQL and Turso Cloud connections run transactions when the HTTP
+    /// transport cannot hold an interactive session open. Writes are validated (parameters are
+    /// converted) when issued but only executed by [`Tx::commit`], so SQL errors surface there,
+    /// reads inside the transaction are rejected, and `execute_dml` cannot report affected rows.
+    Deferred,
+}
+
+impl TxMode {
+    /// What a transaction in this mode can do before it commits.
+    #[must_use]
+    pub fn capabilities(self) -> TxCapabilities {
+        match self {
+            TxMode::Interactive => TxCapabilities {
+                deferred_execution: false,
+                reads: true,
+                affected_rows: true,
+                prepared_statements: true,
+            },
+            TxMode::Deferred => TxCapabilities {
+                deferred_execution: true,
+                reads: false,
+                affected_rows: false,
+                prepared_statements: false,
+            },
+        }
+    }
+}
+
+/// Capability flags for a Turso transaction; see [`Tx::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct TxCapabilities {
+    /// Statements run at commit rather than when issued, so their errors come from `commit`.
+    pub deferred_execution: bool,
+    /// `execute_select` and the query builder's `select` can run inside the transaction.
+    pub reads: bool,
+    /// `execute_dml` and `execute_prepared` return the real affected row count. Without it
+    /// they return `0`.
+    pub affected_rows: bool,
+    /// `prepare` is available.
+    pub prepared_statements: bool,
+}
+
 /// Lightweight transaction wrapper for Turso.
 ///
 /// Wraps a `turso::transaction::Transaction` to keep the public API stable while
-/// benefiting from Turso's transaction-scoped helpers (including prepare).
+/// benefiting from Turso's transaction-scoped helpers (including prepare). A transaction begun
+/// with [`TxMode::Deferred`] has the same API but queues its writes until commit.
+///
+/// Dropping a `Tx` unfinished is reported (see [`tx_drop`](crate::tx_drop)). An interactive
+/// transaction is rolled back by the Turso driver on the connection's next statement, and a
+/// pooled connection is checked for an open transaction, and rolled back, when next checked out.
+/// A deferred transaction has sent nothing, so its queued writes are simply discarded.
 pub struct Tx<'a> {
-    pub(crate) tx: turso::transaction::Transaction<'a>,
+    inner: TxInner<'a>,
+    /// Pool the handle was begun from; none when begun on a raw connection.
+    pool: PoolLink,
+    guard: DropGuard,
+}
+
+enum TxInner<'a> {
+    Interactive(turso::transaction::Transaction<'a>),
+    Deferred {
+        conn: &'a mut turso::Connection,
+        queue: Mutex<Vec<Queued>>,
+    },
+}
+
+/// A write waiting for a deferred transaction to commit.
+enum Queued {
+    Batch(String),
+    Dml(String, turso::params::Params),
 }
 
 /// Prepared statement wrapper for Turso.
@@ -22,13 +100,27 @@ pub struct Prepared {
 }
 
 impl Tx<'_> {
+    /// Link the handle to `pool`: take a token from its rate limit before each statement sent
+    /// and echo what its query builder runs.
+    pub(crate) fn linked(mut self, pool: PoolLink) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// The pool this handle was begun from, if any.
+    pub(crate) fn pool(&self) -> &PoolLink {
+        &self.pool
+    }
+
     /// Prepare a SQL statement tied to this transaction's connection.
     ///
     /// # Errors
     ///
-    /// Returns `SqlMiddlewareDbError` when the underlying Turso prepare call fails.
+    /// Returns `SqlMiddlewareDbError` when the underlying Turso prepare call fails, or
+    /// `SqlMiddlewareDbError::Unimplemented` in a [`TxMode::Deferred`] transaction.
     pub async fn prepare(&self, sql: &str) -> Result<Prepared, SqlMiddlewareDbError> {
-        let stmt = self.tx.prepare(sql).await.map_err(|e| {
+        let tx = self.interactive("prepare")?;
+        let stmt = tx.prepare(sql).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("Turso prepare error: {e}"))
         })?;
 
@@ -46,9 +138,20 @@ impl Tx<'_> {
     ///
     /// Returns `SqlMiddlewareDbError` when the Turso batch execution fails.
     pub async fn execute_batch(&self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
-        self.tx.execute_batch(sql).await.map_err(|e| {
-            SqlMiddlewareDbError::ExecutionError(format!("Turso tx execute_batch error: {e}"))
-        })
+        match &self.inner {
+            TxInner::Interactive(tx) => {
+                self.pool.throttle().await?;
+                tx.execute_batch(sql).await.map_err(|e| {
+                    SqlMiddlewareDbError::ExecutionError(format!(
+                        "Turso tx execute_batch error: {e}"
+                    ))
+                })
+            }
+            TxInner::Deferred { queue, .. } => {
+                push(queue, Queued::Batch(sql.to_owned()));
+                Ok(())
+            }
+        }
     }
 
     /// Execute a parameterized DML statement and return affected rows.
@@ -57,13 +160,23 @@ impl Tx<'_> {
     ///
     /// Returns `SqlMiddlewareDbError` when executing the statement fails or the affected row
     /// count cannot be converted to `usize`.
+    ///
+    /// In a [`TxMode::Deferred`] transaction the statement is queued and `0` is returned.
     pub async fn execute_dml(
         &self,
         query: &str,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
         let converted = convert_params::<TursoParams>(params, ConversionMode::Execute)?;
-        let affected = self.tx.execute(query, converted.0).await.map_err(|e| {
+        let tx = match &self.inner {
+            TxInner::Interactive(tx) => tx,
+            TxInner::Deferred { queue, .. } => {
+                push(queue, Queued::Dml(query.to_owned(), converted.0));
+                return Ok(0);
+            }
+        };
+        self.pool.throttle().await?;
+        let affected = tx.execute(query, converted.0).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("Turso tx execute error: {e}"))
         })?;
         usize::try_from(affected).map_err(|e| {
@@ -84,7 +197,9 @@ impl Tx<'_> {
         prepared: &mut Prepared,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
+        self.interactive("execute_prepared")?;
         let converted = convert_params::<TursoParams>(params, ConversionMode::Execute)?;
+        self.pool.throttle().await?;
         let affected = prepared.stmt.execute(converted.0).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("Turso tx execute(prepared) error: {e}"))
         })?;
@@ -95,21 +210,32 @@ impl Tx<'_> {
         })
     }
 
+    /// Start a [`QueryBuilder`] that runs inside this transaction.
+    ///
+    /// Placeholders are not translated unless the builder asks for it with
+    /// [`translation`](QueryBuilder::translation).
+    pub fn query_builder<'q>(&self, sql: &'q str) -> QueryBuilder<'_, 'q> {
+        QueryBuilder::new_target(QueryTarget::from(self), sql)
+    }
+
     /// Execute a parameterized SELECT and return a `ResultSet`.
     ///
     /// # Errors
     ///
     /// Returns `SqlMiddlewareDbError` when preparing/executing the statement or building the
-    /// `ResultSet` fails.
+    /// `ResultSet` fails, or `SqlMiddlewareDbError::Unimplemented` in a [`TxMode::Deferred`]
+    /// transaction.
     pub async fn execute_select(
         &self,
         query: &str,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        let tx = self.interactive("execute_select")?;
         let converted = convert_params::<TursoParams>(params, ConversionMode::Query)?;
+        self.pool.throttle().await?;
 
         // Prepare to fetch column names, then run using same statement to avoid double-prepare.
-        let mut stmt = self.tx.prepare(query).await.map_err(|e| {
+        let mut stmt = tx.prepare(query).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("Turso tx prepare error: {e}"))
         })?;
 
@@ -134,7 +260,9 @@ impl Tx<'_> {
         prepared: &mut Prepared,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
+        self.interactive("query_prepared")?;
         let converted = convert_params::<TursoParams>(params, ConversionMode::Query)?;
+        self.pool.throttle().await?;
         let rows = prepared.stmt.query(converted.0).await.map_err(|e| {
             SqlMiddlewareDbError::ExecutionError(format!("Turso tx query(prepared) error: {e}"))
         })?;
@@ -143,41 +271,171 @@ impl Tx<'_> {
 
     /// Commit the transaction.
     ///
+    /// A [`TxMode::Deferred`] transaction runs its queued statements here, all or nothing.
+    ///
     /// # Errors
     ///
-    /// Returns `SqlMiddlewareDbError` when issuing the COMMIT statement fails.
-    pub async fn commit(self) -> Result<TxOutcome, SqlMiddlewareDbError> {
-        self.tx
-            .commit()
-            .await
-            .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("Turso commit error: {e}")))
-            .map(|()| TxOutcome::without_restored_connection())
+    /// Returns `SqlMiddlewareDbError` when issuing the COMMIT statement fails, or when a queued
+    /// statement fails (the batch is then rolled back and the error names the statement).
+    pub async fn commit(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
+        match self.inner {
+            TxInner::Interactive(tx) => tx.commit().await.map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("Turso commit error: {e}"))
+            })?,
+            TxInner::Deferred { conn, queue } => {
+                let queued = queue.into_inner().unwrap_or_else(PoisonError::into_inner);
+                submit_batch(conn, queued).await?;
+            }
+        }
+        Ok(TxOutcome::without_restored_connection())
     }
 
     /// Roll back the transaction.
     ///
+    /// A [`TxMode::Deferred`] transaction discards its queue without touching the database.
+    ///
     /// # Errors
     ///
     /// Returns `SqlMiddlewareDbError` when issuing the ROLLBACK statement fails.
-    pub async fn rollback(self) -> Result<TxOutcome, SqlMiddlewareDbError> {
-        self.tx
-            .rollback()
-            .await
-            .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("Turso rollback error: {e}")))
-            .map(|()| TxOutcome::without_restored_connection())
+    pub async fn rollback(mut self) -> Result<TxOutcome, SqlMiddlewareDbError> {
+        self.guard.disarm();
+        if let TxInner::Interactive(tx) = self.inner {
+            tx.rollback().await.map_err(|e| {
+                SqlMiddlewareDbError::ExecutionError(format!("Turso rollback error: {e}"))
+            })?;
+        }
+        Ok(TxOutcome::without_restored_connection())
+    }
+
+    /// The mode this transaction was begun with.
+    #[must_use]
+    pub fn mode(&self) -> TxMode {
+        match self.inner {
+            TxInner::Interactive(_) => TxMode::Interactive,
+            TxInner::Deferred { .. } => TxMode::Deferred,
+        }
     }
+
+    /// What this transaction can do before it commits; generic code checks these flags rather
+    /// than the mode.
+    #[must_use]
+    pub fn capabilities(&self) -> TxCapabilities {
+        self.mode().capabilities()
+    }
+
+    /// Statements queued by a [`TxMode::Deferred`] transaction and not yet committed; always `0`
+    /// for an interactive one.
+    #[must_use]
+    pub fn pending_statements(&self) -> usize {
+        match &self.inner {
+            TxInner::Interactive(_) => 0,
+            TxInner::Deferred { queue, .. } => {
+                queue.lock().unwrap_or_else(PoisonError::into_inner).len()
+            }
+        }
+    }
+}
+
+impl<'a> Tx<'a> {
+    fn interactive(
+        &self,
+        op: &str,
+    ) -> Result<&turso::transaction::Transaction<'a>, SqlMiddlewareDbError> {
+        match &self.inner {
+            TxInner::Interactive(tx) => Ok(tx),
+            TxInner::Deferred { .. } => Err(SqlMiddlewareDbError::Unimplemented(format!(
+                "{op} is not available in a deferred Turso transaction; statements run at commit"
+            ))),
+        }
+    }
+}
+
+fn push(queue: &Mutex<Vec<Queued>>, stmt: Queued) {
+    queue
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(stmt);
+}
+
+/// Run a deferred transaction's statements as one atomic unit.
+async fn submit_batch(
+    conn: &mut turso::Connection,
+    queued: Vec<Queued>,
+) -> Result<(), SqlMiddlewareDbError> {
+    if queued.is_empty() {
+        return Ok(());
+    }
+    let tx = conn.transaction().await.map_err(|e| {
+        SqlMiddlewareDbError::ExecutionError(format!("Turso begin transaction error: {e}"))
+    })?;
+    for (idx, stmt) in queued.into_iter().enumerate() {
+        let step = match stmt {
+            Queued::Batch(sql) => tx.execute_batch(&sql).await,
+            Queued::Dml(sql, params) => tx.execute(&sql, params).await.map(|_| ()),
+        };
+        if let Err(e) = step {
+            let _ = tx.rollback().await;
+            return Err(SqlMiddlewareDbError::ExecutionError(format!(
+                "Turso deferred transaction: statement {} failed, batch rolled back: {e}",
+                idx + 1
+            )));
+        }
+    }
+    tx.commit()
+        .await
+        .map_err(|e| SqlMiddlewareDbError::ExecutionError(format!("Turso commit error: {e}")))
 }
 
 /// Begin a new transaction for the given connection.
 ///
+/// The handle cannot see the pool the connection came from, so its statements are not
+/// rate-limited;
+/// [`MiddlewarePoolConnection::begin_turso_transaction`](crate::pool::MiddlewarePoolConnection::begin_turso_transaction)
+/// begins one that is.
+///
 /// # Errors
 ///
 /// Returns `SqlMiddlewareDbError` when issuing the BEGIN statement fails.
-pub async fn begin_transaction(
+#[track_caller]
+pub fn begin_transaction(
     conn: &mut turso::Connection,
-) -> Result<Tx<'_>, SqlMiddlewareDbError> {
-    let tx = conn.transaction().await.map_err(|e| {
-        SqlMiddlewareDbError::ExecutionError(format!("Turso begin transaction error: {e}"))
-    })?;
-    Ok(Tx { tx })
+) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+    begin_transaction_with(conn, TxMode::Interactive)
+}
+
+/// Begin a transaction in the given [`TxMode`].
+///
+/// A [`TxMode::Deferred`] transaction sends nothing to the database until it commits.
+///
+/// # Errors
+///
+/// Returns `SqlMiddlewareDbError` when issuing the BEGIN statement fails.
+#[track_caller]
+pub fn begin_transaction_with(
+    conn: &mut turso::Connection,
+    mode: TxMode,
+) -> impl Future<Output = Result<Tx<'_>, SqlMiddlewareDbError>> {
+    let guard = DropGuard::new("turso");
+    async move {
+        let inner = match mode {
+            TxMode::Interactive => {
+                let tx = conn.transaction().await.map_err(|e| {
+                    SqlMiddlewareDbError::ExecutionError(format!(
+                        "Turso begin transaction error: {e}"
+                    ))
+                })?;
+                TxInner::Interactive(tx)
+            }
+            TxMode::Deferred => TxInner::Deferred {
+                conn,
+                queue: Mutex::new(Vec::new()),
+            },
+        };
+        Ok(Tx {
+            inner,
+            pool: PoolLink::default(),
+            guard: guard.arm(),
+        })
+    }
 }
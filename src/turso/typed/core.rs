@@ -3,6 +3,7 @@ use std::{future::Future, marker::PhantomData, mem::ManuallyDrop, sync::atomic::
 use bb8::{ManageConnection, Pool, PooledConnection};
 
 use crate::middleware::SqlMiddlewareDbError;
+use crate::tx_drop::DropGuard;
 
 /// Marker types for typestate
 pub enum Idle {}
@@ -47,14 +48,21 @@ impl ManageConnection for TursoManager {
         conn: &mut Self::Connection,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
         async move {
+            // `query` finishes a transaction whose `Transaction` was dropped, per its drop
+            // behavior (rollback by default).
             match conn.query("SELECT 1", ()).await {
                 Ok(mut rows) => {
                     // Consume one row to keep the iterator clean.
                     let _ = rows.next().await;
-                    Ok(())
                 }
-                Err(e) => Err(e),
+                Err(e) => return Err(e),
             }
+            // A transaction still open was begun with raw SQL or left by a deferred `Tx`'s
+            // failed commit; roll it back so the next holder starts clean.
+            if !conn.is_autocommit()? {
+                conn.execute("ROLLBACK", ()).await?;
+            }
+            Ok(())
         }
     }
 
@@ -74,6 +82,8 @@ pub struct TursoConnection<State> {
     /// True when a transaction is in-flight and needs rollback if dropped.
     pub(crate) needs_rollback: bool,
     pub(crate) _state: PhantomData<State>,
+    /// Reports a transaction dropped unfinished; declared last so it runs after the rollback.
+    pub(crate) tx_guard: DropGuard,
 }
 
 impl TursoConnection<Idle> {
@@ -82,13 +92,19 @@ impl TursoConnection<Idle> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if acquiring a connection fails.
     pub async fn from_pool(pool: &Pool<TursoManager>) -> Result<Self, SqlMiddlewareDbError> {
-        let conn = pool.get_owned().await.map_err(|e| {
-            SqlMiddlewareDbError::ConnectionError(format!("turso checkout error: {e}"))
-        })?;
+        let conn = crate::pool::stats::checkout(pool, |source, waited, state| {
+            SqlMiddlewareDbError::PoolErrorTurso {
+                source: Box::new(source),
+                waited,
+                state,
+            }
+        })
+        .await?;
         Ok(Self {
             conn: Some(conn),
             needs_rollback: false,
             _state: PhantomData,
+            tx_guard: DropGuard::untracked(),
         })
     }
 }
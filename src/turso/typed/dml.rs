@@ -3,6 +3,7 @@ use bb8::PooledConnection;
 use crate::adapters::params::convert_params;
 use crate::middleware::{RowValues, SqlMiddlewareDbError};
 use crate::turso::params::Params as TursoParams;
+use crate::tx_drop::DropGuard;
 use crate::types::ConversionMode;
 
 use super::{InTx, TursoConnection, TursoManager};
@@ -13,7 +14,7 @@ impl TursoConnection<super::core::Idle> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if executing the batch fails.
     pub async fn execute_batch(&mut self, sql: &str) -> Result<(), SqlMiddlewareDbError> {
-        let mut tx = super::tx::begin_from_conn(self.take_conn()?).await?;
+        let mut tx = super::tx::begin_from_conn(self.take_conn()?, DropGuard::untracked()).await?;
         tx.execute_batch(sql).await?;
         let idle = tx.commit().await?;
         self.conn = idle.into_conn();
@@ -29,7 +30,7 @@ impl TursoConnection<super::core::Idle> {
         query: &str,
         params: &[RowValues],
     ) -> Result<usize, SqlMiddlewareDbError> {
-        let mut tx = super::tx::begin_from_conn(self.take_conn()?).await?;
+        let mut tx = super::tx::begin_from_conn(self.take_conn()?, DropGuard::untracked()).await?;
         let rows = tx.dml(query, params).await?;
         let idle = tx.commit().await?;
         self.conn = idle.into_conn();
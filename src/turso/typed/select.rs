@@ -7,6 +7,7 @@ use crate::query_builder::QueryBuilder;
 use crate::results::ResultSet;
 use crate::adapters::params::convert_params;
 use crate::turso::params::Params as TursoParams;
+use crate::tx_drop::DropGuard;
 use crate::types::ConversionMode;
 
 use super::{InTx, TursoConnection, TursoManager};
@@ -21,7 +22,8 @@ impl TursoConnection<super::core::Idle> {
         query: &str,
         params: &[RowValues],
     ) -> Result<ResultSet, SqlMiddlewareDbError> {
-        let mut tx = super::tx::begin_from_conn(self.take_conn()?).await?;
+        let mut tx =
+            super::tx::begin_from_conn(self.take_conn()?, DropGuard::untracked()).await?;
         let rows = tx.select(query, params).await?;
         let idle = tx.commit().await?;
         self.conn = idle.into_conn();
@@ -1,17 +1,26 @@
-use tokio::runtime::Handle;
-
 use crate::middleware::SqlMiddlewareDbError;
 
 use super::core::{Idle, InTx, SKIP_DROP_ROLLBACK};
 use super::{TursoConnection, TursoManager};
+use crate::tx_drop::DropGuard;
 
 impl TursoConnection<Idle> {
     /// Begin an explicit transaction.
     ///
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if transitioning into a transaction fails.
-    pub async fn begin(mut self) -> Result<TursoConnection<InTx>, SqlMiddlewareDbError> {
-        begin_from_conn(self.take_conn()?).await
+    #[track_caller]
+    pub fn begin(
+        self,
+    ) -> impl Future<Output = Result<TursoConnection<InTx>, SqlMiddlewareDbError>> {
+        self.begin_guarded(DropGuard::new("turso"))
+    }
+
+    pub(crate) async fn begin_guarded(
+        mut self,
+        tx_guard: DropGuard,
+    ) -> Result<TursoConnection<InTx>, SqlMiddlewareDbError> {
+        begin_from_conn(self.take_conn()?, tx_guard).await
     }
 }
 
@@ -21,12 +30,14 @@ impl TursoConnection<InTx> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if committing fails.
     pub async fn commit(mut self) -> Result<TursoConnection<Idle>, SqlMiddlewareDbError> {
+        self.tx_guard.disarm();
         let conn = self.take_conn()?;
         match conn.execute_batch("COMMIT").await {
             Ok(()) => Ok(TursoConnection {
                 conn: Some(conn),
                 needs_rollback: false,
                 _state: std::marker::PhantomData,
+                tx_guard: DropGuard::untracked(),
             }),
             Err(e) => {
                 // Best-effort rollback; keep needs_rollback so Drop can retry if needed.
@@ -44,12 +55,14 @@ impl TursoConnection<InTx> {
     /// # Errors
     /// Returns `SqlMiddlewareDbError` if rolling back fails.
     pub async fn rollback(mut self) -> Result<TursoConnection<Idle>, SqlMiddlewareDbError> {
+        self.tx_guard.disarm();
         let conn = self.take_conn()?;
         match conn.execute_batch("ROLLBACK").await {
             Ok(()) => Ok(TursoConnection {
                 conn: Some(conn),
                 needs_rollback: false,
                 _state: std::marker::PhantomData,
+                tx_guard: DropGuard::untracked(),
             }),
             Err(e) => {
                 self.conn = Some(conn);
@@ -63,6 +76,7 @@ impl TursoConnection<InTx> {
 
 pub(crate) async fn begin_from_conn(
     conn: bb8::PooledConnection<'static, TursoManager>,
+    tx_guard: DropGuard,
 ) -> Result<TursoConnection<InTx>, SqlMiddlewareDbError> {
     conn.execute_batch("BEGIN")
         .await
@@ -71,6 +85,7 @@ pub(crate) async fn begin_from_conn(
         conn: Some(conn),
         needs_rollback: true,
         _state: std::marker::PhantomData,
+        tx_guard: tx_guard.arm(),
     })
 }
 
@@ -90,9 +105,8 @@ impl<State> Drop for TursoConnection<State> {
         if self.needs_rollback
             && !skip_drop_rollback()
             && let Some(conn) = self.conn.take()
-            && let Ok(handle) = Handle::try_current()
         {
-            handle.spawn(async move {
+            crate::runtime::spawn_detached(async move {
                 let _ = conn.execute_batch("ROLLBACK").await;
             });
         }
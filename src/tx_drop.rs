@@ -0,0 +1,144 @@
+//! Transactions dropped without commit or rollback.
+//!
+//! Every transaction handle (`sqlite::Tx`, `postgres::Tx`, `mssql::Tx`, `turso::Tx`, and the
+//! typed connections in the `InTx` state) remembers the call site that began it. Dropping one
+//! while its transaction is still open rolls the transaction back, counts the drop in
+//! [`dropped_transactions`], and, under the default [`DropPolicy::Warn`], logs a `tracing`
+//! warning (target `sql_middleware::tx_drop`) naming that call site. An early `?` or a cancelled
+//! future between `begin` and `commit` is the usual cause.
+//!
+//! The policy is process-wide; [`set_drop_policy`] changes it:
+//!
+//! ```rust
+//! use sql_middleware::tx_drop::{DropPolicy, set_drop_policy};
+//!
+//! // Make an unfinished transaction fail the test that leaked it.
+//! set_drop_policy(DropPolicy::Panic);
+//! # set_drop_policy(DropPolicy::Warn);
+//! ```
+//!
+//! The rollback itself depends on the backend. `SQLite` rolls back before the connection goes
+//! back to the pool. Postgres leaves it to `tokio-postgres`, which queues `ROLLBACK` on the
+//! connection. SQL Server rolls back in place on a multi-threaded runtime; on a current-thread
+//! runtime the pool rolls back when it next checks the connection out. `turso::Tx` leaves it to
+//! the driver, which rolls back on the connection's next statement, and the pool's checkout does
+//! the same. The typed Postgres and Turso connections spawn `ROLLBACK` on the runtime without
+//! waiting for it.
+
+#[cfg(any_backend)]
+use std::panic::Location;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+/// What happens, besides the rollback, when a transaction is dropped unfinished.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Log a warning naming the call site that began the transaction.
+    #[default]
+    Warn,
+    /// Only count the drop.
+    Quiet,
+    /// Log the warning, then panic (unless the thread is already panicking). Meant for tests.
+    Panic,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Set the process-wide [`DropPolicy`].
+pub fn set_drop_policy(policy: DropPolicy) {
+    let value = match policy {
+        DropPolicy::Warn => 0,
+        DropPolicy::Quiet => 1,
+        DropPolicy::Panic => 2,
+    };
+    POLICY.store(value, Ordering::Relaxed);
+}
+
+/// The current process-wide [`DropPolicy`].
+#[must_use]
+pub fn drop_policy() -> DropPolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        1 => DropPolicy::Quiet,
+        2 => DropPolicy::Panic,
+        _ => DropPolicy::Warn,
+    }
+}
+
+/// Transactions dropped without commit or rollback since the process started.
+#[must_use]
+pub fn dropped_transactions() -> u64 {
+    DROPPED.load(Ordering::Relaxed)
+}
+
+/// Held by a transaction handle: remembers where the transaction began and reports the handle
+/// being dropped between [`arm`](Self::arm) and [`disarm`](Self::disarm). Declare it as the
+/// handle's last field so it reports after the handle's own rollback has run.
+#[cfg(any_backend)]
+#[derive(Debug)]
+pub(crate) struct DropGuard {
+    began_at: Option<(&'static str, &'static Location<'static>)>,
+    armed: bool,
+}
+
+// Only the backend transaction types arm and disarm guards.
+#[cfg(any_backend)]
+impl DropGuard {
+    /// Record the caller as the transaction's origin; `#[track_caller]` functions pass their own
+    /// caller through. The guard stays quiet until [`arm`](Self::arm), so a failed `BEGIN` is
+    /// not reported.
+    #[track_caller]
+    pub(crate) fn new(backend: &'static str) -> Self {
+        Self {
+            began_at: Some((backend, Location::caller())),
+            armed: false,
+        }
+    }
+
+    /// A guard that never reports, for transactions the crate begins and relies on dropping to
+    /// roll back (auto-commit helpers that return early on error).
+    pub(crate) fn untracked() -> Self {
+        Self {
+            began_at: None,
+            armed: false,
+        }
+    }
+
+    /// The transaction is open.
+    pub(crate) fn arm(mut self) -> Self {
+        self.armed = true;
+        self
+    }
+
+    /// The transaction finished through commit or rollback.
+    pub(crate) fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+#[cfg(any_backend)]
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        let Some((backend, location)) = self.began_at.take() else {
+            return;
+        };
+        if !self.armed {
+            return;
+        }
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+        let policy = drop_policy();
+        if policy == DropPolicy::Quiet {
+            return;
+        }
+        tracing::warn!(
+            target: "sql_middleware::tx_drop",
+            backend,
+            began_at = %location,
+            "transaction dropped without commit or rollback"
+        );
+        if policy == DropPolicy::Panic && !std::thread::panicking() {
+            panic!(
+                "{backend} transaction begun at {location} was dropped without commit or rollback"
+            );
+        }
+    }
+}
@@ -62,6 +62,7 @@ impl TxOutcome {
             Some(MiddlewarePoolConnection::Sqlite {
                 mut conn,
                 translate_placeholders,
+                ..
             }) => conn.take().map(|conn| (conn, translate_placeholders)),
             _ => None,
         }
@@ -7,7 +7,9 @@ use crate::sqlite::typed::{Idle as SqIdle, InTx as SqInTx, SqliteTypedConnection
 #[cfg(feature = "turso")]
 use crate::turso::typed::{Idle as TuIdle, InTx as TuInTx, TursoConnection};
 
+#[cfg(any_backend)]
 mod ops;
+#[cfg(any_backend)]
 mod queryable;
 
 /// Backend-neutral idle wrapper.
@@ -1,4 +1,5 @@
 use crate::SqlMiddlewareDbError;
+use crate::tx_drop::DropGuard;
 use crate::typed::traits::{BeginTx, TxConn, TypedConnOps};
 use crate::{middleware::RowValues, results::ResultSet};
 
@@ -131,14 +132,24 @@ impl BeginTx for AnyIdle {
 
     #[allow(clippy::manual_async_fn)]
     fn begin(self) -> impl std::future::Future<Output = Result<Self::Tx, SqlMiddlewareDbError>> {
+        let tx_guard = DropGuard::new(match &self {
+            #[cfg(feature = "postgres")]
+            AnyIdle::Postgres(_) => "postgres",
+            #[cfg(feature = "sqlite")]
+            AnyIdle::Sqlite(_) => "sqlite",
+            #[cfg(feature = "turso")]
+            AnyIdle::Turso(_) => "turso",
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("typed backends are not enabled"),
+        });
         async move {
             match self {
                 #[cfg(feature = "postgres")]
-                AnyIdle::Postgres(conn) => Ok(AnyTx::Postgres(conn.begin().await?)),
+                AnyIdle::Postgres(conn) => Ok(AnyTx::Postgres(conn.begin_guarded(tx_guard).await?)),
                 #[cfg(feature = "sqlite")]
-                AnyIdle::Sqlite(conn) => Ok(AnyTx::Sqlite(conn.begin().await?)),
+                AnyIdle::Sqlite(conn) => Ok(AnyTx::Sqlite(conn.begin_guarded(tx_guard).await?)),
                 #[cfg(feature = "turso")]
-                AnyIdle::Turso(conn) => Ok(AnyTx::Turso(conn.begin().await?)),
+                AnyIdle::Turso(conn) => Ok(AnyTx::Turso(conn.begin_guarded(tx_guard).await?)),
                 #[allow(unreachable_patterns)]
                 _ => unreachable!("typed backends are not enabled"),
             }
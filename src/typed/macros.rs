@@ -71,11 +71,10 @@ macro_rules! impl_typed_backend {
         impl BeginTx for $conn<$idle> {
             type Tx = $conn<$intx>;
 
-            #[allow(clippy::manual_async_fn)]
             fn begin(
                 self,
             ) -> impl std::future::Future<Output = Result<Self::Tx, SqlMiddlewareDbError>> {
-                async move { self.begin().await }
+                self.begin()
             }
         }
 
@@ -3,16 +3,19 @@
 //! This module provides traits for typed database connections with compile-time
 //! transaction state tracking, plus backend-neutral `AnyIdle`/`AnyTx` wrappers.
 
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
 mod any;
-mod macros;
 #[cfg(feature = "postgres")]
 mod impl_postgres;
 #[cfg(feature = "sqlite")]
 mod impl_sqlite;
 #[cfg(feature = "turso")]
 mod impl_turso;
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
+mod macros;
 mod traits;
 
 // Re-export everything for public API
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "turso"))]
 pub use any::{AnyIdle, AnyTx};
 pub use traits::{BeginTx, Queryable, TxConn, TypedConnOps};
@@ -1,6 +1,7 @@
 //! Core traits for typed database connections.
 
 use crate::SqlMiddlewareDbError;
+use crate::results::FromRowValues;
 use crate::{middleware::RowValues, query_builder::QueryBuilder, results::ResultSet};
 
 /// Minimal query surface shared by idle and tx connections.
@@ -29,13 +30,65 @@ pub trait TypedConnOps: Queryable {
         query: &str,
         params: &[RowValues],
     ) -> impl std::future::Future<Output = Result<ResultSet, SqlMiddlewareDbError>>;
+
+    /// Whether the SELECT `query` returns any rows.
+    ///
+    /// Runs `SELECT EXISTS (query)`, so the backend stops at the first row. `query` must be a
+    /// single statement without a trailing `;`.
+    #[allow(clippy::manual_async_fn)]
+    fn exists(
+        &mut self,
+        query: &str,
+        params: &[RowValues],
+    ) -> impl std::future::Future<Output = Result<bool, SqlMiddlewareDbError>> {
+        async move {
+            let wrapped = format!("SELECT EXISTS (\n{query}\n)");
+            // Postgres returns a boolean; SQLite and Turso return 0 or 1.
+            match self
+                .query(&wrapped)
+                .params(params)
+                .select_scalar::<RowValues>()
+                .await?
+            {
+                RowValues::Bool(found) => Ok(found),
+                RowValues::Int(found) => Ok(found != 0),
+                other => Err(SqlMiddlewareDbError::ExecutionError(format!(
+                    "exists: expected a boolean, got {other:?}"
+                ))),
+            }
+        }
+    }
+
+    /// The first column of the first row of `query`, as a `T`; see
+    /// [`QueryBuilder::select_scalar`].
+    #[allow(clippy::manual_async_fn)]
+    fn scalar<T: FromRowValues>(
+        &mut self,
+        query: &str,
+        params: &[RowValues],
+    ) -> impl std::future::Future<Output = Result<T, SqlMiddlewareDbError>> {
+        async move { self.query(query).params(params).select_scalar().await }
+    }
+
+    /// Run the DML `query` and return how many rows it changed.
+    #[allow(clippy::manual_async_fn)]
+    fn affected(
+        &mut self,
+        query: &str,
+        params: &[RowValues],
+    ) -> impl std::future::Future<Output = Result<usize, SqlMiddlewareDbError>> {
+        async move { self.query(query).params(params).dml().await }
+    }
 }
 
 /// Begin a transaction from an idle connection.
 pub trait BeginTx: Sized {
     type Tx: TxConn<Idle = Self>;
 
+    /// Begin the transaction; the caller's location is reported if it is dropped unfinished
+    /// (see [`tx_drop`](crate::tx_drop)).
     #[allow(clippy::manual_async_fn)]
+    #[track_caller]
     fn begin(self) -> impl std::future::Future<Output = Result<Self::Tx, SqlMiddlewareDbError>>;
 }
 
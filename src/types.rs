@@ -1,13 +1,20 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
 use chrono::NaiveDateTime;
+#[cfg(feature = "clap")]
 use clap::ValueEnum;
+#[cfg(feature = "json")]
 use serde_json::Value as JsonValue;
 
+use crate::custom::CustomValue;
 use crate::error::SqlMiddlewareDbError;
 
 /// Values that can be stored in a database row or used as query parameters.
 ///
 /// Reuse the same enum across backends so helper functions do not need to branch on driver
-/// types:
+/// types. Text and blobs are reference-counted, so cloning a value, a row, or a parameter list
+/// for a retry does not copy their contents:
 /// ```rust
 /// use sql_middleware::prelude::*;
 ///
@@ -25,7 +32,7 @@ pub enum RowValues {
     /// Floating point value (64-bit)
     Float(f64),
     /// Text/string value
-    Text(String),
+    Text(Arc<str>),
     /// Boolean value
     Bool(bool),
     /// Timestamp value
@@ -33,9 +40,15 @@ pub enum RowValues {
     /// NULL value
     Null,
     /// JSON value
+    #[cfg(feature = "json")]
     JSON(JsonValue),
     /// Binary data
-    Blob(Vec<u8>),
+    Blob(Arc<[u8]>),
+    /// Application-defined type, bound as its encoding (see [`crate::custom`])
+    Custom(Box<dyn CustomValue>),
+    /// Spatial value as WKB (or PostGIS EWKB) bytes (see [`crate::geo`])
+    #[cfg(feature = "geo")]
+    Geometry(Arc<[u8]>),
 }
 
 impl RowValues {
@@ -45,6 +58,12 @@ impl RowValues {
         matches!(self, Self::Null)
     }
 
+    /// `None` for NULL, otherwise `Some(self)`.
+    #[must_use]
+    pub fn into_option(self) -> Option<Self> {
+        if self.is_null() { None } else { Some(self) }
+    }
+
     #[must_use]
     pub fn as_int(&self) -> Option<&i64> {
         if let RowValues::Int(value) = self {
@@ -111,10 +130,120 @@ impl RowValues {
             None
         }
     }
+
+    /// WKB bytes of a [`Geometry`](RowValues::Geometry) value.
+    #[cfg(feature = "geo")]
+    #[must_use]
+    pub fn as_geometry(&self) -> Option<&[u8]> {
+        if let RowValues::Geometry(wkb) = self {
+            Some(wkb)
+        } else {
+            None
+        }
+    }
+
+    /// Borrow a [`Custom`](RowValues::Custom) value as its concrete type `T`.
+    #[must_use]
+    pub fn as_custom<T: CustomValue + 'static>(&self) -> Option<&T> {
+        if let RowValues::Custom(value) = self {
+            value.as_any().downcast_ref::<T>()
+        } else {
+            None
+        }
+    }
+}
+
+impl RowValues {
+    /// Total ordering across all variants, used to sort rows deterministically.
+    ///
+    /// `Null` sorts first, numbers compare by exact value (`Int` against `Float` included, and
+    /// `-0.0` equal to `0.0`), and the remaining variants order by type and then by value. JSON compares by its serialized text.
+    #[must_use]
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (RowValues::Int(a), RowValues::Int(b)) => a.cmp(b),
+            // Adding `0.0` turns `-0.0` into `0.0`, so the zeros tie, as each does with `Int(0)`.
+            (RowValues::Float(a), RowValues::Float(b)) => (a + 0.0).total_cmp(&(b + 0.0)),
+            (RowValues::Int(a), RowValues::Float(b)) => cmp_int_float(*a, *b),
+            (RowValues::Float(a), RowValues::Int(b)) => cmp_int_float(*b, *a).reverse(),
+            (RowValues::Text(a), RowValues::Text(b)) => a.cmp(b),
+            (RowValues::Bool(a), RowValues::Bool(b)) => a.cmp(b),
+            (RowValues::Timestamp(a), RowValues::Timestamp(b)) => a.cmp(b),
+            #[cfg(feature = "json")]
+            (RowValues::JSON(a), RowValues::JSON(b)) => a.to_string().cmp(&b.to_string()),
+            (RowValues::Blob(a), RowValues::Blob(b)) => a.cmp(b),
+            (RowValues::Custom(a), RowValues::Custom(b)) => a
+                .type_name()
+                .cmp(b.type_name())
+                .then_with(|| a.encode().total_cmp(&b.encode())),
+            #[cfg(feature = "geo")]
+            (RowValues::Geometry(a), RowValues::Geometry(b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+
+    pub(crate) fn variant_name(&self) -> &'static str {
+        match self {
+            RowValues::Int(_) => "Int",
+            RowValues::Float(_) => "Float",
+            RowValues::Text(_) => "Text",
+            RowValues::Bool(_) => "Bool",
+            RowValues::Timestamp(_) => "Timestamp",
+            RowValues::Null => "Null",
+            #[cfg(feature = "json")]
+            RowValues::JSON(_) => "JSON",
+            RowValues::Blob(_) => "Blob",
+            RowValues::Custom(_) => "Custom",
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(_) => "Geometry",
+        }
+    }
+
+    fn type_rank(&self) -> u8 {
+        match self {
+            RowValues::Null => 0,
+            RowValues::Bool(_) => 1,
+            RowValues::Int(_) | RowValues::Float(_) => 2,
+            RowValues::Text(_) => 3,
+            RowValues::Timestamp(_) => 4,
+            #[cfg(feature = "json")]
+            RowValues::JSON(_) => 5,
+            RowValues::Blob(_) => 6,
+            RowValues::Custom(_) => 7,
+            #[cfg(feature = "geo")]
+            RowValues::Geometry(_) => 8,
+        }
+    }
+}
+
+/// Compare an integer with a float exactly, without rounding the integer to `f64`: integer
+/// parts first, then the float's fraction. NaN sorts as in [`f64::total_cmp`].
+fn cmp_int_float(int: i64, float: f64) -> Ordering {
+    // 2^63: the first float past `i64::MAX`; `i64::MIN` is exactly `-2^63`.
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() {
+        return if float.is_sign_negative() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+    }
+    if float >= LIMIT {
+        return Ordering::Less;
+    }
+    if float < -LIMIT {
+        return Ordering::Greater;
+    }
+    let whole = float.trunc();
+    // In range and integral, so the cast is exact.
+    #[allow(clippy::cast_possible_truncation)]
+    let int_part = whole as i64;
+    int.cmp(&int_part).then_with(|| whole.total_cmp(&float))
 }
 
 /// The database type supported by this middleware
-#[derive(Debug, Clone, PartialEq, Eq, Hash, ValueEnum)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
 pub enum DatabaseType {
     /// `PostgreSQL` database
     #[cfg(feature = "postgres")]
@@ -130,6 +259,34 @@ pub enum DatabaseType {
     Turso,
 }
 
+impl DatabaseType {
+    /// Native placeholder for the 1-based parameter `idx` (`$1`, `?1`, or `@P1`).
+    pub(crate) fn placeholder(&self, idx: usize) -> String {
+        match self {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => format!("${idx}"),
+            #[cfg(feature = "mssql")]
+            DatabaseType::Mssql => format!("@P{idx}"),
+            #[allow(unreachable_patterns)]
+            _ => format!("?{idx}"),
+        }
+    }
+
+    /// Whether the backend can prepare a transaction for two-phase commit and finish it later
+    /// from another connection (see [`ConfigAndPool::commit_prepared`]).
+    ///
+    /// [`ConfigAndPool::commit_prepared`]: crate::ConfigAndPool::commit_prepared
+    #[must_use]
+    pub fn supports_prepared_transactions(&self) -> bool {
+        match self {
+            #[cfg(feature = "postgres")]
+            DatabaseType::Postgres => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
 /// The conversion "mode".
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ConversionMode {
@@ -137,6 +294,70 @@ pub enum ConversionMode {
     Query,
     /// When the converted parameters will be used for statement execution (INSERT/UPDATE/etc.)
     Execute,
+    /// Like `Execute`, but fail instead of binding a value as a different type (see
+    /// [`DatabaseType::param_coercion`]).
+    Strict,
+}
+
+impl ConversionMode {
+    #[must_use]
+    pub fn is_strict(self) -> bool {
+        matches!(self, ConversionMode::Strict)
+    }
+}
+
+impl DatabaseType {
+    /// The type this backend binds `value` as when it has no native type for it, such as
+    /// `INTEGER` for a `Bool` on `SQLite`. `None` when the value keeps its own type.
+    #[must_use]
+    pub fn param_coercion(&self, value: &RowValues) -> Option<&'static str> {
+        if let RowValues::Custom(custom) = value {
+            return self.param_coercion(&custom.encode());
+        }
+        match (self, value) {
+            #[cfg(feature = "sqlite")]
+            (DatabaseType::Sqlite, RowValues::Bool(_)) => Some("INTEGER"),
+            #[cfg(feature = "sqlite")]
+            (DatabaseType::Sqlite, RowValues::Timestamp(_)) => Some("TEXT"),
+            #[cfg(all(feature = "sqlite", feature = "json"))]
+            (DatabaseType::Sqlite, RowValues::JSON(_)) => Some("TEXT"),
+            #[cfg(all(feature = "sqlite", feature = "geo"))]
+            (DatabaseType::Sqlite, RowValues::Geometry(_)) => Some("BLOB"),
+            #[cfg(feature = "turso")]
+            (DatabaseType::Turso, RowValues::Bool(_)) => Some("INTEGER"),
+            #[cfg(feature = "turso")]
+            (DatabaseType::Turso, RowValues::Timestamp(_)) => Some("TEXT"),
+            #[cfg(all(feature = "turso", feature = "json"))]
+            (DatabaseType::Turso, RowValues::JSON(_)) => Some("TEXT"),
+            #[cfg(all(feature = "turso", feature = "geo"))]
+            (DatabaseType::Turso, RowValues::Geometry(_)) => Some("BLOB"),
+            #[cfg(feature = "mssql")]
+            (DatabaseType::Mssql, RowValues::Timestamp(_)) => Some("NVARCHAR"),
+            #[cfg(all(feature = "mssql", feature = "json"))]
+            (DatabaseType::Mssql, RowValues::JSON(_)) => Some("NVARCHAR"),
+            #[cfg(all(feature = "mssql", feature = "geo"))]
+            (DatabaseType::Mssql, RowValues::Geometry(_)) => Some("VARBINARY"),
+            _ => None,
+        }
+    }
+
+    /// Reject `params` if any would be coerced (see [`DatabaseType::param_coercion`]).
+    #[cfg(any_backend)]
+    pub(crate) fn check_strict_params(
+        &self,
+        params: &[RowValues],
+    ) -> Result<(), SqlMiddlewareDbError> {
+        for (idx, value) in params.iter().enumerate() {
+            if let Some(bound_as) = self.param_coercion(value) {
+                return Err(SqlMiddlewareDbError::ParameterError(format!(
+                    "strict mode: parameter {} ({}) would be bound as {bound_as} on {self:?}",
+                    idx + 1,
+                    value.variant_name()
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Convert a slice of `RowValues` into database-specific parameters.
@@ -167,3 +388,73 @@ pub trait ParamConverter<'a> {
         true // By default, support both modes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::RowValues;
+
+    #[test]
+    fn ints_and_floats_compare_exactly() {
+        let big = RowValues::Int(9_007_199_254_740_993); // 2^53 + 1, not representable as f64
+        let float = RowValues::Float(9_007_199_254_740_992.0);
+        assert_eq!(big.total_cmp(&float), Ordering::Greater);
+        assert_eq!(float.total_cmp(&big), Ordering::Less);
+
+        assert_eq!(
+            RowValues::Int(1).total_cmp(&RowValues::Float(1.5)),
+            Ordering::Less
+        );
+        assert_eq!(
+            RowValues::Int(-1).total_cmp(&RowValues::Float(-1.5)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            RowValues::Int(2).total_cmp(&RowValues::Float(2.0)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            RowValues::Int(i64::MAX).total_cmp(&RowValues::Float(9.223_372_036_854_776e18)),
+            Ordering::Less
+        );
+        assert_eq!(
+            RowValues::Int(i64::MIN).total_cmp(&RowValues::Float(f64::NEG_INFINITY)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            RowValues::Int(0).total_cmp(&RowValues::Float(f64::NAN)),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn signed_zeros_sort_consistently() {
+        assert_eq!(
+            RowValues::Float(-0.0).total_cmp(&RowValues::Float(0.0)),
+            Ordering::Equal
+        );
+        let mut values = [
+            RowValues::Float(0.0),
+            RowValues::Int(0),
+            RowValues::Float(-0.0),
+            RowValues::Int(0),
+            RowValues::Float(0.0),
+            RowValues::Float(-0.0),
+            RowValues::Float(-0.5),
+            RowValues::Int(1),
+        ];
+        values.sort_by(RowValues::total_cmp);
+        for (i, a) in values.iter().enumerate() {
+            for b in &values[i..] {
+                assert_ne!(
+                    a.total_cmp(b),
+                    Ordering::Greater,
+                    "{a:?} sorted before {b:?}"
+                );
+            }
+        }
+        assert!(matches!(values.first(), Some(RowValues::Float(f)) if *f == -0.5));
+        assert!(matches!(values.last(), Some(RowValues::Int(1))));
+    }
+}
@@ -101,7 +101,7 @@ fn test2_postgres_cr_and_del_tbls() -> Result<(), Box<dyn std::error::Error>> {
             ),
             params: vec![
                 RowValues::Int(123_456),
-                RowValues::Text("test name".to_string()),
+                RowValues::Text("test name".into()),
                 RowValues::Timestamp(NaiveDateTime::parse_from_str(
                     "2021-08-06 16:00:00",
                     "%Y-%m-%d %H:%M:%S",
@@ -139,7 +139,7 @@ fn test2_postgres_cr_and_del_tbls() -> Result<(), Box<dyn std::error::Error>> {
             vec![
                 RowValues::Int(1),
                 RowValues::Int(123_456),
-                RowValues::Text("test name".to_string()),
+                RowValues::Text("test name".into()),
                 RowValues::Timestamp(
                     NaiveDateTime::parse_from_str("2021-08-06 16:00:00", "%Y-%m-%d %H:%M:%S")
                         .unwrap(),
@@ -187,7 +187,7 @@ fn test2_postgres_cr_and_del_tbls() -> Result<(), Box<dyn std::error::Error>> {
                     ),
                     &[
                         RowValues::Int(123_456),
-                        RowValues::Text("test name".to_string()),
+                        RowValues::Text("test name".into()),
                         RowValues::Timestamp(typed_ts),
                     ],
                 )
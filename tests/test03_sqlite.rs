@@ -113,12 +113,12 @@ fn sqlite_and_turso_multiple_column_test_db2() -> Result<(), Box<dyn std::error:
                 vec![RowValues::Int(9)],
                 vec![
                     RowValues::Int(100),
-                    RowValues::Text("Juliet".to_string()),
+                    RowValues::Text("Juliet".into()),
                     RowValues::Float(100.75),
                 ],
             ];
 
-            for (sql, params) in setup_queries.into_iter().zip(param_sets.into_iter()) {
+            for (sql, params) in setup_queries.into_iter().zip(param_sets) {
                 conn.query(sql).params(&params).dml().await?;
             }
 
@@ -389,7 +389,7 @@ END;
 
     // generate 100 params
     let params: Vec<Vec<RowValues>> = (0..100)
-        .map(|i| vec![RowValues::Int(i), RowValues::Text(format!("name_{i}"))])
+        .map(|i| vec![RowValues::Int(i), RowValues::Text(format!("name_{i}").into())])
         .collect();
     // dbg!(&params);
 
@@ -409,7 +409,7 @@ END;
 
     // generate 100 more params
     let params: Vec<Vec<RowValues>> = (100..200)
-        .map(|i| vec![RowValues::Int(i), RowValues::Text(format!("name_{i}"))])
+        .map(|i| vec![RowValues::Int(i), RowValues::Text(format!("name_{i}").into())])
         .collect();
 
     // now let's be a little smarter and write our own loop to exec a 100 inserts
@@ -485,7 +485,7 @@ END;
 
     // generate 200 more params
     let params: Vec<Vec<RowValues>> = (0..200)
-        .map(|i| vec![RowValues::Int(i), RowValues::Text(format!("name_{i}"))])
+        .map(|i| vec![RowValues::Int(i), RowValues::Text(format!("name_{i}").into())])
         .collect();
 
     // now let's be a little smarter and write our own loop to exec inserts
@@ -590,7 +590,7 @@ END;
 
     // let's test a common pattern in rusty-golf
     // generate 1 more param
-    let params: Vec<RowValues> = vec![RowValues::Int(990), RowValues::Text("name_990".to_string())];
+    let params: Vec<RowValues> = vec![RowValues::Int(990), RowValues::Text("name_990".into())];
 
     let query_and_params = QueryAndParams {
         query: parameterized_query.clone(),
@@ -50,7 +50,7 @@ async fn sqlite_tx_concurrency_and_rollbacks() -> Result<(), Box<dyn std::error:
             let _ = apply_pragmas(&mut conn).await;
             let mut tx = begin_transaction(&mut conn).await?;
             let stmt = tx.prepare("INSERT INTO stress (id, val) VALUES (?1, ?2)")?;
-            let params = [RowValues::Int(i), RowValues::Text(format!("ok-{i}"))];
+            let params = [RowValues::Int(i), RowValues::Text(format!("ok-{i}").into())];
             tx.execute_prepared(&stmt, &params).await?;
             tx.commit().await?;
             Ok::<(), SqlMiddlewareDbError>(())
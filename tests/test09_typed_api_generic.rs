@@ -21,7 +21,10 @@ async fn insert_rows(
         let id = start_id + idx;
         conn.query("INSERT INTO typed_api_users (id, name) VALUES ($1, $2)")
             .translation(TranslationMode::ForceOn)
-            .params(&[RowValues::Int(id), RowValues::Text(format!("{label}_{id}"))])
+            .params(&[
+                RowValues::Int(id),
+                RowValues::Text(format!("{label}_{id}").into()),
+            ])
             .dml()
             .await?;
     }
@@ -0,0 +1,68 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sql_middleware::clock::MockClock;
+use sql_middleware::middleware::{ConfigAndPool, MiddlewarePoolConnection, SqlMiddlewareDbError};
+
+// bb8's default max_size for the SQLite pool.
+const DEFAULT_POOL_SIZE: usize = 10;
+
+async fn exhaust(
+    cap: &ConfigAndPool,
+) -> Result<Vec<MiddlewarePoolConnection>, SqlMiddlewareDbError> {
+    let mut held = Vec::with_capacity(DEFAULT_POOL_SIZE);
+    for _ in 0..DEFAULT_POOL_SIZE {
+        held.push(cap.get_connection().await?);
+    }
+    Ok(held)
+}
+
+#[tokio::test]
+async fn checkout_timeout_runs_on_mock_clock() -> Result<(), Box<dyn std::error::Error>> {
+    let clock = MockClock::new();
+    let cap = ConfigAndPool::sqlite_builder("file:test13_mock?mode=memory&cache=shared".into())
+        .build()
+        .await?
+        .with_clock(Arc::new(clock.clone()));
+
+    // Nothing advances the clock, so a free connection wins without any deadline pressure.
+    drop(cap.get_connection_timeout(Duration::from_secs(5)).await?);
+    assert_eq!(clock.elapsed(), Duration::ZERO);
+
+    let held = exhaust(&cap).await?;
+    let (result, ()) = tokio::join!(cap.get_connection_timeout(Duration::from_secs(5)), async {
+        clock.advance(Duration::from_secs(4));
+        tokio::task::yield_now().await;
+        clock.advance(Duration::from_secs(1));
+    });
+    let err = result.expect_err("exhausted pool should time out");
+    assert!(matches!(err, SqlMiddlewareDbError::ConnectionError(_)));
+    assert_eq!(clock.elapsed(), Duration::from_secs(5));
+
+    drop(held);
+    drop(cap.get_connection_timeout(Duration::from_secs(5)).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn checkout_timeout_follows_paused_tokio_time() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test13_paused?mode=memory&cache=shared".into())
+        .build()
+        .await?;
+    let held = exhaust(&cap).await?;
+
+    // Pause only after the pool is warm so bb8's own connect timeouts are unaffected.
+    tokio::time::pause();
+    let start = tokio::time::Instant::now();
+    let err = cap
+        .get_connection_timeout(Duration::from_secs(5))
+        .await
+        .expect_err("exhausted pool should time out");
+    assert!(matches!(err, SqlMiddlewareDbError::ConnectionError(_)));
+    assert!(start.elapsed() >= Duration::from_secs(5));
+
+    drop(held);
+    Ok(())
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::middleware::{ConfigAndPool, RowValues};
+
+#[tokio::test]
+async fn attached_databases_are_visible_on_every_checkout() -> Result<(), Box<dyn std::error::Error>>
+{
+    let dir = tempfile::tempdir()?;
+    let main_path = dir.path().join("main.db");
+    let users_path = dir.path().join("users.db");
+    let audit_path = dir.path().join("audit.db");
+
+    let cap = ConfigAndPool::sqlite_builder(main_path.to_string_lossy().into_owned())
+        .attach("users", users_path.to_string_lossy())
+        .build()
+        .await?;
+
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE orders (id INTEGER, user_id INTEGER);
+         CREATE TABLE users.people (id INTEGER, name TEXT);
+         INSERT INTO orders VALUES (1, 10);
+         INSERT INTO users.people VALUES (10, 'alice');",
+    )
+    .await?;
+
+    // A second checkout opens a different pooled connection that must carry the attachment too.
+    let mut other = cap.get_connection().await?;
+    let rows = other
+        .query("SELECT o.id, p.name FROM main.orders o JOIN users.people p ON p.id = o.user_id")
+        .select()
+        .await?;
+    assert_eq!(rows.results.len(), 1);
+    assert_eq!(
+        rows.results[0].get("name"),
+        Some(&RowValues::Text("alice".into()))
+    );
+
+    // Runtime attach only affects the connection it runs on.
+    conn.attach("audit", &audit_path.to_string_lossy()).await?;
+    conn.execute_batch("CREATE TABLE audit.log (msg TEXT)")
+        .await?;
+    assert!(
+        conn.attach("audit", &audit_path.to_string_lossy())
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
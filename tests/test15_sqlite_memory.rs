@@ -0,0 +1,57 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::middleware::{ConfigAndPool, RowValues, SqlMiddlewareDbError};
+
+async fn count_rows(cap: &ConfigAndPool) -> Result<i64, SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS items (id INTEGER)")
+        .await?;
+    let rows = conn.query("SELECT COUNT(*) FROM items").select().await?;
+    Ok(*rows.results[0]
+        .get("COUNT(*)")
+        .and_then(RowValues::as_int)
+        .unwrap())
+}
+
+#[tokio::test]
+async fn named_memory_database_lives_as_long_as_the_pool() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_sqlite_memory("test15_lifecycle").await?;
+    {
+        let mut conn = cap.get_connection().await?;
+        conn.execute_batch("CREATE TABLE items (id INTEGER); INSERT INTO items VALUES (1), (2);")
+            .await?;
+    }
+    assert_eq!(count_rows(&cap).await?, 2);
+
+    // Another pool on the same name shares the database while the first one is alive.
+    let other = ConfigAndPool::new_sqlite_memory("test15_lifecycle").await?;
+    assert_eq!(count_rows(&other).await?, 2);
+    drop(other);
+    assert_eq!(count_rows(&cap).await?, 2);
+
+    // Once every pool is gone the database is discarded. Worker threads close their connections
+    // asynchronously, so give them a moment.
+    drop(cap);
+    for _ in 0..50 {
+        let fresh = ConfigAndPool::new_sqlite_memory("test15_lifecycle").await?;
+        if count_rows(&fresh).await? == 0 {
+            return Ok(());
+        }
+        drop(fresh);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("in-memory database outlived its pools");
+}
+
+#[tokio::test]
+async fn rejects_names_that_break_the_uri() {
+    for name in ["", "a?mode=rwc", "dir/name"] {
+        let err = ConfigAndPool::new_sqlite_memory(name)
+            .await
+            .expect_err("invalid name should be rejected");
+        assert!(matches!(err, SqlMiddlewareDbError::ConfigError(_)));
+    }
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::middleware::{ConfigAndPool, RowValues};
+
+#[tokio::test]
+async fn select_multi_returns_single_sqlite_result_set() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test16_select_multi").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE t (id INTEGER, name TEXT); INSERT INTO t VALUES (2, 'b'), (1, 'a');",
+    )
+    .await?;
+
+    let sets = conn
+        .query("SELECT id, name FROM t WHERE id >= ?1")
+        .params(&[RowValues::Int(1)])
+        .stable_order()
+        .select_multi()
+        .await?;
+    assert_eq!(sets.len(), 1);
+    let ids: Vec<i64> = sets[0]
+        .results
+        .iter()
+        .map(|row| *row.get("id").and_then(RowValues::as_int).unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 2]);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::middleware::{ConfigAndPool, DatabaseType, InsertBuilder, RowValues};
+
+fn rows(ids: impl Iterator<Item = i64>) -> Vec<Vec<RowValues>> {
+    ids.map(|id| {
+        vec![
+            RowValues::Int(id),
+            RowValues::Text(format!("name-{id}").into()),
+        ]
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn bulk_insert_splits_rows_past_the_parameter_limit() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_sqlite_memory("test17_bulk").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await?;
+
+    // Two columns per row, so this needs twice the parameters one statement may bind.
+    let count = i64::try_from(DatabaseType::Sqlite.max_bind_params())?;
+    let inserted = conn
+        .bulk_insert("items", &["id", "name"], &rows(0..count))
+        .await?;
+    assert_eq!(i64::try_from(inserted)?, count);
+
+    let total = conn
+        .query("SELECT COUNT(*) AS n FROM items")
+        .select()
+        .await?;
+    assert_eq!(total.results[0].get("n"), Some(&RowValues::Int(count)));
+
+    // A failure in a later chunk rolls back the earlier ones.
+    let err = conn
+        .bulk_insert(
+            "items",
+            &["id", "name"],
+            &rows(count..count * 2)
+                .into_iter()
+                .chain(rows(0..1))
+                .collect::<Vec<_>>(),
+        )
+        .await;
+    assert!(err.is_err());
+    let total = conn
+        .query("SELECT COUNT(*) AS n FROM items")
+        .select()
+        .await?;
+    assert_eq!(total.results[0].get("n"), Some(&RowValues::Int(count)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn bulk_insert_rejects_ragged_rows() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test17_ragged").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE items (id INTEGER, name TEXT)")
+        .await?;
+
+    let ragged = vec![vec![RowValues::Int(1)]];
+    assert!(
+        conn.bulk_insert("items", &["id", "name"], &ragged)
+            .await
+            .is_err()
+    );
+    assert_eq!(conn.bulk_insert("items", &["id", "name"], &[]).await?, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn insert_builder_chunks_tuple_rows() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test17_builder").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL)")
+        .await?;
+
+    // Three columns per row: one full statement plus a final row in a second one.
+    let per_statement = DatabaseType::Sqlite.max_bind_params() / 3;
+    let count = i64::try_from(per_statement)?;
+    let builder = InsertBuilder::new("items")
+        .columns(["id", "name", "price"])
+        .rows((0..count).map(|id| (id, format!("name-{id}"), 1.5)))
+        .row((count, "last", None::<f64>));
+
+    let statements = builder.statements(&DatabaseType::Sqlite)?;
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0].1.len(), per_statement * 3);
+    assert_eq!(
+        statements[1].0,
+        r#"INSERT INTO "items" ("id", "name", "price") VALUES (?1, ?2, ?3)"#
+    );
+    assert_eq!(statements[1].1[1], RowValues::Text("last".into()));
+
+    assert_eq!(i64::try_from(builder.execute(&mut conn).await?)?, count + 1);
+    let last = conn
+        .query("SELECT name, price FROM items WHERE id = ?1")
+        .params((count,))
+        .select()
+        .await?;
+    assert_eq!(
+        last.results[0].get("name").and_then(RowValues::as_text),
+        Some("last")
+    );
+    assert_eq!(last.results[0].get("price"), Some(&RowValues::Null));
+    Ok(())
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "sqlite")]
+
+use bb8::Pool;
+use sql_middleware::SqlMiddlewareDbError;
+use sql_middleware::sqlite::config::SqliteManager;
+use sql_middleware::typed_sqlite::{Idle, SqliteTypedConnection};
+
+// `#[tokio::test]` runs on a current-thread runtime, where tokio's `block_in_place` panics.
+#[tokio::test]
+async fn dropped_tx_rolls_back_on_current_thread_runtime() -> Result<(), Box<dyn std::error::Error>>
+{
+    let pool = Pool::builder()
+        .max_size(1)
+        .build(SqliteManager::new(
+            "file:test18_drop?mode=memory&cache=shared".to_string(),
+        ))
+        .await
+        .map_err(|e| SqlMiddlewareDbError::ConnectionError(format!("sqlite pool error: {e}")))?;
+
+    {
+        let mut conn = SqliteTypedConnection::<Idle>::from_pool(&pool).await?;
+        conn.execute_batch("CREATE TABLE items (id INTEGER)")
+            .await?;
+    }
+    {
+        let mut tx = SqliteTypedConnection::<Idle>::from_pool(&pool)
+            .await?
+            .begin()
+            .await?;
+        tx.execute_batch("INSERT INTO items VALUES (1)").await?;
+        // Dropped without commit/rollback.
+    }
+
+    let mut conn = SqliteTypedConnection::<Idle>::from_pool(&pool).await?;
+    let rows = conn
+        .query("SELECT COUNT(*) AS n FROM items")
+        .select()
+        .await?;
+    assert_eq!(rows.results[0].get("n").and_then(|v| v.as_int()), Some(&0));
+    Ok(())
+}
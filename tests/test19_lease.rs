@@ -0,0 +1,36 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::lease::Lease;
+use sql_middleware::middleware::ConfigAndPool;
+
+#[tokio::test]
+async fn only_one_holder_until_release_or_expiry() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test19_lease").await?;
+    let ttl = Duration::from_millis(200);
+
+    let mut leader = Lease::acquire(&cap, "jobs", "a", ttl)
+        .await?
+        .expect("free lease should be acquired");
+    assert!(Lease::acquire(&cap, "jobs", "b", ttl).await?.is_none());
+    // Other names are independent.
+    assert!(Lease::acquire(&cap, "reports", "b", ttl).await?.is_some());
+
+    assert!(leader.renew().await?);
+    assert!(leader.release().await?);
+    let follower = Lease::acquire(&cap, "jobs", "b", ttl)
+        .await?
+        .expect("released lease should be acquired");
+    assert_eq!(follower.holder(), "b");
+
+    // Let b's lease lapse; a takes over and b can no longer renew.
+    tokio::time::sleep(ttl * 2).await;
+    let _leader = Lease::acquire(&cap, "jobs", "a", ttl)
+        .await?
+        .expect("expired lease should be taken over");
+    let mut stale = follower;
+    assert!(!stale.renew().await?);
+    assert!(!stale.release().await?);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::middleware::ConfigAndPool;
+use sql_middleware::queue::JobQueue;
+
+#[tokio::test]
+async fn jobs_are_claimed_once_and_reappear_after_timeout() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_sqlite_memory("test20_queue").await?;
+    let emails = JobQueue::open(&cap, "emails").await?;
+    let reports = JobQueue::open(&cap, "reports").await?;
+
+    let first = emails.enqueue(r#"{"to":"a"}"#).await?;
+    let second = emails.enqueue(r#"{"to":"b"}"#).await?;
+    reports.enqueue("monthly").await?;
+
+    let long = Duration::from_secs(60);
+    let job = emails.dequeue("w1", long).await?.expect("first job");
+    assert_eq!(
+        (job.id, job.payload.as_str(), job.attempts),
+        (first, r#"{"to":"a"}"#, 1)
+    );
+    let other = emails.dequeue("w2", long).await?.expect("second job");
+    assert_eq!(other.id, second);
+    assert!(emails.dequeue("w3", long).await?.is_none());
+
+    assert!(emails.complete(&job).await?);
+    assert!(emails.release(&other).await?);
+    let retried = emails.dequeue("w3", long).await?.expect("released job");
+    assert_eq!((retried.id, retried.attempts), (second, 2));
+
+    // An expired claim becomes visible again and the old worker can no longer complete it.
+    let short = Duration::from_millis(50);
+    let stale = reports.dequeue("w1", short).await?.expect("report job");
+    tokio::time::sleep(short * 3).await;
+    let taken = reports.dequeue("w2", long).await?.expect("timed-out job");
+    assert_eq!(taken.id, stale.id);
+    assert!(!reports.complete(&stale).await?);
+    assert!(reports.complete(&taken).await?);
+    assert!(reports.dequeue("w1", long).await?.is_none());
+    Ok(())
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "sqlite")]
+
+use serde_json::json;
+use sql_middleware::cdc::{self, ChangeCursor, ChangeOp};
+use sql_middleware::middleware::ConfigAndPool;
+
+#[tokio::test]
+async fn triggers_log_row_changes_in_order() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test21_cdc").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")
+        .await?;
+    cdc::enable_for_table(&mut conn, "people").await?;
+    // Re-enabling replaces the triggers instead of duplicating them.
+    cdc::enable_for_table(&mut conn, "people").await?;
+
+    conn.execute_batch(
+        "INSERT INTO people VALUES (1, 'ann', 30);
+         UPDATE people SET age = 31 WHERE id = 1;
+         DELETE FROM people WHERE id = 1;",
+    )
+    .await?;
+
+    let changes = cdc::poll_changes(&mut conn, ChangeCursor::default(), 10).await?;
+    let summary: Vec<_> = changes
+        .iter()
+        .map(|c| (c.table.as_str(), c.op, c.row.clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "people",
+                ChangeOp::Insert,
+                json!({"id": 1, "name": "ann", "age": 30})
+            ),
+            (
+                "people",
+                ChangeOp::Update,
+                json!({"id": 1, "name": "ann", "age": 31})
+            ),
+            (
+                "people",
+                ChangeOp::Delete,
+                json!({"id": 1, "name": "ann", "age": 31})
+            ),
+        ]
+    );
+
+    // Polling resumes after the last cursor and respects the limit.
+    assert_eq!(
+        cdc::poll_changes(&mut conn, changes[0].cursor, 1).await?,
+        changes[1..2]
+    );
+    assert!(
+        cdc::poll_changes(&mut conn, changes[2].cursor, 10)
+            .await?
+            .is_empty()
+    );
+
+    cdc::disable_for_table(&mut conn, "people").await?;
+    conn.execute_batch("INSERT INTO people VALUES (2, 'bo', 40)")
+        .await?;
+    assert!(
+        cdc::poll_changes(&mut conn, changes[2].cursor, 10)
+            .await?
+            .is_empty()
+    );
+    Ok(())
+}
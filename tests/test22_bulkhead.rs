@@ -0,0 +1,195 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::middleware::{ConfigAndPool, QueryOptions, SqlMiddlewareDbError};
+use sql_middleware::pool::PoolOptions;
+
+const BLOCKED: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn bulkhead_caps_tagged_checkouts() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_bulkhead")
+        .await?
+        .with_pool_options(PoolOptions::default().bulkhead("reports", 2));
+    let reports = QueryOptions::default().bulkhead("reports");
+
+    let mut first = cap.get_connection_with(reports.clone()).await?;
+    let second = cap.get_connection_with(reports.clone()).await?;
+    assert!(
+        tokio::time::timeout(BLOCKED, cap.get_connection_with(reports.clone()))
+            .await
+            .is_err(),
+        "a third report checkout should wait"
+    );
+
+    // Untagged work is not held back by the full bulkhead.
+    let mut oltp = cap.get_connection_with(QueryOptions::default()).await?;
+    oltp.query("SELECT 1").select().await?;
+    first.query("SELECT 1").select().await?;
+
+    drop(second);
+    tokio::time::timeout(BLOCKED, cap.get_connection_with(reports)).await??;
+
+    let err = cap
+        .get_connection_with(QueryOptions::default().bulkhead("unknown"))
+        .await
+        .expect_err("unconfigured tag");
+    assert!(matches!(err, SqlMiddlewareDbError::ConfigError(_)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn bulkhead_waiters_hold_no_connection() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_waiters")
+        .await?
+        .with_pool_options(PoolOptions::default().bulkhead("reports", 1));
+    let reports = QueryOptions::default().bulkhead("reports");
+
+    let held = cap.get_connection_with(reports.clone()).await?;
+    // More waiters than the pool has connections; none of them may take one while waiting.
+    let waiters: Vec<_> = (0..16)
+        .map(|_| {
+            let cap = cap.clone();
+            let reports = reports.clone();
+            tokio::spawn(async move { cap.get_connection_with(reports).await.map(drop) })
+        })
+        .collect();
+    tokio::time::sleep(BLOCKED).await;
+
+    let mut oltp = tokio::time::timeout(BLOCKED, cap.get_connection()).await??;
+    oltp.query("SELECT 1").select().await?;
+    drop(oltp);
+
+    drop(held);
+    for waiter in waiters {
+        tokio::time::timeout(BLOCKED * 10, waiter).await???;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn max_concurrent_queries_caps_all_checkouts() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_global")
+        .await?
+        .with_pool_options(
+            PoolOptions::default()
+                .max_concurrent_queries(2)
+                .bulkhead("reports", 2),
+        );
+
+    let held = cap
+        .get_connection_with(QueryOptions::default().bulkhead("reports"))
+        .await?;
+    let _other = cap.get_connection_with(QueryOptions::default()).await?;
+    assert!(
+        tokio::time::timeout(BLOCKED, cap.get_connection_with(QueryOptions::default()))
+            .await
+            .is_err()
+    );
+
+    drop(held);
+    tokio::time::timeout(BLOCKED, cap.get_connection_with(QueryOptions::default())).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn plain_checkouts_count_against_max_concurrent_queries()
+-> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_plain")
+        .await?
+        .with_pool_options(PoolOptions::default().max_concurrent_queries(1));
+
+    let held = cap.get_connection().await?;
+    assert!(
+        tokio::time::timeout(BLOCKED, cap.get_connection())
+            .await
+            .is_err(),
+        "a second checkout should wait"
+    );
+    drop(held);
+    tokio::time::timeout(BLOCKED, cap.get_connection()).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn builder_queries_honour_their_bulkhead() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_builder")
+        .await?
+        .with_pool_options(PoolOptions::default().bulkhead("reports", 1));
+    let reports = QueryOptions::default().bulkhead("reports");
+
+    // The checkout holds the only "reports" permit, and its own tagged queries still run.
+    let mut held = cap.get_connection_with(reports.clone()).await?;
+    held.query("SELECT 1")
+        .options(reports.clone())
+        .select()
+        .await?;
+
+    let mut conn = cap.get_connection().await?;
+    assert!(
+        tokio::time::timeout(
+            BLOCKED,
+            conn.query("SELECT 1").options(reports.clone()).select()
+        )
+        .await
+        .is_err(),
+        "a tagged query should wait for the bulkhead"
+    );
+    conn.query("SELECT 1").select().await?;
+
+    drop(held);
+    tokio::time::timeout(BLOCKED, conn.query("SELECT 1").options(reports).select()).await??;
+
+    let err = conn
+        .query("SELECT 1")
+        .options(QueryOptions::default().bulkhead("unknown"))
+        .select()
+        .await
+        .expect_err("unconfigured tag");
+    assert!(matches!(err, SqlMiddlewareDbError::ConfigError(_)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_options_merge_with_earlier_ones() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_merge")
+        .await?
+        .with_pool_options(PoolOptions::default().soft_delete_column("removed_at"))
+        .with_pool_options(PoolOptions::default().max_concurrent_queries(1));
+
+    let conn = cap.get_connection().await?;
+    assert_eq!(conn.soft_delete_column(), "removed_at");
+    assert!(
+        tokio::time::timeout(BLOCKED, cap.get_connection())
+            .await
+            .is_err()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn zero_limits_are_treated_as_one() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test22_zero")
+        .await?
+        .with_pool_options(
+            PoolOptions::default()
+                .max_concurrent_queries(0)
+                .bulkhead("reports", 0),
+        );
+
+    let held = tokio::time::timeout(
+        BLOCKED,
+        cap.get_connection_with(QueryOptions::default().bulkhead("reports")),
+    )
+    .await??;
+    assert!(
+        tokio::time::timeout(BLOCKED, cap.get_connection())
+            .await
+            .is_err(),
+        "a second checkout should wait"
+    );
+    drop(held);
+    tokio::time::timeout(BLOCKED, cap.get_connection()).await??;
+    Ok(())
+}
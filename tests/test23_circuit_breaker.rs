@@ -0,0 +1,120 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sql_middleware::clock::MockClock;
+use sql_middleware::middleware::{ConfigAndPool, RowValues, SqlMiddlewareDbError};
+use sql_middleware::pool::CircuitState;
+
+fn connection_failure() -> Result<(), SqlMiddlewareDbError> {
+    Err(SqlMiddlewareDbError::ConnectionError(
+        "connection reset".into(),
+    ))
+}
+
+#[tokio::test]
+async fn breaker_opens_fails_fast_and_probes() -> Result<(), Box<dyn std::error::Error>> {
+    let clock = MockClock::new();
+    let cap = ConfigAndPool::new_sqlite_memory("test23_breaker")
+        .await?
+        .with_clock(Arc::new(clock.clone()))
+        .with_circuit_breaker(3, Duration::from_secs(30));
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Closed));
+
+    // Query errors don't count; a success resets the streak.
+    cap.record_result(&connection_failure());
+    cap.record_result(&connection_failure());
+    cap.record_result::<()>(&Err(SqlMiddlewareDbError::ExecutionError("bad sql".into())));
+    cap.record_result(&Ok(()));
+    cap.record_result(&connection_failure());
+    cap.record_result(&connection_failure());
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Closed));
+
+    cap.record_result(&connection_failure());
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Open));
+    let err = cap.get_connection().await.expect_err("breaker is open");
+    assert!(matches!(
+        err,
+        SqlMiddlewareDbError::CircuitOpen { retry_after } if retry_after == Duration::from_secs(30)
+    ));
+
+    clock.advance(Duration::from_secs(10));
+    let err = cap.clone().get_connection().await.expect_err("still open");
+    assert!(matches!(
+        err,
+        SqlMiddlewareDbError::CircuitOpen { retry_after } if retry_after == Duration::from_secs(20)
+    ));
+
+    // After the cool-down the next checkout probes the database and closes the breaker.
+    clock.advance(Duration::from_secs(20));
+    let mut conn = cap.get_connection().await?;
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Closed));
+    conn.query("SELECT 1").select().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn pools_without_breaker_ignore_results() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test23_plain").await?;
+    for _ in 0..10 {
+        cap.record_result(&connection_failure());
+    }
+    assert_eq!(cap.circuit_state(), None);
+    cap.get_connection().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn checkouts_do_not_reset_the_failure_streak() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test23_streak")
+        .await?
+        .with_circuit_breaker(3, Duration::from_secs(30));
+
+    // Failures reported between checkouts still add up.
+    for _ in 0..2 {
+        cap.record_result(&connection_failure());
+        cap.get_connection().await?;
+    }
+    cap.record_result(&connection_failure());
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Open));
+    Ok(())
+}
+
+#[tokio::test]
+async fn statement_success_does_not_close_an_open_breaker() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_sqlite_memory("test23_late")
+        .await?
+        .with_circuit_breaker(1, Duration::from_secs(30));
+    let mut conn = cap.get_connection().await?;
+    cap.record_result(&connection_failure());
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Open));
+
+    // A connection checked out before the breaker opened only resets a closed breaker.
+    conn.query("SELECT 1").select().await?;
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Open));
+    Ok(())
+}
+
+#[tokio::test]
+async fn helper_statements_reset_the_failure_streak() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test23_helpers")
+        .await?
+        .with_circuit_breaker(3, Duration::from_secs(30));
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER)").await?;
+
+    cap.record_result(&connection_failure());
+    cap.record_result(&connection_failure());
+    conn.bulk_insert("t", &["id"], &[vec![RowValues::Int(1)]])
+        .await?;
+    cap.record_result(&connection_failure());
+    cap.record_result(&connection_failure());
+    conn.execute_dml_atomic(&[("INSERT INTO t (id) VALUES (2)", Vec::<RowValues>::new())])
+        .await?;
+    cap.record_result(&connection_failure());
+    cap.record_result(&connection_failure());
+    assert_eq!(cap.circuit_state(), Some(CircuitState::Closed));
+    Ok(())
+}
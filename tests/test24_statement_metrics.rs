@@ -0,0 +1,55 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::metrics;
+use sql_middleware::middleware::{ConfigAndPool, RowValues};
+
+#[tokio::test]
+async fn statements_are_grouped_by_fingerprint() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test24_metrics").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+        .await?;
+
+    // Not recorded while disabled.
+    conn.query("SELECT 1").select().await?;
+
+    metrics::enable();
+    for id in 1..=3 {
+        conn.query("INSERT INTO t (id, name) VALUES (?1, ?2)")
+            .params(&[RowValues::Int(id), RowValues::Text(format!("n{id}").into())])
+            .dml()
+            .await?;
+    }
+    conn.query("select *   from t where id in (1, 2)")
+        .select()
+        .await?;
+    conn.query("SELECT * FROM t WHERE id IN (3)")
+        .select()
+        .await?;
+    assert!(conn.query("SELECT * FROM missing").select().await.is_err());
+    metrics::disable();
+
+    let stats = metrics::snapshot();
+    let find = |fingerprint: &str| {
+        stats
+            .iter()
+            .find(|s| s.fingerprint == fingerprint)
+            .unwrap_or_else(|| panic!("no stats for {fingerprint}: {stats:#?}"))
+    };
+
+    let insert = find("insert into t (id, name) values (?)");
+    assert_eq!((insert.calls, insert.rows, insert.errors), (3, 3, 0));
+    assert!(insert.p95 <= insert.max_time && insert.max_time <= insert.total_time);
+
+    let select = find("select * from t where id in (?)");
+    assert_eq!((select.calls, select.rows, select.errors), (2, 3, 0));
+
+    let missing = find("select * from missing");
+    assert_eq!((missing.calls, missing.errors), (1, 1));
+
+    assert!(stats.iter().all(|s| s.fingerprint != "select ?"));
+
+    metrics::reset();
+    assert!(metrics::snapshot().is_empty());
+    Ok(())
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::metrics;
+use sql_middleware::middleware::{ConfigAndPool, RowValues};
+
+#[tokio::test]
+async fn slow_selects_capture_plans() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test25_slow").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT);
+         INSERT INTO t VALUES (1, 'a'), (2, 'b');",
+    )
+    .await?;
+
+    // A zero threshold makes every SELECT slow; the interval allows only the first explain.
+    metrics::explain_slow_queries(Duration::ZERO, Duration::from_secs(3600));
+    conn.query("SELECT name FROM t WHERE id = ?1")
+        .params(&[RowValues::Int(1)])
+        .select()
+        .await?;
+    conn.query("SELECT * FROM t").select().await?;
+    conn.query("UPDATE t SET name = 'c' WHERE id = 2")
+        .dml()
+        .await?;
+    metrics::disable_slow_query_capture();
+    conn.query("SELECT count(*) FROM t").select().await?;
+
+    let slow = metrics::recent_slow_queries();
+    assert_eq!(slow.len(), 2, "{slow:#?}");
+    assert_eq!(slow[0].fingerprint, "select name from t where id = ?");
+    let plan = slow[0]
+        .plan
+        .as_deref()
+        .expect("first slow query is explained");
+    assert!(
+        plan.contains("SEARCH t USING INTEGER PRIMARY KEY"),
+        "{plan}"
+    );
+    assert_eq!(slow[1].sql, "SELECT * FROM t");
+    assert_eq!(slow[1].plan, None, "rate-limited");
+    Ok(())
+}
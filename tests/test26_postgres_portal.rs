@@ -0,0 +1,68 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+
+use sql_middleware::postgres::begin_transaction;
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+#[tokio::test]
+async fn portal_fetches_in_chunks_with_cached_statements() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut conn = cap.get_connection().await?;
+    let MiddlewarePoolConnection::Postgres { client, .. } = &mut conn else {
+        panic!("Expected Postgres connection");
+    };
+
+    let tx = begin_transaction(client).await?;
+    tx.execute_batch("CREATE TEMP TABLE portal_rows (id BIGINT) ON COMMIT DROP")
+        .await?;
+    let insert = tx
+        .prepare_cached("INSERT INTO portal_rows (id) VALUES ($1)")
+        .await?;
+    for id in 1..=5 {
+        tx.execute_prepared(&insert, &[RowValues::Int(id)]).await?;
+    }
+    tx.prepare_cached("INSERT INTO portal_rows (id) VALUES ($1)")
+        .await?;
+    assert_eq!(tx.cached_statement_count(), 1);
+
+    let select = tx
+        .prepare_cached("SELECT id FROM portal_rows WHERE id >= $1 ORDER BY id")
+        .await?;
+    let mut portal = tx.bind(&select, &[RowValues::Int(2)]).await?;
+
+    let mut chunks = Vec::new();
+    while !portal.is_exhausted() {
+        let chunk = tx.fetch(&mut portal, 2).await?;
+        chunks.push(
+            chunk
+                .results
+                .iter()
+                .map(|row| *row.get("id").and_then(RowValues::as_int).unwrap())
+                .collect::<Vec<_>>(),
+        );
+    }
+    assert_eq!(chunks, vec![vec![2, 3], vec![4, 5], vec![]]);
+
+    let after = tx.fetch(&mut portal, 2).await?;
+    assert!(after.results.is_empty());
+    assert_eq!(
+        after.get_column_names().map(|names| names.as_slice()),
+        Some(&["id".to_string()][..])
+    );
+    assert!(tx.fetch(&mut portal, 0).await.is_err());
+
+    tx.rollback().await?;
+    Ok(())
+}
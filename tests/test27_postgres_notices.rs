@@ -0,0 +1,56 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sql_middleware::postgres::{DbNotice, PostgresOptionsBuilder};
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+#[tokio::test]
+async fn raise_notice_reaches_handler() -> Result<(), Box<dyn std::error::Error>> {
+    let seen: Arc<Mutex<Vec<DbNotice>>> = Arc::default();
+    let sink = Arc::clone(&seen);
+    let cap = PostgresOptionsBuilder::new(pg_config())
+        .notice_handler(Arc::new(move |notice: &DbNotice| {
+            sink.lock().unwrap().push(notice.clone());
+        }))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+
+    conn.execute_batch(
+        "DO $$ BEGIN
+            RAISE NOTICE 'step % of %', 1, 2 USING HINT = 'keep going';
+            RAISE WARNING 'almost done';
+        END $$",
+    )
+    .await?;
+
+    // Notices arrive on the connection task; give it a moment after the batch completes.
+    for _ in 0..50 {
+        if seen.lock().unwrap().len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0].severity, "NOTICE");
+    assert_eq!(seen[0].code, "00000");
+    assert_eq!(seen[0].message, "step 1 of 2");
+    assert_eq!(seen[0].hint.as_deref(), Some("keep going"));
+    assert_eq!(seen[1].severity, "WARNING");
+    assert_eq!(seen[1].message, "almost done");
+    Ok(())
+}
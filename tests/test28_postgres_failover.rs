@@ -0,0 +1,67 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+use std::time::Duration;
+
+use sql_middleware::prelude::*;
+use tokio_postgres::config::{Host, TargetSessionAttrs};
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+#[test]
+fn failover_hosts_are_tried_in_order() {
+    let opts = ConfigAndPool::postgres_builder(pg_config())
+        .failover_host("10.3.0.202", 5433)
+        .read_write(true)
+        .connect_timeout(Duration::from_secs(2))
+        .finish();
+    let cfg = opts.to_tokio_config();
+
+    assert_eq!(
+        cfg.get_hosts(),
+        [
+            Host::Tcp("10.3.0.201".to_string()),
+            Host::Tcp("10.3.0.202".to_string()),
+        ]
+    );
+    assert_eq!(cfg.get_ports(), [5432, 5433]);
+    assert_eq!(
+        cfg.get_target_session_attrs(),
+        TargetSessionAttrs::ReadWrite
+    );
+    assert_eq!(cfg.get_connect_timeout(), Some(&Duration::from_secs(2)));
+}
+
+#[tokio::test]
+async fn unreachable_first_host_fails_over() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cfg = pg_config();
+    let (live_host, live_port) = (cfg.host.take().unwrap(), cfg.port.take().unwrap());
+    // Nothing listens on port 1, so every connection falls through to the live server.
+    cfg.host = Some("127.0.0.1".to_string());
+    cfg.port = Some(1);
+
+    let cap = ConfigAndPool::postgres_builder(cfg)
+        .failover_host(live_host, live_port)
+        .read_write(true)
+        .connect_timeout(Duration::from_secs(2))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    let rs = conn
+        .query("SELECT current_setting('transaction_read_only') AS ro")
+        .select()
+        .await?;
+    assert_eq!(
+        rs.results[0].get("ro").and_then(RowValues::as_text),
+        Some("off")
+    );
+    Ok(())
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "cockroach")]
+
+use std::env;
+
+use sql_middleware::cockroach::{is_retry_error, run_transaction};
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+const FAIL_SERIALIZABLE: &str =
+    "DO $$ BEGIN RAISE EXCEPTION 'restart transaction' USING ERRCODE = '40001'; END $$";
+
+#[tokio::test]
+async fn serialization_failures_retry_from_savepoint() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS crdb_retry; CREATE TABLE crdb_retry (attempt INT NOT NULL)",
+    )
+    .await?;
+    let MiddlewarePoolConnection::Postgres { client, .. } = &mut conn else {
+        panic!("Expected Postgres connection");
+    };
+
+    // Fails twice after writing, then succeeds: only the last attempt's row survives.
+    let mut attempts = 0;
+    let committed = run_transaction(client, 3, async |tx| {
+        attempts += 1;
+        tx.execute_dml(
+            "INSERT INTO crdb_retry (attempt) VALUES ($1)",
+            &[RowValues::Int(attempts)],
+        )
+        .await?;
+        if attempts < 3 {
+            tx.execute_batch(FAIL_SERIALIZABLE).await?;
+        }
+        Ok(attempts)
+    })
+    .await?;
+    assert_eq!(committed, 3);
+
+    // Retries exhausted: the serialization failure is returned and nothing commits.
+    let err = run_transaction(client, 1, async |tx| {
+        tx.execute_dml("INSERT INTO crdb_retry (attempt) VALUES (99)", &[])
+            .await?;
+        tx.execute_batch(FAIL_SERIALIZABLE).await
+    })
+    .await
+    .unwrap_err();
+    assert!(is_retry_error(&err));
+
+    // Other errors are not retried.
+    let mut calls = 0;
+    let err = run_transaction(client, 5, async |tx| {
+        calls += 1;
+        tx.execute_batch("SELECT * FROM crdb_missing_table").await
+    })
+    .await
+    .unwrap_err();
+    assert!(!is_retry_error(&err));
+    assert_eq!(calls, 1);
+
+    let rows = conn
+        .query("SELECT attempt FROM crdb_retry ORDER BY attempt")
+        .select()
+        .await?;
+    let attempts: Vec<_> = rows
+        .results
+        .iter()
+        .map(|row| row.get("attempt").and_then(RowValues::as_int).copied())
+        .collect();
+    assert_eq!(attempts, [Some(3)]);
+    conn.execute_batch("DROP TABLE crdb_retry").await?;
+    Ok(())
+}
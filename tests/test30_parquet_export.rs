@@ -0,0 +1,94 @@
+#![cfg(all(feature = "parquet", feature = "sqlite"))]
+
+use std::fs::File;
+
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sql_middleware::export::{BATCH_ROWS, ParquetColumnType, SchemaHints, select_to_parquet};
+use sql_middleware::prelude::*;
+
+fn read_back(path: &std::path::Path) -> Result<(Vec<DataType>, usize), Box<dyn std::error::Error>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let types = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.data_type().clone())
+        .collect();
+    let mut rows = 0;
+    for batch in reader.build()? {
+        rows += batch?.num_rows();
+    }
+    Ok((types, rows))
+}
+
+#[tokio::test]
+async fn select_streams_rows_to_parquet() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let db_path = dir.path().join("events.db");
+    let cap =
+        ConfigAndPool::new_sqlite(SqliteOptions::new(db_path.to_string_lossy().into())).await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE events (id INTEGER, score REAL, label TEXT, payload BLOB, note TEXT);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 10000)
+         INSERT INTO events SELECT i, i * 0.5, 'e' || i, x'00ff', NULL FROM n;",
+    )
+    .await?;
+
+    let path = dir.path().join("events.parquet");
+    let hints = SchemaHints::new().column("id", ParquetColumnType::TimestampMicros);
+    let written = select_to_parquet(
+        &mut conn,
+        "SELECT id, score, label, payload, note FROM events WHERE id > ?1",
+        &[RowValues::Int(0)],
+        &path,
+        &SchemaHints::new(),
+    )
+    .await?;
+    assert!(written > BATCH_ROWS);
+    assert_eq!(written, 10_000);
+
+    let (types, rows) = read_back(&path)?;
+    assert_eq!(rows, 10_000);
+    assert_eq!(
+        types,
+        [
+            DataType::Int64,
+            DataType::Float64,
+            DataType::Utf8,
+            DataType::Binary,
+            DataType::Utf8,
+        ]
+    );
+
+    // A hint the data does not fit is an error, not a silent cast.
+    let err = select_to_parquet(&mut conn, "SELECT id FROM events", &[], &path, &hints)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(_)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn result_set_writes_with_hints() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let db_path = dir.path().join("one.db");
+    let cap =
+        ConfigAndPool::new_sqlite(SqliteOptions::new(db_path.to_string_lossy().into())).await?;
+    let mut conn = cap.get_connection().await?;
+    let rs = conn
+        .query("SELECT 1 AS amount, NULL AS missing, 1 = 1 AS flag")
+        .select()
+        .await?;
+
+    let path = dir.path().join("one.parquet");
+    let hints = SchemaHints::new().column("amount", ParquetColumnType::Float64);
+    rs.to_parquet(File::create(&path)?, &hints)?;
+
+    let (types, rows) = read_back(&path)?;
+    assert_eq!(rows, 1);
+    // SQLite reports booleans as integers; missing types default to strings.
+    assert_eq!(types, [DataType::Float64, DataType::Utf8, DataType::Int64]);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::error::ErrorKind;
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn sqlite_errors_are_classified_by_code() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite(SqliteOptions::new(
+        "file:error_kind?mode=memory&cache=shared".into(),
+    ))
+    .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE kinds (id INTEGER PRIMARY KEY, name TEXT UNIQUE)")
+        .await?;
+    conn.execute_batch("INSERT INTO kinds (id, name) VALUES (1, 'a')")
+        .await?;
+
+    let err = conn
+        .execute_batch("INSERT INTO kinds (id, name) VALUES (1, 'b')")
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UniqueViolation);
+
+    let err = conn
+        .execute_batch("INSERT INTO kinds (id, name) VALUES (2, 'a')")
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UniqueViolation);
+
+    let err = conn
+        .query("SELEC id FROM kinds")
+        .select()
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Syntax);
+
+    let err = conn
+        .query("SELECT nope FROM kinds")
+        .select()
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+const TENANT: &str = "SELECT current_setting('app.tenant_id', true) AS tenant";
+
+fn tenant(rows: &ResultSet) -> Option<String> {
+    rows.results
+        .first()
+        .and_then(|row| row.get("tenant"))
+        .and_then(RowValues::as_text)
+        .map(str::to_string)
+}
+
+#[tokio::test]
+async fn session_context_is_scoped_to_the_transaction() -> Result<(), Box<dyn std::error::Error>> {
+    // One connection, so whatever the scope leaves behind is visible afterwards.
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut conn = cap.get_connection().await?;
+
+    let seen = conn
+        .with_session_context([("app.tenant_id", "42")], async |tx| {
+            tx.query(TENANT, &[]).await
+        })
+        .await?;
+    assert_eq!(tenant(&seen).as_deref(), Some("42"));
+    let after = conn.query(TENANT).select().await?;
+    assert!(tenant(&after).is_none_or(|value| value.is_empty()));
+
+    // An error from the closure rolls back and still clears the setting.
+    let err = conn
+        .with_session_context([("app.tenant_id", "7")], async |tx| {
+            tx.execute_batch("SELECT missing_column FROM pg_class")
+                .await
+        })
+        .await
+        .unwrap_err();
+    assert!(!matches!(err, SqlMiddlewareDbError::Unimplemented(_)));
+    let after = conn.query(TENANT).select().await?;
+    assert!(tenant(&after).is_none_or(|value| value.is_empty()));
+
+    // Values are bound, not spliced into SQL.
+    let quoted = "1'; RESET ALL; --";
+    let seen = conn
+        .with_session_context([("app.tenant_id", quoted)], async |tx| {
+            tx.query(TENANT, &[]).await
+        })
+        .await?;
+    assert_eq!(tenant(&seen).as_deref(), Some(quoted));
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_has_no_session_context() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite(SqliteOptions::new(
+        "file:session_context?mode=memory&cache=shared".into(),
+    ))
+    .await?;
+    let mut conn = cap.get_connection().await?;
+    let err = conn
+        .with_session_context([("app.tenant_id", "42")], async |_tx| Ok(()))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SqlMiddlewareDbError::Unimplemented(_)));
+    Ok(())
+}
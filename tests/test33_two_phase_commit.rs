@@ -0,0 +1,115 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+
+use sql_middleware::postgres::begin_transaction;
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+async fn count(cap: &ConfigAndPool) -> Result<i64, SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    let rows = conn
+        .query("SELECT count(*) AS n FROM test33_two_phase")
+        .select()
+        .await?;
+    Ok(*rows.results[0]
+        .get("n")
+        .and_then(RowValues::as_int)
+        .expect("count"))
+}
+
+async fn prepare_insert(
+    cap: &ConfigAndPool,
+    gid: &str,
+    id: i64,
+) -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    let MiddlewarePoolConnection::Postgres { client, .. } = &mut conn else {
+        unreachable!("postgres pool");
+    };
+    let tx = begin_transaction(client).await?;
+    tx.execute_dml(
+        "INSERT INTO test33_two_phase (id) VALUES ($1)",
+        &[RowValues::Int(id)],
+    )
+    .await?;
+    tx.prepare_commit(gid).await?;
+    // The session is usable again straight away.
+    conn.query("SELECT 1").select().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn prepared_transactions_commit_and_roll_back_from_another_connection()
+-> Result<(), Box<dyn std::error::Error>> {
+    // Requires max_prepared_transactions > 0 on the server.
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    assert!(cap.db_type.supports_prepared_transactions());
+    for gid in ["test33-commit", "test33-rollback"] {
+        let _ = cap.rollback_prepared(gid).await;
+    }
+    {
+        let mut conn = cap.get_connection().await?;
+        conn.execute_batch(
+            "DROP TABLE IF EXISTS test33_two_phase; CREATE TABLE test33_two_phase (id BIGINT PRIMARY KEY);",
+        )
+        .await?;
+    }
+
+    prepare_insert(&cap, "test33-commit", 1).await?;
+    prepare_insert(&cap, "test33-rollback", 2).await?;
+    let pending = cap.prepared_transactions().await?;
+    assert!(pending.iter().any(|gid| gid == "test33-commit"));
+    assert!(pending.iter().any(|gid| gid == "test33-rollback"));
+    assert_eq!(count(&cap).await?, 0, "prepared work is invisible");
+
+    cap.commit_prepared("test33-commit").await?;
+    cap.rollback_prepared("test33-rollback").await?;
+    assert_eq!(count(&cap).await?, 1);
+    assert!(cap.commit_prepared("test33-commit").await.is_err());
+    assert!(
+        !cap.prepared_transactions()
+            .await?
+            .iter()
+            .any(|gid| gid.starts_with("test33-"))
+    );
+
+    // Quotes in the identifier are escaped, not executed.
+    prepare_insert(&cap, "test33-'quoted", 3).await?;
+    cap.rollback_prepared("test33-'quoted").await?;
+    assert!(matches!(
+        cap.commit_prepared("").await,
+        Err(SqlMiddlewareDbError::ParameterError(_))
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_has_no_prepared_transactions() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite(SqliteOptions::new(
+        "file:two_phase?mode=memory&cache=shared".into(),
+    ))
+    .await?;
+    assert!(!cap.db_type.supports_prepared_transactions());
+    for result in [
+        cap.commit_prepared("gid").await,
+        cap.rollback_prepared("gid").await,
+        cap.prepared_transactions().await.map(|_| ()),
+    ] {
+        assert!(matches!(
+            result,
+            Err(SqlMiddlewareDbError::Unimplemented(_))
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::middleware::{ConfigAndPool, MiddlewarePoolConnection, RowValues};
+use sql_middleware::saga::Saga;
+use sql_middleware::{ResultSet, SqlMiddlewareDbError};
+
+async fn pool(name: &str) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory(name).await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, note TEXT);")
+        .await?;
+    Ok(cap)
+}
+
+async fn ids(cap: &ConfigAndPool) -> Result<Vec<i64>, SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    let rows: ResultSet = conn
+        .query("SELECT id FROM items ORDER BY id")
+        .select()
+        .await?;
+    Ok(rows
+        .results
+        .iter()
+        .filter_map(|row| row.get("id").and_then(RowValues::as_int).copied())
+        .collect())
+}
+
+async fn insert(conn: &mut MiddlewarePoolConnection, id: i64) -> Result<i64, SqlMiddlewareDbError> {
+    conn.query("INSERT INTO items (id) VALUES (?1)")
+        .params(&[RowValues::Int(id)])
+        .dml()
+        .await?;
+    Ok(id)
+}
+
+async fn delete(mut conn: MiddlewarePoolConnection, id: i64) -> Result<(), SqlMiddlewareDbError> {
+    conn.query("DELETE FROM items WHERE id = ?1")
+        .params(&[RowValues::Int(id)])
+        .dml()
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_step_compensates_earlier_steps_in_reverse() -> Result<(), Box<dyn std::error::Error>>
+{
+    let orders = pool("test34_orders").await?;
+    let billing = pool("test34_billing").await?;
+
+    let mut saga = Saga::new();
+    saga.step("order", &orders, async |conn| insert(conn, 1).await, delete)
+        .await?;
+    saga.step(
+        "charge",
+        &billing,
+        async |conn| insert(conn, 10).await,
+        delete,
+    )
+    .await?;
+    // Compensations run newest first, so row 1 is still there when this one runs.
+    saga.step(
+        "audit",
+        &orders,
+        async |conn| insert(conn, 2).await,
+        |mut conn, _| async move {
+            let touched = conn
+                .query("UPDATE items SET note = 'undone' WHERE id = 1")
+                .dml()
+                .await?;
+            if touched != 1 {
+                return Err(SqlMiddlewareDbError::Other(
+                    "order compensated too early".into(),
+                ));
+            }
+            delete(conn, 2).await
+        },
+    )
+    .await?;
+    assert_eq!(
+        saga.completed_steps().collect::<Vec<_>>(),
+        ["order", "charge", "audit"]
+    );
+
+    // Duplicate key: the step fails and everything before it is undone.
+    let err = saga
+        .step(
+            "ship",
+            &billing,
+            async |conn| insert(conn, 10).await,
+            delete,
+        )
+        .await
+        .unwrap_err();
+    let SqlMiddlewareDbError::SagaAborted {
+        step,
+        compensation_failures,
+        ..
+    } = &err
+    else {
+        panic!("expected SagaAborted, got {err:?}");
+    };
+    assert_eq!(step, "ship");
+    assert!(compensation_failures.is_empty());
+    assert!(ids(&orders).await?.is_empty());
+    assert!(ids(&billing).await?.is_empty());
+
+    // An aborted saga refuses further steps.
+    let err = saga
+        .step("late", &orders, async |conn| insert(conn, 3).await, delete)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(_)));
+    assert!(ids(&orders).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn abort_reports_failed_compensations() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = pool("test34_abort").await?;
+
+    let mut saga = Saga::new();
+    saga.step("first", &cap, async |conn| insert(conn, 1).await, delete)
+        .await?;
+    saga.step(
+        "second",
+        &cap,
+        async |conn| insert(conn, 2).await,
+        |mut conn, _| async move { conn.execute_batch("DELETE FROM missing_table").await },
+    )
+    .await?;
+
+    let err = saga
+        .abort(SqlMiddlewareDbError::Other("credit check failed".into()))
+        .await;
+    let SqlMiddlewareDbError::SagaAborted {
+        step,
+        source,
+        compensation_failures,
+    } = err
+    else {
+        panic!("expected SagaAborted");
+    };
+    assert_eq!(step, "abort");
+    assert!(matches!(*source, SqlMiddlewareDbError::Other(_)));
+    let failed: Vec<_> = compensation_failures
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(failed, ["second"]);
+    // The failing compensation doesn't stop the ones after it.
+    assert_eq!(ids(&cap).await?, [2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn completed_saga_keeps_its_work() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = pool("test34_complete").await?;
+    let mut saga = Saga::new();
+    let id = saga
+        .step("only", &cap, async |conn| insert(conn, 5).await, delete)
+        .await?;
+    saga.complete();
+    assert_eq!(ids(&cap).await?, [id]);
+    Ok(())
+}
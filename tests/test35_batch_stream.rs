@@ -0,0 +1,81 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::middleware::{ConfigAndPool, RowValues};
+
+const SCRIPT: &str = "
+CREATE TABLE items (id INTEGER PRIMARY KEY, note TEXT);
+CREATE TABLE item_log (id INTEGER);
+-- The trigger body's semicolons don't split the script.
+CREATE TRIGGER log_items AFTER INSERT ON items BEGIN
+    INSERT INTO item_log VALUES (NEW.id);
+END;
+INSERT INTO items (id, note) VALUES (1, 'a;b'), (2, 'c'), (3, 'd');
+UPDATE items SET note = 'z' WHERE id > 1;
+SELECT count(*) FROM items;
+";
+
+#[tokio::test]
+async fn reports_each_statement_as_it_completes() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test35_progress").await?;
+    let mut conn = cap.get_connection().await?;
+
+    let mut stream = conn.execute_batch_stream(SCRIPT);
+    assert_eq!(stream.total(), 6);
+    let mut seen = Vec::new();
+    while let Some(step) = stream.next().await {
+        let step = step?;
+        assert_eq!(step.total, 6);
+        assert!(step.since_start >= step.elapsed);
+        seen.push((step.index, step.rows_affected));
+    }
+    assert!(stream.next().await.is_none());
+    assert_eq!(seen, [(0, 0), (1, 0), (2, 0), (3, 6), (4, 2), (5, 0)]);
+
+    let rows = conn
+        .query("SELECT count(*) AS n FROM item_log")
+        .select()
+        .await?;
+    assert_eq!(
+        rows.results[0].get("n").and_then(RowValues::as_int),
+        Some(&3)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn stops_at_the_first_error_and_rolls_back_the_script_transaction()
+-> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test35_error").await?;
+    let mut conn = cap.get_connection().await?;
+
+    let script = "CREATE TABLE kept (id INTEGER);
+        BEGIN;
+        INSERT INTO kept VALUES (1);
+        INSERT INTO missing VALUES (1);
+        INSERT INTO kept VALUES (2);
+        COMMIT;";
+    let mut stream = conn.execute_batch_stream(script);
+    let mut completed = 0;
+    let err = loop {
+        match stream.next().await.expect("stream ends with the error") {
+            Ok(_) => completed += 1,
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(completed, 3);
+    assert!(err.to_string().contains("missing"));
+    assert!(stream.next().await.is_none());
+
+    // The CREATE before BEGIN stays; the open transaction was rolled back, so the connection is
+    // usable and the insert inside it is gone.
+    let rows = conn
+        .query("SELECT count(*) AS n FROM kept")
+        .select()
+        .await?;
+    assert_eq!(
+        rows.results[0].get("n").and_then(RowValues::as_int),
+        Some(&0)
+    );
+    conn.execute_batch("INSERT INTO kept VALUES (3)").await?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::middleware::{ConfigAndPool, RowValues, SqlMiddlewareDbError};
+use sql_middleware::pool::LeasedConnection;
+
+async fn staged(lease: &LeasedConnection) -> Result<i64, SqlMiddlewareDbError> {
+    let mut conn = lease.lock().await?;
+    let rows = conn
+        .query("SELECT count(*) AS n FROM staging")
+        .select()
+        .await?;
+    Ok(*rows.results[0]
+        .get("n")
+        .and_then(RowValues::as_int)
+        .expect("count"))
+}
+
+#[tokio::test]
+async fn temp_tables_survive_across_call_sites_and_tasks() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_sqlite_memory("test36_affinity").await?;
+    let lease = cap.lease_connection().await?;
+    lease
+        .lock()
+        .await?
+        .execute_batch("CREATE TEMP TABLE staging (id INTEGER); INSERT INTO staging VALUES (1);")
+        .await?;
+
+    let worker = lease.clone();
+    tokio::spawn(async move {
+        worker
+            .lock()
+            .await?
+            .execute_batch("INSERT INTO staging VALUES (2)")
+            .await
+    })
+    .await??;
+    assert_eq!(staged(&lease).await?, 2);
+
+    // Any other checkout is a different connection without the temp table.
+    let mut other = cap.get_connection().await?;
+    assert!(other.query("SELECT * FROM staging").select().await.is_err());
+    drop(other);
+
+    let holder = lease.clone();
+    lease.release().await;
+    assert!(holder.is_released().await);
+    assert!(matches!(
+        holder.lock().await,
+        Err(SqlMiddlewareDbError::ConnectionError(_))
+    ));
+    holder.release().await;
+    Ok(())
+}
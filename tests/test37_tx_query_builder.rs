@@ -0,0 +1,68 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::begin_transaction;
+
+async fn count(conn: &mut MiddlewarePoolConnection) -> Result<i64, SqlMiddlewareDbError> {
+    let rows = conn
+        .query("SELECT count(*) AS n FROM items")
+        .select()
+        .await?;
+    Ok(*rows.results[0]
+        .get("n")
+        .and_then(RowValues::as_int)
+        .expect("count"))
+}
+
+#[tokio::test]
+async fn builder_runs_inside_sqlite_transaction() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite(
+        SqliteOptions::new("file:test37_commit?mode=memory&cache=shared".into())
+            .with_translation(true),
+    )
+    .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await?;
+
+    let mut tx = begin_transaction(&mut conn).await?;
+    // `$1` placeholders are translated because the pool enables translation.
+    let inserted = tx
+        .query_builder("INSERT INTO items (id, name) VALUES ($1, $2)")
+        .params(&[RowValues::Int(1), RowValues::Text("one".into())])
+        .dml()
+        .await?;
+    assert_eq!(inserted, 1);
+    let rows = tx
+        .query_builder("SELECT name FROM items WHERE id = $1")
+        .params(&[RowValues::Int(1)])
+        .select()
+        .await?;
+    assert_eq!(
+        rows.results[0].get("name").and_then(RowValues::as_text),
+        Some("one")
+    );
+    tx.commit().await?;
+
+    assert_eq!(count(&mut conn).await?, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn builder_work_is_discarded_on_rollback() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test37_rollback").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await?;
+
+    let mut tx = begin_transaction(&mut conn).await?;
+    tx.query_builder("INSERT INTO items (id, name) VALUES ($1, $2)")
+        .params(&[RowValues::Int(1), RowValues::Text("one".into())])
+        .translation(TranslationMode::ForceOn)
+        .dml()
+        .await?;
+    tx.rollback().await?;
+
+    assert_eq!(count(&mut conn).await?, 0);
+    Ok(())
+}
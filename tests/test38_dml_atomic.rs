@@ -0,0 +1,86 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+async fn setup(name: &str) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory(name).await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE parents (id INTEGER PRIMARY KEY);
+         CREATE TABLE children (parent_id INTEGER NOT NULL REFERENCES parents (id), name TEXT NOT NULL);",
+    )
+    .await?;
+    Ok(cap)
+}
+
+async fn count(
+    conn: &mut MiddlewarePoolConnection,
+    table: &str,
+) -> Result<i64, SqlMiddlewareDbError> {
+    let sql = format!("SELECT count(*) AS n FROM {table}");
+    let rows = conn.query(&sql).select().await?;
+    Ok(*rows.results[0]
+        .get("n")
+        .and_then(RowValues::as_int)
+        .expect("count"))
+}
+
+#[tokio::test]
+async fn parent_and_children_commit_together() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = setup("test38_commit").await?;
+    let mut conn = cap.get_connection().await?;
+
+    let counts = conn
+        .execute_dml_atomic(&[
+            (
+                "INSERT INTO parents (id) VALUES (?1)",
+                vec![RowValues::Int(1)],
+            ),
+            (
+                "INSERT INTO children (parent_id, name) VALUES (?1, ?2), (?1, ?3)",
+                vec![
+                    RowValues::Int(1),
+                    RowValues::Text("a".into()),
+                    RowValues::Text("b".into()),
+                ],
+            ),
+        ])
+        .await?;
+    assert_eq!(counts, vec![1, 2]);
+    assert_eq!(count(&mut conn, "children").await?, 2);
+
+    let none: [(&str, Vec<RowValues>); 0] = [];
+    assert!(conn.execute_dml_atomic(&none).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn failing_statement_rolls_back_earlier_ones() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = setup("test38_rollback").await?;
+    let mut conn = cap.get_connection().await?;
+
+    let err = conn
+        .execute_dml_atomic(&[
+            (
+                "INSERT INTO parents (id) VALUES (?1)",
+                vec![RowValues::Int(1)],
+            ),
+            (
+                "INSERT INTO children (parent_id, name) VALUES (?1, ?2)",
+                vec![RowValues::Int(1), RowValues::Null],
+            ),
+        ])
+        .await
+        .expect_err("NOT NULL violation");
+    assert!(err.to_string().contains("NOT NULL"), "{err}");
+    assert_eq!(count(&mut conn, "parents").await?, 0);
+
+    // The connection is usable again after the rollback.
+    conn.execute_dml_atomic(&[(
+        "INSERT INTO parents (id) VALUES (?1)",
+        vec![RowValues::Int(2)],
+    )])
+    .await?;
+    assert_eq!(count(&mut conn, "parents").await?, 1);
+    Ok(())
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::conversion::convert_sql_params;
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::params::Params as SqliteParams;
+
+#[tokio::test]
+async fn strict_queries_reject_coerced_params() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test39_strict").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE flags (id INTEGER PRIMARY KEY, enabled INTEGER)")
+        .await?;
+
+    let err = conn
+        .query("INSERT INTO flags (id, enabled) VALUES (?1, ?2)")
+        .params(&[RowValues::Int(1), RowValues::Bool(true)])
+        .strict()
+        .dml()
+        .await
+        .expect_err("Bool is stored as INTEGER on SQLite");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::ParameterError(msg) if msg.contains("parameter 2 (Bool)")),
+        "{err}"
+    );
+    let rows = conn.query("SELECT id FROM flags").select().await?;
+    assert!(rows.results.is_empty());
+
+    // Native types pass, and the same Bool is accepted outside strict mode.
+    conn.query("INSERT INTO flags (id, enabled) VALUES (?1, ?2)")
+        .params(&[RowValues::Int(1), RowValues::Int(1)])
+        .strict()
+        .dml()
+        .await?;
+    conn.query("INSERT INTO flags (id, enabled) VALUES (?1, ?2)")
+        .params(&[RowValues::Int(2), RowValues::Bool(false)])
+        .dml()
+        .await?;
+
+    let err = conn
+        .query("SELECT id FROM flags WHERE created < ?1")
+        .params(&[RowValues::Timestamp(chrono::NaiveDateTime::default())])
+        .strict()
+        .select()
+        .await
+        .expect_err("Timestamp is stored as TEXT on SQLite");
+    assert!(matches!(err, SqlMiddlewareDbError::ParameterError(_)));
+    Ok(())
+}
+
+#[test]
+fn strict_conversion_mode_rejects_coercions() {
+    let params = [
+        RowValues::Int(1),
+        RowValues::JSON(serde_json::json!({"a": 1})),
+    ];
+    assert!(convert_sql_params::<SqliteParams>(&params, ConversionMode::Execute).is_ok());
+    assert!(convert_sql_params::<SqliteParams>(&params, ConversionMode::Strict).is_err());
+
+    assert_eq!(
+        DatabaseType::Sqlite.param_coercion(&RowValues::Bool(true)),
+        Some("INTEGER")
+    );
+    assert_eq!(
+        DatabaseType::Sqlite.param_coercion(&RowValues::Text("x".into())),
+        None
+    );
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::build_result_set;
+
+#[tokio::test]
+async fn row_values_bind_directly_in_rusqlite() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test40_borrowed").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE docs (id INTEGER, flag INTEGER, body TEXT, data BLOB)")
+        .await?;
+
+    let body = "x".repeat(1 << 20);
+    let data = vec![7u8; 1 << 20];
+    let params = vec![
+        RowValues::Int(1),
+        RowValues::Bool(true),
+        RowValues::Text(body.as_str().into()),
+        RowValues::Blob(data.as_slice().into()),
+    ];
+    conn.with_blocking_sqlite(move |raw| {
+        let mut stmt = raw.prepare("INSERT INTO docs VALUES (?1, ?2, ?3, ?4)")?;
+        stmt.execute(rusqlite::params_from_iter(&params))?;
+        Ok::<(), SqlMiddlewareDbError>(())
+    })
+    .await?;
+
+    let rows = conn
+        .query("SELECT flag, length(body) AS body_len, data FROM docs WHERE id = ?1")
+        .params(&[RowValues::Int(1)])
+        .select()
+        .await?;
+    let row = &rows.results[0];
+    assert_eq!(row.get("flag").and_then(RowValues::as_int), Some(&1));
+    assert_eq!(
+        row.get("body_len").and_then(RowValues::as_int),
+        Some(&i64::try_from(body.len())?)
+    );
+    assert_eq!(
+        row.get("data").and_then(RowValues::as_blob),
+        Some(data.as_slice())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn sqlite_connection_takes_borrowed_or_owned_params() -> Result<(), Box<dyn std::error::Error>>
+{
+    use rusqlite::types::Value;
+
+    let cap = ConfigAndPool::new_sqlite_memory("test40_owned").await?;
+    let (mut sqlite, _) = cap.get_connection().await?.into_sqlite()?;
+    sqlite
+        .execute_batch("CREATE TABLE notes (id INTEGER, body TEXT)")
+        .await?;
+
+    let params = vec![Value::Integer(1), Value::Text("borrowed".into())];
+    let sql = "INSERT INTO notes VALUES (?1, ?2)";
+    assert_eq!(sqlite.execute_dml(sql, &params).await?, 1);
+    let params = vec![Value::Integer(2), Value::Text("owned".into())];
+    assert_eq!(sqlite.execute_dml_owned(sql, params).await?, 1);
+
+    sqlite.begin().await?;
+    let params = vec![Value::Integer(3), Value::Text("borrowed in tx".into())];
+    assert_eq!(sqlite.execute_dml_in_tx(sql, &params).await?, 1);
+    let params = vec![Value::Integer(4), Value::Text("owned in tx".into())];
+    assert_eq!(sqlite.execute_dml_in_tx_owned(sql, params).await?, 1);
+    let count = "SELECT count(*) AS n FROM notes WHERE id > ?1";
+    let params = vec![Value::Integer(0)];
+    let borrowed = sqlite
+        .execute_select_in_tx(count, &params, build_result_set)
+        .await?;
+    let owned = sqlite
+        .execute_select_in_tx_owned(count, params, build_result_set)
+        .await?;
+    assert_eq!(
+        borrowed.results[0].get("n").and_then(RowValues::as_int),
+        Some(&4)
+    );
+    assert_eq!(
+        owned.results[0].get("n").and_then(RowValues::as_int),
+        Some(&4)
+    );
+    sqlite.commit().await?;
+
+    let select = "SELECT body FROM notes WHERE id = ?1";
+    let params = vec![Value::Integer(1)];
+    let borrowed = sqlite
+        .execute_select(select, &params, build_result_set)
+        .await?;
+    let owned = sqlite
+        .execute_select_owned(select, vec![Value::Integer(4)], build_result_set)
+        .await?;
+    assert_eq!(
+        borrowed.results[0].get("body").and_then(RowValues::as_text),
+        Some("borrowed")
+    );
+    assert_eq!(
+        owned.results[0].get("body").and_then(RowValues::as_text),
+        Some("owned in tx")
+    );
+    Ok(())
+}
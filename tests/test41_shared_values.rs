@@ -0,0 +1,48 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn cloned_values_share_their_buffers() -> Result<(), Box<dyn std::error::Error>> {
+    let params = vec![
+        RowValues::Int(1),
+        RowValues::Text("shared".into()),
+        RowValues::Blob(vec![1u8, 2, 3].into()),
+    ];
+    let retry = params.clone();
+    match (&params[1], &retry[1]) {
+        (RowValues::Text(a), RowValues::Text(b)) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("expected text"),
+    }
+    match (&params[2], &retry[2]) {
+        (RowValues::Blob(a), RowValues::Blob(b)) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("expected blob"),
+    }
+
+    let cap = ConfigAndPool::new_sqlite_memory("test41_shared").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE docs (id INTEGER, body TEXT, data BLOB)")
+        .await?;
+    conn.query("INSERT INTO docs VALUES (?1, ?2, ?3)")
+        .params(&retry)
+        .dml()
+        .await?;
+
+    let rows = conn.query("SELECT body, data FROM docs").select().await?;
+    let copy = rows.clone();
+    assert_eq!(
+        copy.results[0].get("body").and_then(RowValues::as_text),
+        Some("shared")
+    );
+    assert_eq!(
+        copy.results[0].get("data").and_then(RowValues::as_blob),
+        Some(&[1u8, 2, 3][..])
+    );
+    match (rows.results[0].get("body"), copy.results[0].get("body")) {
+        (Some(RowValues::Text(a)), Some(RowValues::Text(b))) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("expected text"),
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::compare::{DiffOptions, diff_result_sets};
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn built_result_set_diffs_against_query() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test42_builder").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE people (id INTEGER, name TEXT);
+         INSERT INTO people VALUES (1, 'ada'), (2, 'grace');",
+    )
+    .await?;
+    let queried = conn
+        .query("SELECT id, name FROM people ORDER BY id")
+        .select()
+        .await?;
+
+    // Rows as they might arrive from a CSV import.
+    let csv = "1,ada\n2,grace\n";
+    let mut builder = ResultSetBuilder::new();
+    builder.add_column("id").add_column("name");
+    for line in csv.lines() {
+        let (id, name) = line.split_once(',').expect("two fields");
+        builder.push_row(vec![
+            RowValues::Int(id.parse()?),
+            RowValues::Text(name.into()),
+        ]);
+    }
+    let imported = builder.finish()?;
+
+    assert_eq!(imported.rows_affected, 2);
+    assert_eq!(
+        imported.get_column_names().map(|c| c.as_slice()),
+        Some(&["id".to_string(), "name".to_string()][..])
+    );
+    assert_eq!(
+        imported.results[1].get("name").and_then(RowValues::as_text),
+        Some("grace")
+    );
+    assert!(diff_result_sets(&queried, &imported, DiffOptions::default()).is_empty());
+    Ok(())
+}
+
+#[test]
+fn mismatched_row_width_is_rejected() {
+    let mut builder = ResultSetBuilder::new();
+    builder
+        .add_column("id")
+        .push_row(vec![RowValues::Int(1)])
+        .push_row(vec![RowValues::Int(2), RowValues::Null]);
+    let err = builder.finish().expect_err("second row is too wide");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::ParameterError(msg) if msg.contains("row 1")),
+        "{err}"
+    );
+
+    let empty = ResultSetBuilder::new().finish().expect("no rows");
+    assert!(empty.results.is_empty());
+}
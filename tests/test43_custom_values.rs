@@ -0,0 +1,103 @@
+#![cfg(feature = "sqlite")]
+
+use std::cmp::Ordering;
+
+use sql_middleware::prelude::*;
+
+/// A point stored as `"x,y"` text.
+#[derive(Debug, Clone, PartialEq)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl CustomValue for Point {
+    fn type_name(&self) -> &str {
+        "point"
+    }
+
+    fn encode(&self) -> RowValues {
+        RowValues::Text(format!("{},{}", self.x, self.y).into())
+    }
+}
+
+/// Wraps another custom value to check nested encodings.
+#[derive(Debug, Clone)]
+struct Tagged(Point);
+
+impl CustomValue for Tagged {
+    fn type_name(&self) -> &str {
+        "tagged_point"
+    }
+
+    fn encode(&self) -> RowValues {
+        RowValues::Custom(Box::new(self.0.clone()))
+    }
+}
+
+#[tokio::test]
+async fn custom_values_bind_as_their_encoding() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test43_custom").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE shapes (id INTEGER, at TEXT)")
+        .await?;
+
+    conn.query("INSERT INTO shapes VALUES (?1, ?2), (?3, ?4)")
+        .params(&[
+            RowValues::Int(1),
+            RowValues::Custom(Box::new(Point { x: 3, y: 4 })),
+            RowValues::Int(2),
+            RowValues::Custom(Box::new(Tagged(Point { x: 5, y: 6 }))),
+        ])
+        .strict()
+        .dml()
+        .await?;
+
+    let rows = conn
+        .query("SELECT at FROM shapes ORDER BY id")
+        .select()
+        .await?;
+    let texts: Vec<_> = rows
+        .results
+        .iter()
+        .map(|row| {
+            row.get("at")
+                .and_then(RowValues::as_text)
+                .map(str::to_string)
+        })
+        .collect();
+    assert_eq!(texts, [Some("3,4".to_string()), Some("5,6".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn custom_values_clone_compare_and_downcast() {
+    let value = RowValues::Custom(Box::new(Point { x: 1, y: 2 }));
+    let copy = value.clone();
+    assert_eq!(value, copy);
+    assert_eq!(copy.as_custom::<Point>(), Some(&Point { x: 1, y: 2 }));
+    assert!(copy.as_custom::<Tagged>().is_none());
+    assert!(RowValues::Int(1).as_custom::<Point>().is_none());
+
+    let other = RowValues::Custom(Box::new(Point { x: 1, y: 3 }));
+    assert_ne!(value, other);
+    assert_eq!(value.total_cmp(&other), Ordering::Less);
+    assert_eq!(RowValues::Null.total_cmp(&value), Ordering::Less);
+
+    // Strict mode looks at the encoding.
+    #[derive(Debug, Clone)]
+    struct Flag;
+    impl CustomValue for Flag {
+        fn type_name(&self) -> &str {
+            "flag"
+        }
+        fn encode(&self) -> RowValues {
+            RowValues::Bool(true)
+        }
+    }
+    assert_eq!(
+        DatabaseType::Sqlite.param_coercion(&RowValues::Custom(Box::new(Flag))),
+        Some("INTEGER")
+    );
+    assert_eq!(DatabaseType::Sqlite.param_coercion(&value), None);
+}
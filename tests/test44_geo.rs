@@ -0,0 +1,126 @@
+#![cfg(all(feature = "geo-types", feature = "sqlite"))]
+
+use geo_types::{Geometry, MultiPoint, Point};
+use sql_middleware::geo::{
+    from_wkb, geom_from_wkb_sql, geom_from_wkt_sql, sqlite_blob_to_wkb, to_wkb,
+};
+use sql_middleware::prelude::*;
+
+/// A `SpatiaLite` blob: header with SRID and MBR, class type, body, end marker.
+fn spatialite_blob(class: u32, body: &[u8]) -> Vec<u8> {
+    let mut blob = vec![0x00, 0x01];
+    blob.extend_from_slice(&4326i32.to_le_bytes());
+    for v in [1.0f64, 2.0, 3.0, 4.0] {
+        blob.extend_from_slice(&v.to_le_bytes());
+    }
+    blob.push(0x7C);
+    blob.extend_from_slice(&class.to_le_bytes());
+    blob.extend_from_slice(body);
+    blob.push(0xFE);
+    blob
+}
+
+fn xy(x: f64, y: f64) -> Vec<u8> {
+    [x.to_le_bytes(), y.to_le_bytes()].concat()
+}
+
+#[tokio::test]
+async fn geometry_columns_round_trip_as_wkb() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test44_geo").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE places (id INTEGER, geom GEOMETRY, raw BLOB)")
+        .await?;
+
+    let home = Geometry::Point(Point::new(1.5, -2.0));
+    let value = RowValues::from(&home);
+    conn.query("INSERT INTO places VALUES (?1, ?2, ?2), (?3, ?4, NULL)")
+        .params(&[
+            RowValues::Int(1),
+            value.clone(),
+            RowValues::Int(2),
+            RowValues::Blob(spatialite_blob(1, &xy(3.0, 4.0)).into()),
+        ])
+        .dml()
+        .await?;
+
+    let rows = conn
+        .query("SELECT geom, raw FROM places ORDER BY id")
+        .select()
+        .await?;
+    // Only the column declared as a geometry is decoded.
+    assert_eq!(rows.results[0].get("geom"), Some(&value));
+    assert!(matches!(
+        rows.results[0].get("raw"),
+        Some(RowValues::Blob(_))
+    ));
+    assert_eq!(rows.results[0].get("geom").unwrap().to_geo()?, Some(home));
+    assert_eq!(
+        rows.results[1].get("geom").unwrap().to_geo()?,
+        Some(Geometry::Point(Point::new(3.0, 4.0)))
+    );
+    assert_eq!(RowValues::Int(1).to_geo()?, None);
+    Ok(())
+}
+
+#[test]
+fn spatialite_and_geopackage_blobs_become_wkb() -> Result<(), SqlMiddlewareDbError> {
+    // MULTIPOINT with two entities, each introduced by the 0x69 marker.
+    let mut body = 2u32.to_le_bytes().to_vec();
+    for (x, y) in [(1.0, 2.0), (3.0, 4.0)] {
+        body.push(0x69);
+        body.extend_from_slice(&1u32.to_le_bytes());
+        body.extend_from_slice(&xy(x, y));
+    }
+    let wkb = sqlite_blob_to_wkb(&spatialite_blob(4, &body))?;
+    let expected =
+        Geometry::MultiPoint(MultiPoint(vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)]));
+    assert_eq!(wkb, to_wkb(&expected));
+    assert_eq!(from_wkb(&wkb)?, expected);
+
+    // GeoPackage header (flags 0x03: little-endian, 32-byte envelope) in front of WKB.
+    let point = to_wkb(&Geometry::Point(Point::new(5.0, 6.0)));
+    let mut gpkg = vec![b'G', b'P', 0, 0x03];
+    gpkg.extend_from_slice(&4326i32.to_le_bytes());
+    gpkg.extend_from_slice(&[0u8; 32]);
+    gpkg.extend_from_slice(&point);
+    assert_eq!(sqlite_blob_to_wkb(&gpkg)?, point);
+
+    // Compressed SpatiaLite geometries are reported rather than misread.
+    assert!(sqlite_blob_to_wkb(&spatialite_blob(1_000_002, &[0; 20])).is_err());
+    Ok(())
+}
+
+#[test]
+fn ewkb_srid_and_z_are_skipped() -> Result<(), SqlMiddlewareDbError> {
+    // Big-endian EWKB POINT Z with SRID 4326.
+    let mut ewkb = vec![0x00];
+    ewkb.extend_from_slice(&(0x8000_0000u32 | 0x2000_0000 | 1).to_be_bytes());
+    ewkb.extend_from_slice(&4326u32.to_be_bytes());
+    for v in [7.0f64, 8.0, 9.0] {
+        ewkb.extend_from_slice(&v.to_be_bytes());
+    }
+    assert_eq!(from_wkb(&ewkb)?, Geometry::Point(Point::new(7.0, 8.0)));
+    assert!(from_wkb(&ewkb[..10]).is_err());
+    Ok(())
+}
+
+#[test]
+fn constructor_sql_wraps_placeholders() {
+    assert_eq!(
+        geom_from_wkt_sql(&DatabaseType::Sqlite, 2, Some(4326)),
+        "GeomFromText(?2, 4326)"
+    );
+    assert_eq!(
+        geom_from_wkb_sql(&DatabaseType::Sqlite, 1, None),
+        "GeomFromWKB(?1)"
+    );
+    #[cfg(feature = "postgres")]
+    assert_eq!(
+        geom_from_wkt_sql(&DatabaseType::Postgres, 1, None),
+        "ST_GeomFromText($1)"
+    );
+    assert_eq!(
+        DatabaseType::Sqlite.param_coercion(&RowValues::Geometry(vec![1u8].into())),
+        Some("BLOB")
+    );
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "turso")]
+
+use std::time::Duration;
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn turso_pool_caps_checkouts() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::turso_builder(":memory:".to_string())
+        .max_size(1)
+        .build()
+        .await?;
+
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER); INSERT INTO t VALUES (1);")
+        .await?;
+
+    let err = cap
+        .get_connection_timeout(Duration::from_millis(50))
+        .await
+        .expect_err("the only connection is checked out");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::ConnectionError(_)),
+        "{err}"
+    );
+
+    drop(conn);
+    let mut conn = cap.get_connection_timeout(Duration::from_secs(1)).await?;
+    let rows = conn.query("SELECT id FROM t").select().await?;
+    assert_eq!(
+        rows.results[0].get("id").and_then(RowValues::as_int),
+        Some(&1)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn turso_pool_rejects_zero_size() {
+    let err = ConfigAndPool::turso_builder(":memory:".to_string())
+        .max_size(0)
+        .build()
+        .await
+        .expect_err("an empty pool can never hand out a connection");
+    assert!(matches!(err, SqlMiddlewareDbError::ConfigError(_)));
+}
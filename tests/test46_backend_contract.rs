@@ -0,0 +1,80 @@
+use sql_middleware::backend::BackendApi;
+use sql_middleware::prelude::*;
+
+fn assert_backend<B: BackendApi>(expected: DatabaseType) {
+    assert_eq!(B::DATABASE_TYPE, expected);
+}
+
+#[test]
+fn enabled_backends_implement_the_contract() {
+    #[cfg(feature = "postgres")]
+    {
+        use sql_middleware::postgres;
+        assert_backend::<postgres::PostgresBackend>(DatabaseType::Postgres);
+        let _ = postgres::build_result_set;
+    }
+    #[cfg(feature = "sqlite")]
+    {
+        use sql_middleware::sqlite;
+        assert_backend::<sqlite::SqliteBackend>(DatabaseType::Sqlite);
+        let _ = sqlite::build_result_set;
+    }
+    #[cfg(feature = "mssql")]
+    {
+        use sql_middleware::mssql;
+        assert_backend::<mssql::MssqlBackend>(DatabaseType::Mssql);
+        let _ = mssql::build_result_set;
+    }
+    #[cfg(feature = "turso")]
+    {
+        use sql_middleware::turso;
+        assert_backend::<turso::TursoBackend>(DatabaseType::Turso);
+        let _ = turso::build_result_set;
+    }
+}
+
+#[cfg(feature = "sqlite")]
+async fn insert_ids<B: BackendApi>(
+    conn: &mut B::TxConnection,
+    sql: &str,
+    ids: &[i64],
+) -> Result<usize, SqlMiddlewareDbError> {
+    let mut tx = B::begin_transaction(conn).await?;
+    let mut stmt = B::prepare(&mut tx, sql).await?;
+    let mut inserted = 0;
+    for id in ids {
+        inserted += B::execute_prepared(&mut tx, &mut stmt, &[RowValues::Int(*id)]).await?;
+    }
+    B::commit(tx).await?;
+    Ok(inserted)
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn generic_code_runs_against_sqlite() -> Result<(), Box<dyn std::error::Error>> {
+    use sql_middleware::sqlite::SqliteBackend;
+
+    let cap = ConfigAndPool::new_sqlite_memory("test46_backend").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER)").await?;
+
+    let inserted =
+        insert_ids::<SqliteBackend>(&mut conn, "INSERT INTO t (id) VALUES (?1)", &[1, 2, 3])
+            .await?;
+    assert_eq!(inserted, 3);
+
+    let (mut sqlite, _) = conn.into_sqlite()?;
+    let rows =
+        SqliteBackend::execute_select(&mut sqlite, "SELECT id FROM t ORDER BY id", &[]).await?;
+    assert_eq!(rows.results.len(), 3);
+    assert_eq!(
+        SqliteBackend::execute_dml(
+            &mut sqlite,
+            "DELETE FROM t WHERE id > ?1",
+            &[RowValues::Int(1)]
+        )
+        .await?,
+        2
+    );
+    Ok(())
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn one_statement_helpers_manage_checkout() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test47_oneshot").await?;
+    cap.batch("CREATE TABLE users (id INTEGER, name TEXT)")
+        .await?;
+
+    let inserted = cap
+        .dml(
+            "INSERT INTO users (id, name) VALUES (?1, ?2), (?3, ?4)",
+            &[
+                RowValues::Int(1),
+                RowValues::Text("ada".into()),
+                RowValues::Int(2),
+                RowValues::Text("grace".into()),
+            ],
+        )
+        .await?;
+    assert_eq!(inserted, 2);
+
+    let rows = cap
+        .select("SELECT name FROM users WHERE id = ?1", &[RowValues::Int(2)])
+        .await?;
+    assert_eq!(
+        rows.results[0].get("name").and_then(RowValues::as_text),
+        Some("grace")
+    );
+
+    let err = cap
+        .select("SELECT missing FROM users", &[])
+        .await
+        .expect_err("unknown column");
+    assert!(matches!(err, SqlMiddlewareDbError::SqliteError(_)), "{err}");
+    Ok(())
+}
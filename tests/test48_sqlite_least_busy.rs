@@ -0,0 +1,53 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sql_middleware::clock::MockClock;
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn checkout_skips_worker_running_abandoned_statement()
+-> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test48_least_busy").await?;
+    skips_busy_worker(&cap).await
+}
+
+#[tokio::test]
+async fn busy_probes_wait_on_the_pool_clock() -> Result<(), Box<dyn std::error::Error>> {
+    // Mock time never advances here, so probes only settle on connections that are ready.
+    let cap = ConfigAndPool::new_sqlite_memory("test48_mock_clock")
+        .await?
+        .with_clock(Arc::new(MockClock::new()));
+    skips_busy_worker(&cap).await
+}
+
+async fn skips_busy_worker(cap: &ConfigAndPool) -> Result<(), Box<dyn std::error::Error>> {
+    let idle = cap.get_connection().await?;
+    let mut busy = cap.get_connection().await?;
+
+    // The caller gives up, but the worker keeps running the statement.
+    let slow = busy.with_blocking_sqlite(|_raw| {
+        std::thread::sleep(Duration::from_secs(2));
+        Ok::<(), SqlMiddlewareDbError>(())
+    });
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), slow)
+            .await
+            .is_err()
+    );
+
+    // bb8 hands out idle connections in return order, so the busy one would come first.
+    drop(busy);
+    drop(idle);
+
+    let started = Instant::now();
+    let mut conn = cap.get_connection().await?;
+    conn.query("SELECT 1").select().await?;
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "checkout waited behind the busy worker: {:?}",
+        started.elapsed()
+    );
+    Ok(())
+}
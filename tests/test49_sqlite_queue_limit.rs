@@ -0,0 +1,67 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::QueueFullPolicy;
+
+/// Start a slow statement and stop waiting for it, leaving it running on the worker.
+async fn abandon_slow_statement(conn: &mut MiddlewarePoolConnection) {
+    let slow = conn.with_blocking_sqlite(|_raw| {
+        std::thread::sleep(Duration::from_millis(500));
+        Ok::<(), SqlMiddlewareDbError>(())
+    });
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), slow)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn full_queue_rejects_operations() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test49_reject?mode=memory&cache=shared".into())
+        .queue_limit(1, QueueFullPolicy::Reject)
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    abandon_slow_statement(&mut conn).await;
+
+    let err = conn
+        .query("SELECT 1")
+        .select()
+        .await
+        .expect_err("the abandoned statement holds the only slot");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::Overloaded { limit: 1 }),
+        "{err}"
+    );
+
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    conn.query("SELECT 1").select().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn full_queue_waits_for_a_slot() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test49_wait?mode=memory&cache=shared".into())
+        .queue_limit(1, QueueFullPolicy::Wait)
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    abandon_slow_statement(&mut conn).await;
+
+    let rows = conn.query("SELECT 1 AS one").select().await?;
+    assert_eq!(rows.results.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn zero_queue_limit_is_rejected() {
+    let err = ConfigAndPool::sqlite_builder("file:test49_zero?mode=memory&cache=shared".into())
+        .queue_limit(0, QueueFullPolicy::Wait)
+        .build()
+        .await
+        .expect_err("no operation could ever run");
+    assert!(matches!(err, SqlMiddlewareDbError::ConfigError(_)));
+}
@@ -0,0 +1,99 @@
+use sql_middleware::prelude::*;
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_pool_from_json_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("db.json");
+    std::fs::write(
+        &path,
+        r#"{
+            "backend": "sqlite",
+            "db_path": "file:test50_json?mode=memory&cache=shared",
+            "translate_placeholders": true,
+            "queue_limit": { "max_pending": 4, "policy": "wait" }
+        }"#,
+    )?;
+
+    let cap = ConfigAndPool::from_config_file(&path).await?;
+    assert_eq!(cap.db_type, DatabaseType::Sqlite);
+    assert!(cap.translate_placeholders);
+
+    let rows = cap.select("SELECT 1 AS one", &[]).await?;
+    assert_eq!(
+        rows.results[0].get("one").and_then(RowValues::as_int),
+        Some(&1)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_options_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    use sql_middleware::sqlite::{QueueFullPolicy, WorkerQueueLimit};
+
+    let opts = SqliteOptions::new("app.db".into())
+        .with_translation(true)
+        .with_attachment("aux", "aux.db")
+        .with_queue_limit(8, QueueFullPolicy::Reject);
+    let json = serde_json::to_string(&BackendConfig::Sqlite(opts))?;
+
+    let BackendConfig::Sqlite(back) = BackendConfig::from_json(&json)? else {
+        panic!("expected a sqlite config: {json}");
+    };
+    assert_eq!(back.db_path, "app.db");
+    assert!(back.translate_placeholders);
+    assert_eq!(back.attachments[0].alias, "aux");
+    assert_eq!(
+        back.queue_limit,
+        Some(WorkerQueueLimit::new(8, QueueFullPolicy::Reject))
+    );
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn postgres_password_is_redacted() -> Result<(), Box<dyn std::error::Error>> {
+    let mut pg = PgConfig::new();
+    pg.host = Some("db.internal".into());
+    pg.password = Some("hunter2".into());
+    let opts = PostgresOptions::new(pg).with_connect_timeout(std::time::Duration::from_secs(3));
+
+    let json = serde_json::to_string(&BackendConfig::Postgres(opts))?;
+    assert!(!json.contains("hunter2"), "{json}");
+    assert!(json.contains(sql_middleware::config::REDACTED), "{json}");
+    assert!(json.contains(r#""connect_timeout_ms":3000"#), "{json}");
+
+    let BackendConfig::Postgres(back) = BackendConfig::from_json(&json)? else {
+        panic!("expected a postgres config: {json}");
+    };
+    assert_eq!(back.config.host.as_deref(), Some("db.internal"));
+    assert_eq!(
+        back.connect_timeout,
+        Some(std::time::Duration::from_secs(3))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn bad_config_files_are_config_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+
+    let unknown = dir.path().join("unknown.json");
+    std::fs::write(&unknown, r#"{ "backend": "oracle" }"#)?;
+    let yaml = dir.path().join("db.yaml");
+    std::fs::write(&yaml, "backend: sqlite")?;
+    let missing = dir.path().join("missing.json");
+
+    for path in [unknown, yaml, missing] {
+        let err = ConfigAndPool::from_config_file(&path)
+            .await
+            .expect_err("config should be rejected");
+        assert!(
+            matches!(err, SqlMiddlewareDbError::ConfigError(_)),
+            "{}: {err}",
+            path.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use sql_middleware::prelude::*;
+
+fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let map: HashMap<String, String> = pairs
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect();
+    move |name| map.get(name).cloned()
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn postgres_options_from_env() -> Result<(), SqlMiddlewareDbError> {
+    let opts = PostgresOptions::from_env_with(
+        "APP_PG_",
+        vars(&[
+            ("APP_PG_HOST", "db.internal"),
+            ("APP_PG_PORT", "6432"),
+            ("APP_PG_DBNAME", "app"),
+            ("APP_PG_USER", "svc"),
+            ("APP_PG_PASSWORD", "secret"),
+            ("APP_PG_POOL_SIZE", "4"),
+            ("APP_PG_CONNECT_TIMEOUT_MS", "1500"),
+            ("APP_PG_TRANSLATE_PLACEHOLDERS", "true"),
+        ]),
+    )?;
+    assert_eq!(opts.config.host.as_deref(), Some("db.internal"));
+    assert_eq!(opts.config.port, Some(6432));
+    assert_eq!(opts.config.password.as_deref(), Some("secret"));
+    assert_eq!(opts.max_size, 4);
+    assert_eq!(
+        opts.connect_timeout,
+        Some(std::time::Duration::from_millis(1500))
+    );
+    assert!(opts.translate_placeholders);
+    assert!(!opts.read_write);
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn postgres_env_errors_list_every_problem() {
+    let err = PostgresOptions::from_env_with(
+        "APP_PG_",
+        vars(&[
+            ("APP_PG_HOST", "db.internal"),
+            ("APP_PG_PORT", "not-a-port"),
+            ("APP_PG_USER", "svc"),
+        ]),
+    );
+    let Err(SqlMiddlewareDbError::ConfigError(msg)) = err else {
+        panic!("expected a config error");
+    };
+    assert!(msg.contains("APP_PG_DBNAME"), "{msg}");
+    assert!(msg.contains("APP_PG_PASSWORD"), "{msg}");
+    assert!(msg.contains("APP_PG_PORT=\"not-a-port\""), "{msg}");
+    assert!(!msg.contains("APP_PG_HOST"), "{msg}");
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_pool_from_env() -> Result<(), Box<dyn std::error::Error>> {
+    use sql_middleware::sqlite::{QueueFullPolicy, WorkerQueueLimit};
+
+    let opts = SqliteOptions::from_env_with(
+        "APP_DB_",
+        vars(&[
+            ("APP_DB_DB_PATH", "file:test51_env?mode=memory&cache=shared"),
+            ("APP_DB_TRANSLATE_PLACEHOLDERS", "1"),
+            ("APP_DB_QUEUE_LIMIT", "16"),
+            ("APP_DB_QUEUE_FULL_POLICY", "wait"),
+        ]),
+    )?;
+    assert!(opts.translate_placeholders);
+    assert_eq!(
+        opts.queue_limit,
+        Some(WorkerQueueLimit::new(16, QueueFullPolicy::Wait))
+    );
+
+    let cap = ConfigAndPool::new_sqlite(opts).await?;
+    let rows = cap.select("SELECT 1 AS one", &[]).await?;
+    assert_eq!(rows.results.len(), 1);
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_env_rejects_bad_flag() {
+    let err = SqliteOptions::from_env_with(
+        "APP_DB_",
+        vars(&[("APP_DB_TRANSLATE_PLACEHOLDERS", "maybe")]),
+    )
+    .expect_err("db path is missing and the flag is malformed");
+    let SqlMiddlewareDbError::ConfigError(msg) = err else {
+        panic!("expected a config error, got {err}");
+    };
+    assert!(
+        msg.contains("missing environment variables: APP_DB_DB_PATH"),
+        "{msg}"
+    );
+    assert!(
+        msg.contains("APP_DB_TRANSLATE_PLACEHOLDERS=\"maybe\""),
+        "{msg}"
+    );
+}
+
+#[cfg(feature = "turso")]
+#[test]
+fn turso_options_from_env() -> Result<(), SqlMiddlewareDbError> {
+    let opts = TursoOptions::from_env_with(
+        "T_",
+        vars(&[("T_DB_PATH", ":memory:"), ("T_POOL_SIZE", "2")]),
+    )?;
+    assert_eq!(opts.db_path, ":memory:");
+    assert_eq!(opts.max_size, 2);
+    Ok(())
+}
+
+#[cfg(feature = "mssql")]
+#[test]
+fn mssql_options_from_env() -> Result<(), SqlMiddlewareDbError> {
+    let opts = MssqlOptions::from_env_with(
+        "MS_",
+        vars(&[
+            ("MS_SERVER", "sql.internal"),
+            ("MS_DATABASE", "app"),
+            ("MS_USER", "sa"),
+            ("MS_PASSWORD", "secret"),
+            ("MS_PORT", "1444"),
+        ]),
+    )?;
+    assert_eq!(opts.port, Some(1444));
+    assert_eq!(opts.instance_name, None);
+    assert_eq!(
+        opts.max_size,
+        sql_middleware::mssql::config::DEFAULT_MSSQL_POOL_SIZE
+    );
+    Ok(())
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "postgres")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sql_middleware::credentials::{Credentials, CredentialsProvider, FetchCredentials};
+use sql_middleware::prelude::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Hands out `secret-1`, `secret-2`, ... as if rotated between calls.
+#[derive(Debug, Default)]
+struct Rotating {
+    calls: AtomicUsize,
+    valid_for: Option<Duration>,
+}
+
+impl CredentialsProvider for Rotating {
+    fn fetch(&self) -> FetchCredentials {
+        let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut creds = Credentials::new("vault_user", format!("secret-{n}"));
+        creds.valid_for = self.valid_for;
+        Box::pin(async move { Ok(creds) })
+    }
+}
+
+/// Accept connections, ask each for a cleartext password, record `(user, password)` and reject.
+async fn fake_postgres() -> (u16, Arc<Mutex<Vec<(String, String)>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = Arc::clone(&seen);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let record = Arc::clone(&record);
+            tokio::spawn(async move {
+                if let Ok(login) = reject_login(stream).await {
+                    record.lock().unwrap().push(login);
+                }
+            });
+        }
+    });
+    (port, seen)
+}
+
+async fn reject_login(mut stream: TcpStream) -> std::io::Result<(String, String)> {
+    let len = stream.read_i32().await?;
+    let mut startup = vec![0; usize::try_from(len).unwrap() - 4];
+    stream.read_exact(&mut startup).await?;
+    let fields: Vec<&[u8]> = startup[4..].split(|b| *b == 0).collect();
+    let user = fields
+        .windows(2)
+        .find(|pair| pair[0] == b"user")
+        .map(|pair| String::from_utf8_lossy(pair[1]).into_owned())
+        .unwrap_or_default();
+
+    // AuthenticationCleartextPassword
+    stream.write_all(&[b'R', 0, 0, 0, 8, 0, 0, 0, 3]).await?;
+    assert_eq!(stream.read_u8().await?, b'p');
+    let len = stream.read_i32().await?;
+    let mut password = vec![0; usize::try_from(len).unwrap() - 4];
+    stream.read_exact(&mut password).await?;
+    password.pop();
+
+    let mut body = Vec::new();
+    for (code, value) in [(b'S', "FATAL"), (b'C', "28P01"), (b'M', "rejected by test")] {
+        body.push(code);
+        body.extend_from_slice(value.as_bytes());
+        body.push(0);
+    }
+    body.push(0);
+    stream.write_u8(b'E').await?;
+    stream
+        .write_i32(i32::try_from(body.len()).unwrap() + 4)
+        .await?;
+    stream.write_all(&body).await?;
+    Ok((user, String::from_utf8(password).unwrap()))
+}
+
+fn options(port: u16, provider: Arc<Rotating>) -> PostgresOptions {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".into());
+    cfg.host = Some("127.0.0.1".into());
+    cfg.port = Some(port);
+    PostgresOptions::new(cfg).with_credentials(provider)
+}
+
+#[tokio::test]
+async fn each_new_connection_fetches_fresh_credentials() -> Result<(), Box<dyn std::error::Error>> {
+    let (port, seen) = fake_postgres().await;
+    let provider = Arc::new(Rotating::default());
+    let cap = ConfigAndPool::new_postgres(options(port, Arc::clone(&provider))).await?;
+
+    let _ = cap.get_connection_timeout(Duration::from_millis(700)).await;
+
+    let seen = seen.lock().unwrap().clone();
+    assert!(seen.len() >= 2, "expected retries, saw {seen:?}");
+    for (i, (user, password)) in seen.iter().enumerate() {
+        assert_eq!(user, "vault_user");
+        assert_eq!(password, &format!("secret-{}", i + 1));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn credentials_are_reused_while_valid() -> Result<(), Box<dyn std::error::Error>> {
+    let (port, seen) = fake_postgres().await;
+    let provider = Arc::new(Rotating {
+        valid_for: Some(Duration::from_secs(3600)),
+        ..Rotating::default()
+    });
+    let cap = ConfigAndPool::new_postgres(options(port, Arc::clone(&provider))).await?;
+
+    let _ = cap.get_connection_timeout(Duration::from_millis(700)).await;
+
+    let seen = seen.lock().unwrap().clone();
+    assert!(seen.len() >= 2, "expected retries, saw {seen:?}");
+    assert!(seen.iter().all(|(_, password)| password == "secret-1"));
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn expired_auth_tokens_are_regenerated() -> Result<(), Box<dyn std::error::Error>> {
+    let (port, seen) = fake_postgres().await;
+    let generated = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&generated);
+
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".into());
+    cfg.host = Some("127.0.0.1".into());
+    cfg.port = Some(port);
+    cfg.user = Some("iam_user".into());
+    let cap = ConfigAndPool::postgres_builder(cfg)
+        .auth_token(Duration::ZERO, move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { Ok(format!("token-{n}")) }
+        })
+        .build()
+        .await?;
+
+    let _ = cap.get_connection_timeout(Duration::from_millis(700)).await;
+
+    let seen = seen.lock().unwrap().clone();
+    assert!(seen.len() >= 2, "expected retries, saw {seen:?}");
+    for (i, (user, password)) in seen.iter().enumerate() {
+        assert_eq!(user, "iam_user");
+        assert_eq!(password, &format!("token-{}", i + 1));
+    }
+    assert!(generated.load(Ordering::SeqCst) >= seen.len());
+    Ok(())
+}
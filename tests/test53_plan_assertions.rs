@@ -0,0 +1,61 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::test_helpers::{assert_plan_uses_index, explain_plan};
+
+async fn users_table() -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory("test53_plans").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, name TEXT);
+         CREATE INDEX idx_users_email ON users (email);
+         CREATE INDEX idx_users_email_name ON users (email, name);",
+    )
+    .await?;
+    Ok(conn)
+}
+
+#[tokio::test]
+async fn indexed_lookup_passes() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = users_table().await?;
+    assert_plan_uses_index(
+        &mut conn,
+        "SELECT id FROM users INDEXED BY idx_users_email WHERE email = ?1",
+        &[RowValues::Text("a@example.com".into())],
+        "idx_users_email",
+    )
+    .await
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected the plan for")]
+async fn full_scan_fails_the_assertion() {
+    let mut conn = users_table().await.unwrap();
+    assert_plan_uses_index(
+        &mut conn,
+        "SELECT id FROM users WHERE name = ?1",
+        &[RowValues::Text("alice".into())],
+        "idx_users_email",
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn index_names_match_whole_identifiers() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = users_table().await?;
+    let sql = "SELECT id FROM users INDEXED BY idx_users_email_name WHERE email = ?1 AND name = ?2";
+    let params = [
+        RowValues::Text("a@example.com".into()),
+        RowValues::Text("alice".into()),
+    ];
+    let plan = explain_plan(&mut conn, sql, &params).await?;
+    assert!(plan.contains("idx_users_email_name"), "{plan}");
+
+    let prefix_only = tokio::spawn(async move {
+        assert_plan_uses_index(&mut conn, sql, &params, "idx_users_email").await
+    })
+    .await;
+    assert!(prefix_only.is_err(), "a longer index name must not match");
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::pool::NestedTransaction;
+use sql_middleware::prelude::*;
+
+async fn setup(name: &str) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory(name).await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        .await?;
+    Ok(conn)
+}
+
+async fn insert(conn: &mut MiddlewarePoolConnection, id: i64) -> Result<(), SqlMiddlewareDbError> {
+    conn.transaction(async |conn| {
+        conn.query("INSERT INTO t (id) VALUES (?1)")
+            .params(&[RowValues::Int(id)])
+            .dml()
+            .await
+            .map(|_| ())
+    })
+    .await
+}
+
+async fn ids(conn: &mut MiddlewarePoolConnection) -> Result<Vec<i64>, SqlMiddlewareDbError> {
+    let rows = conn.query("SELECT id FROM t ORDER BY id").select().await?;
+    Ok(rows
+        .results
+        .iter()
+        .filter_map(|row| row.get("id").and_then(RowValues::as_int).copied())
+        .collect())
+}
+
+#[tokio::test]
+async fn nested_calls_compose_through_savepoints() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = setup("test54_nested").await?;
+
+    conn.transaction(async |conn| {
+        assert!(conn.in_transaction().await?);
+        insert(conn, 1).await?;
+        // The duplicate key fails the inner call; only its savepoint is rolled back.
+        assert!(insert(conn, 1).await.is_err());
+        insert(conn, 2).await
+    })
+    .await?;
+
+    assert!(!conn.in_transaction().await?);
+    assert_eq!(ids(&mut conn).await?, vec![1, 2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn outer_failure_discards_inner_work() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = setup("test54_outer_failure").await?;
+
+    let result: Result<(), _> = conn
+        .transaction(async |conn| {
+            insert(conn, 1).await?;
+            Err(SqlMiddlewareDbError::Other("abort".into()))
+        })
+        .await;
+    assert!(result.is_err());
+    assert!(!conn.in_transaction().await?);
+    assert!(ids(&mut conn).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn nesting_can_be_rejected() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = setup("test54_reject").await?;
+
+    let err = conn
+        .transaction(async |conn| {
+            conn.transaction_with(NestedTransaction::Error, async |_| Ok(()))
+                .await
+        })
+        .await
+        .expect_err("nested transaction should be rejected");
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(_)));
+    assert!(!conn.in_transaction().await?);
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use sql_middleware::ident;
+use sql_middleware::prelude::*;
+
+#[test]
+fn names_are_checked_before_interpolation() {
+    assert!(ident::validate_table("events").is_ok());
+    assert!(ident::validate_table("audit.events").is_ok());
+    assert!(ident::validate_column("_created_at2").is_ok());
+
+    for bad in [
+        "",
+        "2fast",
+        "events; DROP TABLE users",
+        "a..b",
+        "a.b.c.d",
+        "évents",
+    ] {
+        let err = ident::validate_table(bad).expect_err(bad);
+        assert!(
+            matches!(err, SqlMiddlewareDbError::ParameterError(_)),
+            "{bad}: {err}"
+        );
+    }
+    assert!(ident::validate_column("audit.events").is_err());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn quoting_escapes_embedded_quotes() -> Result<(), SqlMiddlewareDbError> {
+    assert_eq!(
+        ident::quote("audit.events", &DatabaseType::Sqlite)?,
+        r#""audit"."events""#
+    );
+    assert_eq!(
+        ident::quote(r#"x"; DROP TABLE users; --"#, &DatabaseType::Sqlite)?,
+        r#""x""; DROP TABLE users; --""#
+    );
+    assert!(ident::quote("", &DatabaseType::Sqlite).is_err());
+    assert!(ident::quote("a\0b", &DatabaseType::Sqlite).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "mssql")]
+#[test]
+fn sql_server_uses_brackets() -> Result<(), SqlMiddlewareDbError> {
+    assert_eq!(
+        ident::quote("dbo.odd]name", &DatabaseType::Mssql)?,
+        "[dbo].[odd]]name]"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn hostile_table_name_stays_a_name() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory("test55_hostile").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE users (id INTEGER); INSERT INTO users VALUES (1);")
+        .await?;
+
+    let hostile = "users; DROP TABLE users; --";
+    let table = ident::quote(hostile, &DatabaseType::Sqlite)?;
+    conn.execute_batch(&format!("CREATE TABLE {table} (id INTEGER)"))
+        .await?;
+    conn.execute_batch(&format!("DELETE FROM {table}")).await?;
+
+    let rows = conn
+        .query("SELECT COUNT(*) AS n FROM users")
+        .select()
+        .await?;
+    assert_eq!(
+        rows.results[0].get("n").and_then(RowValues::as_int),
+        Some(&1)
+    );
+    Ok(())
+}
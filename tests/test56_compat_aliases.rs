@@ -0,0 +1,25 @@
+#![cfg(feature = "sqlite")]
+#![allow(deprecated)]
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn deprecated_aliases_still_work() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory("test56_compat").await?;
+
+    let mut conn = MiddlewarePool::get_connection(&cap.pool, false).await?;
+    conn.with_blocking_sqlite(|raw| {
+        raw.execute_batch("CREATE TABLE t (id INTEGER); INSERT INTO t VALUES (7);")?;
+        Ok(())
+    })
+    .await?;
+
+    let mut conn = cap.get_connection().await?;
+    let id = conn
+        .with_blocking_sqlite(|raw| {
+            Ok(raw.query_row("SELECT id FROM t", [], |row| row.get::<_, i64>(0))?)
+        })
+        .await?;
+    assert_eq!(id, 7);
+    Ok(())
+}
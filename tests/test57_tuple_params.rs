@@ -0,0 +1,63 @@
+use sql_middleware::prelude::*;
+
+#[test]
+fn plain_values_convert_to_row_values() {
+    assert_eq!(7_i32.into_row_values(), RowValues::Int(7));
+    assert_eq!("ann".into_row_values(), RowValues::Text("ann".into()));
+    assert_eq!(Some(1.5_f64).into_row_values(), RowValues::Float(1.5));
+    assert_eq!(None::<bool>.into_row_values(), RowValues::Null);
+    assert_eq!(
+        (1_i64, "x", false).into_params().as_ref(),
+        [
+            RowValues::Int(1),
+            RowValues::Text("x".into()),
+            RowValues::Bool(false)
+        ]
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn tuples_bind_like_row_values() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory("test57_tuples").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER, name TEXT, nickname TEXT, active INTEGER, seen TEXT)",
+    )
+    .await?;
+
+    let seen = chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+        .and_then(|d| d.and_hms_opt(12, 0, 0))
+        .expect("valid timestamp");
+    let nickname: Option<String> = None;
+    conn.query("INSERT INTO users VALUES (?1, ?2, ?3, ?4, ?5)")
+        .params((7_i64, String::from("ann"), nickname, true, seen))
+        .dml()
+        .await?;
+
+    let rows = conn
+        .query("SELECT name, nickname, active, seen FROM users WHERE id = ?1")
+        .params((7,))
+        .select()
+        .await?;
+    let row = &rows.results[0];
+    assert_eq!(row.get("name").and_then(RowValues::as_text), Some("ann"));
+    assert!(row.get("nickname").is_some_and(RowValues::is_null));
+    assert_eq!(row.get("active").and_then(RowValues::as_bool), Some(&true));
+    assert_eq!(
+        row.get("seen").and_then(RowValues::as_timestamp),
+        Some(seen)
+    );
+
+    // Slices keep working unchanged.
+    let rows = conn
+        .query("SELECT COUNT(*) AS n FROM users WHERE id = ?1")
+        .params(&[RowValues::Int(7)])
+        .select()
+        .await?;
+    assert_eq!(
+        rows.results[0].get("n").and_then(RowValues::as_int),
+        Some(&1)
+    );
+    Ok(())
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn nullable_columns_read_as_options() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory("test58_nullable").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER, nickname TEXT, active INTEGER, score REAL, seen TEXT);
+         INSERT INTO users VALUES (1, 'annie', 1, 2.5, '2024-05-01 12:00:00');
+         INSERT INTO users VALUES (2, NULL, NULL, NULL, NULL);",
+    )
+    .await?;
+
+    let rows = conn
+        .query("SELECT * FROM users ORDER BY id")
+        .select()
+        .await?;
+    let (full, empty) = (&rows.results[0], &rows.results[1]);
+
+    assert_eq!(
+        full.get_opt::<String>("nickname")?.as_deref(),
+        Some("annie")
+    );
+    assert_eq!(full.get_opt::<bool>("active")?, Some(true));
+    assert_eq!(full.get_opt::<f64>("score")?, Some(2.5));
+    assert!(full.get_opt::<chrono::NaiveDateTime>("seen")?.is_some());
+    assert_eq!(full.get_opt::<i32>("id")?, Some(1));
+
+    assert_eq!(empty.get_opt::<String>("nickname")?, None);
+    assert_eq!(empty.get_opt::<bool>("active")?, None);
+    assert_eq!(empty.get_opt::<chrono::NaiveDateTime>("seen")?, None);
+
+    let err = full
+        .get_opt::<i64>("nickname")
+        .expect_err("text is not an int");
+    assert!(err.to_string().contains("nickname"), "{err}");
+    assert!(full.get_opt::<i64>("missing").is_err());
+
+    assert_eq!(
+        empty
+            .get("nickname")
+            .cloned()
+            .and_then(RowValues::into_option),
+        None
+    );
+    assert_eq!(
+        full.get("id").cloned().and_then(RowValues::into_option),
+        Some(RowValues::Int(1))
+    );
+    Ok(())
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+async fn setup(name: &str) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory(name).await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER, name TEXT);
+         INSERT INTO users VALUES (1, 'ann'), (2, 'bob'), (3, NULL);",
+    )
+    .await?;
+    Ok(conn)
+}
+
+#[tokio::test]
+async fn select_one_returns_the_first_row_or_none() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = setup("test59_one").await?;
+
+    let row = conn
+        .query("SELECT id, name FROM users ORDER BY id DESC -- newest first")
+        .select_one()
+        .await?
+        .expect("a row");
+    assert_eq!(row.get("id").and_then(RowValues::as_int), Some(&3));
+
+    let row = conn
+        .query("SELECT id FROM users ORDER BY id LIMIT 2 OFFSET 1;")
+        .select_one()
+        .await?
+        .expect("a row");
+    assert_eq!(row.get("id").and_then(RowValues::as_int), Some(&2));
+
+    let none = conn
+        .query("SELECT id FROM users WHERE id > ?1")
+        .params((10,))
+        .select_one()
+        .await?;
+    assert!(none.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn select_scalar_reads_the_first_column() -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = setup("test59_scalar").await?;
+
+    let count: i64 = conn
+        .query("SELECT COUNT(*) FROM users")
+        .select_scalar()
+        .await?;
+    assert_eq!(count, 3);
+
+    let exists: bool = conn
+        .query("SELECT EXISTS (SELECT 1 FROM users WHERE name = ?1)")
+        .params(("bob",))
+        .select_scalar()
+        .await?;
+    assert!(exists);
+
+    let name: Option<String> = conn
+        .query("SELECT name FROM users WHERE id = 3")
+        .select_scalar()
+        .await?;
+    assert_eq!(name, None);
+
+    let err = conn
+        .query("SELECT id FROM users WHERE id > 10")
+        .select_scalar::<i64>()
+        .await
+        .expect_err("no rows");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::ExecutionError(_)),
+        "{err}"
+    );
+
+    let err = conn
+        .query("SELECT name FROM users WHERE id = 3")
+        .select_scalar::<String>()
+        .await
+        .expect_err("NULL is not a String");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::ExecutionError(_)),
+        "{err}"
+    );
+    Ok(())
+}
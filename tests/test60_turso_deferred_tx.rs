@@ -0,0 +1,100 @@
+#![cfg(feature = "turso")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::turso::{TxMode, begin_transaction_with};
+
+async fn setup(cap: &ConfigAndPool) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT);")
+        .await?;
+    Ok(conn)
+}
+
+async fn count(conn: &mut MiddlewarePoolConnection) -> Result<i64, SqlMiddlewareDbError> {
+    conn.query("SELECT COUNT(*) FROM t").select_scalar().await
+}
+
+#[tokio::test]
+async fn deferred_writes_run_at_commit() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::turso_builder(":memory:".to_string())
+        .build()
+        .await?;
+    let mut conn = setup(&cap).await?;
+
+    let MiddlewarePoolConnection::Turso {
+        conn: turso_conn, ..
+    } = &mut conn
+    else {
+        panic!("Expected Turso connection");
+    };
+    let tx = begin_transaction_with(turso_conn, TxMode::Deferred).await?;
+    let caps = tx.capabilities();
+    assert!(caps.deferred_execution);
+    assert!(!caps.reads && !caps.affected_rows && !caps.prepared_statements);
+
+    tx.execute_batch("INSERT INTO t (id, name) VALUES (1, 'ann')")
+        .await?;
+    let affected = tx
+        .query_builder("INSERT INTO t (id, name) VALUES (?1, ?2)")
+        .params((2, "bob"))
+        .dml()
+        .await?;
+    assert_eq!(affected, 0);
+    assert_eq!(tx.pending_statements(), 2);
+
+    let err = tx
+        .execute_select("SELECT COUNT(*) FROM t", &[])
+        .await
+        .expect_err("reads are rejected");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::Unimplemented(_)),
+        "{err}"
+    );
+    assert!(tx.prepare("SELECT 1").await.is_err());
+
+    tx.commit().await?;
+    assert_eq!(count(&mut conn).await?, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_deferred_batch_leaves_nothing_behind() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::turso_builder(":memory:".to_string())
+        .build()
+        .await?;
+    let mut conn = setup(&cap).await?;
+
+    let MiddlewarePoolConnection::Turso {
+        conn: turso_conn, ..
+    } = &mut conn
+    else {
+        panic!("Expected Turso connection");
+    };
+    let tx = begin_transaction_with(turso_conn, TxMode::Deferred).await?;
+    tx.execute_dml(
+        "INSERT INTO t (id, name) VALUES (?1, ?2)",
+        &[RowValues::Int(1), RowValues::Text("ann".into())],
+    )
+    .await?;
+    tx.execute_dml(
+        "INSERT INTO t (id, name) VALUES (?1, ?2)",
+        &[RowValues::Int(1), RowValues::Text("duplicate".into())],
+    )
+    .await?;
+    let err = tx.commit().await.expect_err("duplicate key");
+    assert!(err.to_string().contains("statement 2"), "{err}");
+    assert_eq!(count(&mut conn).await?, 0);
+
+    let MiddlewarePoolConnection::Turso {
+        conn: turso_conn, ..
+    } = &mut conn
+    else {
+        panic!("Expected Turso connection");
+    };
+    let tx = begin_transaction_with(turso_conn, TxMode::Deferred).await?;
+    tx.execute_batch("INSERT INTO t (id, name) VALUES (3, 'cy')")
+        .await?;
+    tx.rollback().await?;
+    assert_eq!(count(&mut conn).await?, 0);
+    Ok(())
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sql_middleware::clock::MockClock;
+use sql_middleware::jitter::{Jitter, SeededJitter};
+use sql_middleware::prelude::*;
+
+#[test]
+fn same_seed_same_sequence() {
+    let a = SeededJitter::new(7);
+    let b = SeededJitter::new(7);
+    let c = SeededJitter::new(8);
+    let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+    let second: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+    let other: Vec<u64> = (0..5).map(|_| c.next_u64()).collect();
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
+
+async fn open_breaker(name: &str, seed: u64) -> Result<Duration, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory(name)
+        .await?
+        .with_clock(Arc::new(MockClock::new()))
+        .with_jitter(Arc::new(SeededJitter::new(seed)))
+        .with_circuit_breaker(1, Duration::from_secs(30))
+        .with_circuit_breaker_jitter(0.5);
+    cap.record_result::<()>(&Err(SqlMiddlewareDbError::ConnectionError(
+        "connection reset".into(),
+    )));
+    match cap.get_connection().await {
+        Err(SqlMiddlewareDbError::CircuitOpen { retry_after }) => Ok(retry_after),
+        other => panic!("expected an open breaker, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn breaker_cool_down_is_jittered_reproducibly() -> Result<(), SqlMiddlewareDbError> {
+    let first = open_breaker("test61_a", 42).await?;
+    let again = open_breaker("test61_b", 42).await?;
+    assert_eq!(first, again);
+    assert!(first >= Duration::from_secs(30) && first <= Duration::from_secs(45));
+
+    let spread: Vec<Duration> = [
+        open_breaker("test61_c", 1).await?,
+        open_breaker("test61_d", 2).await?,
+        open_breaker("test61_e", 3).await?,
+    ]
+    .into();
+    assert!(spread.iter().any(|d| *d != first));
+    Ok(())
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn panic_in_blocking_closure_becomes_an_error() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test62_blocking?mode=memory&cache=shared".into())
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+
+    let err = conn
+        .with_blocking_sqlite(|_raw| -> Result<(), SqlMiddlewareDbError> { panic!("boom") })
+        .await
+        .expect_err("the panic should reach the caller as an error");
+    match &err {
+        SqlMiddlewareDbError::Other(message) => {
+            assert!(message.contains("panicked: boom"), "{message}");
+        }
+        other => panic!("expected Other, got {other:?}"),
+    }
+
+    // The worker is gone, so the connection refuses further work instead of reusing it.
+    assert!(conn.query("SELECT 1").select().await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn panic_in_interact_sync_becomes_an_error() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test62_interact?mode=memory&cache=shared".into())
+        .build()
+        .await?;
+    let conn = cap.get_connection().await?;
+
+    let err = conn
+        .interact_sync(|_wrapper| -> () { panic!("{}", String::from("owned boom")) })
+        .await
+        .expect_err("the panic should reach the caller as an error");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::Other(message) if message.contains("owned boom")),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_replaces_a_connection_whose_worker_panicked() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::sqlite_builder("file:test62_recycle?mode=memory&cache=shared".into())
+        .build()
+        .await?;
+    {
+        let mut conn = cap.get_connection().await?;
+        let _ = conn
+            .with_blocking_sqlite(|_raw| -> Result<(), SqlMiddlewareDbError> { panic!("boom") })
+            .await;
+    }
+
+    // Every checkout after the broken connection went back must be usable.
+    for _ in 0..8 {
+        let mut conn = cap.get_connection().await?;
+        let rows = conn.query("SELECT 1 AS one").select().await?;
+        assert_eq!(rows.results.len(), 1);
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "sqlite")]
+
+use std::panic::AssertUnwindSafe;
+
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::config::SqliteManager;
+use sql_middleware::tx_drop::{DropPolicy, dropped_transactions, set_drop_policy};
+use sql_middleware::typed_sqlite::{Idle, SqliteTypedConnection};
+use tokio::sync::Mutex;
+
+// The drop counter and policy are process-wide, so the tests in this file take turns.
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+async fn sqlite_pool(name: &str) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS t (id INTEGER PRIMARY KEY)")
+        .await?;
+    Ok(cap)
+}
+
+async fn row_count(cap: &ConfigAndPool) -> Result<i64, SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    conn.query("SELECT COUNT(*) FROM t")
+        .select_scalar::<i64>()
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dropped_transaction_is_rolled_back_and_counted() -> Result<(), Box<dyn std::error::Error>>
+{
+    let _serial = SERIAL.lock().await;
+    let cap = sqlite_pool("test63_dropped").await?;
+    let before = dropped_transactions();
+
+    {
+        let mut conn = cap.get_connection().await?;
+        let mut tx = sql_middleware::sqlite::begin_transaction(&mut conn).await?;
+        tx.execute_batch("INSERT INTO t (id) VALUES (1)").await?;
+        // Dropped here without commit or rollback.
+    }
+
+    assert_eq!(dropped_transactions(), before + 1);
+    assert_eq!(row_count(&cap).await?, 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn finished_transactions_are_not_counted() -> Result<(), Box<dyn std::error::Error>> {
+    let _serial = SERIAL.lock().await;
+    let cap = sqlite_pool("test63_finished").await?;
+    let before = dropped_transactions();
+
+    let mut conn = cap.get_connection().await?;
+    let mut tx = sql_middleware::sqlite::begin_transaction(&mut conn).await?;
+    tx.execute_batch("INSERT INTO t (id) VALUES (1)").await?;
+    tx.commit().await?;
+    let mut tx = sql_middleware::sqlite::begin_transaction(&mut conn).await?;
+    tx.execute_batch("INSERT INTO t (id) VALUES (2)").await?;
+    tx.rollback().await?;
+    drop(conn);
+
+    // Auto-commit helpers on the typed connection begin transactions internally.
+    let pool = bb8::Pool::builder()
+        .max_size(1)
+        .build(SqliteManager::new(
+            "file:test63_finished?mode=memory&cache=shared".to_string(),
+        ))
+        .await?;
+    let mut typed = SqliteTypedConnection::<Idle>::from_pool(&pool).await?;
+    typed.dml("INSERT INTO t (id) VALUES (3)", &[]).await?;
+    let mut tx = typed.begin().await?;
+    tx.dml("INSERT INTO t (id) VALUES (4)", &[]).await?;
+    tx.commit().await?;
+
+    assert_eq!(dropped_transactions(), before);
+    assert_eq!(row_count(&cap).await?, 3);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn panic_policy_names_the_call_site() -> Result<(), Box<dyn std::error::Error>> {
+    let _serial = SERIAL.lock().await;
+    let pool = bb8::Pool::builder()
+        .max_size(1)
+        .build(SqliteManager::new(
+            "file:test63_panic?mode=memory&cache=shared".to_string(),
+        ))
+        .await?;
+    let typed = SqliteTypedConnection::<Idle>::from_pool(&pool).await?;
+    let tx = typed.begin().await?;
+
+    set_drop_policy(DropPolicy::Panic);
+    let result = std::panic::catch_unwind(AssertUnwindSafe(move || drop(tx)));
+    set_drop_policy(DropPolicy::Warn);
+
+    let payload = result.expect_err("dropping under DropPolicy::Panic should panic");
+    let message = payload
+        .downcast_ref::<String>()
+        .cloned()
+        .unwrap_or_default();
+    assert!(message.contains("test63_tx_drop.rs"), "{message}");
+
+    // The rollback still ran, so the connection is usable again.
+    let mut typed = SqliteTypedConnection::<Idle>::from_pool(&pool).await?;
+    typed.execute_batch("SELECT 1").await?;
+    Ok(())
+}
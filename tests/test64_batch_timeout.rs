@@ -0,0 +1,115 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::error::BatchStopReason;
+use sql_middleware::prelude::*;
+
+/// Never finishes: counts upward forever without materializing the rows.
+const ENDLESS: &str = "INSERT INTO t SELECT max(x) FROM \
+                       (WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) SELECT x FROM c)";
+
+async fn sqlite_conn(name: &str) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (x INTEGER)").await?;
+    Ok(conn)
+}
+
+async fn row_count(conn: &mut MiddlewarePoolConnection) -> Result<i64, SqlMiddlewareDbError> {
+    conn.query("SELECT COUNT(*) FROM t")
+        .select_scalar::<i64>()
+        .await
+}
+
+#[tokio::test]
+async fn timeout_interrupts_the_running_statement_and_rolls_back()
+-> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test64_timeout").await?;
+    let script = format!("INSERT INTO t VALUES (1); {ENDLESS}; INSERT INTO t VALUES (2);");
+
+    let err = conn
+        .execute_batch_with(
+            &script,
+            QueryOptions::default().timeout(Duration::from_millis(100)),
+        )
+        .await
+        .expect_err("the endless statement should time out");
+    match err {
+        SqlMiddlewareDbError::BatchStopped {
+            index,
+            statement,
+            reason,
+            rolled_back,
+        } => {
+            assert_eq!(index, 1);
+            assert!(
+                statement.starts_with("INSERT INTO t SELECT max(x)"),
+                "{statement}"
+            );
+            assert_eq!(reason, BatchStopReason::TimedOut);
+            assert!(rolled_back);
+        }
+        other => panic!("expected BatchStopped, got {other:?}"),
+    }
+
+    // The first insert went with the implicit transaction, and the connection still works.
+    assert_eq!(row_count(&mut conn).await?, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancellation_stops_the_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test64_cancel").await?;
+    let script = format!("INSERT INTO t VALUES (1); {ENDLESS}");
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let _ = stop.send(());
+    });
+
+    let err = conn
+        .execute_batch_until(&script, QueryOptions::default(), async {
+            let _ = stopped.await;
+        })
+        .await
+        .expect_err("the batch should be cancelled");
+    assert!(
+        matches!(
+            err,
+            SqlMiddlewareDbError::BatchStopped {
+                index: 1,
+                reason: BatchStopReason::Cancelled,
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+    assert_eq!(row_count(&mut conn).await?, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_within_its_timeout_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test64_commit").await?;
+    conn.execute_batch_with(
+        "INSERT INTO t VALUES (1); INSERT INTO t VALUES (2);",
+        QueryOptions::default().timeout(Duration::from_secs(30)),
+    )
+    .await?;
+    assert_eq!(row_count(&mut conn).await?, 2);
+
+    // A failing statement still undoes the ones before it.
+    let err = conn
+        .execute_batch_with(
+            "INSERT INTO t VALUES (3); INSERT INTO missing VALUES (4);",
+            QueryOptions::default().timeout(Duration::from_secs(30)),
+        )
+        .await
+        .expect_err("the second statement should fail");
+    assert!(!matches!(err, SqlMiddlewareDbError::BatchStopped { .. }));
+    assert_eq!(row_count(&mut conn).await?, 2);
+    Ok(())
+}
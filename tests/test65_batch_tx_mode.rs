@@ -0,0 +1,132 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+async fn sqlite_conn(name: &str) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (x INTEGER)").await?;
+    Ok(conn)
+}
+
+async fn values(conn: &mut MiddlewarePoolConnection) -> Result<Vec<i64>, SqlMiddlewareDbError> {
+    let rows = conn.query("SELECT x FROM t ORDER BY x").select().await?;
+    Ok(rows
+        .results
+        .iter()
+        .filter_map(|row| row.get("x").and_then(RowValues::as_int).copied())
+        .collect())
+}
+
+fn mode(mode: BatchTxMode) -> QueryOptions {
+    QueryOptions::default().batch(BatchOptions::default().transactional(mode))
+}
+
+#[tokio::test]
+async fn single_rolls_back_the_whole_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test65_single").await?;
+    let script =
+        "INSERT INTO t VALUES (1); INSERT INTO t VALUES (2); INSERT INTO missing VALUES (3);";
+
+    assert!(
+        conn.execute_batch_with(script, mode(BatchTxMode::Single))
+            .await
+            .is_err()
+    );
+    assert!(values(&mut conn).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn autocommit_keeps_statements_before_the_failure() -> Result<(), Box<dyn std::error::Error>>
+{
+    let mut conn = sqlite_conn("test65_autocommit").await?;
+    let script = "INSERT INTO t VALUES (1); INSERT INTO t VALUES (2); INSERT INTO missing VALUES (3); \
+                  INSERT INTO t VALUES (4);";
+
+    assert!(
+        conn.execute_batch_with(script, mode(BatchTxMode::Autocommit))
+            .await
+            .is_err()
+    );
+    assert_eq!(values(&mut conn).await?, [1, 2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn script_follows_its_own_transactions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test65_script").await?;
+    let script = "BEGIN; INSERT INTO t VALUES (1); COMMIT; INSERT INTO t VALUES (2); \
+                  BEGIN; INSERT INTO t VALUES (3); INSERT INTO missing VALUES (4); COMMIT;";
+
+    assert!(
+        conn.execute_batch_with(script, mode(BatchTxMode::Script))
+            .await
+            .is_err()
+    );
+    // The failure rolled back the transaction the script had open, and nothing else.
+    assert_eq!(values(&mut conn).await?, [1, 2]);
+    conn.execute_batch("INSERT INTO t VALUES (5)").await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn managed_modes_reject_embedded_transactions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test65_reject").await?;
+    let script = "INSERT INTO t VALUES (1); BEGIN; INSERT INTO t VALUES (2); COMMIT;";
+
+    for tx_mode in [BatchTxMode::Single, BatchTxMode::Autocommit] {
+        let err = conn
+            .execute_batch_with(script, mode(tx_mode))
+            .await
+            .expect_err("BEGIN inside a managed batch should be rejected");
+        assert!(
+            matches!(&err, SqlMiddlewareDbError::ExecutionError(message) if message.contains("BEGIN")),
+            "{err}"
+        );
+    }
+    // Nothing ran.
+    assert!(values(&mut conn).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn plain_execute_batch_is_single() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test65_plain").await?;
+
+    assert!(
+        conn.execute_batch("INSERT INTO t VALUES (1); INSERT INTO missing VALUES (2);")
+            .await
+            .is_err()
+    );
+    assert!(values(&mut conn).await?.is_empty());
+
+    let err = conn
+        .execute_batch("BEGIN; INSERT INTO t VALUES (1); COMMIT;")
+        .await
+        .expect_err("BEGIN inside the default batch should be rejected");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::ExecutionError(_)),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn plain_execute_batch_joins_an_open_transaction() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test65_join").await?;
+
+    let result: Result<(), SqlMiddlewareDbError> = conn
+        .transaction(async |conn| {
+            conn.execute_batch("INSERT INTO t VALUES (1); INSERT INTO t VALUES (2);")
+                .await?;
+            Err(SqlMiddlewareDbError::ExecutionError("abandon".into()))
+        })
+        .await;
+    assert!(result.is_err());
+    // The batch did not commit on its own; the enclosing rollback undid it.
+    assert!(values(&mut conn).await?.is_empty());
+    Ok(())
+}
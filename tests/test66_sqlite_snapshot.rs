@@ -0,0 +1,83 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+async fn row_count(conn: &mut MiddlewarePoolConnection) -> Result<i64, SqlMiddlewareDbError> {
+    conn.query("SELECT COUNT(*) FROM t")
+        .select_scalar::<i64>()
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn snapshot_reads_stay_consistent_while_writers_commit()
+-> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("snapshot.db");
+    let cap = ConfigAndPool::sqlite_builder(path.to_string_lossy().into_owned())
+        .build()
+        .await?;
+    let mut writer = cap.get_connection().await?;
+    writer
+        .with_blocking_sqlite(|raw| {
+            raw.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+            raw.execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);")?;
+            Ok(())
+        })
+        .await?;
+
+    let mut reader = cap.get_connection().await?;
+    let mut snapshot = reader.snapshot().await?;
+    assert_eq!(
+        snapshot
+            .query("SELECT COUNT(*) FROM t")
+            .select_scalar::<i64>()
+            .await?,
+        1
+    );
+
+    // The writer is not blocked, and the snapshot does not see its commit.
+    writer.execute_batch("INSERT INTO t VALUES (2)").await?;
+    assert_eq!(row_count(&mut writer).await?, 2);
+    let rows = snapshot.select("SELECT x FROM t", &[]).await?;
+    assert_eq!(rows.results.len(), 1);
+
+    assert!(
+        snapshot
+            .query("INSERT INTO t VALUES (3)")
+            .dml()
+            .await
+            .is_err(),
+        "writes through a snapshot should be rejected"
+    );
+    snapshot.release().await?;
+
+    // Released: the connection sees the latest state and can write again.
+    assert_eq!(row_count(&mut reader).await?, 2);
+    reader.execute_batch("INSERT INTO t VALUES (3)").await?;
+
+    // Dropping an unreleased snapshot ends it too.
+    drop(reader.snapshot().await?);
+    reader.execute_batch("INSERT INTO t VALUES (4)").await?;
+    assert_eq!(row_count(&mut writer).await?, 4);
+    Ok(())
+}
+
+#[tokio::test]
+async fn snapshot_requires_wal() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test66_memory?mode=memory&cache=shared".into())
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    let err = conn
+        .snapshot()
+        .await
+        .err()
+        .expect("an in-memory database cannot use WAL");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::ExecutionError(message) if message.contains("WAL")),
+        "{err}"
+    );
+    // The connection is still usable.
+    conn.execute_batch("CREATE TABLE t (x INTEGER)").await?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::schema::{ColumnType, diff_schemas, drift, introspect};
+
+async fn sqlite_pool(name: &str, ddl: &str) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(ddl).await?;
+    Ok(cap)
+}
+
+#[tokio::test]
+async fn introspect_lists_tables_and_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = sqlite_pool(
+        "test67_introspect",
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR(255) NOT NULL, \
+                             active BOOLEAN, created TIMESTAMP);
+         CREATE VIEW active_users AS SELECT * FROM users WHERE active;",
+    )
+    .await?;
+    let schema = introspect(&mut cap.get_connection().await?).await?;
+
+    assert_eq!(schema.tables.len(), 1, "views are not tables");
+    let users = schema.table("USERS").expect("lookup ignores case");
+    let summary: Vec<_> = users
+        .columns
+        .iter()
+        .map(|column| {
+            (
+                column.name.as_str(),
+                column.column_type.clone(),
+                column.nullable,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("id", ColumnType::Integer, false),
+            ("email", ColumnType::Text, false),
+            ("active", ColumnType::Boolean, true),
+            ("created", ColumnType::Timestamp, true),
+        ]
+    );
+    assert_eq!(users.column("email").unwrap().declared_type, "VARCHAR(255)");
+    Ok(())
+}
+
+#[tokio::test]
+async fn drift_reports_structural_differences() -> Result<(), Box<dyn std::error::Error>> {
+    let local = sqlite_pool(
+        "test67_local",
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL, score REAL);
+         CREATE TABLE audit (id INTEGER);",
+    )
+    .await?;
+    let prod = sqlite_pool(
+        "test67_prod",
+        "CREATE TABLE Users (ID BIGINT PRIMARY KEY, email TEXT, score TEXT, nickname TEXT);
+         CREATE TABLE sessions (id INTEGER);",
+    )
+    .await?;
+
+    let drift = drift(&local, &prod).await?;
+    assert_eq!(drift.missing_tables, ["audit"]);
+    assert_eq!(drift.extra_tables, ["sessions"]);
+    assert!(drift.missing_columns.is_empty());
+    assert_eq!(
+        drift.extra_columns,
+        [("users".to_string(), "nickname".to_string())]
+    );
+    // INTEGER and BIGINT are the same family; REAL and TEXT are not.
+    assert_eq!(drift.type_mismatches.len(), 1);
+    assert_eq!(drift.type_mismatches[0].left.name, "score");
+    assert_eq!(drift.type_mismatches[0].right.column_type, ColumnType::Text);
+    assert_eq!(drift.nullability_mismatches.len(), 1);
+    assert_eq!(drift.nullability_mismatches[0].left.name, "email");
+
+    // A schema never drifts from itself.
+    let schema = introspect(&mut local.get_connection().await?).await?;
+    assert!(diff_schemas(&schema, &schema).is_empty());
+    Ok(())
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::{Arc, Mutex};
+
+use sql_middleware::prelude::*;
+use sql_middleware::sync::{CopyOptions, CopyProgress, copy_table};
+
+async fn sqlite_conn(
+    name: &str,
+    ddl: &str,
+) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(ddl).await?;
+    Ok(conn)
+}
+
+#[tokio::test]
+async fn copies_every_row_in_batches() -> Result<(), Box<dyn std::error::Error>> {
+    let mut src = sqlite_conn(
+        "test68_src",
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL, active BOOLEAN);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2500)
+         INSERT INTO items SELECT i, 'item ' || i, i * 1.5, i % 2 FROM n;",
+    )
+    .await?;
+    let mut dst = sqlite_conn(
+        "test68_dst",
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL, active BOOLEAN);",
+    )
+    .await?;
+
+    let seen = Arc::new(Mutex::new(Vec::<CopyProgress>::new()));
+    let recorder = Arc::clone(&seen);
+    let options = CopyOptions::default()
+        .batch_rows(1000)
+        .on_progress(move |progress| recorder.lock().unwrap().push(progress));
+    let copied = copy_table(&mut src, &mut dst, "items", options).await?;
+
+    assert_eq!(copied, 2500);
+    let seen = seen.lock().unwrap().clone();
+    let rows: Vec<_> = seen.iter().map(|progress| progress.rows_copied).collect();
+    assert_eq!(rows, [1000, 2000, 2500]);
+    assert_eq!(seen.last().unwrap().batches, 3);
+
+    let summary = dst
+        .query("SELECT COUNT(*) AS n, SUM(price) AS total, SUM(active) AS active FROM items")
+        .select_one()
+        .await?
+        .expect("one row");
+    assert_eq!(summary.get("n"), Some(&RowValues::Int(2500)));
+    assert_eq!(summary.get("total"), Some(&RowValues::Float(4_689_375.0)));
+    assert_eq!(summary.get("active"), Some(&RowValues::Int(1250)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn copies_selected_columns_into_another_table() -> Result<(), Box<dyn std::error::Error>> {
+    let mut src = sqlite_conn(
+        "test68_columns_src",
+        "CREATE TABLE users (id INTEGER, email TEXT, password_hash TEXT);
+         INSERT INTO users VALUES (1, 'a@example.com', 'x'), (2, 'b@example.com', 'y');",
+    )
+    .await?;
+    let mut dst = sqlite_conn(
+        "test68_columns_dst",
+        "CREATE TABLE contacts (id INTEGER, email TEXT);",
+    )
+    .await?;
+
+    let options = CopyOptions::default()
+        .columns(&["id", "email"])
+        .into_table("contacts");
+    assert_eq!(copy_table(&mut src, &mut dst, "users", options).await?, 2);
+    let emails = dst
+        .query("SELECT email FROM contacts ORDER BY id")
+        .select()
+        .await?;
+    assert_eq!(
+        emails.results[1].get("email").and_then(RowValues::as_text),
+        Some("b@example.com")
+    );
+
+    // Nothing to copy into.
+    let err = copy_table(
+        &mut src,
+        &mut dst,
+        "users",
+        CopyOptions::default().into_table("missing"),
+    )
+    .await
+    .expect_err("the destination table does not exist");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::ExecutionError(message) if message.contains("missing")),
+        "{err}"
+    );
+    Ok(())
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::test_helpers::TestTransaction;
+use sql_middleware::tx_drop::dropped_transactions;
+
+async fn sqlite_pool(name: &str) -> Result<ConfigAndPool, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        .await?;
+    Ok(cap)
+}
+
+async fn count(tx: &mut TestTransaction<'_>) -> Result<i64, SqlMiddlewareDbError> {
+    tx.query("SELECT COUNT(*) FROM t")
+        .select_scalar::<i64>()
+        .await
+}
+
+async fn committed_rows(cap: &ConfigAndPool) -> Result<i64, SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+    conn.query("SELECT COUNT(*) FROM t")
+        .select_scalar::<i64>()
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inner_commits_become_savepoints_and_everything_rolls_back()
+-> Result<(), Box<dyn std::error::Error>> {
+    let cap = sqlite_pool("test69_rollback").await?;
+    let mut conn = cap.get_connection().await?;
+    let mut tx = TestTransaction::begin(&mut conn).await?;
+
+    tx.execute_batch("INSERT INTO t (id) VALUES (1)").await?;
+    tx.transaction(async |tx| {
+        tx.execute_batch("INSERT INTO t (id) VALUES (2)").await?;
+        // A nested failure undoes only its own savepoint.
+        let failed = tx
+            .transaction(async |tx| {
+                tx.execute_batch("INSERT INTO t (id) VALUES (3)").await?;
+                tx.execute_batch("INSERT INTO t (id) VALUES (1)").await
+            })
+            .await;
+        assert!(failed.is_err(), "duplicate key should fail");
+        Ok(())
+    })
+    .await?;
+    assert_eq!(count(&mut tx).await?, 2);
+
+    tx.rollback().await?;
+    drop(conn);
+    assert_eq!(committed_rows(&cap).await?, 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dropping_rolls_back_without_reporting() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = sqlite_pool("test69_drop").await?;
+    let before = dropped_transactions();
+    {
+        let mut conn = cap.get_connection().await?;
+        let mut tx = TestTransaction::begin(&mut conn).await?;
+        tx.query("INSERT INTO t (id) VALUES (?1)")
+            .params(&[RowValues::Int(1)])
+            .dml()
+            .await?;
+    }
+    assert_eq!(committed_rows(&cap).await?, 0);
+    assert_eq!(dropped_transactions(), before);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+#![cfg(all(feature = "sqlite", feature = "json"))]
+
+use std::fs;
+
+use sql_middleware::prelude::*;
+use sql_middleware::schema::foreign_keys;
+use sql_middleware::test_helpers::fixtures;
+
+async fn sqlite_conn(name: &str) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    cap.get_connection().await
+}
+
+#[tokio::test]
+async fn loads_parents_before_children() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    fs::write(
+        dir.path().join("00_schema.sql"),
+        "PRAGMA foreign_keys = ON;
+         CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, active BOOLEAN DEFAULT 1);
+         CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL REFERENCES users(id),
+                              placed TIMESTAMP, meta JSON);
+         CREATE TABLE lines (order_id INTEGER REFERENCES orders(id), sku TEXT);",
+    )?;
+    // Sorted by file name, the children come first.
+    fs::write(
+        dir.path().join("a_lines.json"),
+        r#"{ "lines": [{ "order_id": 10, "sku": "widget" }, { "order_id": 11, "sku": "gadget" }] }"#,
+    )?;
+    fs::write(
+        dir.path().join("b_orders.json"),
+        r#"{ "orders": [
+            { "id": 10, "user_id": 1, "placed": "2024-01-02 03:04:05", "meta": { "gift": true } },
+            { "id": 11, "user_id": 2, "placed": null, "meta": null }
+        ] }"#,
+    )?;
+    fs::write(
+        dir.path().join("c_users.json"),
+        r#"{ "users": [{ "id": 1, "name": "ann" }, { "id": 2, "name": "bob", "active": false }] }"#,
+    )?;
+    fs::write(dir.path().join("notes.txt"), "not a fixture")?;
+
+    let mut conn = sqlite_conn("test70_order").await?;
+    let loaded = fixtures::load(&mut conn, dir.path()).await?;
+    assert_eq!(
+        loaded,
+        [
+            ("users".to_string(), 2),
+            ("orders".to_string(), 2),
+            ("lines".to_string(), 2)
+        ]
+    );
+
+    let keys = foreign_keys(&mut conn).await?;
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].table, "lines");
+    assert_eq!(keys[0].referenced_table, "orders");
+
+    let users = conn
+        .query("SELECT name, active FROM users ORDER BY id")
+        .select()
+        .await?;
+    // An omitted column keeps its default.
+    assert_eq!(users.results[0].get("active"), Some(&RowValues::Int(1)));
+    assert_eq!(users.results[1].get("active"), Some(&RowValues::Int(0)));
+    let meta = conn
+        .query("SELECT meta FROM orders WHERE id = 10")
+        .select_scalar::<String>()
+        .await?;
+    assert_eq!(meta, r#"{"gift":true}"#);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_unknown_tables_and_cycles() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test70_errors").await?;
+    conn.execute_batch(
+        "CREATE TABLE a (id INTEGER PRIMARY KEY, b_id INTEGER REFERENCES b(id));
+         CREATE TABLE b (id INTEGER PRIMARY KEY, a_id INTEGER REFERENCES a(id));",
+    )
+    .await?;
+
+    let dir = tempfile::tempdir()?;
+    fs::write(
+        dir.path().join("data.json"),
+        r#"{ "a": [{ "id": 1 }], "b": [{ "id": 1 }] }"#,
+    )?;
+    let err = fixtures::load(&mut conn, dir.path())
+        .await
+        .expect_err("a and b reference each other");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::ConfigError(message) if message.contains("cycle")),
+        "{err}"
+    );
+
+    fs::write(
+        dir.path().join("data.json"),
+        r#"{ "missing": [{ "id": 1 }] }"#,
+    )?;
+    let err = fixtures::load(&mut conn, dir.path())
+        .await
+        .expect_err("there is no such table");
+    assert!(
+        matches!(&err, SqlMiddlewareDbError::ConfigError(message) if message.contains("missing")),
+        "{err}"
+    );
+    Ok(())
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::begin_transaction;
+
+async fn sqlite_conn(name: &str) -> Result<MiddlewarePoolConnection, SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::sqlite_builder(format!("file:{name}?mode=memory&cache=shared"))
+        .build()
+        .await?;
+    cap.get_connection().await
+}
+
+#[tokio::test]
+async fn run_dispatches_on_statement_kind() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test71_dispatch").await?;
+
+    let outcome = conn
+        .query(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO t (name) VALUES ('ann'), ('bob');",
+        )
+        .run()
+        .await?;
+    assert!(matches!(outcome, QueryOutcome::Batch));
+
+    let outcome = conn
+        .query("UPDATE t SET name = 'select' WHERE id = ?1")
+        .params(&[RowValues::Int(1)])
+        .run()
+        .await?;
+    assert_eq!(outcome.rows_affected(), Some(1));
+
+    let outcome = conn
+        .query("-- leading comment\nSELECT name FROM t ORDER BY id")
+        .run()
+        .await?;
+    let rows = outcome.rows().expect("a query returns rows");
+    assert_eq!(rows.results.len(), 2);
+    assert_eq!(
+        rows.results[0].get("name").and_then(RowValues::as_text),
+        Some("select")
+    );
+
+    let outcome = conn
+        .query("INSERT INTO t (name) VALUES (?1) RETURNING id")
+        .params(&[RowValues::Text("cy".into())])
+        .run()
+        .await?;
+    assert_eq!(
+        outcome
+            .rows()
+            .map(|rows| rows.results[0].get("id").cloned()),
+        Some(Some(RowValues::Int(3)))
+    );
+
+    let err = conn
+        .query("DELETE FROM t WHERE id = ?1; DELETE FROM t WHERE id = ?2")
+        .params(&[RowValues::Int(1), RowValues::Int(2)])
+        .run()
+        .await
+        .expect_err("a batch cannot bind parameters");
+    assert!(
+        matches!(err, SqlMiddlewareDbError::ParameterError(_)),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn run_batches_inside_a_transaction() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = sqlite_conn("test71_tx").await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        .await?;
+
+    let mut tx = begin_transaction(&mut conn).await?;
+    let outcome = tx
+        .query_builder("INSERT INTO t VALUES (1); INSERT INTO t VALUES (2);")
+        .run()
+        .await?;
+    assert!(matches!(outcome, QueryOutcome::Batch));
+    tx.rollback().await?;
+
+    let count: i64 = conn.query("SELECT COUNT(*) FROM t").select_scalar().await?;
+    assert_eq!(count, 0);
+    Ok(())
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+use sql_middleware::test_helpers::explain_plan;
+
+#[tokio::test]
+async fn case_insensitive_lookup_uses_its_index() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test72_nocase").await?;
+    let mut conn = cap.get_connection().await?;
+    let db_type = conn.database_type();
+    assert!(db_type.collation().case_sensitive_by_default);
+    assert_eq!(
+        db_type.collation().case_insensitive_collation,
+        Some("NOCASE")
+    );
+
+    conn.execute_batch(&format!(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, handle {});
+         {};
+         INSERT INTO users (email, handle) VALUES ('Ann@Example.com', 'Ann');",
+        db_type.case_insensitive_column("TEXT"),
+        db_type.case_insensitive_index("users_email_ci", "users", "email")?,
+    ))
+    .await?;
+
+    let exact: Option<i64> = conn
+        .query("SELECT id FROM users WHERE email = ?1")
+        .params(("ann@example.com",))
+        .select_scalar()
+        .await
+        .ok();
+    assert_eq!(exact, None, "plain = is case-sensitive on SQLite");
+
+    let sql = format!(
+        "SELECT id FROM users WHERE {}",
+        QueryBuilder::case_insensitive_compare(&db_type, "email", "?1")
+    );
+    let id: i64 = conn
+        .query(&sql)
+        .params(("ann@example.com",))
+        .select_scalar()
+        .await?;
+    assert_eq!(id, 1);
+    let plan = explain_plan(&mut conn, &sql, &[RowValues::Text("x".into())]).await?;
+    assert!(plan.contains("users_email_ci"), "{plan}");
+
+    // A case-insensitive column needs no special comparison.
+    let id: i64 = conn
+        .query("SELECT id FROM users WHERE handle = ?1")
+        .params(("ANN",))
+        .select_scalar()
+        .await?;
+    assert_eq!(id, 1);
+    Ok(())
+}
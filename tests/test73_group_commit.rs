@@ -0,0 +1,162 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::Duration;
+
+use sql_middleware::prelude::*;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_writes_share_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test73_counters").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE counters (name TEXT PRIMARY KEY, hits INTEGER NOT NULL);
+         INSERT INTO counters VALUES ('home', 0), ('about', 0);",
+    )
+    .await?;
+
+    let writes = cap.group_commit(Duration::from_millis(20)).max_batch(16);
+    let mut tasks = Vec::new();
+    for i in 0..60 {
+        let writes = writes.clone();
+        let name = if i % 3 == 0 { "about" } else { "home" };
+        tasks.push(tokio::spawn(async move {
+            writes
+                .dml(
+                    "UPDATE counters SET hits = hits + 1 WHERE name = ?1",
+                    &[RowValues::Text(name.into())],
+                )
+                .await
+        }));
+    }
+    for task in tasks {
+        assert_eq!(task.await??, 1);
+    }
+
+    let home: i64 = conn
+        .query("SELECT hits FROM counters WHERE name = 'home'")
+        .select_scalar()
+        .await?;
+    let about: i64 = conn
+        .query("SELECT hits FROM counters WHERE name = 'about'")
+        .select_scalar()
+        .await?;
+    assert_eq!((home, about), (40, 20));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_failing_write_does_not_sink_its_group() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test73_failure").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT NOT NULL)")
+        .await?;
+
+    let writes = cap.group_commit(Duration::from_millis(20));
+    let insert = |id: i64, kind: Option<&'static str>| {
+        let writes = writes.clone();
+        tokio::spawn(async move {
+            let kind = kind.map_or(RowValues::Null, |kind| RowValues::Text(kind.into()));
+            writes
+                .dml(
+                    "INSERT INTO events (id, kind) VALUES (?1, ?2)",
+                    &[RowValues::Int(id), kind],
+                )
+                .await
+        })
+    };
+    let ok_a = insert(1, Some("click"));
+    let bad = insert(2, None);
+    let ok_b = insert(3, Some("view"));
+
+    assert_eq!(ok_a.await??, 1);
+    assert_eq!(ok_b.await??, 1);
+    let err = bad.await?.expect_err("NOT NULL violation");
+    assert!(err.to_string().contains("NOT NULL"), "{err}");
+
+    let ids = conn
+        .query("SELECT id FROM events ORDER BY id")
+        .select()
+        .await?;
+    let ids: Vec<_> = ids
+        .results
+        .iter()
+        .filter_map(|row| row.get("id").and_then(RowValues::as_int).copied())
+        .collect();
+    assert_eq!(ids, vec![1, 3]);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_cancelled_leader_hands_its_group_on() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test73_cancel").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT NOT NULL)")
+        .await?;
+
+    let writes = cap.group_commit(Duration::from_millis(200));
+    let insert = |id: i64| {
+        let writes = writes.clone();
+        tokio::spawn(async move {
+            writes
+                .dml(
+                    "INSERT INTO events (id, kind) VALUES (?1, 'click')",
+                    &[RowValues::Int(id)],
+                )
+                .await
+        })
+    };
+    let leader = insert(1);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let follower = insert(2);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    // Cancel the leader while it waits out the window with the follower queued behind it.
+    leader.abort();
+    assert!(leader.await.expect_err("leader was aborted").is_cancelled());
+
+    assert_eq!(follower.await??, 1);
+    let ids = conn
+        .query("SELECT id FROM events ORDER BY id")
+        .select()
+        .await?;
+    let ids: Vec<_> = ids
+        .results
+        .iter()
+        .filter_map(|row| row.get("id").and_then(RowValues::as_int).copied())
+        .collect();
+    assert_eq!(ids, vec![2]);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_leader_flushes_one_batch_then_hands_on() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test73_handoff").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT NOT NULL)")
+        .await?;
+
+    let writes = cap.group_commit(Duration::from_millis(20)).max_batch(1);
+    let insert = |id: i64, sql: &'static str| {
+        let writes = writes.clone();
+        tokio::spawn(async move { writes.dml(sql, &[RowValues::Int(id)]).await })
+    };
+    let leader = insert(1, "INSERT INTO events (id, kind) VALUES (?1, 'click')");
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    // Slow writes queued behind the leader; it should not stay to flush them.
+    let slow = "INSERT INTO events (id, kind) \
+                SELECT ?1, count(*) FROM (WITH RECURSIVE c(x) AS \
+                (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 300000) SELECT x FROM c)";
+    let followers: Vec<_> = (2..5).map(|id| insert(id, slow)).collect();
+
+    assert_eq!(leader.await??, 1);
+    let finished = followers.iter().filter(|task| task.is_finished()).count();
+    assert_eq!(finished, 0, "the leader waited for writes it did not own");
+    for follower in followers {
+        assert_eq!(follower.await??, 1);
+    }
+    let count: i64 = conn
+        .query("SELECT count(*) FROM events")
+        .select_scalar()
+        .await?;
+    assert_eq!(count, 4);
+    Ok(())
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::pool::{PoolOptions, ResetMode};
+use sql_middleware::prelude::*;
+
+// bb8's default max_size for the SQLite pool.
+const DEFAULT_POOL_SIZE: usize = 10;
+
+async fn temp_objects(conn: &mut MiddlewarePoolConnection) -> Result<i64, SqlMiddlewareDbError> {
+    conn.query("SELECT COUNT(*) FROM temp.sqlite_master")
+        .select_scalar()
+        .await
+}
+
+#[tokio::test]
+async fn reset_rolls_back_and_drops_temp_objects() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test74_reset").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE t (id INTEGER PRIMARY KEY);
+         CREATE TEMP TABLE \"odd.name\" (id INTEGER);
+         CREATE TEMP VIEW v AS SELECT id FROM \"odd.name\";
+         CREATE TEMP TRIGGER trg AFTER INSERT ON t BEGIN SELECT 1; END;",
+    )
+    .await?;
+    // Raw SQL left a transaction open.
+    conn.with_blocking_sqlite(|raw| {
+        raw.execute_batch("BEGIN; INSERT INTO t VALUES (1);")?;
+        Ok::<_, SqlMiddlewareDbError>(())
+    })
+    .await?;
+
+    conn.reset_session().await?;
+
+    assert_eq!(temp_objects(&mut conn).await?, 0);
+    let rows: i64 = conn.query("SELECT COUNT(*) FROM t").select_scalar().await?;
+    assert_eq!(rows, 0);
+    conn.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn reset_on_return_cleans_dropped_connections() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test74_on_return")
+        .await?
+        .with_pool_options(PoolOptions::default().reset_on_return(ResetMode::Fast));
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TEMP TABLE scratch (id INTEGER)")
+        .await?;
+    // Dropped without `close`.
+    drop(conn);
+
+    let mut held = Vec::with_capacity(DEFAULT_POOL_SIZE);
+    for _ in 0..DEFAULT_POOL_SIZE {
+        let mut conn = cap.get_connection().await?;
+        assert_eq!(temp_objects(&mut conn).await?, 0);
+        held.push(conn);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn full_reset_restores_pragmas() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test74_full").await?;
+    let mut conn = cap.get_connection().await?;
+    let pragmas = async |conn: &mut MiddlewarePoolConnection| {
+        conn.with_blocking_sqlite(|raw| {
+            let foreign_keys: i64 = raw.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+            let query_only: i64 = raw.query_row("PRAGMA query_only", [], |row| row.get(0))?;
+            Ok::<_, SqlMiddlewareDbError>((foreign_keys, query_only))
+        })
+        .await
+    };
+    let original = pragmas(&mut conn).await?;
+    // Flip each pragma: bundled SQLite may start with `foreign_keys` on.
+    let (foreign_keys, query_only) = (1 - original.0, 1 - original.1);
+    // `execute_batch` runs in a transaction, where `foreign_keys` cannot change.
+    conn.with_blocking_sqlite(move |raw| {
+        raw.execute_batch(&format!(
+            "PRAGMA foreign_keys = {foreign_keys}; PRAGMA query_only = {query_only};"
+        ))?;
+        Ok::<_, SqlMiddlewareDbError>(())
+    })
+    .await?;
+    let changed = pragmas(&mut conn).await?;
+    assert_eq!(changed, (foreign_keys, query_only));
+
+    // Fast leaves pragmas alone.
+    conn.reset_session_with(ResetMode::Fast).await?;
+    assert_eq!(pragmas(&mut conn).await?, changed);
+
+    conn.reset_session_with(ResetMode::Full).await?;
+    assert_eq!(pragmas(&mut conn).await?, original);
+    Ok(())
+}
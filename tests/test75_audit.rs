@@ -0,0 +1,158 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::audit::{self, AUDIT_TABLE, AuditTables};
+use sql_middleware::pool::PoolOptions;
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::begin_transaction;
+use sql_middleware::sqlite::config::SqliteManager;
+use sql_middleware::typed_sqlite::{Idle, SqliteTypedConnection};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn audits_allowlisted_dml() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::sqlite_builder("file:test75_audit?mode=memory&cache=shared".into())
+        .build()
+        .await?
+        .with_pool_options(PoolOptions::default().audit(AuditTables::new(["Accounts"])));
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER);
+         CREATE TABLE scratch (id INTEGER PRIMARY KEY);",
+    )
+    .await?;
+    audit::create_table(&mut conn).await?;
+
+    conn.query("INSERT INTO accounts (id, balance) VALUES (1, 100), (2, 50)")
+        .actor("alice")
+        .dml()
+        .await?;
+    conn.query("UPDATE accounts SET balance = balance - ?1 WHERE id = ?2")
+        .params((10, 1))
+        .dml()
+        .await?;
+    conn.query("INSERT INTO scratch (id) VALUES (1)")
+        .dml()
+        .await?;
+
+    // An audited statement that rolls back takes its audit row with it.
+    let mut tx = begin_transaction(&mut conn).await?;
+    tx.query_builder("DELETE FROM accounts")
+        .actor("bob")
+        .dml()
+        .await?;
+    tx.rollback().await?;
+
+    let rows = conn
+        .query(&format!(
+            "SELECT table_name, op, rows_affected, actor, statement FROM {AUDIT_TABLE} ORDER BY id"
+        ))
+        .select()
+        .await?;
+
+    let summary: Vec<_> = rows
+        .results
+        .iter()
+        .map(|row| {
+            (
+                row.get("table_name")
+                    .and_then(RowValues::as_text)
+                    .map(str::to_owned),
+                row.get("op")
+                    .and_then(RowValues::as_text)
+                    .map(str::to_owned),
+                row.get("rows_affected")
+                    .and_then(RowValues::as_int)
+                    .copied(),
+                row.get("actor")
+                    .and_then(RowValues::as_text)
+                    .map(str::to_owned),
+            )
+        })
+        .collect();
+    let text = |s: &str| Some(s.to_owned());
+    assert_eq!(
+        summary,
+        vec![
+            (text("accounts"), text("INSERT"), Some(2), text("alice")),
+            (text("accounts"), text("UPDATE"), Some(1), None),
+        ]
+    );
+    assert_eq!(
+        rows.results[1]
+            .get("statement")
+            .and_then(RowValues::as_text),
+        Some("UPDATE accounts SET balance = balance - ?1 WHERE id = ?2")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn auditing_is_per_pool() -> Result<(), Box<dyn std::error::Error>> {
+    let uri = "file:test75_per_pool?mode=memory&cache=shared";
+    let audited = ConfigAndPool::sqlite_builder(uri.into())
+        .build()
+        .await?
+        .with_pool_options(PoolOptions::default().audit(AuditTables::new(["accounts"])));
+    let plain = ConfigAndPool::sqlite_builder(uri.into()).build().await?;
+    let mut conn = audited.get_connection().await?;
+    conn.execute_batch("CREATE TABLE accounts (id INTEGER PRIMARY KEY)")
+        .await?;
+    audit::create_table(&mut conn).await?;
+
+    conn.query("INSERT INTO accounts (id) VALUES (1)")
+        .dml()
+        .await?;
+    plain
+        .get_connection()
+        .await?
+        .query("INSERT INTO accounts (id) VALUES (2)")
+        .dml()
+        .await?;
+
+    let audited_rows: i64 = conn
+        .query(&format!("SELECT COUNT(*) FROM {AUDIT_TABLE}"))
+        .select_scalar()
+        .await?;
+    assert_eq!(audited_rows, 1);
+    Ok(())
+}
+
+async fn count(
+    conn: &mut SqliteTypedConnection<Idle>,
+    table: &str,
+) -> Result<i64, SqlMiddlewareDbError> {
+    conn.query(&format!("SELECT COUNT(*) FROM {table}"))
+        .select_scalar()
+        .await
+}
+
+#[tokio::test]
+async fn typed_connections_audit_atomically() -> Result<(), Box<dyn std::error::Error>> {
+    let pool = SqliteManager::new("file:test75_typed?mode=memory&cache=shared".to_string())
+        .build_pool()
+        .await?;
+    let tables = AuditTables::new(["accounts"]);
+    let mut conn = SqliteTypedConnection::<Idle>::from_pool(&pool).await?;
+    conn.execute_batch("CREATE TABLE accounts (id INTEGER PRIMARY KEY)")
+        .await?;
+
+    // Without the audit table the audit insert fails, and the statement rolls back with it.
+    conn.query("INSERT INTO accounts (id) VALUES (1)")
+        .audit(&tables)
+        .dml()
+        .await
+        .expect_err("audit table is missing");
+    assert_eq!(count(&mut conn, "accounts").await?, 0);
+
+    conn.execute_batch(&format!(
+        "CREATE TABLE {AUDIT_TABLE} (id INTEGER PRIMARY KEY AUTOINCREMENT, table_name TEXT, \
+         op TEXT, statement TEXT, rows_affected BIGINT, actor TEXT, recorded_at_ms BIGINT)"
+    ))
+    .await?;
+    conn.query("INSERT INTO accounts (id) VALUES (1)")
+        .audit(&tables)
+        .dml()
+        .await?;
+    assert_eq!(count(&mut conn, "accounts").await?, 1);
+    assert_eq!(count(&mut conn, AUDIT_TABLE).await?, 1);
+    Ok(())
+}
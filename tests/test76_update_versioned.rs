@@ -0,0 +1,61 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn versioned_update_rejects_stale_writes() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test76_versioned").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER, version INTEGER);
+         INSERT INTO accounts VALUES (1, 100, 1);",
+    )
+    .await?;
+
+    let version = conn
+        .update_versioned(
+            "accounts",
+            ("id", RowValues::Int(1)),
+            1,
+            &[("balance", RowValues::Int(90))],
+        )
+        .await?;
+    assert_eq!(version, 2);
+
+    // A writer still holding version 1 loses.
+    let err = conn
+        .update_versioned(
+            "accounts",
+            ("id", RowValues::Int(1)),
+            1,
+            &[("balance", RowValues::Int(80))],
+        )
+        .await
+        .expect_err("version 1 is stale");
+    assert!(
+        matches!(
+            err,
+            SqlMiddlewareDbError::StaleVersion {
+                expected: 1,
+                key: RowValues::Int(1),
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let row = conn
+        .query("SELECT balance, version FROM accounts WHERE id = 1")
+        .select_one()
+        .await?
+        .expect("row 1 exists");
+    assert_eq!(row.get("balance"), Some(&RowValues::Int(90)));
+    assert_eq!(row.get("version"), Some(&RowValues::Int(2)));
+
+    let err = conn
+        .update_versioned("accounts", ("id", RowValues::Int(2)), 1, &[])
+        .await
+        .expect_err("no such row");
+    assert!(matches!(err, SqlMiddlewareDbError::StaleVersion { .. }));
+    Ok(())
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::pool::PoolOptions;
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn soft_deleted_rows_are_filtered() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test77_soft_delete")
+        .await?
+        .with_pool_options(PoolOptions::default().soft_delete_column("removed_at"));
+    let mut conn = cap.get_connection().await?;
+    assert_eq!(conn.soft_delete_column(), "removed_at");
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, team TEXT, removed_at TEXT);
+         INSERT INTO users (id, team) VALUES (1, 'blue'), (2, 'blue'), (3, 'red');",
+    )
+    .await?;
+
+    assert_eq!(
+        conn.soft_delete("users", ("id", RowValues::Int(2))).await?,
+        1
+    );
+    // Already deleted: nothing left to mark.
+    assert_eq!(
+        conn.soft_delete("users", ("id", RowValues::Int(2))).await?,
+        0
+    );
+
+    let ids = |rows: &ResultSet| -> Vec<i64> {
+        rows.results
+            .iter()
+            .filter_map(|row| row.get("id").and_then(RowValues::as_int).copied())
+            .collect()
+    };
+    let live = conn
+        .query("SELECT id FROM users WHERE team = ?1 OR team = ?2 ORDER BY id")
+        .params(("blue", "red"))
+        .filter_deleted(false)
+        .select()
+        .await?;
+    assert_eq!(ids(&live), [1, 3]);
+
+    let deleted = conn
+        .query("SELECT id FROM users")
+        .filter_deleted(true)
+        .select()
+        .await?;
+    assert_eq!(ids(&deleted), [2]);
+
+    let updated = conn
+        .query("UPDATE users SET team = 'green'")
+        .filter_deleted(false)
+        .dml()
+        .await?;
+    assert_eq!(updated, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unfilterable_statements_fail_instead_of_running_unfiltered()
+-> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test77_unfilterable").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, deleted_at TEXT);
+         INSERT INTO users (id, name, deleted_at) VALUES (1, 'ann', NULL), (2, 'bob', 'x');",
+    )
+    .await?;
+
+    // A function named like a statement keyword does not stop the filter.
+    let names: Vec<String> = conn
+        .query("SELECT replace(name, 'a', 'e') AS name FROM users")
+        .filter_deleted(false)
+        .select()
+        .await?
+        .results
+        .iter()
+        .filter_map(|row| {
+            row.get("name")
+                .and_then(RowValues::as_text)
+                .map(str::to_owned)
+        })
+        .collect();
+    assert_eq!(names, ["enn"]);
+
+    let err = conn
+        .query("INSERT INTO users (id, name) VALUES (3, 'cy')")
+        .filter_deleted(false)
+        .dml()
+        .await
+        .expect_err("an INSERT cannot be filtered");
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(_)));
+    let err = conn
+        .query("SELECT 1; SELECT 2")
+        .filter_deleted(true)
+        .run()
+        .await
+        .expect_err("a script cannot be filtered");
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(_)));
+    let count: i64 = conn
+        .query("SELECT COUNT(*) FROM users")
+        .select_scalar()
+        .await?;
+    assert_eq!(count, 2, "the rejected INSERT never ran");
+    Ok(())
+}
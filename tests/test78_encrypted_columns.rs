@@ -0,0 +1,67 @@
+#![cfg(all(feature = "sqlite", feature = "crypto"))]
+
+use std::sync::Arc;
+
+use sql_middleware::crypto::{ColumnCrypto, StaticKeys};
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn encrypted_columns_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test78_crypto").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE users (id INTEGER PRIMARY KEY, ssn BLOB, score BLOB)")
+        .await?;
+
+    let old = ColumnCrypto::new(Arc::new(StaticKeys::new(1, [7; 32])))
+        .column("users", "ssn")
+        .column("users", "score");
+    conn.query("INSERT INTO users (id, ssn, score) VALUES (?1, ?2, ?3)")
+        .params((1, "123-45-6789", 42))
+        .encrypt(&old, "users", &[(2, "ssn"), (3, "score")])?
+        .dml()
+        .await?;
+
+    // Only ciphertext reaches the database.
+    let raw = conn.query("SELECT ssn FROM users").select().await?;
+    let stored = raw.results[0].get("ssn").and_then(RowValues::as_blob);
+    assert!(stored.is_some_and(|bytes| !bytes.windows(3).any(|w| w == b"123")));
+
+    // After rotating to key 2, values written under key 1 still decrypt.
+    let crypto = ColumnCrypto::new(Arc::new(
+        StaticKeys::new(2, [9; 32]).with_old_key(1, [7; 32]),
+    ))
+    .column("users", "ssn")
+    .column("users", "score");
+    conn.query("INSERT INTO users (id, ssn, score) VALUES (?1, ?2, ?3)")
+        .params((2, "987-65-4321", Option::<i64>::None))
+        .encrypt(&crypto, "users", &[(2, "ssn"), (3, "score")])?
+        .dml()
+        .await?;
+
+    let mut rows = conn
+        .query("SELECT id, ssn, score FROM users ORDER BY id")
+        .select()
+        .await?;
+    crypto.decrypt_result_set("users", &mut rows)?;
+    assert_eq!(
+        rows.results[0].get("ssn"),
+        Some(&RowValues::Text("123-45-6789".into()))
+    );
+    assert_eq!(rows.results[0].get("score"), Some(&RowValues::Int(42)));
+    assert_eq!(
+        rows.results[1].get("ssn"),
+        Some(&RowValues::Text("987-65-4321".into()))
+    );
+    assert_eq!(rows.results[1].get("score"), Some(&RowValues::Null));
+
+    // Ciphertext is bound to its column.
+    let ssn = raw.results[0].get("ssn").cloned().expect("ssn column");
+    let err = crypto
+        .decrypt("users", "score", &ssn)
+        .expect_err("ssn ciphertext in the score column");
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(_)));
+
+    let wrong_key = ColumnCrypto::new(Arc::new(StaticKeys::new(1, [8; 32]))).column("users", "ssn");
+    assert!(wrong_key.decrypt("users", "ssn", &ssn).is_err());
+    Ok(())
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sql_middleware::clock::MockClock;
+use sql_middleware::pool::{PoolOptions, RateLimitPolicy};
+use sql_middleware::prelude::*;
+use sql_middleware::sqlite::begin_transaction;
+
+#[tokio::test]
+async fn statements_past_the_burst_wait_for_tokens() -> Result<(), Box<dyn std::error::Error>> {
+    let clock = MockClock::new();
+    let cap = ConfigAndPool::new_sqlite_memory("test79_wait")
+        .await?
+        .with_clock(Arc::new(clock.clone()))
+        .with_pool_options(PoolOptions::default().rate_limit(2, 2));
+    let mut conn = cap.get_connection().await?;
+
+    // The burst goes through without waiting.
+    conn.execute_batch("CREATE TABLE t (id INTEGER)").await?;
+    conn.query("INSERT INTO t (id) VALUES (1)").dml().await?;
+    assert_eq!(clock.elapsed(), Duration::ZERO);
+
+    // The third statement waits half a second (2 per second) for its token.
+    let (rows, ()) = tokio::join!(conn.query("SELECT id FROM t").select(), async {
+        tokio::task::yield_now().await;
+        clock.advance(Duration::from_millis(500));
+    });
+    assert_eq!(rows?.results.len(), 1);
+
+    let stats = cap.rate_limit_stats().expect("rate limit configured");
+    assert_eq!(stats.throttled, 1);
+    assert_eq!(stats.throttled_time, Duration::from_millis(500));
+    assert_eq!(stats.rejected, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reject_policy_fails_fast() -> Result<(), Box<dyn std::error::Error>> {
+    let clock = MockClock::new();
+    let cap = ConfigAndPool::new_sqlite_memory("test79_reject")
+        .await?
+        .with_clock(Arc::new(clock.clone()))
+        .with_pool_options(PoolOptions::default().rate_limit_with(1, 1, RateLimitPolicy::Reject));
+    let mut conn = cap.get_connection().await?;
+
+    conn.query("SELECT 1").select().await?;
+    let err = conn
+        .query("SELECT 1")
+        .select()
+        .await
+        .expect_err("bucket is empty");
+    assert!(matches!(
+        err,
+        SqlMiddlewareDbError::RateLimited { retry_after } if retry_after == Duration::from_secs(1)
+    ));
+
+    clock.advance(Duration::from_secs(1));
+    conn.query("SELECT 1").select().await?;
+    assert_eq!(cap.rate_limit_stats().map(|stats| stats.rejected), Some(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn transaction_handles_are_limited_but_can_always_commit()
+-> Result<(), Box<dyn std::error::Error>> {
+    let clock = MockClock::new();
+    let cap = ConfigAndPool::new_sqlite_memory("test79_tx")
+        .await?
+        .with_clock(Arc::new(clock.clone()))
+        .with_pool_options(PoolOptions::default().rate_limit_with(1, 2, RateLimitPolicy::Reject));
+    let mut conn = cap.get_connection().await?;
+
+    // BEGIN and the first statement spend the burst.
+    let mut tx = begin_transaction(&mut conn).await?;
+    tx.execute_batch("CREATE TABLE t (id INTEGER)").await?;
+    let err = tx
+        .execute_batch("INSERT INTO t (id) VALUES (1)")
+        .await
+        .expect_err("bucket is empty");
+    assert!(matches!(err, SqlMiddlewareDbError::RateLimited { .. }));
+    // COMMIT never takes a token.
+    tx.commit().await?;
+
+    // A refused BEGIN leaves the connection usable once tokens refill.
+    assert!(matches!(
+        begin_transaction(&mut conn).await,
+        Err(SqlMiddlewareDbError::RateLimited { .. })
+    ));
+    clock.advance(Duration::from_secs(1));
+    conn.query("SELECT id FROM t").select().await?;
+    assert_eq!(cap.rate_limit_stats().map(|stats| stats.rejected), Some(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn helpers_take_a_token_per_statement() -> Result<(), Box<dyn std::error::Error>> {
+    let clock = MockClock::new();
+    let cap = ConfigAndPool::new_sqlite_memory("test79_helpers")
+        .await?
+        .with_clock(Arc::new(clock.clone()))
+        .with_pool_options(PoolOptions::default().rate_limit_with(1, 2, RateLimitPolicy::Reject));
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER)").await?;
+    clock.advance(Duration::from_secs(2));
+
+    // BEGIN and the first insert spend the burst; the second is refused and both roll back.
+    let err = conn
+        .execute_dml_atomic(&[
+            ("INSERT INTO t (id) VALUES (1)", Vec::<RowValues>::new()),
+            ("INSERT INTO t (id) VALUES (2)", Vec::new()),
+        ])
+        .await
+        .expect_err("bucket is empty");
+    assert!(matches!(err, SqlMiddlewareDbError::RateLimited { .. }));
+
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(
+        conn.bulk_insert("t", &["id"], &[vec![RowValues::Int(3)]])
+            .await?,
+        1
+    );
+    let err = conn
+        .bulk_insert("t", &["id"], &[vec![RowValues::Int(4)]])
+        .await
+        .expect_err("bucket is empty");
+    assert!(matches!(err, SqlMiddlewareDbError::RateLimited { .. }));
+
+    clock.advance(Duration::from_secs(1));
+    let ids = conn.query("SELECT id FROM t").select().await?;
+    assert_eq!(ids.results.len(), 1);
+    assert_eq!(ids.results[0].get("id"), Some(&RowValues::Int(3)));
+    assert_eq!(cap.rate_limit_stats().map(|stats| stats.rejected), Some(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn pools_without_a_rate_limit_report_none() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test79_none").await?;
+    cap.get_connection()
+        .await?
+        .query("SELECT 1")
+        .select()
+        .await?;
+    assert_eq!(cap.rate_limit_stats(), None);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "sqlite")]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use sql_middleware::prelude::*;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Collects the fields of `sql_middleware::echo` events as `name=value` lines.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<String>>>);
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "sql_middleware::echo"
+    }
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+impl Capture {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+#[tokio::test]
+async fn echo_logs_statements_while_enabled() -> Result<(), Box<dyn std::error::Error>> {
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+
+    let cap = ConfigAndPool::new_sqlite_memory("test80_echo").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER, name TEXT)")
+        .await?;
+    assert!(!cap.debug_echo());
+    assert!(capture.take().is_empty());
+
+    // Switching it on reaches connections that are already checked out.
+    cap.set_debug_echo(true);
+    conn.query("INSERT INTO t (id, name) VALUES ($1, $2)")
+        .translation(TranslationMode::ForceOn)
+        .params((7, "ann"))
+        .dml()
+        .await?;
+    conn.execute_batch("DELETE FROM t WHERE id = 0").await?;
+    let lines = capture.take();
+    assert_eq!(lines.len(), 2, "{lines:?}");
+    assert!(lines[0].contains("sql=INSERT INTO t (id, name) VALUES ($1, $2)"));
+    assert!(lines[0].contains("translated=INSERT INTO t (id, name) VALUES (?1, ?2)"));
+    assert!(lines[0].contains("params=[1] 7, [2] 'ann'"));
+    assert!(lines[1].contains("sql=DELETE FROM t WHERE id = 0"));
+    assert!(!lines[1].contains("translated="));
+
+    cap.set_debug_echo(false);
+    conn.query("SELECT id FROM t").select().await?;
+    assert!(capture.take().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn echo_covers_helper_statements() -> Result<(), Box<dyn std::error::Error>> {
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+
+    let cap = ConfigAndPool::new_sqlite_memory("test80_helpers").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER)").await?;
+    cap.set_debug_echo(true);
+
+    conn.bulk_insert("t", &["id"], &[vec![RowValues::Int(1)]])
+        .await?;
+    conn.execute_dml_atomic(&[("INSERT INTO t (id) VALUES (2)", Vec::<RowValues>::new())])
+        .await?;
+    let lines = capture.take();
+    assert_eq!(lines.len(), 2, "{lines:?}");
+    assert!(lines[0].contains(r#"sql=INSERT INTO "t" ("id") VALUES (?1)"#));
+    assert!(lines[1].contains("sql=INSERT INTO t (id) VALUES (2)"));
+    Ok(())
+}
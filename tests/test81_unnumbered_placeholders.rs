@@ -0,0 +1,37 @@
+#![cfg(feature = "sqlite")]
+
+use sql_middleware::prelude::*;
+
+#[tokio::test]
+async fn unnumbered_placeholders_are_numbered_in_order() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_sqlite_memory("test81_odbc").await?;
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE t (id INTEGER, name TEXT)")
+        .await?;
+
+    conn.query("INSERT INTO t (id, name) VALUES (?, ?)")
+        .translation(TranslationMode::ForceOn)
+        .params((1, "ann"))
+        .dml()
+        .await?;
+    let rows = conn
+        .query("SELECT name FROM t WHERE id = ? AND name <> '?'")
+        .translation(TranslationMode::ForceOn)
+        .params((1,))
+        .select()
+        .await?;
+    assert_eq!(
+        rows.results[0].get("name"),
+        Some(&RowValues::Text("ann".into()))
+    );
+
+    let err = conn
+        .query("SELECT name FROM t WHERE id = ?1 AND name = ?")
+        .translation(TranslationMode::ForceOn)
+        .params((1, "ann"))
+        .select()
+        .await
+        .expect_err("mixed placeholders");
+    assert!(matches!(err, SqlMiddlewareDbError::ParameterError(_)));
+    Ok(())
+}
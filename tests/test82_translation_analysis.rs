@@ -0,0 +1,38 @@
+use sql_middleware::translation::{
+    PlaceholderKind, PlaceholderStyle, TranslationWarning, analyze, translate_placeholders,
+};
+
+#[test]
+fn audit_a_query_corpus_before_enabling_translation() {
+    let corpus = [
+        "SELECT * FROM users WHERE id = ?1 AND note <> '?2'",
+        "INSERT INTO t (a, b) VALUES (?, ?)",
+        "UPDATE t SET a = ? WHERE id = $1",
+        "SELECT * FROM t WHERE name = 'unclosed AND id = ?1",
+    ];
+
+    let unsafe_statements: Vec<(&str, Vec<TranslationWarning>)> = corpus
+        .iter()
+        .map(|sql| (*sql, analyze(sql)))
+        .filter(|(_, report)| !report.is_safe())
+        .map(|(sql, report)| (sql, report.warnings))
+        .collect();
+    assert_eq!(unsafe_statements.len(), 2);
+    assert!(matches!(
+        unsafe_statements[0].1[..],
+        [TranslationWarning::MixedPlaceholders { offset: 17 }]
+    ));
+    assert!(matches!(
+        unsafe_statements[1].1[..],
+        [TranslationWarning::Unterminated { offset: 29, .. }]
+    ));
+
+    // What the report finds is what translation rewrites.
+    let report = analyze(corpus[0]);
+    assert_eq!(report.placeholders.len(), 1);
+    assert_eq!(report.placeholders[0].kind, PlaceholderKind::Question);
+    assert_eq!(
+        translate_placeholders(corpus[0], PlaceholderStyle::Postgres, true),
+        "SELECT * FROM users WHERE id = $1 AND note <> '?2'"
+    );
+}
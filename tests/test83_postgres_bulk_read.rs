@@ -0,0 +1,53 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+const ROWS: &str = "SELECT n::int4 AS id, n * 10000000000 AS big, n / 4.0::float8 AS ratio,
+        n % 2 = 0 AS even, 'row ' || n AS label, NULLIF(n % 3, 0)::text AS maybe,
+        decode(lpad(to_hex(n), 4, '0'), 'hex') AS bytes,
+        TIMESTAMP '2024-01-01' + n * INTERVAL '1 minute' AS at
+    FROM generate_series(1, 5000) AS n
+    ORDER BY n; -- every type the fast path decodes";
+
+#[tokio::test]
+async fn bulk_read_matches_row_by_row_select() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut conn = cap.get_connection().await?;
+
+    let plain = conn.query(ROWS).select().await?;
+    let bulk = conn.query(ROWS).bulk_read().select().await?;
+    assert_eq!(bulk.results.len(), 5000);
+    assert_eq!(bulk.results[0].column_names, plain.results[0].column_names);
+    for (bulk_row, plain_row) in bulk.results.iter().zip(&plain.results) {
+        assert_eq!(bulk_row.rows, plain_row.rows);
+    }
+    assert_eq!(bulk.results[2].get("maybe"), Some(&RowValues::Null));
+
+    // Statements COPY cannot run go through the normal path.
+    let with_params = conn
+        .query("SELECT $1::int8 AS n")
+        .params(&[RowValues::Int(7)])
+        .bulk_read()
+        .select()
+        .await?;
+    assert_eq!(with_params.results[0].get("n"), Some(&RowValues::Int(7)));
+    let empty = conn
+        .query("SELECT 1 AS n WHERE false")
+        .options(QueryOptions::default().bulk_read())
+        .select()
+        .await?;
+    assert!(empty.results.is_empty());
+    Ok(())
+}
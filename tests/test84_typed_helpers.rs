@@ -0,0 +1,88 @@
+#![cfg(any(feature = "postgres", feature = "sqlite"))]
+
+use sql_middleware::middleware::{RowValues, SqlMiddlewareDbError};
+use sql_middleware::typed_api::{AnyIdle, BeginTx, TxConn, TypedConnOps};
+#[cfg(feature = "postgres")]
+use sql_middleware::typed_postgres::{Idle as PgIdle, PgConnection, PgManager};
+#[cfg(feature = "sqlite")]
+use sql_middleware::typed_sqlite::{Idle as SqIdle, SqliteTypedConnection};
+
+async fn check_helpers(mut conn: AnyIdle) -> Result<(), SqlMiddlewareDbError> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS typed_helpers;
+         CREATE TABLE typed_helpers (id BIGINT PRIMARY KEY, name TEXT);",
+    )
+    .await?;
+    let one = [RowValues::Int(1)];
+
+    assert!(
+        !conn
+            .exists("SELECT 1 FROM typed_helpers WHERE id = $1", &one)
+            .await?
+    );
+    let mut tx = conn.begin().await?;
+    let inserted = tx
+        .affected(
+            "INSERT INTO typed_helpers (id, name) VALUES ($1, 'ann'), (2, NULL)",
+            &one,
+        )
+        .await?;
+    assert_eq!(inserted, 2);
+    assert!(
+        tx.exists("SELECT 1 FROM typed_helpers WHERE id = $1", &one)
+            .await?
+    );
+    conn = tx.commit().await?;
+
+    let name: String = conn
+        .scalar("SELECT name FROM typed_helpers WHERE id = $1", &one)
+        .await?;
+    assert_eq!(name, "ann");
+    let missing: Option<String> = conn
+        .scalar("SELECT name FROM typed_helpers WHERE id = 2", &[])
+        .await?;
+    assert_eq!(missing, None);
+    let total: i64 = conn
+        .scalar("SELECT COUNT(*) FROM typed_helpers", &[])
+        .await?;
+    assert_eq!(total, 2);
+    assert_eq!(
+        conn.affected("DELETE FROM typed_helpers WHERE id > $1", &one)
+            .await?,
+        1
+    );
+
+    conn.execute_batch("DROP TABLE typed_helpers;").await
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn typed_helpers_on_sqlite() -> Result<(), SqlMiddlewareDbError> {
+    use sql_middleware::sqlite::config::SqliteManager;
+
+    let pool = SqliteManager::new("file:test84?mode=memory&cache=shared".to_string())
+        .build_pool()
+        .await?;
+    check_helpers(AnyIdle::Sqlite(
+        SqliteTypedConnection::<SqIdle>::from_pool(&pool).await?,
+    ))
+    .await
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn typed_helpers_on_postgres() -> Result<(), SqlMiddlewareDbError> {
+    let mut cfg = tokio_postgres::Config::new();
+    cfg.host("10.3.0.201")
+        .port(5432)
+        .dbname("testing")
+        .user("testuser");
+    if let Ok(pw) = std::env::var("TESTING_PG_PASSWORD") {
+        cfg.password(pw);
+    }
+    let pool = PgManager::new(cfg).build_pool().await?;
+    check_helpers(AnyIdle::Postgres(
+        PgConnection::<PgIdle>::from_pool(&pool).await?,
+    ))
+    .await
+}
@@ -0,0 +1,82 @@
+#![cfg(any(feature = "sqlite", feature = "postgres"))]
+
+use sql_middleware::prelude::*;
+
+async fn check_temp_tables(cap: &ConfigAndPool) -> Result<(), SqlMiddlewareDbError> {
+    let mut conn = cap.get_connection().await?;
+
+    let (table, total) = conn
+        .with_temp_table("id BIGINT, amount BIGINT", async |conn, table| {
+            conn.execute_batch(&format!(
+                "INSERT INTO {table} (id, amount) VALUES (1, 10), (2, 32)"
+            ))
+            .await?;
+            let total: i64 = conn
+                .query(&format!("SELECT CAST(SUM(amount) AS BIGINT) FROM {table}"))
+                .select_scalar()
+                .await?;
+            Ok((table.to_string(), total))
+        })
+        .await?;
+    assert_eq!(total, 42);
+    let gone = conn.query(&format!("SELECT * FROM {table}")).select().await;
+    assert!(gone.is_err(), "{table} should have been dropped");
+
+    // An error from the closure still drops the table, and nested tables get their own names.
+    let mut seen = Vec::new();
+    let err = conn
+        .with_temp_table("id BIGINT", async |conn, outer| {
+            seen.push(outer.to_string());
+            conn.with_temp_table("id BIGINT", async |_, inner| {
+                seen.push(inner.to_string());
+                Ok(())
+            })
+            .await?;
+            Err::<(), _>(SqlMiddlewareDbError::ExecutionError("boom".into()))
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SqlMiddlewareDbError::ExecutionError(msg) if msg == "boom"));
+    assert_eq!(seen.len(), 2);
+    assert_ne!(seen[0], seen[1]);
+    for table in &seen {
+        let gone = conn.query(&format!("SELECT * FROM {table}")).select().await;
+        assert!(gone.is_err(), "{table} should have been dropped");
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn temp_tables_on_sqlite() -> Result<(), SqlMiddlewareDbError> {
+    let cap = ConfigAndPool::new_sqlite_memory("test85_temp").await?;
+    check_temp_tables(&cap).await?;
+
+    let mut conn = cap.get_connection().await?;
+    // Inside a transaction the table is created and dropped without ending it.
+    conn.transaction(async |conn| {
+        conn.with_temp_table("id BIGINT", async |conn, table| {
+            conn.query(&format!("INSERT INTO {table} (id) VALUES ($1)"))
+                .params(&[RowValues::Int(1)])
+                .dml()
+                .await
+        })
+        .await
+    })
+    .await?;
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn temp_tables_on_postgres() -> Result<(), SqlMiddlewareDbError> {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(std::env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(cfg)).await?;
+    check_temp_tables(&cap).await
+}
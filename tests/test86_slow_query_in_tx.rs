@@ -0,0 +1,47 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+use std::time::Duration;
+
+use sql_middleware::metrics;
+use sql_middleware::postgres::begin_transaction;
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+#[tokio::test]
+async fn slow_selects_in_a_transaction_are_not_explained() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut conn = cap.get_connection().await?;
+    let MiddlewarePoolConnection::Postgres { client, .. } = &mut conn else {
+        panic!("Expected Postgres connection");
+    };
+
+    // A zero threshold makes every SELECT slow; the interval allows only one explain.
+    metrics::explain_slow_queries(Duration::ZERO, Duration::from_secs(3600));
+    let tx = begin_transaction(client).await?;
+    tx.query_builder("SELECT $1::int8 AS n")
+        .params(&[RowValues::Int(1)])
+        .select()
+        .await?;
+    tx.commit().await?;
+
+    // The transaction did not use up the explain, so the next SELECT outside one gets it.
+    conn.query("SELECT 2 AS n").select().await?;
+    metrics::disable_slow_query_capture();
+
+    let slow = metrics::recent_slow_queries();
+    assert_eq!(slow.len(), 2, "{slow:#?}");
+    assert_eq!(slow[0].plan, None, "not explained inside the transaction");
+    assert!(slow[1].plan.is_some(), "{slow:#?}");
+    Ok(())
+}
@@ -0,0 +1,81 @@
+#![cfg(all(feature = "postgres", feature = "json"))]
+
+use std::env;
+
+use sql_middleware::cdc::{self, ChangeCursor, ChangeRecord};
+use sql_middleware::postgres::begin_transaction;
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+/// Poll until the log is drained and return the changes to `table`.
+async fn drain(
+    conn: &mut MiddlewarePoolConnection,
+    cursor: &mut ChangeCursor,
+    table: &str,
+) -> Result<Vec<ChangeRecord>, SqlMiddlewareDbError> {
+    let mut seen = Vec::new();
+    loop {
+        let batch = cdc::poll_changes(conn, *cursor, 100).await?;
+        let Some(last) = batch.last() else {
+            return Ok(seen);
+        };
+        *cursor = last.cursor;
+        seen.extend(batch.into_iter().filter(|change| change.table == table));
+    }
+}
+
+#[tokio::test]
+async fn a_late_commit_with_an_earlier_id_is_not_skipped() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut reader = cap.get_connection().await?;
+    reader
+        .execute_batch(
+            "DROP TABLE IF EXISTS test87_items; CREATE TABLE test87_items (id BIGINT PRIMARY KEY)",
+        )
+        .await?;
+    cdc::enable_for_table(&mut reader, "test87_items").await?;
+    let mut cursor = ChangeCursor::default();
+    drain(&mut reader, &mut cursor, "test87_items").await?;
+
+    // `early` logs first, so its change gets the lower id, but commits last.
+    let mut early_conn = cap.get_connection().await?;
+    let MiddlewarePoolConnection::Postgres { client, .. } = &mut early_conn else {
+        panic!("Expected Postgres connection");
+    };
+    let early = begin_transaction(client).await?;
+    early
+        .execute_dml("INSERT INTO test87_items VALUES (1)", &[])
+        .await?;
+    let mut late_conn = cap.get_connection().await?;
+    late_conn
+        .execute_batch("INSERT INTO test87_items VALUES (2)")
+        .await?;
+
+    // The committed change waits behind the open transaction instead of moving the cursor past it.
+    assert!(
+        drain(&mut reader, &mut cursor, "test87_items")
+            .await?
+            .is_empty()
+    );
+    early.commit().await?;
+
+    let rows: Vec<_> = drain(&mut reader, &mut cursor, "test87_items")
+        .await?
+        .iter()
+        .map(|change| change.row["id"].clone())
+        .collect();
+    assert_eq!(rows, [serde_json::json!(1), serde_json::json!(2)]);
+
+    cdc::disable_for_table(&mut reader, "test87_items").await?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+use std::time::Duration;
+
+use sql_middleware::lease::Lease;
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+/// Retry `acquire` for up to two seconds; the server frees a lock once it notices the session
+/// closed.
+async fn acquire_soon(cap: &ConfigAndPool, name: &str, holder: &str, ttl: Duration) -> Lease {
+    for _ in 0..40 {
+        if let Some(lease) = Lease::acquire(cap, name, holder, ttl).await.unwrap() {
+            return lease;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("{holder} never acquired {name}");
+}
+
+#[tokio::test]
+async fn postgres_leases_are_advisory_locks() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let name = format!("test88_{}", std::process::id());
+    let ttl = Duration::from_secs(30);
+
+    let mut leader = Lease::acquire(&cap, &name, "a", ttl)
+        .await?
+        .expect("free lease should be acquired");
+    assert!(Lease::acquire(&cap, &name, "b", ttl).await?.is_none());
+    // The lock belongs to the first lease's session, not to the holder name.
+    assert!(Lease::acquire(&cap, &name, "a", ttl).await?.is_none());
+    assert!(leader.renew().await?);
+
+    let mut conn = cap.get_connection().await?;
+    let holder: String = conn
+        .query("SELECT holder FROM sql_middleware_leases WHERE name = $1")
+        .params((name.as_str(),))
+        .select_scalar()
+        .await?;
+    assert_eq!(holder, "a", "heartbeat row names the holder");
+
+    assert!(leader.release().await?);
+    let follower = Lease::acquire(&cap, &name, "b", ttl)
+        .await?
+        .expect("released lease should be acquired");
+
+    // Dropping a lease closes its session, which frees the lock without a release.
+    drop(follower);
+    let next = acquire_soon(&cap, &name, "c", ttl).await;
+    assert!(next.release().await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn stalled_postgres_holder_loses_the_lease() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let name = format!("test88_stall_{}", std::process::id());
+    let ttl = Duration::from_millis(500);
+
+    let mut stalled = Lease::acquire(&cap, &name, "a", ttl)
+        .await?
+        .expect("free lease should be acquired");
+    // Without a renewal inside the TTL the server ends the session and its lock.
+    tokio::time::sleep(ttl * 3).await;
+    let _next = acquire_soon(&cap, &name, "b", ttl).await;
+    assert!(!stalled.renew().await?);
+    Ok(())
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+
+use sql_middleware::pool::{PoolOptions, ResetMode};
+use sql_middleware::prelude::*;
+
+fn pg_config() -> PgConfig {
+    let mut cfg = PgConfig::new();
+    cfg.dbname = Some("testing".to_string());
+    cfg.host = Some("10.3.0.201".to_string());
+    cfg.port = Some(5432);
+    cfg.user = Some("testuser".to_string());
+    cfg.password = Some(env::var("TESTING_PG_PASSWORD").unwrap_or_default());
+    cfg
+}
+
+/// Leave a transaction open (and, with `abort`, aborted) the way raw SQL would.
+async fn leave_transaction_open(
+    conn: &mut MiddlewarePoolConnection,
+    abort: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let MiddlewarePoolConnection::Postgres { client, .. } = conn else {
+        panic!("Expected Postgres connection");
+    };
+    client
+        .batch_execute("BEGIN; CREATE TEMP TABLE test89_scratch (id INT)")
+        .await?;
+    if abort {
+        assert!(client.batch_execute("SELECT 1 / 0").await.is_err());
+    }
+    Ok(())
+}
+
+async fn in_transaction(conn: &mut MiddlewarePoolConnection) -> Result<bool, SqlMiddlewareDbError> {
+    conn.query("SELECT txid_current_if_assigned() IS NOT NULL")
+        .select_scalar()
+        .await
+}
+
+#[tokio::test]
+async fn reset_rolls_back_open_and_aborted_transactions() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config())).await?;
+    let mut conn = cap.get_connection().await?;
+
+    for (abort, mode) in [
+        (false, ResetMode::Fast),
+        (true, ResetMode::Fast),
+        (false, ResetMode::Full),
+        (true, ResetMode::Full),
+    ] {
+        leave_transaction_open(&mut conn, abort).await?;
+        conn.reset_session_with(mode).await?;
+        assert!(
+            !in_transaction(&mut conn).await?,
+            "{mode:?}, aborted: {abort}"
+        );
+    }
+    conn.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn reset_on_return_rolls_back_dropped_transactions() -> Result<(), Box<dyn std::error::Error>>
+{
+    let cap = ConfigAndPool::new_postgres(PostgresOptions::new(pg_config()))
+        .await?
+        .with_pool_options(PoolOptions::default().reset_on_return(ResetMode::Full));
+    let mut conn = cap.get_connection().await?;
+    leave_transaction_open(&mut conn, true).await?;
+    drop(conn);
+
+    let mut conn = cap.get_connection().await?;
+    assert!(!in_transaction(&mut conn).await?);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "turso")]
+
+use std::time::Duration;
+
+use sql_middleware::audit::{self, AUDIT_TABLE, AuditTables};
+use sql_middleware::pool::PoolOptions;
+use sql_middleware::prelude::*;
+use sql_middleware::turso::TxMode;
+
+#[tokio::test]
+async fn helpers_and_transactions_audit_on_turso() -> Result<(), Box<dyn std::error::Error>> {
+    let cap = ConfigAndPool::turso_builder(":memory:".to_string())
+        .max_size(1)
+        .build()
+        .await?
+        .with_pool_options(PoolOptions::default().audit(AuditTables::new(["accounts"])));
+    let mut conn = cap.get_connection().await?;
+    conn.execute_batch("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")
+        .await?;
+    audit::create_table(&mut conn).await?;
+
+    conn.execute_dml_atomic(&[(
+        "INSERT INTO accounts (id, balance) VALUES (1, 100)",
+        Vec::<RowValues>::new(),
+    )])
+    .await?;
+    conn.bulk_insert(
+        "accounts",
+        &["id", "balance"],
+        &[vec![RowValues::Int(2), RowValues::Int(50)]],
+    )
+    .await?;
+    let tx = conn.begin_turso_transaction(TxMode::Interactive).await?;
+    tx.query_builder("UPDATE accounts SET balance = 0 WHERE id = 2")
+        .dml()
+        .await?;
+    tx.commit().await?;
+    drop(conn);
+
+    // Two writes in one window run together in a shared transaction.
+    let writes = cap.group_commit(Duration::from_millis(5));
+    let (first, second) = tokio::join!(
+        writes.dml("DELETE FROM accounts WHERE id = 1", ()),
+        writes.dml("DELETE FROM accounts WHERE id = 2", ()),
+    );
+    assert_eq!((first?, second?), (1, 1));
+
+    let mut conn = cap.get_connection().await?;
+    let rows = conn
+        .query(&format!("SELECT op FROM {AUDIT_TABLE} ORDER BY id"))
+        .select()
+        .await?;
+    let ops: Vec<_> = rows
+        .results
+        .iter()
+        .filter_map(|row| row.get("op").and_then(RowValues::as_text))
+        .collect();
+    assert_eq!(ops, ["INSERT", "INSERT", "UPDATE", "DELETE", "DELETE"]);
+    Ok(())
+}